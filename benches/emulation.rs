@@ -0,0 +1,53 @@
+//! Emulation speed benchmark
+//!
+//! Runs test ROMs headless for a fixed number of frames and reports frames per
+//! second. Run with `cargo bench`.
+
+use neser::cartridge::Cartridge;
+use neser::nes::{Nes, TvSystem};
+use std::fs;
+use std::time::{Duration, Instant};
+
+const FRAMES: u32 = 600;
+const RUNS: u32 = 5;
+
+/// Run `frames` frames of a ROM and return how long they took
+fn run_frames(rom: &[u8], frames: u32) -> Duration {
+    let mut nes = Nes::new(TvSystem::Ntsc);
+    nes.insert_cartridge(Cartridge::new(rom).expect("invalid ROM"));
+    nes.reset();
+
+    let start = Instant::now();
+    let mut rendered = 0;
+    while rendered < frames {
+        nes.run_cpu_tick();
+        if nes.is_ready_to_render() {
+            nes.clear_ready_to_render();
+            rendered += 1;
+        }
+        while nes.get_sample().is_some() {}
+    }
+    start.elapsed()
+}
+
+fn bench(name: &str, path: &str) {
+    let rom = fs::read(path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+    run_frames(&rom, 60); // Warm up
+    let best = (0..RUNS).map(|_| run_frames(&rom, FRAMES)).min().unwrap();
+    println!(
+        "{:<24} {:>8.1} frames/s ({:.2?} for {} frames, best of {})",
+        name,
+        FRAMES as f64 / best.as_secs_f64(),
+        best,
+        FRAMES,
+        RUNS
+    );
+}
+
+fn main() {
+    bench("nmi_sync demo", "roms/nmi_sync/demo_ntsc.nes");
+    bench(
+        "rainwarrior ntsc_torture",
+        "roms/rainwarrior/ntsc_torture.nes",
+    );
+}
@@ -0,0 +1,33 @@
+#!/bin/sh
+# Regenerate the imported part of the built-in ROM database from nes20db.xml
+#
+# Usage: scripts/update-nes20db.sh path/to/nes20db.xml
+#
+# Everything in src/cartridge/nes20db.txt up to and including the import
+# marker line is kept (the header comment and the entries for the test ROMs
+# in roms/), and everything after it is replaced by the games in the XML
+# file, sorted by CRC32.
+set -eu
+
+if [ $# -ne 1 ]; then
+    echo "usage: $0 path/to/nes20db.xml" >&2
+    exit 1
+fi
+
+cd "$(dirname "$0")/.."
+db=src/cartridge/nes20db.txt
+marker='# --- Imported from nes20db.xml by scripts/update-nes20db.sh ---'
+
+if ! grep -qxF "$marker" "$db"; then
+    echo "$db has no import marker line" >&2
+    exit 1
+fi
+
+games=$(mktemp)
+tmp=$(mktemp)
+trap 'rm -f "$games" "$tmp"' EXIT
+cargo run --release --quiet -- nes20db "$1" > "$games"
+awk -v marker="$marker" '{ print } $0 == marker { exit }' "$db" > "$tmp"
+sort -u "$games" >> "$tmp"
+cat "$tmp" > "$db"
+echo "$(grep -c '^[0-9A-F]' "$db") entries in $db"
@@ -3,6 +3,7 @@ use super::frame_counter::FrameCounter;
 use super::noise::Noise;
 use super::pulse::Pulse;
 use super::triangle::Triangle;
+use crate::savestate::{SaveState, StateReader, StateWriter};
 
 // CPU clock frequency (NTSC)
 const CPU_CLOCK_NTSC: f32 = 1_789_773.0;
@@ -498,7 +499,6 @@ impl Apu {
     ///
     /// # Arguments
     /// * `sample_rate` - Target sample rate in Hz (e.g., 44100.0, 48000.0)
-    #[cfg(test)]
     pub fn set_sample_rate(&mut self, sample_rate: f32) {
         self.cycles_per_sample = CPU_CLOCK_NTSC / sample_rate;
         self.sample_accumulator = 0.0;
@@ -546,6 +546,35 @@ impl Default for Apu {
     }
 }
 
+/// Muted channels, the sample rate and samples not yet taken are not part of
+/// the state
+impl SaveState for Apu {
+    fn save_state(&self, state: &mut StateWriter) {
+        self.frame_counter.save_state(state);
+        self.pulse1.save_state(state);
+        self.pulse2.save_state(state);
+        self.triangle.save_state(state);
+        self.noise.save_state(state);
+        self.dmc.save_state(state);
+        state.write_f32(self.sample_accumulator);
+        state.write_u32(self.apu_cycle);
+        state.write_u8(self.last_4017_write);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        self.frame_counter.load_state(state)?;
+        self.pulse1.load_state(state)?;
+        self.pulse2.load_state(state)?;
+        self.triangle.load_state(state)?;
+        self.noise.load_state(state)?;
+        self.dmc.load_state(state)?;
+        self.sample_accumulator = state.read_f32()?;
+        self.apu_cycle = state.read_u32()?;
+        self.last_4017_write = state.read_u8()?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,3 +1,5 @@
+use crate::savestate::{SaveState, StateReader, StateWriter};
+
 /// NES APU DMC (Delta Modulation Channel)
 ///
 /// The DMC plays 1-bit delta-encoded samples from CPU memory.
@@ -39,6 +41,7 @@ pub struct Dmc {
     sample_length: u16,
     current_address: u16,
     bytes_remaining: u16,
+    sample_fetch: Option<u16>, // Address of the last sample byte fetched
 
     // IRQ
     interrupt_flag: bool,
@@ -60,10 +63,16 @@ impl Dmc {
             sample_length: 0,
             current_address: 0,
             bytes_remaining: 0,
+            sample_fetch: None,
             interrupt_flag: false,
         }
     }
 
+    /// Take the address of the sample byte fetched since the last call
+    pub fn take_sample_fetch(&mut self) -> Option<u16> {
+        self.sample_fetch.take()
+    }
+
     /// Get current output level (0-127)
     pub fn output(&self) -> u8 {
         self.output_level
@@ -155,6 +164,7 @@ impl Dmc {
             // TODO: Read actual byte from CPU memory at current_address
             // For now, use dummy data (0x00) since we don't have memory access
             self.sample_buffer = Some(0x00);
+            self.sample_fetch = Some(self.current_address);
 
             // Advance to next byte
             self.current_address = self.current_address.wrapping_add(1);
@@ -243,6 +253,65 @@ impl Dmc {
     }
 }
 
+impl SaveState for Dmc {
+    fn save_state(&self, state: &mut StateWriter) {
+        for value in [
+            self.timer,
+            self.timer_period,
+            self.sample_address,
+            self.sample_length,
+            self.current_address,
+            self.bytes_remaining,
+        ] {
+            state.write_u16(value);
+        }
+        for value in [self.output_level, self.shift_register, self.bits_remaining] {
+            state.write_u8(value);
+        }
+        for value in [
+            self.irq_enabled,
+            self.loop_flag,
+            self.silence_flag,
+            self.interrupt_flag,
+        ] {
+            state.write_bool(value);
+        }
+        state.write_option_u8(self.sample_buffer);
+        state.write_option_u16(self.sample_fetch);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        for value in [
+            &mut self.timer,
+            &mut self.timer_period,
+            &mut self.sample_address,
+            &mut self.sample_length,
+            &mut self.current_address,
+            &mut self.bytes_remaining,
+        ] {
+            *value = state.read_u16()?;
+        }
+        for value in [
+            &mut self.output_level,
+            &mut self.shift_register,
+            &mut self.bits_remaining,
+        ] {
+            *value = state.read_u8()?;
+        }
+        for value in [
+            &mut self.irq_enabled,
+            &mut self.loop_flag,
+            &mut self.silence_flag,
+            &mut self.interrupt_flag,
+        ] {
+            *value = state.read_bool()?;
+        }
+        self.sample_buffer = state.read_option_u8()?;
+        self.sample_fetch = state.read_option_u16()?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -436,6 +505,19 @@ mod sample_tests {
         assert_eq!(dmc.bytes_remaining, 241);
     }
 
+    #[test]
+    fn test_sample_fetch_address_is_recorded_once() {
+        let mut dmc = Dmc::new();
+        dmc.write_sample_address(0x10); // $C400
+        dmc.write_sample_length(0x00); // 1 byte
+        dmc.restart_sample();
+
+        dmc.start_output_cycle();
+
+        assert_eq!(dmc.take_sample_fetch(), Some(0xC400));
+        assert_eq!(dmc.take_sample_fetch(), None);
+    }
+
     #[test]
     fn test_enable_channel_restarts_sample() {
         let mut dmc = Dmc::new();
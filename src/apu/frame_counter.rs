@@ -1,3 +1,5 @@
+use crate::savestate::{SaveState, StateReader, StateWriter};
+
 /// Frame Counter for the NES APU
 /// Sequences envelope, sweep, and length counter clocks
 /// Operates in two modes: 4-step and 5-step
@@ -195,6 +197,7 @@ impl FrameCounter {
         const STEP_4_CYCLES: u32 = 29829;
         const IRQ_FIRST_CYCLE: u32 = 29831; // IRQ first sets 2 cycles after step 4
         const IRQ_LAST_CYCLE: u32 = 29833; // IRQ sets for 3 cycles total
+        const FRAME_CYCLES: u32 = 29830; // Cycles between two frame IRQs
 
         let quarter_frame = matches!(
             self.cycle_counter,
@@ -212,9 +215,10 @@ impl FrameCounter {
         }
 
         // Wrap around after the last IRQ cycle (after cycle 29833)
-        // This makes the frame 29834 cycles long (0-29833 inclusive)
+        // The first sequence after a $4017 write includes the write delay, so
+        // the following ones continue from cycle 4 to be 29830 cycles long
         if self.cycle_counter > IRQ_LAST_CYCLE {
-            self.cycle_counter = 0;
+            self.cycle_counter = IRQ_LAST_CYCLE + 1 - FRAME_CYCLES;
         }
 
         (quarter_frame, half_frame)
@@ -243,6 +247,37 @@ impl FrameCounter {
     }
 }
 
+impl SaveState for FrameCounter {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bool(self.mode == Mode::FiveStep);
+        state.write_u32(self.cycle_counter);
+        for value in [self.irq_inhibit, self.irq_flag, self.reset_phase] {
+            state.write_bool(value);
+        }
+        state.write_option_u8(self.pending_write);
+        state.write_u8(self.write_delay);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        self.mode = if state.read_bool()? {
+            Mode::FiveStep
+        } else {
+            Mode::FourStep
+        };
+        self.cycle_counter = state.read_u32()?;
+        for value in [
+            &mut self.irq_inhibit,
+            &mut self.irq_flag,
+            &mut self.reset_phase,
+        ] {
+            *value = state.read_bool()?;
+        }
+        self.pending_write = state.read_option_u8()?;
+        self.write_delay = state.read_u8()?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -427,17 +462,17 @@ mod tests {
         let mut fc = FrameCounter::new();
         fc.write_register(0b0000_0000); // 4-step mode
 
-        // Clock through full sequence (29834 cycles: 0-29833 inclusive, wraps after 29833)
+        // Clock through the first sequence (0-29833 inclusive, wraps after 29833)
         for _ in 0..29834 {
             fc.clock();
         }
 
-        // Counter should have wrapped to 0
-        assert_eq!(fc.get_cycle_counter(), 0);
+        // Counter should have wrapped to 4, making later sequences 29830 cycles
+        assert_eq!(fc.get_cycle_counter(), 4);
 
-        // Next clock should be at cycle 1
+        // Next clock should be at cycle 5
         fc.clock();
-        assert_eq!(fc.get_cycle_counter(), 1);
+        assert_eq!(fc.get_cycle_counter(), 5);
     }
 
     #[test]
@@ -448,7 +483,7 @@ mod tests {
         let mut quarter_count = 0;
         let mut half_count = 0;
 
-        // Run through one complete sequence (29834 cycles: 0-29833 inclusive)
+        // Run through the first sequence (29834 cycles: 0-29833 inclusive)
         for _ in 0..29834 {
             let (quarter, half) = fc.clock();
             if quarter {
@@ -461,7 +496,7 @@ mod tests {
 
         assert_eq!(quarter_count, 4); // 4 quarter frame clocks
         assert_eq!(half_count, 2); // 2 half frame clocks
-        assert_eq!(fc.get_cycle_counter(), 0); // Wrapped around after 29833
+        assert_eq!(fc.get_cycle_counter(), 4); // Wrapped around after 29833
     }
 
     #[test]
@@ -487,12 +522,12 @@ mod tests {
         let mut fc = FrameCounter::new();
         fc.write_register(0b0000_0000); // 4-step mode
 
-        // Run two complete sequences (29834 cycles each)
-        for sequence in 0..2 {
+        // Run two complete sequences (29834 cycles, then 29830 cycles)
+        for (sequence, cycles) in [29834, 29830].into_iter().enumerate() {
             let mut quarter_count = 0;
             let mut half_count = 0;
 
-            for _ in 0..29834 {
+            for _ in 0..cycles {
                 let (quarter, half) = fc.clock();
                 if quarter {
                     quarter_count += 1;
@@ -504,7 +539,7 @@ mod tests {
 
             assert_eq!(quarter_count, 4, "Sequence {}", sequence);
             assert_eq!(half_count, 2, "Sequence {}", sequence);
-            assert_eq!(fc.get_cycle_counter(), 0, "Sequence {}", sequence);
+            assert_eq!(fc.get_cycle_counter(), 4, "Sequence {}", sequence);
         }
     }
 
@@ -790,8 +825,8 @@ mod tests {
         let mut fc = FrameCounter::new();
         fc.write_register(0b0000_0000); // 4-step mode
 
-        // First sequence - clock to IRQ cycle
-        for _ in 0..29831 {
+        // First sequence - clock to the last IRQ cycle
+        for _ in 0..29833 {
             fc.clock();
         }
         assert!(fc.get_irq_flag());
@@ -800,11 +835,12 @@ mod tests {
         fc.clear_irq_flag();
         assert!(!fc.get_irq_flag());
 
-        // Second sequence - clock through wraparound and to IRQ cycle again
-        // From 29831 to 29833 (3 more), then wrap, then 0 to 29830 (29831 more) = 29834 total
-        for _ in 0..29834 {
+        // Second sequence - the IRQ sets again 29830 cycles after it first did
+        for _ in 0..29827 {
             fc.clock();
         }
+        assert!(!fc.get_irq_flag());
+        fc.clock();
         assert!(fc.get_irq_flag());
     }
 }
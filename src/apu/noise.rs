@@ -1,3 +1,5 @@
+use crate::savestate::{SaveState, StateReader, StateWriter};
+
 /// NES APU Noise Channel
 ///
 /// Generates pseudo-random noise using a 15-bit Linear Feedback Shift Register (LFSR).
@@ -197,6 +199,61 @@ impl Noise {
     }
 }
 
+impl SaveState for Noise {
+    fn save_state(&self, state: &mut StateWriter) {
+        for value in [self.shift_register, self.timer, self.timer_period] {
+            state.write_u16(value);
+        }
+        for value in [
+            self.envelope_divider_period,
+            self.envelope_divider,
+            self.envelope_decay_level,
+            self.length_counter,
+        ] {
+            state.write_u8(value);
+        }
+        for value in [
+            self.mode,
+            self.envelope_start,
+            self.envelope_loop,
+            self.envelope_constant_volume,
+            self.length_counter_halt,
+            self.length_counter_enabled,
+        ] {
+            state.write_bool(value);
+        }
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        for value in [
+            &mut self.shift_register,
+            &mut self.timer,
+            &mut self.timer_period,
+        ] {
+            *value = state.read_u16()?;
+        }
+        for value in [
+            &mut self.envelope_divider_period,
+            &mut self.envelope_divider,
+            &mut self.envelope_decay_level,
+            &mut self.length_counter,
+        ] {
+            *value = state.read_u8()?;
+        }
+        for value in [
+            &mut self.mode,
+            &mut self.envelope_start,
+            &mut self.envelope_loop,
+            &mut self.envelope_constant_volume,
+            &mut self.length_counter_halt,
+            &mut self.length_counter_enabled,
+        ] {
+            *value = state.read_bool()?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
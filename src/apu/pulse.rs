@@ -1,3 +1,5 @@
+use crate::savestate::{SaveState, StateReader, StateWriter};
+
 /// Pulse wave channel for the NES APU
 /// Generates square waves with variable duty cycle
 pub struct Pulse {
@@ -307,6 +309,71 @@ impl Pulse {
     }
 }
 
+impl SaveState for Pulse {
+    fn save_state(&self, state: &mut StateWriter) {
+        for value in [self.timer_period, self.timer_counter] {
+            state.write_u16(value);
+        }
+        for value in [
+            self.duty_mode,
+            self.sequence_position,
+            self.volume_envelope_period,
+            self.envelope_divider,
+            self.envelope_decay_level,
+            self.length_counter,
+            self.sweep_divider_period,
+            self.sweep_shift,
+            self.sweep_divider,
+        ] {
+            state.write_u8(value);
+        }
+        for value in [
+            self.envelope_start_flag,
+            self.envelope_loop_flag,
+            self.constant_volume_flag,
+            self.length_counter_halt,
+            self.length_counter_enabled,
+            self.sweep_enabled,
+            self.sweep_negate,
+            self.sweep_reload,
+        ] {
+            state.write_bool(value);
+        }
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        for value in [&mut self.timer_period, &mut self.timer_counter] {
+            *value = state.read_u16()?;
+        }
+        for value in [
+            &mut self.duty_mode,
+            &mut self.sequence_position,
+            &mut self.volume_envelope_period,
+            &mut self.envelope_divider,
+            &mut self.envelope_decay_level,
+            &mut self.length_counter,
+            &mut self.sweep_divider_period,
+            &mut self.sweep_shift,
+            &mut self.sweep_divider,
+        ] {
+            *value = state.read_u8()?;
+        }
+        for value in [
+            &mut self.envelope_start_flag,
+            &mut self.envelope_loop_flag,
+            &mut self.constant_volume_flag,
+            &mut self.length_counter_halt,
+            &mut self.length_counter_enabled,
+            &mut self.sweep_enabled,
+            &mut self.sweep_negate,
+            &mut self.sweep_reload,
+        ] {
+            *value = state.read_bool()?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,3 +1,5 @@
+use crate::savestate::{SaveState, StateReader, StateWriter};
+
 /// Triangle wave channel for the NES APU
 /// Generates triangle waves with a 32-step linear sequence
 pub struct Triangle {
@@ -202,6 +204,51 @@ impl Triangle {
     }
 }
 
+impl SaveState for Triangle {
+    fn save_state(&self, state: &mut StateWriter) {
+        for value in [self.timer_period, self.timer_counter] {
+            state.write_u16(value);
+        }
+        for value in [
+            self.sequence_position,
+            self.linear_counter,
+            self.linear_counter_reload_value,
+            self.length_counter,
+        ] {
+            state.write_u8(value);
+        }
+        for value in [
+            self.linear_counter_reload_flag,
+            self.control_flag,
+            self.length_counter_enabled,
+        ] {
+            state.write_bool(value);
+        }
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        for value in [&mut self.timer_period, &mut self.timer_counter] {
+            *value = state.read_u16()?;
+        }
+        for value in [
+            &mut self.sequence_position,
+            &mut self.linear_counter,
+            &mut self.linear_counter_reload_value,
+            &mut self.length_counter,
+        ] {
+            *value = state.read_u8()?;
+        }
+        for value in [
+            &mut self.linear_counter_reload_flag,
+            &mut self.control_flag,
+            &mut self.length_counter_enabled,
+        ] {
+            *value = state.read_bool()?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,17 +2,17 @@
 ///
 /// This module handles SDL2 audio initialization and manages the audio callback
 /// that retrieves samples from the APU.
+use crossbeam_queue::ArrayQueue;
 use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
 use std::sync::{
     Arc,
     atomic::{AtomicU32, Ordering},
-    mpsc::{Receiver, SyncSender, sync_channel},
 };
 
 /// Audio output handler that receives samples from the NES APU
 pub struct NesAudio {
     device: AudioDevice<AudioCallbackImpl>,
-    sample_sender: SyncSender<f32>,
+    samples: Arc<ArrayQueue<f32>>,
     volume: Arc<AtomicU32>,
 }
 
@@ -24,26 +24,27 @@ impl NesAudio {
     /// Create a new audio output handler
     ///
     /// Initializes SDL2 audio subsystem with the specified sample rate.
-    /// Creates a bounded channel for sending audio samples from the emulator to the audio callback.
+    /// Creates a bounded lock-free queue for passing audio samples from the emulator to the audio callback.
     ///
     /// # Arguments
     /// * `sdl_context` - The SDL2 context for audio initialization
     /// * `sample_rate` - Target sample rate in Hz (e.g., 44100, 48000)
+    /// * `latency_ms` - Desired output buffer length in milliseconds
     ///
     /// # Errors
     /// Returns an error if SDL2 audio initialization fails
-    pub fn new(sdl_context: &sdl2::Sdl, sample_rate: i32) -> Result<Self, String> {
+    pub fn new(sdl_context: &sdl2::Sdl, sample_rate: i32, latency_ms: u32) -> Result<Self, String> {
         let audio_subsystem = sdl_context.audio()?;
 
         let desired_spec = AudioSpecDesired {
             freq: Some(sample_rate),
-            channels: Some(1),   // Mono audio
-            samples: Some(1024), // Larger buffer for debug mode (less CPU pressure)
+            channels: Some(1), // Mono audio
+            samples: Some(Self::buffer_samples(sample_rate, latency_ms)),
         };
 
-        // Create bounded channel for sending samples to audio callback
+        // Create bounded queue for passing samples to audio callback
         // This prevents unbounded memory growth if audio callback falls behind
-        let (sender, receiver) = sync_channel(Self::BUFFER_SIZE);
+        let samples = Arc::new(ArrayQueue::new(Self::BUFFER_SIZE));
 
         // Create shared volume control (default 25% to avoid distortion)
         let volume = Arc::new(AtomicU32::new(f32::to_bits(0.25)));
@@ -51,28 +52,37 @@ impl NesAudio {
 
         let device =
             audio_subsystem.open_playback(None, &desired_spec, |_spec| AudioCallbackImpl {
-                sample_receiver: receiver,
+                samples: Arc::clone(&samples),
                 volume: volume_clone,
             })?;
 
         Ok(Self {
             device,
-            sample_sender: sender,
+            samples,
             volume,
         })
     }
 
-    /// Send an audio sample to the audio output
+    /// SDL buffer size in samples for the requested latency
     ///
-    /// Sends a sample to the audio callback for playback.
-    /// If the buffer is full, the sample will be dropped to prevent blocking.
+    /// SDL expects a power of two, so the nearest one within [256, 8192] is used.
+    fn buffer_samples(sample_rate: i32, latency_ms: u32) -> u16 {
+        let samples = (sample_rate.max(0) as u64 * latency_ms as u64 / 1000).clamp(256, 8192);
+        let above = samples.next_power_of_two();
+        let below = above / 2;
+        if above - samples <= samples - below.max(1) {
+            above as u16
+        } else {
+            below.max(256) as u16
+        }
+    }
+
+    /// The queue the audio callback plays samples from
     ///
-    /// # Arguments
-    /// * `sample` - Audio sample in range 0.0 to 1.0
-    pub fn queue_sample(&mut self, sample: f32) {
-        // Send sample to audio callback using try_send to avoid blocking
-        // If the buffer is full, drop the sample to prevent emulation slowdown
-        let _ = self.sample_sender.try_send(sample);
+    /// Samples (in range 0.0 to 1.0) can be pushed to it from any thread.
+    /// If the queue is full, further samples should be dropped to prevent blocking.
+    pub fn sample_queue(&self) -> Arc<ArrayQueue<f32>> {
+        Arc::clone(&self.samples)
     }
 
     /// Start audio playback
@@ -105,7 +115,7 @@ impl NesAudio {
 
 /// SDL2 audio callback implementation
 struct AudioCallbackImpl {
-    sample_receiver: Receiver<f32>,
+    samples: Arc<ArrayQueue<f32>>,
     volume: Arc<AtomicU32>,
 }
 
@@ -117,10 +127,10 @@ impl AudioCallback for AudioCallbackImpl {
         let volume = f32::from_bits(self.volume.load(Ordering::Relaxed));
 
         for sample in out.iter_mut() {
-            // Try to receive a sample from the channel
+            // Try to take a sample from the queue
             // If no sample is available, output silence (0.0 for signed audio)
-            match self.sample_receiver.try_recv() {
-                Ok(raw_sample) => {
+            match self.samples.pop() {
+                Some(raw_sample) => {
                     // NES APU mix() outputs 0.0-1.177, where 0.0 represents silence
                     // SDL2 f32 format expects -1.0 to +1.0 where 0.0 is silence
                     // The NES output needs to be scaled to use the full SDL2 range
@@ -134,7 +144,7 @@ impl AudioCallback for AudioCallbackImpl {
                     // Safety clamp to prevent any unexpected clipping
                     *sample = final_sample.clamp(-1.0, 1.0);
                 }
-                Err(_) => {
+                None => {
                     // Buffer underrun - output silence
                     *sample = 0.0;
                 }
@@ -155,10 +165,10 @@ mod tests {
         // Combine into one test to avoid SDL2 thread issues
         let sdl_context = sdl2::init().expect("Failed to initialize SDL2");
 
-        let audio = NesAudio::new(&sdl_context, 44100);
+        let audio = NesAudio::new(&sdl_context, 44100, 23);
         assert!(audio.is_ok(), "Audio initialization should succeed");
 
-        let mut audio = audio.unwrap();
+        let audio = audio.unwrap();
 
         // Test volume control
         assert_eq!(audio.get_volume(), 0.25, "Default volume should be 0.25");
@@ -174,8 +184,17 @@ mod tests {
         audio.pause();
 
         // Test queueing samples - should not panic
-        audio.queue_sample(0.5);
-        audio.queue_sample(0.3);
-        audio.queue_sample(0.8);
+        let samples = audio.sample_queue();
+        for sample in [0.5, 0.3, 0.8] {
+            samples.push(sample).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_buffer_samples_for_latency() {
+        assert_eq!(NesAudio::buffer_samples(44100, 23), 1024);
+        assert_eq!(NesAudio::buffer_samples(48000, 50), 2048);
+        assert_eq!(NesAudio::buffer_samples(44100, 1), 256);
+        assert_eq!(NesAudio::buffer_samples(192000, 1000), 8192);
     }
 }
@@ -1,185 +1,35 @@
 #[cfg(test)]
 mod tests {
-    /// OAM test infrastructure for automated testing of blargg's OAM test ROMs
-    ///
-    /// This module provides infrastructure to run OAM test ROMs (oam_read, oam_stress, oam3)
-    /// and automatically detect PASS/FAIL status by reading results from PRG-RAM.
-    ///
-    /// Blargg test ROMs write their results to $6000-$6003:
-    /// - $6000 = 0x00: Test passed
-    /// - $6000 = 0x01+: Test failed with error code
-    /// - $6001-$6003: Additional error information or text output
-    use crate::cartridge::Cartridge;
-    use crate::nes::{Nes, TvSystem};
-    use std::fs;
+    //! Runs blargg's test ROMs through the `test_rom` framework, one test per
+    //! ROM. The manifests in `roms/blargg` list the same ROMs for
+    //! `neser test-roms`.
+    use crate::test_rom::{TestRomResult, TestRomRunner, Verification};
 
-    /// Result of running an OAM test ROM
-    #[derive(Debug, PartialEq, Eq)]
-    pub enum BlarggTestResult {
-        /// Test passed (status byte = 0x00)
-        Pass,
-        /// Test failed with error code
-        Fail(u8),
-        /// Test didn't complete within timeout
-        Timeout,
+    /// Run a test ROM and check that it passes
+    fn assert_passes(rom_path: &str, max_frames: u64, verification: Verification) {
+        let report = TestRomRunner::new(rom_path, max_frames, verification)
+            .run()
+            .unwrap_or_else(|message| panic!("{}", message));
+        let rom_name = rom_path.split('/').next_back().unwrap();
+        assert_eq!(
+            report.result,
+            TestRomResult::Pass,
+            "{} should pass, output:\n{}",
+            rom_name,
+            report.text
+        );
     }
 
-    /// Test verification method
-    #[derive(Debug, PartialEq, Eq)]
-    pub enum BlarggTestVerification {
-        /// Verify using status byte at 0x6000
-        StatusByte,
-        /// Verify using console output
-        Console,
-    }
-
-    /// Runner for OAM test ROMs
-    pub struct BlarggTestRunner {
-        rom_path: String,
-        max_frames: u32,
-        wait_reset: u32,
-        verification: BlarggTestVerification,
-    }
-
-    impl BlarggTestRunner {
-        /// Create a new test runner for $6000-based tests
-        pub fn new(rom_path: &str, max_frames: u32, verification: BlarggTestVerification) -> Self {
-            Self {
-                rom_path: rom_path.to_string(),
-                max_frames,
-                wait_reset: 1,
-                verification,
-            }
-        }
-
-        /// Run the test ROM and return the result
-        ///
-        /// The test ROM is executed for up to `max_frames` frames.
-        ///
-        /// Checks for either $6000 status byte or console output:
-        /// - Results are checked by reading $6000 in PRG-RAM:
-        ///   - 0x00 = Pass
-        ///   - 0x01+ = Fail with error code
-        /// - For console-based tests:
-        ///   - Reads nametable text looking for "PASSED" or "FAILED"
-        ///
-        /// Returns `Timeout` if no result is found within max_frames.
-        pub fn run_test(&mut self) -> BlarggTestResult {
-            // Load ROM
-            let rom_data = match fs::read(&self.rom_path) {
-                Ok(data) => data,
-                Err(e) => {
-                    eprintln!("Failed to load ROM {}: {}", self.rom_path, e);
-                    return BlarggTestResult::Fail(0x80 as u8);
-                }
-            };
-
-            let cartridge = match Cartridge::new(&rom_data) {
-                Ok(cart) => cart,
-                Err(e) => {
-                    eprintln!("Failed to parse ROM {}: {}", self.rom_path, e);
-                    return BlarggTestResult::Fail(0x81 as u8);
-                }
-            };
-
-            // Create NES and insert cartridge
-            let mut nes = Nes::new(TvSystem::Ntsc);
-            nes.insert_cartridge(cartridge);
-            nes.reset();
-
-            // println!("Running Blargg-based test ROM: {} ... ", self.rom_path);
-
-            let mut running = false;
-            // Run frames and check for results
-            for _frame in 1..=self.max_frames {
-                // Run one frame (roughly 29780 CPU cycles for NTSC)
-                let mut status = 0;
-                for _ in 0..29780 {
-                    // println!("{}", nes.trace(false));
-                    nes.run_cpu_tick();
-                    if nes.is_ready_to_render() {
-                        nes.clear_ready_to_render();
-                    }
-                    while nes.sample_ready() {
-                        nes.get_sample();
-                    }
-                    status = nes.memory.borrow().read_for_testing(0x6000);
-                    if status == 0x80 {
-                        running = true;
-                    }
-                }
-                if self.verification == BlarggTestVerification::StatusByte && !running {
-                    continue;
-                }
-                let base_addr = nes.base_nametable_addr();
-                let mut text = nes.read_nametable_text(base_addr, 32 * 32);
-                text = text
-                    .as_bytes()
-                    .chunks(32)
-                    .map(|chunk| String::from_utf8_lossy(chunk).trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect::<Vec<_>>()
-                    .join("\n");
-                if self.verification == BlarggTestVerification::StatusByte {
-                    if status == 0x00 {
-                        println!("Test passed!");
-                        return BlarggTestResult::Pass;
-                    } else if status > 0x00 && status < 0x80 {
-                        println!("Test failed with status code: 0x{:02X}", status);
-                        println!("Console output:\n{}", text);
-                        return BlarggTestResult::Fail(status);
-                    } else if status == 0x81 {
-                        if self.wait_reset > 0 {
-                            // println!(
-                            //     "Test indicates reset, waiting {} frames...",
-                            //     self.wait_reset
-                            // );
-                            self.wait_reset -= 1;
-                        } else {
-                            // println!("Test indicates reset, restarting NES...");
-                            nes.reset();
-                            self.wait_reset = 1;
-                        }
-                    } else if status == 0x80 {
-                        // Still running
-                        continue;
-                    }
-                } else if self.verification == BlarggTestVerification::Console {
-                    // Check if $0x test
-                    let is_0x = text.len() == 3 && text.starts_with("$0");
-                    if text.to_uppercase().contains("PASSED") || text == "$01" {
-                        // println!("Test passed!");
-                        return BlarggTestResult::Pass;
-                    } else if text.to_uppercase().contains("FAILED")
-                        || text.to_uppercase().contains("ERROR")
-                        || is_0x
-                    {
-                        println!("Test failed!");
-                        println!("Console output:\n{}", text);
-                        return BlarggTestResult::Fail(1);
-                    }
-                }
-            }
-
-            // No result found within timeout
-            BlarggTestResult::Timeout
-        }
-    }
-
-    /// Macro to generate $6000-based tests with custom timeout
+    /// Macro to generate $6000-based tests with custom timeout in frames
     macro_rules! blargg_test {
         ($test_name:ident, $rom_path:expr, $timeout:expr) => {
             #[test]
             fn $test_name() {
-                let mut runner =
-                    BlarggTestRunner::new($rom_path, $timeout, BlarggTestVerification::StatusByte);
-                let result = runner.run_test();
-                let rom_name = $rom_path.split('/').last().unwrap();
-                assert_eq!(result, BlarggTestResult::Pass, "{} should pass", rom_name);
+                assert_passes($rom_path, $timeout, Verification::Status);
             }
         };
         ($test_name:ident, $rom_path:expr) => {
-            blargg_test!($test_name, $rom_path, 180);
+            blargg_test!($test_name, $rom_path, 600);
         };
     }
 
@@ -187,15 +37,11 @@ mod tests {
         ($test_name:ident, $rom_path:expr, $timeout:expr) => {
             #[test]
             fn $test_name() {
-                let mut runner =
-                    BlarggTestRunner::new($rom_path, $timeout, BlarggTestVerification::Console);
-                let result = runner.run_test();
-                let rom_name = $rom_path.split('/').last().unwrap();
-                assert_eq!(result, BlarggTestResult::Pass, "{} should pass", rom_name);
+                assert_passes($rom_path, $timeout, Verification::Console);
             }
         };
         ($test_name:ident, $rom_path:expr) => {
-            blargg_console_test!($test_name, $rom_path, 180);
+            blargg_console_test!($test_name, $rom_path, 600);
         };
     }
 
@@ -236,22 +82,22 @@ mod tests {
         test_cpu_cli_latency,
         "roms/blargg/cpu_interrupts_v2/rom_singles/1-cli_latency.nes"
     );
-    // blargg_test!(
-    //     test_cpu_nmi_and_brk,
-    //     "roms/blargg/cpu_interrupts_v2/rom_singles/2-nmi_and_brk.nes"
-    // );
-    // blargg_test!(
-    //     test_cpu_nmi_and_irq,
-    //     "roms/blargg/cpu_interrupts_v2/rom_singles/3-nmi_and_irq.nes"
-    // );
-    // blargg_test!(
-    //     test_cpu_irq_and_dma,
-    //     "roms/blargg/cpu_interrupts_v2/rom_singles/4-irq_and_dma.nes"
-    // );
-    // blargg_test!(
-    //     test_cpu_branch_delays_irq,
-    //     "roms/blargg/cpu_interrupts_v2/rom_singles/5-branch_delays_irq.nes"
-    // );
+    blargg_test!(
+        test_cpu_nmi_and_brk,
+        "roms/blargg/cpu_interrupts_v2/rom_singles/2-nmi_and_brk.nes"
+    );
+    blargg_test!(
+        test_cpu_nmi_and_irq,
+        "roms/blargg/cpu_interrupts_v2/rom_singles/3-nmi_and_irq.nes"
+    );
+    blargg_test!(
+        test_cpu_irq_and_dma,
+        "roms/blargg/cpu_interrupts_v2/rom_singles/4-irq_and_dma.nes"
+    );
+    blargg_test!(
+        test_cpu_branch_delays_irq,
+        "roms/blargg/cpu_interrupts_v2/rom_singles/5-branch_delays_irq.nes"
+    );
     blargg_test!(
         test_cpu_reset_registers,
         "roms/blargg/cpu_reset/registers.nes"
@@ -333,7 +179,7 @@ mod tests {
     blargg_test!(
         test_instr_timing,
         "roms/blargg/instr_timing/instr_timing.nes",
-        30 * 60 // According to README, this test can take up to 25 seconds, so let's run it for 30*60 frames
+        30 * 60 // According to README, this test can take up to 25 seconds
     );
     blargg_console_test!(
         test_palette_ram,
@@ -360,7 +206,7 @@ mod tests {
     blargg_test!(
         test_oam_stress,
         "roms/blargg/oam_stress/oam_stress.nes",
-        60 * 10
+        60 * 40 // Takes about 30 seconds according to readme
     );
     blargg_test!(
         test_ppu_open_bus,
@@ -374,7 +220,7 @@ mod tests {
     blargg_test!(
         test_sprite_hit,
         "roms/blargg/ppu_sprite_hit/ppu_sprite_hit.nes",
-        60 * 5
+        60 * 15
     );
     blargg_test!(
         test_sprite_overflow,
@@ -1,5 +1,6 @@
 use crate::cartridge::Mapper;
 use crate::cartridge::MirroringMode;
+use crate::savestate::{SaveState, StateReader, StateWriter};
 
 // Memory size constants
 const CHR_RAM_SIZE: usize = 8192; // 8KB
@@ -41,6 +42,17 @@ impl AxROMMapper {
         }
     }
 
+    /// Size PRG-RAM and CHR-RAM from the ROM header
+    ///
+    /// CHR-RAM is only resized when the header gives a CHR-RAM size.
+    pub fn with_ram(mut self, prg_ram_size: usize, chr_ram_size: usize) -> Self {
+        self.prg_ram = vec![0; prg_ram_size];
+        if chr_ram_size > 0 {
+            self.chr_ram = vec![0; chr_ram_size];
+        }
+        self
+    }
+
     fn get_prg_bank_offset(&self) -> usize {
         // Extract bank number from bits 0-2
         let bank = (self.bank_select & 0x07) as usize;
@@ -59,12 +71,9 @@ impl Mapper for AxROMMapper {
                 self.prg_ram.get(offset).copied().unwrap_or(0)
             }
             // PRG ROM at $8000-$FFFF (32KB switchable bank)
-            0x8000..=0xFFFF => {
-                let bank_offset = self.get_prg_bank_offset();
-                let offset = (addr - 0x8000) as usize;
-                let index = bank_offset + offset;
-                self.prg_rom.get(index).copied().unwrap_or(0)
-            }
+            0x8000..=0xFFFF => self
+                .prg_rom_offset(addr)
+                .map_or(0, |index| self.prg_rom[index]),
             _ => 0,
         }
     }
@@ -110,11 +119,45 @@ impl Mapper for AxROMMapper {
         // The distinction between upper/lower isn't needed at this level
         MirroringMode::SingleScreen
     }
+
+    fn prg_rom(&self) -> &[u8] {
+        &self.prg_rom
+    }
+
+    fn prg_rom_offset(&self, addr: u16) -> Option<usize> {
+        if addr < 0x8000 {
+            return None;
+        }
+        // 32KB switchable bank
+        let index = self.get_prg_bank_offset() + (addr - 0x8000) as usize;
+        (index < self.prg_rom.len()).then_some(index)
+    }
+
+    fn chr_rom_offset(&self, _addr: u16) -> Option<usize> {
+        // AxROM uses CHR-RAM
+        None
+    }
+}
+
+impl SaveState for AxROMMapper {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.prg_ram);
+        state.write_bytes(&self.chr_ram);
+        state.write_u8(self.bank_select);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.read_bytes_into(&mut self.prg_ram)?;
+        state.read_bytes_into(&mut self.chr_ram)?;
+        self.bank_select = state.read_u8()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cartridge::header::RomHeader;
     use crate::cartridge::mapper::create_mapper;
 
     #[test]
@@ -131,8 +174,12 @@ mod tests {
             }
         }
 
-        let mapper = create_mapper(7, prg_rom, vec![], MirroringMode::Horizontal)
-            .expect("Failed to create AxROM mapper");
+        let mapper = create_mapper(
+            &RomHeader::ines(7, prg_rom.len(), 0, MirroringMode::Horizontal),
+            prg_rom,
+            vec![],
+        )
+        .expect("Failed to create AxROM mapper");
 
         // Default bank should be 0
         assert_eq!(mapper.read_prg(0x8000), 0);
@@ -152,8 +199,12 @@ mod tests {
             }
         }
 
-        let mut mapper = create_mapper(7, prg_rom, vec![], MirroringMode::Horizontal)
-            .expect("Failed to create AxROM mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(7, prg_rom.len(), 0, MirroringMode::Horizontal),
+            prg_rom,
+            vec![],
+        )
+        .expect("Failed to create AxROM mapper");
 
         // Write to $8000 with different bank values
         mapper.write_prg(0x8000, 0x00); // Bank 0
@@ -174,8 +225,12 @@ mod tests {
     fn test_axrom_chr_ram() {
         // AxROM uses 8KB CHR-RAM (no CHR ROM)
         let prg_rom = vec![0; 128 * 1024];
-        let mut mapper = create_mapper(7, prg_rom, vec![], MirroringMode::Horizontal)
-            .expect("Failed to create AxROM mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(7, prg_rom.len(), 0, MirroringMode::Horizontal),
+            prg_rom,
+            vec![],
+        )
+        .expect("Failed to create AxROM mapper");
 
         // Write to CHR-RAM
         mapper.write_chr(0x0000, 0x42);
@@ -190,8 +245,12 @@ mod tests {
     fn test_axrom_one_screen_mirroring_lower() {
         // Bit 4 = 0 selects lower nametable (single-screen A)
         let prg_rom = vec![0; 128 * 1024];
-        let mut mapper = create_mapper(7, prg_rom, vec![], MirroringMode::Horizontal)
-            .expect("Failed to create AxROM mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(7, prg_rom.len(), 0, MirroringMode::Horizontal),
+            prg_rom,
+            vec![],
+        )
+        .expect("Failed to create AxROM mapper");
 
         // Write with bit 4 = 0 (lower nametable)
         mapper.write_prg(0x8000, 0x00); // Bits: 0000 0000
@@ -206,8 +265,12 @@ mod tests {
     fn test_axrom_one_screen_mirroring_upper() {
         // Bit 4 = 1 selects upper nametable (single-screen B)
         let prg_rom = vec![0; 128 * 1024];
-        let mut mapper = create_mapper(7, prg_rom, vec![], MirroringMode::Horizontal)
-            .expect("Failed to create AxROM mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(7, prg_rom.len(), 0, MirroringMode::Horizontal),
+            prg_rom,
+            vec![],
+        )
+        .expect("Failed to create AxROM mapper");
 
         // Write with bit 4 = 1 (upper nametable)
         mapper.write_prg(0x8000, 0x10); // Bits: 0001 0000
@@ -227,8 +290,12 @@ mod tests {
             }
         }
 
-        let mut mapper = create_mapper(7, prg_rom, vec![], MirroringMode::Horizontal)
-            .expect("Failed to create AxROM mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(7, prg_rom.len(), 0, MirroringMode::Horizontal),
+            prg_rom,
+            vec![],
+        )
+        .expect("Failed to create AxROM mapper");
 
         // Select each of the 4 banks
         for bank in 0..4 {
@@ -254,8 +321,12 @@ mod tests {
             }
         }
 
-        let mut mapper = create_mapper(7, prg_rom, vec![], MirroringMode::Horizontal)
-            .expect("Failed to create AxROM mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(7, prg_rom.len(), 0, MirroringMode::Horizontal),
+            prg_rom,
+            vec![],
+        )
+        .expect("Failed to create AxROM mapper");
 
         // Write to different addresses in PRG ROM space
         mapper.write_prg(0x8000, 0x00);
@@ -272,8 +343,12 @@ mod tests {
     fn test_axrom_prg_ram_support() {
         // AxROM should support PRG-RAM at $6000-$7FFF
         let prg_rom = vec![0; 128 * 1024];
-        let mut mapper = create_mapper(7, prg_rom, vec![], MirroringMode::Horizontal)
-            .expect("Failed to create AxROM mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(7, prg_rom.len(), 0, MirroringMode::Horizontal),
+            prg_rom,
+            vec![],
+        )
+        .expect("Failed to create AxROM mapper");
 
         // Write to PRG-RAM
         mapper.write_prg(0x6000, 0xAA);
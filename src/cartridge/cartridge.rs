@@ -1,6 +1,9 @@
 use std::io;
 
 use crate::cartridge::Mapper;
+use crate::cartridge::database;
+use crate::cartridge::header::{Region, RomHeader};
+use crate::savestate::{SaveState, StateReader, StateWriter};
 
 // Mirroring types for nametables
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,50 +17,30 @@ pub enum MirroringMode {
 pub struct Cartridge {
     /// Mapper instance that handles banking and memory access
     mapper: Box<dyn Mapper>,
+    /// Header data, corrected by the ROM database when the game is known
+    header: RomHeader,
+    /// CRC32 of PRG-ROM followed by CHR-ROM
+    crc32: u32,
+    /// MD5 of PRG-ROM followed by CHR-ROM, as FCEUX identifies ROMs
+    md5: [u8; 16],
+    /// Game title from the ROM database
+    title: Option<String>,
 }
 
 impl Cartridge {
-    /// Create a new cartridge by parsing iNES v1 file data
+    /// Create a new cartridge by parsing iNES or NES 2.0 file data
+    ///
+    /// PRG-ROM and CHR-ROM are hashed and looked up in the built-in ROM database.
+    /// When the game is known, header fields that disagree with the database
+    /// (mapper, submapper, mirroring, RAM sizes and region) are corrected.
     pub fn new(data: &[u8]) -> io::Result<Self> {
-        // Validate iNES header (first 4 bytes should be "NES\x1A")
-        if data.len() < 16 || &data[0..4] != b"NES\x1A" {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Invalid iNES file format",
-            ));
-        }
-
-        // Parse iNES header
-        let prg_rom_size = data[4] as usize * 16384; // 16 KB units
-        let chr_rom_size = data[5] as usize * 8192; // 8 KB units
-        let flags6 = data[6];
-        let flags7 = data[7];
-
-        // Parse mapper number from flags6 and flags7
-        // Lower nibble: bits 4-7 of flags6
-        // Upper nibble: bits 4-7 of flags7
-        let mapper_number = (flags6 >> 4) | (flags7 & 0xF0);
-
-        // Parse mirroring from flags6
-        // Bit 0: Mirroring (0 = horizontal, 1 = vertical)
-        // Bit 3: Four-screen mode
-        let mirroring = if (flags6 & 0x08) != 0 {
-            MirroringMode::FourScreen
-        } else if (flags6 & 0x01) != 0 {
-            MirroringMode::Vertical
-        } else {
-            MirroringMode::Horizontal
-        };
-
-        // Check if trainer is present (bit 2 of flags6)
-        let has_trainer = (flags6 & 0x04) != 0;
-        let trainer_offset = if has_trainer { 512 } else { 0 };
+        let mut header = RomHeader::parse(data)?;
 
         // Calculate ROM positions
-        let prg_rom_start = 16 + trainer_offset;
-        let prg_rom_end = prg_rom_start + prg_rom_size;
+        let prg_rom_start = header.prg_rom_start();
+        let prg_rom_end = prg_rom_start + header.prg_rom_size;
         let chr_rom_start = prg_rom_end;
-        let chr_rom_end = chr_rom_start + chr_rom_size;
+        let chr_rom_end = chr_rom_start + header.chr_rom_size;
 
         // Validate buffer size
         if data.len() < chr_rom_end {
@@ -71,11 +54,57 @@ impl Cartridge {
         let prg_rom = data[prg_rom_start..prg_rom_end].to_vec();
         let chr_rom = data[chr_rom_start..chr_rom_end].to_vec();
 
+        // Hash PRG+CHR and correct the header from the ROM database
+        let crc32 = crc32fast::hash(&data[prg_rom_start..chr_rom_end]);
+        let md5 = md5::compute(&data[prg_rom_start..chr_rom_end]).0;
+        let title = database::lookup(crc32).map(|entry| {
+            let corrections = entry.apply(&mut header);
+            if !corrections.is_empty() {
+                eprintln!(
+                    "ROM database: corrected header for \"{}\" ({:08X}): {}",
+                    entry.title,
+                    crc32,
+                    corrections.join(", ")
+                );
+            }
+            entry.title.clone()
+        });
+
         // Create mapper instance
-        let mapper =
-            crate::cartridge::mapper::create_mapper(mapper_number, prg_rom, chr_rom, mirroring)?;
+        let mapper = crate::cartridge::mapper::create_mapper(&header, prg_rom, chr_rom)?;
+
+        Ok(Self {
+            mapper,
+            header,
+            crc32,
+            md5,
+            title,
+        })
+    }
+
+    /// Get the (database-corrected) ROM header
+    pub fn header(&self) -> &RomHeader {
+        &self.header
+    }
+
+    /// Get the CRC32 of PRG-ROM followed by CHR-ROM
+    pub fn crc32(&self) -> u32 {
+        self.crc32
+    }
 
-        Ok(Self { mapper })
+    /// Get the MD5 of PRG-ROM followed by CHR-ROM
+    pub fn md5(&self) -> [u8; 16] {
+        self.md5
+    }
+
+    /// Get the game title if the ROM is in the ROM database
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Get the region the ROM was made for
+    pub fn region(&self) -> Region {
+        self.header.region
     }
 
     /// Get a reference to the mapper
@@ -91,9 +120,46 @@ impl Cartridge {
     /// Create a cartridge directly from components (for testing)
     #[cfg(test)]
     pub fn from_parts(prg_rom: Vec<u8>, chr_rom: Vec<u8>, mirroring: MirroringMode) -> Self {
-        use crate::cartridge::nrom::NROMMapper;
-        let mapper = Box::new(NROMMapper::new(prg_rom, chr_rom, mirroring));
-        Self { mapper }
+        let rom = [prg_rom.as_slice(), chr_rom.as_slice()].concat();
+        let crc32 = crc32fast::hash(&rom);
+        let md5 = md5::compute(&rom).0;
+        let header = RomHeader::ines(0, prg_rom.len(), chr_rom.len(), mirroring);
+        let mapper = crate::cartridge::mapper::create_mapper(&header, prg_rom, chr_rom)
+            .expect("NROM is always implemented");
+        Self {
+            mapper,
+            header,
+            crc32,
+            md5,
+            title: None,
+        }
+    }
+}
+
+impl SaveState for MirroringMode {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(*self as u8);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        *self = match state.read_u8()? {
+            0 => MirroringMode::Vertical,
+            1 => MirroringMode::Horizontal,
+            2 => MirroringMode::FourScreen,
+            3 => MirroringMode::SingleScreen,
+            mode => return Err(format!("Invalid mirroring mode {} in the state", mode)),
+        };
+        Ok(())
+    }
+}
+
+impl SaveState for Cartridge {
+    fn save_state(&self, state: &mut StateWriter) {
+        self.mapper.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        self.mapper.load_state(state)
     }
 }
 
@@ -297,4 +363,72 @@ mod tests {
             MirroringMode::Vertical
         ));
     }
+
+    #[test]
+    fn test_unknown_rom_has_no_title() {
+        let rom_data = create_test_rom(1, 1, 0, false);
+        let cartridge = Cartridge::new(&rom_data).unwrap();
+        assert_eq!(cartridge.title(), None);
+        assert_eq!(cartridge.header().mapper, 0);
+    }
+
+    #[test]
+    fn test_crc32_covers_prg_and_chr_only() {
+        let rom_data = create_test_rom(1, 1, 0x04, true);
+        let cartridge = Cartridge::new(&rom_data).unwrap();
+        assert_eq!(cartridge.crc32(), crc32fast::hash(&rom_data[16 + 512..]));
+        assert_eq!(cartridge.md5(), md5::compute(&rom_data[16 + 512..]).0);
+    }
+
+    #[test]
+    fn test_database_corrects_region() {
+        // The nmi_sync PAL demo ships with an NTSC iNES header
+        let rom_data = std::fs::read("roms/nmi_sync/demo_pal.nes").unwrap();
+        let cartridge = Cartridge::new(&rom_data).unwrap();
+        assert_eq!(cartridge.title(), Some("NMI Sync Demo (PAL)"));
+        assert_eq!(cartridge.region(), Region::Pal);
+    }
+
+    #[test]
+    fn test_database_corrects_mapper_and_mirroring() {
+        let mut rom_data = std::fs::read("roms/nmi_sync/demo_ntsc.nes").unwrap();
+        // Corrupt the header: mapper 3 with vertical mirroring
+        rom_data[6] = 0x31;
+        let cartridge = Cartridge::new(&rom_data).unwrap();
+        assert_eq!(cartridge.header().mapper, 0);
+        assert!(matches!(
+            cartridge.mapper().get_mirroring(),
+            MirroringMode::Horizontal
+        ));
+    }
+
+    /// Turn a test ROM into a NES 2.0 ROM with the given RAM size byte 10
+    fn nes2_rom(mut rom_data: Vec<u8>, prg_ram_shifts: u8) -> Vec<u8> {
+        rom_data[7] = 0x08;
+        rom_data[10] = prg_ram_shifts;
+        rom_data
+    }
+
+    #[test]
+    fn test_prg_ram_sized_from_header() {
+        let rom_data = nes2_rom(create_test_rom(1, 1, 0, false), 0x70);
+        let mut cartridge = Cartridge::new(&rom_data).unwrap();
+        cartridge.mapper_mut().write_prg(0x7FFF, 0x55);
+        assert_eq!(cartridge.mapper().read_prg(0x7FFF), 0x55);
+
+        let rom_data = nes2_rom(create_test_rom(1, 1, 0, false), 0x00);
+        let mut cartridge = Cartridge::new(&rom_data).unwrap();
+        cartridge.mapper_mut().write_prg(0x6000, 0x55);
+        assert_eq!(cartridge.mapper().read_prg(0x6000), 0);
+    }
+
+    #[test]
+    fn test_database_corrects_prg_ram_size() {
+        // A NES 2.0 header without PRG-RAM gets the 8 KB from the database
+        let rom_data = nes2_rom(std::fs::read("roms/nmi_sync/demo_ntsc.nes").unwrap(), 0);
+        let mut cartridge = Cartridge::new(&rom_data).unwrap();
+        assert_eq!(cartridge.header().prg_ram_size, 8192);
+        cartridge.mapper_mut().write_prg(0x6000, 0x55);
+        assert_eq!(cartridge.mapper().read_prg(0x6000), 0x55);
+    }
 }
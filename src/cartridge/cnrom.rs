@@ -1,5 +1,6 @@
 use crate::cartridge::Mapper;
 use crate::cartridge::MirroringMode;
+use crate::savestate::{SaveState, StateReader, StateWriter};
 
 // Memory size constants
 const CHR_RAM_SIZE: usize = 8192; // 8KB
@@ -36,6 +37,12 @@ impl CNROMMapper {
         }
     }
 
+    /// Size PRG-RAM from the ROM header (CNROM has no CHR-RAM)
+    pub fn with_ram(mut self, prg_ram_size: usize, _chr_ram_size: usize) -> Self {
+        self.prg_ram = vec![0; prg_ram_size];
+        self
+    }
+
     fn get_chr_bank_offset(&self) -> usize {
         let num_banks = (self.chr_rom.len() / CHR_RAM_SIZE).max(1);
         let bank = (self.chr_bank_select as usize) % num_banks;
@@ -52,11 +59,9 @@ impl Mapper for CNROMMapper {
                 self.prg_ram.get(offset).copied().unwrap_or(0)
             }
             // PRG ROM is fixed at $8000-$FFFF (32KB or 16KB)
-            0x8000..=0xFFFF => {
-                let offset = (addr - 0x8000) as usize;
-                let index = offset % self.prg_rom.len();
-                self.prg_rom.get(index).copied().unwrap_or(0)
-            }
+            0x8000..=0xFFFF => self
+                .prg_rom_offset(addr)
+                .map_or(0, |index| self.prg_rom[index]),
             _ => 0,
         }
     }
@@ -79,9 +84,8 @@ impl Mapper for CNROMMapper {
     }
 
     fn read_chr(&self, addr: u16) -> u8 {
-        let bank_offset = self.get_chr_bank_offset();
-        let index = bank_offset + (addr & CHR_MASK) as usize;
-        self.chr_rom.get(index).copied().unwrap_or(0)
+        self.chr_rom_offset(addr)
+            .map_or(0, |index| self.chr_rom[index])
     }
 
     fn write_chr(&mut self, _addr: u16, _value: u8) {
@@ -95,6 +99,36 @@ impl Mapper for CNROMMapper {
     fn get_mirroring(&self) -> MirroringMode {
         self.mirroring
     }
+
+    fn prg_rom(&self) -> &[u8] {
+        &self.prg_rom
+    }
+
+    fn prg_rom_offset(&self, addr: u16) -> Option<usize> {
+        if addr < 0x8000 || self.prg_rom.is_empty() {
+            return None;
+        }
+        // PRG ROM is fixed at $8000-$FFFF (32KB or 16KB)
+        Some((addr - 0x8000) as usize % self.prg_rom.len())
+    }
+
+    fn chr_rom_offset(&self, addr: u16) -> Option<usize> {
+        let index = self.get_chr_bank_offset() + (addr & CHR_MASK) as usize;
+        (index < self.chr_rom.len()).then_some(index)
+    }
+}
+
+impl SaveState for CNROMMapper {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.prg_ram);
+        state.write_u8(self.chr_bank_select);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.read_bytes_into(&mut self.prg_ram)?;
+        self.chr_bank_select = state.read_u8()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
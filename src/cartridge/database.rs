@@ -0,0 +1,406 @@
+use crate::cartridge::MirroringMode;
+use crate::cartridge::header::{Region, RomHeader};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Embedded database in NES 2.0 header terms, keyed by PRG+CHR CRC32
+const DATABASE_TEXT: &str = include_str!("nes20db.txt");
+
+/// Known-good header data for a single game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameEntry {
+    /// CRC32 of PRG-ROM followed by CHR-ROM
+    pub crc32: u32,
+    /// Game title
+    pub title: String,
+    pub mapper: u16,
+    pub submapper: u8,
+    pub mirroring: MirroringMode,
+    pub prg_ram_size: usize,
+    pub prg_nvram_size: usize,
+    pub chr_ram_size: usize,
+    pub region: Region,
+}
+
+impl GameEntry {
+    /// Overwrite header fields that disagree with this entry
+    ///
+    /// Returns a human-readable description of every corrected field.
+    pub fn apply(&self, header: &mut RomHeader) -> Vec<String> {
+        let mut corrections = Vec::new();
+
+        if header.mapper != self.mapper {
+            corrections.push(format!("mapper {} -> {}", header.mapper, self.mapper));
+            header.mapper = self.mapper;
+        }
+        if header.submapper != self.submapper {
+            corrections.push(format!(
+                "submapper {} -> {}",
+                header.submapper, self.submapper
+            ));
+            header.submapper = self.submapper;
+        }
+        if header.mirroring != self.mirroring {
+            corrections.push(format!(
+                "mirroring {:?} -> {:?}",
+                header.mirroring, self.mirroring
+            ));
+            header.mirroring = self.mirroring;
+        }
+        if header.prg_ram_size != self.prg_ram_size {
+            corrections.push(format!(
+                "PRG-RAM {} -> {} bytes",
+                header.prg_ram_size, self.prg_ram_size
+            ));
+            header.prg_ram_size = self.prg_ram_size;
+        }
+        if header.prg_nvram_size != self.prg_nvram_size {
+            corrections.push(format!(
+                "PRG-NVRAM {} -> {} bytes",
+                header.prg_nvram_size, self.prg_nvram_size
+            ));
+            header.prg_nvram_size = self.prg_nvram_size;
+        }
+        if header.chr_ram_size != self.chr_ram_size {
+            corrections.push(format!(
+                "CHR-RAM {} -> {} bytes",
+                header.chr_ram_size, self.chr_ram_size
+            ));
+            header.chr_ram_size = self.chr_ram_size;
+        }
+        if header.region != self.region {
+            corrections.push(format!("region {:?} -> {:?}", header.region, self.region));
+            header.region = self.region;
+        }
+
+        corrections
+    }
+}
+
+impl fmt::Display for GameEntry {
+    /// Format the entry as a line of the embedded database
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mirroring = match self.mirroring {
+            MirroringMode::Horizontal => "H",
+            MirroringMode::Vertical => "V",
+            MirroringMode::FourScreen => "4",
+            MirroringMode::SingleScreen => "1",
+        };
+        let region = match self.region {
+            Region::Ntsc => "NTSC",
+            Region::Pal => "PAL",
+            Region::Multi => "MULTI",
+            Region::Dendy => "DENDY",
+        };
+        write!(
+            f,
+            "{:08X}   {:<6} {:<3} {:<3} {:<6} {:<8} {:<6} {:<6} {}",
+            self.crc32,
+            self.mapper,
+            self.submapper,
+            mirroring,
+            self.prg_ram_size,
+            self.prg_nvram_size,
+            self.chr_ram_size,
+            region,
+            self.title
+        )
+    }
+}
+
+/// Look up a game by the CRC32 of its PRG-ROM and CHR-ROM
+pub fn lookup(crc32: u32) -> Option<&'static GameEntry> {
+    static DATABASE: OnceLock<HashMap<u32, GameEntry>> = OnceLock::new();
+    DATABASE
+        .get_or_init(|| {
+            let mut database = HashMap::new();
+            // Hand-maintained entries come first and win over imported ones
+            for entry in parse_database(DATABASE_TEXT) {
+                database.entry(entry.crc32).or_insert(entry);
+            }
+            database
+        })
+        .get(&crc32)
+}
+
+/// Parse database text, skipping comments and malformed lines
+fn parse_database(text: &str) -> Vec<GameEntry> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let entry = parse_entry(line);
+            if entry.is_none() {
+                eprintln!("Warning: Ignoring malformed ROM database line: {}", line);
+            }
+            entry
+        })
+        .collect()
+}
+
+/// Parse a single `crc32 mapper sub mir prgram prgnvram chrram region title` line
+fn parse_entry(line: &str) -> Option<GameEntry> {
+    let mut fields = line.split_whitespace();
+    let crc32 = u32::from_str_radix(fields.next()?, 16).ok()?;
+    let mapper = fields.next()?.parse().ok()?;
+    let submapper = fields.next()?.parse().ok()?;
+    let mirroring = match fields.next()? {
+        "H" => MirroringMode::Horizontal,
+        "V" => MirroringMode::Vertical,
+        "4" => MirroringMode::FourScreen,
+        "1" => MirroringMode::SingleScreen,
+        _ => return None,
+    };
+    let prg_ram_size = fields.next()?.parse().ok()?;
+    let prg_nvram_size = fields.next()?.parse().ok()?;
+    let chr_ram_size = fields.next()?.parse().ok()?;
+    let region = match fields.next()? {
+        "NTSC" => Region::Ntsc,
+        "PAL" => Region::Pal,
+        "MULTI" => Region::Multi,
+        "DENDY" => Region::Dendy,
+        _ => return None,
+    };
+
+    // Whatever follows the region column is the title
+    let title = line
+        .split_whitespace()
+        .skip(8)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if title.is_empty() {
+        return None;
+    }
+
+    Some(GameEntry {
+        crc32,
+        title,
+        mapper,
+        submapper,
+        mirroring,
+        prg_ram_size,
+        prg_nvram_size,
+        chr_ram_size,
+        region,
+    })
+}
+
+/// Convert the `<game>` elements of an nes20db.xml file into database entries
+///
+/// This is how `nes20db.txt` is generated (`neser nes20db nes20db.xml`). The
+/// title comes from the comment naming the ROM file, and the key is the CRC32
+/// of the `<rom>` element, which covers PRG-ROM followed by CHR-ROM. Games
+/// without a ROM CRC32 or with mirroring this emulator can't represent are
+/// skipped.
+pub fn import_nes20db(xml: &str) -> Vec<GameEntry> {
+    let mut entries = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find("<game>") {
+        let after = &rest[start + "<game>".len()..];
+        let Some(end) = after.find("</game>") else {
+            break;
+        };
+        if let Some(entry) = import_game(&after[..end]) {
+            entries.push(entry);
+        }
+        rest = &after[end..];
+    }
+
+    entries
+}
+
+/// Convert the body of a single nes20db `<game>` element
+fn import_game(game: &str) -> Option<GameEntry> {
+    let comment_start = game.find("<!--")? + "<!--".len();
+    let comment_end = comment_start + game[comment_start..].find("-->")?;
+    let file_name = game[comment_start..comment_end].trim();
+    let file_name = file_name.rsplit(['\\', '/']).next().unwrap_or(file_name);
+    let title = unescape_xml(file_name.strip_suffix(".nes").unwrap_or(file_name));
+
+    let size = |element: &str| {
+        xml_attribute(game, element, "size")
+            .and_then(|size| size.parse().ok())
+            .unwrap_or(0)
+    };
+    let pcb = |attribute: &str| xml_attribute(game, "pcb", attribute);
+
+    let mirroring = match pcb("mirroring").unwrap_or("H") {
+        "H" => MirroringMode::Horizontal,
+        "V" => MirroringMode::Vertical,
+        "4" => MirroringMode::FourScreen,
+        "1" => MirroringMode::SingleScreen,
+        _ => return None,
+    };
+    let region = match xml_attribute(game, "console", "region").unwrap_or("0") {
+        "0" => Region::Ntsc,
+        "1" => Region::Pal,
+        "2" => Region::Multi,
+        "3" => Region::Dendy,
+        _ => return None,
+    };
+
+    Some(GameEntry {
+        crc32: u32::from_str_radix(xml_attribute(game, "rom", "crc32")?, 16).ok()?,
+        title,
+        mapper: pcb("mapper")?.parse().ok()?,
+        submapper: pcb("submapper").unwrap_or("0").parse().ok()?,
+        mirroring,
+        prg_ram_size: size("prgram"),
+        prg_nvram_size: size("prgnvram"),
+        chr_ram_size: size("chrram"),
+        region,
+    })
+}
+
+/// Get an attribute of the first `<element .../>` in an nes20db game
+fn xml_attribute<'a>(xml: &'a str, element: &str, attribute: &str) -> Option<&'a str> {
+    let open = format!("<{} ", element);
+    let start = xml.find(&open)? + open.len();
+    let tag = &xml[start..start + xml[start..].find('>')?];
+    let key = format!("{}=\"", attribute);
+    // Only match whole attribute names
+    let value_start = tag
+        .match_indices(&key)
+        .find(|(i, _)| *i == 0 || tag.as_bytes()[i - 1].is_ascii_whitespace())?
+        .0
+        + key.len();
+    let value_end = value_start + tag[value_start..].find('"')?;
+    Some(&tag[value_start..value_end])
+}
+
+/// Replace the predefined XML entities
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_database_parses_every_line() {
+        let expected = DATABASE_TEXT
+            .lines()
+            .filter(|l| !l.trim().is_empty() && !l.trim().starts_with('#'))
+            .count();
+        assert_eq!(parse_database(DATABASE_TEXT).len(), expected);
+    }
+
+    #[test]
+    fn test_lookup_known_rom() {
+        let entry = lookup(0x9B37F35A).expect("nmi_sync PAL demo should be in the database");
+        assert_eq!(entry.title, "NMI Sync Demo (PAL)");
+        assert_eq!(entry.region, Region::Pal);
+    }
+
+    #[test]
+    fn test_lookup_unknown_rom() {
+        assert!(lookup(0xDEADBEEF).is_none());
+    }
+
+    #[test]
+    fn test_parse_entry_with_multi_word_title() {
+        let entry = parse_entry("0000ABCD 3 1 V 0 8192 0 MULTI Some  Game (USA)").unwrap();
+        assert_eq!(entry.crc32, 0xABCD);
+        assert_eq!(entry.mapper, 3);
+        assert_eq!(entry.submapper, 1);
+        assert_eq!(entry.mirroring, MirroringMode::Vertical);
+        assert_eq!(entry.prg_nvram_size, 8192);
+        assert_eq!(entry.region, Region::Multi);
+        assert_eq!(entry.title, "Some Game (USA)");
+    }
+
+    #[test]
+    fn test_parse_entry_rejects_bad_mirroring() {
+        assert!(parse_entry("0000ABCD 3 1 X 0 0 0 NTSC Game").is_none());
+    }
+
+    #[test]
+    fn test_apply_reports_corrections() {
+        let entry = parse_entry("00000001 3 0 V 0 8192 0 PAL Game").unwrap();
+        let mut header =
+            RomHeader::parse(&[b'N', b'E', b'S', 0x1A, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0])
+                .unwrap();
+
+        let corrections = entry.apply(&mut header);
+
+        assert_eq!(header.mapper, 3);
+        assert_eq!(header.mirroring, MirroringMode::Vertical);
+        assert_eq!(header.prg_nvram_size, 8192);
+        assert_eq!(header.region, Region::Pal);
+        assert!(corrections.iter().any(|c| c == "mapper 0 -> 3"));
+        assert!(corrections.iter().any(|c| c == "region Ntsc -> Pal"));
+    }
+
+    /// A game in nes20db.xml layout whose dumps commonly carry a mapper 0,
+    /// horizontal mirroring header instead of CNROM with vertical mirroring
+    const NES20DB_GAME: &str = r#"<nes20db date="2024-01-01">
+<game>
+	<!-- Licensed\Bad &amp; Mirrored (USA).nes -->
+	<prgrom size="32768" crc32="11111111" sha1="0" sum16="0"/>
+	<chrrom size="32768" crc32="22222222" sha1="0" sum16="0"/>
+	<prgnvram size="8192"/>
+	<rom size="65536" crc32="0BAD0BAD" sha1="0"/>
+	<console type="0" region="1"/>
+	<pcb mapper="3" submapper="2" mirroring="V" battery="1"/>
+</game>
+<game>
+	<!-- No Checksum.nes -->
+	<pcb mapper="0" submapper="0" mirroring="H" battery="0"/>
+</game>
+</nes20db>"#;
+
+    #[test]
+    fn test_import_nes20db() {
+        let entries = import_nes20db(NES20DB_GAME);
+
+        assert_eq!(entries.len(), 1, "games without a ROM CRC32 are skipped");
+        let entry = &entries[0];
+        assert_eq!(entry.crc32, 0x0BAD0BAD);
+        assert_eq!(entry.title, "Bad & Mirrored (USA)");
+        assert_eq!(entry.mapper, 3);
+        assert_eq!(entry.submapper, 2);
+        assert_eq!(entry.mirroring, MirroringMode::Vertical);
+        assert_eq!(entry.prg_ram_size, 0);
+        assert_eq!(entry.prg_nvram_size, 8192);
+        assert_eq!(entry.chr_ram_size, 0);
+        assert_eq!(entry.region, Region::Pal);
+    }
+
+    #[test]
+    fn test_imported_entry_round_trips_through_database_line() {
+        let entry = import_nes20db(NES20DB_GAME).remove(0);
+        assert_eq!(parse_entry(&entry.to_string()), Some(entry));
+    }
+
+    #[test]
+    fn test_imported_entry_corrects_bad_header() {
+        let entry = import_nes20db(NES20DB_GAME).remove(0);
+        let mut header =
+            RomHeader::parse(&[b'N', b'E', b'S', 0x1A, 2, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0])
+                .unwrap();
+
+        let corrections = entry.apply(&mut header);
+
+        assert_eq!(header.mapper, 3);
+        assert_eq!(header.submapper, 2);
+        assert_eq!(header.mirroring, MirroringMode::Vertical);
+        assert_eq!(
+            corrections,
+            [
+                "mapper 0 -> 3",
+                "submapper 0 -> 2",
+                "mirroring Horizontal -> Vertical",
+                "PRG-RAM 8192 -> 0 bytes",
+                "PRG-NVRAM 0 -> 8192 bytes",
+                "region Ntsc -> Pal",
+            ]
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use crate::cartridge::MirroringMode;
+use crate::nes::TvSystem;
+use std::io;
+
+/// Size of the iNES / NES 2.0 header in bytes
+pub const HEADER_SIZE: usize = 16;
+
+/// Size of the optional trainer that follows the header
+const TRAINER_SIZE: usize = 512;
+
+/// Console region / CPU-PPU timing a ROM was made for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    /// RP2C02 (North America, Japan)
+    Ntsc,
+    /// RP2C07 (Europe, Australia)
+    Pal,
+    /// Works identically on NTSC and PAL consoles
+    Multi,
+    /// UA6538 (Dendy and other famiclones)
+    Dendy,
+}
+
+impl Region {
+    /// Returns the TV system to emulate for this region
+    ///
+    /// Multi-region games run on NTSC. Dendy timing is not emulated, so Dendy
+    /// games fall back to PAL, which has the same frame rate.
+    pub fn tv_system(&self) -> TvSystem {
+        match self {
+            Region::Ntsc | Region::Multi => TvSystem::Ntsc,
+            Region::Pal | Region::Dendy => TvSystem::Pal,
+        }
+    }
+}
+
+/// Decoded contents of an iNES or NES 2.0 header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RomHeader {
+    /// True if the header uses the NES 2.0 format
+    pub nes2: bool,
+    /// Mapper number (12 bits for NES 2.0, 8 bits for iNES)
+    pub mapper: u16,
+    /// Submapper number (NES 2.0 only, 0 otherwise)
+    pub submapper: u8,
+    /// Hard-wired nametable mirroring
+    pub mirroring: MirroringMode,
+    /// Cartridge contains battery-backed memory
+    pub battery: bool,
+    /// A 512-byte trainer sits between the header and PRG-ROM
+    pub trainer: bool,
+    /// PRG-ROM size in bytes
+    pub prg_rom_size: usize,
+    /// CHR-ROM size in bytes (0 means the cartridge uses CHR-RAM)
+    pub chr_rom_size: usize,
+    /// Volatile PRG-RAM size in bytes
+    pub prg_ram_size: usize,
+    /// Battery-backed PRG-RAM size in bytes
+    pub prg_nvram_size: usize,
+    /// Volatile CHR-RAM size in bytes
+    pub chr_ram_size: usize,
+    /// Region the ROM was made for
+    pub region: Region,
+}
+
+impl RomHeader {
+    /// Parse the 16-byte header at the start of a ROM file
+    pub fn parse(data: &[u8]) -> io::Result<Self> {
+        // Validate iNES header (first 4 bytes should be "NES\x1A")
+        if data.len() < HEADER_SIZE || &data[0..4] != b"NES\x1A" {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Invalid iNES file format",
+            ));
+        }
+
+        let flags6 = data[6];
+        let flags7 = data[7];
+
+        // Bits 2-3 of flags7 equal to %10 identify a NES 2.0 header
+        let nes2 = (flags7 & 0x0C) == 0x08;
+
+        // Parse mirroring from flags6
+        // Bit 0: Mirroring (0 = horizontal, 1 = vertical)
+        // Bit 3: Four-screen mode
+        let mirroring = if (flags6 & 0x08) != 0 {
+            MirroringMode::FourScreen
+        } else if (flags6 & 0x01) != 0 {
+            MirroringMode::Vertical
+        } else {
+            MirroringMode::Horizontal
+        };
+
+        // Lower nibble: bits 4-7 of flags6
+        // Upper nibble: bits 4-7 of flags7
+        let mut mapper = ((flags6 >> 4) | (flags7 & 0xF0)) as u16;
+
+        let header = if nes2 {
+            // Byte 8: mapper bits 8-11 (low nibble), submapper (high nibble)
+            mapper |= ((data[8] & 0x0F) as u16) << 8;
+            let region = match data[12] & 0x03 {
+                0 => Region::Ntsc,
+                1 => Region::Pal,
+                2 => Region::Multi,
+                _ => Region::Dendy,
+            };
+            Self {
+                nes2,
+                mapper,
+                submapper: data[8] >> 4,
+                mirroring,
+                battery: (flags6 & 0x02) != 0,
+                trainer: (flags6 & 0x04) != 0,
+                prg_rom_size: Self::nes2_rom_size(data[4], data[9] & 0x0F, 16384),
+                chr_rom_size: Self::nes2_rom_size(data[5], data[9] >> 4, 8192),
+                prg_ram_size: Self::nes2_ram_size(data[10] & 0x0F),
+                prg_nvram_size: Self::nes2_ram_size(data[10] >> 4),
+                chr_ram_size: Self::nes2_ram_size(data[11] & 0x0F),
+                region,
+            }
+        } else {
+            let battery = (flags6 & 0x02) != 0;
+            // Byte 8: PRG-RAM size in 8 KB units, 0 infers 8 KB for compatibility
+            let prg_ram_size = (data[8].max(1) as usize) * 8192;
+            let chr_rom_size = data[5] as usize * 8192; // 8 KB units
+            Self {
+                nes2,
+                mapper,
+                submapper: 0,
+                mirroring,
+                battery,
+                trainer: (flags6 & 0x04) != 0,
+                prg_rom_size: data[4] as usize * 16384, // 16 KB units
+                chr_rom_size,
+                prg_ram_size: if battery { 0 } else { prg_ram_size },
+                prg_nvram_size: if battery { prg_ram_size } else { 0 },
+                chr_ram_size: if chr_rom_size == 0 { 8192 } else { 0 },
+                // Flags 9 bit 0: TV system (rarely set by dumpers)
+                region: if (data[9] & 0x01) != 0 {
+                    Region::Pal
+                } else {
+                    Region::Ntsc
+                },
+            }
+        };
+
+        Ok(header)
+    }
+
+    /// iNES header for a mapper and ROM sizes, with the default RAM (for testing)
+    #[cfg(test)]
+    pub fn ines(
+        mapper: u16,
+        prg_rom_size: usize,
+        chr_rom_size: usize,
+        mirroring: MirroringMode,
+    ) -> Self {
+        Self {
+            nes2: false,
+            mapper,
+            submapper: 0,
+            mirroring,
+            battery: false,
+            trainer: false,
+            prg_rom_size,
+            chr_rom_size,
+            prg_ram_size: 8192,
+            prg_nvram_size: 0,
+            chr_ram_size: if chr_rom_size == 0 { 8192 } else { 0 },
+            region: Region::Ntsc,
+        }
+    }
+
+    /// Offset of the first PRG-ROM byte in the file
+    pub fn prg_rom_start(&self) -> usize {
+        HEADER_SIZE + if self.trainer { TRAINER_SIZE } else { 0 }
+    }
+
+    /// NES 2.0 ROM size from the LSB byte and MSB nibble
+    ///
+    /// An MSB nibble of $F selects exponent-multiplier notation,
+    /// where the size is 2^E * (MM*2+1) bytes.
+    fn nes2_rom_size(lsb: u8, msb: u8, unit: usize) -> usize {
+        if msb == 0x0F {
+            let exponent = (lsb >> 2) as u32;
+            let multiplier = (lsb & 0x03) as usize * 2 + 1;
+            2usize.saturating_pow(exponent).saturating_mul(multiplier)
+        } else {
+            (((msb as usize) << 8) | lsb as usize) * unit
+        }
+    }
+
+    /// NES 2.0 RAM size from a shift count (0 means no RAM)
+    fn nes2_ram_size(shift: u8) -> usize {
+        if shift == 0 { 0 } else { 64 << shift }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(bytes: [u8; 12]) -> Vec<u8> {
+        let mut data = b"NES\x1A".to_vec();
+        data.extend_from_slice(&bytes);
+        data
+    }
+
+    #[test]
+    fn test_ines_header() {
+        let h = RomHeader::parse(&header([2, 1, 0x31, 0x00, 0, 0, 0, 0, 0, 0, 0, 0])).unwrap();
+        assert!(!h.nes2);
+        assert_eq!(h.mapper, 3);
+        assert_eq!(h.mirroring, MirroringMode::Vertical);
+        assert_eq!(h.prg_rom_size, 32768);
+        assert_eq!(h.chr_rom_size, 8192);
+        assert_eq!(h.prg_ram_size, 8192);
+        assert_eq!(h.chr_ram_size, 0);
+        assert_eq!(h.region, Region::Ntsc);
+    }
+
+    #[test]
+    fn test_ines_battery_and_chr_ram() {
+        let h = RomHeader::parse(&header([8, 0, 0x12, 0x00, 0, 0, 0, 0, 0, 0, 0, 0])).unwrap();
+        assert_eq!(h.mapper, 1);
+        assert!(h.battery);
+        assert_eq!(h.prg_ram_size, 0);
+        assert_eq!(h.prg_nvram_size, 8192);
+        assert_eq!(h.chr_ram_size, 8192);
+    }
+
+    #[test]
+    fn test_ines_pal_flag() {
+        let h = RomHeader::parse(&header([1, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0])).unwrap();
+        assert_eq!(h.region, Region::Pal);
+    }
+
+    #[test]
+    fn test_nes2_header() {
+        // Mapper $104, submapper 2, PRG-RAM shift 7 = 8 KB, CHR-RAM shift 9 = 32 KB, Dendy
+        let h = RomHeader::parse(&header([
+            4, 0, 0x40, 0x08, 0x21, 0x00, 0x07, 0x09, 0x03, 0, 0, 0,
+        ]))
+        .unwrap();
+        assert!(h.nes2);
+        assert_eq!(h.mapper, 0x104);
+        assert_eq!(h.submapper, 2);
+        assert_eq!(h.prg_ram_size, 8192);
+        assert_eq!(h.chr_ram_size, 32768);
+        assert_eq!(h.region, Region::Dendy);
+    }
+
+    #[test]
+    fn test_nes2_exponent_rom_size() {
+        // $F MSB nibble: 2^4 * (1*2+1) = 48 bytes
+        let h =
+            RomHeader::parse(&header([0x11, 0, 0x00, 0x08, 0, 0x0F, 0, 0, 0, 0, 0, 0])).unwrap();
+        assert_eq!(h.prg_rom_size, 48);
+    }
+
+    #[test]
+    fn test_trainer_offset() {
+        let h = RomHeader::parse(&header([1, 1, 0x04, 0, 0, 0, 0, 0, 0, 0, 0, 0])).unwrap();
+        assert_eq!(h.prg_rom_start(), 16 + 512);
+    }
+
+    #[test]
+    fn test_invalid_magic() {
+        assert!(RomHeader::parse(b"NES\x00............").is_err());
+    }
+}
@@ -0,0 +1,295 @@
+use flate2::read::GzDecoder;
+use std::fs;
+use std::io::{self, Cursor, Read};
+use std::path::Path;
+use zip::ZipArchive;
+
+/// File extension of the ROM images that can be loaded from archives
+const ROM_EXTENSION: &str = "nes";
+
+/// Extensions of other NES formats that archives may contain but can't be played
+const UNSUPPORTED_EXTENSIONS: [&str; 3] = ["fds", "nsf", "unf"];
+
+/// Magic bytes at the start of a zip archive
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Magic bytes at the start of a gzip stream
+const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+
+/// Largest ROM accepted from an archive (the biggest multicarts are 32 MB)
+const MAX_ROM_SIZE: u64 = 32 * 1024 * 1024;
+
+/// Read a ROM image from disk, transparently decompressing archives
+///
+/// Plain ROM files are returned as-is. Zip archives and gzip-compressed files
+/// are detected by their magic bytes and decompressed in memory, so the result
+/// can be passed straight to `Cartridge::new`.
+///
+/// # Arguments
+/// * `path` - Path to a `.nes`, `.zip` or `.gz` file
+/// * `member` - Name of the zip member to load. When `None`, the first `.nes`
+///   member is used. Ignored for non-zip files.
+pub fn load_rom(path: &Path, member: Option<&str>) -> io::Result<Vec<u8>> {
+    let data = fs::read(path)?;
+    decompress_rom(data, member)
+}
+
+/// Decompress ROM data that may be a zip archive or gzip stream
+pub fn decompress_rom(data: Vec<u8>, member: Option<&str>) -> io::Result<Vec<u8>> {
+    if data.starts_with(ZIP_MAGIC) {
+        read_zip_member(data, member)
+    } else if data.starts_with(GZIP_MAGIC) {
+        read_limited(GzDecoder::new(data.as_slice()), MAX_ROM_SIZE)
+    } else {
+        Ok(data)
+    }
+}
+
+/// Extract a ROM from a zip archive
+fn read_zip_member(data: Vec<u8>, member: Option<&str>) -> io::Result<Vec<u8>> {
+    let mut archive = ZipArchive::new(Cursor::new(data)).map_err(zip_error)?;
+
+    // Collect files in archive order, skipping directories
+    let names: Vec<String> = (0..archive.len())
+        .filter_map(|index| archive.name_for_index(index))
+        .filter(|name| !name.ends_with('/'))
+        .map(str::to_string)
+        .collect();
+    let roms: Vec<String> = names
+        .iter()
+        .filter(|name| has_extension(name, &[ROM_EXTENSION]))
+        .cloned()
+        .collect();
+
+    let name = match member {
+        Some(requested) => select_member(&roms, requested)?,
+        None => match roms.first() {
+            Some(name) => name.clone(),
+            None => {
+                let unsupported = names
+                    .iter()
+                    .find(|name| has_extension(name, &UNSUPPORTED_EXTENSIONS));
+                return Err(match unsupported {
+                    Some(name) => io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        format!(
+                            "Archive contains {}, but only .{} ROMs are supported",
+                            name, ROM_EXTENSION
+                        ),
+                    ),
+                    None => io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Archive contains no ROM files (expected .{})",
+                            ROM_EXTENSION
+                        ),
+                    ),
+                });
+            }
+        },
+    };
+
+    let file = archive.by_name(&name).map_err(zip_error)?;
+    read_limited(file, MAX_ROM_SIZE)
+}
+
+/// Read decompressed ROM data, failing if it is larger than `limit` bytes
+///
+/// Archive headers can claim any size, so nothing is allocated up front.
+fn read_limited(reader: impl Read, limit: u64) -> io::Result<Vec<u8>> {
+    let mut rom = Vec::new();
+    reader.take(limit + 1).read_to_end(&mut rom)?;
+    if rom.len() as u64 > limit {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Decompressed ROM is larger than {} bytes", limit),
+        ));
+    }
+    Ok(rom)
+}
+
+/// Pick the member the user asked for
+///
+/// An exact path match wins. Otherwise the request is matched case-insensitively
+/// against file names, and must identify exactly one ROM.
+fn select_member(roms: &[String], requested: &str) -> io::Result<String> {
+    if let Some(name) = roms.iter().find(|name| *name == requested) {
+        return Ok(name.clone());
+    }
+
+    let needle = requested.to_lowercase();
+    let matches: Vec<&String> = roms
+        .iter()
+        .filter(|name| name.to_lowercase().contains(&needle))
+        .collect();
+
+    match matches.as_slice() {
+        [name] => Ok((*name).clone()),
+        [] => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "Archive has no ROM matching \"{}\". Available: {}",
+                requested,
+                list_names(roms.iter())
+            ),
+        )),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "\"{}\" matches several ROMs in the archive: {}",
+                requested,
+                list_names(matches.into_iter())
+            ),
+        )),
+    }
+}
+
+/// Check whether a file name has one of the given extensions
+fn has_extension(name: &str, extensions: &[&str]) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            extensions
+                .iter()
+                .any(|expected| ext.eq_ignore_ascii_case(expected))
+        })
+}
+
+fn list_names<'a>(names: impl Iterator<Item = &'a String>) -> String {
+    let names: Vec<&str> = names.map(String::as_str).collect();
+    if names.is_empty() {
+        "none".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+fn zip_error(e: zip::result::ZipError) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid zip archive: {}", e),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    fn make_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_plain_rom_is_passed_through() {
+        let data = b"NES\x1Arest".to_vec();
+        assert_eq!(decompress_rom(data.clone(), None).unwrap(), data);
+    }
+
+    #[test]
+    fn test_gzip_rom() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"NES\x1Agzipped").unwrap();
+        let data = encoder.finish().unwrap();
+
+        assert_eq!(decompress_rom(data, None).unwrap(), b"NES\x1Agzipped");
+    }
+
+    #[test]
+    fn test_zip_picks_first_rom_member() {
+        let data = make_zip(&[
+            ("readme.txt", b"hello"),
+            ("games/b.nes", b"second"),
+            ("games/a.nes", b"third"),
+        ]);
+
+        assert_eq!(decompress_rom(data, None).unwrap(), b"second");
+    }
+
+    #[test]
+    fn test_zip_skips_unsupported_formats() {
+        let data = make_zip(&[("disk.fds", b"fds"), ("Game.NES", b"nes")]);
+        assert_eq!(decompress_rom(data, None).unwrap(), b"nes");
+    }
+
+    #[test]
+    fn test_zip_with_only_unsupported_formats_is_an_error() {
+        let data = make_zip(&[("readme.txt", b"hello"), ("music.NSF", b"nsf")]);
+        let err = decompress_rom(data, None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        assert!(err.to_string().contains("music.NSF"));
+    }
+
+    #[test]
+    fn test_zip_user_selected_member() {
+        let data = make_zip(&[("Zelda (USA).nes", b"zelda"), ("Mario (USA).nes", b"mario")]);
+        assert_eq!(decompress_rom(data, Some("mario")).unwrap(), b"mario");
+    }
+
+    #[test]
+    fn test_zip_exact_member_wins_over_substring() {
+        let data = make_zip(&[("game.nes", b"exact"), ("game.nes.bak.nes", b"other")]);
+        assert_eq!(decompress_rom(data, Some("game.nes")).unwrap(), b"exact");
+    }
+
+    #[test]
+    fn test_zip_without_roms_is_an_error() {
+        let data = make_zip(&[("readme.txt", b"hello")]);
+        let err = decompress_rom(data, None).unwrap_err();
+        assert!(err.to_string().contains("no ROM files"));
+    }
+
+    #[test]
+    fn test_zip_ambiguous_member_is_an_error() {
+        let data = make_zip(&[("Game (USA).nes", b"usa"), ("Game (Europe).nes", b"eur")]);
+        let err = decompress_rom(data, Some("game")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("Game (USA).nes"));
+        assert!(err.to_string().contains("Game (Europe).nes"));
+    }
+
+    #[test]
+    fn test_zip_missing_member_is_an_error() {
+        let data = make_zip(&[("a.nes", b"a")]);
+        let err = decompress_rom(data, Some("b.nes")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("a.nes"));
+    }
+
+    #[test]
+    fn test_read_limited() {
+        assert_eq!(read_limited(&b"1234"[..], 4).unwrap(), b"1234");
+        let err = read_limited(&b"12345"[..], 4).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_oversized_gzip_is_an_error() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        encoder
+            .write_all(&vec![0; MAX_ROM_SIZE as usize + 1])
+            .unwrap();
+        let data = encoder.finish().unwrap();
+
+        let err = decompress_rom(data, None).unwrap_err();
+        assert!(err.to_string().contains("larger than"));
+    }
+
+    #[test]
+    fn test_corrupt_zip_is_an_error() {
+        let mut data = ZIP_MAGIC.to_vec();
+        data.extend_from_slice(&[0; 16]);
+        assert!(decompress_rom(data, None).is_err());
+    }
+}
@@ -1,4 +1,6 @@
 use crate::cartridge::MirroringMode;
+use crate::cartridge::header::RomHeader;
+use crate::savestate::SaveState;
 use std::io;
 
 use super::axrom::AxROMMapper;
@@ -7,7 +9,10 @@ use super::mmc1::MMC1Mapper;
 use super::nrom::NROMMapper;
 use super::uxrom::UxROMMapper;
 
-pub trait Mapper {
+/// Bank switching and memory of a cartridge
+///
+/// The saved state covers the cartridge RAM and the mapper registers.
+pub trait Mapper: Send + SaveState {
     /// Read a byte from PRG address space (CPU $6000-$FFFF)
     /// - $6000-$7FFF: PRG-RAM (8KB, battery-backed on some cartridges)
     /// - $8000-$FFFF: PRG-ROM (with bank switching on advanced mappers)
@@ -34,24 +39,52 @@ pub trait Mapper {
     /// Get the current nametable mirroring mode
     /// Some mappers can change mirroring dynamically
     fn get_mirroring(&self) -> MirroringMode;
+
+    /// Get the whole PRG-ROM, independent of the current bank mapping
+    fn prg_rom(&self) -> &[u8];
+
+    /// Get the PRG-ROM offset that a CPU address is currently mapped to
+    ///
+    /// Returns `None` for addresses below $8000.
+    fn prg_rom_offset(&self, addr: u16) -> Option<usize>;
+
+    /// Get the CHR-ROM offset that a PPU address ($0000-$1FFF) is currently mapped to
+    ///
+    /// Returns `None` for cartridges with CHR-RAM.
+    fn chr_rom_offset(&self, addr: u16) -> Option<usize>;
 }
 
-/// Create a mapper instance based on mapper number
+/// Create a mapper instance for a ROM header
+///
+/// PRG-RAM holds both the volatile and the battery-backed PRG-RAM of the
+/// header, and CHR-RAM gets the header's CHR-RAM size.
 pub fn create_mapper(
-    mapper_number: u8,
+    header: &RomHeader,
     prg_rom: Vec<u8>,
     chr_rom: Vec<u8>,
-    mirroring: MirroringMode,
 ) -> io::Result<Box<dyn Mapper>> {
-    match mapper_number {
-        0 => Ok(Box::new(NROMMapper::new(prg_rom, chr_rom, mirroring))),
-        1 => Ok(Box::new(MMC1Mapper::new(prg_rom, chr_rom, mirroring))),
-        2 => Ok(Box::new(UxROMMapper::new(prg_rom, chr_rom, mirroring))),
-        3 => Ok(Box::new(CNROMMapper::new(prg_rom, chr_rom, mirroring))),
-        7 => Ok(Box::new(AxROMMapper::new(prg_rom, chr_rom, mirroring))),
-        _ => Err(io::Error::new(
+    let mirroring = header.mirroring;
+    let prg_ram = header.prg_ram_size + header.prg_nvram_size;
+    let chr_ram = header.chr_ram_size;
+    match header.mapper {
+        0 => Ok(Box::new(
+            NROMMapper::new(prg_rom, chr_rom, mirroring).with_ram(prg_ram, chr_ram),
+        )),
+        1 => Ok(Box::new(
+            MMC1Mapper::new(prg_rom, chr_rom, mirroring).with_ram(prg_ram, chr_ram),
+        )),
+        2 => Ok(Box::new(
+            UxROMMapper::new(prg_rom, chr_rom, mirroring).with_ram(prg_ram, chr_ram),
+        )),
+        3 => Ok(Box::new(
+            CNROMMapper::new(prg_rom, chr_rom, mirroring).with_ram(prg_ram, chr_ram),
+        )),
+        7 => Ok(Box::new(
+            AxROMMapper::new(prg_rom, chr_rom, mirroring).with_ram(prg_ram, chr_ram),
+        )),
+        number => Err(io::Error::new(
             io::ErrorKind::Unsupported,
-            format!("Mapper {} not implemented", mapper_number),
+            format!("Mapper {} not implemented", number),
         )),
     }
 }
@@ -1,5 +1,6 @@
 use crate::cartridge::Mapper;
 use crate::cartridge::MirroringMode;
+use crate::savestate::{SaveState, StateReader, StateWriter};
 
 // Memory size constants
 const CHR_RAM_SIZE: usize = 8192; // 8KB
@@ -73,6 +74,18 @@ impl MMC1Mapper {
         }
     }
 
+    /// Size PRG-RAM and CHR-RAM from the ROM header
+    ///
+    /// CHR-RAM is only resized when the cartridge has no CHR-ROM and the
+    /// header gives a CHR-RAM size.
+    pub fn with_ram(mut self, prg_ram_size: usize, chr_ram_size: usize) -> Self {
+        self.prg_ram = vec![0; prg_ram_size];
+        if self.has_chr_ram && chr_ram_size > 0 {
+            self.chr_memory = vec![0; chr_ram_size];
+        }
+        self
+    }
+
     fn reset_shift_register(&mut self) {
         self.shift_register = 0;
         self.write_count = 0;
@@ -185,6 +198,18 @@ impl MMC1Mapper {
             }
         }
     }
+
+    /// Index into CHR memory for a PPU address
+    fn get_chr_index(&self, addr: u16) -> usize {
+        let offset = if self.get_chr_mode() == 0 {
+            // 8KB mode
+            (addr & 0x1FFF) as usize
+        } else {
+            // 4KB mode
+            (addr & 0x0FFF) as usize
+        };
+        self.get_chr_bank_offset(addr) + offset
+    }
 }
 
 impl Mapper for MMC1Mapper {
@@ -194,18 +219,9 @@ impl Mapper for MMC1Mapper {
                 let offset = (addr - 0x6000) as usize;
                 self.prg_ram.get(offset).copied().unwrap_or(0)
             }
-            0x8000..=0xFFFF => {
-                let bank_offset = self.get_prg_bank_offset(addr);
-                let offset = if self.get_prg_mode() <= 1 {
-                    // 32KB mode
-                    (addr - 0x8000) as usize
-                } else {
-                    // 16KB mode
-                    (addr & 0x3FFF) as usize
-                };
-                let index = bank_offset + offset;
-                self.prg_rom.get(index).copied().unwrap_or(0)
-            }
+            0x8000..=0xFFFF => self
+                .prg_rom_offset(addr)
+                .map_or(0, |index| self.prg_rom[index]),
             _ => 0,
         }
     }
@@ -226,15 +242,7 @@ impl Mapper for MMC1Mapper {
     }
 
     fn read_chr(&self, addr: u16) -> u8 {
-        let bank_offset = self.get_chr_bank_offset(addr);
-        let offset = if self.get_chr_mode() == 0 {
-            // 8KB mode
-            (addr & 0x1FFF) as usize
-        } else {
-            // 4KB mode
-            (addr & 0x0FFF) as usize
-        };
-        let index = bank_offset + offset;
+        let index = self.get_chr_index(addr);
         self.chr_memory.get(index).copied().unwrap_or(0)
     }
 
@@ -243,15 +251,7 @@ impl Mapper for MMC1Mapper {
             return; // CHR ROM is read-only
         }
 
-        let bank_offset = self.get_chr_bank_offset(addr);
-        let offset = if self.get_chr_mode() == 0 {
-            // 8KB mode
-            (addr & 0x1FFF) as usize
-        } else {
-            // 4KB mode
-            (addr & 0x0FFF) as usize
-        };
-        let index = bank_offset + offset;
+        let index = self.get_chr_index(addr);
         if index < self.chr_memory.len() {
             self.chr_memory[index] = value;
         }
@@ -264,11 +264,73 @@ impl Mapper for MMC1Mapper {
     fn get_mirroring(&self) -> MirroringMode {
         self.get_mirroring_mode()
     }
+
+    fn prg_rom(&self) -> &[u8] {
+        &self.prg_rom
+    }
+
+    fn prg_rom_offset(&self, addr: u16) -> Option<usize> {
+        if addr < 0x8000 {
+            return None;
+        }
+        let offset = if self.get_prg_mode() <= 1 {
+            // 32KB mode
+            (addr - 0x8000) as usize
+        } else {
+            // 16KB mode
+            (addr & 0x3FFF) as usize
+        };
+        let index = self.get_prg_bank_offset(addr) + offset;
+        (index < self.prg_rom.len()).then_some(index)
+    }
+
+    fn chr_rom_offset(&self, addr: u16) -> Option<usize> {
+        let index = self.get_chr_index(addr);
+        (!self.has_chr_ram && index < self.chr_memory.len()).then_some(index)
+    }
+}
+
+impl SaveState for MMC1Mapper {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.prg_ram);
+        if self.has_chr_ram {
+            state.write_bytes(&self.chr_memory);
+        }
+        for register in [
+            self.shift_register,
+            self.write_count,
+            self.control,
+            self.chr_bank_0,
+            self.chr_bank_1,
+            self.prg_bank,
+        ] {
+            state.write_u8(register);
+        }
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.read_bytes_into(&mut self.prg_ram)?;
+        if self.has_chr_ram {
+            state.read_bytes_into(&mut self.chr_memory)?;
+        }
+        for register in [
+            &mut self.shift_register,
+            &mut self.write_count,
+            &mut self.control,
+            &mut self.chr_bank_0,
+            &mut self.chr_bank_1,
+            &mut self.prg_bank,
+        ] {
+            *register = state.read_u8()?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cartridge::header::RomHeader;
     use crate::cartridge::mapper::create_mapper;
 
     #[test]
@@ -279,8 +341,12 @@ mod tests {
 
         let prg_rom = vec![0; 128 * 1024]; // 128KB = 8 banks of 16KB
         let chr_rom = vec![0; 32 * 1024]; // 32KB = 8 banks of 4KB
-        let mut mapper = create_mapper(1, prg_rom, chr_rom, MirroringMode::Horizontal)
-            .expect("Failed to create MMC1 mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(1, prg_rom.len(), chr_rom.len(), MirroringMode::Horizontal),
+            prg_rom,
+            chr_rom,
+        )
+        .expect("Failed to create MMC1 mapper");
 
         // Load value 0b00011 (3) into control register at $8000-$9FFF
         // This requires 5 writes, each with bit 0 containing the next bit of the value
@@ -302,8 +368,12 @@ mod tests {
         // Writing with bit 7 set should reset the shift register
         let prg_rom = vec![0; 256 * 1024];
         let chr_rom = vec![0; 128 * 1024];
-        let mut mapper = create_mapper(1, prg_rom, chr_rom, MirroringMode::Horizontal)
-            .expect("Failed to create MMC1 mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(1, prg_rom.len(), chr_rom.len(), MirroringMode::Horizontal),
+            prg_rom,
+            chr_rom,
+        )
+        .expect("Failed to create MMC1 mapper");
 
         // Start loading a value
         mapper.write_prg(0x8000, 0b00000001);
@@ -330,8 +400,12 @@ mod tests {
         // 3: horizontal
         let prg_rom = vec![0; 256 * 1024];
         let chr_rom = vec![0; 128 * 1024];
-        let mut mapper = create_mapper(1, prg_rom, chr_rom, MirroringMode::Horizontal)
-            .expect("Failed to create MMC1 mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(1, prg_rom.len(), chr_rom.len(), MirroringMode::Horizontal),
+            prg_rom,
+            chr_rom,
+        )
+        .expect("Failed to create MMC1 mapper");
 
         // Load 0b00000 (mirroring = 0)
         for _ in 0..5 {
@@ -378,8 +452,12 @@ mod tests {
         }
 
         let chr_rom = vec![0; 8 * 1024];
-        let mut mapper = create_mapper(1, prg_rom, chr_rom, MirroringMode::Horizontal)
-            .expect("Failed to create MMC1 mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(1, prg_rom.len(), chr_rom.len(), MirroringMode::Horizontal),
+            prg_rom,
+            chr_rom,
+        )
+        .expect("Failed to create MMC1 mapper");
 
         // Set control register to PRG mode 0 (bits 2-3 = 0b00) and mirroring
         // Value: 0b00000 (mirroring=0, prg_mode=0, chr_mode=0)
@@ -420,8 +498,12 @@ mod tests {
         }
 
         let chr_rom = vec![0; 8 * 1024];
-        let mut mapper = create_mapper(1, prg_rom, chr_rom, MirroringMode::Horizontal)
-            .expect("Failed to create MMC1 mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(1, prg_rom.len(), chr_rom.len(), MirroringMode::Horizontal),
+            prg_rom,
+            chr_rom,
+        )
+        .expect("Failed to create MMC1 mapper");
 
         // Set control register to PRG mode 2 (bits 2-3 = 0b10)
         // Value: 0b01000 (mirroring=0, prg_mode=2, chr_mode=0)
@@ -459,8 +541,12 @@ mod tests {
         }
 
         let chr_rom = vec![0; 8 * 1024];
-        let mut mapper = create_mapper(1, prg_rom, chr_rom, MirroringMode::Horizontal)
-            .expect("Failed to create MMC1 mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(1, prg_rom.len(), chr_rom.len(), MirroringMode::Horizontal),
+            prg_rom,
+            chr_rom,
+        )
+        .expect("Failed to create MMC1 mapper");
 
         // Set control register to PRG mode 3 (bits 2-3 = 0b11) - this is the default
         // Value: 0b01100 (mirroring=0, prg_mode=3, chr_mode=0)
@@ -498,8 +584,12 @@ mod tests {
         }
 
         let prg_rom = vec![0; 32 * 1024];
-        let mut mapper = create_mapper(1, prg_rom, chr_rom, MirroringMode::Horizontal)
-            .expect("Failed to create MMC1 mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(1, prg_rom.len(), chr_rom.len(), MirroringMode::Horizontal),
+            prg_rom,
+            chr_rom,
+        )
+        .expect("Failed to create MMC1 mapper");
 
         // Set control register to CHR mode 0 (bit 4 = 0)
         // Value: 0b00000 (mirroring=0, prg_mode=0, chr_mode=0)
@@ -535,8 +625,12 @@ mod tests {
         }
 
         let prg_rom = vec![0; 32 * 1024];
-        let mut mapper = create_mapper(1, prg_rom, chr_rom, MirroringMode::Horizontal)
-            .expect("Failed to create MMC1 mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(1, prg_rom.len(), chr_rom.len(), MirroringMode::Horizontal),
+            prg_rom,
+            chr_rom,
+        )
+        .expect("Failed to create MMC1 mapper");
 
         // Set control register to CHR mode 1 (bit 4 = 1)
         // Value: 0b10000 (mirroring=0, prg_mode=0, chr_mode=1)
@@ -570,8 +664,12 @@ mod tests {
         // MMC1 should support 8KB PRG-RAM at $6000-$7FFF
         let prg_rom = vec![0; 128 * 1024];
         let chr_rom = vec![0; 8 * 1024];
-        let mut mapper = create_mapper(1, prg_rom, chr_rom, MirroringMode::Horizontal)
-            .expect("Failed to create MMC1 mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(1, prg_rom.len(), chr_rom.len(), MirroringMode::Horizontal),
+            prg_rom,
+            chr_rom,
+        )
+        .expect("Failed to create MMC1 mapper");
 
         // Write to PRG-RAM
         mapper.write_prg(0x6000, 0xAA);
@@ -588,8 +686,12 @@ mod tests {
     fn test_mmc1_chr_ram_when_no_chr_rom() {
         // If CHR ROM is empty, MMC1 should use CHR-RAM
         let prg_rom = vec![0; 128 * 1024];
-        let mut mapper = create_mapper(1, prg_rom, vec![], MirroringMode::Horizontal)
-            .expect("Failed to create MMC1 mapper");
+        let mut mapper = create_mapper(
+            &RomHeader::ines(1, prg_rom.len(), 0, MirroringMode::Horizontal),
+            prg_rom,
+            vec![],
+        )
+        .expect("Failed to create MMC1 mapper");
 
         // Initially should read 0
         assert_eq!(mapper.read_chr(0x0000), 0x00);
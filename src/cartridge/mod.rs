@@ -1,10 +1,15 @@
 mod axrom;
 mod cartridge;
 mod cnrom;
+mod database;
+mod header;
+mod loader;
 mod mapper;
 mod mmc1;
 mod nrom;
 mod uxrom;
 
 pub use cartridge::{Cartridge, MirroringMode};
+pub use database::import_nes20db;
+pub use loader::load_rom;
 pub use mapper::Mapper;
@@ -1,5 +1,6 @@
 use crate::cartridge::Mapper;
 use crate::cartridge::MirroringMode;
+use crate::savestate::{SaveState, StateReader, StateWriter};
 
 // Memory size constants
 const CHR_RAM_SIZE: usize = 8192; // 8KB
@@ -44,6 +45,18 @@ impl NROMMapper {
             has_chr_ram,
         }
     }
+
+    /// Size PRG-RAM and CHR-RAM from the ROM header
+    ///
+    /// CHR-RAM is only resized when the cartridge has no CHR-ROM and the
+    /// header gives a CHR-RAM size.
+    pub fn with_ram(mut self, prg_ram_size: usize, chr_ram_size: usize) -> Self {
+        self.prg_ram = vec![0; prg_ram_size];
+        if self.has_chr_ram && chr_ram_size > 0 {
+            self.chr_memory = vec![0; chr_ram_size];
+        }
+        self
+    }
 }
 
 impl Mapper for NROMMapper {
@@ -55,21 +68,9 @@ impl Mapper for NROMMapper {
                 self.prg_ram.get(offset).copied().unwrap_or(0)
             }
             // PRG ROM at $8000-$FFFF
-            0x8000..=0xFFFF => {
-                let offset = (addr - 0x8000) as usize;
-
-                // Handle 16KB vs 32KB PRG ROM
-                if self.prg_rom.len() == PRG_BANK_SIZE {
-                    // 16KB ROM: mirror at $C000
-                    // $8000-$BFFF maps to ROM, $C000-$FFFF mirrors to same ROM
-                    let index = offset % PRG_BANK_SIZE;
-                    self.prg_rom.get(index).copied().unwrap_or(0)
-                } else {
-                    // 32KB or larger ROM: direct mapping
-                    let index = offset % self.prg_rom.len();
-                    self.prg_rom.get(index).copied().unwrap_or(0)
-                }
-            }
+            0x8000..=0xFFFF => self
+                .prg_rom_offset(addr)
+                .map_or(0, |index| self.prg_rom[index]),
             _ => 0,
         }
     }
@@ -112,6 +113,47 @@ impl Mapper for NROMMapper {
     fn get_mirroring(&self) -> MirroringMode {
         self.mirroring
     }
+
+    fn prg_rom(&self) -> &[u8] {
+        &self.prg_rom
+    }
+
+    fn prg_rom_offset(&self, addr: u16) -> Option<usize> {
+        if addr < 0x8000 || self.prg_rom.is_empty() {
+            return None;
+        }
+        let offset = (addr - 0x8000) as usize;
+        if self.prg_rom.len() == PRG_BANK_SIZE {
+            // 16KB ROM: $C000-$FFFF mirrors $8000-$BFFF
+            Some(offset % PRG_BANK_SIZE)
+        } else {
+            // 32KB ROM: direct mapping
+            Some(offset % self.prg_rom.len())
+        }
+    }
+
+    fn chr_rom_offset(&self, addr: u16) -> Option<usize> {
+        let index = (addr & CHR_MASK) as usize;
+        (!self.has_chr_ram && index < self.chr_memory.len()).then_some(index)
+    }
+}
+
+/// ROM contents are not part of the state, only RAM
+impl SaveState for NROMMapper {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.prg_ram);
+        if self.has_chr_ram {
+            state.write_bytes(&self.chr_memory);
+        }
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.read_bytes_into(&mut self.prg_ram)?;
+        if self.has_chr_ram {
+            state.read_bytes_into(&mut self.chr_memory)?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -1,5 +1,6 @@
 use crate::cartridge::Mapper;
 use crate::cartridge::MirroringMode;
+use crate::savestate::{SaveState, StateReader, StateWriter};
 
 // Memory size constants
 const CHR_RAM_SIZE: usize = 8192; // 8KB
@@ -38,6 +39,17 @@ impl UxROMMapper {
         }
     }
 
+    /// Size PRG-RAM and CHR-RAM from the ROM header
+    ///
+    /// CHR-RAM is only resized when the header gives a CHR-RAM size.
+    pub fn with_ram(mut self, prg_ram_size: usize, chr_ram_size: usize) -> Self {
+        self.prg_ram = vec![0; prg_ram_size];
+        if chr_ram_size > 0 {
+            self.chr_ram = vec![0; chr_ram_size];
+        }
+        self
+    }
+
     fn get_last_bank_offset(&self) -> usize {
         self.prg_rom.len().saturating_sub(PRG_BANK_SIZE)
     }
@@ -52,21 +64,9 @@ impl Mapper for UxROMMapper {
                 self.prg_ram.get(offset).copied().unwrap_or(0)
             }
             // PRG ROM at $8000-$FFFF
-            0x8000..=0xFFFF => {
-                let offset = (addr - 0x8000) as usize;
-
-                if addr < 0xC000 {
-                    // $8000-$BFFF: Switchable 16KB bank
-                    let bank_offset = (self.bank_select as usize) * PRG_BANK_SIZE;
-                    let index = bank_offset + offset;
-                    self.prg_rom.get(index).copied().unwrap_or(0)
-                } else {
-                    // $C000-$FFFF: Fixed to last 16KB bank
-                    let last_bank_offset = self.get_last_bank_offset();
-                    let index = last_bank_offset + (offset - PRG_BANK_SIZE);
-                    self.prg_rom.get(index).copied().unwrap_or(0)
-                }
-            }
+            0x8000..=0xFFFF => self
+                .prg_rom_offset(addr)
+                .map_or(0, |index| self.prg_rom[index]),
             _ => 0,
         }
     }
@@ -107,6 +107,43 @@ impl Mapper for UxROMMapper {
     fn get_mirroring(&self) -> MirroringMode {
         self.mirroring
     }
+
+    fn prg_rom(&self) -> &[u8] {
+        &self.prg_rom
+    }
+
+    fn prg_rom_offset(&self, addr: u16) -> Option<usize> {
+        let index = match addr {
+            // $8000-$BFFF: Switchable 16KB bank
+            0x8000..=0xBFFF => {
+                (self.bank_select as usize) * PRG_BANK_SIZE + (addr - 0x8000) as usize
+            }
+            // $C000-$FFFF: Fixed to last 16KB bank
+            0xC000..=0xFFFF => self.get_last_bank_offset() + (addr - 0xC000) as usize,
+            _ => return None,
+        };
+        (index < self.prg_rom.len()).then_some(index)
+    }
+
+    fn chr_rom_offset(&self, _addr: u16) -> Option<usize> {
+        // UxROM uses CHR-RAM
+        None
+    }
+}
+
+impl SaveState for UxROMMapper {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.prg_ram);
+        state.write_bytes(&self.chr_ram);
+        state.write_u8(self.bank_select);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.read_bytes_into(&mut self.prg_ram)?;
+        state.read_bytes_into(&mut self.chr_ram)?;
+        self.bank_select = state.read_u8()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
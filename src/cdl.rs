@@ -0,0 +1,221 @@
+//! Code/Data Logger
+//!
+//! Records how every PRG-ROM and CHR-ROM byte was used while the game runs,
+//! for separating code from data when disassembling. The flags and file layout
+//! match FCEUX `.cdl` files: one flag byte per PRG-ROM byte, followed by one
+//! per CHR-ROM byte.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// PRG byte was executed as part of an instruction
+pub const CODE: u8 = 0x01;
+/// PRG byte was read as data
+pub const DATA: u8 = 0x02;
+/// Mask of the bits holding the 8 KiB CPU window ($8000/$A000/$C000/$E000) the
+/// byte was last accessed through
+pub const CPU_WINDOW_MASK: u8 = 0x0C;
+/// PRG byte is the target of an indirect jump
+pub const INDIRECT_CODE: u8 = 0x10;
+/// PRG byte was read through an indirect addressing mode
+pub const INDIRECT_DATA: u8 = 0x20;
+/// PRG byte was fetched by the DMC as sample data
+pub const PCM: u8 = 0x40;
+
+/// CHR byte was fetched by the PPU for rendering
+pub const DRAWN: u8 = 0x01;
+/// CHR byte was read through PPUDATA ($2007)
+pub const READ: u8 = 0x02;
+
+/// Per-byte usage flags for PRG-ROM and CHR-ROM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeDataLog {
+    prg: Vec<u8>,
+    chr: Vec<u8>,
+}
+
+impl CodeDataLog {
+    /// Create an empty log for a ROM with these PRG-ROM and CHR-ROM sizes
+    pub fn new(prg_size: usize, chr_size: usize) -> Self {
+        Self {
+            prg: vec![0; prg_size],
+            chr: vec![0; chr_size],
+        }
+    }
+
+    /// Load a `.cdl` file to continue logging
+    ///
+    /// Returns an error if the file size does not match the ROM.
+    pub fn load(path: &Path, prg_size: usize, chr_size: usize) -> io::Result<Self> {
+        let mut data = fs::read(path)?;
+        if data.len() != prg_size + chr_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "CDL file has {} bytes, expected {} for {} KiB PRG-ROM and {} KiB CHR-ROM",
+                    data.len(),
+                    prg_size + chr_size,
+                    prg_size / 1024,
+                    chr_size / 1024
+                ),
+            ));
+        }
+        let chr = data.split_off(prg_size);
+        Ok(Self { prg: data, chr })
+    }
+
+    /// Write the log as a `.cdl` file
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, [self.prg.as_slice(), self.chr.as_slice()].concat())
+    }
+
+    /// Flags for each PRG-ROM byte
+    #[cfg(test)]
+    pub fn prg(&self) -> &[u8] {
+        &self.prg
+    }
+
+    /// Flags for each CHR-ROM byte
+    #[cfg(test)]
+    pub fn chr(&self) -> &[u8] {
+        &self.chr
+    }
+
+    /// Add flags to a PRG-ROM byte accessed through CPU address `addr`
+    pub fn mark_prg(&mut self, offset: usize, addr: u16, flags: u8) {
+        if let Some(byte) = self.prg.get_mut(offset) {
+            let window = (((addr >> 13) & 0x03) as u8) << 2;
+            *byte = (*byte & !CPU_WINDOW_MASK) | window | flags;
+        }
+    }
+
+    /// Add flags to a CHR-ROM byte
+    pub fn mark_chr(&mut self, offset: usize, flags: u8) {
+        if let Some(byte) = self.chr.get_mut(offset) {
+            *byte |= flags;
+        }
+    }
+
+    /// Count logged bytes by kind
+    pub fn coverage(&self) -> Coverage {
+        let count = |bytes: &[u8], flags: u8| bytes.iter().filter(|&&b| b & flags != 0).count();
+        Coverage {
+            prg_size: self.prg.len(),
+            code: count(&self.prg, CODE),
+            data: count(&self.prg, DATA),
+            pcm: count(&self.prg, PCM),
+            prg_unused: self
+                .prg
+                .iter()
+                .filter(|&&b| b & (CODE | DATA | PCM) == 0)
+                .count(),
+            chr_size: self.chr.len(),
+            drawn: count(&self.chr, DRAWN),
+            read: count(&self.chr, READ),
+            chr_unused: self
+                .chr
+                .iter()
+                .filter(|&&b| b & (DRAWN | READ) == 0)
+                .count(),
+        }
+    }
+}
+
+/// Byte counts of a `CodeDataLog`
+///
+/// A byte can count as more than one kind, such as code that is also read as data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coverage {
+    pub prg_size: usize,
+    pub code: usize,
+    pub data: usize,
+    pub pcm: usize,
+    pub prg_unused: usize,
+    pub chr_size: usize,
+    pub drawn: usize,
+    pub read: usize,
+    pub chr_unused: usize,
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = |count: usize, total: usize| {
+            if total == 0 {
+                0.0
+            } else {
+                count as f64 * 100.0 / total as f64
+            }
+        };
+        write!(
+            f,
+            "PRG: {} code ({:.1}%), {} data ({:.1}%), {} PCM ({:.1}%), {} unused ({:.1}%)",
+            self.code,
+            percent(self.code, self.prg_size),
+            self.data,
+            percent(self.data, self.prg_size),
+            self.pcm,
+            percent(self.pcm, self.prg_size),
+            self.prg_unused,
+            percent(self.prg_unused, self.prg_size)
+        )?;
+        if self.chr_size > 0 {
+            write!(
+                f,
+                "\nCHR: {} drawn ({:.1}%), {} read ({:.1}%), {} unused ({:.1}%)",
+                self.drawn,
+                percent(self.drawn, self.chr_size),
+                self.read,
+                percent(self.read, self.chr_size),
+                self.chr_unused,
+                percent(self.chr_unused, self.chr_size)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mark_records_flags_and_cpu_window() {
+        let mut cdl = CodeDataLog::new(0x8000, 0x2000);
+        cdl.mark_prg(0x10, 0xC010, CODE);
+        cdl.mark_prg(0x10, 0xC010, DATA);
+        cdl.mark_prg(0x20, 0xE020, DATA | INDIRECT_DATA);
+        cdl.mark_chr(0x100, DRAWN);
+        // Out of range offsets are ignored
+        cdl.mark_prg(0x8000, 0x8000, CODE);
+
+        assert_eq!(cdl.prg()[0x10], CODE | DATA | 0x08);
+        assert_eq!(cdl.prg()[0x20], DATA | INDIRECT_DATA | 0x0C);
+        assert_eq!(cdl.chr()[0x100], DRAWN);
+
+        let coverage = cdl.coverage();
+        assert_eq!((coverage.code, coverage.data, coverage.pcm), (1, 2, 0));
+        assert_eq!(coverage.prg_unused, 0x8000 - 2);
+        assert_eq!(coverage.drawn, 1);
+        assert!(
+            coverage
+                .to_string()
+                .starts_with("PRG: 1 code (0.0%), 2 data")
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join("neser_test_cdl.cdl");
+        let mut cdl = CodeDataLog::new(0x4000, 0x2000);
+        cdl.mark_prg(0x3FFF, 0xFFFF, CODE);
+        cdl.mark_chr(0, READ);
+        cdl.save(&path).unwrap();
+
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0x6000);
+        assert_eq!(CodeDataLog::load(&path, 0x4000, 0x2000).unwrap(), cdl);
+        assert!(CodeDataLog::load(&path, 0x8000, 0x2000).is_err());
+        fs::remove_file(&path).ok();
+    }
+}
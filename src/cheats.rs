@@ -0,0 +1,439 @@
+//! Cheat codes
+//!
+//! Cheats change what the game sees in one of two ways:
+//!
+//! - ROM codes replace a byte as the CPU reads it from PRG-ROM ($8000-$FFFF),
+//!   like a Game Genie between the cartridge and the console. An optional
+//!   compare value makes the code apply only while the ROM holds that byte, so
+//!   it leaves other banks mapped at the same address alone.
+//! - RAM codes write a byte into CPU RAM ($0000-$1FFF) or PRG-RAM
+//!   ($6000-$7FFF) every frame, like a Pro Action Replay.
+//!
+//! Codes are given as 6- or 8-letter Game Genie codes (`SXIOPO`,
+//! `ZEXPYGLA`), as 6-digit Pro Action Replay codes of address and value
+//! (`075A09`), or raw as `ADDR:VAL` or `ADDR?CMP:VAL` in hex (`075A:09`,
+//! `94A7?03:02`). A code made only of Game Genie letters is a Game Genie code.
+//!
+//! Cheat lists are text files with a cheat per line: the code, then its name.
+//! A `-` before the code turns the cheat off, and `#` starts a comment:
+//!
+//! ```text
+//! # Super Mario Bros.
+//! SXIOPO   Infinite lives
+//! -075A:09 Nine lives
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Game Genie letters, for the values 0 to 15
+const GAME_GENIE_LETTERS: &[u8; 16] = b"APZLGITYEOXUKSVN";
+
+/// What a cheat changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Code {
+    /// Replace the PRG-ROM byte at `addr` with `value` as the CPU reads it, if
+    /// it is `compare`
+    Rom {
+        addr: u16,
+        value: u8,
+        compare: Option<u8>,
+    },
+    /// Write `value` to RAM at `addr` every frame
+    Ram { addr: u16, value: u8 },
+}
+
+impl Code {
+    /// Parse a Game Genie, Pro Action Replay or raw code
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the code is none of these, or a raw code's address
+    /// is neither RAM nor ROM.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let upper = text.to_ascii_uppercase();
+        if matches!(upper.len(), 6 | 8)
+            && upper
+                .bytes()
+                .all(|letter| GAME_GENIE_LETTERS.contains(&letter))
+        {
+            return decode_game_genie(&upper);
+        }
+        if upper.len() == 6 && upper.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+            let addr = hex(&upper[..4], text)?;
+            return Self::raw(addr, hex(&upper[4..], text)? as u8, None, text);
+        }
+
+        let (target, value) = upper
+            .split_once(':')
+            .ok_or_else(|| format!("\"{}\" is not a cheat code", text))?;
+        let (addr, compare) = match target.split_once('?') {
+            Some((addr, compare)) => (addr, Some(hex(compare, text)? as u8)),
+            None => (target, None),
+        };
+        Self::raw(hex(addr, text)?, hex(value, text)? as u8, compare, text)
+    }
+
+    fn raw(addr: u16, value: u8, compare: Option<u8>, text: &str) -> Result<Self, String> {
+        match addr {
+            0x8000..=0xFFFF => Ok(Code::Rom {
+                addr,
+                value,
+                compare,
+            }),
+            _ if compare.is_some() => Err(format!(
+                "Cheat \"{}\" compares a byte outside ROM ($8000-$FFFF)",
+                text
+            )),
+            0x0000..=0x1FFF | 0x6000..=0x7FFF => Ok(Code::Ram { addr, value }),
+            _ => Err(format!(
+                "Cheat \"{}\" writes ${:04X}, which is neither RAM nor ROM",
+                text, addr
+            )),
+        }
+    }
+}
+
+/// Parse a hex number of up to four digits from `code`
+fn hex(digits: &str, code: &str) -> Result<u16, String> {
+    let digits = digits.strip_prefix('$').unwrap_or(digits);
+    if digits.is_empty() || digits.len() > 4 {
+        return Err(format!("\"{}\" is not a cheat code", code));
+    }
+    u16::from_str_radix(digits, 16).map_err(|_| format!("\"{}\" is not a cheat code", code))
+}
+
+/// Decode a 6- or 8-letter Game Genie code
+///
+/// The letters hold the address, value and (with 8 letters) compare value
+/// with their bits shuffled.
+///
+/// # Errors
+///
+/// Returns an error if the code has another length or a letter the Game
+/// Genie does not use.
+pub fn decode_game_genie(code: &str) -> Result<Code, String> {
+    let n: Vec<u16> = code
+        .bytes()
+        .map(|letter| {
+            GAME_GENIE_LETTERS
+                .iter()
+                .position(|&l| l == letter.to_ascii_uppercase())
+                .map(|value| value as u16)
+        })
+        .collect::<Option<_>>()
+        .ok_or_else(|| format!("\"{}\" is not a Game Genie code", code))?;
+    if !matches!(n.len(), 6 | 8) {
+        return Err(format!(
+            "Game Genie codes have 6 or 8 letters, \"{}\" has {}",
+            code,
+            n.len()
+        ));
+    }
+
+    let addr = 0x8000
+        | ((n[3] & 7) << 12)
+        | ((n[5] & 7) << 8)
+        | ((n[4] & 8) << 8)
+        | ((n[2] & 7) << 4)
+        | ((n[1] & 8) << 4)
+        | (n[4] & 7)
+        | (n[3] & 8);
+    // The last letter holds the high bit of the value's low nibble
+    let last = n[n.len() - 1];
+    let value = ((n[1] & 7) << 4) | ((n[0] & 8) << 4) | (n[0] & 7) | (last & 8);
+    let compare =
+        (n.len() == 8).then(|| ((n[7] & 7) << 4) | ((n[6] & 8) << 4) | (n[6] & 7) | (n[5] & 8));
+    Ok(Code::Rom {
+        addr,
+        value: value as u8,
+        compare: compare.map(|compare| compare as u8),
+    })
+}
+
+/// A named code that can be turned on and off
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cheat {
+    pub name: String,
+    /// The code as given, e.g. `SXIOPO`
+    pub text: String,
+    pub code: Code,
+    pub enabled: bool,
+}
+
+/// The cheats of a game
+///
+/// Besides turning single cheats on and off, the whole list can be switched
+/// off and back on without losing which cheats were on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheatList {
+    cheats: Vec<Cheat>,
+    active: bool,
+    /// ROM codes of enabled cheats, while active
+    rom_codes: Vec<(u16, u8, Option<u8>)>,
+    /// RAM codes of enabled cheats, while active
+    ram_codes: Vec<(u16, u8)>,
+}
+
+impl Default for CheatList {
+    fn default() -> Self {
+        Self {
+            cheats: Vec::new(),
+            active: true,
+            rom_codes: Vec::new(),
+            ram_codes: Vec::new(),
+        }
+    }
+}
+
+impl CheatList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Path of the cheat list for a game
+    ///
+    /// Lists live next to the config file in `cheats/<CRC32>.txt`, keyed like
+    /// the game's settings.
+    pub fn path(config_path: &Path, crc32: u32) -> PathBuf {
+        config_path
+            .with_file_name("cheats")
+            .join(format!("{:08X}.txt", crc32))
+    }
+
+    /// Load a cheat list file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read cheats \"{}\": {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("Cannot load cheats \"{}\": {}", path.display(), e))
+    }
+
+    /// Parse the text of a cheat list file
+    ///
+    /// # Errors
+    ///
+    /// Returns the first line that does not hold a valid code.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut list = Self::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (code, name) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let (code, enabled) = match code.strip_prefix('-') {
+                Some(code) => (code, false),
+                None => (code, true),
+            };
+            let index = list
+                .add(name.trim(), code)
+                .map_err(|e| format!("line {}: {}", number + 1, e))?;
+            list.set_enabled(index, enabled);
+        }
+        Ok(list)
+    }
+
+    /// Add an enabled cheat and return its index
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `code` is not a valid code.
+    pub fn add(&mut self, name: &str, code: &str) -> Result<usize, String> {
+        self.cheats.push(Cheat {
+            name: name.to_string(),
+            text: code.trim().to_string(),
+            code: Code::parse(code)?,
+            enabled: true,
+        });
+        self.update();
+        Ok(self.cheats.len() - 1)
+    }
+
+    /// Turn the cheat at `index` on or off
+    ///
+    /// Returns `false` if there is no such cheat.
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) -> bool {
+        let Some(cheat) = self.cheats.get_mut(index) else {
+            return false;
+        };
+        cheat.enabled = enabled;
+        self.update();
+        true
+    }
+
+    /// Whether the enabled cheats apply
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Switch all enabled cheats on or off at once
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+        self.update();
+    }
+
+    pub fn cheats(&self) -> &[Cheat] {
+        &self.cheats
+    }
+
+    /// The byte the CPU reads at `addr` in PRG-ROM, where the ROM holds `value`
+    pub fn patch_rom_read(&self, addr: u16, value: u8) -> u8 {
+        for &(code_addr, code_value, compare) in &self.rom_codes {
+            if code_addr == addr && compare.is_none_or(|compare| compare == value) {
+                return code_value;
+            }
+        }
+        value
+    }
+
+    /// Addresses and values RAM codes write every frame
+    pub fn ram_writes(&self) -> &[(u16, u8)] {
+        &self.ram_codes
+    }
+
+    /// Gather the codes that apply, so reads need not look at every cheat
+    fn update(&mut self) {
+        self.rom_codes.clear();
+        self.ram_codes.clear();
+        if !self.active {
+            return;
+        }
+        for cheat in self.cheats.iter().filter(|cheat| cheat.enabled) {
+            match cheat.code {
+                Code::Rom {
+                    addr,
+                    value,
+                    compare,
+                } => self.rom_codes.push((addr, value, compare)),
+                Code::Ram { addr, value } => self.ram_codes.push((addr, value)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rom(addr: u16, value: u8, compare: Option<u8>) -> Code {
+        Code::Rom {
+            addr,
+            value,
+            compare,
+        }
+    }
+
+    #[test]
+    fn test_decode_six_letter_game_genie_codes() {
+        // Super Mario Bros. infinite lives: DEC $075A becomes LDA $075A
+        assert_eq!(decode_game_genie("SXIOPO"), Ok(rom(0x91D9, 0xAD, None)));
+        assert_eq!(decode_game_genie("GOSSIP"), Ok(rom(0xD1DD, 0x14, None)));
+        assert_eq!(decode_game_genie("AATOZE"), Ok(rom(0x906A, 0x08, None)));
+        assert_eq!(decode_game_genie("aaaaaa"), Ok(rom(0x8000, 0x00, None)));
+    }
+
+    #[test]
+    fn test_decode_eight_letter_game_genie_codes() {
+        assert_eq!(
+            decode_game_genie("ZEXPYGLA"),
+            Ok(rom(0x94A7, 0x02, Some(0x03)))
+        );
+        assert_eq!(
+            decode_game_genie("SLXPLOVS"),
+            Ok(rom(0x9123, 0xBD, Some(0xDE)))
+        );
+    }
+
+    #[test]
+    fn test_invalid_game_genie_codes() {
+        assert!(decode_game_genie("SXIOP").is_err());
+        assert!(decode_game_genie("SXIOPOAAA").is_err());
+        assert!(decode_game_genie("SXIOPB").is_err());
+    }
+
+    #[test]
+    fn test_parse_raw_and_pro_action_replay_codes() {
+        assert_eq!(
+            Code::parse("075A09"),
+            Ok(Code::Ram {
+                addr: 0x075A,
+                value: 0x09
+            })
+        );
+        assert_eq!(
+            Code::parse("$6000:FF"),
+            Ok(Code::Ram {
+                addr: 0x6000,
+                value: 0xFF
+            })
+        );
+        assert_eq!(Code::parse("94a7?03:02"), Ok(rom(0x94A7, 0x02, Some(0x03))));
+        assert_eq!(Code::parse("C000:EA"), Ok(rom(0xC000, 0xEA, None)));
+        assert_eq!(Code::parse(" sxiopo "), Ok(rom(0x91D9, 0xAD, None)));
+
+        assert!(Code::parse("2000:00").is_err());
+        assert!(Code::parse("0300?01:02").is_err());
+        assert!(Code::parse("12345:00").is_err());
+        assert!(Code::parse("075A").is_err());
+        assert!(Code::parse("G0:00").is_err());
+    }
+
+    #[test]
+    fn test_parse_cheat_list() {
+        let list = CheatList::parse(
+            "# Super Mario Bros.\n\
+             SXIOPO   Infinite lives\n\
+             \n\
+             -075A:09 Nine lives # for later\n\
+             C000:EA\n",
+        )
+        .unwrap();
+        let summary: Vec<_> = list
+            .cheats()
+            .iter()
+            .map(|cheat| (cheat.name.as_str(), cheat.text.as_str(), cheat.enabled))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Infinite lives", "SXIOPO", true),
+                ("Nine lives", "075A:09", false),
+                ("", "C000:EA", true),
+            ]
+        );
+
+        let error = CheatList::parse("SXIOPO\nXYZ Broken\n").unwrap_err();
+        assert!(error.starts_with("line 2:"), "{}", error);
+    }
+
+    #[test]
+    fn test_only_enabled_cheats_apply() {
+        let mut list = CheatList::new();
+        let lives = list.add("Lives", "SXIOPO").unwrap();
+        list.add("Compare", "ZEXPYGLA").unwrap();
+        let poke = list.add("Poke", "075A:09").unwrap();
+
+        assert_eq!(list.patch_rom_read(0x91D9, 0xCE), 0xAD);
+        assert_eq!(list.patch_rom_read(0x91DA, 0x5A), 0x5A);
+        // The 8-letter code only hits where the ROM holds its compare value
+        assert_eq!(list.patch_rom_read(0x94A7, 0x03), 0x02);
+        assert_eq!(list.patch_rom_read(0x94A7, 0x04), 0x04);
+        assert_eq!(list.ram_writes(), [(0x075A, 0x09)]);
+
+        assert!(list.set_enabled(lives, false));
+        assert_eq!(list.patch_rom_read(0x91D9, 0xCE), 0xCE);
+        assert!(!list.set_enabled(9, false));
+
+        list.set_active(false);
+        assert_eq!(list.patch_rom_read(0x94A7, 0x03), 0x03);
+        assert!(list.ram_writes().is_empty());
+        list.set_active(true);
+        assert_eq!(list.ram_writes(), [(0x075A, 0x09)]);
+        // Switching the list back on keeps the cheat that was off, off
+        assert_eq!(list.patch_rom_read(0x91D9, 0xCE), 0xCE);
+
+        assert!(list.set_enabled(poke, false));
+        assert!(list.ram_writes().is_empty());
+    }
+}
@@ -0,0 +1,939 @@
+use crate::headless::StopCondition;
+use crate::nes::TvSystem;
+use crate::netplay::NetplaySession;
+use crate::runahead::RunAhead;
+use crate::trace::{FlagsStyle, TraceFormat, TraceOptions, Trigger};
+
+/// Default window scaling factor
+pub const DEFAULT_SCALE: f32 = 4.0;
+/// Default emulation speed multiplier
+pub const DEFAULT_SPEED: f32 = 1.0;
+/// Default audio sample rate in Hz
+pub const DEFAULT_SAMPLE_RATE: u32 = 44100;
+/// Default audio volume in percent
+pub const DEFAULT_VOLUME: u8 = 25;
+
+/// What the user asked the program to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Print usage and exit
+    Help,
+    /// Run a ROM (`neser [run]`)
+    Run(Box<CliOptions>),
+    /// Disassemble a ROM (`neser disasm`)
+    Disasm(DisasmOptions),
+    /// Run the test ROMs listed in manifests (`neser test-roms`)
+    TestRoms(TestRomsOptions),
+    /// Print ROM database lines for an nes20db.xml file (`neser nes20db`)
+    Nes20Db(String),
+}
+
+/// Options parsed from the command line
+///
+/// Settings that have a default are `None` when not given on the command line,
+/// so callers can tell an explicit choice from a default.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CliOptions {
+    /// Path to the ROM (`.nes`, `.zip` or `.gz`)
+    pub rom_path: String,
+    /// Member to load when the ROM is a zip archive
+    pub archive_member: Option<String>,
+    /// Config file to use instead of the one in the user's config directory
+    pub config_path: Option<String>,
+    /// Window scaling factor
+    pub scale: Option<f32>,
+    /// Emulation speed multiplier
+    pub speed: Option<f32>,
+    /// TV system, `None` means auto-detect from the ROM header
+    pub tv_system: Option<TvSystem>,
+    /// Audio sample rate in Hz
+    pub sample_rate: Option<u32>,
+    /// Audio volume in percent (0-100)
+    pub volume: Option<u8>,
+    /// Disable audio output
+    pub no_audio: bool,
+    /// Frames to run ahead to hide input latency
+    pub run_ahead: Option<u32>,
+    /// Store the effective settings as overrides for the loaded game
+    pub save_game_settings: bool,
+    /// Start in the terminal debugger instead of opening a window
+    pub debug: bool,
+    /// Port to wait for a GDB remote debugger on, instead of opening a window
+    pub gdb_port: Option<u16>,
+    /// Symbol files (`.dbg`, `.nl` or `.mlb`) for the debugger and traces
+    pub symbol_files: Vec<String>,
+    /// Code/data log (`.cdl`) to update, created if it does not exist
+    pub cdl_path: Option<String>,
+    /// Cheat list to load instead of the game's own
+    pub cheats_path: Option<String>,
+    /// File to write an instruction trace to
+    pub trace_path: Option<String>,
+    /// Trace format, fields and triggers, used with `trace_path`
+    pub trace: TraceOptions,
+    /// Run without a window, audio device or SDL, e.g. in CI
+    pub headless: bool,
+    /// Frames to run headless, `None` means `headless::DEFAULT_FRAMES`
+    pub frames: Option<u64>,
+    /// Input script to play back when headless
+    pub input_script: Option<String>,
+    /// Conditions that end a headless run early
+    pub until: Vec<StopCondition>,
+    /// PNG file to save the last frame to when headless
+    pub screenshot_path: Option<String>,
+    /// WAV file to save the audio to when headless
+    pub wav_path: Option<String>,
+    /// Movie file to record the input into (`.fm2` for FCEUX)
+    pub record_movie: Option<String>,
+    /// Movie file to play back (`.fm2` for FCEUX)
+    pub play_movie: Option<String>,
+    /// Start paused for making a tool-assisted movie
+    pub tas: bool,
+    /// Port to wait on for a netplay partner, playing as player 1
+    pub netplay_host: Option<u16>,
+    /// Address of a netplay host to join as player 2
+    pub netplay_join: Option<String>,
+    /// Frames of netplay input delay, `None` means the default
+    pub netplay_delay: Option<u32>,
+    /// Muted APU channels
+    pub disable_pulse1: bool,
+    pub disable_pulse2: bool,
+    pub disable_triangle: bool,
+    pub disable_noise: bool,
+    pub disable_dmc: bool,
+}
+
+/// Options of the `disasm` subcommand
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DisasmOptions {
+    /// Path to the ROM (`.nes`, `.zip` or `.gz`)
+    pub rom_path: String,
+    /// Member to load when the ROM is a zip archive
+    pub archive_member: Option<String>,
+    /// First CPU address to disassemble (default $8000)
+    pub start: Option<u16>,
+    /// Last CPU address to disassemble (default $FFFF, or the end of the bank)
+    pub end: Option<u16>,
+    /// 16 KiB PRG-ROM bank to disassemble as if mapped at `start`, instead of
+    /// the banks mapped at power-on
+    pub bank: Option<usize>,
+    /// Symbol files (`.dbg`, `.nl` or `.mlb`) used to label the listing
+    pub symbol_files: Vec<String>,
+}
+
+/// Options of the `test-roms` subcommand
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TestRomsOptions {
+    /// Directory searched for manifests, including its subdirectories
+    pub dir: String,
+    /// Only run ROMs whose path contains this text
+    pub filter: Option<String>,
+}
+
+/// Size of the PRG-ROM banks selected with `disasm --bank`
+pub const DISASM_BANK_SIZE: usize = 0x4000;
+
+/// Parse command-line arguments (without the program name)
+///
+/// Options taking a value accept both `--scale 3` and `--scale=3`.
+/// Returns a user-facing error message for unknown options, missing or
+/// invalid values, and a missing ROM path.
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    let args = match args.first().map(String::as_str) {
+        Some("disasm") => return parse_disasm_args(&args[1..]),
+        Some("test-roms") => return parse_test_roms_args(&args[1..]),
+        Some("nes20db") => return parse_nes20db_args(&args[1..]),
+        Some("run") => &args[1..],
+        _ => args,
+    };
+
+    let mut options = CliOptions::default();
+    let mut rom_path = None;
+    let mut trace_options_given = false;
+    let mut headless_options_given = false;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        // Split "--option=value" into name and inline value
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = |option: &str| -> Result<String, String> {
+            match inline_value.clone() {
+                Some(value) => Ok(value),
+                None => iter
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| format!("Option {} requires a value", option)),
+            }
+        };
+
+        match name {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--scale" => options.scale = Some(parse_number(name, &value(name)?, 1.0, 5.0)?),
+            "--speed" => {
+                options.speed = Some(parse_number(name, &value(name)?, 0.001, 100.0)?);
+            }
+            "--region" => options.tv_system = parse_region(&value(name)?)?,
+            "-pal" | "--pal" => options.tv_system = Some(TvSystem::Pal),
+            "--ntsc" => options.tv_system = Some(TvSystem::Ntsc),
+            "--sample-rate" => {
+                options.sample_rate = Some(parse_integer(name, &value(name)?, 8000, 192000)?);
+            }
+            "--volume" => options.volume = Some(parse_integer(name, &value(name)?, 0, 100)?),
+            "--member" => options.archive_member = Some(value(name)?),
+            "--config" => options.config_path = Some(value(name)?),
+            "--no-audio" => options.no_audio = true,
+            "--run-ahead" => {
+                let frames = parse_integer(name, &value(name)?, 0, RunAhead::MAX_FRAMES)?;
+                options.run_ahead = Some(frames);
+            }
+            "--save-game-settings" => options.save_game_settings = true,
+            "--debug" => options.debug = true,
+            "--gdb" => {
+                let port = value(name)?;
+                options.gdb_port = Some(port.parse().map_err(|_| {
+                    format!("Option {} expects a port number, got \"{}\"", name, port)
+                })?);
+            }
+            "--symbols" => options.symbol_files.push(value(name)?),
+            "--cdl" => options.cdl_path = Some(value(name)?),
+            "--cheats" => options.cheats_path = Some(value(name)?),
+            "--trace" => options.trace_path = Some(value(name)?),
+            "--trace-format" => {
+                let format = value(name)?;
+                options.trace.format = TraceFormat::from_name(&format).ok_or_else(|| {
+                    format!(
+                        "Invalid trace format \"{}\" (use nestest, mesen or fceux)",
+                        format
+                    )
+                })?;
+                trace_options_given = true;
+            }
+            "--trace-fields" => {
+                options.trace.fields = options.trace.fields.parse(&value(name)?)?;
+                trace_options_given = true;
+            }
+            "--trace-flags" => {
+                options.trace.fields.flags = match value(name)?.to_lowercase().as_str() {
+                    "hex" => FlagsStyle::Hex,
+                    "letters" => FlagsStyle::Letters,
+                    other => {
+                        return Err(format!(
+                            "Invalid flags style \"{}\" (use hex or letters)",
+                            other
+                        ));
+                    }
+                };
+                trace_options_given = true;
+            }
+            "--trace-start" => {
+                options.trace.start = Some(Trigger::parse(&value(name)?)?);
+                trace_options_given = true;
+            }
+            "--trace-stop" => {
+                options.trace.stop = Some(Trigger::parse(&value(name)?)?);
+                trace_options_given = true;
+            }
+            "--trace-ring" => {
+                options.trace.ring = Some(parse_integer(name, &value(name)?, 1, 10_000_000)?);
+                trace_options_given = true;
+            }
+            "--headless" => options.headless = true,
+            "--frames" => {
+                let frames = value(name)?;
+                options.frames = Some(frames.parse().map_err(|_| {
+                    format!("Option {} expects a frame count, got \"{}\"", name, frames)
+                })?);
+                headless_options_given = true;
+            }
+            "--input" => {
+                options.input_script = Some(value(name)?);
+                headless_options_given = true;
+            }
+            "--until" => {
+                options.until.push(StopCondition::parse(&value(name)?)?);
+                headless_options_given = true;
+            }
+            "--screenshot" => {
+                options.screenshot_path = Some(value(name)?);
+                headless_options_given = true;
+            }
+            "--wav" => {
+                options.wav_path = Some(value(name)?);
+                headless_options_given = true;
+            }
+            "--record" => options.record_movie = Some(value(name)?),
+            "--play" => options.play_movie = Some(value(name)?),
+            "--tas" => options.tas = true,
+            "--netplay-host" => {
+                let port = value(name)?;
+                options.netplay_host = Some(port.parse().map_err(|_| {
+                    format!("Option {} expects a port number, got \"{}\"", name, port)
+                })?);
+            }
+            "--netplay-join" => options.netplay_join = Some(value(name)?),
+            "--netplay-delay" => {
+                let max = NetplaySession::MAX_ROLLBACK as u32;
+                options.netplay_delay = Some(parse_integer(name, &value(name)?, 0, max)?);
+            }
+            "--disable-pulse1" => options.disable_pulse1 = true,
+            "--disable-pulse2" => options.disable_pulse2 = true,
+            "--disable-triangle" => options.disable_triangle = true,
+            "--disable-noise" => options.disable_noise = true,
+            "--disable-dmc" => options.disable_dmc = true,
+            _ if name.starts_with('-') && name.len() > 1 => {
+                return Err(format!("Unknown option {} (see --help)", name));
+            }
+            _ => {
+                if let Some(previous) = rom_path.replace(arg.clone()) {
+                    return Err(format!(
+                        "Only one ROM can be given, got \"{}\" and \"{}\"",
+                        previous, arg
+                    ));
+                }
+            }
+        }
+    }
+
+    if options.debug && options.gdb_port.is_some() {
+        return Err("--debug and --gdb cannot be used together".to_string());
+    }
+    if options.headless && (options.debug || options.gdb_port.is_some()) {
+        return Err("--headless cannot be used with --debug or --gdb".to_string());
+    }
+    if headless_options_given && !options.headless {
+        return Err(
+            "The --frames, --input, --until, --screenshot and --wav options require --headless"
+                .to_string(),
+        );
+    }
+    if options.record_movie.is_some() && options.play_movie.is_some() {
+        return Err("--record and --play cannot be used together".to_string());
+    }
+    if (options.record_movie.is_some() || options.play_movie.is_some())
+        && (options.debug || options.gdb_port.is_some())
+    {
+        return Err("--record and --play cannot be used with --debug or --gdb".to_string());
+    }
+    if options.tas && (options.headless || options.debug || options.gdb_port.is_some()) {
+        return Err("--tas cannot be used with --headless, --debug or --gdb".to_string());
+    }
+    let netplay = options.netplay_host.is_some() || options.netplay_join.is_some();
+    if options.netplay_host.is_some() && options.netplay_join.is_some() {
+        return Err("--netplay-host and --netplay-join cannot be used together".to_string());
+    }
+    if netplay
+        && (options.headless
+            || options.debug
+            || options.gdb_port.is_some()
+            || options.record_movie.is_some()
+            || options.play_movie.is_some()
+            || options.tas)
+    {
+        return Err(
+            "Netplay cannot be used with --headless, --debug, --gdb, --record, --play or --tas"
+                .to_string(),
+        );
+    }
+    if options.netplay_delay.is_some() && !netplay {
+        return Err("--netplay-delay requires --netplay-host or --netplay-join".to_string());
+    }
+    if trace_options_given && options.trace_path.is_none() {
+        return Err("The --trace-* options require --trace <FILE>".to_string());
+    }
+    options.rom_path = rom_path.ok_or("No ROM file given (see --help)")?;
+    Ok(Command::Run(Box::new(options)))
+}
+
+/// Parse the arguments of the `disasm` subcommand
+fn parse_disasm_args(args: &[String]) -> Result<Command, String> {
+    let mut options = DisasmOptions::default();
+    let mut rom_path = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = |option: &str| -> Result<String, String> {
+            match inline_value.clone() {
+                Some(value) => Ok(value),
+                None => iter
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| format!("Option {} requires a value", option)),
+            }
+        };
+        let address = |option: &str, value: String| {
+            crate::debugger::parse_value(&value)
+                .map_err(|message| format!("Option {}: {}", option, message))
+        };
+
+        match name {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--start" => options.start = Some(address(name, value(name)?)?),
+            "--end" => options.end = Some(address(name, value(name)?)?),
+            "--bank" => {
+                let bank = value(name)?;
+                options.bank = Some(bank.parse().map_err(|_| {
+                    format!("Option --bank expects a bank number, got \"{}\"", bank)
+                })?);
+            }
+            "--member" => options.archive_member = Some(value(name)?),
+            "--symbols" => options.symbol_files.push(value(name)?),
+            _ if name.starts_with('-') && name.len() > 1 => {
+                return Err(format!("Unknown disasm option {} (see --help)", name));
+            }
+            _ => {
+                if let Some(previous) = rom_path.replace(arg.clone()) {
+                    return Err(format!(
+                        "Only one ROM can be given, got \"{}\" and \"{}\"",
+                        previous, arg
+                    ));
+                }
+            }
+        }
+    }
+
+    if let (Some(start), Some(end)) = (options.start, options.end)
+        && end < start
+    {
+        return Err(format!(
+            "--end ${:04X} is before --start ${:04X}",
+            end, start
+        ));
+    }
+    options.rom_path = rom_path.ok_or("No ROM file given (see --help)")?;
+    Ok(Command::Disasm(options))
+}
+
+/// Parse the arguments of the `test-roms` subcommand
+fn parse_test_roms_args(args: &[String]) -> Result<Command, String> {
+    let mut options = TestRomsOptions::default();
+    let mut dir = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        match name {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--filter" => {
+                options.filter = Some(
+                    inline_value
+                        .or_else(|| iter.next().cloned())
+                        .ok_or("Option --filter requires a value")?,
+                );
+            }
+            _ if name.starts_with('-') && name.len() > 1 => {
+                return Err(format!("Unknown test-roms option {} (see --help)", name));
+            }
+            _ => {
+                if let Some(previous) = dir.replace(arg.clone()) {
+                    return Err(format!(
+                        "Only one directory can be given, got \"{}\" and \"{}\"",
+                        previous, arg
+                    ));
+                }
+            }
+        }
+    }
+
+    options.dir = dir.ok_or("No test ROM directory given (see --help)")?;
+    Ok(Command::TestRoms(options))
+}
+
+/// Parse the arguments of the `nes20db` subcommand
+fn parse_nes20db_args(args: &[String]) -> Result<Command, String> {
+    match args {
+        [arg] if arg == "-h" || arg == "--help" => Ok(Command::Help),
+        [arg] if arg.starts_with('-') && arg.len() > 1 => {
+            Err(format!("Unknown nes20db option {} (see --help)", arg))
+        }
+        [path] => Ok(Command::Nes20Db(path.clone())),
+        [] => Err("No nes20db.xml file given (see --help)".to_string()),
+        _ => Err("Only one nes20db.xml file can be given".to_string()),
+    }
+}
+
+/// Parse a numeric option value and check it lies within [min, max]
+fn parse_number(option: &str, value: &str, min: f32, max: f32) -> Result<f32, String> {
+    let number: f32 = value
+        .parse()
+        .map_err(|_| format!("Option {} expects a number, got \"{}\"", option, value))?;
+    if !(min..=max).contains(&number) {
+        return Err(format!(
+            "Option {} must be between {} and {}, got {}",
+            option, min, max, number
+        ));
+    }
+    Ok(number)
+}
+
+/// Parse a whole-number option value and check it lies within [min, max]
+fn parse_integer<T>(option: &str, value: &str, min: T, max: T) -> Result<T, String>
+where
+    T: std::str::FromStr + PartialOrd + std::fmt::Display,
+{
+    let number: T = value.parse().map_err(|_| {
+        format!(
+            "Option {} expects a whole number, got \"{}\"",
+            option, value
+        )
+    })?;
+    if number < min || number > max {
+        return Err(format!(
+            "Option {} must be between {} and {}, got {}",
+            option, min, max, number
+        ));
+    }
+    Ok(number)
+}
+
+/// Parse a `--region` value, where `auto` means detect from the ROM header
+pub(crate) fn parse_region(value: &str) -> Result<Option<TvSystem>, String> {
+    match value.to_lowercase().as_str() {
+        "auto" => Ok(None),
+        "ntsc" => Ok(Some(TvSystem::Ntsc)),
+        "pal" => Ok(Some(TvSystem::Pal)),
+        _ => Err(format!(
+            "Unknown region \"{}\" (expected auto, ntsc or pal)",
+            value
+        )),
+    }
+}
+
+/// Usage text shown by `--help`
+pub fn usage() -> String {
+    format!(
+        "NES Emulator
+
+Usage: neser [run] [OPTIONS] <ROM>
+       neser disasm [DISASM OPTIONS] <ROM>
+       neser test-roms [--filter <TEXT>] <DIR>
+       neser nes20db <XML>
+
+<ROM> may be a .nes file, a .zip archive or a gzip-compressed file.
+
+Options:
+  --scale <N>           Window scale, 1-5 (default: {scale})
+  --speed <N>           Emulation speed multiplier, 0.001-100 (default: {speed})
+  --region <R>          auto, ntsc or pal (default: auto, from the ROM header)
+  -pal                  Same as --region pal
+  --sample-rate <HZ>    Audio sample rate (default: {rate})
+  --volume <PERCENT>    Audio volume, 0-100 (default: {volume})
+  --no-audio            Disable audio output
+  --run-ahead <N>       Show each frame N frames early to hide input lag,
+                        0-{run_ahead} (default: 0)
+  --member <NAME>       ROM to load from a zip archive (default: first ROM)
+  --config <FILE>       Config file (default: {config})
+  --save-game-settings  Save palette, region, sprite limit, port 2 device and
+                        overscan as settings for this game
+  --debug               Run in the terminal debugger instead of a window
+  --gdb <PORT>          Wait for a GDB remote debugger on 127.0.0.1:PORT
+                        instead of opening a window
+  --symbols <FILE>      Load labels from a ca65 .dbg, FCEUX .nl or Mesen .mlb
+                        file for the debugger (repeatable)
+  --cdl <FILE>          Record which ROM bytes are used as code and data in an
+                        FCEUX .cdl file, adding to FILE if it exists
+  --cheats <FILE>       Load Game Genie, Pro Action Replay or raw codes from
+                        FILE (default: cheats/<CRC32>.txt next to the config
+                        file, if there is one)
+  --record <FILE>       Record the controller input from power-on into a movie
+                        (FCEUX format if FILE ends in .fm2)
+  --play <FILE>         Play back a movie recorded with --record or an FCEUX
+                        .fm2 movie, warning if it goes out of sync
+  --tas                 Start paused, showing the frame and lag counters and
+                        the input; while paused, controller keys toggle the
+                        buttons for the next frame (F advances a frame, Q
+                        makes the movie read-only or read-write)
+  --netplay-host <PORT> Wait for a second player on UDP port PORT and play as
+                        player 1
+  --netplay-join <ADDR> Join the game hosted at HOST:PORT as player 2
+  --netplay-delay <N>   Frames between pressing a button and the game seeing
+                        it in netplay, 0-{max_rollback} (default: {delay})
+  -h, --help            Show this help
+
+Options given on the command line override the config file, which is created
+with the defaults on first run.
+
+Trace Logging:
+  --trace <FILE>        Write a line per executed instruction to FILE
+  --trace-format <F>    nestest, mesen or fceux (default: nestest)
+  --trace-fields <LIST> Fields of mesen and fceux lines, from cycle, scanline,
+                        frame and bank (default: cycle,scanline)
+  --trace-flags <S>     Show P as hex or letters (default: hex)
+  --trace-start <WHEN>  Start logging at an address or at frame:<N>
+  --trace-stop <WHEN>   Stop logging at an address or at frame:<N>
+  --trace-ring <N>      Keep only the last N lines, written when the CPU halts,
+                        the emulator crashes or exits
+
+Headless Mode:
+  --headless            Run without a window, audio device or SDL, e.g. in CI
+  --frames <N>          Frames to run (default: {frames}, or the length of the
+                        movie given with --play)
+  --input <FILE>        Play back an input script: lines of a frame number and
+                        the buttons held from then on, e.g. \"60 start\"
+                        (buttons after a | are for controller 2)
+  --until <WHEN>        Stop early at pc:<ADDR>, when the test ROM reports a
+                        result at $6000 (status), or once text:<TEXT> is on
+                        screen (repeatable)
+  --screenshot <FILE>   Save the last frame as a PNG
+  --wav <FILE>          Save the audio as a WAV file
+
+  Prints the outcome and a CRC-32 of the last frame. Exits with 0 when done,
+  3 if the test failed, the CPU halted or the movie went out of sync, and 4 if
+  no --until condition was met within the frames.
+
+APU Channel Control (for debugging):
+  --disable-pulse1      Mute pulse 1 channel
+  --disable-pulse2      Mute pulse 2 channel
+  --disable-triangle    Mute triangle channel
+  --disable-noise       Mute noise channel
+  --disable-dmc         Mute DMC channel
+
+Disasm Options:
+  --start <ADDR>        First CPU address (default: $8000)
+  --end <ADDR>          Last CPU address (default: $FFFF)
+  --bank <N>            Disassemble 16 KiB PRG-ROM bank N mapped at --start,
+                        instead of the banks mapped at power-on
+  --member <NAME>       ROM to load from a zip archive
+  --symbols <FILE>      Label the listing from a .dbg, .nl or .mlb file
+                        (repeatable)
+
+Addresses are $hex, 0xhex or decimal.
+
+Test ROMs:
+  Runs the ROMs listed in the {manifest} files in DIR and its subdirectories
+  and prints a pass/fail matrix. Each manifest line holds a ROM, how it reports
+  its result (status at $6000, or console text), the frames to run at most and
+  the expected result:
+    registers.nes  status  600  pass
+  --filter <TEXT>       Only run ROMs whose path contains TEXT
+
+  Exits with 0 when every ROM gave the expected result, and 3 otherwise.
+
+ROM Database:
+  Prints the games in an nes20db.xml file as lines of the built-in ROM
+  database, src/cartridge/nes20db.txt, which corrects bad iNES headers.
+  scripts/update-nes20db.sh uses this to regenerate the imported entries.
+
+Examples:
+  neser --scale 3 --disable-pulse2 roms/game.zip
+  neser run --headless --until status --screenshot out.png roms/test.nes
+  neser disasm --bank 2 --start '$8000' roms/game.nes
+  neser test-roms roms/blargg",
+        scale = DEFAULT_SCALE,
+        speed = DEFAULT_SPEED,
+        run_ahead = RunAhead::MAX_FRAMES,
+        max_rollback = NetplaySession::MAX_ROLLBACK,
+        delay = NetplaySession::DEFAULT_DELAY,
+        rate = DEFAULT_SAMPLE_RATE,
+        volume = DEFAULT_VOLUME,
+        frames = crate::headless::DEFAULT_FRAMES,
+        manifest = crate::test_rom::MANIFEST_NAME,
+        config = crate::config::Config::default_path()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "none".to_string()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        parse_args(&args)
+    }
+
+    fn parse_run(args: &[&str]) -> CliOptions {
+        match parse(args) {
+            Ok(Command::Run(options)) => *options,
+            other => panic!("Expected run command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_rom_path_only() {
+        let options = parse_run(&["game.nes"]);
+        assert_eq!(options.rom_path, "game.nes");
+        assert_eq!(options.scale, None);
+        assert_eq!(options.tv_system, None);
+        assert!(!options.no_audio);
+    }
+
+    #[test]
+    fn test_help() {
+        assert_eq!(parse(&["--help"]), Ok(Command::Help));
+        assert_eq!(parse(&["game.nes", "-h"]), Ok(Command::Help));
+    }
+
+    #[test]
+    fn test_missing_rom_is_an_error() {
+        let err = parse(&["--scale", "2"]).unwrap_err();
+        assert!(err.contains("No ROM file"));
+    }
+
+    #[test]
+    fn test_two_roms_is_an_error() {
+        assert!(parse(&["a.nes", "b.nes"]).is_err());
+    }
+
+    #[test]
+    fn test_value_options() {
+        let options = parse_run(&[
+            "--scale",
+            "3",
+            "--speed=0.5",
+            "--sample-rate",
+            "48000",
+            "--volume",
+            "80",
+            "--run-ahead",
+            "2",
+            "--member",
+            "Game (USA).nes",
+            "--config=my.toml",
+            "games.zip",
+        ]);
+        assert_eq!(options.scale, Some(3.0));
+        assert_eq!(options.speed, Some(0.5));
+        assert_eq!(options.sample_rate, Some(48000));
+        assert_eq!(options.volume, Some(80));
+        assert_eq!(options.run_ahead, Some(2));
+        assert_eq!(options.archive_member.as_deref(), Some("Game (USA).nes"));
+        assert_eq!(options.config_path.as_deref(), Some("my.toml"));
+        assert_eq!(options.rom_path, "games.zip");
+    }
+
+    #[test]
+    fn test_region() {
+        assert_eq!(
+            parse_run(&["--region", "pal", "g.nes"]).tv_system,
+            Some(TvSystem::Pal)
+        );
+        assert_eq!(
+            parse_run(&["--region=NTSC", "g.nes"]).tv_system,
+            Some(TvSystem::Ntsc)
+        );
+        assert_eq!(parse_run(&["--region", "auto", "g.nes"]).tv_system, None);
+        assert_eq!(parse_run(&["-pal", "g.nes"]).tv_system, Some(TvSystem::Pal));
+        assert!(parse(&["--region", "secam", "g.nes"]).is_err());
+    }
+
+    #[test]
+    fn test_channel_flags() {
+        let options = parse_run(&[
+            "g.nes",
+            "--disable-pulse2",
+            "--disable-dmc",
+            "--no-audio",
+            "--save-game-settings",
+            "--debug",
+            "--symbols",
+            "game.dbg",
+            "--cdl=game.cdl",
+            "--cheats",
+            "game.txt",
+        ]);
+        assert!(!options.disable_pulse1);
+        assert!(options.disable_pulse2);
+        assert!(options.disable_dmc);
+        assert!(options.no_audio);
+        assert!(options.save_game_settings);
+        assert!(options.debug);
+        assert_eq!(options.symbol_files, vec!["game.dbg"]);
+        assert_eq!(options.cdl_path.as_deref(), Some("game.cdl"));
+        assert_eq!(options.cheats_path.as_deref(), Some("game.txt"));
+    }
+
+    #[test]
+    fn test_gdb_port() {
+        assert_eq!(parse_run(&["--gdb", "2345", "g.nes"]).gdb_port, Some(2345));
+        assert!(parse(&["--gdb", "70000", "g.nes"]).is_err());
+        assert!(parse(&["--gdb", "2345", "--debug", "g.nes"]).is_err());
+    }
+
+    #[test]
+    fn test_trace_options() {
+        let options = parse_run(&[
+            "g.nes",
+            "--trace",
+            "trace.log",
+            "--trace-format=mesen",
+            "--trace-fields",
+            "frame,bank",
+            "--trace-flags",
+            "letters",
+            "--trace-start",
+            "frame:10",
+            "--trace-stop",
+            "$C000",
+            "--trace-ring",
+            "1000",
+        ]);
+        assert_eq!(options.trace_path.as_deref(), Some("trace.log"));
+        assert_eq!(options.trace.format, TraceFormat::Mesen);
+        assert!(options.trace.fields.frame && !options.trace.fields.cycle);
+        assert_eq!(options.trace.fields.flags, FlagsStyle::Letters);
+        assert_eq!(options.trace.start, Some(Trigger::Frame(10)));
+        assert_eq!(options.trace.stop, Some(Trigger::Address(0xC000)));
+        assert_eq!(options.trace.ring, Some(1000));
+
+        assert!(parse(&["g.nes", "--trace", "t.log", "--trace-ring", "1.5"]).is_err());
+        assert!(parse(&["g.nes", "--trace-format", "mesen"]).is_err());
+        assert!(parse(&["g.nes", "--trace", "t.log", "--trace-format", "bizhawk"]).is_err());
+    }
+
+    #[test]
+    fn test_missing_value_is_an_error() {
+        let err = parse(&["g.nes", "--scale"]).unwrap_err();
+        assert!(err.contains("requires a value"));
+    }
+
+    #[test]
+    fn test_invalid_number_is_an_error() {
+        assert!(parse(&["g.nes", "--scale", "big"]).is_err());
+        assert!(parse(&["g.nes", "--scale", "9"]).is_err());
+        assert!(parse(&["g.nes", "--volume", "101"]).is_err());
+        assert!(parse(&["g.nes", "--run-ahead", "1.5"]).is_err());
+    }
+
+    #[test]
+    fn test_fractional_integer_option_is_an_error() {
+        let err = parse(&["g.nes", "--volume", "12.7"]).unwrap_err();
+        assert!(err.contains("whole number"));
+        assert!(parse(&["g.nes", "--sample-rate", "44100.5"]).is_err());
+        assert!(parse(&["g.nes", "--volume", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_disasm_subcommand() {
+        let options = match parse(&["disasm", "--start", "$C000", "--bank=3", "g.nes"]) {
+            Ok(Command::Disasm(options)) => options,
+            other => panic!("Expected disasm command, got {:?}", other),
+        };
+        assert_eq!(options.start, Some(0xC000));
+        assert_eq!(options.end, None);
+        assert_eq!(options.bank, Some(3));
+        assert_eq!(options.rom_path, "g.nes");
+
+        let options = match parse(&["disasm", "--symbols", "a.dbg", "--symbols=b.nl", "g.nes"]) {
+            Ok(Command::Disasm(options)) => options,
+            other => panic!("Expected disasm command, got {:?}", other),
+        };
+        assert_eq!(options.symbol_files, vec!["a.dbg", "b.nl"]);
+
+        assert!(parse(&["disasm"]).is_err());
+        assert!(parse(&["disasm", "--start", "$C000", "--end", "$8000", "g.nes"]).is_err());
+        assert!(parse(&["disasm", "--scale", "2", "g.nes"]).is_err());
+    }
+
+    #[test]
+    fn test_movie_options() {
+        let options = parse_run(&["--record", "run.nmv", "g.nes"]);
+        assert_eq!(options.record_movie.as_deref(), Some("run.nmv"));
+        let options = parse_run(&["--headless", "--play=run.fm2", "g.nes"]);
+        assert_eq!(options.play_movie.as_deref(), Some("run.fm2"));
+
+        assert!(parse(&["--record", "a.nmv", "--play", "b.nmv", "g.nes"]).is_err());
+        assert!(parse(&["--play", "a.nmv", "--debug", "g.nes"]).is_err());
+
+        assert!(parse_run(&["--tas", "--play", "run.nmv", "g.nes"]).tas);
+        assert!(parse(&["--tas", "--headless", "g.nes"]).is_err());
+    }
+
+    #[test]
+    fn test_netplay_options() {
+        let options = parse_run(&["--netplay-host", "7000", "g.nes"]);
+        assert_eq!(options.netplay_host, Some(7000));
+        let options = parse_run(&[
+            "--netplay-join=10.0.0.2:7000",
+            "--netplay-delay",
+            "3",
+            "g.nes",
+        ]);
+        assert_eq!(options.netplay_join.as_deref(), Some("10.0.0.2:7000"));
+        assert_eq!(options.netplay_delay, Some(3));
+
+        assert!(parse(&["--netplay-host", "x", "g.nes"]).is_err());
+        assert!(parse(&["--netplay-host", "7000", "--netplay-join", "a:1", "g.nes"]).is_err());
+        assert!(parse(&["--netplay-host", "7000", "--record", "a.nmv", "g.nes"]).is_err());
+        assert!(parse(&["--netplay-join", "a:1", "--headless", "g.nes"]).is_err());
+        assert!(parse(&["--netplay-delay", "2", "g.nes"]).is_err());
+        assert!(parse(&["--netplay-host", "7000", "--netplay-delay", "9", "g.nes"]).is_err());
+        assert!(parse(&["--netplay-host", "7000", "--netplay-delay", "1.5", "g.nes"]).is_err());
+    }
+
+    #[test]
+    fn test_test_roms_subcommand() {
+        assert_eq!(
+            parse(&["test-roms", "--filter=instr", "roms/blargg"]),
+            Ok(Command::TestRoms(TestRomsOptions {
+                dir: "roms/blargg".to_string(),
+                filter: Some("instr".to_string()),
+            }))
+        );
+        assert!(parse(&["test-roms"]).is_err());
+        assert!(parse(&["test-roms", "a", "b"]).is_err());
+        assert!(parse(&["test-roms", "--frames", "9", "a"]).is_err());
+    }
+
+    #[test]
+    fn test_nes20db_subcommand() {
+        assert_eq!(
+            parse(&["nes20db", "nes20db.xml"]),
+            Ok(Command::Nes20Db("nes20db.xml".to_string()))
+        );
+        assert!(parse(&["nes20db"]).is_err());
+        assert!(parse(&["nes20db", "a.xml", "b.xml"]).is_err());
+        assert!(parse(&["nes20db", "--filter", "a.xml"]).is_err());
+    }
+
+    #[test]
+    fn test_headless_options() {
+        let options = parse_run(&[
+            "run",
+            "--headless",
+            "--frames=600",
+            "--input",
+            "moves.txt",
+            "--until",
+            "status",
+            "--until=text:Passed",
+            "--screenshot",
+            "out.png",
+            "--wav",
+            "out.wav",
+            "test.nes",
+        ]);
+        assert!(options.headless);
+        assert_eq!(options.frames, Some(600));
+        assert_eq!(options.input_script.as_deref(), Some("moves.txt"));
+        assert_eq!(
+            options.until,
+            vec![
+                StopCondition::Status,
+                StopCondition::Text("Passed".to_string())
+            ]
+        );
+        assert_eq!(options.screenshot_path.as_deref(), Some("out.png"));
+        assert_eq!(options.wav_path.as_deref(), Some("out.wav"));
+        assert_eq!(options.rom_path, "test.nes");
+
+        assert!(parse(&["--frames", "600", "g.nes"]).is_err());
+        assert!(parse(&["--headless", "--until", "frame:3", "g.nes"]).is_err());
+        assert!(parse(&["--headless", "--debug", "g.nes"]).is_err());
+    }
+
+    #[test]
+    fn test_unknown_option_is_an_error() {
+        let err = parse(&["g.nes", "--turbo"]).unwrap_err();
+        assert!(err.contains("--turbo"));
+    }
+}
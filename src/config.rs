@@ -0,0 +1,862 @@
+use crate::cli;
+use crate::input::{Button, InputDevice};
+use crate::nes::TvSystem;
+use crate::ppu::{self, Palette};
+use crate::runahead::RunAhead;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Default audio latency in milliseconds
+pub const DEFAULT_LATENCY_MS: u32 = 23;
+
+/// Palette setting that selects the built-in palette
+pub const DEFAULT_PALETTE: &str = "default";
+
+/// Persistent emulator settings, stored as TOML in the user's config directory
+///
+/// Every section and field is optional in the file. Missing entries take their
+/// default value, and entries that cannot be used are replaced by their default
+/// with a warning, so a broken config never prevents the emulator from starting.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub input: InputConfig,
+    pub hotkeys: HotkeyConfig,
+    pub video: VideoConfig,
+    pub audio: AudioConfig,
+    pub emulation: EmulationConfig,
+    pub rewind: RewindConfig,
+    pub run_ahead: RunAheadConfig,
+}
+
+/// Keyboard bindings for both controllers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RawInputConfig")]
+pub struct InputConfig {
+    pub player1: ControllerBindings,
+    pub player2: ControllerBindings,
+    /// Device plugged into controller port 2
+    pub port2: InputDevice,
+}
+
+/// Key names (as understood by SDL, e.g. "Up", "Z", "Right Shift") for each button
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ControllerBindings {
+    pub up: String,
+    pub down: String,
+    pub left: String,
+    pub right: String,
+    pub a: String,
+    pub b: String,
+    pub select: String,
+    pub start: String,
+}
+
+/// Keys for emulator functions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeyConfig {
+    pub quit: String,
+    pub pause: String,
+    pub reset: String,
+    /// Held to step play backwards
+    pub rewind: String,
+    /// Emulate one frame and pause
+    pub frame_advance: String,
+    /// Switch a movie between read-only and read-write
+    pub read_only: String,
+    /// Show or hide the frame counter, lag counter and input
+    pub show_info: String,
+    /// Switch all cheats on or off
+    pub cheats: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VideoConfig {
+    /// Window scaling factor (1-5)
+    pub scale: f32,
+    /// "default" for the built-in palette, otherwise the path to a `.pal` file
+    pub palette: String,
+    /// Pixels hidden at each edge of the picture
+    pub overscan: Overscan,
+}
+
+/// Number of pixels cropped from each edge of the 256x240 picture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Overscan {
+    pub top: u32,
+    pub bottom: u32,
+    pub left: u32,
+    pub right: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+    /// Volume in percent (0-100)
+    pub volume: u8,
+    /// Output buffer length in milliseconds
+    pub latency_ms: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmulationConfig {
+    /// Emulation speed multiplier
+    pub speed: f32,
+    /// "auto" to use the ROM header, "ntsc" or "pal"
+    pub region: String,
+    /// Draw at most 8 sprites per scanline, as the hardware does
+    pub sprite_limit: bool,
+}
+
+/// States kept for stepping play backwards
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RewindConfig {
+    pub enabled: bool,
+    /// Memory for the compressed states in MiB
+    pub memory_mb: u32,
+    /// Frames between states, and so per step backwards
+    pub interval: u32,
+}
+
+/// Frames shown early to hide input latency
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunAheadConfig {
+    /// Frames to run ahead, 0 to turn run-ahead off
+    pub frames: u32,
+    /// Run ahead on a second console, keeping the audio of the real one
+    /// untouched, instead of restoring the real one after each frame
+    pub second_instance: bool,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            player1: ControllerBindings::new(["Up", "Down", "Left", "Right", "X", "Z", "A", "S"]),
+            player2: ControllerBindings::new(["I", "K", "J", "L", "M", "N", "O", "P"]),
+            port2: InputDevice::Controller,
+        }
+    }
+}
+
+impl ControllerBindings {
+    /// Create bindings from key names in up, down, left, right, A, B, select, start order
+    fn new(keys: [&str; 8]) -> Self {
+        let [up, down, left, right, a, b, select, start] = keys.map(str::to_string);
+        Self {
+            up,
+            down,
+            left,
+            right,
+            a,
+            b,
+            select,
+            start,
+        }
+    }
+
+    /// Key name bound to each button
+    pub fn buttons(&self) -> [(Button, &str); 8] {
+        [
+            (Button::Up, &self.up),
+            (Button::Down, &self.down),
+            (Button::Left, &self.left),
+            (Button::Right, &self.right),
+            (Button::A, &self.a),
+            (Button::B, &self.b),
+            (Button::Select, &self.select),
+            (Button::Start, &self.start),
+        ]
+    }
+}
+
+/// `[input]` section as written in the file, where every key is optional
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawInputConfig {
+    player1: RawControllerBindings,
+    player2: RawControllerBindings,
+    port2: InputDevice,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawControllerBindings {
+    up: Option<String>,
+    down: Option<String>,
+    left: Option<String>,
+    right: Option<String>,
+    a: Option<String>,
+    b: Option<String>,
+    select: Option<String>,
+    start: Option<String>,
+}
+
+impl RawControllerBindings {
+    /// Fill keys missing from the file with the player's default bindings
+    fn merge(self, defaults: ControllerBindings) -> ControllerBindings {
+        ControllerBindings {
+            up: self.up.unwrap_or(defaults.up),
+            down: self.down.unwrap_or(defaults.down),
+            left: self.left.unwrap_or(defaults.left),
+            right: self.right.unwrap_or(defaults.right),
+            a: self.a.unwrap_or(defaults.a),
+            b: self.b.unwrap_or(defaults.b),
+            select: self.select.unwrap_or(defaults.select),
+            start: self.start.unwrap_or(defaults.start),
+        }
+    }
+}
+
+impl From<RawInputConfig> for InputConfig {
+    fn from(raw: RawInputConfig) -> Self {
+        let defaults = InputConfig::default();
+        Self {
+            player1: raw.player1.merge(defaults.player1),
+            player2: raw.player2.merge(defaults.player2),
+            port2: raw.port2,
+        }
+    }
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        Self {
+            quit: "Escape".to_string(),
+            pause: "Space".to_string(),
+            reset: "F1".to_string(),
+            rewind: "Backspace".to_string(),
+            frame_advance: "F".to_string(),
+            read_only: "Q".to_string(),
+            show_info: "F3".to_string(),
+            cheats: "F4".to_string(),
+        }
+    }
+}
+
+impl Default for VideoConfig {
+    fn default() -> Self {
+        Self {
+            scale: cli::DEFAULT_SCALE,
+            palette: DEFAULT_PALETTE.to_string(),
+            overscan: Overscan::default(),
+        }
+    }
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: cli::DEFAULT_SAMPLE_RATE,
+            volume: cli::DEFAULT_VOLUME,
+            latency_ms: DEFAULT_LATENCY_MS,
+        }
+    }
+}
+
+impl Default for EmulationConfig {
+    fn default() -> Self {
+        Self {
+            speed: cli::DEFAULT_SPEED,
+            region: "auto".to_string(),
+            sprite_limit: true,
+        }
+    }
+}
+
+impl Default for RewindConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            memory_mb: 32,
+            interval: 1,
+        }
+    }
+}
+
+impl Config {
+    /// Location of the config file, `<config dir>/neser/config.toml`
+    ///
+    /// Returns `None` if the platform has no config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("neser").join("config.toml"))
+    }
+
+    /// Load the config file at `path`
+    ///
+    /// A missing file yields the defaults. Unreadable or malformed files fall back
+    /// to defaults, and every problem is reported as a warning on stderr.
+    pub fn load(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                let (config, warnings) = Self::from_toml(&text);
+                for warning in warnings {
+                    eprintln!("Warning: {}: {}", path.display(), warning);
+                }
+                config
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                eprintln!(
+                    "Warning: Cannot read config file {}: {}. Using defaults.",
+                    path.display(),
+                    e
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Parse config text, returning the config and a list of problems found
+    ///
+    /// Sections are parsed independently, so an error in one section only resets
+    /// that section to its defaults.
+    pub fn from_toml(text: &str) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        let table: toml::Table = match text.parse() {
+            Ok(table) => table,
+            Err(e) => {
+                warnings.push(format!("Malformed config, using defaults: {}", e));
+                return (Self::default(), warnings);
+            }
+        };
+
+        let mut config = Self {
+            input: parse_section(&table, "input", &mut warnings),
+            hotkeys: parse_section(&table, "hotkeys", &mut warnings),
+            video: parse_section(&table, "video", &mut warnings),
+            audio: parse_section(&table, "audio", &mut warnings),
+            emulation: parse_section(&table, "emulation", &mut warnings),
+            rewind: parse_section(&table, "rewind", &mut warnings),
+            run_ahead: parse_section(&table, "run_ahead", &mut warnings),
+        };
+        for name in table.keys() {
+            if ![
+                "input",
+                "hotkeys",
+                "video",
+                "audio",
+                "emulation",
+                "rewind",
+                "run_ahead",
+            ]
+            .contains(&name.as_str())
+            {
+                warnings.push(format!("Ignoring unknown section [{}]", name));
+            }
+        }
+        config.validate(&mut warnings);
+
+        (config, warnings)
+    }
+
+    /// Serialize the config to TOML
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("Config always serializes")
+    }
+
+    /// Write the config to `path`, creating parent directories as needed
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_toml())
+    }
+
+    /// TV system to force, or `None` to detect it from the ROM header
+    pub fn tv_system(&self) -> Option<TvSystem> {
+        // The region is validated on load, anything else means auto
+        cli::parse_region(&self.emulation.region).unwrap_or(None)
+    }
+
+    /// Load the configured palette, falling back to the built-in one with a warning
+    pub fn palette(&self) -> Palette {
+        let setting = &self.video.palette;
+        if setting.is_empty() || setting.eq_ignore_ascii_case(DEFAULT_PALETTE) {
+            return ppu::default_palette();
+        }
+        ppu::load_palette_file(Path::new(setting)).unwrap_or_else(|e| {
+            eprintln!(
+                "Warning: Cannot load palette \"{}\": {}. Using the default palette.",
+                setting, e
+            );
+            ppu::default_palette()
+        })
+    }
+
+    /// Reset out-of-range values to their defaults
+    fn validate(&mut self, warnings: &mut Vec<String>) {
+        let video = VideoConfig::default();
+        let audio = AudioConfig::default();
+        let emulation = EmulationConfig::default();
+        let rewind = RewindConfig::default();
+
+        check_range(
+            &mut self.video.scale,
+            1.0,
+            5.0,
+            video.scale,
+            "video.scale",
+            warnings,
+        );
+        check_range(
+            &mut self.audio.sample_rate,
+            8000,
+            192000,
+            audio.sample_rate,
+            "audio.sample_rate",
+            warnings,
+        );
+        check_range(
+            &mut self.audio.volume,
+            0,
+            100,
+            audio.volume,
+            "audio.volume",
+            warnings,
+        );
+        check_range(
+            &mut self.audio.latency_ms,
+            5,
+            1000,
+            audio.latency_ms,
+            "audio.latency_ms",
+            warnings,
+        );
+        check_range(
+            &mut self.emulation.speed,
+            0.001,
+            100.0,
+            emulation.speed,
+            "emulation.speed",
+            warnings,
+        );
+        check_range(
+            &mut self.rewind.memory_mb,
+            1,
+            4096,
+            rewind.memory_mb,
+            "rewind.memory_mb",
+            warnings,
+        );
+        check_range(
+            &mut self.rewind.interval,
+            1,
+            60,
+            rewind.interval,
+            "rewind.interval",
+            warnings,
+        );
+        check_range(
+            &mut self.run_ahead.frames,
+            0,
+            RunAhead::MAX_FRAMES,
+            0,
+            "run_ahead.frames",
+            warnings,
+        );
+        if let Err(e) = cli::parse_region(&self.emulation.region) {
+            warnings.push(format!("emulation.region: {}, using auto", e));
+            self.emulation.region = emulation.region;
+        }
+        let overscan = &mut self.video.overscan;
+        for (value, name) in [
+            (&mut overscan.top, "video.overscan.top"),
+            (&mut overscan.bottom, "video.overscan.bottom"),
+            (&mut overscan.left, "video.overscan.left"),
+            (&mut overscan.right, "video.overscan.right"),
+        ] {
+            check_range(value, 0, Overscan::MAX, 0, name, warnings);
+        }
+    }
+}
+
+impl Overscan {
+    /// Largest crop allowed at a single edge
+    pub const MAX: u32 = 64;
+}
+
+/// Per-game settings applied on top of the global configuration
+///
+/// Overrides live next to the config file in `games/<CRC32>.toml`, keyed by the
+/// CRC32 of the ROM's PRG-ROM and CHR-ROM. Settings left out of the file keep
+/// their global value.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub palette: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sprite_limit: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port2: Option<InputDevice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overscan: Option<Overscan>,
+}
+
+impl GameOverrides {
+    /// Capture the per-game settings of a configuration
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            palette: Some(config.video.palette.clone()),
+            region: Some(config.emulation.region.clone()),
+            sprite_limit: Some(config.emulation.sprite_limit),
+            port2: Some(config.input.port2),
+            overscan: Some(config.video.overscan),
+        }
+    }
+
+    /// Path of the override file for a game
+    ///
+    /// # Arguments
+    /// * `config_path` - Path of the global config file
+    /// * `crc32` - CRC32 of the game's PRG-ROM and CHR-ROM
+    pub fn path(config_path: &Path, crc32: u32) -> PathBuf {
+        config_path
+            .with_file_name("games")
+            .join(format!("{:08X}.toml", crc32))
+    }
+
+    /// Load overrides from `path`, or `None` if the game has none
+    ///
+    /// Malformed files are reported as a warning and ignored.
+    pub fn load(path: &Path) -> Option<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                eprintln!("Warning: Cannot read {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        match toml::from_str(&text) {
+            Ok(overrides) => Some(overrides),
+            Err(e) => {
+                eprintln!(
+                    "Warning: Ignoring malformed game settings {}: {}",
+                    path.display(),
+                    e.to_string().trim()
+                );
+                None
+            }
+        }
+    }
+
+    /// Write the overrides to `path`, creating parent directories as needed
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let text = toml::to_string_pretty(self).expect("GameOverrides always serializes");
+        fs::write(path, text)
+    }
+}
+
+impl Config {
+    /// Return this configuration with a game's overrides applied
+    ///
+    /// Override values are validated like the config file, so out-of-range
+    /// values fall back to defaults with a warning.
+    pub fn with_overrides(&self, overrides: &GameOverrides) -> Self {
+        let mut config = self.clone();
+        if let Some(palette) = &overrides.palette {
+            config.video.palette = palette.clone();
+        }
+        if let Some(region) = &overrides.region {
+            config.emulation.region = region.clone();
+        }
+        if let Some(sprite_limit) = overrides.sprite_limit {
+            config.emulation.sprite_limit = sprite_limit;
+        }
+        if let Some(port2) = overrides.port2 {
+            config.input.port2 = port2;
+        }
+        if let Some(overscan) = overrides.overscan {
+            config.video.overscan = overscan;
+        }
+
+        let mut warnings = Vec::new();
+        config.validate(&mut warnings);
+        for warning in warnings {
+            eprintln!("Warning: Game settings: {}", warning);
+        }
+        config
+    }
+
+    /// Apply the stored overrides for a game, if it has any
+    ///
+    /// # Arguments
+    /// * `config_path` - Path of the global config file
+    /// * `crc32` - CRC32 of the game's PRG-ROM and CHR-ROM
+    pub fn for_game(&self, config_path: &Path, crc32: u32) -> Self {
+        match GameOverrides::load(&GameOverrides::path(config_path, crc32)) {
+            Some(overrides) => self.with_overrides(&overrides),
+            None => self.clone(),
+        }
+    }
+
+    /// Save the current per-game settings as overrides for a game
+    ///
+    /// Returns the path the overrides were written to.
+    pub fn save_game_overrides(&self, config_path: &Path, crc32: u32) -> io::Result<PathBuf> {
+        let path = GameOverrides::path(config_path, crc32);
+        GameOverrides::from_config(self).save(&path)?;
+        Ok(path)
+    }
+}
+
+/// Deserialize one top-level section, falling back to its defaults on error
+fn parse_section<T: DeserializeOwned + Default>(
+    table: &toml::Table,
+    name: &str,
+    warnings: &mut Vec<String>,
+) -> T {
+    match table.get(name) {
+        None => T::default(),
+        Some(value) => value.clone().try_into().unwrap_or_else(|e| {
+            warnings.push(format!(
+                "Invalid [{}] section, using defaults: {}",
+                name,
+                e.to_string().trim()
+            ));
+            T::default()
+        }),
+    }
+}
+
+/// Replace `value` with `default` if it lies outside [min, max]
+fn check_range<T: PartialOrd + Copy + std::fmt::Display>(
+    value: &mut T,
+    min: T,
+    max: T,
+    default: T,
+    name: &str,
+    warnings: &mut Vec<String>,
+) {
+    if *value < min || *value > max {
+        warnings.push(format!(
+            "{} must be between {} and {}, got {}. Using {}.",
+            name, min, max, value, default
+        ));
+        *value = default;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_config_is_default() {
+        let (config, warnings) = Config::from_toml("");
+        assert_eq!(config, Config::default());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_defaults_round_trip() {
+        let text = Config::default().to_toml();
+        let (config, warnings) = Config::from_toml(&text);
+        assert_eq!(config, Config::default());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_partial_config_keeps_other_defaults() {
+        let (config, warnings) = Config::from_toml(
+            "[video]\nscale = 2.0\n\n[input.player2]\na = \"Return\"\n\n[emulation]\nregion = \"pal\"\n",
+        );
+        assert!(warnings.is_empty());
+        assert_eq!(config.video.scale, 2.0);
+        assert_eq!(config.video.palette, DEFAULT_PALETTE);
+        assert_eq!(config.input.player2.a, "Return");
+        assert_eq!(config.input.player2.b, "N");
+        assert_eq!(config.input.player1, InputConfig::default().player1);
+        assert_eq!(config.tv_system(), Some(TvSystem::Pal));
+    }
+
+    #[test]
+    fn test_malformed_toml_falls_back_to_defaults() {
+        let (config, warnings) = Config::from_toml("[video\nscale = ");
+        assert_eq!(config, Config::default());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Malformed"));
+    }
+
+    #[test]
+    fn test_invalid_section_only_resets_that_section() {
+        let (config, warnings) =
+            Config::from_toml("[video]\nscale = \"huge\"\n\n[audio]\nvolume = 60\n");
+        assert_eq!(config.video, VideoConfig::default());
+        assert_eq!(config.audio.volume, 60);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("[video]"));
+    }
+
+    #[test]
+    fn test_out_of_range_values_are_reset() {
+        let (config, warnings) = Config::from_toml(
+            "[video]\nscale = 9.0\n\n[audio]\nvolume = 200\nsample_rate = 48000\n\n[emulation]\nregion = \"secam\"\n",
+        );
+        assert_eq!(config.video.scale, cli::DEFAULT_SCALE);
+        assert_eq!(config.audio.volume, cli::DEFAULT_VOLUME);
+        assert_eq!(config.audio.sample_rate, 48000);
+        assert_eq!(config.tv_system(), None);
+        assert_eq!(warnings.len(), 3);
+    }
+
+    #[test]
+    fn test_rewind_settings() {
+        let (config, warnings) = Config::from_toml(
+            "[hotkeys]\nrewind = \"R\"\n\n[rewind]\nmemory_mb = 8\ninterval = 0\n",
+        );
+        assert_eq!(config.hotkeys.rewind, "R");
+        assert_eq!(config.hotkeys.frame_advance, "F");
+        assert_eq!(config.hotkeys.cheats, "F4");
+        assert!(config.rewind.enabled);
+        assert_eq!(config.rewind.memory_mb, 8);
+        assert_eq!(config.rewind.interval, RewindConfig::default().interval);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("rewind.interval"));
+    }
+
+    #[test]
+    fn test_run_ahead_settings() {
+        assert_eq!(Config::default().run_ahead.frames, 0);
+        let (config, warnings) =
+            Config::from_toml("[run_ahead]\nframes = 2\nsecond_instance = true\n");
+        assert_eq!(config.run_ahead.frames, 2);
+        assert!(config.run_ahead.second_instance);
+        assert!(warnings.is_empty());
+
+        let (config, warnings) = Config::from_toml("[run_ahead]\nframes = 60\n");
+        assert_eq!(config.run_ahead.frames, 0);
+        assert!(warnings[0].contains("run_ahead.frames"));
+    }
+
+    #[test]
+    fn test_unknown_section_is_reported() {
+        let (_, warnings) = Config::from_toml("[cheats]\nenabled = true\n");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("[cheats]"));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("neser-config-test-{}", std::process::id()))
+            .join("config.toml");
+        let mut config = Config::default();
+        config.audio.latency_ms = 50;
+        config.hotkeys.pause = "P".to_string();
+
+        config.save(&path).unwrap();
+        let loaded = Config::load(&path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(loaded, config);
+    }
+
+    #[test]
+    fn test_missing_file_is_default() {
+        assert_eq!(
+            Config::load(Path::new("/nonexistent/neser/config.toml")),
+            Config::default()
+        );
+    }
+
+    #[test]
+    fn test_missing_palette_file_falls_back_to_default() {
+        let mut config = Config::default();
+        config.video.palette = "/nonexistent/palette.pal".to_string();
+        assert_eq!(config.palette(), ppu::default_palette());
+    }
+
+    #[test]
+    fn test_new_settings_parse() {
+        let (config, warnings) = Config::from_toml(
+            "[input]\nport2 = \"zapper\"\n\n[video.overscan]\ntop = 8\nbottom = 8\n\n[emulation]\nsprite_limit = false\n",
+        );
+        assert!(warnings.is_empty());
+        assert_eq!(config.input.port2, InputDevice::Zapper);
+        assert_eq!(config.video.overscan.top, 8);
+        assert_eq!(config.video.overscan.left, 0);
+        assert!(!config.emulation.sprite_limit);
+    }
+
+    #[test]
+    fn test_overrides_apply_on_top_of_config() {
+        let mut config = Config::default();
+        config.video.scale = 2.0;
+        let overrides = GameOverrides {
+            region: Some("pal".to_string()),
+            port2: Some(InputDevice::Zapper),
+            ..Default::default()
+        };
+
+        let game = config.with_overrides(&overrides);
+
+        assert_eq!(game.tv_system(), Some(TvSystem::Pal));
+        assert_eq!(game.input.port2, InputDevice::Zapper);
+        assert_eq!(game.video.scale, 2.0);
+        assert!(game.emulation.sprite_limit);
+    }
+
+    #[test]
+    fn test_invalid_override_values_fall_back() {
+        let overrides = GameOverrides {
+            overscan: Some(Overscan {
+                top: 500,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let game = Config::default().with_overrides(&overrides);
+        assert_eq!(game.video.overscan.top, 0);
+    }
+
+    #[test]
+    fn test_game_overrides_path() {
+        let path = GameOverrides::path(Path::new("/home/u/.config/neser/config.toml"), 0x1A2B);
+        assert_eq!(path, Path::new("/home/u/.config/neser/games/00001A2B.toml"));
+    }
+
+    #[test]
+    fn test_save_and_load_game_overrides() {
+        let dir = std::env::temp_dir().join(format!("neser-game-test-{}", std::process::id()));
+        let config_path = dir.join("config.toml");
+        let mut config = Config::default();
+        config.emulation.sprite_limit = false;
+        config.video.overscan.bottom = 8;
+
+        let saved = config.save_game_overrides(&config_path, 0xCAFE).unwrap();
+        let loaded = Config::default().for_game(&config_path, 0xCAFE);
+        let other = Config::default().for_game(&config_path, 0xBEEF);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(saved.ends_with("games/0000CAFE.toml"));
+        assert!(!loaded.emulation.sprite_limit);
+        assert_eq!(loaded.video.overscan.bottom, 8);
+        assert_eq!(other, Config::default());
+    }
+
+    #[test]
+    fn test_controller_bindings_cover_every_button() {
+        let bindings = InputConfig::default().player1;
+        let buttons = bindings.buttons();
+        assert_eq!(buttons[0], (Button::Up, "Up"));
+        assert_eq!(buttons[7], (Button::Start, "S"));
+    }
+}
@@ -165,7 +165,7 @@ impl Cpu {
         // Track if we're setting a new delay this instruction
         let mut new_delayed_i_flag: Option<bool> = None;
 
-        let opcode_byte = self.memory.borrow().read(self.pc);
+        let opcode_byte = self.memory.borrow_mut().read(self.pc);
         self.pc += 1;
 
         let opcode = super::opcode::lookup(opcode_byte)
@@ -179,18 +179,18 @@ impl Cpu {
             }
             ADC_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.adc(value);
             }
             ADC_ZPX => {
                 let base = self.read_byte();
                 let addr = base.wrapping_add(self.x) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.adc(value);
             }
             ADC_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.adc(value);
             }
             ADC_ABSX => {
@@ -199,10 +199,10 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.adc(value);
             }
             ADC_ABSY => {
@@ -211,17 +211,17 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.adc(value);
             }
             ADC_INDX => {
                 let base = self.read_byte();
                 let ptr = base.wrapping_add(self.x);
                 let addr = self.read_word_from_zp(ptr);
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.adc(value);
             }
             ADC_INDY => {
@@ -231,10 +231,10 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.adc(value);
             }
             AND_IMM => {
@@ -243,18 +243,18 @@ impl Cpu {
             }
             AND_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.and(value);
             }
             AND_ZPX => {
                 let base = self.read_byte();
                 let addr = base.wrapping_add(self.x) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.and(value);
             }
             AND_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.and(value);
             }
             AND_ABSX => {
@@ -263,10 +263,10 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.and(value);
             }
             AND_ABSY => {
@@ -275,17 +275,17 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.and(value);
             }
             AND_INDX => {
                 let base = self.read_byte();
                 let ptr = base.wrapping_add(self.x);
                 let addr = self.read_word_from_zp(ptr);
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.and(value);
             }
             AND_INDY => {
@@ -295,10 +295,10 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.and(value);
             }
             ASL_A => {
@@ -306,7 +306,7 @@ impl Cpu {
             }
             ASL_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -316,7 +316,7 @@ impl Cpu {
             ASL_ZPX => {
                 let base = self.read_byte();
                 let addr = base.wrapping_add(self.x) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -325,7 +325,7 @@ impl Cpu {
             }
             ASL_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -338,12 +338,12 @@ impl Cpu {
                 // RMW instructions ALWAYS read during indexed address calculation
                 // If page crossed, read from wrong address; otherwise from correct address
                 if (base & 0xFF) + (self.x as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -352,12 +352,12 @@ impl Cpu {
             }
             BIT_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.bit(value);
             }
             BIT_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.bit(value);
             }
             BCC => {
@@ -454,8 +454,8 @@ impl Cpu {
                 // The NES loop must set nmi_pending before calling run_opcode if NMI is active
 
                 // Cycles 1-2: Dummy reads
-                let _ = self.memory.borrow().read(self.pc);
-                let _ = self.memory.borrow().read(self.pc);
+                let _ = self.memory.borrow_mut().read(self.pc);
+                let _ = self.memory.borrow_mut().read(self.pc);
                 self.pc += 1; // Move past padding byte
 
                 // Cycles 3-4: Push PC
@@ -479,7 +479,7 @@ impl Cpu {
 
                 // Cycles 6-7: Read interrupt vector (NMI if hijacked, otherwise IRQ)
                 let vector = if use_nmi { NMI_VECTOR } else { IRQ_VECTOR };
-                self.pc = self.memory.borrow().read_u16(vector);
+                self.pc = self.memory.borrow_mut().read_u16(vector);
 
                 cycles = 7;
             }
@@ -489,18 +489,18 @@ impl Cpu {
             }
             CMP_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.cmp(value);
             }
             CMP_ZPX => {
                 let base = self.read_byte();
                 let addr = base.wrapping_add(self.x) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.cmp(value);
             }
             CMP_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.cmp(value);
             }
             CMP_ABSX => {
@@ -509,10 +509,10 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.cmp(value);
             }
             CMP_ABSY => {
@@ -521,17 +521,17 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.cmp(value);
             }
             CMP_INDX => {
                 let base = self.read_byte();
                 let ptr = base.wrapping_add(self.x);
                 let addr = self.read_word_from_zp(ptr);
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.cmp(value);
             }
             CMP_INDY => {
@@ -541,10 +541,10 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.cmp(value);
             }
             CPX_IMM => {
@@ -553,12 +553,12 @@ impl Cpu {
             }
             CPX_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.cpx(value);
             }
             CPX_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.cpx(value);
             }
             CPY_IMM => {
@@ -567,17 +567,17 @@ impl Cpu {
             }
             CPY_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.cpy(value);
             }
             CPY_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.cpy(value);
             }
             DEC_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -586,7 +586,7 @@ impl Cpu {
             }
             DEC_ZPX => {
                 let addr = self.read_byte().wrapping_add(self.x) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -595,7 +595,7 @@ impl Cpu {
             }
             DEC_ABS => {
                 let addr = self.read_word() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -607,12 +607,12 @@ impl Cpu {
                 let addr = base.wrapping_add(self.x as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.x as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -625,17 +625,17 @@ impl Cpu {
             }
             EOR_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.eor(value);
             }
             EOR_ZPX => {
                 let addr = self.read_byte().wrapping_add(self.x) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.eor(value);
             }
             EOR_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.eor(value);
             }
             EOR_ABSX => {
@@ -644,10 +644,10 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.eor(value);
             }
             EOR_ABSY => {
@@ -656,16 +656,16 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.eor(value);
             }
             EOR_INDX => {
                 let ptr = self.read_byte().wrapping_add(self.x);
                 let addr = self.read_word_from_zp(ptr);
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.eor(value);
             }
             EOR_INDY => {
@@ -675,10 +675,10 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.eor(value);
             }
             CLC => {
@@ -712,7 +712,7 @@ impl Cpu {
             }
             INC_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -721,7 +721,7 @@ impl Cpu {
             }
             INC_ZPX => {
                 let addr = self.read_byte().wrapping_add(self.x) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -730,7 +730,7 @@ impl Cpu {
             }
             INC_ABS => {
                 let addr = self.read_word() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -742,12 +742,12 @@ impl Cpu {
                 let addr = base.wrapping_add(self.x as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.x as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -775,17 +775,17 @@ impl Cpu {
             }
             LDA_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.lda(value);
             }
             LDA_ZPX => {
                 let addr = self.read_byte().wrapping_add(self.x) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.lda(value);
             }
             LDA_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.lda(value);
             }
             LDA_ABSX => {
@@ -796,9 +796,9 @@ impl Cpu {
                     // Perform dummy read from base + X (without carry from low byte)
                     let dummy_addr =
                         (base & 0xFF00) | ((base.wrapping_add(self.x as u16)) & 0x00FF);
-                    self.memory.borrow().read(dummy_addr);
+                    self.memory.borrow_mut().read(dummy_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.lda(value);
             }
             LDA_ABSY => {
@@ -809,15 +809,15 @@ impl Cpu {
                     // Perform dummy read from base + Y (without carry from low byte)
                     let dummy_addr =
                         (base & 0xFF00) | ((base.wrapping_add(self.y as u16)) & 0x00FF);
-                    self.memory.borrow().read(dummy_addr);
+                    self.memory.borrow_mut().read(dummy_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.lda(value);
             }
             LDA_INDX => {
                 let ptr = self.read_byte().wrapping_add(self.x);
                 let addr = self.read_word_from_zp(ptr);
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.lda(value);
             }
             LDA_INDY => {
@@ -829,9 +829,9 @@ impl Cpu {
                     // Perform dummy read from base + Y (without carry from low byte)
                     let dummy_addr =
                         (base & 0xFF00) | ((base.wrapping_add(self.y as u16)) & 0x00FF);
-                    self.memory.borrow().read(dummy_addr);
+                    self.memory.borrow_mut().read(dummy_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.lda(value);
             }
             LDX_IMM => {
@@ -840,17 +840,17 @@ impl Cpu {
             }
             LDX_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ldx(value);
             }
             LDX_ZPY => {
                 let addr = self.read_byte().wrapping_add(self.y) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ldx(value);
             }
             LDX_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ldx(value);
             }
             LDX_ABSY => {
@@ -861,9 +861,9 @@ impl Cpu {
                     // Perform dummy read from base + Y (without carry from low byte)
                     let dummy_addr =
                         (base & 0xFF00) | ((base.wrapping_add(self.y as u16)) & 0x00FF);
-                    self.memory.borrow().read(dummy_addr);
+                    self.memory.borrow_mut().read(dummy_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ldx(value);
             }
             LDY_IMM => {
@@ -872,17 +872,17 @@ impl Cpu {
             }
             LDY_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ldy(value);
             }
             LDY_ZPX => {
                 let addr = self.read_byte().wrapping_add(self.x) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ldy(value);
             }
             LDY_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ldy(value);
             }
             LDY_ABSX => {
@@ -893,9 +893,9 @@ impl Cpu {
                     // Perform dummy read from base + X (without carry from low byte)
                     let dummy_addr =
                         (base & 0xFF00) | ((base.wrapping_add(self.x as u16)) & 0x00FF);
-                    self.memory.borrow().read(dummy_addr);
+                    self.memory.borrow_mut().read(dummy_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ldy(value);
             }
             LSR_ACC => {
@@ -903,7 +903,7 @@ impl Cpu {
             }
             LSR_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -912,7 +912,7 @@ impl Cpu {
             }
             LSR_ZPX => {
                 let addr = self.read_byte().wrapping_add(self.x) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -921,7 +921,7 @@ impl Cpu {
             }
             LSR_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -933,12 +933,12 @@ impl Cpu {
                 let addr = base.wrapping_add(self.x as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.x as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -954,17 +954,17 @@ impl Cpu {
             }
             ORA_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ora(value);
             }
             ORA_ZPX => {
                 let addr = self.read_byte().wrapping_add(self.x) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ora(value);
             }
             ORA_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ora(value);
             }
             ORA_ABSX => {
@@ -973,10 +973,10 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ora(value);
             }
             ORA_ABSY => {
@@ -985,16 +985,16 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ora(value);
             }
             ORA_INDX => {
                 let ptr = self.read_byte().wrapping_add(self.x);
                 let addr = self.read_word_from_zp(ptr);
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ora(value);
             }
             ORA_INDY => {
@@ -1004,10 +1004,10 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.ora(value);
             }
             DEX => {
@@ -1039,7 +1039,7 @@ impl Cpu {
             }
             ROL_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -1048,7 +1048,7 @@ impl Cpu {
             }
             ROL_ZPX => {
                 let addr = self.read_byte().wrapping_add(self.x) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -1057,7 +1057,7 @@ impl Cpu {
             }
             ROL_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -1069,12 +1069,12 @@ impl Cpu {
                 let addr = base.wrapping_add(self.x as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.x as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -1086,7 +1086,7 @@ impl Cpu {
             }
             ROR_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -1095,7 +1095,7 @@ impl Cpu {
             }
             ROR_ZPX => {
                 let addr = self.read_byte().wrapping_add(self.x) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -1104,7 +1104,7 @@ impl Cpu {
             }
             ROR_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -1116,12 +1116,12 @@ impl Cpu {
                 let addr = base.wrapping_add(self.x as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.x as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 // Dummy write
                 self.memory.borrow_mut().write(addr, value, true);
                 // Real operation and write
@@ -1130,7 +1130,7 @@ impl Cpu {
             }
             RTI => {
                 // Dummy read of next byte (after RTI opcode) before popping from stack
-                let _ = self.memory.borrow().read(self.pc);
+                let _ = self.memory.borrow_mut().read(self.pc);
                 let value = self.pop_byte();
                 // RTI behaves like PLP - ignores B flag and unused bit
                 // Load bits 0-3 and 6-7 from stack, always set unused bit to 1, clear B flag
@@ -1141,7 +1141,7 @@ impl Cpu {
             }
             RTS => {
                 // Dummy read of next byte (after RTS opcode) before popping return address
-                let _ = self.memory.borrow().read(self.pc);
+                let _ = self.memory.borrow_mut().read(self.pc);
                 self.pc = self.pop_word();
                 self.pc = self.pc.wrapping_add(1);
             }
@@ -1152,17 +1152,17 @@ impl Cpu {
             }
             SBC_ZP => {
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.sbc(value);
             }
             SBC_ZPX => {
                 let addr = self.read_byte().wrapping_add(self.x) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.sbc(value);
             }
             SBC_ABS => {
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.sbc(value);
             }
             SBC_ABSX => {
@@ -1171,10 +1171,10 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.sbc(value);
             }
             SBC_ABSY => {
@@ -1183,16 +1183,16 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.sbc(value);
             }
             SBC_INDX => {
                 let ptr = self.read_byte().wrapping_add(self.x);
                 let addr = self.read_word_from_zp(ptr);
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.sbc(value);
             }
             SBC_INDY => {
@@ -1202,10 +1202,10 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.sbc(value);
             }
             STA_ZP => {
@@ -1225,7 +1225,7 @@ impl Cpu {
                 let addr = base.wrapping_add(self.x as u16);
                 // STA always performs a dummy read before the write
                 let dummy_addr = (base & 0xFF00) | ((base.wrapping_add(self.x as u16)) & 0x00FF);
-                self.memory.borrow().read(dummy_addr);
+                self.memory.borrow_mut().read(dummy_addr);
                 self.memory.borrow_mut().write(addr, self.a, false);
             }
             SXA_ABSY => {
@@ -1236,7 +1236,7 @@ impl Cpu {
                 // Write instructions ALWAYS perform dummy read during indexed address calculation
                 let dummy_addr =
                     (base_addr & 0xFF00) | ((base_addr.wrapping_add(self.y as u16)) & 0x00FF);
-                self.memory.borrow().read(dummy_addr);
+                self.memory.borrow_mut().read(dummy_addr);
 
                 let page_crossed = (base_addr & 0xFF00) != (addr_no_cross & 0xFF00);
                 let high_byte = ((base_addr >> 8) as u8).wrapping_add(1);
@@ -1260,7 +1260,7 @@ impl Cpu {
                 // Write instructions ALWAYS perform dummy read during indexed address calculation
                 let dummy_addr =
                     (base_addr & 0xFF00) | ((base_addr.wrapping_add(self.x as u16)) & 0x00FF);
-                self.memory.borrow().read(dummy_addr);
+                self.memory.borrow_mut().read(dummy_addr);
 
                 let page_crossed = (base_addr & 0xFF00) != (addr_no_cross & 0xFF00);
                 let high_byte = ((base_addr >> 8) as u8).wrapping_add(1);
@@ -1281,7 +1281,7 @@ impl Cpu {
                 let addr = base.wrapping_add(self.y as u16);
                 // STA always performs a dummy read before the write
                 let dummy_addr = (base & 0xFF00) | ((base.wrapping_add(self.y as u16)) & 0x00FF);
-                self.memory.borrow().read(dummy_addr);
+                self.memory.borrow_mut().read(dummy_addr);
                 self.memory.borrow_mut().write(addr, self.a, false);
             }
             STA_INDX => {
@@ -1295,7 +1295,7 @@ impl Cpu {
                 let addr = base.wrapping_add(self.y as u16);
                 // STA always performs a dummy read before the write
                 let dummy_addr = (base & 0xFF00) | ((base.wrapping_add(self.y as u16)) & 0x00FF);
-                self.memory.borrow().read(dummy_addr);
+                self.memory.borrow_mut().read(dummy_addr);
                 self.memory.borrow_mut().write(addr, self.a, false);
             }
             TXS => {
@@ -1445,7 +1445,7 @@ impl Cpu {
                 // Write instructions ALWAYS perform dummy read during indexed address calculation
                 let dummy_addr =
                     (base_addr & 0xFF00) | ((base_addr.wrapping_add(self.y as u16)) & 0x00FF);
-                self.memory.borrow().read(dummy_addr);
+                self.memory.borrow_mut().read(dummy_addr);
                 let high_byte = (addr >> 8) as u8;
                 let value = self.a & self.x & high_byte.wrapping_add(1);
                 self.memory.borrow_mut().write(addr, value, false);
@@ -1457,7 +1457,7 @@ impl Cpu {
                 // Write instructions ALWAYS perform dummy read during indexed address calculation
                 let dummy_addr =
                     (base_addr & 0xFF00) | ((base_addr.wrapping_add(self.y as u16)) & 0x00FF);
-                self.memory.borrow().read(dummy_addr);
+                self.memory.borrow_mut().read(dummy_addr);
                 let high_byte = (addr >> 8) as u8;
                 let value = self.a & self.x & high_byte.wrapping_add(1);
                 self.memory.borrow_mut().write(addr, value, false);
@@ -1497,10 +1497,11 @@ impl Cpu {
                 let addr = base_addr.wrapping_add(self.y as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base_addr & 0xFF) + (self.y as u16) > 0xFF {
-                    let wrong_addr = (base_addr & 0xFF00) | ((base_addr + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr =
+                        (base_addr & 0xFF00) | (base_addr.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.dcp(addr);
             }
@@ -1515,10 +1516,10 @@ impl Cpu {
                 let addr = base.wrapping_add(self.y as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.y as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.dcp(addr);
             }
@@ -1528,10 +1529,10 @@ impl Cpu {
                 let addr = base.wrapping_add(self.x as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.x as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.dcp(addr);
             }
@@ -1543,8 +1544,11 @@ impl Cpu {
             ISB_INDX => {
                 // Undocumented: Increment memory then subtract from A with borrow
                 let zp_addr = self.read_byte().wrapping_add(self.x);
-                let addr_lo = self.memory.borrow().read(zp_addr as u16);
-                let addr_hi = self.memory.borrow().read(zp_addr.wrapping_add(1) as u16);
+                let addr_lo = self.memory.borrow_mut().read(zp_addr as u16);
+                let addr_hi = self
+                    .memory
+                    .borrow_mut()
+                    .read(zp_addr.wrapping_add(1) as u16);
                 let addr = u16::from_le_bytes([addr_lo, addr_hi]);
                 self.isc(addr);
             }
@@ -1561,16 +1565,20 @@ impl Cpu {
             ISB_INDY => {
                 // Undocumented: Increment memory then subtract from A with borrow
                 let zp_addr = self.read_byte();
-                let addr_lo = self.memory.borrow().read(zp_addr as u16);
-                let addr_hi = self.memory.borrow().read(zp_addr.wrapping_add(1) as u16);
+                let addr_lo = self.memory.borrow_mut().read(zp_addr as u16);
+                let addr_hi = self
+                    .memory
+                    .borrow_mut()
+                    .read(zp_addr.wrapping_add(1) as u16);
                 let base_addr = u16::from_le_bytes([addr_lo, addr_hi]);
                 let addr = base_addr.wrapping_add(self.y as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base_addr & 0xFF) + (self.y as u16) > 0xFF {
-                    let wrong_addr = (base_addr & 0xFF00) | ((base_addr + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr =
+                        (base_addr & 0xFF00) | (base_addr.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.isc(addr);
             }
@@ -1585,10 +1593,10 @@ impl Cpu {
                 let addr = base.wrapping_add(self.y as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.y as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.isc(addr);
             }
@@ -1598,10 +1606,10 @@ impl Cpu {
                 let addr = base.wrapping_add(self.x as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.x as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.isc(addr);
             }
@@ -1617,10 +1625,10 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 let result = self.sp & value;
                 self.a = result;
                 self.x = result;
@@ -1641,10 +1649,10 @@ impl Cpu {
                 // Undocumented: Load A and X with memory value (LDA + LDX)
                 let base = self.read_byte();
                 let ptr = base.wrapping_add(self.x);
-                let lo = self.memory.borrow().read(ptr as u16) as u16;
-                let hi = self.memory.borrow().read(ptr.wrapping_add(1) as u16) as u16;
+                let lo = self.memory.borrow_mut().read(ptr as u16) as u16;
+                let hi = self.memory.borrow_mut().read(ptr.wrapping_add(1) as u16) as u16;
                 let addr = (hi << 8) | lo;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.a = value;
                 self.x = value;
                 self.update_zero_and_negative_flags(value);
@@ -1652,7 +1660,7 @@ impl Cpu {
             LAX_ZP => {
                 // Undocumented: Load A and X with memory value (LDA + LDX)
                 let addr = self.read_byte() as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.a = value;
                 self.x = value;
                 self.update_zero_and_negative_flags(value);
@@ -1660,7 +1668,7 @@ impl Cpu {
             LAX_ABS => {
                 // Undocumented: Load A and X with memory value (LDA + LDX)
                 let addr = self.read_word();
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.a = value;
                 self.x = value;
                 self.update_zero_and_negative_flags(value);
@@ -1668,17 +1676,17 @@ impl Cpu {
             LAX_INDY => {
                 // Undocumented: Load A and X with memory value (LDA + LDX)
                 let ptr = self.read_byte() as u16;
-                let lo = self.memory.borrow().read(ptr) as u16;
-                let hi = self.memory.borrow().read((ptr + 1) & 0xFF) as u16;
+                let lo = self.memory.borrow_mut().read(ptr) as u16;
+                let hi = self.memory.borrow_mut().read((ptr + 1) & 0xFF) as u16;
                 let base = (hi << 8) | lo;
                 let addr = base.wrapping_add(self.y as u16);
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.a = value;
                 self.x = value;
                 self.update_zero_and_negative_flags(value);
@@ -1687,7 +1695,7 @@ impl Cpu {
                 // Undocumented: Load A and X with memory value (LDA + LDX)
                 let base = self.read_byte();
                 let addr = base.wrapping_add(self.y) as u16;
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.a = value;
                 self.x = value;
                 self.update_zero_and_negative_flags(value);
@@ -1699,10 +1707,10 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
-                let value = self.memory.borrow().read(addr);
+                let value = self.memory.borrow_mut().read(addr);
                 self.a = value;
                 self.x = value;
                 self.update_zero_and_negative_flags(value);
@@ -1734,10 +1742,11 @@ impl Cpu {
                 let addr = base_addr.wrapping_add(self.y as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base_addr & 0xFF) + (self.y as u16) > 0xFF {
-                    let wrong_addr = (base_addr & 0xFF00) | ((base_addr + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr =
+                        (base_addr & 0xFF00) | (base_addr.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.rla(addr);
             }
@@ -1752,10 +1761,10 @@ impl Cpu {
                 let addr = base.wrapping_add(self.y as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.y as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.rla(addr);
             }
@@ -1765,10 +1774,10 @@ impl Cpu {
                 let addr = base.wrapping_add(self.x as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.x as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.rla(addr);
             }
@@ -1795,10 +1804,11 @@ impl Cpu {
                 let addr = base_addr.wrapping_add(self.y as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base_addr & 0xFF) + (self.y as u16) > 0xFF {
-                    let wrong_addr = (base_addr & 0xFF00) | ((base_addr + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr =
+                        (base_addr & 0xFF00) | (base_addr.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.rra(addr);
             }
@@ -1813,10 +1823,10 @@ impl Cpu {
                 let addr = base.wrapping_add(self.y as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.y as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.rra(addr);
             }
@@ -1826,10 +1836,10 @@ impl Cpu {
                 let addr = base.wrapping_add(self.x as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.x as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.rra(addr);
             }
@@ -1856,10 +1866,11 @@ impl Cpu {
                 let addr = base_addr.wrapping_add(self.y as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base_addr & 0xFF) + (self.y as u16) > 0xFF {
-                    let wrong_addr = (base_addr & 0xFF00) | ((base_addr + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr =
+                        (base_addr & 0xFF00) | (base_addr.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.slo(addr);
             }
@@ -1874,10 +1885,10 @@ impl Cpu {
                 let addr = base.wrapping_add(self.y as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.y as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.slo(addr);
             }
@@ -1887,10 +1898,10 @@ impl Cpu {
                 let addr = base.wrapping_add(self.x as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.x as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.slo(addr);
             }
@@ -1917,10 +1928,11 @@ impl Cpu {
                 let addr = base_addr.wrapping_add(self.y as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base_addr & 0xFF) + (self.y as u16) > 0xFF {
-                    let wrong_addr = (base_addr & 0xFF00) | ((base_addr + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr =
+                        (base_addr & 0xFF00) | (base_addr.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.sre(addr);
             }
@@ -1935,10 +1947,10 @@ impl Cpu {
                 let addr = base.wrapping_add(self.y as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.y as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.y as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.y as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.sre(addr);
             }
@@ -1948,10 +1960,10 @@ impl Cpu {
                 let addr = base.wrapping_add(self.x as u16);
                 // RMW instructions ALWAYS read during indexed address calculation
                 if (base & 0xFF) + (self.x as u16) > 0xFF {
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 } else {
-                    self.memory.borrow().read(addr);
+                    self.memory.borrow_mut().read(addr);
                 }
                 self.sre(addr);
             }
@@ -1966,11 +1978,11 @@ impl Cpu {
                 if Self::page_crossed(base, addr) {
                     cycles += 1;
                     // Perform dummy read from wrong address (without carry)
-                    let wrong_addr = (base & 0xFF00) | ((base + self.x as u16) & 0x00FF);
-                    self.memory.borrow().read(wrong_addr);
+                    let wrong_addr = (base & 0xFF00) | (base.wrapping_add(self.x as u16) & 0x00FF);
+                    self.memory.borrow_mut().read(wrong_addr);
                 }
                 // Still perform the final read to fully emulate hardware behavior
-                self.memory.borrow().read(addr);
+                self.memory.borrow_mut().read(addr);
             }
             XAA_IMM => {
                 // Undocumented: Highly unstable opcode
@@ -1988,7 +2000,7 @@ impl Cpu {
                 // Write instructions ALWAYS perform dummy read during indexed address calculation
                 let dummy_addr =
                     (base_addr & 0xFF00) | ((base_addr.wrapping_add(self.y as u16)) & 0x00FF);
-                self.memory.borrow().read(dummy_addr);
+                self.memory.borrow_mut().read(dummy_addr);
                 self.sp = self.a & self.x;
                 let high_byte = (base_addr >> 8) as u8;
                 let result = self.sp & high_byte.wrapping_add(1);
@@ -2016,7 +2028,7 @@ impl Cpu {
         // If no instruction is in progress, start a new one
         if self.current_instruction.is_none() {
             // Read the opcode byte before executing
-            let opcode_byte = self.memory.borrow().read(self.pc);
+            let opcode_byte = self.memory.borrow_mut().read(self.pc);
             // Debug output only for addresses near the critical test section
             if self.pc >= 0xC000
                 && self.pc < 0xC100
@@ -2135,11 +2147,11 @@ impl Cpu {
             0 => {
                 // Cycle 1: Increment PC past opcode, first dummy read
                 self.pc += 1;
-                let _ = self.memory.borrow().read(self.pc);
+                let _ = self.memory.borrow_mut().read(self.pc);
             }
             1 => {
                 // Cycle 2: Second dummy read at PC (padding byte), then increment PC
-                let _ = self.memory.borrow().read(self.pc);
+                let _ = self.memory.borrow_mut().read(self.pc);
                 self.pc += 1;
             }
             2 => {
@@ -2176,7 +2188,7 @@ impl Cpu {
                 // Cycle 6: Read vector low byte
                 if let Some(ref inst) = self.current_instruction.clone() {
                     let vector = inst.temp_addr.unwrap();
-                    let lo = self.memory.borrow().read(vector) as u16;
+                    let lo = self.memory.borrow_mut().read(vector) as u16;
                     if let Some(ref mut inst) = self.current_instruction {
                         inst.temp_value = Some(lo as u8);
                     }
@@ -2187,7 +2199,7 @@ impl Cpu {
                 if let Some(ref inst) = self.current_instruction.clone() {
                     let vector = inst.temp_addr.unwrap();
                     let lo = inst.temp_value.unwrap() as u16;
-                    let hi = self.memory.borrow().read(vector + 1) as u16;
+                    let hi = self.memory.borrow_mut().read(vector + 1) as u16;
                     self.pc = (hi << 8) | lo;
                 }
             }
@@ -2277,7 +2289,7 @@ impl Cpu {
 
     /// Read a byte from memory at PC and increment PC
     fn read_byte(&mut self) -> u8 {
-        let value = self.memory.borrow().read(self.pc);
+        let value = self.memory.borrow_mut().read(self.pc);
         self.pc += 1;
         value
     }
@@ -2293,13 +2305,13 @@ impl Cpu {
 
     /// Read a 16-bit address from the reset vector at 0xFFFC-0xFFFD
     fn read_reset_vector(&self) -> u16 {
-        self.memory.borrow().read_u16(RESET_VECTOR)
+        self.memory.borrow_mut().read_u16(RESET_VECTOR)
     }
 
     /// Read a 16-bit word from zero page (wraps at page boundary)
     fn read_word_from_zp(&self, addr: u8) -> u16 {
-        let lo = self.memory.borrow().read(addr as u16) as u16;
-        let hi = self.memory.borrow().read(addr.wrapping_add(1) as u16) as u16;
+        let lo = self.memory.borrow_mut().read(addr as u16) as u16;
+        let hi = self.memory.borrow_mut().read(addr.wrapping_add(1) as u16) as u16;
         (hi << 8) | lo
     }
 
@@ -2307,14 +2319,14 @@ impl Cpu {
     /// If the address is at a page boundary (e.g., 0x10FF), the high byte
     /// is read from the start of the same page (0x1000) instead of the next page (0x1100)
     fn read_word_indirect(&self, addr: u16) -> u16 {
-        let lo = self.memory.borrow().read(addr) as u16;
+        let lo = self.memory.borrow_mut().read(addr) as u16;
         let hi_addr = if addr & 0xFF == 0xFF {
             // Page boundary bug: wrap within the same page
             addr & 0xFF00
         } else {
             addr + 1
         };
-        let hi = self.memory.borrow().read(hi_addr) as u16;
+        let hi = self.memory.borrow_mut().read(hi_addr) as u16;
         (hi << 8) | lo
     }
 
@@ -2335,7 +2347,7 @@ impl Cpu {
     fn pop_byte(&mut self) -> u8 {
         self.sp = self.sp.wrapping_add(1);
         let addr = 0x0100 | (self.sp as u16);
-        self.memory.borrow().read(addr)
+        self.memory.borrow_mut().read(addr)
     }
 
     /// Pull a word from the stack (low byte first)
@@ -2484,7 +2496,7 @@ impl Cpu {
 
     /// Decrement and Compare - DCP undocumented operation
     fn dcp(&mut self, addr: u16) {
-        let value = self.memory.borrow().read(addr);
+        let value = self.memory.borrow_mut().read(addr);
         // Dummy write
         self.memory.borrow_mut().write(addr, value, true);
         // Real operation and write
@@ -2508,7 +2520,7 @@ impl Cpu {
 
     /// ISC - Undocumented opcode: Increment memory then subtract from A with borrow
     fn isc(&mut self, addr: u16) {
-        let value = self.memory.borrow().read(addr);
+        let value = self.memory.borrow_mut().read(addr);
         // Dummy write
         self.memory.borrow_mut().write(addr, value, true);
         // Real operation and write
@@ -2519,7 +2531,7 @@ impl Cpu {
 
     /// RLA - Undocumented opcode: Rotate left memory then AND with accumulator
     fn rla(&mut self, addr: u16) {
-        let value = self.memory.borrow().read(addr);
+        let value = self.memory.borrow_mut().read(addr);
         // Dummy write
         self.memory.borrow_mut().write(addr, value, true);
         // Real operation and write
@@ -2531,7 +2543,7 @@ impl Cpu {
 
     /// RRA - Undocumented opcode: Rotate right memory then ADC with accumulator
     fn rra(&mut self, addr: u16) {
-        let value = self.memory.borrow().read(addr);
+        let value = self.memory.borrow_mut().read(addr);
         // Dummy write
         self.memory.borrow_mut().write(addr, value, true);
         // Real operation and write
@@ -2542,7 +2554,7 @@ impl Cpu {
 
     /// SLO - Undocumented opcode: Shift left memory then ORA with accumulator
     fn slo(&mut self, addr: u16) {
-        let value = self.memory.borrow().read(addr);
+        let value = self.memory.borrow_mut().read(addr);
         // Dummy write
         self.memory.borrow_mut().write(addr, value, true);
         // Real operation and write
@@ -2553,7 +2565,7 @@ impl Cpu {
 
     /// SRE - Undocumented opcode: Shift right memory then EOR with accumulator
     fn sre(&mut self, addr: u16) {
-        let value = self.memory.borrow().read(addr);
+        let value = self.memory.borrow_mut().read(addr);
         // Dummy write
         self.memory.borrow_mut().write(addr, value, true);
         // Real operation and write
@@ -2720,7 +2732,7 @@ impl Cpu {
         self.push_byte(p_with_break);
 
         // Set PC to NMI vector
-        self.pc = self.memory.borrow().read_u16(NMI_VECTOR);
+        self.pc = self.memory.borrow_mut().read_u16(NMI_VECTOR);
 
         // Set Interrupt Disable flag
         self.p |= FLAG_INTERRUPT;
@@ -2759,7 +2771,7 @@ impl Cpu {
         self.push_byte(p_with_break);
 
         // Set PC to IRQ vector
-        self.pc = self.memory.borrow().read_u16(IRQ_VECTOR);
+        self.pc = self.memory.borrow_mut().read_u16(IRQ_VECTOR);
 
         // Set Interrupt Disable flag
         self.p |= FLAG_INTERRUPT;
@@ -2785,11 +2797,7 @@ mod tests {
 
     // Test helper function to create a Memory instance with a PPU for testing
     fn create_test_memory() -> MemController {
-        let ppu = Rc::new(RefCell::new(crate::ppu::Ppu::new(
-            crate::nes::TvSystem::Ntsc,
-        )));
-        let apu = Rc::new(RefCell::new(crate::apu::Apu::new()));
-        MemController::new(ppu, apu)
+        MemController::new(crate::nes::TvSystem::Ntsc)
     }
 
     // Test helper function to run the CPU until halted (KIL instruction)
@@ -3232,7 +3240,7 @@ mod tests {
         cpu.reset();
         cpu.memory.borrow_mut().write(0x42, 0b0011_0011, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x42), 0b0110_0110);
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0b0110_0110);
         assert_eq!(cpu.p & FLAG_CARRY, 0);
     }
 
@@ -3246,7 +3254,7 @@ mod tests {
         cpu.x = 0x05;
         cpu.memory.borrow_mut().write(0x47, 0b1010_0101, false); // 0x42 + 0x05
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x47), 0b0100_1010);
+        assert_eq!(cpu.memory.borrow_mut().read(0x47), 0b0100_1010);
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY);
     }
 
@@ -3259,7 +3267,7 @@ mod tests {
         cpu.reset();
         cpu.memory.borrow_mut().write(0x1234, 0b0100_0001, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1234), 0b1000_0010);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1234), 0b1000_0010);
         assert_eq!(cpu.p & FLAG_NEGATIVE, FLAG_NEGATIVE);
     }
 
@@ -3273,7 +3281,7 @@ mod tests {
         cpu.x = 0x10;
         cpu.memory.borrow_mut().write(0x1244, 0b0000_0001, false); // 0x1234 + 0x10
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1244), 0b0000_0010);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1244), 0b0000_0010);
         assert_eq!(cpu.p & FLAG_CARRY, 0);
         assert_eq!(cpu.p & FLAG_ZERO, 0);
     }
@@ -3852,7 +3860,7 @@ mod tests {
         cpu.reset();
         cpu.memory.borrow_mut().write(0x42, 0x50, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x42), 0x4F);
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0x4F);
         assert_eq!(cpu.p & FLAG_ZERO, 0);
         assert_eq!(cpu.p & FLAG_NEGATIVE, 0);
     }
@@ -3866,7 +3874,7 @@ mod tests {
         cpu.reset();
         cpu.memory.borrow_mut().write(0x42, 0x01, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x42), 0x00);
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0x00);
         assert_eq!(cpu.p & FLAG_ZERO, FLAG_ZERO);
         assert_eq!(cpu.p & FLAG_NEGATIVE, 0);
     }
@@ -3880,7 +3888,7 @@ mod tests {
         cpu.reset();
         cpu.memory.borrow_mut().write(0x42, 0x00, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x42), 0xFF);
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0xFF);
         assert_eq!(cpu.p & FLAG_ZERO, 0);
         assert_eq!(cpu.p & FLAG_NEGATIVE, FLAG_NEGATIVE);
     }
@@ -3895,7 +3903,7 @@ mod tests {
         cpu.x = 0x05;
         cpu.memory.borrow_mut().write(0x47, 0x80, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x47), 0x7F);
+        assert_eq!(cpu.memory.borrow_mut().read(0x47), 0x7F);
         assert_eq!(cpu.p & FLAG_NEGATIVE, 0);
     }
 
@@ -3908,7 +3916,7 @@ mod tests {
         cpu.reset();
         cpu.memory.borrow_mut().write(0x1234, 0x30, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1234), 0x2F);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1234), 0x2F);
         assert_eq!(cpu.p & FLAG_NEGATIVE, 0);
     }
 
@@ -3922,7 +3930,7 @@ mod tests {
         cpu.x = 0x10;
         cpu.memory.borrow_mut().write(0x1244, 0x90, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1244), 0x8F);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1244), 0x8F);
         assert_eq!(cpu.p & FLAG_NEGATIVE, FLAG_NEGATIVE);
     }
 
@@ -4159,7 +4167,7 @@ mod tests {
         cpu.reset();
         cpu.memory.borrow_mut().write(0x42, 0x50, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x42), 0x51);
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0x51);
         assert_eq!(cpu.p & FLAG_ZERO, 0);
         assert_eq!(cpu.p & FLAG_NEGATIVE, 0);
     }
@@ -4173,7 +4181,7 @@ mod tests {
         cpu.reset();
         cpu.memory.borrow_mut().write(0x42, 0xFF, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x42), 0x00);
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0x00);
         assert_eq!(cpu.p & FLAG_ZERO, FLAG_ZERO);
         assert_eq!(cpu.p & FLAG_NEGATIVE, 0);
     }
@@ -4187,7 +4195,7 @@ mod tests {
         cpu.reset();
         cpu.memory.borrow_mut().write(0x42, 0x7F, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x42), 0x80);
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0x80);
         assert_eq!(cpu.p & FLAG_ZERO, 0);
         assert_eq!(cpu.p & FLAG_NEGATIVE, FLAG_NEGATIVE);
     }
@@ -4202,7 +4210,7 @@ mod tests {
         cpu.x = 0x05;
         cpu.memory.borrow_mut().write(0x47, 0x20, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x47), 0x21);
+        assert_eq!(cpu.memory.borrow_mut().read(0x47), 0x21);
         assert_eq!(cpu.p & FLAG_NEGATIVE, 0);
     }
 
@@ -4215,7 +4223,7 @@ mod tests {
         cpu.reset();
         cpu.memory.borrow_mut().write(0x1234, 0x30, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1234), 0x31);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1234), 0x31);
         assert_eq!(cpu.p & FLAG_NEGATIVE, 0);
     }
 
@@ -4229,7 +4237,7 @@ mod tests {
         cpu.x = 0x10;
         cpu.memory.borrow_mut().write(0x1244, 0x8F, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1244), 0x90);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1244), 0x90);
         assert_eq!(cpu.p & FLAG_NEGATIVE, FLAG_NEGATIVE);
     }
 
@@ -4301,8 +4309,8 @@ mod tests {
         assert_eq!(cpu.pc, 0x1235); // PC after BRK at 0x1234
         assert_eq!(cpu.sp, 0xFD); // SP decremented by 2 (pushed 2 bytes)
         // Return address should be 0x0602 (address of last byte of JSR instruction)
-        assert_eq!(cpu.memory.borrow().read(0x01FF), 0x06); // High byte of return address
-        assert_eq!(cpu.memory.borrow().read(0x01FE), 0x02); // Low byte of return address
+        assert_eq!(cpu.memory.borrow_mut().read(0x01FF), 0x06); // High byte of return address
+        assert_eq!(cpu.memory.borrow_mut().read(0x01FE), 0x02); // Low byte of return address
     }
 
     #[test]
@@ -4647,7 +4655,7 @@ mod tests {
         cpu.reset();
         cpu.memory.borrow_mut().write(0x42, 0b11001100, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x42), 0b01100110);
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0b01100110);
         assert_eq!(cpu.p & FLAG_CARRY, 0);
     }
 
@@ -4661,7 +4669,7 @@ mod tests {
         cpu.x = 0x05;
         cpu.memory.borrow_mut().write(0x47, 0b10101011, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x47), 0b01010101);
+        assert_eq!(cpu.memory.borrow_mut().read(0x47), 0b01010101);
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY);
     }
 
@@ -4674,7 +4682,7 @@ mod tests {
         cpu.reset();
         cpu.memory.borrow_mut().write(0x1234, 0b01010100, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1234), 0b00101010);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1234), 0b00101010);
         assert_eq!(cpu.p & FLAG_CARRY, 0);
     }
 
@@ -4688,7 +4696,7 @@ mod tests {
         cpu.x = 0x10;
         cpu.memory.borrow_mut().write(0x1244, 0b00000011, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1244), 0b00000001);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1244), 0b00000001);
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY);
     }
 
@@ -5070,7 +5078,7 @@ mod tests {
         cpu.memory.borrow_mut().write(0x42, 0b11001100, false);
         cpu.p = 0;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x42), 0b10011000);
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0b10011000);
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY);
     }
 
@@ -5085,7 +5093,7 @@ mod tests {
         cpu.memory.borrow_mut().write(0x47, 0b10101011, false);
         cpu.p = FLAG_CARRY;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x47), 0b01010111);
+        assert_eq!(cpu.memory.borrow_mut().read(0x47), 0b01010111);
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY);
     }
 
@@ -5099,7 +5107,7 @@ mod tests {
         cpu.memory.borrow_mut().write(0x1234, 0b01010100, false);
         cpu.p = 0;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1234), 0b10101000);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1234), 0b10101000);
         assert_eq!(cpu.p & FLAG_CARRY, 0);
     }
 
@@ -5114,7 +5122,7 @@ mod tests {
         cpu.memory.borrow_mut().write(0x1244, 0b00000011, false);
         cpu.p = 0;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1244), 0b00000110);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1244), 0b00000110);
         assert_eq!(cpu.p & FLAG_CARRY, 0);
     }
 
@@ -5159,7 +5167,7 @@ mod tests {
         cpu.memory.borrow_mut().write(0x42, 0b11001100, false);
         cpu.p = 0;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x42), 0b01100110);
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0b01100110);
         assert_eq!(cpu.p & FLAG_CARRY, 0);
     }
 
@@ -5174,7 +5182,7 @@ mod tests {
         cpu.memory.borrow_mut().write(0x47, 0b10101011, false);
         cpu.p = FLAG_CARRY;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x47), 0b11010101);
+        assert_eq!(cpu.memory.borrow_mut().read(0x47), 0b11010101);
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY);
     }
 
@@ -5188,7 +5196,7 @@ mod tests {
         cpu.memory.borrow_mut().write(0x1234, 0b01010100, false);
         cpu.p = 0;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1234), 0b00101010);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1234), 0b00101010);
         assert_eq!(cpu.p & FLAG_CARRY, 0);
     }
 
@@ -5203,7 +5211,7 @@ mod tests {
         cpu.memory.borrow_mut().write(0x1244, 0b00000011, false);
         cpu.p = 0;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1244), 0b00000001);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1244), 0b00000001);
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY);
     }
 
@@ -5406,7 +5414,7 @@ mod tests {
         cpu.reset();
         cpu.a = 0x42;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x10), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x10), 0x42);
     }
 
     #[test]
@@ -5419,7 +5427,7 @@ mod tests {
         cpu.a = 0x42;
         cpu.x = 0x05;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x15), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x15), 0x42);
     }
 
     #[test]
@@ -5431,7 +5439,7 @@ mod tests {
         cpu.reset();
         cpu.a = 0x42;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1000), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1000), 0x42);
     }
 
     #[test]
@@ -5444,7 +5452,7 @@ mod tests {
         cpu.a = 0x42;
         cpu.x = 0x05;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1005), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1005), 0x42);
     }
 
     #[test]
@@ -5457,7 +5465,7 @@ mod tests {
         cpu.a = 0x42;
         cpu.y = 0x05;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1005), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1005), 0x42);
     }
 
     #[test]
@@ -5472,7 +5480,7 @@ mod tests {
         cpu.memory.borrow_mut().write(0x15, 0x00, false);
         cpu.memory.borrow_mut().write(0x16, 0x10, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1000), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1000), 0x42);
     }
 
     #[test]
@@ -5487,7 +5495,7 @@ mod tests {
         cpu.memory.borrow_mut().write(0x10, 0x00, false);
         cpu.memory.borrow_mut().write(0x11, 0x10, false);
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1005), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1005), 0x42);
     }
 
     #[test]
@@ -5541,7 +5549,7 @@ mod tests {
         cpu.sp = 0xFD;
         run(&mut cpu);
         assert_eq!(cpu.sp, 0xFC);
-        assert_eq!(cpu.memory.borrow().read(0x01FD), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x01FD), 0x42);
     }
 
     #[test]
@@ -5600,7 +5608,7 @@ mod tests {
         run(&mut cpu);
         assert_eq!(cpu.sp, 0xFC);
         // PHP should push P with B flag (bit 4) and unused bit (bit 5) set to 1
-        assert_eq!(cpu.memory.borrow().read(0x01FD), 0xFF);
+        assert_eq!(cpu.memory.borrow_mut().read(0x01FD), 0xFF);
     }
 
     #[test]
@@ -5616,7 +5624,7 @@ mod tests {
         run(&mut cpu);
         assert_eq!(cpu.sp, 0xFC);
         // Should push 0xF0 (0xC0 | 0x30) - B flag and unused bit both set
-        assert_eq!(cpu.memory.borrow().read(0x01FD), 0xF0);
+        assert_eq!(cpu.memory.borrow_mut().read(0x01FD), 0xF0);
     }
 
     #[test]
@@ -5664,7 +5672,7 @@ mod tests {
         cpu.reset();
         cpu.x = 0x42;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x10), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x10), 0x42);
     }
 
     #[test]
@@ -5677,7 +5685,7 @@ mod tests {
         cpu.x = 0x42;
         cpu.y = 0x05;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x15), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x15), 0x42);
     }
 
     #[test]
@@ -5689,7 +5697,7 @@ mod tests {
         cpu.reset();
         cpu.x = 0x42;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1000), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1000), 0x42);
     }
 
     #[test]
@@ -5701,7 +5709,7 @@ mod tests {
         cpu.reset();
         cpu.y = 0x42;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x10), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x10), 0x42);
     }
 
     #[test]
@@ -5714,7 +5722,7 @@ mod tests {
         cpu.y = 0x42;
         cpu.x = 0x05;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x15), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x15), 0x42);
     }
 
     #[test]
@@ -5726,7 +5734,7 @@ mod tests {
         cpu.reset();
         cpu.y = 0x42;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1000), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1000), 0x42);
     }
 
     #[test]
@@ -5734,8 +5742,8 @@ mod tests {
         let memory = create_test_memory();
         let cpu = Cpu::new(Rc::new(RefCell::new(memory)));
         cpu.memory.borrow_mut().write_u16(0x1234, 0xABCD);
-        assert_eq!(cpu.memory.borrow().read(0x1234), 0xCD); // Low byte
-        assert_eq!(cpu.memory.borrow().read(0x1235), 0xAB); // High byte
+        assert_eq!(cpu.memory.borrow_mut().read(0x1234), 0xCD); // Low byte
+        assert_eq!(cpu.memory.borrow_mut().read(0x1235), 0xAB); // High byte
     }
 
     #[test]
@@ -5744,7 +5752,7 @@ mod tests {
         let cpu = Cpu::new(Rc::new(RefCell::new(memory)));
         cpu.memory.borrow_mut().write(0x1234, 0xCD, false); // Low byte
         cpu.memory.borrow_mut().write(0x1235, 0xAB, false); // High byte
-        let result = cpu.memory.borrow().read_u16(0x1234);
+        let result = cpu.memory.borrow_mut().read_u16(0x1234);
         assert_eq!(result, 0xABCD);
     }
 
@@ -5753,7 +5761,7 @@ mod tests {
         let memory = create_test_memory();
         let cpu = Cpu::new(Rc::new(RefCell::new(memory)));
         cpu.memory.borrow_mut().write_u16(0x1000, 0x1234);
-        let result = cpu.memory.borrow().read_u16(0x1000);
+        let result = cpu.memory.borrow_mut().read_u16(0x1000);
         assert_eq!(result, 0x1234);
     }
 
@@ -5767,9 +5775,9 @@ mod tests {
         run(&mut cpu);
         assert_eq!(cpu.a, 0x42);
         // Verify program was loaded at 0x8000
-        assert_eq!(cpu.memory.borrow().read(0x8000), LDA_IMM);
-        assert_eq!(cpu.memory.borrow().read(0x8001), 0x42);
-        assert_eq!(cpu.memory.borrow().read(0x8002), KIL);
+        assert_eq!(cpu.memory.borrow_mut().read(0x8000), LDA_IMM);
+        assert_eq!(cpu.memory.borrow_mut().read(0x8001), 0x42);
+        assert_eq!(cpu.memory.borrow_mut().read(0x8002), KIL);
     }
 
     #[test]
@@ -5812,7 +5820,7 @@ mod tests {
         cpu.a = 0b11110000;
         cpu.x = 0b10101010;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x0050), 0b10100000);
+        assert_eq!(cpu.memory.borrow_mut().read(0x0050), 0b10100000);
     }
 
     #[test]
@@ -5826,7 +5834,7 @@ mod tests {
         cpu.x = 0b10101010;
         cpu.y = 0x05;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x0055), 0b10100000);
+        assert_eq!(cpu.memory.borrow_mut().read(0x0055), 0b10100000);
     }
 
     #[test]
@@ -5839,7 +5847,7 @@ mod tests {
         cpu.a = 0b11110000;
         cpu.x = 0b10101010;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1000), 0b10100000);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1000), 0b10100000);
     }
 
     #[test]
@@ -5857,7 +5865,7 @@ mod tests {
         cpu.memory.borrow_mut().write(0x00EB, 0x10, false);
         run(&mut cpu);
         // Should store A & X = 0b11111111 & 0b10101010 = 0b10101010 at 0x1000
-        assert_eq!(cpu.memory.borrow().read(0x1000), 0b10101010);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1000), 0b10101010);
     }
 
     #[test]
@@ -6032,7 +6040,7 @@ mod tests {
         // Value = A AND X AND (high byte of address + 1)
         // high byte of final address 0x1005 is 0x10
         // Value = 0xFF AND 0x7F AND (0x10 + 1) = 0xFF AND 0x7F AND 0x11 = 0x11
-        let stored_value = cpu.memory.borrow().read(0x1005);
+        let stored_value = cpu.memory.borrow_mut().read(0x1005);
         assert_eq!(stored_value, 0x11);
     }
 
@@ -6050,7 +6058,7 @@ mod tests {
         // Value = A AND X AND (high byte of address + 1)
         // high byte of final address 0x1010 is 0x10
         // Value = 0xFF AND 0x3F AND (0x10 + 1) = 0xFF AND 0x3F AND 0x11 = 0x11
-        let stored_value = cpu.memory.borrow().read(0x1010);
+        let stored_value = cpu.memory.borrow_mut().read(0x1010);
         assert_eq!(stored_value, 0x11);
     }
 
@@ -6068,7 +6076,7 @@ mod tests {
         // Value = A AND X AND (high byte of address + 1)
         // high byte of final address 0x1100 is 0x11
         // Value = 0xFF AND 0xFF AND (0x11 + 1) = 0xFF AND 0xFF AND 0x12 = 0x12
-        let stored_value = cpu.memory.borrow().read(0x1100);
+        let stored_value = cpu.memory.borrow_mut().read(0x1100);
         assert_eq!(stored_value, 0x12);
     }
 
@@ -6137,7 +6145,7 @@ mod tests {
         cpu.a = 0x0F;
         run(&mut cpu);
         // Memory at 0x42: 0x10 - 1 = 0x0F
-        assert_eq!(cpu.memory.borrow().read(0x42), 0x0F);
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0x0F);
         // Compare A (0x0F) with memory (0x0F)
         assert_eq!(cpu.p & FLAG_ZERO, FLAG_ZERO); // Equal
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY); // A >= memory
@@ -6156,7 +6164,7 @@ mod tests {
         cpu.a = 0x30;
         run(&mut cpu);
         // Memory at 0x1005: 0x20 - 1 = 0x1F
-        assert_eq!(cpu.memory.borrow().read(0x1005), 0x1F);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1005), 0x1F);
         // Compare A (0x30) with memory (0x1F): 0x30 > 0x1F
         assert_eq!(cpu.p & FLAG_ZERO, 0);
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY); // A >= memory
@@ -6177,7 +6185,7 @@ mod tests {
         cpu.a = 0x03;
         run(&mut cpu);
         // Memory at 0x1010: 0x05 - 1 = 0x04
-        assert_eq!(cpu.memory.borrow().read(0x1010), 0x04);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1010), 0x04);
         // Compare A (0x03) with memory (0x04): 0x03 < 0x04
         assert_eq!(cpu.p & FLAG_ZERO, 0);
         assert_eq!(cpu.p & FLAG_CARRY, 0); // A < memory (borrow)
@@ -6198,7 +6206,7 @@ mod tests {
         let saved_status = cpu.p;
         run(&mut cpu);
         // DOP does nothing - just reads memory and discards
-        assert_eq!(cpu.memory.borrow().read(0x42), 0xFF); // Memory unchanged
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0xFF); // Memory unchanged
         assert_eq!(cpu.a, 0x10); // A unchanged
         assert_eq!(cpu.x, 0x20); // X unchanged
         assert_eq!(cpu.y, 0x30); // Y unchanged
@@ -6219,7 +6227,7 @@ mod tests {
         let saved_status = cpu.p;
         run(&mut cpu);
         // DOP does nothing - just reads memory at 0x40 + X = 0x45 and discards
-        assert_eq!(cpu.memory.borrow().read(0x45), 0xAA); // Memory unchanged
+        assert_eq!(cpu.memory.borrow_mut().read(0x45), 0xAA); // Memory unchanged
         assert_eq!(cpu.a, 0x10); // A unchanged
         assert_eq!(cpu.x, 0x05); // X unchanged
         assert_eq!(cpu.y, 0x30); // Y unchanged
@@ -6257,7 +6265,7 @@ mod tests {
         cpu.p |= FLAG_CARRY; // Set carry (no borrow)
         run(&mut cpu);
         // Memory at 0x42: 0x10 + 1 = 0x11
-        assert_eq!(cpu.memory.borrow().read(0x42), 0x11);
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0x11);
         // Then SBC: A = 0x50 - 0x11 - (1 - carry) = 0x50 - 0x11 - 0 = 0x3F
         assert_eq!(cpu.a, 0x3F);
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY); // No borrow
@@ -6278,7 +6286,7 @@ mod tests {
         cpu.p |= FLAG_CARRY; // Set carry (no borrow)
         run(&mut cpu);
         // Memory at 0x1005: 0xFF + 1 = 0x00 (wraps)
-        assert_eq!(cpu.memory.borrow().read(0x1005), 0x00);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1005), 0x00);
         // Then SBC: A = 0x00 - 0x00 - 0 = 0x00
         assert_eq!(cpu.a, 0x00);
         assert_eq!(cpu.p & FLAG_ZERO, FLAG_ZERO);
@@ -6300,7 +6308,7 @@ mod tests {
         cpu.p |= FLAG_CARRY; // Set carry (no borrow)
         run(&mut cpu);
         // Memory at 0x1010: 0x05 + 1 = 0x06
-        assert_eq!(cpu.memory.borrow().read(0x1010), 0x06);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1010), 0x06);
         // Then SBC: A = 0x10 - 0x06 - 0 = 0x0A
         assert_eq!(cpu.a, 0x0A);
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY);
@@ -6486,7 +6494,7 @@ mod tests {
         run(&mut cpu);
         // RLA: ROL memory (0x6A << 1 = 0xD4), then AND with A
         // Memory should be 0xD4, A should be 0xF0 & 0xD4 = 0xD0
-        assert_eq!(cpu.memory.borrow().read(0x42), 0xD4);
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0xD4);
         assert_eq!(cpu.a, 0xD0);
         assert_eq!(cpu.p & FLAG_CARRY, 0); // Carry clear (bit 7 was 0)
         assert_eq!(cpu.p & FLAG_NEGATIVE, FLAG_NEGATIVE); // Negative set
@@ -6507,7 +6515,7 @@ mod tests {
         run(&mut cpu);
         // RLA: ROL memory (0x81 << 1 + carry = 0x03), then AND with A
         // Memory should be 0x03, A should be 0xFF & 0x03 = 0x03
-        assert_eq!(cpu.memory.borrow().read(0x1005), 0x03);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1005), 0x03);
         assert_eq!(cpu.a, 0x03);
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY); // Carry set (bit 7 was 1)
         assert_eq!(cpu.p & FLAG_NEGATIVE, 0);
@@ -6530,7 +6538,7 @@ mod tests {
         run(&mut cpu);
         // RLA: ROL memory (0x01 << 1 = 0x02), then AND with A
         // Memory should be 0x02, A should be 0x01 & 0x02 = 0x00
-        assert_eq!(cpu.memory.borrow().read(0x1010), 0x02);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1010), 0x02);
         assert_eq!(cpu.a, 0x00);
         assert_eq!(cpu.p & FLAG_ZERO, FLAG_ZERO); // Zero flag set
         assert_eq!(cpu.p & FLAG_NEGATIVE, 0);
@@ -6548,7 +6556,7 @@ mod tests {
         cpu.p &= !FLAG_CARRY; // Clear carry
         run(&mut cpu);
         // RRA: ROR memory (0xAA >> 1 = 0x55), then ADC with A (0x10 + 0x55 = 0x65)
-        assert_eq!(cpu.memory.borrow().read(0x10), 0x55);
+        assert_eq!(cpu.memory.borrow_mut().read(0x10), 0x55);
         assert_eq!(cpu.a, 0x65);
         assert_eq!(cpu.p & FLAG_CARRY, 0); // No carry from addition
         assert_eq!(cpu.p & FLAG_ZERO, 0);
@@ -6570,7 +6578,7 @@ mod tests {
         // RRA: ROR memory (0x01 >> 1 with carry = 0x80), then ADC with A (0xFF + 0x80 + carry=1)
         // Memory rotates to 0x80 (carry goes into bit 7), bit 0 goes to carry
         // Then: 0xFF + 0x80 + 1 (carry from ROR) = 0x180 = 0x80 with carry set
-        assert_eq!(cpu.memory.borrow().read(0x1005), 0x80);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1005), 0x80);
         assert_eq!(cpu.a, 0x80);
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY); // Carry from addition
         assert_eq!(cpu.p & FLAG_NEGATIVE, FLAG_NEGATIVE); // Result is negative
@@ -6591,7 +6599,7 @@ mod tests {
         cpu.p &= !FLAG_CARRY;
         run(&mut cpu);
         // RRA: ROR memory (0x02 >> 1 = 0x01), then ADC with A (0x00 + 0x01 = 0x01)
-        assert_eq!(cpu.memory.borrow().read(0x1010), 0x01);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1010), 0x01);
         assert_eq!(cpu.a, 0x01);
         assert_eq!(cpu.p & FLAG_CARRY, 0); // No carry
         assert_eq!(cpu.p & FLAG_ZERO, 0);
@@ -6626,7 +6634,7 @@ mod tests {
         cpu.a = 0b0000_1111; // 0x0F
         run(&mut cpu);
         // SLO: ASL memory (0x55 << 1 = 0xAA), then ORA with A (0x0F | 0xAA = 0xAF)
-        assert_eq!(cpu.memory.borrow().read(0x10), 0xAA);
+        assert_eq!(cpu.memory.borrow_mut().read(0x10), 0xAA);
         assert_eq!(cpu.a, 0xAF);
         assert_eq!(cpu.p & FLAG_CARRY, 0); // No carry from shift
         assert_eq!(cpu.p & FLAG_NEGATIVE, FLAG_NEGATIVE); // Result is negative
@@ -6644,7 +6652,7 @@ mod tests {
         cpu.a = 0b0000_0010; // 0x02
         run(&mut cpu);
         // SLO: ASL memory (0x81 << 1 = 0x02, carry set), then ORA with A (0x02 | 0x02 = 0x02)
-        assert_eq!(cpu.memory.borrow().read(0x1005), 0x02);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1005), 0x02);
         assert_eq!(cpu.a, 0x02);
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY); // Carry from shift
         assert_eq!(cpu.p & FLAG_ZERO, 0);
@@ -6664,7 +6672,7 @@ mod tests {
         cpu.a = 0b0000_0000; // 0x00
         run(&mut cpu);
         // SLO: ASL memory (0x01 << 1 = 0x02), then ORA with A (0x00 | 0x02 = 0x02)
-        assert_eq!(cpu.memory.borrow().read(0x1010), 0x02);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1010), 0x02);
         assert_eq!(cpu.a, 0x02);
         assert_eq!(cpu.p & FLAG_CARRY, 0); // No carry
         assert_eq!(cpu.p & FLAG_ZERO, 0);
@@ -6681,7 +6689,7 @@ mod tests {
         cpu.a = 0b0000_0001; // 0x01
         run(&mut cpu);
         // SRE: LSR memory (0x06 >> 1 = 0x03), then EOR with A (0x01 ^ 0x03 = 0x02)
-        assert_eq!(cpu.memory.borrow().read(0x42), 0x03);
+        assert_eq!(cpu.memory.borrow_mut().read(0x42), 0x03);
         assert_eq!(cpu.a, 0x02);
         assert_eq!(cpu.p & FLAG_CARRY, 0); // No carry from shift
         assert_eq!(cpu.p & FLAG_ZERO, 0);
@@ -6701,7 +6709,7 @@ mod tests {
         cpu.a = 0b0000_0011; // 0x03
         run(&mut cpu);
         // SRE: LSR memory (0x05 >> 1 = 0x02 with carry), then EOR with A (0x03 ^ 0x02 = 0x01)
-        assert_eq!(cpu.memory.borrow().read(0x1010), 0x02);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1010), 0x02);
         assert_eq!(cpu.a, 0x01);
         assert_eq!(cpu.p & FLAG_CARRY, FLAG_CARRY); // Carry from LSR
         assert_eq!(cpu.p & FLAG_ZERO, 0);
@@ -6721,7 +6729,7 @@ mod tests {
         cpu.a = 0b0000_0100; // 0x04
         run(&mut cpu);
         // SRE: LSR memory (0x08 >> 1 = 0x04), then EOR with A (0x04 ^ 0x04 = 0x00)
-        assert_eq!(cpu.memory.borrow().read(0x1010), 0x04);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1010), 0x04);
         assert_eq!(cpu.a, 0x00);
         assert_eq!(cpu.p & FLAG_CARRY, 0); // No carry
         assert_eq!(cpu.p & FLAG_ZERO, FLAG_ZERO); // Result is zero
@@ -6742,7 +6750,7 @@ mod tests {
         cpu.x = 0xFF;
         cpu.y = 0x10;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1010), 0x11); // X AND (0x10 + 1)
+        assert_eq!(cpu.memory.borrow_mut().read(0x1010), 0x11); // X AND (0x10 + 1)
     }
 
     #[test]
@@ -6760,7 +6768,7 @@ mod tests {
         cpu.y = 0xFF;
         cpu.x = 0x10;
         run(&mut cpu);
-        assert_eq!(cpu.memory.borrow().read(0x1010), 0x11); // Y AND (0x10 + 1)
+        assert_eq!(cpu.memory.borrow_mut().read(0x1010), 0x11); // Y AND (0x10 + 1)
     }
 
     #[test]
@@ -6832,7 +6840,7 @@ mod tests {
         // SP should be A & X
         assert_eq!(cpu.sp, 0xF0);
         // Memory at $1010 should be SP & (HIGH(addr) + 1) = 0xF0 & 0x11 = 0x10
-        assert_eq!(cpu.memory.borrow().read(0x1010), 0x10);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1010), 0x10);
     }
 
     // Cycle counting tests
@@ -6908,7 +6916,7 @@ mod tests {
         cpu.x = 0x05;
         let cycles = cpu.run_opcode();
         assert_eq!(cycles, 5);
-        assert_eq!(cpu.memory.borrow().read(0x1304), 0x99);
+        assert_eq!(cpu.memory.borrow_mut().read(0x1304), 0x99);
     }
 
     #[test]
@@ -7075,13 +7083,13 @@ mod tests {
         // PC was at 0x8000, BRK is 1 byte, but we push PC+2 = 0x8002
         let stack_base = 0x0100;
         assert_eq!(
-            cpu.memory.borrow().read(stack_base + initial_sp as u16),
+            cpu.memory.borrow_mut().read(stack_base + initial_sp as u16),
             0x80,
             "High byte of PC+2 should be pushed first"
         );
         assert_eq!(
             cpu.memory
-                .borrow()
+                .borrow_mut()
                 .read(stack_base + initial_sp.wrapping_sub(1) as u16),
             0x02,
             "Low byte of PC+2 should be pushed second"
@@ -7090,7 +7098,7 @@ mod tests {
         // Verify P was pushed with B flag and unused flag set
         let pushed_p = cpu
             .memory
-            .borrow()
+            .borrow_mut()
             .read(stack_base + initial_sp.wrapping_sub(2) as u16);
         assert_eq!(
             pushed_p & FLAG_BREAK,
@@ -7234,8 +7242,8 @@ mod tests {
         // Read back from PPU memory at $3040 to verify
         cpu.memory.borrow_mut().write(0x2006, 0x30, false);
         cpu.memory.borrow_mut().write(0x2006, 0x40, false);
-        let _ = cpu.memory.borrow().read(0x2007); // Dummy read (buffered)
-        let value = cpu.memory.borrow().read(0x2007); // Actual value
+        let _ = cpu.memory.borrow_mut().read(0x2007); // Dummy read (buffered)
+        let value = cpu.memory.borrow_mut().read(0x2007); // Actual value
 
         assert_eq!(
             value, 0xAB,
@@ -7390,9 +7398,9 @@ mod tests {
 
         // Check stack contents
         let stack_base = 0x0100;
-        let pch = cpu.memory.borrow().read(stack_base + 0xFD as u16);
-        let pcl = cpu.memory.borrow().read(stack_base + 0xFC as u16);
-        let status = cpu.memory.borrow().read(stack_base + 0xFB as u16);
+        let pch = cpu.memory.borrow_mut().read(stack_base + 0xFD as u16);
+        let pcl = cpu.memory.borrow_mut().read(stack_base + 0xFC as u16);
+        let status = cpu.memory.borrow_mut().read(stack_base + 0xFB as u16);
 
         let return_addr = ((pch as u16) << 8) | (pcl as u16);
         assert_eq!(return_addr, start_pc + 2, "Return address should be PC + 2");
@@ -7464,7 +7472,7 @@ mod tests {
         );
 
         // Verify B flag is STILL set on stack (BRK's B flag, not NMI's)
-        let status = cpu.memory.borrow().read(0x0100 + 0xFB as u16);
+        let status = cpu.memory.borrow_mut().read(0x0100 + 0xFB as u16);
         assert_eq!(
             status & FLAG_BREAK,
             FLAG_BREAK,
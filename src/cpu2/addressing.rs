@@ -6,8 +6,6 @@
 use super::traits::AddressingMode;
 use super::types::CpuState;
 use crate::mem_controller::MemController;
-use std::cell::RefCell;
-use std::rc::Rc;
 // use super::types::AddressingState;
 
 /// Type of memory operation for addressing modes
@@ -47,11 +45,11 @@ impl AddressingMode for Implied {
         true
     }
 
-    fn tick(&mut self, cpu_state: &mut CpuState, memory: Rc<RefCell<MemController>>) {
+    fn tick(&mut self, cpu_state: &mut CpuState, memory: &mut MemController) {
         // Perform dummy read of next byte at PC
         // This satisfies the 6502 requirement that every cycle is either a read or write
         // The value is discarded; this is just to generate a bus cycle
-        let _ = memory.borrow().read(cpu_state.pc);
+        let _ = memory.read(cpu_state.pc);
     }
 }
 
@@ -79,11 +77,11 @@ impl AddressingMode for Immediate {
         self.has_read
     }
 
-    fn tick(&mut self, cpu_state: &mut CpuState, memory: Rc<RefCell<MemController>>) {
+    fn tick(&mut self, cpu_state: &mut CpuState, memory: &mut MemController) {
         debug_assert!(!self.has_read, "Immediate::tick called after already done");
 
         // The operand value is the byte at PC
-        self.value = memory.borrow().read(cpu_state.pc);
+        self.value = memory.read(cpu_state.pc);
         cpu_state.pc = cpu_state.pc.wrapping_add(1);
         self.has_read = true;
     }
@@ -104,17 +102,26 @@ impl AddressingMode for Immediate {
 /// Examples: LDA $42, STA $10
 ///
 /// Cycles: 2 (fetch zero page address from PC, read value from address)
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy)]
 pub struct ZeroPage {
     cycle: u8,
     address: u16,
     value: u8,
+    access: MemoryAccess,
 }
 
 impl ZeroPage {
     /// Create a new ZeroPage addressing mode instance
-    pub fn new() -> Self {
-        Self::default()
+    ///
+    /// # Arguments
+    /// * `access` - The type of memory access operation
+    pub fn new(access: MemoryAccess) -> Self {
+        Self {
+            cycle: 0,
+            address: 0,
+            value: 0,
+            access,
+        }
     }
 }
 
@@ -123,20 +130,23 @@ impl AddressingMode for ZeroPage {
         self.cycle == 2
     }
 
-    fn tick(&mut self, cpu_state: &mut CpuState, memory: Rc<RefCell<MemController>>) {
+    fn tick(&mut self, cpu_state: &mut CpuState, memory: &mut MemController) {
         debug_assert!(self.cycle < 2, "ZeroPage::tick called after already done");
 
         match self.cycle {
             0 => {
                 // Cycle 1: Read zero page address (low byte only, high byte is always 0x00)
-                let zp_addr = memory.borrow().read(cpu_state.pc);
+                let zp_addr = memory.read(cpu_state.pc);
                 self.address = zp_addr as u16;
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
                 self.cycle = 1;
             }
             1 => {
                 // Cycle 2: Read value from zero page address
-                self.value = memory.borrow().read(self.address);
+                // Write operations skip this read, the instruction writes instead
+                if self.access != MemoryAccess::Write {
+                    self.value = memory.read(self.address);
+                }
                 self.cycle = 2;
             }
             _ => unreachable!(),
@@ -198,20 +208,20 @@ impl AddressingMode for Absolute {
         }
     }
 
-    fn tick(&mut self, cpu_state: &mut CpuState, memory: Rc<RefCell<MemController>>) {
+    fn tick(&mut self, cpu_state: &mut CpuState, memory: &mut MemController) {
         debug_assert!(!self.is_done(), "Absolute::tick called after already done");
 
         match self.cycle {
             0 => {
                 // Cycle 1: Fetch low byte of address
-                let low = memory.borrow().read(cpu_state.pc);
+                let low = memory.read(cpu_state.pc);
                 self.address = low as u16;
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
                 self.cycle = 1;
             }
             1 => {
                 // Cycle 2: Fetch high byte of address
-                let high = memory.borrow().read(cpu_state.pc);
+                let high = memory.read(cpu_state.pc);
                 self.address |= (high as u16) << 8;
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
                 self.cycle = 2;
@@ -221,7 +231,7 @@ impl AddressingMode for Absolute {
                 // Write operations skip this read, Jump doesn't reach here
                 if self.access == MemoryAccess::Read || self.access == MemoryAccess::ReadModifyWrite
                 {
-                    self.value = memory.borrow().read(self.address);
+                    self.value = memory.read(self.address);
                 }
                 self.cycle = 3;
             }
@@ -253,17 +263,26 @@ impl AddressingMode for Absolute {
 /// Examples: LDA $42,X, STX $10,X
 ///
 /// Cycles: 3 (fetch base address, dummy read while adding index, read value from final address)
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy)]
 pub struct ZeroPageX {
     cycle: u8,
     address: u16,
     value: u8,
+    access: MemoryAccess,
 }
 
 impl ZeroPageX {
     /// Create a new ZeroPageX addressing mode instance
-    pub fn new() -> Self {
-        Self::default()
+    ///
+    /// # Arguments
+    /// * `access` - The type of memory access operation
+    pub fn new(access: MemoryAccess) -> Self {
+        Self {
+            cycle: 0,
+            address: 0,
+            value: 0,
+            access,
+        }
     }
 }
 
@@ -272,13 +291,13 @@ impl AddressingMode for ZeroPageX {
         self.cycle == 3
     }
 
-    fn tick(&mut self, cpu_state: &mut CpuState, memory: Rc<RefCell<MemController>>) {
+    fn tick(&mut self, cpu_state: &mut CpuState, memory: &mut MemController) {
         debug_assert!(self.cycle < 3, "ZeroPageX::tick called after already done");
 
         match self.cycle {
             0 => {
                 // Cycle 1: Fetch base zero page address
-                let base = memory.borrow().read(cpu_state.pc);
+                let base = memory.read(cpu_state.pc);
                 self.address = base as u16;
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
                 self.cycle = 1;
@@ -286,12 +305,16 @@ impl AddressingMode for ZeroPageX {
             1 => {
                 // Cycle 2: Dummy read from base address while adding X index
                 // The address wraps within zero page (stays in $00-$FF)
+                let _ = memory.read(self.address);
                 self.address = (self.address.wrapping_add(cpu_state.x as u16)) & 0xFF;
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Read value from final address
-                self.value = memory.borrow().read(self.address);
+                // Write operations skip this read, the instruction writes instead
+                if self.access != MemoryAccess::Write {
+                    self.value = memory.read(self.address);
+                }
                 self.cycle = 3;
             }
             _ => unreachable!(),
@@ -322,17 +345,26 @@ impl AddressingMode for ZeroPageX {
 /// Examples: LDX $42,Y, STX $10,Y
 ///
 /// Cycles: 3 (fetch base address, dummy read while adding index, read value from final address)
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy)]
 pub struct ZeroPageY {
     cycle: u8,
     address: u16,
     value: u8,
+    access: MemoryAccess,
 }
 
 impl ZeroPageY {
     /// Create a new ZeroPageY addressing mode instance
-    pub fn new() -> Self {
-        Self::default()
+    ///
+    /// # Arguments
+    /// * `access` - The type of memory access operation
+    pub fn new(access: MemoryAccess) -> Self {
+        Self {
+            cycle: 0,
+            address: 0,
+            value: 0,
+            access,
+        }
     }
 }
 
@@ -341,13 +373,13 @@ impl AddressingMode for ZeroPageY {
         self.cycle == 3
     }
 
-    fn tick(&mut self, cpu_state: &mut CpuState, memory: Rc<RefCell<MemController>>) {
+    fn tick(&mut self, cpu_state: &mut CpuState, memory: &mut MemController) {
         debug_assert!(self.cycle < 3, "ZeroPageY::tick called after already done");
 
         match self.cycle {
             0 => {
                 // Cycle 1: Fetch base zero page address
-                let base = memory.borrow().read(cpu_state.pc);
+                let base = memory.read(cpu_state.pc);
                 self.address = base as u16;
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
                 self.cycle = 1;
@@ -355,12 +387,16 @@ impl AddressingMode for ZeroPageY {
             1 => {
                 // Cycle 2: Dummy read from base address while adding Y index
                 // The address wraps within zero page (stays in $00-$FF)
+                let _ = memory.read(self.address);
                 self.address = (self.address.wrapping_add(cpu_state.y as u16)) & 0xFF;
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Read value from final address
-                self.value = memory.borrow().read(self.address);
+                // Write operations skip this read, the instruction writes instead
+                if self.access != MemoryAccess::Write {
+                    self.value = memory.read(self.address);
+                }
                 self.cycle = 3;
             }
             _ => unreachable!(),
@@ -427,7 +463,7 @@ impl AddressingMode for AbsoluteX {
         }
     }
 
-    fn tick(&mut self, cpu_state: &mut CpuState, memory: Rc<RefCell<MemController>>) {
+    fn tick(&mut self, cpu_state: &mut CpuState, memory: &mut MemController) {
         debug_assert!(
             !self.is_done(),
             "AbsoluteX::tick called after addressing complete"
@@ -436,14 +472,14 @@ impl AddressingMode for AbsoluteX {
         match self.cycle {
             0 => {
                 // Cycle 1: Fetch low byte of base address
-                let low = memory.borrow().read(cpu_state.pc);
+                let low = memory.read(cpu_state.pc);
                 self.address = low as u16;
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
                 self.cycle = 1;
             }
             1 => {
                 // Cycle 2: Fetch high byte of base address
-                let high = memory.borrow().read(cpu_state.pc);
+                let high = memory.read(cpu_state.pc);
                 let base_addr = self.address | ((high as u16) << 8);
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
 
@@ -458,13 +494,13 @@ impl AddressingMode for AbsoluteX {
                 match self.access {
                     MemoryAccess::Read if !self.page_crossed => {
                         // Read op with no page cross - read actual value and complete
-                        self.value = memory.borrow().read(self.address);
+                        self.value = memory.read(self.address);
                     }
                     _ => {
                         // Write/RMW or page cross - dummy read from wrong page
                         let dummy_addr = (self.address.wrapping_sub(cpu_state.x as u16) & 0xFF00)
                             | (self.address & 0x00FF);
-                        let _ = memory.borrow().read(dummy_addr);
+                        let _ = memory.read(dummy_addr);
                     }
                 }
                 self.cycle = 3;
@@ -472,7 +508,7 @@ impl AddressingMode for AbsoluteX {
             3 => {
                 // Cycle 4: Read actual value (for Read and RMW operations only)
                 if self.access != MemoryAccess::Write {
-                    self.value = memory.borrow().read(self.address);
+                    self.value = memory.read(self.address);
                 }
                 self.cycle = 4;
             }
@@ -544,7 +580,7 @@ impl AddressingMode for AbsoluteY {
         }
     }
 
-    fn tick(&mut self, cpu_state: &mut CpuState, memory: Rc<RefCell<MemController>>) {
+    fn tick(&mut self, cpu_state: &mut CpuState, memory: &mut MemController) {
         debug_assert!(
             !self.is_done(),
             "AbsoluteY::tick called after addressing complete"
@@ -553,14 +589,14 @@ impl AddressingMode for AbsoluteY {
         match self.cycle {
             0 => {
                 // Cycle 1: Fetch low byte of base address
-                let low = memory.borrow().read(cpu_state.pc);
+                let low = memory.read(cpu_state.pc);
                 self.address = low as u16;
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
                 self.cycle = 1;
             }
             1 => {
                 // Cycle 2: Fetch high byte of base address
-                let high = memory.borrow().read(cpu_state.pc);
+                let high = memory.read(cpu_state.pc);
                 let base_addr = self.address | ((high as u16) << 8);
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
 
@@ -575,13 +611,13 @@ impl AddressingMode for AbsoluteY {
                 match self.access {
                     MemoryAccess::Read if !self.page_crossed => {
                         // Read op with no page cross - read actual value and complete
-                        self.value = memory.borrow().read(self.address);
+                        self.value = memory.read(self.address);
                     }
                     _ => {
                         // Write/RMW or page cross - dummy read from wrong page
                         let dummy_addr = (self.address.wrapping_sub(cpu_state.y as u16) & 0xFF00)
                             | (self.address & 0x00FF);
-                        let _ = memory.borrow().read(dummy_addr);
+                        let _ = memory.read(dummy_addr);
                     }
                 }
                 self.cycle = 3;
@@ -589,7 +625,7 @@ impl AddressingMode for AbsoluteY {
             3 => {
                 // Cycle 4: Read actual value (for Read and RMW operations only)
                 if self.access != MemoryAccess::Write {
-                    self.value = memory.borrow().read(self.address);
+                    self.value = memory.read(self.address);
                 }
                 self.cycle = 4;
             }
@@ -646,27 +682,27 @@ impl AddressingMode for Indirect {
         self.cycle == 4
     }
 
-    fn tick(&mut self, cpu_state: &mut CpuState, memory: Rc<RefCell<MemController>>) {
+    fn tick(&mut self, cpu_state: &mut CpuState, memory: &mut MemController) {
         debug_assert!(self.cycle < 4, "Indirect::tick called after already done");
 
         match self.cycle {
             0 => {
                 // Fetch low byte of pointer address
-                let low = memory.borrow().read(cpu_state.pc);
+                let low = memory.read(cpu_state.pc);
                 self.pointer = low as u16;
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
                 self.cycle = 1;
             }
             1 => {
                 // Fetch high byte of pointer address
-                let high = memory.borrow().read(cpu_state.pc);
+                let high = memory.read(cpu_state.pc);
                 self.pointer |= (high as u16) << 8;
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
                 self.cycle = 2;
             }
             2 => {
                 // Fetch low byte of target address from pointer
-                let low = memory.borrow().read(self.pointer);
+                let low = memory.read(self.pointer);
                 self.address = low as u16;
                 self.cycle = 3;
             }
@@ -678,7 +714,7 @@ impl AddressingMode for Indirect {
                 } else {
                     self.pointer.wrapping_add(1)
                 };
-                let high = memory.borrow().read(high_addr);
+                let high = memory.read(high_addr);
                 self.address |= (high as u16) << 8;
                 self.cycle = 4;
             }
@@ -733,7 +769,7 @@ impl AddressingMode for IndexedIndirect {
         self.cycle == 5
     }
 
-    fn tick(&mut self, cpu_state: &mut CpuState, memory: Rc<RefCell<MemController>>) {
+    fn tick(&mut self, cpu_state: &mut CpuState, memory: &mut MemController) {
         debug_assert!(
             !self.is_done(),
             "IndexedIndirect::tick called after already done"
@@ -742,34 +778,34 @@ impl AddressingMode for IndexedIndirect {
         match self.cycle {
             0 => {
                 // Cycle 1: Fetch zero-page base address from PC
-                let base = memory.borrow().read(cpu_state.pc);
+                self.pointer_addr = memory.read(cpu_state.pc);
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
-                // Add X register to base (wraps within zero page)
-                self.pointer_addr = base.wrapping_add(cpu_state.x);
                 self.cycle = 1;
             }
             1 => {
-                // Cycle 2: Dummy read at zero page base address (CPU performs internal X addition)
-                let _ = memory.borrow().read(self.pointer_addr as u16);
+                // Cycle 2: Dummy read at zero page base address while adding X
+                // (wraps within zero page)
+                let _ = memory.read(self.pointer_addr as u16);
+                self.pointer_addr = self.pointer_addr.wrapping_add(cpu_state.x);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Read low byte of pointer from zero page
-                let low = memory.borrow().read(self.pointer_addr as u16);
+                let low = memory.read(self.pointer_addr as u16);
                 self.address = low as u16;
                 self.cycle = 3;
             }
             3 => {
                 // Cycle 4: Read high byte of pointer from zero page (wraps within zero page)
                 let high_addr = self.pointer_addr.wrapping_add(1);
-                let high = memory.borrow().read(high_addr as u16);
+                let high = memory.read(high_addr as u16);
                 self.address |= (high as u16) << 8;
                 self.cycle = 4;
             }
             4 => {
                 // Cycle 5: Read value from final address (Read and RMW only)
                 if self.access != MemoryAccess::Write {
-                    self.value = memory.borrow().read(self.address);
+                    self.value = memory.read(self.address);
                 }
                 self.cycle = 5;
             }
@@ -842,7 +878,7 @@ impl AddressingMode for IndirectIndexed {
         }
     }
 
-    fn tick(&mut self, cpu_state: &mut CpuState, memory: Rc<RefCell<MemController>>) {
+    fn tick(&mut self, cpu_state: &mut CpuState, memory: &mut MemController) {
         debug_assert!(
             !self.is_done(),
             "IndirectIndexed::tick called after already done"
@@ -851,20 +887,20 @@ impl AddressingMode for IndirectIndexed {
         match self.cycle {
             0 => {
                 // Cycle 1: Fetch zero-page pointer address from PC
-                self.pointer_addr = memory.borrow().read(cpu_state.pc);
+                self.pointer_addr = memory.read(cpu_state.pc);
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
                 self.cycle = 1;
             }
             1 => {
                 // Cycle 2: Read pointer low byte from zero page
-                let low = memory.borrow().read(self.pointer_addr as u16);
+                let low = memory.read(self.pointer_addr as u16);
                 self.base_address = low as u16;
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Read pointer high byte from zero page (wraps within zero page)
                 let high_addr = self.pointer_addr.wrapping_add(1);
-                let high = memory.borrow().read(high_addr as u16);
+                let high = memory.read(high_addr as u16);
                 self.base_address |= (high as u16) << 8;
                 self.cycle = 3;
             }
@@ -876,12 +912,12 @@ impl AddressingMode for IndirectIndexed {
                 match self.access {
                     MemoryAccess::Read if !self.page_crossed => {
                         // Read op with no page cross - read actual value and complete
-                        self.value = memory.borrow().read(self.address);
+                        self.value = memory.read(self.address);
                     }
                     _ => {
                         // Write/RMW or page cross - dummy read from wrong page
                         let dummy_addr = (self.base_address & 0xFF00) | (self.address & 0x00FF);
-                        let _ = memory.borrow().read(dummy_addr);
+                        let _ = memory.read(dummy_addr);
                     }
                 }
                 self.cycle = 4;
@@ -889,7 +925,7 @@ impl AddressingMode for IndirectIndexed {
             4 => {
                 // Cycle 5: Read actual value (for Read and RMW operations only)
                 if self.access != MemoryAccess::Write {
-                    self.value = memory.borrow().read(self.address);
+                    self.value = memory.read(self.address);
                 }
                 self.cycle = 5;
             }
@@ -946,13 +982,13 @@ impl AddressingMode for Relative {
         self.cycle == 1
     }
 
-    fn tick(&mut self, cpu_state: &mut CpuState, memory: Rc<RefCell<MemController>>) {
+    fn tick(&mut self, cpu_state: &mut CpuState, memory: &mut MemController) {
         debug_assert!(self.cycle < 1, "Relative::tick called after already done");
 
         match self.cycle {
             0 => {
                 // Cycle 1: Fetch signed offset from PC and compute target address
-                self.offset = memory.borrow().read(cpu_state.pc) as i8;
+                self.offset = memory.read(cpu_state.pc) as i8;
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
                 // Cast i8 -> i16 for sign extension, then to u16 for wrapping arithmetic
                 // Target address = (PC after increment) + signed offset
@@ -990,20 +1026,14 @@ mod new_tests {
     #[test]
     fn test_immediate_completes_after_one_tick() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
         // Setup memory with a value at PC
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write test value to RAM address 0x0200
-        memory.borrow_mut().write(0x0200, 0x42, false);
+        memory.write(0x0200, 0x42, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1021,7 +1051,7 @@ mod new_tests {
         let mut mode = Immediate::new();
 
         // After one tick, it should be done
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
 
         assert!(
             mode.is_done(),
@@ -1042,7 +1072,7 @@ mod new_tests {
 
     #[test]
     fn test_zeropage_starts_not_done() {
-        let mode = ZeroPage::new();
+        let mode = ZeroPage::new(MemoryAccess::Read);
         assert!(
             !mode.is_done(),
             "ZeroPage mode should not be done initially"
@@ -1052,22 +1082,16 @@ mod new_tests {
     #[test]
     fn test_zeropage_completes_after_two_ticks() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
         // Setup memory
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write zero page address at PC (use RAM address 0x0200 instead of ROM)
-        memory.borrow_mut().write(0x0200, 0x42, false); // Zero page address
+        memory.write(0x0200, 0x42, false); // Zero page address
         // Write value at zero page address 0x0042
-        memory.borrow_mut().write(0x0042, 0x55, false);
+        memory.write(0x0042, 0x55, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1082,17 +1106,17 @@ mod new_tests {
             irq_pending: false,
         };
 
-        let mut mode = ZeroPage::new();
+        let mut mode = ZeroPage::new(MemoryAccess::Read);
 
         // After first tick, should not be done
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(
             !mode.is_done(),
             "ZeroPage mode should not be done after first tick"
         );
 
         // After second tick, it should be done
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(
             mode.is_done(),
             "ZeroPage mode should be done after one tick"
@@ -1109,7 +1133,7 @@ mod new_tests {
     #[test]
     #[should_panic(expected = "ZeroPage::get_address called before addressing complete")]
     fn test_zeropage_get_address_before_done_panics() {
-        let mode = ZeroPage::new();
+        let mode = ZeroPage::new(MemoryAccess::Read);
         mode.get_address(); // Should panic in debug builds
     }
 
@@ -1127,20 +1151,14 @@ mod new_tests {
     #[test]
     fn test_absolute_not_done_after_two_ticks() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write low and high bytes at PC
-        memory.borrow_mut().write(0x0200, 0x34, false);
-        memory.borrow_mut().write(0x0201, 0x12, false);
+        memory.write(0x0200, 0x34, false);
+        memory.write(0x0201, 0x12, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1156,7 +1174,7 @@ mod new_tests {
         };
 
         let mut mode = Absolute::new(MemoryAccess::Read);
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
 
         assert!(
             !mode.is_done(),
@@ -1165,7 +1183,7 @@ mod new_tests {
         assert_eq!(cpu_state.pc, 0x0201, "PC should have advanced by 1");
 
         // After second tick, still not done
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(
             !mode.is_done(),
             "Absolute mode should not be done after one tick"
@@ -1176,22 +1194,16 @@ mod new_tests {
     #[test]
     fn test_absolute_completes_after_three_ticks() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write address bytes at PC (little-endian: low byte first, high byte second)
-        memory.borrow_mut().write(0x0200, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0201, 0x12, false); // High byte
+        memory.write(0x0200, 0x34, false); // Low byte
+        memory.write(0x0201, 0x12, false); // High byte
         // Write value at address 0x1234
-        memory.borrow_mut().write(0x1234, 0x99, false);
+        memory.write(0x1234, 0x99, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1209,15 +1221,15 @@ mod new_tests {
         let mut mode = Absolute::new(MemoryAccess::Read);
 
         // First tick - read low byte
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after first tick");
 
         // Second tick - read high byte
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after second tick");
 
         // Third tick - read value
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(mode.is_done(), "Should be done after third tick");
         assert_eq!(
             mode.get_address(),
@@ -1238,20 +1250,14 @@ mod new_tests {
     #[test]
     fn test_absolute_without_read_completes_after_two_ticks() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write address bytes at PC (little-endian: low byte first, high byte second)
-        memory.borrow_mut().write(0x0200, 0x78, false); // Low byte
-        memory.borrow_mut().write(0x0201, 0x56, false); // High byte
+        memory.write(0x0200, 0x78, false); // Low byte
+        memory.write(0x0201, 0x56, false); // High byte
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1269,11 +1275,11 @@ mod new_tests {
         let mut mode = Absolute::new(MemoryAccess::Jump); // Don't read value
 
         // First tick - read low byte
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after first tick");
 
         // Second tick - read high byte and complete (no value read)
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(
             mode.is_done(),
             "Should be done after second tick when not reading value"
@@ -1289,22 +1295,16 @@ mod new_tests {
     #[test]
     fn test_absolute_with_read_takes_three_ticks() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write address bytes at PC
-        memory.borrow_mut().write(0x0200, 0xAB, false); // Low byte
-        memory.borrow_mut().write(0x0201, 0x12, false); // High byte
+        memory.write(0x0200, 0xAB, false); // Low byte
+        memory.write(0x0201, 0x12, false); // High byte
         // Write value at target address
-        memory.borrow_mut().write(0x12AB, 0x42, false);
+        memory.write(0x12AB, 0x42, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1322,18 +1322,18 @@ mod new_tests {
         let mut mode = Absolute::new(MemoryAccess::Read); // Read value
 
         // First tick - read low byte
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after first tick");
 
         // Second tick - read high byte (not done yet)
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(
             !mode.is_done(),
             "Should not be done after second tick when reading value"
         );
 
         // Third tick - read value
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(mode.is_done(), "Should be done after third tick");
         assert_eq!(mode.get_address(), 0x12AB, "Address should be correct");
         assert_eq!(mode.get_u8_value(), 0x42, "Value should be 0x42");
@@ -1344,7 +1344,7 @@ mod new_tests {
 
     #[test]
     fn test_zeropagex_starts_not_done() {
-        let mode = ZeroPageX::new();
+        let mode = ZeroPageX::new(MemoryAccess::Read);
         assert!(
             !mode.is_done(),
             "ZeroPageX mode should not be done initially"
@@ -1354,19 +1354,13 @@ mod new_tests {
     #[test]
     fn test_zeropagex_not_done_after_two_ticks() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write base zero page address at PC
-        memory.borrow_mut().write(0x0200, 0x80, false);
+        memory.write(0x0200, 0x80, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1381,8 +1375,8 @@ mod new_tests {
             irq_pending: false,
         };
 
-        let mut mode = ZeroPageX::new();
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        let mut mode = ZeroPageX::new(MemoryAccess::Read);
+        mode.tick(&mut cpu_state, &mut memory);
 
         assert!(
             !mode.is_done(),
@@ -1391,7 +1385,7 @@ mod new_tests {
         assert_eq!(cpu_state.pc, 0x0201, "PC should have advanced by 1");
 
         // After second tick, still not done
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(
             !mode.is_done(),
             "ZeroPageX mode should not be done after one tick"
@@ -1401,21 +1395,15 @@ mod new_tests {
     #[test]
     fn test_zeropagex_completes_after_three_ticks() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write base zero page address at PC
-        memory.borrow_mut().write(0x0200, 0x80, false);
+        memory.write(0x0200, 0x80, false);
         // Write value at 0x85 (0x80 + 0x05)
-        memory.borrow_mut().write(0x0085, 0xAA, false);
+        memory.write(0x0085, 0xAA, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1430,18 +1418,18 @@ mod new_tests {
             irq_pending: false,
         };
 
-        let mut mode = ZeroPageX::new();
+        let mut mode = ZeroPageX::new(MemoryAccess::Read);
 
         // First tick - read base address
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after first tick");
 
         // Second tick - dummy read and add index
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after second tick");
 
         // Third tick - read value
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(mode.is_done(), "Should be done after third tick");
         assert_eq!(
             mode.get_address(),
@@ -1455,21 +1443,15 @@ mod new_tests {
     #[test]
     fn test_zeropagex_wraps_around() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write base zero page address at PC
-        memory.borrow_mut().write(0x0200, 0xFF, false);
+        memory.write(0x0200, 0xFF, false);
         // Write value at 0x04 (wraps around)
-        memory.borrow_mut().write(0x0004, 0xBB, false);
+        memory.write(0x0004, 0xBB, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1484,11 +1466,11 @@ mod new_tests {
             irq_pending: false,
         };
 
-        let mut mode = ZeroPageX::new();
+        let mut mode = ZeroPageX::new(MemoryAccess::Read);
 
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
+        mode.tick(&mut cpu_state, &mut memory);
+        mode.tick(&mut cpu_state, &mut memory);
 
         assert!(mode.is_done(), "Should be done after three ticks");
         // 0xFF + 0x05 = 0x104, but wraps to 0x04 in zero page
@@ -1503,7 +1485,7 @@ mod new_tests {
     #[test]
     #[should_panic(expected = "ZeroPageX::get_address called before addressing complete")]
     fn test_zeropagex_get_address_before_done_panics() {
-        let mode = ZeroPageX::new();
+        let mode = ZeroPageX::new(MemoryAccess::Read);
         mode.get_address(); // Should panic in debug builds
     }
 
@@ -1511,7 +1493,7 @@ mod new_tests {
 
     #[test]
     fn test_zeropagey_starts_not_done() {
-        let mode = ZeroPageY::new();
+        let mode = ZeroPageY::new(MemoryAccess::Read);
         assert!(
             !mode.is_done(),
             "ZeroPageY mode should not be done initially"
@@ -1521,21 +1503,15 @@ mod new_tests {
     #[test]
     fn test_zeropagey_completes_after_three_ticks() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write base zero page address at PC
-        memory.borrow_mut().write(0x0200, 0x80, false);
+        memory.write(0x0200, 0x80, false);
         // Write value at 0x87 (0x80 + 0x07)
-        memory.borrow_mut().write(0x0087, 0xCC, false);
+        memory.write(0x0087, 0xCC, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1550,18 +1526,18 @@ mod new_tests {
             irq_pending: false,
         };
 
-        let mut mode = ZeroPageY::new();
+        let mut mode = ZeroPageY::new(MemoryAccess::Read);
 
         // First tick - read base address
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after first tick");
 
         // Second tick - dummy read and add index
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after second tick");
 
         // Third tick - read value
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(mode.is_done(), "Should be done after third tick");
         assert_eq!(
             mode.get_address(),
@@ -1575,21 +1551,15 @@ mod new_tests {
     #[test]
     fn test_zeropagey_wraps_around() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write base zero page address at PC
-        memory.borrow_mut().write(0x0200, 0xFE, false);
+        memory.write(0x0200, 0xFE, false);
         // Write value at 0x0E (wraps around)
-        memory.borrow_mut().write(0x000E, 0xDD, false);
+        memory.write(0x000E, 0xDD, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1604,11 +1574,11 @@ mod new_tests {
             irq_pending: false,
         };
 
-        let mut mode = ZeroPageY::new();
+        let mut mode = ZeroPageY::new(MemoryAccess::Read);
 
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
+        mode.tick(&mut cpu_state, &mut memory);
+        mode.tick(&mut cpu_state, &mut memory);
 
         assert!(mode.is_done(), "Should be done after three ticks");
         // 0xFE + 0x10 = 0x10E, but wraps to 0x0E in zero page
@@ -1623,7 +1593,7 @@ mod new_tests {
     #[test]
     #[should_panic(expected = "ZeroPageY::get_address called before addressing complete")]
     fn test_zeropagey_get_address_before_done_panics() {
-        let mode = ZeroPageY::new();
+        let mode = ZeroPageY::new(MemoryAccess::Read);
         mode.get_address(); // Should panic in debug builds
     }
 
@@ -1641,22 +1611,16 @@ mod new_tests {
     #[test]
     fn test_absolutex_completes_after_three_ticks_no_page_cross() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write address bytes (little-endian)
-        memory.borrow_mut().write(0x0200, 0x00, false); // Low byte
-        memory.borrow_mut().write(0x0201, 0x12, false); // High byte
+        memory.write(0x0200, 0x00, false); // Low byte
+        memory.write(0x0201, 0x12, false); // High byte
         // Write value at 0x1205
-        memory.borrow_mut().write(0x1205, 0xEE, false);
+        memory.write(0x1205, 0xEE, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1674,15 +1638,15 @@ mod new_tests {
         let mut mode = AbsoluteX::new(MemoryAccess::Read);
 
         // First tick - read low byte
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after first tick");
 
         // Second tick - read high byte
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after second tick");
 
         // Third tick - read value (no page cross)
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(
             mode.is_done(),
             "Should be done after third tick (no page cross)"
@@ -1699,22 +1663,16 @@ mod new_tests {
     #[test]
     fn test_absolutex_completes_after_four_ticks_with_page_cross() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write address bytes (little-endian)
-        memory.borrow_mut().write(0x0200, 0xFF, false); // Low byte
-        memory.borrow_mut().write(0x0201, 0x12, false); // High byte
+        memory.write(0x0200, 0xFF, false); // Low byte
+        memory.write(0x0201, 0x12, false); // High byte
         // Write value at 0x1304
-        memory.borrow_mut().write(0x1304, 0xFF, false);
+        memory.write(0x1304, 0xFF, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1732,22 +1690,22 @@ mod new_tests {
         let mut mode = AbsoluteX::new(MemoryAccess::Read);
 
         // First tick - read low byte
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after first tick");
 
         // Second tick - read high byte, detect page cross
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(
             !mode.is_done(),
             "Should not be done after second tick (page crossed)"
         );
 
         // Third tick - dummy read for page cross penalty
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after third tick");
 
         // Fourth tick - read value
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(mode.is_done(), "Should be done after fourth tick");
         assert_eq!(
             mode.get_address(),
@@ -1779,22 +1737,16 @@ mod new_tests {
     #[test]
     fn test_absolutey_completes_after_three_ticks_no_page_cross() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write address bytes (little-endian)
-        memory.borrow_mut().write(0x0200, 0x00, false); // Low byte
-        memory.borrow_mut().write(0x0201, 0x12, false); // High byte
+        memory.write(0x0200, 0x00, false); // Low byte
+        memory.write(0x0201, 0x12, false); // High byte
         // Write value at 0x1208
-        memory.borrow_mut().write(0x1208, 0x11, false);
+        memory.write(0x1208, 0x11, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1812,15 +1764,15 @@ mod new_tests {
         let mut mode = AbsoluteY::new(MemoryAccess::Read);
 
         // First tick - read low byte
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after first tick");
 
         // Second tick - read high byte
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after second tick");
 
         // Third tick - read value (no page cross)
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(
             mode.is_done(),
             "Should be done after third tick (no page cross)"
@@ -1837,22 +1789,16 @@ mod new_tests {
     #[test]
     fn test_absolutey_completes_after_four_ticks_with_page_cross() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // Write address bytes (little-endian)
-        memory.borrow_mut().write(0x0200, 0xFE, false); // Low byte
-        memory.borrow_mut().write(0x0201, 0x12, false); // High byte
+        memory.write(0x0200, 0xFE, false); // Low byte
+        memory.write(0x0201, 0x12, false); // High byte
         // Write value at 0x130E
-        memory.borrow_mut().write(0x130E, 0x22, false);
+        memory.write(0x130E, 0x22, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1870,22 +1816,22 @@ mod new_tests {
         let mut mode = AbsoluteY::new(MemoryAccess::Read);
 
         // First tick - read low byte
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after first tick");
 
         // Second tick - read high byte, detect page cross
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(
             !mode.is_done(),
             "Should not be done after second tick (page crossed)"
         );
 
         // Third tick - dummy read for page cross penalty
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after third tick");
 
         // Fourth tick - read value
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(mode.is_done(), "Should be done after fourth tick");
         assert_eq!(
             mode.get_address(),
@@ -1917,23 +1863,17 @@ mod new_tests {
     #[test]
     fn test_indirect_completes_after_four_ticks() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // JMP ($0210) - indirect address at $0210
-        memory.borrow_mut().write(0x0200, 0x10, false); // Pointer low byte
-        memory.borrow_mut().write(0x0201, 0x02, false); // Pointer high byte
+        memory.write(0x0200, 0x10, false); // Pointer low byte
+        memory.write(0x0201, 0x02, false); // Pointer high byte
         // Target address at $0210
-        memory.borrow_mut().write(0x0210, 0x34, false); // Target low byte
-        memory.borrow_mut().write(0x0211, 0x12, false); // Target high byte
+        memory.write(0x0210, 0x34, false); // Target low byte
+        memory.write(0x0211, 0x12, false); // Target high byte
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -1951,19 +1891,19 @@ mod new_tests {
         let mut mode = Indirect::new();
 
         // Tick 1 - read pointer low byte
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after tick 0");
 
         // Tick 2 - read pointer high byte
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after tick 0");
 
         // Tick 3 - read target low byte
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(!mode.is_done(), "Should not be done after tick 3");
 
         // Tick 4 - read target high byte
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(mode.is_done(), "Should be done after tick 4");
         assert_eq!(
             mode.get_address(),
@@ -1976,25 +1916,19 @@ mod new_tests {
     #[test]
     fn test_indirect_page_boundary_bug() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // JMP ($02FF) - pointer at page boundary
         // PC starts at 0x0400
-        memory.borrow_mut().write(0x0400, 0xFF, false); // Pointer low byte at PC
-        memory.borrow_mut().write(0x0401, 0x02, false); // Pointer high byte at PC+1
+        memory.write(0x0400, 0xFF, false); // Pointer low byte at PC
+        memory.write(0x0401, 0x02, false); // Pointer high byte at PC+1
         // The pointer is $02FF
-        memory.borrow_mut().write(0x02FF, 0x34, false); // Target low byte at $02FF
-        memory.borrow_mut().write(0x0200, 0x56, false); // Target high byte at $0200 (page wrap bug)
-        memory.borrow_mut().write(0x0300, 0x78, false); // This would be read if there was no bug
+        memory.write(0x02FF, 0x34, false); // Target low byte at $02FF
+        memory.write(0x0200, 0x56, false); // Target high byte at $0200 (page wrap bug)
+        memory.write(0x0300, 0x78, false); // This would be read if there was no bug
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -2011,10 +1945,10 @@ mod new_tests {
 
         let mut mode = Indirect::new();
 
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
+        mode.tick(&mut cpu_state, &mut memory);
+        mode.tick(&mut cpu_state, &mut memory);
+        mode.tick(&mut cpu_state, &mut memory);
 
         assert!(mode.is_done(), "Should be done after four ticks");
         // Because of the page boundary bug, high byte comes from $0200 (0x56), not $0300 (0x78)
@@ -2042,23 +1976,17 @@ mod new_tests {
     #[test]
     fn test_indexed_indirect_completes_after_five_ticks() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // LDA ($20,X) where X=0x05
-        memory.borrow_mut().write(0x0400, 0x20, false); // Base address at PC
-        memory.borrow_mut().write(0x25, 0x34, false); // Pointer low at $25 ($20 + X)
-        memory.borrow_mut().write(0x26, 0x12, false); // Pointer high at $26
+        memory.write(0x0400, 0x20, false); // Base address at PC
+        memory.write(0x25, 0x34, false); // Pointer low at $25 ($20 + X)
+        memory.write(0x26, 0x12, false); // Pointer high at $26
         // Final address should be $1234
-        memory.borrow_mut().write(0x1234, 0x99, false); // Value at $1234
+        memory.write(0x1234, 0x99, false); // Value at $1234
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -2076,7 +2004,7 @@ mod new_tests {
         let mut mode = IndexedIndirect::new(MemoryAccess::Read);
 
         for i in 1..=5 {
-            mode.tick(&mut cpu_state, Rc::clone(&memory));
+            mode.tick(&mut cpu_state, &mut memory);
             if i < 5 {
                 assert!(!mode.is_done(), "Should not be done after tick {}", i);
             }
@@ -2097,23 +2025,17 @@ mod new_tests {
     #[test]
     fn test_indexed_indirect_wraps_in_zero_page() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // LDA ($FF,X) where X=0x02 - should wrap to $01
-        memory.borrow_mut().write(0x0400, 0xFF, false); // Base address at PC
-        memory.borrow_mut().write(0x01, 0x78, false); // Pointer low at $01 ($FF + 2 = $01 wrapped)
-        memory.borrow_mut().write(0x02, 0x05, false); // Pointer high at $02 (use 0x0578 which is in RAM)
+        memory.write(0x0400, 0xFF, false); // Base address at PC
+        memory.write(0x01, 0x78, false); // Pointer low at $01 ($FF + 2 = $01 wrapped)
+        memory.write(0x02, 0x05, false); // Pointer high at $02 (use 0x0578 which is in RAM)
         // Final address should be $0578 (RAM)
-        memory.borrow_mut().write(0x0578, 0xAA, false); // Value at $0578
+        memory.write(0x0578, 0xAA, false); // Value at $0578
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -2131,7 +2053,7 @@ mod new_tests {
         let mut mode = IndexedIndirect::new(MemoryAccess::Read);
 
         for _ in 0..5 {
-            mode.tick(&mut cpu_state, Rc::clone(&memory));
+            mode.tick(&mut cpu_state, &mut memory);
         }
 
         assert!(mode.is_done(), "Should be done after five ticks");
@@ -2146,25 +2068,19 @@ mod new_tests {
     #[test]
     fn test_indexed_indirect_pointer_wraps_in_zero_page() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // LDA ($FE,X) where X=0x00 - pointer at $FE/$FF, high byte wraps to $00
-        memory.borrow_mut().write(0x0400, 0xFE, false); // Base address at PC
-        memory.borrow_mut().write(0xFE, 0xAB, false); // Pointer low at $FE
-        memory.borrow_mut().write(0xFF, 0x05, false); // Pointer high at $FF (use 0x05AB which is RAM)
-        memory.borrow_mut().write(0x00, 0xEF, false); // Would wrap to $00 if pointer read wraps
+        memory.write(0x0400, 0xFE, false); // Base address at PC
+        memory.write(0xFE, 0xAB, false); // Pointer low at $FE
+        memory.write(0xFF, 0x05, false); // Pointer high at $FF (use 0x05AB which is RAM)
+        memory.write(0x00, 0xEF, false); // Would wrap to $00 if pointer read wraps
         // When reading pointer from $FE/$FF, high byte should come from $FF, not wrap to $00
         // Final address should be $05AB
-        memory.borrow_mut().write(0x05AB, 0xBB, false); // Value at $05AB
+        memory.write(0x05AB, 0xBB, false); // Value at $05AB
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -2182,7 +2098,7 @@ mod new_tests {
         let mut mode = IndexedIndirect::new(MemoryAccess::Read);
 
         for _ in 0..5 {
-            mode.tick(&mut cpu_state, Rc::clone(&memory));
+            mode.tick(&mut cpu_state, &mut memory);
         }
 
         assert!(mode.is_done(), "Should be done after five ticks");
@@ -2204,23 +2120,17 @@ mod new_tests {
     #[test]
     fn test_indirect_indexed_completes_after_four_ticks_no_page_cross() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // LDA ($20),Y where Y=0x05
-        memory.borrow_mut().write(0x0400, 0x20, false); // Zero-page pointer address at PC
-        memory.borrow_mut().write(0x20, 0x30, false); // Pointer low at $20
-        memory.borrow_mut().write(0x21, 0x12, false); // Pointer high at $21
+        memory.write(0x0400, 0x20, false); // Zero-page pointer address at PC
+        memory.write(0x20, 0x30, false); // Pointer low at $20
+        memory.write(0x21, 0x12, false); // Pointer high at $21
         // Base pointer is $1230, Y=0x05, final address = $1235 (no page cross)
-        memory.borrow_mut().write(0x1235, 0xCC, false); // Value at $1235
+        memory.write(0x1235, 0xCC, false); // Value at $1235
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -2238,7 +2148,7 @@ mod new_tests {
         let mut mode = IndirectIndexed::new(MemoryAccess::Read);
 
         for i in 1..=4 {
-            mode.tick(&mut cpu_state, Rc::clone(&memory));
+            mode.tick(&mut cpu_state, &mut memory);
             if i < 4 {
                 assert!(!mode.is_done(), "Should not be done after tick {}", i);
             }
@@ -2259,23 +2169,17 @@ mod new_tests {
     #[test]
     fn test_indirect_indexed_completes_after_five_ticks_with_page_cross() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // LDA ($20),Y where Y=0xFF causes page cross
-        memory.borrow_mut().write(0x0400, 0x20, false); // Zero-page pointer address at PC
-        memory.borrow_mut().write(0x20, 0x80, false); // Pointer low at $20
-        memory.borrow_mut().write(0x21, 0x12, false); // Pointer high at $21
+        memory.write(0x0400, 0x20, false); // Zero-page pointer address at PC
+        memory.write(0x20, 0x80, false); // Pointer low at $20
+        memory.write(0x21, 0x12, false); // Pointer high at $21
         // Base pointer is $1280, Y=0xFF, final address = $137F (page cross: $12 -> $13)
-        memory.borrow_mut().write(0x137F, 0xDD, false); // Value at $137F
+        memory.write(0x137F, 0xDD, false); // Value at $137F
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -2293,7 +2197,7 @@ mod new_tests {
         let mut mode = IndirectIndexed::new(MemoryAccess::Read);
 
         for i in 1..=5 {
-            mode.tick(&mut cpu_state, Rc::clone(&memory));
+            mode.tick(&mut cpu_state, &mut memory);
             if i < 5 {
                 assert!(!mode.is_done(), "Should not be done after tick {}", i);
             }
@@ -2321,24 +2225,18 @@ mod new_tests {
     #[test]
     fn test_indirect_indexed_pointer_wraps_in_zero_page() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // LDA ($FF),Y - pointer at $FF/$00 (wraps in zero page)
-        memory.borrow_mut().write(0x0400, 0xFF, false); // Zero-page pointer address at PC
-        memory.borrow_mut().write(0xFF, 0x34, false); // Pointer low at $FF
-        memory.borrow_mut().write(0x00, 0x12, false); // Pointer high at $00 (wrapped)
-        memory.borrow_mut().write(0x01, 0x56, false); // Should not read this
+        memory.write(0x0400, 0xFF, false); // Zero-page pointer address at PC
+        memory.write(0xFF, 0x34, false); // Pointer low at $FF
+        memory.write(0x00, 0x12, false); // Pointer high at $00 (wrapped)
+        memory.write(0x01, 0x56, false); // Should not read this
         // Base pointer is $1234, Y=0x05, final address = $1239
-        memory.borrow_mut().write(0x1239, 0xEE, false); // Value at $1239
+        memory.write(0x1239, 0xEE, false); // Value at $1239
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -2356,7 +2254,7 @@ mod new_tests {
         let mut mode = IndirectIndexed::new(MemoryAccess::Read);
 
         for _ in 0..4 {
-            mode.tick(&mut cpu_state, Rc::clone(&memory));
+            mode.tick(&mut cpu_state, &mut memory);
         }
 
         assert!(mode.is_done(), "Should be done after four ticks");
@@ -2378,19 +2276,13 @@ mod new_tests {
     #[test]
     fn test_relative_completes_after_one_tick() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // BEQ with offset +10 (0x0A)
-        memory.borrow_mut().write(0x0400, 0x0A, false); // Positive offset at PC
+        memory.write(0x0400, 0x0A, false); // Positive offset at PC
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -2407,7 +2299,7 @@ mod new_tests {
 
         let mut mode = Relative::new();
 
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
         assert!(mode.is_done(), "Should be done after one tick");
         // PC was 0x0400, after reading offset it's 0x0401, offset is +10, so target is 0x040B
         assert_eq!(
@@ -2427,19 +2319,13 @@ mod new_tests {
     #[test]
     fn test_relative_positive_offset() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // BEQ with offset +127 (0x7F, maximum positive)
-        memory.borrow_mut().write(0x0400, 0x7F, false);
+        memory.write(0x0400, 0x7F, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -2455,7 +2341,7 @@ mod new_tests {
         };
 
         let mut mode = Relative::new();
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
 
         assert!(mode.is_done(), "Should be done");
         // PC was 0x0400, after read it's 0x0401, +127 = 0x0480
@@ -2469,19 +2355,13 @@ mod new_tests {
     #[test]
     fn test_relative_negative_offset() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // BEQ with offset -2 (0xFE in two's complement)
-        memory.borrow_mut().write(0x0400, 0xFE, false);
+        memory.write(0x0400, 0xFE, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -2497,7 +2377,7 @@ mod new_tests {
         };
 
         let mut mode = Relative::new();
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
 
         assert!(mode.is_done(), "Should be done");
         // PC was 0x0400, after read it's 0x0401, -2 = 0x03FF
@@ -2507,19 +2387,13 @@ mod new_tests {
     #[test]
     fn test_relative_negative_offset_crosses_page() {
         use super::super::types::CpuState;
-        use crate::apu::Apu;
         use crate::mem_controller::MemController;
         use crate::nes::TvSystem;
-        use crate::ppu::Ppu;
-        use std::cell::RefCell;
-        use std::rc::Rc;
 
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // BEQ with offset -128 (0x80, maximum negative)
-        memory.borrow_mut().write(0x0450, 0x80, false);
+        memory.write(0x0450, 0x80, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -2535,7 +2409,7 @@ mod new_tests {
         };
 
         let mut mode = Relative::new();
-        mode.tick(&mut cpu_state, Rc::clone(&memory));
+        mode.tick(&mut cpu_state, &mut memory);
 
         assert!(mode.is_done(), "Should be done");
         // PC was 0x0450, after read it's 0x0451, -128 = 0x03D1
@@ -42,16 +42,13 @@ use super::types::{
 };
 use crate::cpu2::CpuState;
 use crate::mem_controller::MemController;
+use crate::savestate::{SaveState, StateReader, StateWriter};
 use core::panic;
-use std::cell::RefCell;
-use std::rc::Rc;
 
 /// NES 6502 CPU
 pub struct Cpu2 {
     /// State of the CPU
     state: CpuState,
-    /// Memory
-    memory: Rc<RefCell<MemController>>,
     /// Halted state (set by KIL instruction)
     halted: bool,
     /// Total cycles executed since last reset
@@ -81,7 +78,7 @@ pub struct Cpu2 {
 
 impl Cpu2 {
     /// Create a new CPU with default register values at power-on
-    pub fn new(memory: Rc<RefCell<MemController>>) -> Self {
+    pub fn new() -> Self {
         Self {
             state: CpuState {
                 a: 0,
@@ -97,7 +94,6 @@ impl Cpu2 {
                 nmi_pending: false,
                 irq_pending: false,
             },
-            memory,
             halted: false,
             total_cycles: 0,
             current_instruction: None,
@@ -118,16 +114,16 @@ impl Cpu2 {
         )
     }
 
-    /// Execute a single CPU cycle
+    /// Execute a single CPU cycle on the bus
     /// Returns true when the current instruction completes
-    pub fn tick_cycle(&mut self) -> bool {
+    pub fn tick_cycle(&mut self, memory: &mut MemController) -> bool {
         if self.halted {
             return true;
         }
 
         // If no current instruction, fetch and decode a new one
         if self.current_instruction.is_none() {
-            let opcode = self.memory.borrow().read(self.state.pc);
+            let opcode = memory.read_opcode(self.state.pc);
             self.current_opcode = opcode;
             if let Some(instruction) = Self::decode(opcode) {
                 self.state.pc = self.state.pc.wrapping_add(1);
@@ -151,7 +147,7 @@ impl Cpu2 {
 
         // Execute one cycle of the current instruction
         if let Some(ref mut instruction) = self.current_instruction {
-            instruction.tick(&mut self.state, Rc::clone(&self.memory));
+            instruction.tick(&mut self.state, memory);
 
             // Check if both addressing and instruction are done
             if instruction.is_done() {
@@ -160,8 +156,10 @@ impl Cpu2 {
 
                 // Clear in_interrupt_sequence flag when an instruction completes
                 // This allows interrupt polling to happen after at least one
-                // instruction has executed from the interrupt handler
-                self.in_interrupt_sequence = false;
+                // instruction has executed from the interrupt handler. BRK is an
+                // interrupt sequence itself, so the first handler instruction runs
+                // before an NMI that arrived too late to hijack it
+                self.in_interrupt_sequence = self.current_opcode == BRK;
 
                 // Handle interrupt check delay (1-instruction delay after CLI/SEI/PLP)
                 // If a delay was just requested by the current instruction, activate it
@@ -218,28 +216,28 @@ impl Cpu2 {
             DOP_ZP => {
                 // DOP Zero Page - read and discard (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Dop::new()),
                 ))
             }
             ORA_ZP => {
                 // ORA Zero Page: ORA zp
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Ora::new()),
                 ))
             }
             ASL_ZP => {
                 // ASL Zero Page: ASL zp - Arithmetic Shift Left
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Asl::new()),
                 ))
             }
             SLO_ZP => {
                 // SLO Zero Page: SLO zp - Shift left and OR (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Slo::new()),
                 ))
             }
@@ -321,28 +319,28 @@ impl Cpu2 {
             DOP_ZPX => {
                 // DOP Zero Page,X: DOP $nn,X (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Dop::new()),
                 ))
             }
             ORA_ZPX => {
                 // ORA Zero Page,X: ORA $nn,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Ora::new()),
                 ))
             }
             ASL_ZPX => {
                 // ASL Zero Page,X: ASL $nn,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Asl::new()),
                 ))
             }
             SLO_ZPX => {
                 // SLO Zero Page,X: SLO $nn,X (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Slo::new()),
                 ))
             }
@@ -421,28 +419,28 @@ impl Cpu2 {
             BIT_ZP => {
                 // BIT Zero Page: BIT zp - test bits in memory with accumulator
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Bit::new()),
                 ))
             }
             AND_ZP => {
                 // AND Zero Page: AND zp
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(And::new()),
                 ))
             }
             ROL_ZP => {
                 // ROL Zero Page: ROL zp - Rotate Left
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Rol::new()),
                 ))
             }
             RLA_ZP => {
                 // RLA Zero Page: RLA zp - Rotate left and AND (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Rla::new()),
                 ))
             }
@@ -524,28 +522,28 @@ impl Cpu2 {
             DOP_ZPX2 => {
                 // DOP Zero Page,X (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Dop::new()),
                 ))
             }
             AND_ZPX => {
                 // AND Zero Page,X: AND zp,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(And::new()),
                 ))
             }
             ROL_ZPX => {
                 // ROL Zero Page,X: ROL zp,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Rol::new()),
                 ))
             }
             RLA_ZPX => {
                 // RLA Zero Page,X: RLA zp,X (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Rla::new()),
                 ))
             }
@@ -624,28 +622,28 @@ impl Cpu2 {
             DOP_ZP2 => {
                 // DOP Zero Page (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Dop::new()),
                 ))
             }
             EOR_ZP => {
                 // EOR Zero Page: EOR $nn
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Eor::new()),
                 ))
             }
             LSR_ZP => {
                 // LSR Zero Page: LSR $nn
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Lsr::new()),
                 ))
             }
             SRE_ZP => {
                 // SRE Zero Page: SRE $nn (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Sre::new()),
                 ))
             }
@@ -731,28 +729,28 @@ impl Cpu2 {
             DOP_ZP3 => {
                 // DOP: Double NOP (Zero Page, illegal)
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Dop::new()),
                 ))
             }
             ADC_ZP => {
                 // ADC: Add with Carry (Zero Page)
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Adc::new()),
                 ))
             }
             ROR_ZP => {
                 // ROR: Rotate Right (Zero Page)
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Ror::new()),
                 ))
             }
             RRA_ZP => {
                 // RRA: Rotate Right then Add with Carry (Zero Page, illegal)
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Rra::new()),
                 ))
             }
@@ -827,28 +825,28 @@ impl Cpu2 {
             DOP_ZPX3 => {
                 // DOP Zero Page,X (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Dop::new()),
                 ))
             }
             EOR_ZPX => {
                 // EOR Zero Page,X: EOR $nn,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Eor::new()),
                 ))
             }
             LSR_ZPX => {
                 // LSR Zero Page,X: LSR $nn,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Lsr::new()),
                 ))
             }
             SRE_ZPX => {
                 // SRE Zero Page,X: SRE $nn,X (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Sre::new()),
                 ))
             }
@@ -884,28 +882,28 @@ impl Cpu2 {
             DOP_ZPX4 => {
                 // DOP Zero Page,X (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Dop::new()),
                 ))
             }
             ADC_ZPX => {
                 // ADC Zero Page,X: ADC $nn,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Adc::new()),
                 ))
             }
             ROR_ZPX => {
                 // ROR Zero Page,X: ROR $nn,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Ror::new()),
                 ))
             }
             RRA_ZPX => {
                 // RRA Zero Page,X: RRA $nn,X (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Rra::new()),
                 ))
             }
@@ -990,28 +988,28 @@ impl Cpu2 {
             STY_ZP => {
                 // STY Zero Page: STY $nn
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Write)),
                     Box::new(Sty::new()),
                 ))
             }
             STA_ZP => {
                 // STA Zero Page: STA $nn
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Write)),
                     Box::new(Sta::new()),
                 ))
             }
             STX_ZP => {
                 // STX Zero Page: STX $nn
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Write)),
                     Box::new(Stx::new()),
                 ))
             }
             SAX_ZP => {
                 // SAX Zero Page: SAX $nn (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Write)),
                     Box::new(Sax::new()),
                 ))
             }
@@ -1096,28 +1094,28 @@ impl Cpu2 {
             LDY_ZP => {
                 // LDY Zero Page: LDY $nn
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Ldy::new()),
                 ))
             }
             LDA_ZP => {
                 // LDA Zero Page: LDA $nn
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Lda::new()),
                 ))
             }
             LDX_ZP => {
                 // LDX Zero Page: LDX $nn
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Ldx::new()),
                 ))
             }
             LAX_ZP => {
                 // LAX Zero Page: LAX $nn (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Lax::new()),
                 ))
             }
@@ -1199,28 +1197,28 @@ impl Cpu2 {
             LDY_ZPX => {
                 // LDY Zero Page,X: LDY $nn,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Ldy::new()),
                 ))
             }
             LDA_ZPX => {
                 // LDA Zero Page,X: LDA $nn,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Lda::new()),
                 ))
             }
             LDX_ZPY => {
                 // LDX Zero Page,Y: LDX $nn,Y
                 Some(Instruction::new(
-                    Box::new(ZeroPageY::new()),
+                    Box::new(ZeroPageY::new(MemoryAccess::Read)),
                     Box::new(Ldx::new()),
                 ))
             }
             LAX_ZPY => {
                 // LAX Zero Page,Y: LAX $nn,Y (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPageY::new()),
+                    Box::new(ZeroPageY::new(MemoryAccess::Read)),
                     Box::new(Lax::new()),
                 ))
             }
@@ -1305,28 +1303,28 @@ impl Cpu2 {
             CPY_ZP => {
                 // CPY Zero Page: CPY $nn
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Cpy::new()),
                 ))
             }
             CMP_ZP => {
                 // CMP Zero Page: CMP $nn
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Cmp::new()),
                 ))
             }
             DEC_ZP => {
                 // DEC Zero Page: DEC $nn
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Dec::new()),
                 ))
             }
             DCP_ZP => {
                 // DCP Zero Page: DCP $nn (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Dcp::new()),
                 ))
             }
@@ -1408,28 +1406,28 @@ impl Cpu2 {
             DOP_ZPX5 => {
                 // DOP Zero Page,X (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Dop::new()),
                 ))
             }
             CMP_ZPX => {
                 // CMP Zero Page,X: CMP $nn,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Cmp::new()),
                 ))
             }
             DEC_ZPX => {
                 // DEC Zero Page,X: DEC $nn,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Dec::new()),
                 ))
             }
             DCP_ZPX => {
                 // DCP Zero Page,X: DCP $nn,X (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Dcp::new()),
                 ))
             }
@@ -1514,28 +1512,28 @@ impl Cpu2 {
             CPX_ZP => {
                 // CPX Zero Page: CPX $nn
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Cpx::new()),
                 ))
             }
             SBC_ZP => {
                 // SBC Zero Page: SBC $nn
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Sbc::new()),
                 ))
             }
             INC_ZP => {
                 // INC Zero Page: INC $nn
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Inc::new()),
                 ))
             }
             ISB_ZP => {
                 // ISB Zero Page: ISB $nn (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPage::new()),
+                    Box::new(ZeroPage::new(MemoryAccess::Read)),
                     Box::new(Isb::new()),
                 ))
             }
@@ -1617,28 +1615,28 @@ impl Cpu2 {
             DOP_ZPX6 => {
                 // DOP Zero Page,X: DOP $nn,X (illegal NOP)
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Dop::new()),
                 ))
             }
             SBC_ZPX => {
                 // SBC Zero Page,X: SBC $nn,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Sbc::new()),
                 ))
             }
             INC_ZPX => {
                 // INC Zero Page,X: INC $nn,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Inc::new()),
                 ))
             }
             ISB_ZPX => {
                 // ISB Zero Page,X: ISB $nn,X (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Read)),
                     Box::new(Isb::new()),
                 ))
             }
@@ -1766,28 +1764,28 @@ impl Cpu2 {
             STY_ZPX => {
                 // STY Zero Page,X: STY $nn,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Write)),
                     Box::new(Sty::new()),
                 ))
             }
             STA_ZPX => {
                 // STA Zero Page,X: STA $nn,X
                 Some(Instruction::new(
-                    Box::new(ZeroPageX::new()),
+                    Box::new(ZeroPageX::new(MemoryAccess::Write)),
                     Box::new(Sta::new()),
                 ))
             }
             STX_ZPY => {
                 // STX Zero Page,Y: STX $nn,Y
                 Some(Instruction::new(
-                    Box::new(ZeroPageY::new()),
+                    Box::new(ZeroPageY::new(MemoryAccess::Write)),
                     Box::new(Stx::new()),
                 ))
             }
             SAX_ZPY => {
                 // SAX Zero Page,Y: SAX $nn,Y (illegal opcode)
                 Some(Instruction::new(
-                    Box::new(ZeroPageY::new()),
+                    Box::new(ZeroPageY::new(MemoryAccess::Write)),
                     Box::new(Sax::new()),
                 ))
             }
@@ -1880,7 +1878,7 @@ impl Cpu2 {
     /// References:
     /// - https://www.nesdev.org/wiki/CPU_power_up_state
     /// - https://www.nesdev.org/wiki/CPU_interrupts#IRQ_and_NMI_tick-by-tick_execution
-    pub fn reset(&mut self) {
+    pub fn reset(&mut self, memory: &mut MemController) {
         // Cycles 1-2: Opcode fetch and read (both discarded)
         // These happen automatically before reset is called in hardware
         // We don't simulate them explicitly here since reset() is called directly
@@ -1889,16 +1887,16 @@ impl Cpu2 {
         // Each read decrements SP to simulate the stack push sequence
         for _ in 0..3 {
             let stack_addr = STACK_BASE | (self.state.sp as u16);
-            let _ = self.memory.borrow().read(stack_addr); // Dummy read
+            let _ = memory.read(stack_addr); // Dummy read
             self.state.sp = self.state.sp.wrapping_sub(1);
         }
 
         // Cycle 6: Read PCL from reset vector and set I flag
-        let pcl = self.memory.borrow().read(RESET_VECTOR);
+        let pcl = memory.read(RESET_VECTOR);
         self.state.p |= FLAG_INTERRUPT;
 
         // Cycle 7: Read PCH from reset vector
-        let pch = self.memory.borrow().read(RESET_VECTOR + 1);
+        let pch = memory.read(RESET_VECTOR + 1);
         self.state.pc = ((pch as u16) << 8) | (pcl as u16);
 
         // Clear cycle-accurate instruction state
@@ -1914,15 +1912,15 @@ impl Cpu2 {
 
     /// Trigger an NMI (Non-Maskable Interrupt)
     /// Returns the number of cycles consumed (7 cycles)
-    pub fn trigger_nmi(&mut self) -> u8 {
+    pub fn trigger_nmi(&mut self, memory: &mut MemController) -> u8 {
         // Push PC and P onto stack
-        self.push_word(self.state.pc);
+        self.push_word(memory, self.state.pc);
         let mut p_with_break = self.state.p & !FLAG_BREAK; // Clear Break flag
         p_with_break |= FLAG_UNUSED; // Set unused flag
-        self.push_byte(p_with_break);
+        self.push_byte(memory, p_with_break);
 
         // Set PC to NMI vector
-        self.state.pc = self.memory.borrow().read_u16(NMI_VECTOR);
+        self.state.pc = memory.read_u16(NMI_VECTOR);
 
         // Set Interrupt Disable flag
         self.state.p |= FLAG_INTERRUPT;
@@ -1940,21 +1938,21 @@ impl Cpu2 {
     }
 
     /// Read a 16-bit address from the reset vector at 0xFFFC-0xFFFD
-    fn read_reset_vector(&self) -> u16 {
-        self.memory.borrow().read_u16(RESET_VECTOR)
+    fn read_reset_vector(memory: &mut MemController) -> u16 {
+        memory.read_u16(RESET_VECTOR)
     }
 
     /// Push a byte onto the stack
-    fn push_byte(&mut self, value: u8) {
+    fn push_byte(&mut self, memory: &mut MemController, value: u8) {
         let addr = STACK_BASE | (self.state.sp as u16);
-        self.memory.borrow_mut().write(addr, value, false);
+        memory.write(addr, value, false);
         self.state.sp = self.state.sp.wrapping_sub(1);
     }
 
     /// Push a word onto the stack (high byte first)
-    fn push_word(&mut self, value: u16) {
-        self.push_byte((value >> 8) as u8); // High byte first
-        self.push_byte(value as u8); // Low byte second
+    fn push_word(&mut self, memory: &mut MemController, value: u16) {
+        self.push_byte(memory, (value >> 8) as u8); // High byte first
+        self.push_byte(memory, value as u8); // Low byte second
     }
 
     /// Add cycles to the total cycle count
@@ -2048,6 +2046,14 @@ impl Cpu2 {
         self.in_interrupt_sequence = false;
     }
 
+    /// Check if the last instruction executed was a branch
+    pub fn executed_branch(&self) -> bool {
+        matches!(
+            self.current_opcode,
+            BPL | BMI | BVC | BVS | BCC | BCS | BNE | BEQ
+        )
+    }
+
     /// Check if CPU is currently in an interrupt sequence
     pub fn is_in_interrupt_sequence(&self) -> bool {
         self.in_interrupt_sequence
@@ -2097,19 +2103,24 @@ impl Cpu2 {
     /// 6. Read PCL from IRQ vector ($FFFE), set I flag
     /// 7. Read PCH from IRQ vector ($FFFF)
     ///
+    /// An NMI that is pending by the time P is pushed hijacks the sequence:
+    /// the NMI vector is read instead and the NMI is serviced.
+    ///
     /// Returns the number of cycles consumed (7 cycles)
-    pub fn trigger_irq(&mut self) -> u8 {
+    pub fn trigger_irq(&mut self, memory: &mut MemController) -> u8 {
         // Push PC to stack (high byte first, then low byte)
-        self.push_word(self.state.pc);
+        self.push_word(memory, self.state.pc);
 
         // Push status register to stack with B flag clear, unused flag set
         let mut p_with_flags = self.state.p & !FLAG_BREAK; // Clear B flag (distinguishes IRQ from BRK)
         p_with_flags |= FLAG_UNUSED; // Set unused flag (always set when pushed)
-        self.push_byte(p_with_flags);
+        self.push_byte(memory, p_with_flags);
 
-        // Read PC from IRQ vector at $FFFE-$FFFF
-        let pcl = self.memory.borrow().read(IRQ_VECTOR);
-        let pch = self.memory.borrow().read(IRQ_VECTOR + 1);
+        // Read PC from IRQ vector at $FFFE-$FFFF, or the NMI vector if hijacked
+        let vector = self.get_interrupt_vector();
+        self.state.nmi_pending = false;
+        let pcl = memory.read(vector);
+        let pch = memory.read(vector + 1);
         self.state.pc = ((pch as u16) << 8) | (pcl as u16);
 
         // Set Interrupt Disable flag to prevent nested IRQs
@@ -2166,27 +2177,86 @@ impl Cpu2 {
     }
 }
 
+impl Default for Cpu2 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Saved between instructions, so there is never an instruction in progress
+impl SaveState for Cpu2 {
+    fn save_state(&self, state: &mut StateWriter) {
+        debug_assert!(
+            self.current_instruction.is_none(),
+            "CPU state saved in the middle of an instruction"
+        );
+        let cpu = &self.state;
+        for value in [cpu.a, cpu.x, cpu.y, cpu.sp, cpu.p] {
+            state.write_u8(value);
+        }
+        state.write_u16(cpu.pc);
+        for flag in [
+            cpu.delay_interrupt_check,
+            cpu.saved_i_flag,
+            cpu.nmi_pending,
+            cpu.irq_pending,
+            self.halted,
+            self.in_interrupt_sequence,
+            self.delay_interrupt_check,
+            self.saved_i_flag_for_delay,
+            self.nmi_line_prev,
+            self.irq_line,
+        ] {
+            state.write_bool(flag);
+        }
+        state.write_u64(self.total_cycles);
+        state.write_u8(self.current_opcode);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        let cpu = &mut self.state;
+        for value in [&mut cpu.a, &mut cpu.x, &mut cpu.y, &mut cpu.sp, &mut cpu.p] {
+            *value = state.read_u8()?;
+        }
+        cpu.pc = state.read_u16()?;
+        for flag in [
+            &mut cpu.delay_interrupt_check,
+            &mut cpu.saved_i_flag,
+            &mut cpu.nmi_pending,
+            &mut cpu.irq_pending,
+            &mut self.halted,
+            &mut self.in_interrupt_sequence,
+            &mut self.delay_interrupt_check,
+            &mut self.saved_i_flag_for_delay,
+            &mut self.nmi_line_prev,
+            &mut self.irq_line,
+        ] {
+            *flag = state.read_bool()?;
+        }
+        self.total_cycles = state.read_u64()?;
+        self.current_opcode = state.read_u8()?;
+        self.current_instruction = None;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::apu::Apu;
     use crate::nes::TvSystem;
-    use crate::ppu::Ppu;
 
     // Helper function to create a test memory controller
-    fn create_test_memory() -> Rc<RefCell<MemController>> {
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        Rc::new(RefCell::new(MemController::new(ppu, apu)))
+    fn create_test_memory() -> MemController {
+        MemController::new(TvSystem::Ntsc)
     }
 
     // Helper function to execute CPU until instruction completes
-    fn execute_instruction(cpu: &mut Cpu2) -> u64 {
+    fn execute_instruction(cpu: &mut Cpu2, memory: &mut MemController) -> u64 {
         let start_cycles = cpu.total_cycles();
         let mut instruction_complete = false;
         let mut safety = 0;
         while !instruction_complete && safety < 100 {
-            instruction_complete = cpu.tick_cycle();
+            instruction_complete = cpu.tick_cycle(memory);
             safety += 1;
         }
         assert!(instruction_complete, "Instruction did not complete");
@@ -2197,7 +2267,7 @@ mod tests {
     fn test_opcode_00() {
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Create a 32KB PRG ROM cartridge with IRQ vector at $FFFE-$FFFF
         let mut prg_rom = vec![0; 0x8000]; // 32KB
@@ -2212,15 +2282,15 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x8400; // Start at ROM address (not $0400)
         cpu.state.sp = 0xFD;
         cpu.state.p = 0b0010_0000; // Only unused flag set
         cpu.state.a = 0x42; // Some value to verify registers aren't affected
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // PC should now point to the IRQ handler at $8000
         assert_eq!(cpu.state.pc, 0x8000, "PC should be loaded from IRQ vector");
@@ -2233,13 +2303,13 @@ mod tests {
         );
 
         // Check return address on stack (PC+2 = $8402)
-        let pch = memory.borrow().read(0x01FD); // High byte at original SP
-        let pcl = memory.borrow().read(0x01FC); // Low byte at SP-1
+        let pch = memory.read(0x01FD); // High byte at original SP
+        let pcl = memory.read(0x01FC); // Low byte at SP-1
         let return_address = ((pch as u16) << 8) | (pcl as u16);
         assert_eq!(return_address, 0x8402, "Return address should be PC+2");
 
         // Check status register on stack (should have B flag set)
-        let status_on_stack = memory.borrow().read(0x01FB); // Status at SP-2
+        let status_on_stack = memory.read(0x01FB); // Status at SP-2
         assert_eq!(
             status_on_stack & FLAG_BREAK,
             FLAG_BREAK,
@@ -2269,7 +2339,7 @@ mod tests {
     fn test_reset_preserves_registers() {
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Create a simple ROM with reset vector pointing to $8000
         let mut prg_rom = vec![0; 0x8000]; // 32KB
@@ -2278,9 +2348,9 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Set registers to known values before reset
         cpu.state.a = 0x42;
@@ -2291,7 +2361,7 @@ mod tests {
         cpu.state.pc = 0x1234;
 
         // Call reset
-        cpu.reset();
+        cpu.reset(&mut memory);
 
         // According to https://www.nesdev.org/wiki/CPU_power_up_state:
         // - A, X, Y are unchanged
@@ -2353,7 +2423,7 @@ mod tests {
     fn test_power_on_state() {
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Create a simple ROM with reset vector
         let mut prg_rom = vec![0; 0x8000];
@@ -2362,9 +2432,9 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let cpu = Cpu2::new(Rc::clone(&memory));
+        let cpu = Cpu2::new();
 
         // According to https://www.nesdev.org/wiki/CPU_power_up_state
         // At power-on (before reset sequence):
@@ -2379,14 +2449,17 @@ mod tests {
         );
         assert_eq!(cpu.total_cycles, 0, "Cycle count should be 0 at power-on");
         assert!(!cpu.halted, "CPU should not be halted at power-on");
-        assert!(!cpu.state.nmi_pending, "NMI should not be pending at power-on");
+        assert!(
+            !cpu.state.nmi_pending,
+            "NMI should not be pending at power-on"
+        );
     }
 
     #[test]
     fn test_reset_after_power_on() {
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Create a ROM with reset vector pointing to $C000
         let mut prg_rom = vec![0; 0x8000];
@@ -2395,16 +2468,16 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Verify initial power-on state
         assert_eq!(cpu.state.sp, 0x00, "SP should be 0x00 at power-on");
         assert_eq!(cpu.state.p, FLAG_UNUSED, "Only unused bit should be set");
 
         // Call reset (simulates what happens after power-on)
-        cpu.reset();
+        cpu.reset(&mut memory);
 
         // After reset following power-on:
         // - A, X, Y remain 0 (they were 0 at power-on)
@@ -2441,7 +2514,7 @@ mod tests {
     fn test_reset_with_sp_wrapping() {
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         let mut prg_rom = vec![0; 0x8000];
         prg_rom[0x7FFC] = 0x00;
@@ -2449,23 +2522,23 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Test SP wrapping: if SP is 0x01, after subtracting 3 it should wrap to 0xFE
         cpu.state.sp = 0x01;
-        cpu.reset();
+        cpu.reset(&mut memory);
         assert_eq!(cpu.state.sp, 0xFE, "SP should wrap: 0x01 - 3 = 0xFE");
 
         // Test SP wrapping: if SP is 0x00, it should wrap to 0xFD
         cpu.state.sp = 0x00;
-        cpu.reset();
+        cpu.reset(&mut memory);
         assert_eq!(cpu.state.sp, 0xFD, "SP should wrap: 0x00 - 3 = 0xFD");
 
         // Test SP wrapping: if SP is 0x02, it should become 0xFF
         cpu.state.sp = 0x02;
-        cpu.reset();
+        cpu.reset(&mut memory);
         assert_eq!(cpu.state.sp, 0xFF, "SP should wrap: 0x02 - 3 = 0xFF");
     }
 
@@ -2473,7 +2546,7 @@ mod tests {
     fn test_multiple_resets() {
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         let mut prg_rom = vec![0; 0x8000];
         prg_rom[0x7FFC] = 0x34;
@@ -2481,9 +2554,9 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Set some register state
         cpu.state.a = 0xAA;
@@ -2493,13 +2566,13 @@ mod tests {
         cpu.state.p = FLAG_CARRY | FLAG_ZERO;
 
         // First reset
-        cpu.reset();
+        cpu.reset(&mut memory);
         assert_eq!(cpu.state.a, 0xAA, "A preserved after first reset");
         assert_eq!(cpu.state.sp, 0xFA, "SP = 0xFD - 3 = 0xFA");
         assert_eq!(cpu.state.pc, 0x1234, "PC loaded from vector");
 
         // Second reset - registers should still be preserved
-        cpu.reset();
+        cpu.reset(&mut memory);
         assert_eq!(cpu.state.a, 0xAA, "A still preserved after second reset");
         assert_eq!(cpu.state.x, 0xBB, "X still preserved after second reset");
         assert_eq!(cpu.state.y, 0xCC, "Y still preserved after second reset");
@@ -2516,7 +2589,7 @@ mod tests {
     fn test_reset_performs_dummy_stack_reads() {
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         let mut prg_rom = vec![0; 0x8000];
         prg_rom[0x7FFC] = 0x00; // Reset vector low byte
@@ -2524,33 +2597,33 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Set up known values in stack memory to verify reads happen
         cpu.state.sp = 0xFD;
-        memory.borrow_mut().write(0x01FD, 0xAA, false); // Will be read during cycle 3
-        memory.borrow_mut().write(0x01FC, 0xBB, false); // Will be read during cycle 4
-        memory.borrow_mut().write(0x01FB, 0xCC, false); // Will be read during cycle 5
+        memory.write(0x01FD, 0xAA, false); // Will be read during cycle 3
+        memory.write(0x01FC, 0xBB, false); // Will be read during cycle 4
+        memory.write(0x01FB, 0xCC, false); // Will be read during cycle 5
 
         // These values should NOT be affected by reset (reads only, no writes)
-        cpu.reset();
+        cpu.reset(&mut memory);
 
         // Verify the stack memory was read but NOT modified
         // (Reset performs dummy reads, not writes)
         assert_eq!(
-            memory.borrow().read(0x01FD),
+            memory.read(0x01FD),
             0xAA,
             "Stack at 0x01FD should be unchanged (read, not written)"
         );
         assert_eq!(
-            memory.borrow().read(0x01FC),
+            memory.read(0x01FC),
             0xBB,
             "Stack at 0x01FC should be unchanged (read, not written)"
         );
         assert_eq!(
-            memory.borrow().read(0x01FB),
+            memory.read(0x01FB),
             0xCC,
             "Stack at 0x01FB should be unchanged (read, not written)"
         );
@@ -2569,7 +2642,7 @@ mod tests {
     fn test_irq_trigger_basic() {
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up IRQ vector at $FFFE-$FFFF to point to $9000
         let mut prg_rom = vec![0; 0x8000];
@@ -2580,9 +2653,9 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Set up CPU state before IRQ
         cpu.state.pc = 0x1234;
@@ -2593,7 +2666,7 @@ mod tests {
         let initial_cycles = cpu.total_cycles;
 
         // Trigger IRQ
-        let cycles = cpu.trigger_irq();
+        let cycles = cpu.trigger_irq(&mut memory);
 
         // Verify IRQ took 7 cycles
         assert_eq!(cycles, 7, "IRQ should take 7 cycles");
@@ -2620,19 +2693,11 @@ mod tests {
         assert_eq!(cpu.state.sp, 0xFA, "SP should be decremented by 3");
 
         // Verify stack contents: PCH, PCL, P (with B flag clear)
-        assert_eq!(
-            memory.borrow().read(0x01FD),
-            0x12,
-            "PCH should be pushed to stack"
-        );
-        assert_eq!(
-            memory.borrow().read(0x01FC),
-            0x34,
-            "PCL should be pushed to stack"
-        );
+        assert_eq!(memory.read(0x01FD), 0x12, "PCH should be pushed to stack");
+        assert_eq!(memory.read(0x01FC), 0x34, "PCL should be pushed to stack");
 
         // Status should have B flag clear (0) and unused flag set (1)
-        let pushed_p = memory.borrow().read(0x01FB);
+        let pushed_p = memory.read(0x01FB);
         assert_eq!(
             pushed_p & FLAG_BREAK,
             0,
@@ -2659,7 +2724,7 @@ mod tests {
     fn test_irq_respects_i_flag() {
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         let mut prg_rom = vec![0; 0x8000];
         prg_rom[0x7FFC] = 0x00;
@@ -2669,9 +2734,9 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Set I flag - IRQ should NOT be polled
         cpu.state.p = FLAG_INTERRUPT;
@@ -2702,7 +2767,7 @@ mod tests {
     fn test_irq_clears_b_flag() {
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         let mut prg_rom = vec![0; 0x8000];
         prg_rom[0x7FFC] = 0x00;
@@ -2712,19 +2777,19 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         cpu.state.pc = 0x1000;
         cpu.state.sp = 0xFD;
         cpu.state.p = FLAG_BREAK; // Set B flag explicitly
         cpu.state.irq_pending = true;
 
-        cpu.trigger_irq();
+        cpu.trigger_irq(&mut memory);
 
         // Check pushed status has B flag clear
-        let pushed_p = memory.borrow().read(0x01FB);
+        let pushed_p = memory.read(0x01FB);
         assert_eq!(
             pushed_p & FLAG_BREAK,
             0,
@@ -2739,8 +2804,7 @@ mod tests {
 
     #[test]
     fn test_irq_set_and_check() {
-        let memory = create_test_memory();
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Initially no IRQ
         assert!(!cpu.is_irq_pending(), "IRQ should not be pending initially");
@@ -2761,7 +2825,7 @@ mod tests {
     fn test_irq_stack_wrapping() {
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         let mut prg_rom = vec![0; 0x8000];
         prg_rom[0x7FFC] = 0x00;
@@ -2771,9 +2835,9 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Set SP to a low value that will wrap during push operations
         cpu.state.pc = 0xABCD;
@@ -2781,7 +2845,7 @@ mod tests {
         cpu.state.p = 0;
         cpu.state.irq_pending = true;
 
-        cpu.trigger_irq();
+        cpu.trigger_irq(&mut memory);
 
         // Verify stack pointer wrapped correctly
         assert_eq!(
@@ -2790,10 +2854,10 @@ mod tests {
         );
 
         // Verify data was pushed to correct wrapped addresses
-        assert_eq!(memory.borrow().read(0x0101), 0xAB, "PCH pushed to 0x0101");
-        assert_eq!(memory.borrow().read(0x0100), 0xCD, "PCL pushed to 0x0100");
+        assert_eq!(memory.read(0x0101), 0xAB, "PCH pushed to 0x0101");
+        assert_eq!(memory.read(0x0100), 0xCD, "PCL pushed to 0x0100");
         assert_eq!(
-            memory.borrow().read(0x01FF) & FLAG_UNUSED,
+            memory.read(0x01FF) & FLAG_UNUSED,
             FLAG_UNUSED,
             "Status pushed to 0x01FF (wrapped)"
         );
@@ -2803,7 +2867,7 @@ mod tests {
     fn test_interrupt_polling_nmi_priority() {
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up interrupt vectors
         let mut prg_rom = vec![0; 0x8000];
@@ -2819,9 +2883,9 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x8000;
         cpu.state.sp = 0xFD;
         cpu.state.p = 0; // I flag clear, so IRQ can be serviced
@@ -2833,7 +2897,7 @@ mod tests {
         // Execute NOP instruction cycle by cycle
         let mut cycles = 0;
         loop {
-            let done = cpu.tick_cycle();
+            let done = cpu.tick_cycle(&mut memory);
             cycles += 1;
             if done {
                 break;
@@ -2875,7 +2939,7 @@ mod tests {
     fn test_interrupt_not_polled_during_interrupt_sequence() {
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         let mut prg_rom = vec![0; 0x8000];
         prg_rom[0x7FFC] = 0x00; // Reset vector
@@ -2888,9 +2952,9 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x1234;
         cpu.state.sp = 0xFD;
         cpu.state.p = 0;
@@ -2899,7 +2963,7 @@ mod tests {
         cpu.state.nmi_pending = true;
 
         // Trigger NMI
-        cpu.trigger_nmi();
+        cpu.trigger_nmi(&mut memory);
 
         // PC should now point to NMI handler
         assert_eq!(cpu.state.pc, 0x9000, "PC should be at NMI handler");
@@ -2922,7 +2986,7 @@ mod tests {
         // Now execute one instruction (NOP) from the handler
         let mut cycles = 0;
         loop {
-            let done = cpu.tick_cycle();
+            let done = cpu.tick_cycle(&mut memory);
             cycles += 1;
             if done {
                 break;
@@ -2948,7 +3012,7 @@ mod tests {
     fn test_nmi_clears_b_flag() {
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         let mut prg_rom = vec![0; 0x8000];
         prg_rom[0x7FFA] = 0x00; // NMI vector ($9000)
@@ -2956,19 +3020,19 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         cpu.state.pc = 0x1000;
         cpu.state.sp = 0xFD;
         cpu.state.p = FLAG_BREAK | FLAG_ZERO | FLAG_CARRY; // Set B flag and some other flags
         cpu.state.nmi_pending = true;
 
-        cpu.trigger_nmi();
+        cpu.trigger_nmi(&mut memory);
 
         // Check pushed status has B flag clear but other flags preserved
-        let pushed_p = memory.borrow().read(0x01FB);
+        let pushed_p = memory.read(0x01FB);
         assert_eq!(
             pushed_p & FLAG_BREAK,
             0,
@@ -2993,18 +3057,18 @@ mod tests {
 
     #[test]
     fn test_rti_ignores_break_and_unused_bits() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RTI instruction at address $0400
-        memory.borrow_mut().write(0x0400, RTI, false);
+        memory.write(0x0400, RTI, false);
 
         // Set up stack with status byte that has both B and unused bits set
         let status_on_stack = 0xFF; // All flags set including B=1 and unused=1
-        memory.borrow_mut().write(0x01FD, status_on_stack, false); // Status
-        memory.borrow_mut().write(0x01FE, 0x34, false); // PCL
-        memory.borrow_mut().write(0x01FF, 0x12, false); // PCH
+        memory.write(0x01FD, status_on_stack, false); // Status
+        memory.write(0x01FE, 0x34, false); // PCL
+        memory.write(0x01FF, 0x12, false); // PCH
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.sp = 0xFC; // Points below the three stack bytes
         cpu.state.p = 0x00; // Start with all flags clear
@@ -3012,7 +3076,7 @@ mod tests {
         // Execute RTI instruction
         let mut cycles = 0;
         loop {
-            let done = cpu.tick_cycle();
+            let done = cpu.tick_cycle(&mut memory);
             cycles += 1;
             if done {
                 break;
@@ -3069,16 +3133,16 @@ mod tests {
 
     #[test]
     fn test_plp_ignores_break_and_unused_bits() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up PLP instruction at address $0400
-        memory.borrow_mut().write(0x0400, PLP, false);
+        memory.write(0x0400, PLP, false);
 
         // Set up stack with status byte that has both B and unused bits set
         let status_on_stack = 0xFF; // All flags set including B=1 and unused=1
-        memory.borrow_mut().write(0x01FD, status_on_stack, false); // Status
+        memory.write(0x01FD, status_on_stack, false); // Status
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.sp = 0xFC; // Points below the stack byte
         cpu.state.p = 0x00; // Start with all flags clear
@@ -3086,7 +3150,7 @@ mod tests {
         // Execute PLP instruction
         let mut cycles = 0;
         loop {
-            let done = cpu.tick_cycle();
+            let done = cpu.tick_cycle(&mut memory);
             cycles += 1;
             if done {
                 break;
@@ -3142,27 +3206,27 @@ mod tests {
 
     #[test]
     fn test_nop_performs_dummy_read() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up NOP instruction at address $0400
-        memory.borrow_mut().write(0x0400, NOP, false);
+        memory.write(0x0400, NOP, false);
         // Next byte that should be read during dummy read
-        memory.borrow_mut().write(0x0401, 0x42, false);
+        memory.write(0x0401, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
 
         // Mark memory as unread at 0x0401
         let test_addr = 0x0401;
-        let initial_value = memory.borrow().read(test_addr);
+        let initial_value = memory.read(test_addr);
 
         // Execute NOP instruction - fetch opcode
-        let done = cpu.tick_cycle();
+        let done = cpu.tick_cycle(&mut memory);
         assert!(!done, "NOP should not be done after cycle 1");
 
         // During cycle 2, NOP should perform a dummy read from PC (0x0401)
         // We'll verify this by checking that tick_cycle executes correctly
-        let done = cpu.tick_cycle();
+        let done = cpu.tick_cycle(&mut memory);
         assert!(done, "NOP should be done after cycle 2");
 
         // Verify instruction behaved correctly
@@ -3175,23 +3239,23 @@ mod tests {
 
     #[test]
     fn test_clc_performs_dummy_read() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CLC instruction at address $0400
-        memory.borrow_mut().write(0x0400, CLC, false);
+        memory.write(0x0400, CLC, false);
         // Next byte that should be read during dummy read
-        memory.borrow_mut().write(0x0401, 0xFF, false);
+        memory.write(0x0401, 0xFF, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = FLAG_CARRY; // Set carry flag to test CLC clears it
 
         // Execute CLC instruction - fetch opcode
-        let done = cpu.tick_cycle();
+        let done = cpu.tick_cycle(&mut memory);
         assert!(!done, "CLC should not be done after cycle 1");
 
         // During cycle 2, CLC should perform a dummy read
-        let done = cpu.tick_cycle();
+        let done = cpu.tick_cycle(&mut memory);
         assert!(done, "CLC should be done after cycle 2");
 
         // Verify instruction behaved correctly
@@ -3201,24 +3265,24 @@ mod tests {
 
     #[test]
     fn test_tax_performs_dummy_read() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up TAX instruction at address $0400
-        memory.borrow_mut().write(0x0400, TAX, false);
+        memory.write(0x0400, TAX, false);
         // Next byte that should be read during dummy read
-        memory.borrow_mut().write(0x0401, 0xAA, false);
+        memory.write(0x0401, 0xAA, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x42;
         cpu.state.x = 0x00;
 
         // Execute TAX instruction - fetch opcode
-        let done = cpu.tick_cycle();
+        let done = cpu.tick_cycle(&mut memory);
         assert!(!done, "TAX should not be done after cycle 1");
 
         // During cycle 2, TAX should perform a dummy read
-        let done = cpu.tick_cycle();
+        let done = cpu.tick_cycle(&mut memory);
         assert!(done, "TAX should be done after cycle 2");
 
         // Verify instruction behaved correctly
@@ -3228,20 +3292,20 @@ mod tests {
 
     #[test]
     fn test_absolute_write_skips_final_read() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STA absolute instruction at address $0400
-        memory.borrow_mut().write(0x0400, STA_ABS, false);
-        memory.borrow_mut().write(0x0401, 0x00, false); // Low byte of address
-        memory.borrow_mut().write(0x0402, 0x20, false); // High byte of address ($2000)
+        memory.write(0x0400, STA_ABS, false);
+        memory.write(0x0401, 0x00, false); // Low byte of address
+        memory.write(0x0402, 0x20, false); // High byte of address ($2000)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x42;
 
         // Execute STA instruction completely
         loop {
-            let done = cpu.tick_cycle();
+            let done = cpu.tick_cycle(&mut memory);
             if done {
                 break;
             }
@@ -3249,7 +3313,7 @@ mod tests {
 
         // Verify the value was written
         assert_eq!(
-            memory.borrow().read(0x2000),
+            memory.read(0x2000),
             0x42,
             "Value should be written to memory"
         );
@@ -3261,24 +3325,24 @@ mod tests {
 
     #[test]
     fn test_absolutex_page_cross_dummy_read() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDA $12FF,X instruction where X will cause page cross
-        memory.borrow_mut().write(0x0400, LDA_ABSX, false);
-        memory.borrow_mut().write(0x0401, 0xFF, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte ($12FF)
+        memory.write(0x0400, LDA_ABSX, false);
+        memory.write(0x0401, 0xFF, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte ($12FF)
 
         // Place value at the final address after page cross ($12FF + $05 = $1304)
-        memory.borrow_mut().write(0x1304, 0x42, false);
+        memory.write(0x1304, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05; // This will cause page cross
 
         // Execute LDA instruction
         let mut cycles = 0;
         loop {
-            let done = cpu.tick_cycle();
+            let done = cpu.tick_cycle(&mut memory);
             cycles += 1;
             if done {
                 break;
@@ -3294,14 +3358,14 @@ mod tests {
 
     #[test]
     fn test_absolutex_write_always_takes_5_cycles() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STA $1200,X instruction (no page cross)
-        memory.borrow_mut().write(0x0400, STA_ABSX, false);
-        memory.borrow_mut().write(0x0401, 0x00, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte ($1200)
+        memory.write(0x0400, STA_ABSX, false);
+        memory.write(0x0401, 0x00, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte ($1200)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05; // No page cross: $1200 + $05 = $1205
         cpu.state.a = 0x42;
@@ -3309,7 +3373,7 @@ mod tests {
         // Execute STA instruction
         let mut cycles = 0;
         loop {
-            let done = cpu.tick_cycle();
+            let done = cpu.tick_cycle(&mut memory);
             cycles += 1;
             if done {
                 break;
@@ -3322,33 +3386,29 @@ mod tests {
             cycles, 5,
             "STA abs,X should always take 5 cycles (with dummy read)"
         );
-        assert_eq!(
-            memory.borrow().read(0x1205),
-            0x42,
-            "Value should be written"
-        );
+        assert_eq!(memory.read(0x1205), 0x42, "Value should be written");
         assert_eq!(cpu.state.pc, 0x0403, "PC should advance by 3");
     }
 
     #[test]
     fn test_rmw_performs_dummy_read() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up INC $2000 instruction (Read-Modify-Write)
-        memory.borrow_mut().write(0x0400, INC_ABS, false);
-        memory.borrow_mut().write(0x0401, 0x00, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x20, false); // High byte ($2000)
+        memory.write(0x0400, INC_ABS, false);
+        memory.write(0x0401, 0x00, false); // Low byte
+        memory.write(0x0402, 0x20, false); // High byte ($2000)
 
         // Initial value at $2000
-        memory.borrow_mut().write(0x2000, 0x41, false);
+        memory.write(0x2000, 0x41, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
 
         // Execute INC instruction
         let mut cycles = 0;
         loop {
-            let done = cpu.tick_cycle();
+            let done = cpu.tick_cycle(&mut memory);
             cycles += 1;
             if done {
                 break;
@@ -3358,19 +3418,14 @@ mod tests {
         // INC absolute should take 6 cycles total
         // Including the dummy read during modification
         assert_eq!(cycles, 6, "INC abs should take 6 cycles");
-        assert_eq!(
-            memory.borrow().read(0x2000),
-            0x42,
-            "Value should be incremented"
-        );
+        assert_eq!(memory.read(0x2000), 0x42, "Value should be incremented");
         assert_eq!(cpu.state.pc, 0x0403, "PC should advance by 3");
     }
 
     #[test]
     fn test_nmi_edge_detection_high_to_low() {
         // NMI should trigger on a high-to-low transition (falling edge)
-        let memory = create_test_memory();
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Initially NMI line is high (not asserted) - no NMI pending
         cpu.set_nmi_line(true);
@@ -3390,8 +3445,7 @@ mod tests {
     #[test]
     fn test_nmi_edge_detection_stays_low() {
         // NMI should NOT trigger again while line stays low
-        let memory = create_test_memory();
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Start with line high
         cpu.set_nmi_line(true);
@@ -3417,8 +3471,7 @@ mod tests {
     #[test]
     fn test_nmi_edge_detection_multiple_edges() {
         // Multiple high-to-low transitions should each trigger NMI
-        let memory = create_test_memory();
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Start high
         cpu.set_nmi_line(true);
@@ -3446,8 +3499,7 @@ mod tests {
     #[test]
     fn test_irq_level_detection_active_low() {
         // IRQ should be active when line is low (level-triggered)
-        let memory = create_test_memory();
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Clear I flag so IRQ can be serviced
         cpu.state.p &= !FLAG_INTERRUPT;
@@ -3477,8 +3529,7 @@ mod tests {
     #[test]
     fn test_irq_level_detection_masked_by_i_flag() {
         // IRQ should be masked when I flag is set
-        let memory = create_test_memory();
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Set I flag to mask IRQ
         cpu.state.p |= FLAG_INTERRUPT;
@@ -3501,8 +3552,7 @@ mod tests {
     #[test]
     fn test_nmi_not_masked_by_i_flag() {
         // NMI should trigger regardless of I flag (non-maskable)
-        let memory = create_test_memory();
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
 
         // Set I flag
         cpu.state.p |= FLAG_INTERRUPT;
@@ -3521,17 +3571,17 @@ mod tests {
     #[test]
     fn test_nmi_priority_over_irq() {
         // When both NMI and IRQ are pending, NMI should be serviced first
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up NMI vector to point to $8000
-        memory.borrow_mut().write(NMI_VECTOR, 0x00, false);
-        memory.borrow_mut().write(NMI_VECTOR + 1, 0x80, false);
+        memory.write(NMI_VECTOR, 0x00, false);
+        memory.write(NMI_VECTOR + 1, 0x80, false);
 
         // Set up IRQ vector to point to $9000
-        memory.borrow_mut().write(IRQ_VECTOR, 0x00, false);
-        memory.borrow_mut().write(IRQ_VECTOR + 1, 0x90, false);
+        memory.write(IRQ_VECTOR, 0x00, false);
+        memory.write(IRQ_VECTOR + 1, 0x90, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p &= !FLAG_INTERRUPT; // Clear I flag so IRQ can be serviced
 
@@ -3551,17 +3601,17 @@ mod tests {
     #[test]
     fn test_nmi_hijacks_irq_sequence() {
         // If NMI arrives during IRQ sequence, it should hijack and redirect to NMI vector
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up NMI vector to point to $8000
-        memory.borrow_mut().write(NMI_VECTOR, 0x00, false);
-        memory.borrow_mut().write(NMI_VECTOR + 1, 0x80, false);
+        memory.write(NMI_VECTOR, 0x00, false);
+        memory.write(NMI_VECTOR + 1, 0x80, false);
 
         // Set up IRQ vector to point to $9000
-        memory.borrow_mut().write(IRQ_VECTOR, 0x00, false);
-        memory.borrow_mut().write(IRQ_VECTOR + 1, 0x90, false);
+        memory.write(IRQ_VECTOR, 0x00, false);
+        memory.write(IRQ_VECTOR + 1, 0x90, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p &= !FLAG_INTERRUPT;
 
@@ -3585,8 +3635,7 @@ mod tests {
     fn test_no_nested_interrupts_during_sequence() {
         // Once in an interrupt sequence, another interrupt shouldn't start
         // until the current one completes (RTI)
-        let memory = create_test_memory();
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p &= !FLAG_INTERRUPT;
 
@@ -3623,12 +3672,12 @@ mod tests {
         // After ONE instruction executes from interrupt handler,
         // in_interrupt_sequence flag should be cleared
         // This test verifies the flag is cleared, allowing further interrupt polling
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up NOP at $0400 (will be first instruction in handler)
-        memory.borrow_mut().write(0x0400, NOP, false);
+        memory.write(0x0400, NOP, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
 
         // Mark that we're in an interrupt sequence (as if we just entered handler)
@@ -3640,7 +3689,7 @@ mod tests {
 
         // Execute one instruction (NOP)
         loop {
-            let done = cpu.tick_cycle();
+            let done = cpu.tick_cycle(&mut memory);
             if done {
                 break;
             }
@@ -3659,7 +3708,7 @@ mod tests {
         // Test complete NMI trigger with cycle-accurate behavior
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up vectors in cartridge ROM
         let mut prg_rom = vec![0; 0x8000];
@@ -3670,9 +3719,9 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x1234;
         cpu.state.sp = 0xFD;
         cpu.state.p = 0x24; // Some flags set
@@ -3683,7 +3732,7 @@ mod tests {
         cpu.set_nmi_line(false); // Falling edge
         assert!(cpu.is_nmi_pending(), "NMI should be pending");
 
-        let cycles = cpu.trigger_nmi();
+        let cycles = cpu.trigger_nmi(&mut memory);
 
         // Verify NMI took 7 cycles
         assert_eq!(cycles, 7, "NMI should take 7 cycles");
@@ -3704,9 +3753,9 @@ mod tests {
         assert_eq!(cpu.state.sp, 0xFA, "SP should be decremented by 3");
 
         // Check stack contents
-        let pch = memory.borrow().read(0x01FD);
-        let pcl = memory.borrow().read(0x01FC);
-        let status = memory.borrow().read(0x01FB);
+        let pch = memory.read(0x01FD);
+        let pcl = memory.read(0x01FC);
+        let status = memory.read(0x01FB);
 
         assert_eq!(pch, 0x12, "High byte of PC should be on stack");
         assert_eq!(pcl, 0x34, "Low byte of PC should be on stack");
@@ -3730,7 +3779,7 @@ mod tests {
         // Test complete IRQ trigger with cycle-accurate behavior
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up vectors in cartridge ROM
         let mut prg_rom = vec![0; 0x8000];
@@ -3741,9 +3790,9 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x1234;
         cpu.state.sp = 0xFD;
         cpu.state.p = 0x20; // I flag CLEAR (bit 2 = 0), unused flag set (bit 5 = 1)
@@ -3753,7 +3802,7 @@ mod tests {
         cpu.set_irq_line(false); // Active low
         assert!(cpu.should_poll_irq(), "IRQ should be active");
 
-        let cycles = cpu.trigger_irq();
+        let cycles = cpu.trigger_irq(&mut memory);
 
         // Verify IRQ took 7 cycles
         assert_eq!(cycles, 7, "IRQ should take 7 cycles");
@@ -3774,9 +3823,9 @@ mod tests {
         assert_eq!(cpu.state.sp, 0xFA, "SP should be decremented by 3");
 
         // Check stack contents
-        let pch = memory.borrow().read(0x01FD);
-        let pcl = memory.borrow().read(0x01FC);
-        let status = memory.borrow().read(0x01FB);
+        let pch = memory.read(0x01FD);
+        let pcl = memory.read(0x01FC);
+        let status = memory.read(0x01FB);
 
         assert_eq!(pch, 0x12, "High byte of PC should be on stack");
         assert_eq!(pcl, 0x34, "Low byte of PC should be on stack");
@@ -3805,7 +3854,7 @@ mod tests {
         // but still sets B flag (BRK initiated the sequence)
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up vectors in cartridge ROM
         let mut prg_rom = vec![0; 0x8000];
@@ -3822,9 +3871,9 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x8000; // Start at beginning of PRG ROM
         cpu.state.sp = 0xFD;
         cpu.state.p = 0x24;
@@ -3837,7 +3886,7 @@ mod tests {
         // Execute BRK instruction completely
         let mut cycles = 0;
         loop {
-            let done = cpu.tick_cycle();
+            let done = cpu.tick_cycle(&mut memory);
             cycles += 1;
             if done {
                 break;
@@ -3851,7 +3900,7 @@ mod tests {
         assert_eq!(cpu.state.pc, 0x8000, "Should jump to NMI vector");
 
         // But B flag should be SET in pushed status (BRK initiated)
-        let status = memory.borrow().read(0x01FB);
+        let status = memory.read(0x01FB);
         assert_eq!(
             status & FLAG_BREAK,
             FLAG_BREAK,
@@ -3868,7 +3917,7 @@ mod tests {
         // and clears B flag (IRQ hijacked it)
         use crate::cartridge::Cartridge;
 
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up vectors in cartridge ROM
         let mut prg_rom = vec![0; 0x8000];
@@ -3883,9 +3932,9 @@ mod tests {
 
         let cartridge =
             Cartridge::from_parts(prg_rom, vec![], crate::cartridge::MirroringMode::Horizontal);
-        memory.borrow_mut().map_cartridge(cartridge);
+        memory.map_cartridge(cartridge);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x8000; // Start at beginning of PRG ROM
         cpu.state.sp = 0xFD;
         cpu.state.p = 0x20; // I flag CLEAR (bit 2 = 0)
@@ -3897,7 +3946,7 @@ mod tests {
         // Execute BRK instruction completely
         let mut cycles = 0;
         loop {
-            let done = cpu.tick_cycle();
+            let done = cpu.tick_cycle(&mut memory);
             cycles += 1;
             if done {
                 break;
@@ -3911,7 +3960,7 @@ mod tests {
         assert_eq!(cpu.state.pc, 0x9000, "Should jump to IRQ vector");
 
         // B flag should be CLEAR in pushed status (IRQ hijacked)
-        let status = memory.borrow().read(0x01FB);
+        let status = memory.read(0x01FB);
         assert_eq!(
             status & FLAG_BREAK,
             0,
@@ -3921,29 +3970,29 @@ mod tests {
 
     #[test]
     fn test_opcode_01() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ORA ($20,X) instruction at address $0400
         // With X=0x04, reads from zero page address ($20+$04) = $24
         // At $24-$25 we store the pointer $1234
         // At $1234 we store the value to ORA with
-        memory.borrow_mut().write(0x0400, ORA_INDX, false); // ORA (zp,X) opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, ORA_INDX, false); // ORA (zp,X) opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
         // Set up pointer at zero page $24 (base $20 + X register $04)
-        memory.borrow_mut().write(0x0024, 0x34, false); // Low byte of target address
-        memory.borrow_mut().write(0x0025, 0x12, false); // High byte of target address
+        memory.write(0x0024, 0x34, false); // Low byte of target address
+        memory.write(0x0025, 0x12, false); // High byte of target address
 
         // Set up value to ORA at address $1234
-        memory.borrow_mut().write(0x1234, 0b1010_1010, false);
+        memory.write(0x1234, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x04;
         cpu.state.a = 0b1100_1100;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1100_1100 | 0b1010_1010 = 0b1110_1110
         assert_eq!(cpu.state.a, 0b1110_1110, "A should contain result of ORA");
@@ -3958,12 +4007,12 @@ mod tests {
 
     #[test]
     fn test_opcode_02() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up KIL instruction at address $0400
-        memory.borrow_mut().write(0x0400, KIL, false); // KIL opcode
+        memory.write(0x0400, KIL, false); // KIL opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.sp = 0xFD;
         cpu.state.a = 0x42;
@@ -3978,14 +4027,14 @@ mod tests {
         let max_ticks = 10;
 
         // Tick once to fetch and start the KIL instruction
-        cpu.tick_cycle();
+        cpu.tick_cycle(&mut memory);
         ticks += 1;
 
         // The CPU should now be halted and subsequent ticks should not advance
         let pc_after_fetch = cpu.state.pc;
 
         while ticks < max_ticks {
-            cpu.tick_cycle();
+            cpu.tick_cycle(&mut memory);
             ticks += 1;
         }
 
@@ -4008,32 +4057,32 @@ mod tests {
 
     #[test]
     fn test_opcode_03() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SLO ($20,X) instruction at address $0400
         // With X=0x04, reads from zero page address ($20+$04) = $24
         // At $24-$25 we store the pointer $1234
         // At $1234 we store the value to shift and OR with
-        memory.borrow_mut().write(0x0400, SLO_INDX, false); // SLO (zp,X) opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, SLO_INDX, false); // SLO (zp,X) opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
         // Set up pointer at zero page $24 (base $20 + X register $04)
-        memory.borrow_mut().write(0x0024, 0x34, false); // Low byte of target address
-        memory.borrow_mut().write(0x0025, 0x12, false); // High byte of target address
+        memory.write(0x0024, 0x34, false); // Low byte of target address
+        memory.write(0x0025, 0x12, false); // High byte of target address
 
         // Set up value at target address $1234
-        memory.borrow_mut().write(0x1234, 0b0101_0101, false); // Value to shift left
+        memory.write(0x1234, 0b0101_0101, false); // Value to shift left
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x04;
         cpu.state.a = 0b1100_0011; // Accumulator value to OR with
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1234 should be shifted left: 0b0101_0101 << 1 = 0b1010_1010
-        let memory_value = memory.borrow().read(0x1234);
+        let memory_value = memory.read(0x1234);
         assert_eq!(
             memory_value, 0b1010_1010,
             "Memory should contain shifted value"
@@ -4058,16 +4107,16 @@ mod tests {
 
     #[test]
     fn test_opcode_04() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DOP $20 instruction at address $0400
-        memory.borrow_mut().write(0x0400, DOP_ZP, false); // DOP Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, DOP_ZP, false); // DOP Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up a value at zero page $20 (will be read but ignored)
-        memory.borrow_mut().write(0x0020, 0x42, false);
+        memory.write(0x0020, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.sp = 0xFD;
         cpu.state.a = 0x11;
@@ -4075,7 +4124,7 @@ mod tests {
         cpu.state.y = 0x33;
         cpu.state.p = 0x44;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // DOP reads from memory but does nothing - all registers should be unchanged
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -4085,7 +4134,7 @@ mod tests {
         assert_eq!(cpu.state.p, 0x44, "P should not change");
 
         // Verify the memory value is still there (not modified)
-        let memory_value = memory.borrow().read(0x0020);
+        let memory_value = memory.read(0x0020);
         assert_eq!(memory_value, 0x42, "Memory should not be modified");
 
         // DOP with zero page should take 3 cycles
@@ -4095,21 +4144,21 @@ mod tests {
 
     #[test]
     fn test_opcode_05() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ORA $20 instruction at address $0400
-        memory.borrow_mut().write(0x0400, ORA_ZP, false); // ORA Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, ORA_ZP, false); // ORA Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at zero page $20
-        memory.borrow_mut().write(0x0020, 0b1010_1010, false);
+        memory.write(0x0020, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1100_0011 | 0b1010_1010 = 0b1110_1011
         assert_eq!(cpu.state.a, 0b1110_1011, "A should contain result of ORA");
@@ -4129,23 +4178,23 @@ mod tests {
 
     #[test]
     fn test_opcode_06() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ASL $20 instruction at address $0400
-        memory.borrow_mut().write(0x0400, ASL_ZP, false); // ASL Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, ASL_ZP, false); // ASL Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at zero page $20
-        memory.borrow_mut().write(0x0020, 0b0101_0101, false);
+        memory.write(0x0020, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $20 should be shifted left: 0b0101_0101 << 1 = 0b1010_1010
-        let memory_value = memory.borrow().read(0x0020);
+        let memory_value = memory.read(0x0020);
         assert_eq!(
             memory_value, 0b1010_1010,
             "Memory should contain shifted value"
@@ -4167,24 +4216,24 @@ mod tests {
 
     #[test]
     fn test_opcode_07() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SLO $20 instruction at address $0400
-        memory.borrow_mut().write(0x0400, SLO_ZP, false); // SLO Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, SLO_ZP, false); // SLO Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at zero page $20
-        memory.borrow_mut().write(0x0020, 0b0101_0101, false);
+        memory.write(0x0020, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011; // Accumulator value to OR with
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $20 should be shifted left: 0b0101_0101 << 1 = 0b1010_1010
-        let memory_value = memory.borrow().read(0x0020);
+        let memory_value = memory.read(0x0020);
         assert_eq!(
             memory_value, 0b1010_1010,
             "Memory should contain shifted value"
@@ -4209,23 +4258,23 @@ mod tests {
 
     #[test]
     fn test_opcode_08() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up PHP instruction at address $0400
-        memory.borrow_mut().write(0x0400, PHP, false); // PHP opcode
+        memory.write(0x0400, PHP, false); // PHP opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.sp = 0xFD;
         cpu.state.p = 0b1010_0101; // Set some flags: N=1, V=0, unused=1, B=0, D=0, I=1, Z=0, C=1
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Stack pointer should have decremented by 1
         assert_eq!(cpu.state.sp, 0xFC, "SP should decrement by 1");
 
         // Check the value pushed to stack (should have B and unused flags set)
-        let pushed_value = memory.borrow().read(0x01FD); // Read from original SP location
+        let pushed_value = memory.read(0x01FD); // Read from original SP location
         assert_eq!(
             pushed_value & FLAG_BREAK,
             FLAG_BREAK,
@@ -4262,18 +4311,18 @@ mod tests {
 
     #[test]
     fn test_opcode_09() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ORA #$AA instruction at address $0400
-        memory.borrow_mut().write(0x0400, ORA_IMM, false); // ORA Immediate opcode
-        memory.borrow_mut().write(0x0401, 0b1010_1010, false); // Immediate value
+        memory.write(0x0400, ORA_IMM, false); // ORA Immediate opcode
+        memory.write(0x0401, 0b1010_1010, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1100_0011 | 0b1010_1010 = 0b1110_1011
         assert_eq!(cpu.state.a, 0b1110_1011, "A should contain result of ORA");
@@ -4293,17 +4342,17 @@ mod tests {
 
     #[test]
     fn test_opcode_0a() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ASL A instruction at address $0400
-        memory.borrow_mut().write(0x0400, ASL_A, false); // ASL A opcode
+        memory.write(0x0400, ASL_A, false); // ASL A opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b0101_0101; // Value to shift left
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be shifted left: 0b0101_0101 << 1 = 0b1010_1010
         assert_eq!(
@@ -4326,18 +4375,18 @@ mod tests {
 
     #[test]
     fn test_opcode_0b() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up AAC #$AA instruction at address $0400 (illegal opcode)
-        memory.borrow_mut().write(0x0400, AAC_IMM, false); // AAC Immediate opcode
-        memory.borrow_mut().write(0x0401, 0b1010_1010, false); // Immediate value
+        memory.write(0x0400, AAC_IMM, false); // AAC Immediate opcode
+        memory.write(0x0401, 0b1010_1010, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1100_0011 & 0b1010_1010 = 0b1000_0010
         assert_eq!(cpu.state.a, 0b1000_0010, "A should contain result of AND");
@@ -4361,15 +4410,15 @@ mod tests {
 
     #[test]
     fn test_opcode_0c() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up TOP $1234 instruction at address $0400 (illegal opcode - triple NOP)
-        memory.borrow_mut().write(0x0400, TOP_ABS, false); // TOP Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte of address
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte of address
-        memory.borrow_mut().write(0x1234, 0x42, false); // Value at $1234 (will be read but ignored)
+        memory.write(0x0400, TOP_ABS, false); // TOP Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte of address
+        memory.write(0x0402, 0x12, false); // High byte of address
+        memory.write(0x1234, 0x42, false); // Value at $1234 (will be read but ignored)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.sp = 0xFD;
         cpu.state.a = 0x11;
@@ -4377,7 +4426,7 @@ mod tests {
         cpu.state.y = 0x33;
         cpu.state.p = 0x44;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // TOP reads from memory but does nothing - all registers should be unchanged
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -4392,20 +4441,20 @@ mod tests {
 
     #[test]
     fn test_opcode_0d() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ORA $1234 instruction at address $0400
-        memory.borrow_mut().write(0x0400, ORA_ABS, false); // ORA Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte of address
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte of address
-        memory.borrow_mut().write(0x1234, 0b1010_1010, false); // Value at $1234
+        memory.write(0x0400, ORA_ABS, false); // ORA Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte of address
+        memory.write(0x0402, 0x12, false); // High byte of address
+        memory.write(0x1234, 0b1010_1010, false); // Value at $1234
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1100_0011 | 0b1010_1010 = 0b1110_1011
         assert_eq!(cpu.state.a, 0b1110_1011, "A should contain result of ORA");
@@ -4425,22 +4474,22 @@ mod tests {
 
     #[test]
     fn test_opcode_0e() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ASL $1234 instruction at address $0400
-        memory.borrow_mut().write(0x0400, 0x0E, false); // ASL Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte of address
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte of address
-        memory.borrow_mut().write(0x1234, 0b0101_0101, false); // Value at $1234
+        memory.write(0x0400, 0x0E, false); // ASL Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte of address
+        memory.write(0x0402, 0x12, false); // High byte of address
+        memory.write(0x1234, 0b0101_0101, false); // Value at $1234
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1234 should be shifted left: 0b0101_0101 << 1 = 0b1010_1010
-        let result = memory.borrow().read(0x1234);
+        let result = memory.read(0x1234);
         assert_eq!(result, 0b1010_1010, "Memory should be shifted left");
 
         // Flags: N=1 (bit 7 set), Z=0 (non-zero), C=0 (bit 7 of original was 0)
@@ -4458,23 +4507,23 @@ mod tests {
 
     #[test]
     fn test_opcode_0f() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SLO $1234 instruction at address $0400 (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x0F, false); // SLO Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte of address
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte of address
-        memory.borrow_mut().write(0x1234, 0b0101_0101, false); // Value at $1234
+        memory.write(0x0400, 0x0F, false); // SLO Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte of address
+        memory.write(0x0402, 0x12, false); // High byte of address
+        memory.write(0x1234, 0b0101_0101, false); // Value at $1234
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1234 should be shifted left: 0b0101_0101 << 1 = 0b1010_1010
-        let mem_result = memory.borrow().read(0x1234);
+        let mem_result = memory.read(0x1234);
         assert_eq!(mem_result, 0b1010_1010, "Memory should be shifted left");
 
         // A should be ORed with shifted value: 0b1100_0011 | 0b1010_1010 = 0b1110_1011
@@ -4498,17 +4547,17 @@ mod tests {
 
     #[test]
     fn test_opcode_10() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up BPL $10 instruction at address $0400 (branch if positive)
-        memory.borrow_mut().write(0x0400, 0x10, false); // BPL opcode
-        memory.borrow_mut().write(0x0401, 0x10, false); // Relative offset (+16)
+        memory.write(0x0400, 0x10, false); // BPL opcode
+        memory.write(0x0401, 0x10, false); // Relative offset (+16)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0x00; // N flag clear (positive)
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // PC should be at $0412 (0x0402 + 0x10)
         assert_eq!(cpu.state.pc, 0x0412, "PC should branch to 0x0412");
@@ -4519,17 +4568,17 @@ mod tests {
 
     #[test]
     fn test_opcode_10_not_taken() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up BPL $10 instruction at address $0400
-        memory.borrow_mut().write(0x0400, 0x10, false); // BPL opcode
-        memory.borrow_mut().write(0x0401, 0x10, false); // Relative offset
+        memory.write(0x0400, 0x10, false); // BPL opcode
+        memory.write(0x0401, 0x10, false); // Relative offset
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = FLAG_NEGATIVE; // N flag set (negative)
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // PC should be at $0402 (not branched)
         assert_eq!(cpu.state.pc, 0x0402, "PC should not branch");
@@ -4540,26 +4589,26 @@ mod tests {
 
     #[test]
     fn test_opcode_11() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ORA ($20),Y instruction at address $0400
-        memory.borrow_mut().write(0x0400, 0x11, false); // ORA Indirect,Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, 0x11, false); // ORA Indirect,Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up pointer at $20-$21 to point to $1230
-        memory.borrow_mut().write(0x0020, 0x30, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x30, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
         // Set up value at $1234 (base $1230 + Y offset $04)
-        memory.borrow_mut().write(0x1234, 0b1010_1010, false);
+        memory.write(0x1234, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011;
         cpu.state.y = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1100_0011 | 0b1010_1010 = 0b1110_1011
         assert_eq!(cpu.state.a, 0b1110_1011, "A should contain result of ORA");
@@ -4578,15 +4627,15 @@ mod tests {
 
     #[test]
     fn test_opcode_12() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up KIL instruction at address $0400 (illegal opcode that halts CPU)
-        memory.borrow_mut().write(0x0400, 0x12, false); // KIL opcode
+        memory.write(0x0400, 0x12, false); // KIL opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // CPU should be halted
         assert!(cpu.is_halted(), "CPU should be halted");
@@ -4597,29 +4646,29 @@ mod tests {
 
     #[test]
     fn test_opcode_13() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SLO ($20),Y instruction at address $0400 (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x13, false); // SLO Indirect,Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, 0x13, false); // SLO Indirect,Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up pointer at $20-$21 to point to $1230
-        memory.borrow_mut().write(0x0020, 0x30, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x30, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
         // Set up value at $1234 (base $1230 + Y offset $04)
-        memory.borrow_mut().write(0x1234, 0b0101_0101, false);
+        memory.write(0x1234, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011;
         cpu.state.y = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1234 should be shifted left: 0b0101_0101 << 1 = 0b1010_1010
-        let mem_result = memory.borrow().read(0x1234);
+        let mem_result = memory.read(0x1234);
         assert_eq!(mem_result, 0b1010_1010, "Memory should be shifted left");
 
         // A should be ORed with shifted value: 0b1100_0011 | 0b1010_1010 = 0b1110_1011
@@ -4643,14 +4692,14 @@ mod tests {
 
     #[test]
     fn test_opcode_14() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DOP $20,X instruction at address $0400 (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x14, false); // DOP Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
-        memory.borrow_mut().write(0x0025, 0x42, false); // Value at $25 (will be read but ignored)
+        memory.write(0x0400, 0x14, false); // DOP Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0025, 0x42, false); // Value at $25 (will be read but ignored)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05;
         cpu.state.sp = 0xFD;
@@ -4658,7 +4707,7 @@ mod tests {
         cpu.state.y = 0x22;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // DOP reads from memory but does nothing - all registers should be unchanged
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -4673,20 +4722,20 @@ mod tests {
 
     #[test]
     fn test_opcode_15() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ORA $20,X instruction at address $0400
-        memory.borrow_mut().write(0x0400, 0x15, false); // ORA Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
-        memory.borrow_mut().write(0x0025, 0b1010_1010, false); // Value at $25 (base $20 + X offset $05)
+        memory.write(0x0400, 0x15, false); // ORA Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0025, 0b1010_1010, false); // Value at $25 (base $20 + X offset $05)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1100_0011 | 0b1010_1010 = 0b1110_1011
         assert_eq!(cpu.state.a, 0b1110_1011, "A should contain result of ORA");
@@ -4705,22 +4754,22 @@ mod tests {
 
     #[test]
     fn test_opcode_16() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ASL $20,X instruction at address $0400
-        memory.borrow_mut().write(0x0400, 0x16, false); // ASL Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
-        memory.borrow_mut().write(0x0025, 0b0101_0101, false); // Value at $25 (base $20 + X offset $05)
+        memory.write(0x0400, 0x16, false); // ASL Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0025, 0b0101_0101, false); // Value at $25 (base $20 + X offset $05)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $25 should be shifted left: 0b0101_0101 << 1 = 0b1010_1010
-        let result = memory.borrow().read(0x0025);
+        let result = memory.read(0x0025);
         assert_eq!(result, 0b1010_1010, "Memory should be shifted left");
 
         // Flags: N=1 (bit 7 set), Z=0 (non-zero), C=0 (bit 7 of original was 0)
@@ -4738,23 +4787,23 @@ mod tests {
 
     #[test]
     fn test_opcode_17() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SLO $20,X instruction at address $0400 (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x17, false); // SLO Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
-        memory.borrow_mut().write(0x0025, 0b0101_0101, false); // Value at $25
+        memory.write(0x0400, 0x17, false); // SLO Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0025, 0b0101_0101, false); // Value at $25
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $25 should be shifted left: 0b0101_0101 << 1 = 0b1010_1010
-        let mem_result = memory.borrow().read(0x0025);
+        let mem_result = memory.read(0x0025);
         assert_eq!(mem_result, 0b1010_1010, "Memory should be shifted left");
 
         // A should be ORed with shifted value: 0b1100_0011 | 0b1010_1010 = 0b1110_1011
@@ -4775,16 +4824,16 @@ mod tests {
 
     #[test]
     fn test_opcode_18() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CLC instruction at address $0400
-        memory.borrow_mut().write(0x0400, 0x18, false); // CLC opcode
+        memory.write(0x0400, 0x18, false); // CLC opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0xFF; // All flags set
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Carry flag should be cleared
         assert_eq!(cpu.state.p & FLAG_CARRY, 0, "Carry flag should be clear");
@@ -4797,21 +4846,21 @@ mod tests {
 
     #[test]
     fn test_opcode_19() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ORA $1234,Y instruction at address $0400
-        memory.borrow_mut().write(0x0400, 0x19, false); // ORA Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte of address
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte of address
-        memory.borrow_mut().write(0x1238, 0b1010_1010, false); // Value at $1238 (base $1234 + Y offset $04)
+        memory.write(0x0400, 0x19, false); // ORA Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte of address
+        memory.write(0x0402, 0x12, false); // High byte of address
+        memory.write(0x1238, 0b1010_1010, false); // Value at $1238 (base $1234 + Y offset $04)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011;
         cpu.state.y = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1100_0011 | 0b1010_1010 = 0b1110_1011
         assert_eq!(cpu.state.a, 0b1110_1011, "A should contain result of ORA");
@@ -4830,12 +4879,12 @@ mod tests {
 
     #[test]
     fn test_opcode_1a() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up NOP instruction at address $0400 (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x1A, false); // NOP implied opcode
+        memory.write(0x0400, 0x1A, false); // NOP implied opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.sp = 0xFD;
         cpu.state.a = 0x11;
@@ -4843,7 +4892,7 @@ mod tests {
         cpu.state.y = 0x33;
         cpu.state.p = 0x44;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // NOP does nothing - all registers should be unchanged
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -4858,24 +4907,24 @@ mod tests {
 
     #[test]
     fn test_opcode_1b() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SLO $1234,Y instruction at address $0400 (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x1B, false); // SLO Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte of address
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte of address
-        memory.borrow_mut().write(0x1238, 0b0101_0101, false); // Value at $1238
+        memory.write(0x0400, 0x1B, false); // SLO Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte of address
+        memory.write(0x0402, 0x12, false); // High byte of address
+        memory.write(0x1238, 0b0101_0101, false); // Value at $1238
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011;
         cpu.state.y = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1238 should be shifted left: 0b0101_0101 << 1 = 0b1010_1010
-        let mem_result = memory.borrow().read(0x1238);
+        let mem_result = memory.read(0x1238);
         assert_eq!(mem_result, 0b1010_1010, "Memory should be shifted left");
 
         // A should be ORed with shifted value: 0b1100_0011 | 0b1010_1010 = 0b1110_1011
@@ -4896,15 +4945,15 @@ mod tests {
 
     #[test]
     fn test_opcode_1c() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up TOP $1234,X instruction at address $0400 (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x1C, false); // TOP Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte of address
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte of address
-        memory.borrow_mut().write(0x1238, 0x42, false); // Value at $1238 (will be read but ignored)
+        memory.write(0x0400, 0x1C, false); // TOP Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte of address
+        memory.write(0x0402, 0x12, false); // High byte of address
+        memory.write(0x1238, 0x42, false); // Value at $1238 (will be read but ignored)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x04;
         cpu.state.sp = 0xFD;
@@ -4912,7 +4961,7 @@ mod tests {
         cpu.state.y = 0x22;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // TOP reads from memory but does nothing - all registers should be unchanged
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -4927,21 +4976,21 @@ mod tests {
 
     #[test]
     fn test_opcode_1d() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ORA $1234,X instruction at address $0400
-        memory.borrow_mut().write(0x0400, 0x1D, false); // ORA Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte of address
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte of address
-        memory.borrow_mut().write(0x1238, 0b1010_1010, false); // Value at $1238
+        memory.write(0x0400, 0x1D, false); // ORA Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte of address
+        memory.write(0x0402, 0x12, false); // High byte of address
+        memory.write(0x1238, 0b1010_1010, false); // Value at $1238
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011;
         cpu.state.x = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1100_0011 | 0b1010_1010 = 0b1110_1011
         assert_eq!(cpu.state.a, 0b1110_1011, "A should contain result of ORA");
@@ -4960,23 +5009,23 @@ mod tests {
 
     #[test]
     fn test_opcode_1e() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ASL $1234,X instruction at address $0400
-        memory.borrow_mut().write(0x0400, 0x1E, false); // ASL Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte of address
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte of address
-        memory.borrow_mut().write(0x1238, 0b0101_0101, false); // Value at $1238
+        memory.write(0x0400, 0x1E, false); // ASL Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte of address
+        memory.write(0x0402, 0x12, false); // High byte of address
+        memory.write(0x1238, 0b0101_0101, false); // Value at $1238
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1238 should be shifted left: 0b0101_0101 << 1 = 0b1010_1010
-        let result = memory.borrow().read(0x1238);
+        let result = memory.read(0x1238);
         assert_eq!(result, 0b1010_1010, "Memory should be shifted left");
 
         // Flags: N=1 (bit 7 set), Z=0 (non-zero), C=0 (bit 7 of original was 0)
@@ -4994,24 +5043,24 @@ mod tests {
 
     #[test]
     fn test_opcode_1f() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SLO $1234,X instruction at address $0400 (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x1F, false); // SLO Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte of address
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte of address
-        memory.borrow_mut().write(0x1238, 0b0101_0101, false); // Value at $1238
+        memory.write(0x0400, 0x1F, false); // SLO Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte of address
+        memory.write(0x0402, 0x12, false); // High byte of address
+        memory.write(0x1238, 0b0101_0101, false); // Value at $1238
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011;
         cpu.state.x = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1238 should be shifted left: 0b0101_0101 << 1 = 0b1010_1010
-        let mem_result = memory.borrow().read(0x1238);
+        let mem_result = memory.read(0x1238);
         assert_eq!(mem_result, 0b1010_1010, "Memory should be shifted left");
 
         // A should be ORed with shifted value: 0b1100_0011 | 0b1010_1010 = 0b1110_1011
@@ -5032,18 +5081,18 @@ mod tests {
 
     #[test]
     fn test_opcode_20() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // JSR is already implemented and tested - this is just for completeness
-        memory.borrow_mut().write(0x0400, JSR, false); // JSR opcode
-        memory.borrow_mut().write(0x0401, 0x00, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x10, false); // High byte
+        memory.write(0x0400, JSR, false); // JSR opcode
+        memory.write(0x0401, 0x00, false); // Low byte
+        memory.write(0x0402, 0x10, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.sp = 0xFD;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         assert_eq!(cpu.state.pc, 0x1000, "PC should jump to subroutine");
         assert_eq!(cycles, 6, "JSR should take 6 cycles");
@@ -5051,26 +5100,26 @@ mod tests {
 
     #[test]
     fn test_opcode_21() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up AND ($20,X) instruction
-        memory.borrow_mut().write(0x0400, AND_INDX, false); // AND (Indirect,X) opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base
+        memory.write(0x0400, AND_INDX, false); // AND (Indirect,X) opcode
+        memory.write(0x0401, 0x20, false); // Zero page base
 
         // Set up pointer at $24 (base $20 + X $04)
-        memory.borrow_mut().write(0x0024, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0025, 0x12, false); // High byte
+        memory.write(0x0024, 0x34, false); // Low byte
+        memory.write(0x0025, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b1010_1010, false);
+        memory.write(0x1234, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_1111;
         cpu.state.x = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_1111 & 0b1010_1010 = 0b1010_1010
         assert_eq!(cpu.state.a, 0b1010_1010, "A should contain result of AND");
@@ -5085,15 +5134,15 @@ mod tests {
 
     #[test]
     fn test_opcode_22() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up KIL instruction
-        memory.borrow_mut().write(0x0400, KIL3, false); // KIL opcode
+        memory.write(0x0400, KIL3, false); // KIL opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         assert!(cpu.is_halted(), "CPU should be halted");
         assert_eq!(cycles, 1, "KIL should take 1 cycle");
@@ -5101,29 +5150,29 @@ mod tests {
 
     #[test]
     fn test_opcode_23() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RLA ($20,X) instruction (illegal opcode - ROL + AND)
-        memory.borrow_mut().write(0x0400, RLA_INDX, false); // RLA (Indirect,X) opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base
+        memory.write(0x0400, RLA_INDX, false); // RLA (Indirect,X) opcode
+        memory.write(0x0401, 0x20, false); // Zero page base
 
         // Set up pointer at $24
-        memory.borrow_mut().write(0x0024, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0025, 0x12, false); // High byte
+        memory.write(0x0024, 0x34, false); // Low byte
+        memory.write(0x0025, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b0101_0101, false);
+        memory.write(0x1234, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_1111;
         cpu.state.x = 0x04;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated left: 0b0101_0101 ROL with C=1 = 0b1010_1011
-        let mem_result = memory.borrow().read(0x1234);
+        let mem_result = memory.read(0x1234);
         assert_eq!(mem_result, 0b1010_1011, "Memory should be rotated left");
 
         // A should be ANDed with result: 0b1111_1111 & 0b1010_1011 = 0b1010_1011
@@ -5143,21 +5192,21 @@ mod tests {
 
     #[test]
     fn test_opcode_24() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up BIT $20 instruction
-        memory.borrow_mut().write(0x0400, BIT_ZP, false); // BIT Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, BIT_ZP, false); // BIT Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $20
-        memory.borrow_mut().write(0x0020, 0b1100_0000, false);
+        memory.write(0x0020, 0b1100_0000, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_1111;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Flags: N=1 (bit 7 of memory), V=1 (bit 6 of memory), Z=0 (AND result non-zero)
         assert_eq!(
@@ -5179,21 +5228,21 @@ mod tests {
 
     #[test]
     fn test_opcode_25() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up AND $20 instruction
-        memory.borrow_mut().write(0x0400, AND_ZP, false); // AND Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, AND_ZP, false); // AND Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $20
-        memory.borrow_mut().write(0x0020, 0b1010_1010, false);
+        memory.write(0x0020, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 & 0b1010_1010 = 0b1010_0000
         assert_eq!(cpu.state.a, 0b1010_0000, "A should contain result of AND");
@@ -5209,23 +5258,23 @@ mod tests {
 
     #[test]
     fn test_opcode_26() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ROL $20 instruction
-        memory.borrow_mut().write(0x0400, ROL_ZP, false); // ROL Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, ROL_ZP, false); // ROL Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $20
-        memory.borrow_mut().write(0x0020, 0b0101_0101, false);
+        memory.write(0x0020, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated left: 0b0101_0101 ROL with C=1 = 0b1010_1011
-        let result = memory.borrow().read(0x0020);
+        let result = memory.read(0x0020);
         assert_eq!(result, 0b1010_1011, "Memory should be rotated left");
 
         // Flags: N=1, Z=0, C=0 (bit 7 of original was 0)
@@ -5242,24 +5291,24 @@ mod tests {
 
     #[test]
     fn test_opcode_27() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RLA $20 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, RLA_ZP, false); // RLA Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, RLA_ZP, false); // RLA Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $20
-        memory.borrow_mut().write(0x0020, 0b0101_0101, false);
+        memory.write(0x0020, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_1111;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated left: 0b0101_0101 ROL with C=1 = 0b1010_1011
-        let mem_result = memory.borrow().read(0x0020);
+        let mem_result = memory.read(0x0020);
         assert_eq!(mem_result, 0b1010_1011, "Memory should be rotated left");
 
         // A should be ANDed with result: 0b1111_1111 & 0b1010_1011 = 0b1010_1011
@@ -5279,20 +5328,20 @@ mod tests {
 
     #[test]
     fn test_opcode_28() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up PLP instruction
-        memory.borrow_mut().write(0x0400, PLP, false); // PLP opcode
+        memory.write(0x0400, PLP, false); // PLP opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.sp = 0xFC;
         cpu.state.p = 0x00;
 
         // Push a value onto the stack
-        memory.borrow_mut().write(0x01FD, 0b1110_0101, false);
+        memory.write(0x01FD, 0b1110_0101, false);
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Status should be pulled from stack (ignore bits 4 and 5)
         assert_eq!(
@@ -5307,18 +5356,18 @@ mod tests {
 
     #[test]
     fn test_opcode_29() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up AND #$AA instruction
-        memory.borrow_mut().write(0x0400, AND_IMM, false); // AND Immediate opcode
-        memory.borrow_mut().write(0x0401, 0b1010_1010, false); // Immediate value
+        memory.write(0x0400, AND_IMM, false); // AND Immediate opcode
+        memory.write(0x0401, 0b1010_1010, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 & 0b1010_1010 = 0b1010_0000
         assert_eq!(cpu.state.a, 0b1010_0000, "A should contain result of AND");
@@ -5334,17 +5383,17 @@ mod tests {
 
     #[test]
     fn test_opcode_2a() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ROL A instruction
-        memory.borrow_mut().write(0x0400, 0x2A, false); // ROL A opcode
+        memory.write(0x0400, 0x2A, false); // ROL A opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b0101_0101;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be rotated left: 0b0101_0101 ROL with C=1 = 0b1010_1011
         assert_eq!(cpu.state.a, 0b1010_1011, "A should be rotated left");
@@ -5363,18 +5412,18 @@ mod tests {
 
     #[test]
     fn test_opcode_2b() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up AAC #$AA instruction (illegal opcode, same as 0x0B)
-        memory.borrow_mut().write(0x0400, AAC_IMM2, false); // AAC Immediate opcode
-        memory.borrow_mut().write(0x0401, 0b1010_1010, false); // Immediate value
+        memory.write(0x0400, AAC_IMM2, false); // AAC Immediate opcode
+        memory.write(0x0401, 0b1010_1010, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1100_0011;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1100_0011 & 0b1010_1010 = 0b1000_0010
         assert_eq!(cpu.state.a, 0b1000_0010, "A should contain result of AND");
@@ -5393,22 +5442,22 @@ mod tests {
 
     #[test]
     fn test_opcode_2c() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up BIT $1234 instruction
-        memory.borrow_mut().write(0x0400, BIT_ABS, false); // BIT Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, BIT_ABS, false); // BIT Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b1100_0000, false);
+        memory.write(0x1234, 0b1100_0000, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_1111;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Flags: N=1, V=1, Z=0
         assert_eq!(
@@ -5430,22 +5479,22 @@ mod tests {
 
     #[test]
     fn test_opcode_2d() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up AND $1234 instruction
-        memory.borrow_mut().write(0x0400, AND_ABS, false); // AND Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, AND_ABS, false); // AND Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b1010_1010, false);
+        memory.write(0x1234, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 & 0b1010_1010 = 0b1010_0000
         assert_eq!(cpu.state.a, 0b1010_0000, "A should contain result of AND");
@@ -5461,24 +5510,24 @@ mod tests {
 
     #[test]
     fn test_opcode_2e() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ROL $1234 instruction
-        memory.borrow_mut().write(0x0400, ROL_ABS, false); // ROL Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, ROL_ABS, false); // ROL Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b0101_0101, false);
+        memory.write(0x1234, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated left: 0b0101_0101 ROL with C=1 = 0b1010_1011
-        let result = memory.borrow().read(0x1234);
+        let result = memory.read(0x1234);
         assert_eq!(result, 0b1010_1011, "Memory should be rotated left");
 
         // Flags: N=1, Z=0, C=0
@@ -5495,25 +5544,25 @@ mod tests {
 
     #[test]
     fn test_opcode_2f() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RLA $1234 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, RLA_ABS, false); // RLA Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, RLA_ABS, false); // RLA Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b0101_0101, false);
+        memory.write(0x1234, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_1111;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated left: 0b0101_0101 ROL with C=1 = 0b1010_1011
-        let mem_result = memory.borrow().read(0x1234);
+        let mem_result = memory.read(0x1234);
         assert_eq!(mem_result, 0b1010_1011, "Memory should be rotated left");
 
         // A should be ANDed with result: 0b1111_1111 & 0b1010_1011 = 0b1010_1011
@@ -5533,17 +5582,17 @@ mod tests {
 
     #[test]
     fn test_opcode_30() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up BMI $10 instruction (branch if minus/negative)
-        memory.borrow_mut().write(0x0400, BMI, false); // BMI opcode
-        memory.borrow_mut().write(0x0401, 0x10, false); // Relative offset (+16)
+        memory.write(0x0400, BMI, false); // BMI opcode
+        memory.write(0x0401, 0x10, false); // Relative offset (+16)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = FLAG_NEGATIVE; // N flag set (negative)
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // PC should be at $0412 (0x0402 + 0x10)
         assert_eq!(cpu.state.pc, 0x0412, "PC should branch to 0x0412");
@@ -5552,26 +5601,26 @@ mod tests {
 
     #[test]
     fn test_opcode_31() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up AND ($20),Y instruction
-        memory.borrow_mut().write(0x0400, AND_INDY, false); // AND (Indirect),Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, AND_INDY, false); // AND (Indirect),Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up pointer at $20-$21 to point to $1230
-        memory.borrow_mut().write(0x0020, 0x30, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x30, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
         // Set up value at $1234 (base $1230 + Y offset $04)
-        memory.borrow_mut().write(0x1234, 0b1010_1010, false);
+        memory.write(0x1234, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.y = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 & 0b1010_1010 = 0b1010_0000
         assert_eq!(cpu.state.a, 0b1010_0000, "A should contain result of AND");
@@ -5586,15 +5635,15 @@ mod tests {
 
     #[test]
     fn test_opcode_32() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up KIL instruction
-        memory.borrow_mut().write(0x0400, KIL4, false); // KIL opcode
+        memory.write(0x0400, KIL4, false); // KIL opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         assert!(cpu.is_halted(), "CPU should be halted");
         assert_eq!(cycles, 1, "KIL should take 1 cycle");
@@ -5602,29 +5651,29 @@ mod tests {
 
     #[test]
     fn test_opcode_33() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RLA ($20),Y instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, RLA_INDY, false); // RLA (Indirect),Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, RLA_INDY, false); // RLA (Indirect),Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up pointer at $20-$21
-        memory.borrow_mut().write(0x0020, 0x30, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x30, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b0101_0101, false);
+        memory.write(0x1234, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_1111;
         cpu.state.y = 0x04;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated left
-        let mem_result = memory.borrow().read(0x1234);
+        let mem_result = memory.read(0x1234);
         assert_eq!(mem_result, 0b1010_1011, "Memory should be rotated left");
         // A should be ANDed with result
         assert_eq!(cpu.state.a, 0b1010_1011, "A should contain AND result");
@@ -5638,19 +5687,19 @@ mod tests {
 
     #[test]
     fn test_opcode_34() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DOP $20,X instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, DOP_ZPX2, false); // DOP Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, DOP_ZPX2, false); // DOP Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05;
         cpu.state.a = 0x11;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // DOP does nothing - registers should be unchanged
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -5660,22 +5709,22 @@ mod tests {
 
     #[test]
     fn test_opcode_35() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up AND $20,X instruction
-        memory.borrow_mut().write(0x0400, AND_ZPX, false); // AND Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base
+        memory.write(0x0400, AND_ZPX, false); // AND Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base
 
         // Set up value at $25 (base $20 + X $05)
-        memory.borrow_mut().write(0x0025, 0b1010_1010, false);
+        memory.write(0x0025, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 & 0b1010_1010 = 0b1010_0000
         assert_eq!(cpu.state.a, 0b1010_0000, "A should contain result of AND");
@@ -5689,24 +5738,24 @@ mod tests {
 
     #[test]
     fn test_opcode_36() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ROL $20,X instruction
-        memory.borrow_mut().write(0x0400, ROL_ZPX, false); // ROL Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base
+        memory.write(0x0400, ROL_ZPX, false); // ROL Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base
 
         // Set up value at $25
-        memory.borrow_mut().write(0x0025, 0b0101_0101, false);
+        memory.write(0x0025, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated left
-        let result = memory.borrow().read(0x0025);
+        let result = memory.read(0x0025);
         assert_eq!(result, 0b1010_1011, "Memory should be rotated left");
         assert_eq!(
             cpu.state.p & FLAG_NEGATIVE,
@@ -5718,25 +5767,25 @@ mod tests {
 
     #[test]
     fn test_opcode_37() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RLA $20,X instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, RLA_ZPX, false); // RLA Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base
+        memory.write(0x0400, RLA_ZPX, false); // RLA Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base
 
         // Set up value at $25
-        memory.borrow_mut().write(0x0025, 0b0101_0101, false);
+        memory.write(0x0025, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_1111;
         cpu.state.x = 0x05;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated left
-        let mem_result = memory.borrow().read(0x0025);
+        let mem_result = memory.read(0x0025);
         assert_eq!(mem_result, 0b1010_1011, "Memory should be rotated left");
         // A should be ANDed with result
         assert_eq!(cpu.state.a, 0b1010_1011, "A should contain AND result");
@@ -5745,16 +5794,16 @@ mod tests {
 
     #[test]
     fn test_opcode_38() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SEC instruction
-        memory.borrow_mut().write(0x0400, SEC, false); // SEC opcode
+        memory.write(0x0400, SEC, false); // SEC opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0x00; // All flags clear
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Carry flag should be set
         assert_eq!(cpu.state.p & FLAG_CARRY, 0x01, "Carry flag should be set");
@@ -5763,23 +5812,23 @@ mod tests {
 
     #[test]
     fn test_opcode_39() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up AND $1234,Y instruction
-        memory.borrow_mut().write(0x0400, AND_ABSY, false); // AND Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, AND_ABSY, false); // AND Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1238 (base $1234 + Y $04)
-        memory.borrow_mut().write(0x1238, 0b1010_1010, false);
+        memory.write(0x1238, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.y = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 & 0b1010_1010 = 0b1010_0000
         assert_eq!(cpu.state.a, 0b1010_0000, "A should contain result of AND");
@@ -5793,17 +5842,17 @@ mod tests {
 
     #[test]
     fn test_opcode_3a() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up NOP instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, NOP_IMP2, false); // NOP implied opcode
+        memory.write(0x0400, NOP_IMP2, false); // NOP implied opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x11;
         cpu.state.p = 0x44;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // NOP does nothing
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -5813,26 +5862,26 @@ mod tests {
 
     #[test]
     fn test_opcode_3b() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RLA $1234,Y instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, RLA_ABSY, false); // RLA Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, RLA_ABSY, false); // RLA Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1238
-        memory.borrow_mut().write(0x1238, 0b0101_0101, false);
+        memory.write(0x1238, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_1111;
         cpu.state.y = 0x04;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated left
-        let mem_result = memory.borrow().read(0x1238);
+        let mem_result = memory.read(0x1238);
         assert_eq!(mem_result, 0b1010_1011, "Memory should be rotated left");
         // A should be ANDed with result
         assert_eq!(cpu.state.a, 0b1010_1011, "A should contain AND result");
@@ -5841,20 +5890,20 @@ mod tests {
 
     #[test]
     fn test_opcode_3c() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up TOP $1234,X instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, TOP_ABSX2, false); // TOP Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, TOP_ABSX2, false); // TOP Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x04;
         cpu.state.a = 0x11;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // TOP does nothing
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -5864,23 +5913,23 @@ mod tests {
 
     #[test]
     fn test_opcode_3d() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up AND $1234,X instruction
-        memory.borrow_mut().write(0x0400, AND_ABSX, false); // AND Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, AND_ABSX, false); // AND Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1238
-        memory.borrow_mut().write(0x1238, 0b1010_1010, false);
+        memory.write(0x1238, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.x = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 & 0b1010_1010 = 0b1010_0000
         assert_eq!(cpu.state.a, 0b1010_0000, "A should contain result of AND");
@@ -5894,25 +5943,25 @@ mod tests {
 
     #[test]
     fn test_opcode_3e() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ROL $1234,X instruction
-        memory.borrow_mut().write(0x0400, ROL_ABSX, false); // ROL Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, ROL_ABSX, false); // ROL Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1238
-        memory.borrow_mut().write(0x1238, 0b0101_0101, false);
+        memory.write(0x1238, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x04;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated left
-        let result = memory.borrow().read(0x1238);
+        let result = memory.read(0x1238);
         assert_eq!(result, 0b1010_1011, "Memory should be rotated left");
         assert_eq!(
             cpu.state.p & FLAG_NEGATIVE,
@@ -5924,26 +5973,26 @@ mod tests {
 
     #[test]
     fn test_opcode_3f() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RLA $1234,X instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, RLA_ABSX, false); // RLA Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, RLA_ABSX, false); // RLA Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1238
-        memory.borrow_mut().write(0x1238, 0b0101_0101, false);
+        memory.write(0x1238, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_1111;
         cpu.state.x = 0x04;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated left
-        let mem_result = memory.borrow().read(0x1238);
+        let mem_result = memory.read(0x1238);
         assert_eq!(mem_result, 0b1010_1011, "Memory should be rotated left");
         // A should be ANDed with result
         assert_eq!(cpu.state.a, 0b1010_1011, "A should contain AND result");
@@ -5952,22 +6001,22 @@ mod tests {
 
     #[test]
     fn test_opcode_40() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RTI instruction
-        memory.borrow_mut().write(0x0400, 0x40, false); // RTI opcode
+        memory.write(0x0400, 0x40, false); // RTI opcode
 
         // Push status and return address to stack
-        memory.borrow_mut().write(0x01FD, 0x12, false); // PCH
-        memory.borrow_mut().write(0x01FC, 0x34, false); // PCL
-        memory.borrow_mut().write(0x01FB, 0b1100_0011, false); // Status
+        memory.write(0x01FD, 0x12, false); // PCH
+        memory.write(0x01FC, 0x34, false); // PCL
+        memory.write(0x01FB, 0b1100_0011, false); // Status
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.sp = 0xFA;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         assert_eq!(cpu.state.pc, 0x1234, "PC should be restored");
         assert_eq!(
@@ -5980,26 +6029,26 @@ mod tests {
 
     #[test]
     fn test_opcode_41() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up EOR ($20,X) instruction
-        memory.borrow_mut().write(0x0400, 0x41, false); // EOR (Indirect,X) opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base
+        memory.write(0x0400, 0x41, false); // EOR (Indirect,X) opcode
+        memory.write(0x0401, 0x20, false); // Zero page base
 
         // Set up pointer at $24 (base $20 + X $04)
-        memory.borrow_mut().write(0x0024, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0025, 0x12, false); // High byte
+        memory.write(0x0024, 0x34, false); // Low byte
+        memory.write(0x0025, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b1010_1010, false);
+        memory.write(0x1234, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.x = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 ^ 0b1010_1010 = 0b0101_1010
         assert_eq!(cpu.state.a, 0b0101_1010, "A should contain result of EOR");
@@ -6010,15 +6059,15 @@ mod tests {
 
     #[test]
     fn test_opcode_42() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up KIL instruction
-        memory.borrow_mut().write(0x0400, 0x42, false); // KIL opcode
+        memory.write(0x0400, 0x42, false); // KIL opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         assert!(cpu.is_halted(), "CPU should be halted");
         assert_eq!(cycles, 1, "KIL should take 1 cycle");
@@ -6026,29 +6075,29 @@ mod tests {
 
     #[test]
     fn test_opcode_43() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SRE ($20,X) instruction (illegal opcode - LSR + EOR)
-        memory.borrow_mut().write(0x0400, 0x43, false); // SRE (Indirect,X) opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base
+        memory.write(0x0400, 0x43, false); // SRE (Indirect,X) opcode
+        memory.write(0x0401, 0x20, false); // Zero page base
 
         // Set up pointer at $24
-        memory.borrow_mut().write(0x0024, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0025, 0x12, false); // High byte
+        memory.write(0x0024, 0x34, false); // Low byte
+        memory.write(0x0025, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b1010_1010, false);
+        memory.write(0x1234, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.x = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be shifted right: 0b1010_1010 >> 1 = 0b0101_0101
-        let mem_result = memory.borrow().read(0x1234);
+        let mem_result = memory.read(0x1234);
         assert_eq!(mem_result, 0b0101_0101, "Memory should be shifted right");
         // A should be EORed with result: 0b1111_0000 ^ 0b0101_0101 = 0b1010_0101
         assert_eq!(cpu.state.a, 0b1010_0101, "A should contain EOR result");
@@ -6058,18 +6107,18 @@ mod tests {
 
     #[test]
     fn test_opcode_44() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DOP $20 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x44, false); // DOP Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, 0x44, false); // DOP Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x11;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // DOP does nothing
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -6079,21 +6128,21 @@ mod tests {
 
     #[test]
     fn test_opcode_45() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up EOR $20 instruction
-        memory.borrow_mut().write(0x0400, 0x45, false); // EOR Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, 0x45, false); // EOR Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $20
-        memory.borrow_mut().write(0x0020, 0b1010_1010, false);
+        memory.write(0x0020, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 ^ 0b1010_1010 = 0b0101_1010
         assert_eq!(cpu.state.a, 0b0101_1010, "A should contain result of EOR");
@@ -6103,23 +6152,23 @@ mod tests {
 
     #[test]
     fn test_opcode_46() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LSR $20 instruction
-        memory.borrow_mut().write(0x0400, 0x46, false); // LSR Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, 0x46, false); // LSR Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $20
-        memory.borrow_mut().write(0x0020, 0b1010_1011, false);
+        memory.write(0x0020, 0b1010_1011, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be shifted right: 0b1010_1011 >> 1 = 0b0101_0101
-        let result = memory.borrow().read(0x0020);
+        let result = memory.read(0x0020);
         assert_eq!(result, 0b0101_0101, "Memory should be shifted right");
         assert_eq!(cpu.state.p & FLAG_NEGATIVE, 0, "N flag should be clear");
         assert_eq!(cpu.state.p & FLAG_ZERO, 0, "Z flag should be clear");
@@ -6133,24 +6182,24 @@ mod tests {
 
     #[test]
     fn test_opcode_47() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SRE $20 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x47, false); // SRE Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, 0x47, false); // SRE Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $20
-        memory.borrow_mut().write(0x0020, 0b1010_1010, false);
+        memory.write(0x0020, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be shifted right
-        let mem_result = memory.borrow().read(0x0020);
+        let mem_result = memory.read(0x0020);
         assert_eq!(mem_result, 0b0101_0101, "Memory should be shifted right");
         // A should be EORed with result
         assert_eq!(cpu.state.a, 0b1010_0101, "A should contain EOR result");
@@ -6159,20 +6208,20 @@ mod tests {
 
     #[test]
     fn test_opcode_48() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up PHA instruction
-        memory.borrow_mut().write(0x0400, 0x48, false); // PHA opcode
+        memory.write(0x0400, 0x48, false); // PHA opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.sp = 0xFD;
         cpu.state.a = 0x42;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Check stack
-        let stack_value = memory.borrow().read(0x01FD);
+        let stack_value = memory.read(0x01FD);
         assert_eq!(stack_value, 0x42, "A should be pushed to stack");
         assert_eq!(cpu.state.sp, 0xFC, "SP should be decremented");
         assert_eq!(cycles, 3, "PHA should take 3 cycles");
@@ -6180,18 +6229,18 @@ mod tests {
 
     #[test]
     fn test_opcode_49() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up EOR #$AA instruction
-        memory.borrow_mut().write(0x0400, 0x49, false); // EOR Immediate opcode
-        memory.borrow_mut().write(0x0401, 0b1010_1010, false); // Immediate value
+        memory.write(0x0400, 0x49, false); // EOR Immediate opcode
+        memory.write(0x0401, 0b1010_1010, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 ^ 0b1010_1010 = 0b0101_1010
         assert_eq!(cpu.state.a, 0b0101_1010, "A should contain result of EOR");
@@ -6200,17 +6249,17 @@ mod tests {
 
     #[test]
     fn test_opcode_4a() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LSR A instruction
-        memory.borrow_mut().write(0x0400, 0x4A, false); // LSR Accumulator opcode
+        memory.write(0x0400, 0x4A, false); // LSR Accumulator opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1010_1011;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be shifted right: 0b1010_1011 >> 1 = 0b0101_0101
         assert_eq!(cpu.state.a, 0b0101_0101, "A should be shifted right");
@@ -6226,18 +6275,18 @@ mod tests {
 
     #[test]
     fn test_opcode_4b() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ASR #$AA instruction (illegal opcode - AND + LSR)
-        memory.borrow_mut().write(0x0400, 0x4B, false); // ASR Immediate opcode
-        memory.borrow_mut().write(0x0401, 0b1010_1010, false); // Immediate value
+        memory.write(0x0400, 0x4B, false); // ASR Immediate opcode
+        memory.write(0x0401, 0b1010_1010, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0011;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be ANDed then shifted: (0b1111_0011 & 0b1010_1010) >> 1 = 0b1010_0010 >> 1 = 0b0101_0001
         assert_eq!(cpu.state.a, 0b0101_0001, "A should contain AND+LSR result");
@@ -6247,14 +6296,14 @@ mod tests {
 
     #[test]
     fn test_opcode_4c() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up JMP $5678 instruction at address $0800
-        memory.borrow_mut().write(0x0800, JMP_ABS, false); // JMP Absolute opcode (0x4C)
-        memory.borrow_mut().write(0x0801, 0x78, false); // Low byte of target
-        memory.borrow_mut().write(0x0802, 0x56, false); // High byte of target
+        memory.write(0x0800, JMP_ABS, false); // JMP Absolute opcode (0x4C)
+        memory.write(0x0801, 0x78, false); // Low byte of target
+        memory.write(0x0802, 0x56, false); // High byte of target
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0800;
         cpu.state.sp = 0xFD;
         cpu.state.a = 0xAA;
@@ -6262,7 +6311,7 @@ mod tests {
         cpu.state.y = 0xCC;
         cpu.state.p = 0xDD;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Verify the CPU state after JMP execution
         assert_eq!(cpu.state.pc, 0x5678, "PC should jump to target address");
@@ -6279,22 +6328,22 @@ mod tests {
 
     #[test]
     fn test_opcode_4d() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up EOR $1234 instruction
-        memory.borrow_mut().write(0x0400, 0x4D, false); // EOR Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, 0x4D, false); // EOR Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b1010_1010, false);
+        memory.write(0x1234, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 ^ 0b1010_1010 = 0b0101_1010
         assert_eq!(cpu.state.a, 0b0101_1010, "A should contain result of EOR");
@@ -6303,24 +6352,24 @@ mod tests {
 
     #[test]
     fn test_opcode_4e() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LSR $1234 instruction
-        memory.borrow_mut().write(0x0400, 0x4E, false); // LSR Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, 0x4E, false); // LSR Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b1010_1011, false);
+        memory.write(0x1234, 0b1010_1011, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be shifted right
-        let result = memory.borrow().read(0x1234);
+        let result = memory.read(0x1234);
         assert_eq!(result, 0b0101_0101, "Memory should be shifted right");
         assert_eq!(cpu.state.p & FLAG_CARRY, 1, "C flag should be set");
         assert_eq!(cycles, 6, "LSR absolute should take 6 cycles");
@@ -6328,25 +6377,25 @@ mod tests {
 
     #[test]
     fn test_opcode_4f() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SRE $1234 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x4F, false); // SRE Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, 0x4F, false); // SRE Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b1010_1010, false);
+        memory.write(0x1234, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be shifted right
-        let mem_result = memory.borrow().read(0x1234);
+        let mem_result = memory.read(0x1234);
         assert_eq!(mem_result, 0b0101_0101, "Memory should be shifted right");
         // A should be EORed with result
         assert_eq!(cpu.state.a, 0b1010_0101, "A should contain EOR result");
@@ -6355,17 +6404,17 @@ mod tests {
 
     #[test]
     fn test_opcode_50() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up BVC instruction (Branch if Overflow Clear)
-        memory.borrow_mut().write(0x0400, 0x50, false); // BVC opcode
-        memory.borrow_mut().write(0x0401, 0x10, false); // Relative offset (+16)
+        memory.write(0x0400, 0x50, false); // BVC opcode
+        memory.write(0x0401, 0x10, false); // Relative offset (+16)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0; // V flag clear
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Branch should be taken
         assert_eq!(cpu.state.pc, 0x0412, "PC should branch to 0x0412");
@@ -6377,26 +6426,26 @@ mod tests {
 
     #[test]
     fn test_opcode_51() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up EOR ($20),Y instruction
-        memory.borrow_mut().write(0x0400, 0x51, false); // EOR (Indirect),Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, 0x51, false); // EOR (Indirect),Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up pointer at $20
-        memory.borrow_mut().write(0x0020, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x34, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
         // Set up value at $1234 + Y
-        memory.borrow_mut().write(0x1238, 0b1010_1010, false);
+        memory.write(0x1238, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.y = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 ^ 0b1010_1010 = 0b0101_1010
         assert_eq!(cpu.state.a, 0b0101_1010, "A should contain result of EOR");
@@ -6408,15 +6457,15 @@ mod tests {
 
     #[test]
     fn test_opcode_52() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up KIL instruction
-        memory.borrow_mut().write(0x0400, 0x52, false); // KIL opcode
+        memory.write(0x0400, 0x52, false); // KIL opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         assert!(cpu.is_halted(), "CPU should be halted");
         assert_eq!(cycles, 1, "KIL should take 1 cycle");
@@ -6424,29 +6473,29 @@ mod tests {
 
     #[test]
     fn test_opcode_53() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SRE ($20),Y instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x53, false); // SRE (Indirect),Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, 0x53, false); // SRE (Indirect),Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up pointer at $20
-        memory.borrow_mut().write(0x0020, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x34, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
         // Set up value at $1234 + Y
-        memory.borrow_mut().write(0x1238, 0b1010_1010, false);
+        memory.write(0x1238, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.y = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be shifted right
-        let mem_result = memory.borrow().read(0x1238);
+        let mem_result = memory.read(0x1238);
         assert_eq!(mem_result, 0b0101_0101, "Memory should be shifted right");
         // A should be EORed with result
         assert_eq!(cpu.state.a, 0b1010_0101, "A should contain EOR result");
@@ -6455,19 +6504,19 @@ mod tests {
 
     #[test]
     fn test_opcode_54() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DOP $20,X instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x54, false); // DOP Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base
+        memory.write(0x0400, 0x54, false); // DOP Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05;
         cpu.state.a = 0x11;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // DOP does nothing
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -6477,22 +6526,22 @@ mod tests {
 
     #[test]
     fn test_opcode_55() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up EOR $20,X instruction
-        memory.borrow_mut().write(0x0400, 0x55, false); // EOR Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base
+        memory.write(0x0400, 0x55, false); // EOR Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base
 
         // Set up value at $25
-        memory.borrow_mut().write(0x0025, 0b1010_1010, false);
+        memory.write(0x0025, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 ^ 0b1010_1010 = 0b0101_1010
         assert_eq!(cpu.state.a, 0b0101_1010, "A should contain result of EOR");
@@ -6501,24 +6550,24 @@ mod tests {
 
     #[test]
     fn test_opcode_56() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LSR $20,X instruction
-        memory.borrow_mut().write(0x0400, 0x56, false); // LSR Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base
+        memory.write(0x0400, 0x56, false); // LSR Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base
 
         // Set up value at $25
-        memory.borrow_mut().write(0x0025, 0b1010_1011, false);
+        memory.write(0x0025, 0b1010_1011, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be shifted right
-        let result = memory.borrow().read(0x0025);
+        let result = memory.read(0x0025);
         assert_eq!(result, 0b0101_0101, "Memory should be shifted right");
         assert_eq!(
             cpu.state.p & FLAG_CARRY,
@@ -6530,25 +6579,25 @@ mod tests {
 
     #[test]
     fn test_opcode_57() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SRE $20,X instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x57, false); // SRE Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base
+        memory.write(0x0400, 0x57, false); // SRE Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base
 
         // Set up value at $25
-        memory.borrow_mut().write(0x0025, 0b1010_1010, false);
+        memory.write(0x0025, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be shifted right
-        let mem_result = memory.borrow().read(0x0025);
+        let mem_result = memory.read(0x0025);
         assert_eq!(mem_result, 0b0101_0101, "Memory should be shifted right");
         // A should be EORed with result
         assert_eq!(cpu.state.a, 0b1010_0101, "A should contain EOR result");
@@ -6557,16 +6606,16 @@ mod tests {
 
     #[test]
     fn test_opcode_58() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CLI instruction
-        memory.borrow_mut().write(0x0400, 0x58, false); // CLI opcode
+        memory.write(0x0400, 0x58, false); // CLI opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0xFF; // All flags set
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         assert_eq!(cpu.state.p & 0x04, 0, "I flag should be clear");
         assert_eq!(cycles, 2, "CLI should take 2 cycles");
@@ -6574,23 +6623,23 @@ mod tests {
 
     #[test]
     fn test_opcode_59() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up EOR $1234,Y instruction
-        memory.borrow_mut().write(0x0400, 0x59, false); // EOR Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, 0x59, false); // EOR Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + Y
-        memory.borrow_mut().write(0x1238, 0b1010_1010, false);
+        memory.write(0x1238, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.y = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 ^ 0b1010_1010 = 0b0101_1010
         assert_eq!(cpu.state.a, 0b0101_1010, "A should contain result of EOR");
@@ -6602,17 +6651,17 @@ mod tests {
 
     #[test]
     fn test_opcode_5a() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up NOP instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x5A, false); // NOP opcode
+        memory.write(0x0400, 0x5A, false); // NOP opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x11;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // NOP does nothing
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -6622,26 +6671,26 @@ mod tests {
 
     #[test]
     fn test_opcode_5b() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SRE $1234,Y instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x5B, false); // SRE Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, 0x5B, false); // SRE Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + Y
-        memory.borrow_mut().write(0x1238, 0b1010_1010, false);
+        memory.write(0x1238, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.y = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be shifted right
-        let mem_result = memory.borrow().read(0x1238);
+        let mem_result = memory.read(0x1238);
         assert_eq!(mem_result, 0b0101_0101, "Memory should be shifted right");
         // A should be EORed with result
         assert_eq!(cpu.state.a, 0b1010_0101, "A should contain EOR result");
@@ -6650,20 +6699,20 @@ mod tests {
 
     #[test]
     fn test_opcode_5c() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up TOP $1234,X instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x5C, false); // TOP Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, 0x5C, false); // TOP Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x04;
         cpu.state.a = 0x11;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // TOP does nothing
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -6676,23 +6725,23 @@ mod tests {
 
     #[test]
     fn test_opcode_5d() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up EOR $1234,X instruction
-        memory.borrow_mut().write(0x0400, 0x5D, false); // EOR Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, 0x5D, false); // EOR Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + X
-        memory.borrow_mut().write(0x1238, 0b1010_1010, false);
+        memory.write(0x1238, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.x = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0b1111_0000 ^ 0b1010_1010 = 0b0101_1010
         assert_eq!(cpu.state.a, 0b0101_1010, "A should contain result of EOR");
@@ -6704,25 +6753,25 @@ mod tests {
 
     #[test]
     fn test_opcode_5e() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LSR $1234,X instruction
-        memory.borrow_mut().write(0x0400, 0x5E, false); // LSR Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, 0x5E, false); // LSR Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + X
-        memory.borrow_mut().write(0x1238, 0b1010_1011, false);
+        memory.write(0x1238, 0b1010_1011, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be shifted right
-        let result = memory.borrow().read(0x1238);
+        let result = memory.read(0x1238);
         assert_eq!(result, 0b0101_0101, "Memory should be shifted right");
         assert_eq!(cpu.state.p & FLAG_CARRY, 1, "C flag should be set");
         assert_eq!(cycles, 7, "LSR absolute,X should take 7 cycles");
@@ -6730,26 +6779,26 @@ mod tests {
 
     #[test]
     fn test_opcode_5f() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SRE $1234,X instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, 0x5F, false); // SRE Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, 0x5F, false); // SRE Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + X
-        memory.borrow_mut().write(0x1238, 0b1010_1010, false);
+        memory.write(0x1238, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.x = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be shifted right
-        let mem_result = memory.borrow().read(0x1238);
+        let mem_result = memory.read(0x1238);
         assert_eq!(mem_result, 0b0101_0101, "Memory should be shifted right");
         // A should be EORed with result
         assert_eq!(cpu.state.a, 0b1010_0101, "A should contain EOR result");
@@ -6758,20 +6807,20 @@ mod tests {
 
     #[test]
     fn test_opcode_60() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RTS instruction
-        memory.borrow_mut().write(0x0400, RTS, false); // RTS opcode
+        memory.write(0x0400, RTS, false); // RTS opcode
 
         // Push return address to stack (RTS pulls PC-1, so push 0x1233)
-        memory.borrow_mut().write(0x01FD, 0x33, false); // PCL
-        memory.borrow_mut().write(0x01FE, 0x12, false); // PCH
+        memory.write(0x01FD, 0x33, false); // PCL
+        memory.write(0x01FE, 0x12, false); // PCH
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.sp = 0xFC;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         assert_eq!(
             cpu.state.pc, 0x1234,
@@ -6783,26 +6832,26 @@ mod tests {
 
     #[test]
     fn test_opcode_61() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ADC ($20,X) instruction
-        memory.borrow_mut().write(0x0400, ADC_INDX, false); // ADC (Indirect,X) opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base
+        memory.write(0x0400, ADC_INDX, false); // ADC (Indirect,X) opcode
+        memory.write(0x0401, 0x20, false); // Zero page base
 
         // Set up pointer at $24 (base $20 + X $04)
-        memory.borrow_mut().write(0x0024, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0025, 0x12, false); // High byte
+        memory.write(0x0024, 0x34, false); // Low byte
+        memory.write(0x0025, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0x05, false);
+        memory.write(0x1234, 0x05, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.x = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0x10 + 0x05 = 0x15
         assert_eq!(cpu.state.a, 0x15, "A should contain sum");
@@ -6812,15 +6861,15 @@ mod tests {
 
     #[test]
     fn test_opcode_62() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up KIL instruction
-        memory.borrow_mut().write(0x0400, KIL7, false); // KIL opcode
+        memory.write(0x0400, KIL7, false); // KIL opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         assert!(cpu.is_halted(), "CPU should be halted");
         assert_eq!(cycles, 1, "KIL should take 1 cycle");
@@ -6828,29 +6877,29 @@ mod tests {
 
     #[test]
     fn test_opcode_63() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RRA ($20,X) instruction (illegal opcode - ROR + ADC)
-        memory.borrow_mut().write(0x0400, RRA_INDX, false); // RRA (Indirect,X) opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base
+        memory.write(0x0400, RRA_INDX, false); // RRA (Indirect,X) opcode
+        memory.write(0x0401, 0x20, false); // Zero page base
 
         // Set up pointer at $24
-        memory.borrow_mut().write(0x0024, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0025, 0x12, false); // High byte
+        memory.write(0x0024, 0x34, false); // Low byte
+        memory.write(0x0025, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b1010_1010, false);
+        memory.write(0x1234, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.x = 0x04;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated right: 0b1010_1010 ROR with C=1 = 0b1101_0101
-        let mem_result = memory.borrow().read(0x1234);
+        let mem_result = memory.read(0x1234);
         assert_eq!(mem_result, 0b1101_0101, "Memory should be rotated right");
         // A should be ADCed with result: 0x10 + 0xD5 = 0xE5
         assert_eq!(cpu.state.a, 0xE5, "A should contain ADC result");
@@ -6859,18 +6908,18 @@ mod tests {
 
     #[test]
     fn test_opcode_64() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DOP $20 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, DOP_ZP3, false); // DOP Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, DOP_ZP3, false); // DOP Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x11;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // DOP does nothing
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -6880,21 +6929,21 @@ mod tests {
 
     #[test]
     fn test_opcode_65() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ADC $20 instruction
-        memory.borrow_mut().write(0x0400, ADC_ZP, false); // ADC Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, ADC_ZP, false); // ADC Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $20
-        memory.borrow_mut().write(0x0020, 0x05, false);
+        memory.write(0x0020, 0x05, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0x10 + 0x05 = 0x15
         assert_eq!(cpu.state.a, 0x15, "A should contain sum");
@@ -6903,23 +6952,23 @@ mod tests {
 
     #[test]
     fn test_opcode_66() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ROR $20 instruction
-        memory.borrow_mut().write(0x0400, ROR_ZP, false); // ROR Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, ROR_ZP, false); // ROR Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $20
-        memory.borrow_mut().write(0x0020, 0b0101_0101, false);
+        memory.write(0x0020, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated right: 0b0101_0101 ROR with C=1 = 0b1010_1010
-        let result = memory.borrow().read(0x0020);
+        let result = memory.read(0x0020);
         assert_eq!(result, 0b1010_1010, "Memory should be rotated right");
         assert_eq!(
             cpu.state.p & FLAG_NEGATIVE,
@@ -6936,24 +6985,24 @@ mod tests {
 
     #[test]
     fn test_opcode_67() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RRA $20 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, RRA_ZP, false); // RRA Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, RRA_ZP, false); // RRA Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $20
-        memory.borrow_mut().write(0x0020, 0b1010_1010, false);
+        memory.write(0x0020, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated right
-        let mem_result = memory.borrow().read(0x0020);
+        let mem_result = memory.read(0x0020);
         assert_eq!(mem_result, 0b1101_0101, "Memory should be rotated right");
         // A should be ADCed with result
         assert_eq!(cpu.state.a, 0xE5, "A should contain ADC result");
@@ -6962,20 +7011,20 @@ mod tests {
 
     #[test]
     fn test_opcode_68() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up PLA instruction
-        memory.borrow_mut().write(0x0400, PLA, false); // PLA opcode
+        memory.write(0x0400, PLA, false); // PLA opcode
 
         // Push value to stack
-        memory.borrow_mut().write(0x01FD, 0x42, false);
+        memory.write(0x01FD, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.sp = 0xFC;
         cpu.state.a = 0x00;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         assert_eq!(cpu.state.a, 0x42, "A should be pulled from stack");
         assert_eq!(cpu.state.sp, 0xFD, "SP should be incremented");
@@ -6984,18 +7033,18 @@ mod tests {
 
     #[test]
     fn test_opcode_69() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ADC #$05 instruction
-        memory.borrow_mut().write(0x0400, ADC_IMM, false); // ADC Immediate opcode
-        memory.borrow_mut().write(0x0401, 0x05, false); // Immediate value
+        memory.write(0x0400, ADC_IMM, false); // ADC Immediate opcode
+        memory.write(0x0401, 0x05, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0x10 + 0x05 = 0x15
         assert_eq!(cpu.state.a, 0x15, "A should contain sum");
@@ -7004,17 +7053,17 @@ mod tests {
 
     #[test]
     fn test_opcode_6a() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ROR A instruction
-        memory.borrow_mut().write(0x0400, ROR_ACC, false); // ROR Accumulator opcode
+        memory.write(0x0400, ROR_ACC, false); // ROR Accumulator opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b0101_0101;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be rotated right: 0b0101_0101 ROR with C=1 = 0b1010_1010
         assert_eq!(cpu.state.a, 0b1010_1010, "A should be rotated right");
@@ -7033,18 +7082,18 @@ mod tests {
 
     #[test]
     fn test_opcode_6b() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ARR #$AA instruction (illegal opcode - AND + ROR)
-        memory.borrow_mut().write(0x0400, ARR_IMM, false); // ARR Immediate opcode
-        memory.borrow_mut().write(0x0401, 0b1010_1010, false); // Immediate value
+        memory.write(0x0400, ARR_IMM, false); // ARR Immediate opcode
+        memory.write(0x0401, 0b1010_1010, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0011;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be ANDed then rotated: (0b1111_0011 & 0b1010_1010) ROR with C=1 = 0b1010_0010 ROR = 0b1101_0001
         assert_eq!(cpu.state.a, 0b1101_0001, "A should contain AND+ROR result");
@@ -7053,18 +7102,18 @@ mod tests {
 
     #[test]
     fn test_opcode_6c() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up JMP ($1200) instruction at address $0800
-        memory.borrow_mut().write(0x0800, JMP_IND, false); // JMP Indirect opcode (0x6C)
-        memory.borrow_mut().write(0x0801, 0x00, false); // Low byte of indirect address
-        memory.borrow_mut().write(0x0802, 0x12, false); // High byte of indirect address
+        memory.write(0x0800, JMP_IND, false); // JMP Indirect opcode (0x6C)
+        memory.write(0x0801, 0x00, false); // Low byte of indirect address
+        memory.write(0x0802, 0x12, false); // High byte of indirect address
 
         // Set up the target address at the indirect location $1200
-        memory.borrow_mut().write(0x1200, 0x34, false); // Low byte of target
-        memory.borrow_mut().write(0x1201, 0x56, false); // High byte of target
+        memory.write(0x1200, 0x34, false); // Low byte of target
+        memory.write(0x1201, 0x56, false); // High byte of target
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0800;
         cpu.state.sp = 0xFD;
         cpu.state.a = 0xAA;
@@ -7072,7 +7121,7 @@ mod tests {
         cpu.state.y = 0xCC;
         cpu.state.p = 0xDD;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Verify the CPU state after JMP execution
         assert_eq!(cpu.state.pc, 0x5634, "PC should jump to target address");
@@ -7089,24 +7138,24 @@ mod tests {
 
     #[test]
     fn test_opcode_6c_boundary_bug() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up JMP ($12FF) instruction at address $0800
         // This tests the page boundary bug where high byte is read from $1200 instead of $1300
-        memory.borrow_mut().write(0x0800, JMP_IND, false); // JMP Indirect opcode (0x6C)
-        memory.borrow_mut().write(0x0801, 0xFF, false); // Low byte of indirect address
-        memory.borrow_mut().write(0x0802, 0x12, false); // High byte of indirect address
+        memory.write(0x0800, JMP_IND, false); // JMP Indirect opcode (0x6C)
+        memory.write(0x0801, 0xFF, false); // Low byte of indirect address
+        memory.write(0x0802, 0x12, false); // High byte of indirect address
 
         // Set up target address with page boundary bug
-        memory.borrow_mut().write(0x12FF, 0x34, false); // Low byte at $12FF
-        memory.borrow_mut().write(0x1200, 0x56, false); // High byte wraps to $1200 (bug)
-        memory.borrow_mut().write(0x1300, 0x99, false); // This would be correct but is not used
+        memory.write(0x12FF, 0x34, false); // Low byte at $12FF
+        memory.write(0x1200, 0x56, false); // High byte wraps to $1200 (bug)
+        memory.write(0x1300, 0x99, false); // This would be correct but is not used
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0800;
         cpu.state.sp = 0xFD;
 
-        execute_instruction(&mut cpu);
+        execute_instruction(&mut cpu, &mut memory);
 
         // Verify the CPU jumps to $5634 (using $1200 for high byte, not $1300)
         assert_eq!(
@@ -7117,22 +7166,22 @@ mod tests {
 
     #[test]
     fn test_opcode_6d() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ADC $1234 instruction
-        memory.borrow_mut().write(0x0400, ADC_ABS, false); // ADC Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, ADC_ABS, false); // ADC Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0x05, false);
+        memory.write(0x1234, 0x05, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0x10 + 0x05 = 0x15
         assert_eq!(cpu.state.a, 0x15, "A should contain sum");
@@ -7141,24 +7190,24 @@ mod tests {
 
     #[test]
     fn test_opcode_6e() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ROR $1234 instruction
-        memory.borrow_mut().write(0x0400, ROR_ABS, false); // ROR Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, ROR_ABS, false); // ROR Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b0101_0101, false);
+        memory.write(0x1234, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated right
-        let result = memory.borrow().read(0x1234);
+        let result = memory.read(0x1234);
         assert_eq!(result, 0b1010_1010, "Memory should be rotated right");
         assert_eq!(cpu.state.p & FLAG_CARRY, FLAG_CARRY, "C flag should be set");
         assert_eq!(cycles, 6, "ROR absolute should take 6 cycles");
@@ -7166,25 +7215,25 @@ mod tests {
 
     #[test]
     fn test_opcode_6f() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RRA $1234 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, RRA_ABS, false); // RRA Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, RRA_ABS, false); // RRA Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0b1010_1010, false);
+        memory.write(0x1234, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.p = FLAG_CARRY; // Set carry flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated right
-        let mem_result = memory.borrow().read(0x1234);
+        let mem_result = memory.read(0x1234);
         assert_eq!(mem_result, 0b1101_0101, "Memory should be rotated right");
         // A should be ADCed with result
         assert_eq!(cpu.state.a, 0xE5, "A should contain ADC result");
@@ -7193,17 +7242,17 @@ mod tests {
 
     #[test]
     fn test_opcode_70() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up BVS instruction with branch offset
-        memory.borrow_mut().write(0x0400, BVS, false); // BVS opcode
-        memory.borrow_mut().write(0x0401, 0x10, false); // Branch offset +16
+        memory.write(0x0400, BVS, false); // BVS opcode
+        memory.write(0x0401, 0x10, false); // Branch offset +16
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = FLAG_OVERFLOW; // Set overflow flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // PC should branch: 0x0402 + 0x10 = 0x0412
         assert_eq!(cpu.state.pc, 0x0412, "PC should branch when V flag is set");
@@ -7212,26 +7261,26 @@ mod tests {
 
     #[test]
     fn test_opcode_71() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ADC ($20),Y instruction
-        memory.borrow_mut().write(0x0400, ADC_INDY, false); // ADC (Indirect),Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, ADC_INDY, false); // ADC (Indirect),Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up pointer at $20
-        memory.borrow_mut().write(0x0020, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x34, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
         // Set up value at $1234 + Y
-        memory.borrow_mut().write(0x1238, 0x05, false);
+        memory.write(0x1238, 0x05, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.y = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0x10 + 0x05 = 0x15
         assert_eq!(cpu.state.a, 0x15, "A should contain sum");
@@ -7243,15 +7292,15 @@ mod tests {
 
     #[test]
     fn test_opcode_72() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up KIL instruction
-        memory.borrow_mut().write(0x0400, KIL8, false); // KIL opcode
+        memory.write(0x0400, KIL8, false); // KIL opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         assert!(cpu.is_halted(), "CPU should be halted");
         assert_eq!(cycles, 1, "KIL should take 1 cycle");
@@ -7259,29 +7308,29 @@ mod tests {
 
     #[test]
     fn test_opcode_73() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RRA ($20),Y instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, RRA_INDY, false); // RRA (Indirect),Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, RRA_INDY, false); // RRA (Indirect),Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up pointer at $20
-        memory.borrow_mut().write(0x0020, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x34, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
         // Set up value at $1234 + Y
-        memory.borrow_mut().write(0x1238, 0b1010_1010, false);
+        memory.write(0x1238, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.y = 0x04;
         cpu.state.p = FLAG_CARRY;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated right
-        let mem_result = memory.borrow().read(0x1238);
+        let mem_result = memory.read(0x1238);
         assert_eq!(mem_result, 0b1101_0101, "Memory should be rotated right");
         // A should be ADCed with result
         assert_eq!(cpu.state.a, 0xE5, "A should contain ADC result");
@@ -7290,19 +7339,19 @@ mod tests {
 
     #[test]
     fn test_opcode_74() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DOP $20,X instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, DOP_ZPX4, false); // DOP Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, DOP_ZPX4, false); // DOP Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x04;
         cpu.state.a = 0x11;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // DOP does nothing
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -7312,22 +7361,22 @@ mod tests {
 
     #[test]
     fn test_opcode_75() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ADC $20,X instruction
-        memory.borrow_mut().write(0x0400, ADC_ZPX, false); // ADC Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, ADC_ZPX, false); // ADC Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $24 (wraps in zero page)
-        memory.borrow_mut().write(0x0024, 0x05, false);
+        memory.write(0x0024, 0x05, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.x = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0x10 + 0x05 = 0x15
         assert_eq!(cpu.state.a, 0x15, "A should contain sum");
@@ -7336,24 +7385,24 @@ mod tests {
 
     #[test]
     fn test_opcode_76() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ROR $20,X instruction
-        memory.borrow_mut().write(0x0400, ROR_ZPX, false); // ROR Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, ROR_ZPX, false); // ROR Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $24
-        memory.borrow_mut().write(0x0024, 0b0101_0101, false);
+        memory.write(0x0024, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x04;
         cpu.state.p = FLAG_CARRY;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated right
-        let result = memory.borrow().read(0x0024);
+        let result = memory.read(0x0024);
         assert_eq!(result, 0b1010_1010, "Memory should be rotated right");
         assert_eq!(cpu.state.p & FLAG_CARRY, FLAG_CARRY, "C flag should be set");
         assert_eq!(cycles, 6, "ROR zero page,X should take 6 cycles");
@@ -7361,25 +7410,25 @@ mod tests {
 
     #[test]
     fn test_opcode_77() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RRA $20,X instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, RRA_ZPX, false); // RRA Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, RRA_ZPX, false); // RRA Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $24
-        memory.borrow_mut().write(0x0024, 0b1010_1010, false);
+        memory.write(0x0024, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.x = 0x04;
         cpu.state.p = FLAG_CARRY;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated right
-        let mem_result = memory.borrow().read(0x0024);
+        let mem_result = memory.read(0x0024);
         assert_eq!(mem_result, 0b1101_0101, "Memory should be rotated right");
         // A should be ADCed with result
         assert_eq!(cpu.state.a, 0xE5, "A should contain ADC result");
@@ -7388,16 +7437,16 @@ mod tests {
 
     #[test]
     fn test_opcode_78() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SEI instruction
-        memory.borrow_mut().write(0x0400, SEI, false); // SEI opcode
+        memory.write(0x0400, SEI, false); // SEI opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         assert_eq!(
             cpu.state.p & FLAG_INTERRUPT,
@@ -7409,23 +7458,23 @@ mod tests {
 
     #[test]
     fn test_opcode_79() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ADC $1234,Y instruction
-        memory.borrow_mut().write(0x0400, ADC_ABSY, false); // ADC Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, ADC_ABSY, false); // ADC Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + Y
-        memory.borrow_mut().write(0x1238, 0x05, false);
+        memory.write(0x1238, 0x05, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.y = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0x10 + 0x05 = 0x15
         assert_eq!(cpu.state.a, 0x15, "A should contain sum");
@@ -7437,17 +7486,17 @@ mod tests {
 
     #[test]
     fn test_opcode_7a() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up NOP instruction
-        memory.borrow_mut().write(0x0400, NOP_IMP4, false); // NOP opcode
+        memory.write(0x0400, NOP_IMP4, false); // NOP opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x11;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // NOP does nothing
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -7457,26 +7506,26 @@ mod tests {
 
     #[test]
     fn test_opcode_7b() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RRA $1234,Y instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, RRA_ABSY, false); // RRA Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, RRA_ABSY, false); // RRA Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + Y
-        memory.borrow_mut().write(0x1238, 0b1010_1010, false);
+        memory.write(0x1238, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.y = 0x04;
         cpu.state.p = FLAG_CARRY;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated right
-        let mem_result = memory.borrow().read(0x1238);
+        let mem_result = memory.read(0x1238);
         assert_eq!(mem_result, 0b1101_0101, "Memory should be rotated right");
         // A should be ADCed with result
         assert_eq!(cpu.state.a, 0xE5, "A should contain ADC result");
@@ -7485,20 +7534,20 @@ mod tests {
 
     #[test]
     fn test_opcode_7c() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up TOP $1234,X instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, TOP_ABSX4, false); // TOP Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, TOP_ABSX4, false); // TOP Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x04;
         cpu.state.a = 0x11;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // TOP does nothing
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -7511,23 +7560,23 @@ mod tests {
 
     #[test]
     fn test_opcode_7d() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ADC $1234,X instruction
-        memory.borrow_mut().write(0x0400, ADC_ABSX, false); // ADC Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, ADC_ABSX, false); // ADC Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + X
-        memory.borrow_mut().write(0x1238, 0x05, false);
+        memory.write(0x1238, 0x05, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.x = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be 0x10 + 0x05 = 0x15
         assert_eq!(cpu.state.a, 0x15, "A should contain sum");
@@ -7539,25 +7588,25 @@ mod tests {
 
     #[test]
     fn test_opcode_7e() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ROR $1234,X instruction
-        memory.borrow_mut().write(0x0400, ROR_ABSX, false); // ROR Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, ROR_ABSX, false); // ROR Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + X
-        memory.borrow_mut().write(0x1238, 0b0101_0101, false);
+        memory.write(0x1238, 0b0101_0101, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x04;
         cpu.state.p = FLAG_CARRY;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated right
-        let result = memory.borrow().read(0x1238);
+        let result = memory.read(0x1238);
         assert_eq!(result, 0b1010_1010, "Memory should be rotated right");
         assert_eq!(cpu.state.p & FLAG_CARRY, FLAG_CARRY, "C flag should be set");
         assert_eq!(cycles, 7, "ROR absolute,X should take 7 cycles");
@@ -7565,26 +7614,26 @@ mod tests {
 
     #[test]
     fn test_opcode_7f() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up RRA $1234,X instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, RRA_ABSX, false); // RRA Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, RRA_ABSX, false); // RRA Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + X
-        memory.borrow_mut().write(0x1238, 0b1010_1010, false);
+        memory.write(0x1238, 0b1010_1010, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x10;
         cpu.state.x = 0x04;
         cpu.state.p = FLAG_CARRY;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be rotated right
-        let mem_result = memory.borrow().read(0x1238);
+        let mem_result = memory.read(0x1238);
         assert_eq!(mem_result, 0b1101_0101, "Memory should be rotated right");
         // A should be ADCed with result
         assert_eq!(cpu.state.a, 0xE5, "A should contain ADC result");
@@ -7593,18 +7642,18 @@ mod tests {
 
     #[test]
     fn test_opcode_80() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DOP #$12 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, DOP_IMM, false); // DOP Immediate opcode
-        memory.borrow_mut().write(0x0401, 0x12, false); // Immediate value (ignored)
+        memory.write(0x0400, DOP_IMM, false); // DOP Immediate opcode
+        memory.write(0x0401, 0x12, false); // Immediate value (ignored)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x11;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // DOP does nothing
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -7614,43 +7663,43 @@ mod tests {
 
     #[test]
     fn test_opcode_81() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STA ($20,X) instruction
-        memory.borrow_mut().write(0x0400, STA_INDX, false); // STA (Indirect,X) opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, STA_INDX, false); // STA (Indirect,X) opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
         // Set up pointer at $24 (0x20 + 0x04)
-        memory.borrow_mut().write(0x0024, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0025, 0x12, false); // High byte
+        memory.write(0x0024, 0x34, false); // Low byte
+        memory.write(0x0025, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x42;
         cpu.state.x = 0x04;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be stored at $1234
-        let result = memory.borrow().read(0x1234);
+        let result = memory.read(0x1234);
         assert_eq!(result, 0x42, "A should be stored at target address");
         assert_eq!(cycles, 6, "STA (indirect,X) should take 6 cycles");
     }
 
     #[test]
     fn test_opcode_82() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DOP #$12 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, DOP_IMM2, false); // DOP Immediate opcode
-        memory.borrow_mut().write(0x0401, 0x12, false); // Immediate value (ignored)
+        memory.write(0x0400, DOP_IMM2, false); // DOP Immediate opcode
+        memory.write(0x0401, 0x12, false); // Immediate value (ignored)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x11;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // DOP does nothing
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -7660,123 +7709,123 @@ mod tests {
 
     #[test]
     fn test_opcode_83() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SAX ($20,X) instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, SAX_INDX, false); // SAX (Indirect,X) opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, SAX_INDX, false); // SAX (Indirect,X) opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
         // Set up pointer at $24 (0x20 + 0x04)
-        memory.borrow_mut().write(0x0024, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0025, 0x12, false); // High byte
+        memory.write(0x0024, 0x34, false); // Low byte
+        memory.write(0x0025, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.x = 0x04;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A AND X should be stored at $1234
-        let result = memory.borrow().read(0x1234);
+        let result = memory.read(0x1234);
         assert_eq!(result, 0b0000_0000, "A AND X should be stored");
         assert_eq!(cycles, 6, "SAX (indirect,X) should take 6 cycles");
     }
 
     #[test]
     fn test_opcode_84() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STY $20 instruction
-        memory.borrow_mut().write(0x0400, STY_ZP, false); // STY Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, STY_ZP, false); // STY Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0x42;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y should be stored at $20
-        let result = memory.borrow().read(0x0020);
+        let result = memory.read(0x0020);
         assert_eq!(result, 0x42, "Y should be stored at zero page address");
         assert_eq!(cycles, 3, "STY zero page should take 3 cycles");
     }
 
     #[test]
     fn test_opcode_85() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STA $20 instruction
-        memory.borrow_mut().write(0x0400, STA_ZP, false); // STA Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, STA_ZP, false); // STA Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x42;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be stored at $20
-        let result = memory.borrow().read(0x0020);
+        let result = memory.read(0x0020);
         assert_eq!(result, 0x42, "A should be stored at zero page address");
         assert_eq!(cycles, 3, "STA zero page should take 3 cycles");
     }
 
     #[test]
     fn test_opcode_86() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STX $20 instruction
-        memory.borrow_mut().write(0x0400, STX_ZP, false); // STX Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, STX_ZP, false); // STX Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x42;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X should be stored at $20
-        let result = memory.borrow().read(0x0020);
+        let result = memory.read(0x0020);
         assert_eq!(result, 0x42, "X should be stored at zero page address");
         assert_eq!(cycles, 3, "STX zero page should take 3 cycles");
     }
 
     #[test]
     fn test_opcode_87() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SAX $20 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, SAX_ZP, false); // SAX Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, SAX_ZP, false); // SAX Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.x = 0b1010_1010;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A AND X should be stored at $20
-        let result = memory.borrow().read(0x0020);
+        let result = memory.read(0x0020);
         assert_eq!(result, 0b1010_0000, "A AND X should be stored");
         assert_eq!(cycles, 3, "SAX zero page should take 3 cycles");
     }
 
     #[test]
     fn test_opcode_88() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DEY instruction
-        memory.borrow_mut().write(0x0400, DEY, false); // DEY opcode
+        memory.write(0x0400, DEY, false); // DEY opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0x42;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y should be decremented
         assert_eq!(cpu.state.y, 0x41, "Y should be decremented");
@@ -7785,18 +7834,18 @@ mod tests {
 
     #[test]
     fn test_opcode_89() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DOP #$12 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, DOP_IMM3, false); // DOP Immediate opcode
-        memory.borrow_mut().write(0x0401, 0x12, false); // Immediate value (ignored)
+        memory.write(0x0400, DOP_IMM3, false); // DOP Immediate opcode
+        memory.write(0x0401, 0x12, false); // Immediate value (ignored)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x11;
         cpu.state.p = 0x33;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // DOP does nothing
         assert_eq!(cpu.state.a, 0x11, "A should not change");
@@ -7806,18 +7855,18 @@ mod tests {
 
     #[test]
     fn test_opcode_8a() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up TXA instruction
-        memory.borrow_mut().write(0x0400, TXA, false); // TXA opcode
+        memory.write(0x0400, TXA, false); // TXA opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x42;
         cpu.state.a = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should contain X
         assert_eq!(cpu.state.a, 0x42, "A should contain X");
@@ -7826,19 +7875,19 @@ mod tests {
 
     #[test]
     fn test_opcode_8b() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up XAA #$12 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, XAA_IMM, false); // XAA Immediate opcode
-        memory.borrow_mut().write(0x0401, 0b1111_0000, false); // Immediate value
+        memory.write(0x0400, XAA_IMM, false); // XAA Immediate opcode
+        memory.write(0x0401, 0b1111_0000, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1010_1010;
         cpu.state.x = 0b0011_1100;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be (A OR MAGIC) AND X AND IMM
         // XAA is highly unstable, but we'll implement a basic version
@@ -7847,103 +7896,103 @@ mod tests {
 
     #[test]
     fn test_opcode_8c() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STY $1234 instruction
-        memory.borrow_mut().write(0x0400, STY_ABS, false); // STY Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, STY_ABS, false); // STY Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0x42;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y should be stored at $1234
-        let result = memory.borrow().read(0x1234);
+        let result = memory.read(0x1234);
         assert_eq!(result, 0x42, "Y should be stored at absolute address");
         assert_eq!(cycles, 4, "STY absolute should take 4 cycles");
     }
 
     #[test]
     fn test_opcode_8d() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STA $1234 instruction
-        memory.borrow_mut().write(0x0400, STA_ABS, false); // STA Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, STA_ABS, false); // STA Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x42;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be stored at $1234
-        let result = memory.borrow().read(0x1234);
+        let result = memory.read(0x1234);
         assert_eq!(result, 0x42, "A should be stored at absolute address");
         assert_eq!(cycles, 4, "STA absolute should take 4 cycles");
     }
 
     #[test]
     fn test_opcode_8e() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STX $1234 instruction
-        memory.borrow_mut().write(0x0400, STX_ABS, false); // STX Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, STX_ABS, false); // STX Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x42;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X should be stored at $1234
-        let result = memory.borrow().read(0x1234);
+        let result = memory.read(0x1234);
         assert_eq!(result, 0x42, "X should be stored at absolute address");
         assert_eq!(cycles, 4, "STX absolute should take 4 cycles");
     }
 
     #[test]
     fn test_opcode_8f() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SAX $1234 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, SAX_ABS, false); // SAX Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, SAX_ABS, false); // SAX Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1111_0000;
         cpu.state.x = 0b1010_1010;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A AND X should be stored at $1234
-        let result = memory.borrow().read(0x1234);
+        let result = memory.read(0x1234);
         assert_eq!(result, 0b1010_0000, "A AND X should be stored");
         assert_eq!(cycles, 4, "SAX absolute should take 4 cycles");
     }
 
     #[test]
     fn test_opcode_a0() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDY #$42 instruction
-        memory.borrow_mut().write(0x0400, LDY_IMM, false); // LDY Immediate opcode
-        memory.borrow_mut().write(0x0401, 0x42, false); // Immediate value
+        memory.write(0x0400, LDY_IMM, false); // LDY Immediate opcode
+        memory.write(0x0401, 0x42, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y should be loaded with 0x42
         assert_eq!(cpu.state.y, 0x42, "Y should be loaded with immediate value");
@@ -7952,25 +8001,25 @@ mod tests {
 
     #[test]
     fn test_opcode_a1() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDA ($20,X) instruction
-        memory.borrow_mut().write(0x0400, LDA_INDX, false); // LDA (Indirect,X) opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, LDA_INDX, false); // LDA (Indirect,X) opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
         // Set up pointer at $24 (0x20 + 0x04)
-        memory.borrow_mut().write(0x0024, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0025, 0x12, false); // High byte
+        memory.write(0x0024, 0x34, false); // Low byte
+        memory.write(0x0025, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0x42, false);
+        memory.write(0x1234, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.x = 0x04;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be loaded with 0x42
         assert_eq!(cpu.state.a, 0x42, "A should be loaded from target address");
@@ -7979,18 +8028,18 @@ mod tests {
 
     #[test]
     fn test_opcode_a2() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDX #$42 instruction
-        memory.borrow_mut().write(0x0400, LDX_IMM, false); // LDX Immediate opcode
-        memory.borrow_mut().write(0x0401, 0x42, false); // Immediate value
+        memory.write(0x0400, LDX_IMM, false); // LDX Immediate opcode
+        memory.write(0x0401, 0x42, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X should be loaded with 0x42
         assert_eq!(cpu.state.x, 0x42, "X should be loaded with immediate value");
@@ -7999,25 +8048,25 @@ mod tests {
 
     #[test]
     fn test_opcode_a3() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LAX ($20,X) instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, LAX_INDX, false); // LAX (Indirect,X) opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, LAX_INDX, false); // LAX (Indirect,X) opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
         // Set up pointer at $24 (0x20 + 0x04)
-        memory.borrow_mut().write(0x0024, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0025, 0x12, false); // High byte
+        memory.write(0x0024, 0x34, false); // Low byte
+        memory.write(0x0025, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0x42, false);
+        memory.write(0x1234, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.x = 0x04;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Both A and X should be loaded with 0x42
         assert_eq!(cpu.state.a, 0x42, "A should be loaded from target address");
@@ -8027,21 +8076,21 @@ mod tests {
 
     #[test]
     fn test_opcode_a4() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDY $20 instruction
-        memory.borrow_mut().write(0x0400, LDY_ZP, false); // LDY Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, LDY_ZP, false); // LDY Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $20
-        memory.borrow_mut().write(0x0020, 0x42, false);
+        memory.write(0x0020, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y should be loaded with 0x42
         assert_eq!(cpu.state.y, 0x42, "Y should be loaded from zero page");
@@ -8050,21 +8099,21 @@ mod tests {
 
     #[test]
     fn test_opcode_a5() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDA $20 instruction
-        memory.borrow_mut().write(0x0400, LDA_ZP, false); // LDA Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, LDA_ZP, false); // LDA Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $20
-        memory.borrow_mut().write(0x0020, 0x42, false);
+        memory.write(0x0020, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be loaded with 0x42
         assert_eq!(cpu.state.a, 0x42, "A should be loaded from zero page");
@@ -8073,21 +8122,21 @@ mod tests {
 
     #[test]
     fn test_opcode_a6() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDX $20 instruction
-        memory.borrow_mut().write(0x0400, LDX_ZP, false); // LDX Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, LDX_ZP, false); // LDX Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $20
-        memory.borrow_mut().write(0x0020, 0x42, false);
+        memory.write(0x0020, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X should be loaded with 0x42
         assert_eq!(cpu.state.x, 0x42, "X should be loaded from zero page");
@@ -8096,22 +8145,22 @@ mod tests {
 
     #[test]
     fn test_opcode_a7() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LAX $20 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, LAX_ZP, false); // LAX Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, LAX_ZP, false); // LAX Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up value at $20
-        memory.borrow_mut().write(0x0020, 0x42, false);
+        memory.write(0x0020, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.x = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Both A and X should be loaded with 0x42
         assert_eq!(cpu.state.a, 0x42, "A should be loaded from zero page");
@@ -8121,18 +8170,18 @@ mod tests {
 
     #[test]
     fn test_opcode_a8() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up TAY instruction
-        memory.borrow_mut().write(0x0400, TAY, false); // TAY opcode
+        memory.write(0x0400, TAY, false); // TAY opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x42;
         cpu.state.y = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y should contain A
         assert_eq!(cpu.state.y, 0x42, "Y should contain A");
@@ -8141,18 +8190,18 @@ mod tests {
 
     #[test]
     fn test_opcode_a9() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDA #$42 instruction
-        memory.borrow_mut().write(0x0400, LDA_IMM, false); // LDA Immediate opcode
-        memory.borrow_mut().write(0x0401, 0x42, false); // Immediate value
+        memory.write(0x0400, LDA_IMM, false); // LDA Immediate opcode
+        memory.write(0x0401, 0x42, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be loaded with 0x42
         assert_eq!(cpu.state.a, 0x42, "A should be loaded with immediate value");
@@ -8161,18 +8210,18 @@ mod tests {
 
     #[test]
     fn test_opcode_aa() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up TAX instruction
-        memory.borrow_mut().write(0x0400, TAX, false); // TAX opcode
+        memory.write(0x0400, TAX, false); // TAX opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x42;
         cpu.state.x = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X should contain A
         assert_eq!(cpu.state.x, 0x42, "X should contain A");
@@ -8181,19 +8230,19 @@ mod tests {
 
     #[test]
     fn test_opcode_ab() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ATX #$42 instruction (illegal opcode, also called OAL/ANE)
-        memory.borrow_mut().write(0x0400, ATX_IMM, false); // ATX Immediate opcode
-        memory.borrow_mut().write(0x0401, 0b1111_0000, false); // Immediate value
+        memory.write(0x0400, ATX_IMM, false); // ATX Immediate opcode
+        memory.write(0x0401, 0b1111_0000, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b1010_1010;
         cpu.state.x = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // ATX: A = X = (A OR CONST) AND IMM
         // CONST is typically 0xEE or 0xFF, we'll use 0xFF for simplicity
@@ -8205,22 +8254,22 @@ mod tests {
 
     #[test]
     fn test_opcode_ac() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDY $1234 instruction
-        memory.borrow_mut().write(0x0400, LDY_ABS, false); // LDY Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, LDY_ABS, false); // LDY Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0x42, false);
+        memory.write(0x1234, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y should be loaded with 0x42
         assert_eq!(
@@ -8232,22 +8281,22 @@ mod tests {
 
     #[test]
     fn test_opcode_ad() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDA $1234 instruction
-        memory.borrow_mut().write(0x0400, LDA_ABS, false); // LDA Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, LDA_ABS, false); // LDA Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0x42, false);
+        memory.write(0x1234, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be loaded with 0x42
         assert_eq!(
@@ -8259,22 +8308,22 @@ mod tests {
 
     #[test]
     fn test_opcode_ae() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDX $1234 instruction
-        memory.borrow_mut().write(0x0400, LDX_ABS, false); // LDX Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, LDX_ABS, false); // LDX Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0x42, false);
+        memory.write(0x1234, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X should be loaded with 0x42
         assert_eq!(
@@ -8286,23 +8335,23 @@ mod tests {
 
     #[test]
     fn test_opcode_af() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LAX $1234 instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, LAX_ABS, false); // LAX Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, LAX_ABS, false); // LAX Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0x42, false);
+        memory.write(0x1234, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.x = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Both A and X should be loaded with 0x42
         assert_eq!(
@@ -8318,17 +8367,17 @@ mod tests {
 
     #[test]
     fn test_opcode_b0() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up BCS instruction with positive offset
-        memory.borrow_mut().write(0x0400, BCS, false); // BCS opcode
-        memory.borrow_mut().write(0x0401, 0x10, false); // Offset +16
+        memory.write(0x0400, BCS, false); // BCS opcode
+        memory.write(0x0401, 0x10, false); // Offset +16
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = FLAG_CARRY; // Carry is set, branch should be taken
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // PC should be 0x0402 + 0x10 = 0x0412
         assert_eq!(cpu.state.pc, 0x0412, "Branch should be taken");
@@ -8340,26 +8389,26 @@ mod tests {
 
     #[test]
     fn test_opcode_b1() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDA ($20),Y instruction
-        memory.borrow_mut().write(0x0400, LDA_INDY, false); // LDA (Indirect),Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address containing pointer
+        memory.write(0x0400, LDA_INDY, false); // LDA (Indirect),Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address containing pointer
 
         // Set up pointer at $20-$21 pointing to $1200
-        memory.borrow_mut().write(0x0020, 0x00, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x00, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
         // Set up value at $1200 + Y ($05) = $1205
-        memory.borrow_mut().write(0x1205, 0x42, false);
+        memory.write(0x1205, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.y = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be loaded with 0x42
         assert_eq!(
@@ -8374,15 +8423,15 @@ mod tests {
 
     #[test]
     fn test_opcode_b2() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up KIL instruction
-        memory.borrow_mut().write(0x0400, KIL10, false); // KIL opcode
+        memory.write(0x0400, KIL10, false); // KIL opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // KIL halts the CPU, PC should not advance beyond the opcode
         assert_eq!(cycles, 1, "KIL should take 1 cycle");
@@ -8390,27 +8439,27 @@ mod tests {
 
     #[test]
     fn test_opcode_b3() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LAX ($20),Y instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, LAX_INDY, false); // LAX (Indirect),Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address containing pointer
+        memory.write(0x0400, LAX_INDY, false); // LAX (Indirect),Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address containing pointer
 
         // Set up pointer at $20-$21 pointing to $1200
-        memory.borrow_mut().write(0x0020, 0x00, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x00, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
         // Set up value at $1200 + Y ($05) = $1205
-        memory.borrow_mut().write(0x1205, 0x42, false);
+        memory.write(0x1205, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.x = 0x00;
         cpu.state.y = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Both A and X should be loaded with 0x42
         assert_eq!(
@@ -8429,22 +8478,22 @@ mod tests {
 
     #[test]
     fn test_opcode_b4() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDY $20,X instruction
-        memory.borrow_mut().write(0x0400, LDY_ZPX, false); // LDY Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, LDY_ZPX, false); // LDY Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
         // Set up value at $20 + X ($05) = $25
-        memory.borrow_mut().write(0x0025, 0x42, false);
+        memory.write(0x0025, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0x00;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y should be loaded with 0x42
         assert_eq!(cpu.state.y, 0x42, "Y should be loaded from zero page,X");
@@ -8453,22 +8502,22 @@ mod tests {
 
     #[test]
     fn test_opcode_b5() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDA $20,X instruction
-        memory.borrow_mut().write(0x0400, LDA_ZPX, false); // LDA Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, LDA_ZPX, false); // LDA Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
         // Set up value at $20 + X ($05) = $25
-        memory.borrow_mut().write(0x0025, 0x42, false);
+        memory.write(0x0025, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be loaded with 0x42
         assert_eq!(cpu.state.a, 0x42, "A should be loaded from zero page,X");
@@ -8477,22 +8526,22 @@ mod tests {
 
     #[test]
     fn test_opcode_b6() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDX $20,Y instruction
-        memory.borrow_mut().write(0x0400, LDX_ZPY, false); // LDX Zero Page,Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, LDX_ZPY, false); // LDX Zero Page,Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
         // Set up value at $20 + Y ($05) = $25
-        memory.borrow_mut().write(0x0025, 0x42, false);
+        memory.write(0x0025, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x00;
         cpu.state.y = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X should be loaded with 0x42
         assert_eq!(cpu.state.x, 0x42, "X should be loaded from zero page,Y");
@@ -8501,23 +8550,23 @@ mod tests {
 
     #[test]
     fn test_opcode_b7() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LAX $20,Y instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, LAX_ZPY, false); // LAX Zero Page,Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, LAX_ZPY, false); // LAX Zero Page,Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
         // Set up value at $20 + Y ($05) = $25
-        memory.borrow_mut().write(0x0025, 0x42, false);
+        memory.write(0x0025, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.x = 0x00;
         cpu.state.y = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Both A and X should be loaded with 0x42
         assert_eq!(cpu.state.a, 0x42, "A should be loaded from zero page,Y");
@@ -8527,16 +8576,16 @@ mod tests {
 
     #[test]
     fn test_opcode_b8() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CLV instruction
-        memory.borrow_mut().write(0x0400, CLV, false); // CLV opcode
+        memory.write(0x0400, CLV, false); // CLV opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = FLAG_OVERFLOW; // Set overflow flag
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Overflow flag should be cleared
         assert_eq!(
@@ -8549,23 +8598,23 @@ mod tests {
 
     #[test]
     fn test_opcode_b9() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDA $1234,Y instruction
-        memory.borrow_mut().write(0x0400, LDA_ABSY, false); // LDA Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, LDA_ABSY, false); // LDA Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + Y ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0x42, false);
+        memory.write(0x1239, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.y = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be loaded with 0x42
         assert_eq!(cpu.state.a, 0x42, "A should be loaded from absolute,Y");
@@ -8577,18 +8626,18 @@ mod tests {
 
     #[test]
     fn test_opcode_ba() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up TSX instruction
-        memory.borrow_mut().write(0x0400, TSX, false); // TSX opcode
+        memory.write(0x0400, TSX, false); // TSX opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.sp = 0xFD;
         cpu.state.x = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X should contain SP
         assert_eq!(cpu.state.x, 0xFD, "X should contain SP");
@@ -8597,17 +8646,17 @@ mod tests {
 
     #[test]
     fn test_opcode_bb() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LAR $1234,Y instruction (illegal opcode, also called LAS)
-        memory.borrow_mut().write(0x0400, LAR_ABSY, false); // LAR Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, LAR_ABSY, false); // LAR Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + Y ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0b1111_0000, false);
+        memory.write(0x1239, 0b1111_0000, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.x = 0x00;
@@ -8615,7 +8664,7 @@ mod tests {
         cpu.state.sp = 0b1010_1010;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // LAR: A = X = SP = (SP AND M)
         // Result: 0b1010_1010 AND 0b1111_0000 = 0b1010_0000
@@ -8630,23 +8679,23 @@ mod tests {
 
     #[test]
     fn test_opcode_bc() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDY $1234,X instruction
-        memory.borrow_mut().write(0x0400, LDY_ABSX, false); // LDY Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, LDY_ABSX, false); // LDY Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + X ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0x42, false);
+        memory.write(0x1239, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0x00;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y should be loaded with 0x42
         assert_eq!(cpu.state.y, 0x42, "Y should be loaded from absolute,X");
@@ -8658,23 +8707,23 @@ mod tests {
 
     #[test]
     fn test_opcode_bd() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDA $1234,X instruction
-        memory.borrow_mut().write(0x0400, LDA_ABSX, false); // LDA Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, LDA_ABSX, false); // LDA Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + X ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0x42, false);
+        memory.write(0x1239, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be loaded with 0x42
         assert_eq!(cpu.state.a, 0x42, "A should be loaded from absolute,X");
@@ -8686,23 +8735,23 @@ mod tests {
 
     #[test]
     fn test_opcode_be() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LDX $1234,Y instruction
-        memory.borrow_mut().write(0x0400, LDX_ABSY, false); // LDX Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, LDX_ABSY, false); // LDX Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + Y ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0x42, false);
+        memory.write(0x1239, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x00;
         cpu.state.y = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X should be loaded with 0x42
         assert_eq!(cpu.state.x, 0x42, "X should be loaded from absolute,Y");
@@ -8714,24 +8763,24 @@ mod tests {
 
     #[test]
     fn test_opcode_bf() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up LAX $1234,Y instruction (illegal opcode)
-        memory.borrow_mut().write(0x0400, LAX_ABSY, false); // LAX Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, LAX_ABSY, false); // LAX Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + Y ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0x42, false);
+        memory.write(0x1239, 0x42, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x00;
         cpu.state.x = 0x00;
         cpu.state.y = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Both A and X should be loaded with 0x42
         assert_eq!(cpu.state.a, 0x42, "A should be loaded from absolute,Y");
@@ -8744,18 +8793,18 @@ mod tests {
 
     #[test]
     fn test_opcode_c0() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CPY #imm instruction
-        memory.borrow_mut().write(0x0400, CPY_IMM, false); // CPY Immediate opcode
-        memory.borrow_mut().write(0x0401, 0x50, false); // Compare with 0x50
+        memory.write(0x0400, CPY_IMM, false); // CPY Immediate opcode
+        memory.write(0x0401, 0x50, false); // Compare with 0x50
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0x50;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y == 0x50: Z=1, C=1, N=0
         assert_eq!(
@@ -8768,26 +8817,26 @@ mod tests {
 
     #[test]
     fn test_opcode_c1() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CMP ($40,X) instruction
-        memory.borrow_mut().write(0x0400, CMP_INDX, false); // CMP Indexed Indirect opcode
-        memory.borrow_mut().write(0x0401, 0x40, false); // Zero page address
+        memory.write(0x0400, CMP_INDX, false); // CMP Indexed Indirect opcode
+        memory.write(0x0401, 0x40, false); // Zero page address
 
         // Set up indirect address at $40 + X ($05) = $45
-        memory.borrow_mut().write(0x0045, 0x00, false); // Low byte of address
-        memory.borrow_mut().write(0x0046, 0x30, false); // High byte of address
+        memory.write(0x0045, 0x00, false); // Low byte of address
+        memory.write(0x0046, 0x30, false); // High byte of address
 
         // Set up value at $3000
-        memory.borrow_mut().write(0x3000, 0x50, false);
+        memory.write(0x3000, 0x50, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A == 0x50: Z=1, C=1, N=0
         assert_eq!(
@@ -8800,18 +8849,18 @@ mod tests {
 
     #[test]
     fn test_opcode_c2() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DOP #imm instruction (illegal NOP)
-        memory.borrow_mut().write(0x0400, DOP_IMM4, false); // DOP Immediate opcode
-        memory.borrow_mut().write(0x0401, 0x42, false); // Dummy operand
+        memory.write(0x0400, DOP_IMM4, false); // DOP Immediate opcode
+        memory.write(0x0401, 0x42, false); // Dummy operand
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x99;
         cpu.state.p = 0xFF;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // DOP does nothing
         assert_eq!(cpu.state.a, 0x99, "A should be unchanged");
@@ -8821,34 +8870,30 @@ mod tests {
 
     #[test]
     fn test_opcode_c3() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DCP ($40,X) instruction (illegal: DEC then CMP)
-        memory.borrow_mut().write(0x0400, DCP_INDX, false); // DCP Indexed Indirect opcode
-        memory.borrow_mut().write(0x0401, 0x40, false); // Zero page address
+        memory.write(0x0400, DCP_INDX, false); // DCP Indexed Indirect opcode
+        memory.write(0x0401, 0x40, false); // Zero page address
 
         // Set up indirect address at $40 + X ($05) = $45
-        memory.borrow_mut().write(0x0045, 0x00, false); // Low byte of address
-        memory.borrow_mut().write(0x0046, 0x30, false); // High byte of address
+        memory.write(0x0045, 0x00, false); // Low byte of address
+        memory.write(0x0046, 0x30, false); // High byte of address
 
         // Set up value at $3000
-        memory.borrow_mut().write(0x3000, 0x51, false);
+        memory.write(0x3000, 0x51, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $3000 should be decremented: 0x51 -> 0x50
         // Then compared with A (0x50): Z=1, C=1, N=0
-        assert_eq!(
-            memory.borrow().read(0x3000),
-            0x50,
-            "Memory should be decremented"
-        );
+        assert_eq!(memory.read(0x3000), 0x50, "Memory should be decremented");
         assert_eq!(
             cpu.state.p & 0b0000_0011,
             0b0000_0011,
@@ -8859,21 +8904,21 @@ mod tests {
 
     #[test]
     fn test_opcode_c4() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CPY $40 instruction
-        memory.borrow_mut().write(0x0400, CPY_ZP, false); // CPY Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x40, false); // Zero page address
+        memory.write(0x0400, CPY_ZP, false); // CPY Zero Page opcode
+        memory.write(0x0401, 0x40, false); // Zero page address
 
         // Set up value at $40
-        memory.borrow_mut().write(0x0040, 0x30, false);
+        memory.write(0x0040, 0x30, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0x50;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y (0x50) > memory (0x30): Z=0, C=1, N=0
         assert_eq!(cpu.state.p & 0b0000_0001, 0b0000_0001, "C should be set");
@@ -8883,21 +8928,21 @@ mod tests {
 
     #[test]
     fn test_opcode_c5() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CMP $40 instruction
-        memory.borrow_mut().write(0x0400, CMP_ZP, false); // CMP Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x40, false); // Zero page address
+        memory.write(0x0400, CMP_ZP, false); // CMP Zero Page opcode
+        memory.write(0x0401, 0x40, false); // Zero page address
 
         // Set up value at $40
-        memory.borrow_mut().write(0x0040, 0x30, false);
+        memory.write(0x0040, 0x30, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A (0x50) > memory (0x30): Z=0, C=1, N=0
         assert_eq!(cpu.state.p & 0b0000_0001, 0b0000_0001, "C should be set");
@@ -8907,56 +8952,48 @@ mod tests {
 
     #[test]
     fn test_opcode_c6() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DEC $40 instruction
-        memory.borrow_mut().write(0x0400, DEC_ZP, false); // DEC Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x40, false); // Zero page address
+        memory.write(0x0400, DEC_ZP, false); // DEC Zero Page opcode
+        memory.write(0x0401, 0x40, false); // Zero page address
 
         // Set up value at $40
-        memory.borrow_mut().write(0x0040, 0x01, false);
+        memory.write(0x0040, 0x01, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be decremented: 0x01 -> 0x00
-        assert_eq!(
-            memory.borrow().read(0x0040),
-            0x00,
-            "Memory should be decremented"
-        );
+        assert_eq!(memory.read(0x0040), 0x00, "Memory should be decremented");
         assert_eq!(cpu.state.p & 0b0000_0010, 0b0000_0010, "Z should be set");
         assert_eq!(cycles, 5, "DEC zero page should take 5 cycles");
     }
 
     #[test]
     fn test_opcode_c7() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DCP $40 instruction (illegal: DEC then CMP)
-        memory.borrow_mut().write(0x0400, DCP_ZP, false); // DCP Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x40, false); // Zero page address
+        memory.write(0x0400, DCP_ZP, false); // DCP Zero Page opcode
+        memory.write(0x0401, 0x40, false); // Zero page address
 
         // Set up value at $40
-        memory.borrow_mut().write(0x0040, 0x51, false);
+        memory.write(0x0040, 0x51, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $40 should be decremented: 0x51 -> 0x50
         // Then compared with A (0x50): Z=1, C=1, N=0
-        assert_eq!(
-            memory.borrow().read(0x0040),
-            0x50,
-            "Memory should be decremented"
-        );
+        assert_eq!(memory.read(0x0040), 0x50, "Memory should be decremented");
         assert_eq!(
             cpu.state.p & 0b0000_0011,
             0b0000_0011,
@@ -8967,17 +9004,17 @@ mod tests {
 
     #[test]
     fn test_opcode_c8() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up INY instruction
-        memory.borrow_mut().write(0x0400, INY, false); // INY Implied opcode
+        memory.write(0x0400, INY, false); // INY Implied opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0xFE;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y should be incremented: 0xFE -> 0xFF
         assert_eq!(cpu.state.y, 0xFF, "Y should be incremented");
@@ -8987,18 +9024,18 @@ mod tests {
 
     #[test]
     fn test_opcode_c9() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CMP #imm instruction
-        memory.borrow_mut().write(0x0400, CMP_IMM, false); // CMP Immediate opcode
-        memory.borrow_mut().write(0x0401, 0x50, false); // Compare with 0x50
+        memory.write(0x0400, CMP_IMM, false); // CMP Immediate opcode
+        memory.write(0x0401, 0x50, false); // Compare with 0x50
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A == 0x50: Z=1, C=1, N=0
         assert_eq!(
@@ -9011,17 +9048,17 @@ mod tests {
 
     #[test]
     fn test_opcode_ca() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DEX instruction
-        memory.borrow_mut().write(0x0400, DEX, false); // DEX Implied opcode
+        memory.write(0x0400, DEX, false); // DEX Implied opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x01;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X should be decremented: 0x01 -> 0x00
         assert_eq!(cpu.state.x, 0x00, "X should be decremented");
@@ -9031,19 +9068,19 @@ mod tests {
 
     #[test]
     fn test_opcode_cb() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up AXS #imm instruction (illegal: (A AND X) - imm -> X without borrow)
-        memory.borrow_mut().write(0x0400, AXS_IMM, false); // AXS Immediate opcode
-        memory.borrow_mut().write(0x0401, 0x10, false); // Subtract 0x10
+        memory.write(0x0400, AXS_IMM, false); // AXS Immediate opcode
+        memory.write(0x0401, 0x10, false); // Subtract 0x10
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0xFF;
         cpu.state.x = 0x50;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // (A AND X) - imm -> X
         // (0xFF AND 0x50) - 0x10 = 0x50 - 0x10 = 0x40
@@ -9058,22 +9095,22 @@ mod tests {
 
     #[test]
     fn test_opcode_cc() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CPY $1234 instruction
-        memory.borrow_mut().write(0x0400, CPY_ABS, false); // CPY Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, CPY_ABS, false); // CPY Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0x30, false);
+        memory.write(0x1234, 0x30, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0x50;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y (0x50) > memory (0x30): Z=0, C=1, N=0
         assert_eq!(cpu.state.p & 0b0000_0001, 0b0000_0001, "C should be set");
@@ -9083,22 +9120,22 @@ mod tests {
 
     #[test]
     fn test_opcode_cd() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CMP $1234 instruction
-        memory.borrow_mut().write(0x0400, CMP_ABS, false); // CMP Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, CMP_ABS, false); // CMP Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0x30, false);
+        memory.write(0x1234, 0x30, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A (0x50) > memory (0x30): Z=0, C=1, N=0
         assert_eq!(cpu.state.p & 0b0000_0001, 0b0000_0001, "C should be set");
@@ -9108,58 +9145,50 @@ mod tests {
 
     #[test]
     fn test_opcode_ce() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DEC $1234 instruction
-        memory.borrow_mut().write(0x0400, DEC_ABS, false); // DEC Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, DEC_ABS, false); // DEC Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0x01, false);
+        memory.write(0x1234, 0x01, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be decremented: 0x01 -> 0x00
-        assert_eq!(
-            memory.borrow().read(0x1234),
-            0x00,
-            "Memory should be decremented"
-        );
+        assert_eq!(memory.read(0x1234), 0x00, "Memory should be decremented");
         assert_eq!(cpu.state.p & 0b0000_0010, 0b0000_0010, "Z should be set");
         assert_eq!(cycles, 6, "DEC absolute should take 6 cycles");
     }
 
     #[test]
     fn test_opcode_cf() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DCP $1234 instruction (illegal: DEC then CMP)
-        memory.borrow_mut().write(0x0400, DCP_ABS, false); // DCP Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, DCP_ABS, false); // DCP Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0x51, false);
+        memory.write(0x1234, 0x51, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1234 should be decremented: 0x51 -> 0x50
         // Then compared with A (0x50): Z=1, C=1, N=0
-        assert_eq!(
-            memory.borrow().read(0x1234),
-            0x50,
-            "Memory should be decremented"
-        );
+        assert_eq!(memory.read(0x1234), 0x50, "Memory should be decremented");
         assert_eq!(
             cpu.state.p & 0b0000_0011,
             0b0000_0011,
@@ -9170,17 +9199,17 @@ mod tests {
 
     #[test]
     fn test_opcode_d0() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up BNE $10 instruction (branch if not equal)
-        memory.borrow_mut().write(0x0400, BNE, false); // BNE opcode
-        memory.borrow_mut().write(0x0401, 0x10, false); // Relative offset (+16)
+        memory.write(0x0400, BNE, false); // BNE opcode
+        memory.write(0x0401, 0x10, false); // Relative offset (+16)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0x00; // Z flag clear (not equal)
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // PC should branch to $0412 (0x0402 + 0x10)
         assert_eq!(cpu.state.pc, 0x0412, "PC should branch to 0x0412");
@@ -9189,26 +9218,26 @@ mod tests {
 
     #[test]
     fn test_opcode_d1() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CMP ($20),Y instruction
-        memory.borrow_mut().write(0x0400, CMP_INDY, false); // CMP Indirect,Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, CMP_INDY, false); // CMP Indirect,Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up pointer at $20-$21 to point to $1230
-        memory.borrow_mut().write(0x0020, 0x30, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x30, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
         // Set up value at $1234 (base $1230 + Y offset $04)
-        memory.borrow_mut().write(0x1234, 0x30, false);
+        memory.write(0x1234, 0x30, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.y = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A (0x50) > memory (0x30): Z=0, C=1, N=0
         assert_eq!(cpu.state.p & 0b0000_0001, 0b0000_0001, "C should be set");
@@ -9218,15 +9247,15 @@ mod tests {
 
     #[test]
     fn test_opcode_d2() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up KIL instruction (illegal opcode that halts CPU)
-        memory.borrow_mut().write(0x0400, KIL11, false); // KIL opcode
+        memory.write(0x0400, KIL11, false); // KIL opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // CPU should be halted
         assert!(cpu.is_halted(), "CPU should be halted");
@@ -9235,34 +9264,30 @@ mod tests {
 
     #[test]
     fn test_opcode_d3() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DCP ($20),Y instruction (illegal: DEC then CMP)
-        memory.borrow_mut().write(0x0400, DCP_INDY, false); // DCP Indirect,Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, DCP_INDY, false); // DCP Indirect,Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up pointer at $20-$21 to point to $1230
-        memory.borrow_mut().write(0x0020, 0x30, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x30, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
         // Set up value at $1234 (base $1230 + Y offset $04)
-        memory.borrow_mut().write(0x1234, 0x51, false);
+        memory.write(0x1234, 0x51, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.y = 0x04;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1234 should be decremented: 0x51 -> 0x50
         // Then compared with A (0x50): Z=1, C=1, N=0
-        assert_eq!(
-            memory.borrow().read(0x1234),
-            0x50,
-            "Memory should be decremented"
-        );
+        assert_eq!(memory.read(0x1234), 0x50, "Memory should be decremented");
         assert_eq!(
             cpu.state.p & 0b0000_0011,
             0b0000_0011,
@@ -9273,20 +9298,20 @@ mod tests {
 
     #[test]
     fn test_opcode_d4() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DOP $20,X instruction (illegal NOP)
-        memory.borrow_mut().write(0x0400, DOP_ZPX5, false); // DOP Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
-        memory.borrow_mut().write(0x0025, 0x42, false); // Value at $25 (will be read but ignored)
+        memory.write(0x0400, DOP_ZPX5, false); // DOP Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0025, 0x42, false); // Value at $25 (will be read but ignored)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05;
         cpu.state.a = 0x99;
         cpu.state.p = 0xFF;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // DOP does nothing
         assert_eq!(cpu.state.a, 0x99, "A should be unchanged");
@@ -9296,20 +9321,20 @@ mod tests {
 
     #[test]
     fn test_opcode_d5() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CMP $20,X instruction
-        memory.borrow_mut().write(0x0400, CMP_ZPX, false); // CMP Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
-        memory.borrow_mut().write(0x0025, 0x30, false); // Value at $25 (base $20 + X offset $05)
+        memory.write(0x0400, CMP_ZPX, false); // CMP Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0025, 0x30, false); // Value at $25 (base $20 + X offset $05)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A (0x50) > memory (0x30): Z=0, C=1, N=0
         assert_eq!(cpu.state.p & 0b0000_0001, 0b0000_0001, "C should be set");
@@ -9319,54 +9344,46 @@ mod tests {
 
     #[test]
     fn test_opcode_d6() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DEC $20,X instruction
-        memory.borrow_mut().write(0x0400, DEC_ZPX, false); // DEC Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
-        memory.borrow_mut().write(0x0025, 0x01, false); // Value at $25 (base $20 + X offset $05)
+        memory.write(0x0400, DEC_ZPX, false); // DEC Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0025, 0x01, false); // Value at $25 (base $20 + X offset $05)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be decremented: 0x01 -> 0x00
-        assert_eq!(
-            memory.borrow().read(0x0025),
-            0x00,
-            "Memory should be decremented"
-        );
+        assert_eq!(memory.read(0x0025), 0x00, "Memory should be decremented");
         assert_eq!(cpu.state.p & 0b0000_0010, 0b0000_0010, "Z should be set");
         assert_eq!(cycles, 6, "DEC zero page,X should take 6 cycles");
     }
 
     #[test]
     fn test_opcode_d7() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DCP $20,X instruction (illegal: DEC then CMP)
-        memory.borrow_mut().write(0x0400, DCP_ZPX, false); // DCP Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
-        memory.borrow_mut().write(0x0025, 0x51, false); // Value at $25 (base $20 + X offset $05)
+        memory.write(0x0400, DCP_ZPX, false); // DCP Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0025, 0x51, false); // Value at $25 (base $20 + X offset $05)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $25 should be decremented: 0x51 -> 0x50
         // Then compared with A (0x50): Z=1, C=1, N=0
-        assert_eq!(
-            memory.borrow().read(0x0025),
-            0x50,
-            "Memory should be decremented"
-        );
+        assert_eq!(memory.read(0x0025), 0x50, "Memory should be decremented");
         assert_eq!(
             cpu.state.p & 0b0000_0011,
             0b0000_0011,
@@ -9377,16 +9394,16 @@ mod tests {
 
     #[test]
     fn test_opcode_d8() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CLD instruction
-        memory.borrow_mut().write(0x0400, CLD, false); // CLD opcode
+        memory.write(0x0400, CLD, false); // CLD opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0xFF; // All flags set
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Decimal flag should be cleared
         assert_eq!(
@@ -9399,23 +9416,23 @@ mod tests {
 
     #[test]
     fn test_opcode_d9() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CMP $1234,Y instruction
-        memory.borrow_mut().write(0x0400, CMP_ABSY, false); // CMP Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, CMP_ABSY, false); // CMP Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + Y ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0x30, false);
+        memory.write(0x1239, 0x30, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.y = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A (0x50) > memory (0x30): Z=0, C=1, N=0
         assert_eq!(cpu.state.p & 0b0000_0001, 0b0000_0001, "C should be set");
@@ -9428,19 +9445,19 @@ mod tests {
 
     #[test]
     fn test_opcode_da() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up NOP instruction (implied)
-        memory.borrow_mut().write(0x0400, NOP_IMP5, false); // NOP opcode
+        memory.write(0x0400, NOP_IMP5, false); // NOP opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x42;
         cpu.state.x = 0x55;
         cpu.state.y = 0x66;
         cpu.state.p = 0x24;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // NOP does nothing - all registers should be unchanged
         assert_eq!(cpu.state.a, 0x42, "A should not change");
@@ -9452,31 +9469,27 @@ mod tests {
 
     #[test]
     fn test_opcode_db() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DCP $1234,Y instruction (illegal: DEC then CMP)
-        memory.borrow_mut().write(0x0400, DCP_ABSY, false); // DCP Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, DCP_ABSY, false); // DCP Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + Y ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0x51, false);
+        memory.write(0x1239, 0x51, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.y = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1239 should be decremented: 0x51 -> 0x50
         // Then compared with A (0x50): Z=1, C=1, N=0
-        assert_eq!(
-            memory.borrow().read(0x1239),
-            0x50,
-            "Memory should be decremented"
-        );
+        assert_eq!(memory.read(0x1239), 0x50, "Memory should be decremented");
         assert_eq!(
             cpu.state.p & 0b0000_0011,
             0b0000_0011,
@@ -9487,21 +9500,21 @@ mod tests {
 
     #[test]
     fn test_opcode_dc() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up TOP $1234,X instruction (illegal NOP that reads absolute,X)
-        memory.borrow_mut().write(0x0400, TOP_ABSX5, false); // TOP Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
-        memory.borrow_mut().write(0x1239, 0x42, false); // Value at $1239 (will be read but ignored)
+        memory.write(0x0400, TOP_ABSX5, false); // TOP Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
+        memory.write(0x1239, 0x42, false); // Value at $1239 (will be read but ignored)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05;
         cpu.state.a = 0x99;
         cpu.state.p = 0xFF;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // TOP does nothing
         assert_eq!(cpu.state.a, 0x99, "A should be unchanged");
@@ -9514,23 +9527,23 @@ mod tests {
 
     #[test]
     fn test_opcode_dd() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CMP $1234,X instruction
-        memory.borrow_mut().write(0x0400, CMP_ABSX, false); // CMP Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, CMP_ABSX, false); // CMP Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + X ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0x30, false);
+        memory.write(0x1239, 0x30, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A (0x50) > memory (0x30): Z=0, C=1, N=0
         assert_eq!(cpu.state.p & 0b0000_0001, 0b0000_0001, "C should be set");
@@ -9543,60 +9556,52 @@ mod tests {
 
     #[test]
     fn test_opcode_de() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DEC $1234,X instruction
-        memory.borrow_mut().write(0x0400, DEC_ABSX, false); // DEC Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, DEC_ABSX, false); // DEC Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + X ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0x01, false);
+        memory.write(0x1239, 0x01, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be decremented: 0x01 -> 0x00
-        assert_eq!(
-            memory.borrow().read(0x1239),
-            0x00,
-            "Memory should be decremented"
-        );
+        assert_eq!(memory.read(0x1239), 0x00, "Memory should be decremented");
         assert_eq!(cpu.state.p & 0b0000_0010, 0b0000_0010, "Z should be set");
         assert_eq!(cycles, 7, "DEC absolute,X should take 7 cycles");
     }
 
     #[test]
     fn test_opcode_df() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DCP $1234,X instruction (illegal: DEC then CMP)
-        memory.borrow_mut().write(0x0400, DCP_ABSX, false); // DCP Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, DCP_ABSX, false); // DCP Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + X ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0x51, false);
+        memory.write(0x1239, 0x51, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1239 should be decremented: 0x51 -> 0x50
         // Then compared with A (0x50): Z=1, C=1, N=0
-        assert_eq!(
-            memory.borrow().read(0x1239),
-            0x50,
-            "Memory should be decremented"
-        );
+        assert_eq!(memory.read(0x1239), 0x50, "Memory should be decremented");
         assert_eq!(
             cpu.state.p & 0b0000_0011,
             0b0000_0011,
@@ -9607,18 +9612,18 @@ mod tests {
 
     #[test]
     fn test_opcode_e0() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CPX #$50 instruction
-        memory.borrow_mut().write(0x0400, CPX_IMM, false); // CPX Immediate opcode
-        memory.borrow_mut().write(0x0401, 0x50, false); // Immediate value
+        memory.write(0x0400, CPX_IMM, false); // CPX Immediate opcode
+        memory.write(0x0401, 0x50, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x60;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X (0x60) > immediate (0x50): Z=0, C=1, N=0
         assert_eq!(cpu.state.p & 0b0000_0001, 0b0000_0001, "C should be set");
@@ -9628,26 +9633,26 @@ mod tests {
 
     #[test]
     fn test_opcode_e1() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SBC ($20,X) instruction
-        memory.borrow_mut().write(0x0400, SBC_INDX, false); // SBC Indexed Indirect opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, SBC_INDX, false); // SBC Indexed Indirect opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
         // Set up pointer at $25 ($20 + X offset $05) pointing to $1234
-        memory.borrow_mut().write(0x0025, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0026, 0x12, false); // High byte
+        memory.write(0x0025, 0x34, false); // Low byte
+        memory.write(0x0026, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0x30, false);
+        memory.write(0x1234, 0x30, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.x = 0x05;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
@@ -9657,18 +9662,18 @@ mod tests {
 
     #[test]
     fn test_opcode_e2() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DOP #$42 instruction (illegal NOP)
-        memory.borrow_mut().write(0x0400, DOP_IMM5, false); // DOP Immediate opcode
-        memory.borrow_mut().write(0x0401, 0x42, false); // Immediate value (will be read but ignored)
+        memory.write(0x0400, DOP_IMM5, false); // DOP Immediate opcode
+        memory.write(0x0401, 0x42, false); // Immediate value (will be read but ignored)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x99;
         cpu.state.p = 0xFF;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // DOP does nothing
         assert_eq!(cpu.state.a, 0x99, "A should be unchanged");
@@ -9678,53 +9683,49 @@ mod tests {
 
     #[test]
     fn test_opcode_e3() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ISB ($20,X) instruction (illegal: INC then SBC)
-        memory.borrow_mut().write(0x0400, ISB_INDX, false); // ISB Indexed Indirect opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, ISB_INDX, false); // ISB Indexed Indirect opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
         // Set up pointer at $25 ($20 + X offset $05) pointing to $1234
-        memory.borrow_mut().write(0x0025, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0026, 0x12, false); // High byte
+        memory.write(0x0025, 0x34, false); // Low byte
+        memory.write(0x0026, 0x12, false); // High byte
 
         // Set up value at $1234
-        memory.borrow_mut().write(0x1234, 0x2F, false);
+        memory.write(0x1234, 0x2F, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.x = 0x05;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1234 should be incremented: 0x2F -> 0x30
         // Then SBC: A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
-        assert_eq!(
-            memory.borrow().read(0x1234),
-            0x30,
-            "Memory should be incremented"
-        );
+        assert_eq!(memory.read(0x1234), 0x30, "Memory should be incremented");
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
         assert_eq!(cycles, 8, "ISB indexed indirect should take 8 cycles");
     }
 
     #[test]
     fn test_opcode_e4() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CPX $20 instruction
-        memory.borrow_mut().write(0x0400, CPX_ZP, false); // CPX Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
-        memory.borrow_mut().write(0x0020, 0x50, false); // Value at $20
+        memory.write(0x0400, CPX_ZP, false); // CPX Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0020, 0x50, false); // Value at $20
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x60;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X (0x60) > memory (0x50): Z=0, C=1, N=0
         assert_eq!(cpu.state.p & 0b0000_0001, 0b0000_0001, "C should be set");
@@ -9734,19 +9735,19 @@ mod tests {
 
     #[test]
     fn test_opcode_e5() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SBC $20 instruction
-        memory.borrow_mut().write(0x0400, SBC_ZP, false); // SBC Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
-        memory.borrow_mut().write(0x0020, 0x30, false); // Value at $20
+        memory.write(0x0400, SBC_ZP, false); // SBC Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0020, 0x30, false); // Value at $20
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
@@ -9756,69 +9757,61 @@ mod tests {
 
     #[test]
     fn test_opcode_e6() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up INC $20 instruction
-        memory.borrow_mut().write(0x0400, INC_ZP, false); // INC Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
-        memory.borrow_mut().write(0x0020, 0xFF, false); // Value at $20
+        memory.write(0x0400, INC_ZP, false); // INC Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0020, 0xFF, false); // Value at $20
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be incremented: 0xFF -> 0x00
-        assert_eq!(
-            memory.borrow().read(0x0020),
-            0x00,
-            "Memory should be incremented"
-        );
+        assert_eq!(memory.read(0x0020), 0x00, "Memory should be incremented");
         assert_eq!(cpu.state.p & FLAG_ZERO, FLAG_ZERO, "Z should be set");
         assert_eq!(cycles, 5, "INC zero page should take 5 cycles");
     }
 
     #[test]
     fn test_opcode_e7() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ISB $20 instruction (illegal: INC then SBC)
-        memory.borrow_mut().write(0x0400, ISB_ZP, false); // ISB Zero Page opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
-        memory.borrow_mut().write(0x0020, 0x2F, false); // Value at $20
+        memory.write(0x0400, ISB_ZP, false); // ISB Zero Page opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0020, 0x2F, false); // Value at $20
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $20 should be incremented: 0x2F -> 0x30
         // Then SBC: A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
-        assert_eq!(
-            memory.borrow().read(0x0020),
-            0x30,
-            "Memory should be incremented"
-        );
+        assert_eq!(memory.read(0x0020), 0x30, "Memory should be incremented");
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
         assert_eq!(cycles, 5, "ISB zero page should take 5 cycles");
     }
 
     #[test]
     fn test_opcode_e8() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up INX instruction
-        memory.borrow_mut().write(0x0400, INX, false); // INX opcode
+        memory.write(0x0400, INX, false); // INX opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0xFF;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X should be incremented: 0xFF -> 0x00
         assert_eq!(cpu.state.x, 0x00, "X should be 0x00");
@@ -9828,18 +9821,18 @@ mod tests {
 
     #[test]
     fn test_opcode_e9() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SBC #$30 instruction
-        memory.borrow_mut().write(0x0400, SBC_IMM, false); // SBC Immediate opcode
-        memory.borrow_mut().write(0x0401, 0x30, false); // Immediate value
+        memory.write(0x0400, SBC_IMM, false); // SBC Immediate opcode
+        memory.write(0x0401, 0x30, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
@@ -9849,19 +9842,19 @@ mod tests {
 
     #[test]
     fn test_opcode_ea() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up NOP instruction
-        memory.borrow_mut().write(0x0400, NOP, false); // NOP opcode
+        memory.write(0x0400, NOP, false); // NOP opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x42;
         cpu.state.x = 0x55;
         cpu.state.y = 0x66;
         cpu.state.p = 0x24;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // NOP does nothing - all registers should be unchanged
         assert_eq!(cpu.state.a, 0x42, "A should not change");
@@ -9873,18 +9866,18 @@ mod tests {
 
     #[test]
     fn test_opcode_eb() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SBC #$30 instruction (illegal duplicate of SBC immediate)
-        memory.borrow_mut().write(0x0400, SBC_IMM2, false); // SBC Immediate opcode (illegal)
-        memory.borrow_mut().write(0x0401, 0x30, false); // Immediate value
+        memory.write(0x0400, SBC_IMM2, false); // SBC Immediate opcode (illegal)
+        memory.write(0x0401, 0x30, false); // Immediate value
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
@@ -9894,20 +9887,20 @@ mod tests {
 
     #[test]
     fn test_opcode_ec() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up CPX $1234 instruction
-        memory.borrow_mut().write(0x0400, CPX_ABS, false); // CPX Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
-        memory.borrow_mut().write(0x1234, 0x50, false); // Value at $1234
+        memory.write(0x0400, CPX_ABS, false); // CPX Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
+        memory.write(0x1234, 0x50, false); // Value at $1234
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x60;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X (0x60) > memory (0x50): Z=0, C=1, N=0
         assert_eq!(cpu.state.p & 0b0000_0001, 0b0000_0001, "C should be set");
@@ -9917,20 +9910,20 @@ mod tests {
 
     #[test]
     fn test_opcode_ed() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SBC $1234 instruction
-        memory.borrow_mut().write(0x0400, SBC_ABS, false); // SBC Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
-        memory.borrow_mut().write(0x1234, 0x30, false); // Value at $1234
+        memory.write(0x0400, SBC_ABS, false); // SBC Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
+        memory.write(0x1234, 0x30, false); // Value at $1234
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
@@ -9940,71 +9933,63 @@ mod tests {
 
     #[test]
     fn test_opcode_ee() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up INC $1234 instruction
-        memory.borrow_mut().write(0x0400, INC_ABS, false); // INC Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
-        memory.borrow_mut().write(0x1234, 0xFF, false); // Value at $1234
+        memory.write(0x0400, INC_ABS, false); // INC Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
+        memory.write(0x1234, 0xFF, false); // Value at $1234
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be incremented: 0xFF -> 0x00
-        assert_eq!(
-            memory.borrow().read(0x1234),
-            0x00,
-            "Memory should be incremented"
-        );
+        assert_eq!(memory.read(0x1234), 0x00, "Memory should be incremented");
         assert_eq!(cpu.state.p & FLAG_ZERO, FLAG_ZERO, "Z should be set");
         assert_eq!(cycles, 6, "INC absolute should take 6 cycles");
     }
 
     #[test]
     fn test_opcode_ef() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ISB $1234 instruction (illegal: INC then SBC)
-        memory.borrow_mut().write(0x0400, ISB_ABS, false); // ISB Absolute opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
-        memory.borrow_mut().write(0x1234, 0x2F, false); // Value at $1234
+        memory.write(0x0400, ISB_ABS, false); // ISB Absolute opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
+        memory.write(0x1234, 0x2F, false); // Value at $1234
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1234 should be incremented: 0x2F -> 0x30
         // Then SBC: A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
-        assert_eq!(
-            memory.borrow().read(0x1234),
-            0x30,
-            "Memory should be incremented"
-        );
+        assert_eq!(memory.read(0x1234), 0x30, "Memory should be incremented");
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
         assert_eq!(cycles, 6, "ISB absolute should take 6 cycles");
     }
 
     #[test]
     fn test_opcode_f0() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up BEQ $10 instruction (branch if equal)
-        memory.borrow_mut().write(0x0400, BEQ, false); // BEQ opcode
-        memory.borrow_mut().write(0x0401, 0x10, false); // Relative offset (+16)
+        memory.write(0x0400, BEQ, false); // BEQ opcode
+        memory.write(0x0401, 0x10, false); // Relative offset (+16)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = FLAG_ZERO; // Z flag set (equal)
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // PC should branch to $0412 (0x0402 + 0x10)
         assert_eq!(cpu.state.pc, 0x0412, "PC should branch to 0x0412");
@@ -10013,26 +9998,26 @@ mod tests {
 
     #[test]
     fn test_opcode_f1() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SBC ($20),Y instruction
-        memory.borrow_mut().write(0x0400, SBC_INDY, false); // SBC Indirect,Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, SBC_INDY, false); // SBC Indirect,Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up pointer at $20-$21 to point to $1230
-        memory.borrow_mut().write(0x0020, 0x30, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x30, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
         // Set up value at $1234 (base $1230 + Y offset $04)
-        memory.borrow_mut().write(0x1234, 0x30, false);
+        memory.write(0x1234, 0x30, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.y = 0x04;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
@@ -10042,15 +10027,15 @@ mod tests {
 
     #[test]
     fn test_opcode_f2() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up KIL instruction (illegal opcode that halts CPU)
-        memory.borrow_mut().write(0x0400, KIL12, false); // KIL opcode
+        memory.write(0x0400, KIL12, false); // KIL opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // CPU should be halted
         assert!(cpu.is_halted(), "CPU should be halted");
@@ -10059,54 +10044,50 @@ mod tests {
 
     #[test]
     fn test_opcode_f3() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ISB ($20),Y instruction (illegal: INC then SBC)
-        memory.borrow_mut().write(0x0400, ISB_INDY, false); // ISB Indirect,Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, ISB_INDY, false); // ISB Indirect,Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up pointer at $20-$21 to point to $1230
-        memory.borrow_mut().write(0x0020, 0x30, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x30, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
         // Set up value at $1234 (base $1230 + Y offset $04)
-        memory.borrow_mut().write(0x1234, 0x2F, false);
+        memory.write(0x1234, 0x2F, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.y = 0x04;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1234 should be incremented: 0x2F -> 0x30
         // Then SBC: A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
-        assert_eq!(
-            memory.borrow().read(0x1234),
-            0x30,
-            "Memory should be incremented"
-        );
+        assert_eq!(memory.read(0x1234), 0x30, "Memory should be incremented");
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
         assert_eq!(cycles, 8, "ISB (Indirect),Y should take 8 cycles");
     }
 
     #[test]
     fn test_opcode_f4() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up DOP $20,X instruction (illegal NOP)
-        memory.borrow_mut().write(0x0400, DOP_ZPX6, false); // DOP Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
-        memory.borrow_mut().write(0x0025, 0x42, false); // Value at $25 (will be read but ignored)
+        memory.write(0x0400, DOP_ZPX6, false); // DOP Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0025, 0x42, false); // Value at $25 (will be read but ignored)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05;
         cpu.state.a = 0x99;
         cpu.state.p = 0xFF;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // DOP does nothing
         assert_eq!(cpu.state.a, 0x99, "A should be unchanged");
@@ -10116,20 +10097,20 @@ mod tests {
 
     #[test]
     fn test_opcode_f5() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SBC $20,X instruction
-        memory.borrow_mut().write(0x0400, SBC_ZPX, false); // SBC Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
-        memory.borrow_mut().write(0x0025, 0x30, false); // Value at $25 (base $20 + X offset $05)
+        memory.write(0x0400, SBC_ZPX, false); // SBC Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0025, 0x30, false); // Value at $25 (base $20 + X offset $05)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.x = 0x05;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
@@ -10139,70 +10120,62 @@ mod tests {
 
     #[test]
     fn test_opcode_f6() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up INC $20,X instruction
-        memory.borrow_mut().write(0x0400, INC_ZPX, false); // INC Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
-        memory.borrow_mut().write(0x0025, 0xFF, false); // Value at $25 (base $20 + X offset $05)
+        memory.write(0x0400, INC_ZPX, false); // INC Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0025, 0xFF, false); // Value at $25 (base $20 + X offset $05)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be incremented: 0xFF -> 0x00
-        assert_eq!(
-            memory.borrow().read(0x0025),
-            0x00,
-            "Memory should be incremented"
-        );
+        assert_eq!(memory.read(0x0025), 0x00, "Memory should be incremented");
         assert_eq!(cpu.state.p & FLAG_ZERO, FLAG_ZERO, "Z should be set");
         assert_eq!(cycles, 6, "INC zero page,X should take 6 cycles");
     }
 
     #[test]
     fn test_opcode_f7() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ISB $20,X instruction (illegal: INC then SBC)
-        memory.borrow_mut().write(0x0400, ISB_ZPX, false); // ISB Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
-        memory.borrow_mut().write(0x0025, 0x2F, false); // Value at $25 (base $20 + X offset $05)
+        memory.write(0x0400, ISB_ZPX, false); // ISB Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0025, 0x2F, false); // Value at $25 (base $20 + X offset $05)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.x = 0x05;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $25 should be incremented: 0x2F -> 0x30
         // Then SBC: A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
-        assert_eq!(
-            memory.borrow().read(0x0025),
-            0x30,
-            "Memory should be incremented"
-        );
+        assert_eq!(memory.read(0x0025), 0x30, "Memory should be incremented");
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
         assert_eq!(cycles, 6, "ISB zero page,X should take 6 cycles");
     }
 
     #[test]
     fn test_opcode_f8() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SED instruction
-        memory.borrow_mut().write(0x0400, SED, false); // SED opcode
+        memory.write(0x0400, SED, false); // SED opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0x00; // All flags clear
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Decimal flag should be set
         assert_eq!(
@@ -10215,23 +10188,23 @@ mod tests {
 
     #[test]
     fn test_opcode_f9() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SBC $1234,Y instruction
-        memory.borrow_mut().write(0x0400, SBC_ABSY, false); // SBC Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, SBC_ABSY, false); // SBC Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + Y ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0x30, false);
+        memory.write(0x1239, 0x30, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.y = 0x05;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
@@ -10244,19 +10217,19 @@ mod tests {
 
     #[test]
     fn test_opcode_fa() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up NOP instruction (implied)
-        memory.borrow_mut().write(0x0400, NOP_IMP6, false); // NOP opcode
+        memory.write(0x0400, NOP_IMP6, false); // NOP opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x42;
         cpu.state.x = 0x55;
         cpu.state.y = 0x66;
         cpu.state.p = 0x24;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // NOP does nothing - all registers should be unchanged
         assert_eq!(cpu.state.a, 0x42, "A should not change");
@@ -10268,52 +10241,48 @@ mod tests {
 
     #[test]
     fn test_opcode_fb() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ISB $1234,Y instruction (illegal: INC then SBC)
-        memory.borrow_mut().write(0x0400, ISB_ABSY, false); // ISB Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, ISB_ABSY, false); // ISB Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + Y ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0x2F, false);
+        memory.write(0x1239, 0x2F, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.y = 0x05;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1239 should be incremented: 0x2F -> 0x30
         // Then SBC: A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
-        assert_eq!(
-            memory.borrow().read(0x1239),
-            0x30,
-            "Memory should be incremented"
-        );
+        assert_eq!(memory.read(0x1239), 0x30, "Memory should be incremented");
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
         assert_eq!(cycles, 7, "ISB absolute,Y should take 7 cycles");
     }
 
     #[test]
     fn test_opcode_fc() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up TOP $1234,X instruction (illegal NOP that reads absolute,X)
-        memory.borrow_mut().write(0x0400, TOP_ABSX6, false); // TOP Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
-        memory.borrow_mut().write(0x1239, 0x42, false); // Value at $1239 (will be read but ignored)
+        memory.write(0x0400, TOP_ABSX6, false); // TOP Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
+        memory.write(0x1239, 0x42, false); // Value at $1239 (will be read but ignored)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05;
         cpu.state.a = 0x99;
         cpu.state.p = 0xFF;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // TOP does nothing
         assert_eq!(cpu.state.a, 0x99, "A should be unchanged");
@@ -10326,23 +10295,23 @@ mod tests {
 
     #[test]
     fn test_opcode_fd() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SBC $1234,X instruction
-        memory.borrow_mut().write(0x0400, SBC_ABSX, false); // SBC Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, SBC_ABSX, false); // SBC Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + X ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0x30, false);
+        memory.write(0x1239, 0x30, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.x = 0x05;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
@@ -10355,77 +10324,69 @@ mod tests {
 
     #[test]
     fn test_opcode_fe() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up INC $1234,X instruction
-        memory.borrow_mut().write(0x0400, INC_ABSX, false); // INC Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, INC_ABSX, false); // INC Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + X ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0xFF, false);
+        memory.write(0x1239, 0xFF, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x05;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory should be incremented: 0xFF -> 0x00
-        assert_eq!(
-            memory.borrow().read(0x1239),
-            0x00,
-            "Memory should be incremented"
-        );
+        assert_eq!(memory.read(0x1239), 0x00, "Memory should be incremented");
         assert_eq!(cpu.state.p & FLAG_ZERO, FLAG_ZERO, "Z should be set");
         assert_eq!(cycles, 7, "INC absolute,X should take 7 cycles");
     }
 
     #[test]
     fn test_opcode_ff() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up ISB $1234,X instruction (illegal: INC then SBC)
-        memory.borrow_mut().write(0x0400, ISB_ABSX, false); // ISB Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, ISB_ABSX, false); // ISB Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
         // Set up value at $1234 + X ($05) = $1239
-        memory.borrow_mut().write(0x1239, 0x2F, false);
+        memory.write(0x1239, 0x2F, false);
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x50;
         cpu.state.x = 0x05;
         cpu.state.p = FLAG_CARRY; // Set carry for SBC
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Memory at $1239 should be incremented: 0x2F -> 0x30
         // Then SBC: A = 0x50 - 0x30 - (1 - C) = 0x50 - 0x30 - 0 = 0x20
-        assert_eq!(
-            memory.borrow().read(0x1239),
-            0x30,
-            "Memory should be incremented"
-        );
+        assert_eq!(memory.read(0x1239), 0x30, "Memory should be incremented");
         assert_eq!(cpu.state.a, 0x20, "A should be 0x20");
         assert_eq!(cycles, 7, "ISB absolute,X should take 7 cycles");
     }
 
     #[test]
     fn test_opcode_90() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up BCC instruction (Branch if Carry Clear)
-        memory.borrow_mut().write(0x0400, BCC, false); // BCC opcode
-        memory.borrow_mut().write(0x0401, 0x10, false); // Relative offset (+16)
+        memory.write(0x0400, BCC, false); // BCC opcode
+        memory.write(0x0401, 0x10, false); // Relative offset (+16)
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.p = 0; // C flag clear
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Branch should be taken to 0x0412 (0x0402 + 0x10)
         assert_eq!(cpu.state.pc, 0x0412, "PC should branch to 0x0412");
@@ -10434,39 +10395,39 @@ mod tests {
 
     #[test]
     fn test_opcode_91() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STA ($20),Y instruction
-        memory.borrow_mut().write(0x0400, STA_INDY, false); // STA (Indirect),Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, STA_INDY, false); // STA (Indirect),Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up pointer at $20-$21 to point to $1230
-        memory.borrow_mut().write(0x0020, 0x30, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x30, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x42;
         cpu.state.y = 0x04;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be stored at $1234 (base $1230 + Y offset $04)
-        assert_eq!(memory.borrow().read(0x1234), 0x42, "A should be stored");
+        assert_eq!(memory.read(0x1234), 0x42, "A should be stored");
         assert_eq!(cycles, 6, "STA (Indirect),Y should take 6 cycles");
     }
 
     #[test]
     fn test_opcode_92() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up KIL instruction (illegal opcode that halts CPU)
-        memory.borrow_mut().write(0x0400, KIL9, false); // KIL opcode
+        memory.write(0x0400, KIL9, false); // KIL opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // CPU should be halted
         assert!(cpu.is_halted(), "CPU should be halted");
@@ -10475,129 +10436,125 @@ mod tests {
 
     #[test]
     fn test_opcode_93() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up AXA ($20),Y instruction (illegal: stores A & X & (H+1))
-        memory.borrow_mut().write(0x0400, AXA_INDY, false); // AXA (Indirect),Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page address
+        memory.write(0x0400, AXA_INDY, false); // AXA (Indirect),Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page address
 
         // Set up pointer at $20-$21 to point to $1230
-        memory.borrow_mut().write(0x0020, 0x30, false); // Low byte
-        memory.borrow_mut().write(0x0021, 0x12, false); // High byte
+        memory.write(0x0020, 0x30, false); // Low byte
+        memory.write(0x0021, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0xFF;
         cpu.state.x = 0xFF;
         cpu.state.y = 0x04;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // AXA stores A & X & (high_byte + 1)
         // Target is $1234, high byte is 0x12, so stores 0xFF & 0xFF & 0x13 = 0x13
-        assert_eq!(
-            memory.borrow().read(0x1234),
-            0x13,
-            "AXA result should be stored"
-        );
+        assert_eq!(memory.read(0x1234), 0x13, "AXA result should be stored");
         assert_eq!(cycles, 6, "AXA (Indirect),Y should take 6 cycles");
     }
 
     #[test]
     fn test_opcode_94() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STY $20,X instruction
-        memory.borrow_mut().write(0x0400, STY_ZPX, false); // STY Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, STY_ZPX, false); // STY Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0x42;
         cpu.state.x = 0x05;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y should be stored at $25 (base $20 + X offset $05)
-        assert_eq!(memory.borrow().read(0x0025), 0x42, "Y should be stored");
+        assert_eq!(memory.read(0x0025), 0x42, "Y should be stored");
         assert_eq!(cycles, 4, "STY zero page,X should take 4 cycles");
     }
 
     #[test]
     fn test_opcode_95() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STA $20,X instruction
-        memory.borrow_mut().write(0x0400, STA_ZPX, false); // STA Zero Page,X opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, STA_ZPX, false); // STA Zero Page,X opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x42;
         cpu.state.x = 0x05;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be stored at $25 (base $20 + X offset $05)
-        assert_eq!(memory.borrow().read(0x0025), 0x42, "A should be stored");
+        assert_eq!(memory.read(0x0025), 0x42, "A should be stored");
         assert_eq!(cycles, 4, "STA zero page,X should take 4 cycles");
     }
 
     #[test]
     fn test_opcode_96() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STX $20,Y instruction
-        memory.borrow_mut().write(0x0400, STX_ZPY, false); // STX Zero Page,Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, STX_ZPY, false); // STX Zero Page,Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x42;
         cpu.state.y = 0x05;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X should be stored at $25 (base $20 + Y offset $05)
-        assert_eq!(memory.borrow().read(0x0025), 0x42, "X should be stored");
+        assert_eq!(memory.read(0x0025), 0x42, "X should be stored");
         assert_eq!(cycles, 4, "STX zero page,Y should take 4 cycles");
     }
 
     #[test]
     fn test_opcode_97() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SAX $20,Y instruction (illegal: stores A & X)
-        memory.borrow_mut().write(0x0400, SAX_ZPY, false); // SAX Zero Page,Y opcode
-        memory.borrow_mut().write(0x0401, 0x20, false); // Zero page base address
+        memory.write(0x0400, SAX_ZPY, false); // SAX Zero Page,Y opcode
+        memory.write(0x0401, 0x20, false); // Zero page base address
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0b11110000;
         cpu.state.x = 0b10101010;
         cpu.state.y = 0x05;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A & X = 0b10100000 = 0xA0 should be stored at $25
-        assert_eq!(memory.borrow().read(0x0025), 0xA0, "A & X should be stored");
+        assert_eq!(memory.read(0x0025), 0xA0, "A & X should be stored");
         assert_eq!(cycles, 4, "SAX zero page,Y should take 4 cycles");
     }
 
     #[test]
     fn test_opcode_98() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up TYA instruction (Transfer Y to A)
-        memory.borrow_mut().write(0x0400, TYA, false); // TYA opcode
+        memory.write(0x0400, TYA, false); // TYA opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0x42;
         cpu.state.a = 0x00;
         cpu.state.p = 0;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Y should be transferred to A
         assert_eq!(cpu.state.a, 0x42, "A should equal Y");
@@ -10608,38 +10565,38 @@ mod tests {
 
     #[test]
     fn test_opcode_99() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STA $1234,Y instruction
-        memory.borrow_mut().write(0x0400, STA_ABSY, false); // STA Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, STA_ABSY, false); // STA Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x42;
         cpu.state.y = 0x05;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be stored at $1239 (base $1234 + Y offset $05)
-        assert_eq!(memory.borrow().read(0x1239), 0x42, "A should be stored");
+        assert_eq!(memory.read(0x1239), 0x42, "A should be stored");
         assert_eq!(cycles, 5, "STA absolute,Y should take 5 cycles");
     }
 
     #[test]
     fn test_opcode_9a() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up TXS instruction (Transfer X to SP)
-        memory.borrow_mut().write(0x0400, TXS, false); // TXS opcode
+        memory.write(0x0400, TXS, false); // TXS opcode
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0x42;
         cpu.state.sp = 0xFF;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // X should be transferred to SP
         assert_eq!(cpu.state.sp, 0x42, "SP should equal X");
@@ -10648,127 +10605,111 @@ mod tests {
 
     #[test]
     fn test_opcode_9b() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up XAS $1234,Y instruction (illegal: SP = A & X, stores SP & (H+1))
-        memory.borrow_mut().write(0x0400, XAS_ABSY, false); // XAS Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, XAS_ABSY, false); // XAS Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0xFF;
         cpu.state.x = 0xFF;
         cpu.state.y = 0x05;
         cpu.state.sp = 0x00;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // SP should be set to A & X = 0xFF
         assert_eq!(cpu.state.sp, 0xFF, "SP should be A & X");
         // Stores SP & (high_byte + 1) = 0xFF & 0x13 = 0x13 at $1239
-        assert_eq!(
-            memory.borrow().read(0x1239),
-            0x13,
-            "XAS result should be stored"
-        );
+        assert_eq!(memory.read(0x1239), 0x13, "XAS result should be stored");
         assert_eq!(cycles, 5, "XAS absolute,Y should take 5 cycles");
     }
 
     #[test]
     fn test_opcode_9c() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SYA $1234,X instruction (illegal: stores Y & (H+1))
-        memory.borrow_mut().write(0x0400, SYA_ABSX, false); // SYA Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, SYA_ABSX, false); // SYA Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.y = 0xFF;
         cpu.state.x = 0x05;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Stores Y & (high_byte + 1) = 0xFF & 0x13 = 0x13 at $1239
-        assert_eq!(
-            memory.borrow().read(0x1239),
-            0x13,
-            "SYA result should be stored"
-        );
+        assert_eq!(memory.read(0x1239), 0x13, "SYA result should be stored");
         assert_eq!(cycles, 5, "SYA absolute,X should take 5 cycles");
     }
 
     #[test]
     fn test_opcode_9d() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up STA $1234,X instruction
-        memory.borrow_mut().write(0x0400, STA_ABSX, false); // STA Absolute,X opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, STA_ABSX, false); // STA Absolute,X opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0x42;
         cpu.state.x = 0x05;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // A should be stored at $1239 (base $1234 + X offset $05)
-        assert_eq!(memory.borrow().read(0x1239), 0x42, "A should be stored");
+        assert_eq!(memory.read(0x1239), 0x42, "A should be stored");
         assert_eq!(cycles, 5, "STA absolute,X should take 5 cycles");
     }
 
     #[test]
     fn test_opcode_9e() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up SXA $1234,Y instruction (illegal: stores X & (H+1))
-        memory.borrow_mut().write(0x0400, SXA_ABSY, false); // SXA Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, SXA_ABSY, false); // SXA Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.x = 0xFF;
         cpu.state.y = 0x05;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Stores X & (high_byte + 1) = 0xFF & 0x13 = 0x13 at $1239
-        assert_eq!(
-            memory.borrow().read(0x1239),
-            0x13,
-            "SXA result should be stored"
-        );
+        assert_eq!(memory.read(0x1239), 0x13, "SXA result should be stored");
         assert_eq!(cycles, 5, "SXA absolute,Y should take 5 cycles");
     }
 
     #[test]
     fn test_opcode_9f() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Set up AXA $1234,Y instruction (illegal: stores A & X & (H+1))
-        memory.borrow_mut().write(0x0400, AXA_ABSY, false); // AXA Absolute,Y opcode
-        memory.borrow_mut().write(0x0401, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0402, 0x12, false); // High byte
+        memory.write(0x0400, AXA_ABSY, false); // AXA Absolute,Y opcode
+        memory.write(0x0401, 0x34, false); // Low byte
+        memory.write(0x0402, 0x12, false); // High byte
 
-        let mut cpu = Cpu2::new(Rc::clone(&memory));
+        let mut cpu = Cpu2::new();
         cpu.state.pc = 0x0400;
         cpu.state.a = 0xFF;
         cpu.state.x = 0xFF;
         cpu.state.y = 0x05;
 
-        let cycles = execute_instruction(&mut cpu);
+        let cycles = execute_instruction(&mut cpu, &mut memory);
 
         // Stores A & X & (high_byte + 1) = 0xFF & 0xFF & 0x13 = 0x13 at $1239
-        assert_eq!(
-            memory.borrow().read(0x1239),
-            0x13,
-            "AXA result should be stored"
-        );
+        assert_eq!(memory.read(0x1239), 0x13, "AXA result should be stored");
         assert_eq!(cycles, 5, "AXA absolute,Y should take 5 cycles");
     }
 }
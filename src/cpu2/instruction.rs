@@ -6,8 +6,6 @@
 use super::traits::{AddressingMode, InstructionType};
 use super::types::CpuState;
 use crate::mem_controller::MemController;
-use std::cell::RefCell;
-use std::rc::Rc;
 
 /// Instruction struct that combines an addressing mode with an instruction type
 ///
@@ -36,7 +34,7 @@ impl Instruction {
     /// 1. First, ticks the addressing mode until it's done
     /// 2. Then, ticks the instruction type until it's done. Note that first tick is done on the same cycle
     /// as the final addressing mode tick
-    pub fn tick(&mut self, cpu_state: &mut CpuState, memory: Rc<RefCell<MemController>>) {
+    pub fn tick(&mut self, cpu_state: &mut CpuState, memory: &mut MemController) {
         debug_assert!(
             !self.is_done(),
             "Instruction::tick called when instruction is already done"
@@ -44,7 +42,7 @@ impl Instruction {
 
         // First, complete the addressing mode if not done
         if !self.addressing_mode.is_done() {
-            self.addressing_mode.tick(cpu_state, Rc::clone(&memory));
+            self.addressing_mode.tick(cpu_state, memory);
         }
         // Once addressing is done, execute the instruction
         // Note that the first tick is done on the same cycle as the final addressing mode tick
@@ -9,8 +9,6 @@ use super::types::{
     FLAG_ZERO, IRQ_VECTOR, NMI_VECTOR,
 };
 use crate::mem_controller::MemController;
-use std::cell::RefCell;
-use std::rc::Rc;
 
 /// Helper function to set or clear the Zero flag based on a value
 #[inline]
@@ -69,7 +67,7 @@ impl InstructionType for Aac {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Aac::tick called after already done");
@@ -124,7 +122,7 @@ impl InstructionType for Top {
     fn tick(
         &mut self,
         _cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Top::tick called after already done");
@@ -157,6 +155,8 @@ pub struct Bpl {
     cycle: u8,
     branch_taken: bool,
     page_crossed: bool,
+    /// Address of the next instruction, had the branch not been taken
+    next_pc: u16,
 }
 
 impl Bpl {
@@ -176,7 +176,7 @@ impl InstructionType for Bpl {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(!self.is_done(), "Bpl::tick called after already done");
@@ -197,16 +197,21 @@ impl InstructionType for Bpl {
                     self.page_crossed = current_page != target_page;
 
                     // Update PC to target
+                    self.next_pc = cpu_state.pc;
                     cpu_state.pc = target;
                     self.cycle = 1;
                 }
             }
             1 => {
                 // Cycle 2: Extra cycle for branch taken (same page)
+                // Read the next opcode (throw away) while adding the offset to PCL
+                memory.read(self.next_pc);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Extra cycle for page crossing
+                // Read from the target before PCH is fixed (throw away)
+                memory.read((self.next_pc & 0xFF00) | (cpu_state.pc & 0x00FF));
                 self.cycle = 3;
             }
             _ => unreachable!(),
@@ -243,13 +248,16 @@ impl InstructionType for Clc {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Clc::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Clear carry flag
                 cpu_state.p &= !super::types::FLAG_CARRY;
                 self.cycle = 1;
@@ -288,13 +296,16 @@ impl InstructionType for Sec {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Sec::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Set carry flag
                 cpu_state.p |= super::types::FLAG_CARRY;
                 self.cycle = 1;
@@ -321,6 +332,8 @@ pub struct Bmi {
     cycle: u8,
     branch_taken: bool,
     page_crossed: bool,
+    /// Address of the next instruction, had the branch not been taken
+    next_pc: u16,
 }
 
 impl Bmi {
@@ -340,7 +353,7 @@ impl InstructionType for Bmi {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(!self.is_done(), "Bmi::tick called after already done");
@@ -361,16 +374,21 @@ impl InstructionType for Bmi {
                     self.page_crossed = current_page != target_page;
 
                     // Update PC to target
+                    self.next_pc = cpu_state.pc;
                     cpu_state.pc = target;
                     self.cycle = 1;
                 }
             }
             1 => {
                 // Cycle 2: Extra cycle for branch taken (same page)
+                // Read the next opcode (throw away) while adding the offset to PCL
+                memory.read(self.next_pc);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Extra cycle for page crossing
+                // Read from the target before PCH is fixed (throw away)
+                memory.read((self.next_pc & 0xFF00) | (cpu_state.pc & 0x00FF));
                 self.cycle = 3;
             }
             _ => unreachable!(),
@@ -395,6 +413,8 @@ pub struct Bne {
     cycle: u8,
     branch_taken: bool,
     page_crossed: bool,
+    /// Address of the next instruction, had the branch not been taken
+    next_pc: u16,
 }
 
 impl Bne {
@@ -414,7 +434,7 @@ impl InstructionType for Bne {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(!self.is_done(), "Bne::tick called after already done");
@@ -435,16 +455,21 @@ impl InstructionType for Bne {
                     self.page_crossed = current_page != target_page;
 
                     // Update PC to target
+                    self.next_pc = cpu_state.pc;
                     cpu_state.pc = target;
                     self.cycle = 1;
                 }
             }
             1 => {
                 // Cycle 2: Extra cycle for branch taken (same page)
+                // Read the next opcode (throw away) while adding the offset to PCL
+                memory.read(self.next_pc);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Extra cycle for page crossing
+                // Read from the target before PCH is fixed (throw away)
+                memory.read((self.next_pc & 0xFF00) | (cpu_state.pc & 0x00FF));
                 self.cycle = 3;
             }
             _ => unreachable!(),
@@ -481,14 +506,17 @@ impl InstructionType for Nop {
 
     fn tick(
         &mut self,
-        _cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        cpu_state: &mut CpuState,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Nop::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Do nothing
                 self.cycle = 1;
             }
@@ -531,7 +559,7 @@ impl InstructionType for Jsr {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 6, "Jsr::tick called after already done");
@@ -539,13 +567,14 @@ impl InstructionType for Jsr {
         match self.cycle {
             0 => {
                 // Cycle 1: Fetch low byte of target address from PC
-                let low = memory.borrow().read(cpu_state.pc);
+                let low = memory.read(cpu_state.pc);
                 self.target_address = low as u16;
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
                 self.cycle = 1;
             }
             1 => {
-                // Cycle 2: Internal operation (prepare for stack operations)
+                // Cycle 2: Internal operation (dummy read of the stack)
+                memory.read(0x0100 | (cpu_state.sp as u16));
                 self.cycle = 2;
             }
             2 => {
@@ -554,7 +583,7 @@ impl InstructionType for Jsr {
                 let return_address = cpu_state.pc;
                 let pch = (return_address >> 8) as u8;
                 let stack_addr = 0x0100 | (cpu_state.sp as u16);
-                memory.borrow_mut().write(stack_addr, pch, false);
+                memory.write(stack_addr, pch, false);
                 cpu_state.sp = cpu_state.sp.wrapping_sub(1);
                 self.cycle = 3;
             }
@@ -563,13 +592,13 @@ impl InstructionType for Jsr {
                 let return_address = cpu_state.pc;
                 let pcl = (return_address & 0xFF) as u8;
                 let stack_addr = 0x0100 | (cpu_state.sp as u16);
-                memory.borrow_mut().write(stack_addr, pcl, false);
+                memory.write(stack_addr, pcl, false);
                 cpu_state.sp = cpu_state.sp.wrapping_sub(1);
                 self.cycle = 4;
             }
             4 => {
                 // Cycle 5: Fetch high byte of target address from PC and jump
-                let high = memory.borrow().read(cpu_state.pc);
+                let high = memory.read(cpu_state.pc);
                 self.target_address |= (high as u16) << 8;
                 cpu_state.pc = self.target_address;
                 self.cycle = 5;
@@ -605,7 +634,7 @@ impl InstructionType for Jmp {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Jmp::tick called after already done");
@@ -631,21 +660,22 @@ impl InstructionType for Jmp {
 /// - If IRQ is pending (and I flag clear): Uses IRQ vector ($FFFE) and clears B flag
 /// - Otherwise: Normal BRK uses IRQ vector ($FFFE) with B flag SET
 ///
-/// Priority: NMI > IRQ. Vector determination occurs at cycle 6 (marked *** in 6502 docs).
+/// Priority: NMI > IRQ. The vector is chosen when the status is pushed in cycle 5, so
+/// an interrupt must be pending by the end of cycle 4 to hijack BRK.
 ///
 /// Total cycles: 7 (opcode fetch + 5 execution cycles + completion cycle)
 ///   1. Opcode fetch (handled by CPU)
 ///   2. Fetch next byte (padding byte, ignored)
 ///   3. Push PCH (high byte of PC+2) to stack
 ///   4. Push PCL (low byte of PC+2) to stack
-///   5. Push status register with B flag set to stack
-///   6. Check for hijacking, load PCL from chosen vector, set I flag
+///   5. Check for hijacking, push status register to stack
+///   6. Load PCL from chosen vector, set I flag
 ///   7. Load PCH from chosen vector (completion handled by CPU)
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Brk {
     cycle: u8,
     return_address: u16,
-    vector: u16, // Store which interrupt vector to use (determined at cycle 4)
+    vector: u16, // Store which interrupt vector to use (determined at cycle 5)
 }
 
 impl Brk {
@@ -663,7 +693,7 @@ impl InstructionType for Brk {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 6, "Brk::tick called after already done");
@@ -671,7 +701,7 @@ impl InstructionType for Brk {
         match self.cycle {
             0 => {
                 // Cycle 2: Fetch padding byte (ignored) and calculate return address (PC+2)
-                let _padding = memory.borrow().read(cpu_state.pc);
+                let _padding = memory.read(cpu_state.pc);
                 self.return_address = cpu_state.pc.wrapping_add(1); // PC+2 after opcode+padding
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
                 self.cycle = 1;
@@ -680,7 +710,7 @@ impl InstructionType for Brk {
                 // Cycle 3: Push PCH (high byte of return address) to stack
                 let pch = (self.return_address >> 8) as u8;
                 let stack_addr = 0x0100 | (cpu_state.sp as u16);
-                memory.borrow_mut().write(stack_addr, pch, false);
+                memory.write(stack_addr, pch, false);
                 cpu_state.sp = cpu_state.sp.wrapping_sub(1);
                 self.cycle = 2;
             }
@@ -688,21 +718,14 @@ impl InstructionType for Brk {
                 // Cycle 4: Push PCL (low byte of return address) to stack
                 let pcl = (self.return_address & 0xFF) as u8;
                 let stack_addr = 0x0100 | (cpu_state.sp as u16);
-                memory.borrow_mut().write(stack_addr, pcl, false);
+                memory.write(stack_addr, pcl, false);
                 cpu_state.sp = cpu_state.sp.wrapping_sub(1);
                 self.cycle = 3;
             }
             3 => {
-                // Cycle 5: Push status register with B and unused flags set
-                let status = cpu_state.p | FLAG_BREAK | FLAG_UNUSED;
-                let stack_addr = 0x0100 | (cpu_state.sp as u16);
-                memory.borrow_mut().write(stack_addr, status, false);
-                cpu_state.sp = cpu_state.sp.wrapping_sub(1);
-                self.cycle = 4;
-            }
-            4 => {
-                // Cycle 6: Determine interrupt vector and load PCL
+                // Cycle 5: Determine interrupt vector and push status register
                 // Check for interrupt hijacking (NMI has priority over IRQ)
+                let mut status = cpu_state.p | FLAG_BREAK | FLAG_UNUSED;
                 self.vector = if cpu_state.nmi_pending {
                     // NMI hijacks BRK: use NMI vector, keep B flag SET (in pushed status)
                     cpu_state.nmi_pending = false;
@@ -710,27 +733,28 @@ impl InstructionType for Brk {
                 } else if cpu_state.irq_pending && (cpu_state.p & FLAG_INTERRUPT) == 0 {
                     // IRQ hijacks BRK: use IRQ vector, clear B flag (in pushed status)
                     cpu_state.irq_pending = false;
-                    
-                    // Modify the status byte already pushed to stack to clear B flag
-                    let status_stack_addr = 0x0100 | (cpu_state.sp.wrapping_add(1) as u16);
-                    let pushed_status = memory.borrow().read(status_stack_addr);
-                    memory.borrow_mut().write(status_stack_addr, pushed_status & !FLAG_BREAK, false);
-                    
+                    status &= !FLAG_BREAK;
                     IRQ_VECTOR
                 } else {
                     // Normal BRK: use IRQ vector, B flag remains SET (in pushed status)
                     IRQ_VECTOR
                 };
-                
-                // Load low byte from chosen vector and set I flag
-                let pcl = memory.borrow().read(self.vector);
+
+                let stack_addr = 0x0100 | (cpu_state.sp as u16);
+                memory.write(stack_addr, status, false);
+                cpu_state.sp = cpu_state.sp.wrapping_sub(1);
+                self.cycle = 4;
+            }
+            4 => {
+                // Cycle 6: Load low byte from chosen vector and set I flag
+                let pcl = memory.read(self.vector);
                 cpu_state.pc = pcl as u16;
                 cpu_state.p |= FLAG_INTERRUPT;
                 self.cycle = 5;
             }
             5 => {
                 // Cycle 7: Load PCH from vector (using the vector determined in cycle 4)
-                let pch = memory.borrow().read(self.vector + 1);
+                let pch = memory.read(self.vector + 1);
                 cpu_state.pc |= (pch as u16) << 8;
                 self.cycle = 6;
             }
@@ -768,7 +792,7 @@ impl InstructionType for Php {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 2, "Php::tick called after already done");
@@ -782,7 +806,7 @@ impl InstructionType for Php {
                 // Cycle 2: Push status register with B and unused flags set
                 let status = cpu_state.p | FLAG_BREAK | FLAG_UNUSED;
                 let stack_addr = 0x0100 | (cpu_state.sp as u16);
-                memory.borrow_mut().write(stack_addr, status, false);
+                memory.write(stack_addr, status, false);
                 cpu_state.sp = cpu_state.sp.wrapping_sub(1);
                 self.cycle = 2;
             }
@@ -818,7 +842,7 @@ impl InstructionType for Ora {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Ora::tick called after already done");
@@ -842,10 +866,8 @@ impl InstructionType for Ora {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::apu::Apu;
     use crate::cpu2::traits::AddressingMode;
     use crate::nes::TvSystem;
-    use crate::ppu::Ppu;
 
     #[test]
     fn test_jsr_starts_not_done() {
@@ -855,13 +877,11 @@ mod tests {
 
     #[test]
     fn test_jsr_completes_after_five_cycles() {
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // JSR $1234 at address $0400
-        memory.borrow_mut().write(0x0400, 0x34, false); // Low byte of target
-        memory.borrow_mut().write(0x0401, 0x12, false); // High byte of target
+        memory.write(0x0400, 0x34, false); // Low byte of target
+        memory.write(0x0401, 0x12, false); // High byte of target
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -881,7 +901,7 @@ mod tests {
         // Execute 6 cycles
         let addressing_mode = crate::cpu2::addressing::Implied;
         for i in 1..=5 {
-            jsr.tick(&mut cpu_state, Rc::clone(&memory), &addressing_mode);
+            jsr.tick(&mut cpu_state, &mut memory, &addressing_mode);
             if i < 5 {
                 assert!(!jsr.is_done(), "Should not be done after cycle {}", i);
             }
@@ -896,8 +916,8 @@ mod tests {
 
         // Check return address on stack (PC was 0x0402 when returning, so we push 0x0401)
         // Stack grows downward: PCH at 0x01FD, PCL at 0x01FC
-        let pch = memory.borrow().read(0x01FD);
-        let pcl = memory.borrow().read(0x01FC);
+        let pch = memory.read(0x01FD);
+        let pcl = memory.read(0x01FC);
         let return_address = ((pch as u16) << 8) | (pcl as u16);
         assert_eq!(
             return_address, 0x0401,
@@ -907,13 +927,11 @@ mod tests {
 
     #[test]
     fn test_jsr_pushes_correct_return_address() {
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         // JSR $0234 at address $0500
-        memory.borrow_mut().write(0x0500, 0x34, false);
-        memory.borrow_mut().write(0x0501, 0x02, false);
+        memory.write(0x0500, 0x34, false);
+        memory.write(0x0501, 0x02, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -932,7 +950,7 @@ mod tests {
 
         let addressing_mode = crate::cpu2::addressing::Implied;
         for _ in 0..5 {
-            jsr.tick(&mut cpu_state, Rc::clone(&memory), &addressing_mode);
+            jsr.tick(&mut cpu_state, &mut memory, &addressing_mode);
         }
 
         assert!(jsr.is_done());
@@ -940,20 +958,18 @@ mod tests {
         assert_eq!(cpu_state.sp, 0xFD, "SP should decrement by 2");
 
         // Return address should be 0x0501 (points to high byte of JSR operand)
-        let pch = memory.borrow().read(0x01FF);
-        let pcl = memory.borrow().read(0x01FE);
+        let pch = memory.read(0x01FF);
+        let pcl = memory.read(0x01FE);
         assert_eq!(pch, 0x05, "High byte of return address should be correct");
         assert_eq!(pcl, 0x01, "Low byte of return address should be correct");
     }
 
     #[test]
     fn test_jsr_with_stack_wrap() {
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
-        memory.borrow_mut().write(0x0500, 0x00, false);
-        memory.borrow_mut().write(0x0501, 0x06, false);
+        memory.write(0x0500, 0x00, false);
+        memory.write(0x0501, 0x06, false);
 
         let mut cpu_state = CpuState {
             a: 0,
@@ -972,7 +988,7 @@ mod tests {
 
         let addressing_mode = crate::cpu2::addressing::Implied;
         for _ in 0..5 {
-            jsr.tick(&mut cpu_state, Rc::clone(&memory), &addressing_mode);
+            jsr.tick(&mut cpu_state, &mut memory, &addressing_mode);
         }
 
         assert!(jsr.is_done());
@@ -988,9 +1004,7 @@ mod tests {
 
     #[test]
     fn test_jmp_completes_after_one_cycle() {
-        let ppu = Rc::new(RefCell::new(Ppu::new(TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(Apu::new()));
-        let memory = Rc::new(RefCell::new(MemController::new(ppu, apu)));
+        let mut memory = MemController::new(TvSystem::Ntsc);
 
         let mut cpu_state = CpuState {
             a: 0xAA,
@@ -1011,15 +1025,15 @@ mod tests {
         let mut absolute_addr =
             crate::cpu2::addressing::Absolute::new(crate::cpu2::addressing::MemoryAccess::Jump);
         // Simulate that addressing mode has resolved to 0x1234
-        memory.borrow_mut().write(0x0400, 0x34, false); // Low byte
-        memory.borrow_mut().write(0x0401, 0x12, false); // High byte
+        memory.write(0x0400, 0x34, false); // Low byte
+        memory.write(0x0401, 0x12, false); // High byte
         // Tick addressing mode to completion
         while !absolute_addr.is_done() {
-            absolute_addr.tick(&mut cpu_state, Rc::clone(&memory));
+            absolute_addr.tick(&mut cpu_state, &mut memory);
         }
 
         // Execute 1 cycle
-        jmp.tick(&mut cpu_state, Rc::clone(&memory), &absolute_addr);
+        jmp.tick(&mut cpu_state, &mut memory, &absolute_addr);
 
         assert!(jmp.is_done(), "Should be done after 1 cycle");
         assert_eq!(cpu_state.pc, 0x1234, "PC should be set to target address");
@@ -1058,7 +1072,7 @@ impl InstructionType for Kil {
     fn tick(
         &mut self,
         _cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         // KIL does nothing - it just loops forever
@@ -1100,7 +1114,7 @@ impl InstructionType for Slo {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 3, "Slo::tick called after already done");
@@ -1113,9 +1127,7 @@ impl InstructionType for Slo {
             }
             1 => {
                 // Cycle 2: Write original value back (dummy write)
-                memory
-                    .borrow_mut()
-                    .write(addressing_mode.get_address(), self.value, false);
+                memory.write(addressing_mode.get_address(), self.value, false);
                 self.cycle = 2;
             }
             2 => {
@@ -1123,9 +1135,7 @@ impl InstructionType for Slo {
                 let (shifted, carry) = shift_left(self.value);
 
                 // Write shifted value back to memory
-                memory
-                    .borrow_mut()
-                    .write(addressing_mode.get_address(), shifted, false);
+                memory.write(addressing_mode.get_address(), shifted, false);
 
                 // OR with accumulator
                 cpu_state.a |= shifted;
@@ -1173,7 +1183,7 @@ impl InstructionType for Dop {
     fn tick(
         &mut self,
         _cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Dop::tick called after already done");
@@ -1219,7 +1229,7 @@ impl InstructionType for And {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "And::tick called after already done");
@@ -1270,7 +1280,7 @@ impl InstructionType for Bit {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Bit::tick called after already done");
@@ -1332,7 +1342,7 @@ impl InstructionType for Rol {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 3, "Rol::tick called after already done");
@@ -1346,7 +1356,7 @@ impl InstructionType for Rol {
             1 => {
                 // Cycle 2: Dummy write (write original value back)
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, self.value, false);
+                memory.write(address, self.value, false);
                 self.cycle = 2;
             }
             2 => {
@@ -1357,7 +1367,7 @@ impl InstructionType for Rol {
 
                 // Write modified value
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, rotated, false);
+                memory.write(address, rotated, false);
 
                 // Set flags
                 set_negative_flag(&mut cpu_state.p, rotated);
@@ -1401,13 +1411,16 @@ impl InstructionType for RolA {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "RolA::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Rotate accumulator
                 let old_carry = cpu_state.p & super::types::FLAG_CARRY;
                 let new_carry = (cpu_state.a & 0x80) != 0;
@@ -1457,26 +1470,27 @@ impl InstructionType for Plp {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 3, "Plp::tick called after already done");
 
         match self.cycle {
             0 => {
-                // Cycle 1: Internal operation (dummy read)
-                let _ = memory.borrow().read(0x0100 | (cpu_state.sp as u16));
+                // Cycle 1: Read next byte (throw away)
+                memory.read(cpu_state.pc);
                 self.cycle = 1;
             }
             1 => {
-                // Cycle 2: Increment SP
+                // Cycle 2: Dummy read of the stack, increment SP
+                memory.read(0x0100 | (cpu_state.sp as u16));
                 cpu_state.sp = cpu_state.sp.wrapping_add(1);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Pull status from stack
                 let addr = 0x0100 | (cpu_state.sp as u16);
-                let status = memory.borrow().read(addr);
+                let status = memory.read(addr);
 
                 // Save the OLD I flag value before changing it
                 cpu_state.saved_i_flag = (cpu_state.p & super::types::FLAG_INTERRUPT) != 0;
@@ -1529,7 +1543,7 @@ impl InstructionType for Rla {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 3, "Rla::tick called after already done");
@@ -1543,7 +1557,7 @@ impl InstructionType for Rla {
             1 => {
                 // Cycle 2: Dummy write (write original value back)
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, self.value, false);
+                memory.write(address, self.value, false);
                 self.cycle = 2;
             }
             2 => {
@@ -1554,7 +1568,7 @@ impl InstructionType for Rla {
 
                 // Write modified value
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, rotated, false);
+                memory.write(address, rotated, false);
 
                 // AND with accumulator
                 cpu_state.a &= rotated;
@@ -1605,7 +1619,7 @@ impl InstructionType for Asl {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 3, "Asl::tick called after already done");
@@ -1618,9 +1632,7 @@ impl InstructionType for Asl {
             }
             1 => {
                 // Cycle 2: Write original value back (dummy write)
-                memory
-                    .borrow_mut()
-                    .write(addressing_mode.get_address(), self.value, false);
+                memory.write(addressing_mode.get_address(), self.value, false);
                 self.cycle = 2;
             }
             2 => {
@@ -1628,9 +1640,7 @@ impl InstructionType for Asl {
                 let (shifted, carry) = shift_left(self.value);
 
                 // Write shifted value back to memory
-                memory
-                    .borrow_mut()
-                    .write(addressing_mode.get_address(), shifted, false);
+                memory.write(addressing_mode.get_address(), shifted, false);
 
                 // Set flags based on result
                 set_zero_flag(&mut cpu_state.p, shifted);
@@ -1674,13 +1684,16 @@ impl InstructionType for AslA {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "AslA::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Shift accumulator left
                 let (shifted, carry) = shift_left(cpu_state.a);
                 cpu_state.a = shifted;
@@ -1742,7 +1755,7 @@ impl InstructionType for Rti {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 5, "Rti::tick called after already done");
@@ -1750,32 +1763,33 @@ impl InstructionType for Rti {
         match self.cycle {
             0 => {
                 // Cycle 1: Read next byte (throw away)
-                memory.borrow().read(cpu_state.pc);
+                memory.read(cpu_state.pc);
                 self.cycle = 1;
             }
             1 => {
-                // Cycle 2: Increment S
+                // Cycle 2: Dummy read of the stack, increment S
+                memory.read(0x0100 | (cpu_state.sp as u16));
                 cpu_state.sp = cpu_state.sp.wrapping_add(1);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Pull P from stack
                 let stack_addr = 0x0100 | (cpu_state.sp as u16);
-                self.p = memory.borrow().read(stack_addr);
+                self.p = memory.read(stack_addr);
                 cpu_state.sp = cpu_state.sp.wrapping_add(1);
                 self.cycle = 3;
             }
             3 => {
                 // Cycle 4: Pull PCL from stack
                 let stack_addr = 0x0100 | (cpu_state.sp as u16);
-                self.pcl = memory.borrow().read(stack_addr);
+                self.pcl = memory.read(stack_addr);
                 cpu_state.sp = cpu_state.sp.wrapping_add(1);
                 self.cycle = 4;
             }
             4 => {
                 // Cycle 5: Pull PCH from stack
                 let stack_addr = 0x0100 | (cpu_state.sp as u16);
-                let pch = memory.borrow().read(stack_addr);
+                let pch = memory.read(stack_addr);
                 cpu_state.pc = ((pch as u16) << 8) | (self.pcl as u16);
 
                 // Restore processor status from stack
@@ -1819,7 +1833,7 @@ impl InstructionType for Eor {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Eor::tick called after already done");
@@ -1875,7 +1889,7 @@ impl InstructionType for Lsr {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 3, "Lsr::tick called after already done");
@@ -1889,14 +1903,14 @@ impl InstructionType for Lsr {
             1 => {
                 // Cycle 2: Dummy write (write original value back)
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, self.value, false);
+                memory.write(address, self.value, false);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Shift right and write result
                 let (shifted, carry) = shift_right(self.value);
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, shifted, false);
+                memory.write(address, shifted, false);
 
                 // Set flags based on result
                 set_zero_flag(&mut cpu_state.p, shifted);
@@ -1940,13 +1954,16 @@ impl InstructionType for LsrA {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "LsrA::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Shift accumulator right
                 let (shifted, carry) = shift_right(cpu_state.a);
                 cpu_state.a = shifted;
@@ -1993,7 +2010,7 @@ impl InstructionType for Pha {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 2, "Pha::tick called after already done");
@@ -2001,13 +2018,13 @@ impl InstructionType for Pha {
         match self.cycle {
             0 => {
                 // Cycle 1: Read next byte (throw away)
-                memory.borrow().read(cpu_state.pc);
+                memory.read(cpu_state.pc);
                 self.cycle = 1;
             }
             1 => {
                 // Cycle 2: Push A to stack
                 let stack_addr = 0x0100 | (cpu_state.sp as u16);
-                memory.borrow_mut().write(stack_addr, cpu_state.a, false);
+                memory.write(stack_addr, cpu_state.a, false);
                 cpu_state.sp = cpu_state.sp.wrapping_sub(1);
                 self.cycle = 2;
             }
@@ -2050,7 +2067,7 @@ impl InstructionType for Sre {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 3, "Sre::tick called after already done");
@@ -2064,14 +2081,14 @@ impl InstructionType for Sre {
             1 => {
                 // Cycle 2: Dummy write (write original value back)
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, self.value, false);
+                memory.write(address, self.value, false);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Shift right, EOR with A, write result
                 let (shifted, carry) = shift_right(self.value);
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, shifted, false);
+                memory.write(address, shifted, false);
 
                 // EOR with accumulator
                 cpu_state.a ^= shifted;
@@ -2118,7 +2135,7 @@ impl InstructionType for Asr {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Asr::tick called after already done");
@@ -2158,6 +2175,8 @@ pub struct Bvc {
     cycle: u8,
     branch_taken: bool,
     page_crossed: bool,
+    /// Address of the next instruction, had the branch not been taken
+    next_pc: u16,
 }
 
 impl Bvc {
@@ -2177,7 +2196,7 @@ impl InstructionType for Bvc {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(!self.is_done(), "Bvc::tick called after already done");
@@ -2198,16 +2217,21 @@ impl InstructionType for Bvc {
                     self.page_crossed = current_page != target_page;
 
                     // Update PC to target
+                    self.next_pc = cpu_state.pc;
                     cpu_state.pc = target;
                     self.cycle = 1;
                 }
             }
             1 => {
                 // Cycle 2: Additional cycle for branch taken (same page)
+                // Read the next opcode (throw away) while adding the offset to PCL
+                memory.read(self.next_pc);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Additional cycle for page boundary crossed
+                // Read from the target before PCH is fixed (throw away)
+                memory.read((self.next_pc & 0xFF00) | (cpu_state.pc & 0x00FF));
                 self.cycle = 3;
             }
             _ => unreachable!(),
@@ -2244,13 +2268,16 @@ impl InstructionType for Cli {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Cli::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Clear interrupt disable flag
                 // Save the OLD I flag value before changing it
                 cpu_state.saved_i_flag = (cpu_state.p & super::types::FLAG_INTERRUPT) != 0;
@@ -2294,13 +2321,16 @@ impl InstructionType for Cld {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Cld::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Clear decimal flag
                 cpu_state.p &= !super::types::FLAG_DECIMAL;
                 self.cycle = 1;
@@ -2329,7 +2359,7 @@ impl InstructionType for Rts {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 5, "Rts::tick called after already done");
@@ -2337,18 +2367,19 @@ impl InstructionType for Rts {
         match self.cycle {
             0 => {
                 // Cycle 1: Read next byte (throw away)
-                memory.borrow().read(cpu_state.pc);
+                memory.read(cpu_state.pc);
                 self.cycle = 1;
             }
             1 => {
-                // Cycle 2: Increment stack pointer
+                // Cycle 2: Dummy read of the stack, increment stack pointer
+                memory.read(0x0100 | (cpu_state.sp as u16));
                 cpu_state.sp = cpu_state.sp.wrapping_add(1);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Pull PCL from stack
                 let stack_addr = 0x0100 | (cpu_state.sp as u16);
-                let pcl = memory.borrow().read(stack_addr) as u16;
+                let pcl = memory.read(stack_addr) as u16;
                 cpu_state.pc = (cpu_state.pc & 0xFF00) | pcl;
                 cpu_state.sp = cpu_state.sp.wrapping_add(1);
                 self.cycle = 3;
@@ -2356,12 +2387,13 @@ impl InstructionType for Rts {
             3 => {
                 // Cycle 4: Pull PCH from stack
                 let stack_addr = 0x0100 | (cpu_state.sp as u16);
-                let pch = memory.borrow().read(stack_addr) as u16;
+                let pch = memory.read(stack_addr) as u16;
                 cpu_state.pc = (pch << 8) | (cpu_state.pc & 0x00FF);
                 self.cycle = 4;
             }
             4 => {
-                // Cycle 5: Increment PC
+                // Cycle 5: Dummy read at the pulled PC, increment PC
+                memory.read(cpu_state.pc);
                 cpu_state.pc = cpu_state.pc.wrapping_add(1);
                 self.cycle = 5;
             }
@@ -2389,7 +2421,7 @@ impl InstructionType for Adc {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Adc::tick called after already done");
@@ -2446,7 +2478,7 @@ impl InstructionType for Sbc {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Sbc::tick called after already done");
@@ -2499,7 +2531,7 @@ impl InstructionType for Ror {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 3, "Ror::tick called after already done");
@@ -2508,12 +2540,12 @@ impl InstructionType for Ror {
             0 => {
                 // Cycle 1: Read value from memory
                 self.address = addressing_mode.get_address();
-                self.value = memory.borrow().read(self.address);
+                self.value = memory.read(self.address);
                 self.cycle = 1;
             }
             1 => {
                 // Cycle 2: Write old value back (dummy write)
-                memory.borrow_mut().write(self.address, self.value, false);
+                memory.write(self.address, self.value, false);
                 self.cycle = 2;
             }
             2 => {
@@ -2526,7 +2558,7 @@ impl InstructionType for Ror {
                 set_negative_flag(&mut cpu_state.p, self.value);
                 set_carry_flag(&mut cpu_state.p, new_carry);
 
-                memory.borrow_mut().write(self.address, self.value, false);
+                memory.write(self.address, self.value, false);
                 self.cycle = 3;
             }
             _ => unreachable!(),
@@ -2553,11 +2585,14 @@ impl InstructionType for RorA {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "RorA::tick called after already done");
 
+        // Read next byte (throw away)
+        memory.read(cpu_state.pc);
+
         let old_carry = if cpu_state.p & FLAG_CARRY != 0 { 1 } else { 0 };
         let new_carry = cpu_state.a & 0x01 != 0;
         cpu_state.a = (cpu_state.a >> 1) | (old_carry << 7);
@@ -2589,7 +2624,7 @@ impl InstructionType for Pla {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 3, "Pla::tick called after already done");
@@ -2597,18 +2632,19 @@ impl InstructionType for Pla {
         match self.cycle {
             0 => {
                 // Cycle 1: Read next byte (throw away)
-                memory.borrow().read(cpu_state.pc);
+                memory.read(cpu_state.pc);
                 self.cycle = 1;
             }
             1 => {
-                // Cycle 2: Increment stack pointer
+                // Cycle 2: Dummy read of the stack, increment stack pointer
+                memory.read(0x0100 | (cpu_state.sp as u16));
                 cpu_state.sp = cpu_state.sp.wrapping_add(1);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Pull value from stack
                 let stack_addr = 0x0100 | (cpu_state.sp as u16);
-                cpu_state.a = memory.borrow().read(stack_addr);
+                cpu_state.a = memory.read(stack_addr);
                 set_zero_flag(&mut cpu_state.p, cpu_state.a);
                 set_negative_flag(&mut cpu_state.p, cpu_state.a);
                 self.cycle = 3;
@@ -2639,7 +2675,7 @@ impl InstructionType for Rra {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 3, "Rra::tick called after already done");
@@ -2648,12 +2684,12 @@ impl InstructionType for Rra {
             0 => {
                 // Cycle 1: Read value from memory
                 self.address = addressing_mode.get_address();
-                self.value = memory.borrow().read(self.address);
+                self.value = memory.read(self.address);
                 self.cycle = 1;
             }
             1 => {
                 // Cycle 2: Write old value back (dummy write)
-                memory.borrow_mut().write(self.address, self.value, false);
+                memory.write(self.address, self.value, false);
                 self.cycle = 2;
             }
             2 => {
@@ -2680,7 +2716,7 @@ impl InstructionType for Rra {
                 }
 
                 cpu_state.a = result;
-                memory.borrow_mut().write(self.address, self.value, false);
+                memory.write(self.address, self.value, false);
                 self.cycle = 3;
             }
             _ => unreachable!(),
@@ -2707,7 +2743,7 @@ impl InstructionType for Arr {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Arr::tick called after already done");
@@ -2746,6 +2782,8 @@ pub struct Bvs {
     cycle: u8,
     branch_taken: bool,
     page_crossed: bool,
+    /// Address of the next instruction, had the branch not been taken
+    next_pc: u16,
 }
 
 impl Bvs {
@@ -2764,7 +2802,7 @@ impl InstructionType for Bvs {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(!self.is_done(), "Bvs::tick called after already done");
@@ -2785,16 +2823,21 @@ impl InstructionType for Bvs {
                     self.page_crossed = current_page != target_page;
 
                     // Update PC to target
+                    self.next_pc = cpu_state.pc;
                     cpu_state.pc = target;
                     self.cycle = 1;
                 }
             }
             1 => {
                 // Cycle 2: Extra cycle for branch taken (same page)
+                // Read the next opcode (throw away) while adding the offset to PCL
+                memory.read(self.next_pc);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Extra cycle for page crossing
+                // Read from the target before PCH is fixed (throw away)
+                memory.read((self.next_pc & 0xFF00) | (cpu_state.pc & 0x00FF));
                 self.cycle = 3;
             }
             _ => unreachable!(),
@@ -2821,13 +2864,16 @@ impl InstructionType for Sei {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Sei::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Set interrupt disable flag
                 // Save the OLD I flag value before changing it
                 cpu_state.saved_i_flag = (cpu_state.p & super::types::FLAG_INTERRUPT) != 0;
@@ -2861,7 +2907,7 @@ impl InstructionType for Sta {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Sta::tick called after already done");
@@ -2870,7 +2916,7 @@ impl InstructionType for Sta {
             0 => {
                 // Cycle 1: Write A to memory at the address
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, cpu_state.a, false);
+                memory.write(address, cpu_state.a, false);
                 self.cycle = 1;
             }
             _ => unreachable!(),
@@ -2897,7 +2943,7 @@ impl InstructionType for Sty {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Sty::tick called after already done");
@@ -2906,7 +2952,7 @@ impl InstructionType for Sty {
             0 => {
                 // Cycle 1: Write Y to memory at the address
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, cpu_state.y, false);
+                memory.write(address, cpu_state.y, false);
                 self.cycle = 1;
             }
             _ => unreachable!(),
@@ -2933,7 +2979,7 @@ impl InstructionType for Stx {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Stx::tick called after already done");
@@ -2942,7 +2988,7 @@ impl InstructionType for Stx {
             0 => {
                 // Cycle 1: Write X to memory at the address
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, cpu_state.x, false);
+                memory.write(address, cpu_state.x, false);
                 self.cycle = 1;
             }
             _ => unreachable!(),
@@ -2969,13 +3015,16 @@ impl InstructionType for Dey {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Dey::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Decrement Y
                 cpu_state.y = cpu_state.y.wrapping_sub(1);
 
@@ -3009,13 +3058,16 @@ impl InstructionType for Txa {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Txa::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Transfer X to A
                 cpu_state.a = cpu_state.x;
 
@@ -3049,7 +3101,7 @@ impl InstructionType for Sax {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Sax::tick called after already done");
@@ -3059,7 +3111,7 @@ impl InstructionType for Sax {
                 // Cycle 1: Store A AND X to memory (illegal opcode)
                 let value = cpu_state.a & cpu_state.x;
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, value, false);
+                memory.write(address, value, false);
                 self.cycle = 1;
             }
             _ => unreachable!(),
@@ -3086,7 +3138,7 @@ impl InstructionType for Xaa {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Xaa::tick called after already done");
@@ -3130,7 +3182,7 @@ impl InstructionType for Lda {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Lda::tick called after already done");
@@ -3170,7 +3222,7 @@ impl InstructionType for Ldx {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Ldx::tick called after already done");
@@ -3210,7 +3262,7 @@ impl InstructionType for Ldy {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Ldy::tick called after already done");
@@ -3250,13 +3302,16 @@ impl InstructionType for Tax {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Tax::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Transfer A to X
                 cpu_state.x = cpu_state.a;
 
@@ -3290,13 +3345,16 @@ impl InstructionType for Tay {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Tay::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Transfer A to Y
                 cpu_state.y = cpu_state.a;
 
@@ -3330,7 +3388,7 @@ impl InstructionType for Lax {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Lax::tick called after already done");
@@ -3372,7 +3430,7 @@ impl InstructionType for Atx {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Atx::tick called after already done");
@@ -3403,6 +3461,8 @@ pub struct Bcs {
     cycle: u8,
     branch_taken: bool,
     page_crossed: bool,
+    /// Address of the next instruction, had the branch not been taken
+    next_pc: u16,
 }
 
 impl Bcs {
@@ -3421,7 +3481,7 @@ impl InstructionType for Bcs {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(!self.is_done(), "Bcs::tick called after already done");
@@ -3442,16 +3502,21 @@ impl InstructionType for Bcs {
                     self.page_crossed = current_page != target_page;
 
                     // Update PC to target
+                    self.next_pc = cpu_state.pc;
                     cpu_state.pc = target;
                     self.cycle = 1;
                 }
             }
             1 => {
                 // Cycle 2: Additional cycle for branch taken (same page)
+                // Read the next opcode (throw away) while adding the offset to PCL
+                memory.read(self.next_pc);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Additional cycle for page boundary crossed
+                // Read from the target before PCH is fixed (throw away)
+                memory.read((self.next_pc & 0xFF00) | (cpu_state.pc & 0x00FF));
                 self.cycle = 3;
             }
             _ => unreachable!(),
@@ -3478,13 +3543,16 @@ impl InstructionType for Clv {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Clv::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Clear overflow flag
                 cpu_state.p &= !super::types::FLAG_OVERFLOW;
                 self.cycle = 1;
@@ -3513,13 +3581,16 @@ impl InstructionType for Tsx {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Tsx::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Transfer SP to X
                 cpu_state.x = cpu_state.sp;
 
@@ -3553,7 +3624,7 @@ impl InstructionType for Lar {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Lar::tick called after already done");
@@ -3599,7 +3670,7 @@ impl InstructionType for Cpy {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Cpy::tick called after already done");
@@ -3647,7 +3718,7 @@ impl InstructionType for Cmp {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Cmp::tick called after already done");
@@ -3703,7 +3774,7 @@ impl InstructionType for Cpx {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Cpx::tick called after already done");
@@ -3752,7 +3823,7 @@ impl InstructionType for Dec {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 3, "Dec::tick called after already done");
@@ -3765,16 +3836,14 @@ impl InstructionType for Dec {
             }
             1 => {
                 // Cycle 2: Write original value back (dummy write)
-                memory
-                    .borrow_mut()
-                    .write(addressing_mode.get_address(), self.value, false);
+                memory.write(addressing_mode.get_address(), self.value, false);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Write back decremented value
                 let result = self.value.wrapping_sub(1);
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, result, false);
+                memory.write(address, result, false);
 
                 // Set flags
                 set_zero_flag(&mut cpu_state.p, result);
@@ -3807,13 +3876,16 @@ impl InstructionType for Iny {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Iny::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Increment Y
                 cpu_state.y = cpu_state.y.wrapping_add(1);
 
@@ -3859,7 +3931,7 @@ impl InstructionType for Inc {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 3, "Inc::tick called after already done");
@@ -3872,16 +3944,14 @@ impl InstructionType for Inc {
             }
             1 => {
                 // Cycle 2: Write original value back (dummy write)
-                memory
-                    .borrow_mut()
-                    .write(addressing_mode.get_address(), self.value, false);
+                memory.write(addressing_mode.get_address(), self.value, false);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Write back incremented value
                 let result = self.value.wrapping_add(1);
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, result, false);
+                memory.write(address, result, false);
 
                 // Set flags
                 set_zero_flag(&mut cpu_state.p, result);
@@ -3921,13 +3991,16 @@ impl InstructionType for Inx {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Inx::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Increment X
                 cpu_state.x = cpu_state.x.wrapping_add(1);
 
@@ -3962,13 +4035,16 @@ impl InstructionType for Dex {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Dex::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Decrement X
                 cpu_state.x = cpu_state.x.wrapping_sub(1);
 
@@ -4003,7 +4079,7 @@ impl InstructionType for Axs {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        _memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Axs::tick called after already done");
@@ -4054,7 +4130,7 @@ impl InstructionType for Dcp {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 3, "Dcp::tick called after already done");
@@ -4067,16 +4143,14 @@ impl InstructionType for Dcp {
             }
             1 => {
                 // Cycle 2: Write original value back (dummy write)
-                memory
-                    .borrow_mut()
-                    .write(addressing_mode.get_address(), self.value, false);
+                memory.write(addressing_mode.get_address(), self.value, false);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Decrement and write back, then compare with A
                 let result = self.value.wrapping_sub(1);
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, result, false);
+                memory.write(address, result, false);
 
                 // Compare A with decremented value
                 let cmp_result = cpu_state.a.wrapping_sub(result);
@@ -4129,7 +4203,7 @@ impl InstructionType for Isb {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 3, "Isb::tick called after already done");
@@ -4142,16 +4216,14 @@ impl InstructionType for Isb {
             }
             1 => {
                 // Cycle 2: Write original value back (dummy write)
-                memory
-                    .borrow_mut()
-                    .write(addressing_mode.get_address(), self.value, false);
+                memory.write(addressing_mode.get_address(), self.value, false);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Increment and write back, then perform SBC with A
                 let result = self.value.wrapping_add(1);
                 let address = addressing_mode.get_address();
-                memory.borrow_mut().write(address, result, false);
+                memory.write(address, result, false);
 
                 // Perform SBC: A = A - result - (1 - C)
                 let a = cpu_state.a;
@@ -4200,6 +4272,8 @@ pub struct Beq {
     cycle: u8,
     branch_taken: bool,
     page_crossed: bool,
+    /// Address of the next instruction, had the branch not been taken
+    next_pc: u16,
 }
 
 impl Beq {
@@ -4219,7 +4293,7 @@ impl InstructionType for Beq {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(!self.is_done(), "Beq::tick called after already done");
@@ -4240,16 +4314,21 @@ impl InstructionType for Beq {
                     self.page_crossed = current_page != target_page;
 
                     // Update PC to target
+                    self.next_pc = cpu_state.pc;
                     cpu_state.pc = target;
                     self.cycle = 1;
                 }
             }
             1 => {
                 // Cycle 2: Extra cycle for branch taken (same page)
+                // Read the next opcode (throw away) while adding the offset to PCL
+                memory.read(self.next_pc);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Extra cycle for page crossing
+                // Read from the target before PCH is fixed (throw away)
+                memory.read((self.next_pc & 0xFF00) | (cpu_state.pc & 0x00FF));
                 self.cycle = 3;
             }
             _ => unreachable!(),
@@ -4288,13 +4367,16 @@ impl InstructionType for Sed {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Sed::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Set decimal flag
                 cpu_state.p |= FLAG_DECIMAL;
                 self.cycle = 1;
@@ -4321,6 +4403,8 @@ pub struct Bcc {
     cycle: u8,
     branch_taken: bool,
     page_crossed: bool,
+    /// Address of the next instruction, had the branch not been taken
+    next_pc: u16,
 }
 
 impl Bcc {
@@ -4340,7 +4424,7 @@ impl InstructionType for Bcc {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(!self.is_done(), "Bcc::tick called after already done");
@@ -4361,16 +4445,21 @@ impl InstructionType for Bcc {
                     self.page_crossed = current_page != target_page;
 
                     // Update PC to target
+                    self.next_pc = cpu_state.pc;
                     cpu_state.pc = target;
                     self.cycle = 1;
                 }
             }
             1 => {
                 // Cycle 2: Extra cycle for branch taken (same page)
+                // Read the next opcode (throw away) while adding the offset to PCL
+                memory.read(self.next_pc);
                 self.cycle = 2;
             }
             2 => {
                 // Cycle 3: Extra cycle for page crossing
+                // Read from the target before PCH is fixed (throw away)
+                memory.read((self.next_pc & 0xFF00) | (cpu_state.pc & 0x00FF));
                 self.cycle = 3;
             }
             _ => unreachable!(),
@@ -4408,13 +4497,16 @@ impl InstructionType for Tya {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Tya::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Transfer Y to A
                 cpu_state.a = cpu_state.y;
 
@@ -4459,13 +4551,16 @@ impl InstructionType for Txs {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        _memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         _addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Txs::tick called after already done");
 
         match self.cycle {
             0 => {
+                // Read next byte (throw away)
+                memory.read(cpu_state.pc);
+
                 // Cycle 1: Transfer X to SP
                 cpu_state.sp = cpu_state.x;
                 self.cycle = 1;
@@ -4505,7 +4600,7 @@ impl InstructionType for Axa {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Axa::tick called after already done");
@@ -4518,7 +4613,7 @@ impl InstructionType for Axa {
                 // For indirect indexed (base + Y), remove Y to get base high byte
                 let base_high_byte = ((address.wrapping_sub(cpu_state.y as u16)) >> 8) as u8;
                 let value = cpu_state.a & cpu_state.x & base_high_byte.wrapping_add(1);
-                memory.borrow_mut().write(address, value, false);
+                memory.write(address, value, false);
                 self.cycle = 1;
             }
             _ => unreachable!(),
@@ -4556,7 +4651,7 @@ impl InstructionType for Sxa {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Sxa::tick called after already done");
@@ -4579,7 +4674,7 @@ impl InstructionType for Sxa {
                     address
                 };
 
-                memory.borrow_mut().write(final_addr, value, false);
+                memory.write(final_addr, value, false);
                 self.cycle = 1;
             }
             _ => unreachable!(),
@@ -4617,7 +4712,7 @@ impl InstructionType for Sya {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Sya::tick called after already done");
@@ -4640,7 +4735,7 @@ impl InstructionType for Sya {
                     address
                 };
 
-                memory.borrow_mut().write(final_addr, value, false);
+                memory.write(final_addr, value, false);
                 self.cycle = 1;
             }
             _ => unreachable!(),
@@ -4678,7 +4773,7 @@ impl InstructionType for Xas {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn super::traits::AddressingMode,
     ) {
         debug_assert!(self.cycle < 1, "Xas::tick called after already done");
@@ -4702,7 +4797,7 @@ impl InstructionType for Xas {
                     address
                 };
 
-                memory.borrow_mut().write(final_addr, value, false);
+                memory.write(final_addr, value, false);
                 self.cycle = 1;
             }
             _ => unreachable!(),
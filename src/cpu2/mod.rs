@@ -7,6 +7,8 @@ pub mod addressing;
 pub mod cpu;
 pub mod instruction;
 pub mod instruction_types;
+#[cfg(test)]
+pub mod single_step;
 pub mod traits;
 pub mod types;
 
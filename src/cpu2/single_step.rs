@@ -0,0 +1,241 @@
+//! Per-cycle CPU tests in the SingleStepTests (ProcessorTests) JSON format
+//!
+//! Each file holds the tests for one opcode. A test gives the registers and
+//! memory before and after one instruction, plus every bus cycle in between:
+//!
+//! ```json
+//! [{"name": "b1 db 6f", "initial": {"pc": 28415, "s": 255, "a": 215, "x": 98,
+//!   "y": 206, "p": 38, "ram": [[28415, 177], [28416, 219], ...]},
+//!   "final": {...}, "cycles": [[28415, 177, "read"], ...]}]
+//! ```
+//!
+//! The instruction runs on `Cpu2` with a flat 64KB memory and a bus log, so
+//! dummy reads and writes are checked as well as the end state.
+//!
+//! `roms/single_step` holds 20 tests for each of 34 opcodes covering every
+//! addressing mode, so the tests run offline. They follow the per-cycle tables
+//! in `6502_cpu.txt`, in the file layout and test naming of the suite. To also
+//! run the full suite (<https://github.com/SingleStepTests/65x02>,
+//! `nes6502/v1`), point `SUITE_ENV` at a checkout of it.
+
+use super::Cpu2;
+use crate::debugger::Access;
+use crate::mem_controller::{BusCycle, MemController};
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Break and unused bits of P, which only exist when P is pushed
+const PUSHED_ONLY_FLAGS: u8 = 0x30;
+
+/// Set to a directory of suite files to run them as well as the shipped ones
+pub const SUITE_ENV: &str = "NESER_SINGLE_STEP_TESTS";
+
+/// Longest instruction, in cycles, before the CPU is considered stuck
+const MAX_CYCLES: usize = 16;
+
+/// One test: an instruction with its start and end state
+#[derive(Debug, Deserialize)]
+pub struct SingleStepTest {
+    pub name: String,
+    pub initial: TestState,
+    #[serde(rename = "final")]
+    pub final_state: TestState,
+    /// Bus cycles as (address, value, "read" or "write")
+    pub cycles: Vec<(u16, u8, String)>,
+}
+
+/// Registers and the memory bytes a test touches
+#[derive(Debug, Deserialize)]
+pub struct TestState {
+    pub pc: u16,
+    pub s: u8,
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub p: u8,
+    pub ram: Vec<(u16, u8)>,
+}
+
+/// Load the tests of one JSON file
+pub fn load(path: &Path) -> io::Result<Vec<SingleStepTest>> {
+    let data = fs::read(path)?;
+    serde_json::from_slice(&data).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), e),
+        )
+    })
+}
+
+/// Run a test on `Cpu2`, describing every mismatch on failure
+pub fn run(test: &SingleStepTest) -> Result<(), String> {
+    let mut memory = MemController::new_flat();
+    for &(addr, value) in &test.initial.ram {
+        memory.write(addr, value, false);
+    }
+    memory.start_bus_log();
+
+    let mut cpu = Cpu2::new();
+    let initial = &test.initial;
+    let state = cpu.get_state();
+    state.pc = initial.pc;
+    state.sp = initial.s;
+    state.a = initial.a;
+    state.x = initial.x;
+    state.y = initial.y;
+    state.p = initial.p;
+    let mut ticks = 0;
+    while ticks < MAX_CYCLES {
+        ticks += 1;
+        if cpu.tick_cycle(&mut memory) {
+            break;
+        }
+    }
+
+    let mut errors = Vec::new();
+    let expected = &test.final_state;
+    let state = cpu.get_state();
+    let registers = [
+        ("PC", state.pc, expected.pc),
+        ("S", state.sp as u16, expected.s as u16),
+        ("A", state.a as u16, expected.a as u16),
+        ("X", state.x as u16, expected.x as u16),
+        ("Y", state.y as u16, expected.y as u16),
+        (
+            "P",
+            (state.p & !PUSHED_ONLY_FLAGS) as u16,
+            (expected.p & !PUSHED_ONLY_FLAGS) as u16,
+        ),
+    ];
+    for (name, actual, expected) in registers {
+        if actual != expected {
+            errors.push(format!(
+                "{} is ${:02X}, expected ${:02X}",
+                name, actual, expected
+            ));
+        }
+    }
+    for &(addr, value) in &expected.ram {
+        let actual = memory.peek(addr);
+        if actual != value {
+            errors.push(format!(
+                "${:04X} is ${:02X}, expected ${:02X}",
+                addr, actual, value
+            ));
+        }
+    }
+
+    let cycles = memory.take_bus_log();
+    let expected_cycles: Vec<BusCycle> = test
+        .cycles
+        .iter()
+        .map(|(addr, value, kind)| BusCycle {
+            addr: *addr,
+            value: *value,
+            access: if kind == "write" {
+                Access::Write
+            } else {
+                Access::Read
+            },
+        })
+        .collect();
+    if ticks != expected_cycles.len() || cycles != expected_cycles {
+        errors.push(format!(
+            "took {} cycles, expected {}\n  bus:      {}\n  expected: {}",
+            ticks,
+            expected_cycles.len(),
+            format_cycles(&cycles),
+            format_cycles(&expected_cycles)
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("{}: {}", test.name, errors.join("; ")))
+    }
+}
+
+/// Run every JSON file in a directory, returning a line per failing file
+pub fn run_dir(dir: &Path) -> io::Result<Vec<String>> {
+    let mut paths: Vec<_> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<_>>()?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "json"));
+    paths.sort();
+
+    let mut failures = Vec::new();
+    for path in &paths {
+        let tests = load(path)?;
+        let errors: Vec<String> = tests.iter().filter_map(|test| run(test).err()).collect();
+        if let Some(first) = errors.first() {
+            failures.push(format!(
+                "{}: {} of {} failed, first: {}",
+                path.display(),
+                errors.len(),
+                tests.len(),
+                first
+            ));
+        }
+    }
+    Ok(failures)
+}
+
+fn format_cycles(cycles: &[BusCycle]) -> String {
+    cycles
+        .iter()
+        .map(|cycle| {
+            let kind = if cycle.access == Access::Write {
+                "W"
+            } else {
+                "R"
+            };
+            format!("{}${:04X}=${:02X}", kind, cycle.addr, cycle.value)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_step_files() {
+        let failures = run_dir(Path::new("roms/single_step")).expect("roms/single_step is missing");
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn test_single_step_suite() {
+        // Only runs when pointed at a checkout of the full suite
+        let Some(dir) = std::env::var_os(SUITE_ENV) else {
+            return;
+        };
+        let failures = run_dir(Path::new(&dir)).expect("suite directory is unreadable");
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn test_bus_log_records_dummy_read() {
+        // LDA $12FF,X with X=1 crosses a page: the first read hits $1200
+        let test: SingleStepTest = serde_json::from_str(
+            r#"{"name": "bd ff 12", "initial": {"pc": 512, "s": 253, "a": 0, "x": 1,
+               "y": 0, "p": 36, "ram": [[512, 189], [513, 255], [514, 18], [4608, 17],
+               [4864, 34]]},
+               "final": {"pc": 515, "s": 253, "a": 34, "x": 1, "y": 0, "p": 36,
+               "ram": [[4864, 34]]},
+               "cycles": [[512, 189, "read"], [513, 255, "read"], [514, 18, "read"],
+               [4608, 17, "read"], [4864, 34, "read"]]}"#,
+        )
+        .unwrap();
+        assert_eq!(run(&test), Ok(()));
+
+        let mut wrong = test;
+        wrong.cycles.remove(3);
+        let error = run(&wrong).unwrap_err();
+        assert!(error.contains("took 5 cycles, expected 4"), "{}", error);
+    }
+}
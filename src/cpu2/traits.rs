@@ -5,13 +5,11 @@
 
 use super::types::CpuState;
 use crate::mem_controller::MemController;
-use std::cell::RefCell;
-use std::rc::Rc;
 
 // use super::types::AddressingState;
 
 /// Trait for addressing modes that resolve addresses cycle-by-cycle
-pub trait AddressingMode {
+pub trait AddressingMode: Send {
     /// Returns true if the addressing mode has completed address resolution
     /// This is typically determined by the number of cycles taken
     /// and any page crossing penalties.
@@ -20,7 +18,7 @@ pub trait AddressingMode {
     /// Ticks the addressing mode by one cycle
     /// This may involve reading bytes from memory and updating internal state.
     /// Note that is_done should be called first to see if any more ticks are needed.
-    fn tick(&mut self, cpu_state: &mut CpuState, memory: Rc<RefCell<MemController>>);
+    fn tick(&mut self, cpu_state: &mut CpuState, memory: &mut MemController);
 
     /// Returns true if this addressing mode has page crossing penalty for reads
     fn has_page_cross_penalty(&self) -> bool {
@@ -40,7 +38,7 @@ pub trait AddressingMode {
     }
 }
 
-pub trait InstructionType {
+pub trait InstructionType: Send {
     /// Returns true if the instruction has completed execution
     fn is_done(&self) -> bool;
 
@@ -48,7 +46,7 @@ pub trait InstructionType {
     fn tick(
         &mut self,
         cpu_state: &mut CpuState,
-        memory: Rc<RefCell<MemController>>,
+        memory: &mut MemController,
         addressing_mode: &dyn AddressingMode,
     );
 }
@@ -0,0 +1,277 @@
+use super::parse_address;
+use crate::cpu2::CpuState;
+use std::fmt;
+
+/// A CPU register that conditions can test and the debugger can edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    A,
+    X,
+    Y,
+    Sp,
+    P,
+    Pc,
+}
+
+impl Register {
+    /// Look up a register by name (`a`, `x`, `y`, `sp`/`s`, `p`, `pc`), ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "a" => Some(Register::A),
+            "x" => Some(Register::X),
+            "y" => Some(Register::Y),
+            "sp" | "s" => Some(Register::Sp),
+            "p" => Some(Register::P),
+            "pc" => Some(Register::Pc),
+            _ => None,
+        }
+    }
+
+    /// Read the register from a CPU state
+    pub fn get(&self, state: &CpuState) -> u16 {
+        match self {
+            Register::A => state.a as u16,
+            Register::X => state.x as u16,
+            Register::Y => state.y as u16,
+            Register::Sp => state.sp as u16,
+            Register::P => state.p as u16,
+            Register::Pc => state.pc,
+        }
+    }
+
+    /// Write the register in a CPU state
+    ///
+    /// Returns an error if the value does not fit an 8-bit register.
+    pub fn set(&self, state: &mut CpuState, value: u16) -> Result<(), String> {
+        if *self != Register::Pc && value > 0xFF {
+            return Err(format!("{} is an 8-bit register, got ${:X}", self, value));
+        }
+        match self {
+            Register::A => state.a = value as u8,
+            Register::X => state.x = value as u8,
+            Register::Y => state.y = value as u8,
+            Register::Sp => state.sp = value as u8,
+            Register::P => state.p = value as u8,
+            Register::Pc => state.pc = value,
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Register::A => "A",
+            Register::X => "X",
+            Register::Y => "Y",
+            Register::Sp => "SP",
+            Register::P => "P",
+            Register::Pc => "PC",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How a condition compares a register with its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    /// Operators in the order they must be matched (longest first)
+    const OPERATORS: [(&'static str, Comparison); 6] = [
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+
+    fn operator(&self) -> &'static str {
+        Self::OPERATORS
+            .iter()
+            .find(|(_, comparison)| comparison == self)
+            .map(|(operator, _)| *operator)
+            .unwrap_or("==")
+    }
+}
+
+/// A register comparison such as `A == $10`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    pub register: Register,
+    pub comparison: Comparison,
+    pub value: u16,
+}
+
+impl Condition {
+    /// Parse a condition like `a == $10`, `x>=3` or `pc != 0xC000`
+    ///
+    /// The value may also be a symbol looked up with `resolve`, like `pc == reset`.
+    pub fn parse(text: &str, resolve: &dyn Fn(&str) -> Option<u16>) -> Result<Self, String> {
+        let (index, operator, comparison) = Comparison::OPERATORS
+            .iter()
+            .filter_map(|(operator, comparison)| {
+                text.find(operator)
+                    .map(|index| (index, *operator, *comparison))
+            })
+            .min_by_key(|(index, operator, _)| (*index, usize::MAX - operator.len()))
+            .ok_or_else(|| format!("Condition \"{}\" has no comparison operator", text))?;
+
+        let name = text[..index].trim();
+        let register = Register::from_name(name)
+            .ok_or_else(|| format!("Unknown register \"{}\" in condition", name))?;
+        let value = parse_address(text[index + operator.len()..].trim(), resolve)?;
+        Ok(Self {
+            register,
+            comparison,
+            value,
+        })
+    }
+
+    /// Check the condition against a CPU state
+    pub fn is_met(&self, state: &CpuState) -> bool {
+        let register = self.register.get(state);
+        match self.comparison {
+            Comparison::Equal => register == self.value,
+            Comparison::NotEqual => register != self.value,
+            Comparison::Less => register < self.value,
+            Comparison::LessOrEqual => register <= self.value,
+            Comparison::Greater => register > self.value,
+            Comparison::GreaterOrEqual => register >= self.value,
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} ${:02X}",
+            self.register,
+            self.comparison.operator(),
+            self.value
+        )
+    }
+}
+
+/// Stops execution before the instruction at an address, when a condition holds, or both
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Breakpoint {
+    /// Program counter to stop at, or `None` to stop at any address
+    pub addr: Option<u16>,
+    /// Register condition that must also hold
+    pub condition: Option<Condition>,
+}
+
+impl Breakpoint {
+    /// Break before executing the instruction at `addr`
+    pub fn at(addr: u16) -> Self {
+        Self {
+            addr: Some(addr),
+            condition: None,
+        }
+    }
+
+    /// Break before any instruction once `condition` holds
+    pub fn when(condition: Condition) -> Self {
+        Self {
+            addr: None,
+            condition: Some(condition),
+        }
+    }
+
+    /// Only break when `condition` also holds
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// Check whether the breakpoint triggers for the CPU state at an instruction boundary
+    pub fn is_hit(&self, state: &CpuState) -> bool {
+        self.addr.is_none_or(|addr| addr == state.pc)
+            && self
+                .condition
+                .is_none_or(|condition| condition.is_met(state))
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.addr, self.condition) {
+            (Some(addr), Some(condition)) => write!(f, "${:04X} if {}", addr, condition),
+            (Some(addr), None) => write!(f, "${:04X}", addr),
+            (None, Some(condition)) => write!(f, "if {}", condition),
+            (None, None) => write!(f, "always"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(pc: u16, a: u8) -> CpuState {
+        CpuState {
+            pc,
+            a,
+            ..CpuState::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_condition() {
+        let condition = Condition::parse("a == $10", &|_| None).unwrap();
+        assert_eq!(condition.register, Register::A);
+        assert_eq!(condition.comparison, Comparison::Equal);
+        assert_eq!(condition.value, 0x10);
+
+        let condition = Condition::parse("X>=3", &|_| None).unwrap();
+        assert_eq!(condition.register, Register::X);
+        assert_eq!(condition.comparison, Comparison::GreaterOrEqual);
+        assert_eq!(condition.value, 3);
+
+        let condition = Condition::parse("pc < 0xC000", &|_| None).unwrap();
+        assert_eq!(condition.comparison, Comparison::Less);
+        assert_eq!(condition.value, 0xC000);
+    }
+
+    #[test]
+    fn test_parse_condition_errors() {
+        assert!(Condition::parse("a 10", &|_| None).is_err());
+        assert!(Condition::parse("q == 1", &|_| None).is_err());
+        assert!(Condition::parse("a == zz", &|_| None).is_err());
+    }
+
+    #[test]
+    fn test_breakpoint_hit() {
+        let plain = Breakpoint::at(0xC000);
+        assert!(plain.is_hit(&state(0xC000, 0)));
+        assert!(!plain.is_hit(&state(0xC001, 0)));
+
+        let conditional = plain.with_condition(Condition::parse("a != 0", &|_| None).unwrap());
+        assert!(!conditional.is_hit(&state(0xC000, 0)));
+        assert!(conditional.is_hit(&state(0xC000, 1)));
+
+        let anywhere = Breakpoint::when(Condition::parse("a > $7F", &|_| None).unwrap());
+        assert!(anywhere.is_hit(&state(0x1234, 0x80)));
+        assert!(!anywhere.is_hit(&state(0x1234, 0x7F)));
+    }
+
+    #[test]
+    fn test_set_register() {
+        let mut cpu = CpuState::default();
+        Register::Pc.set(&mut cpu, 0x8000).unwrap();
+        Register::Sp.set(&mut cpu, 0xFD).unwrap();
+        assert_eq!(cpu.pc, 0x8000);
+        assert_eq!(cpu.sp, 0xFD);
+        assert!(Register::A.set(&mut cpu, 0x100).is_err());
+    }
+}
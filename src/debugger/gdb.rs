@@ -0,0 +1,671 @@
+//! GDB remote serial protocol server
+//!
+//! Lets GDB, or any frontend that speaks its remote protocol, debug the 6502
+//! over TCP. Breakpoints, watchpoints and run control go through the same
+//! `Nes` methods as the terminal debugger. GDB has no built-in 6502 support,
+//! so the register layout is sent as a target description: `a`, `x`, `y`, `p`
+//! and `sp` as bytes, then `pc` as a little-endian word.
+//!
+//! Supported packets: `?`, `g`, `G`, `p`, `P`, `m`, `M`, `c`, `s`, `Z0`-`Z4`,
+//! `z0`-`z4`, `D`, `k`, `qSupported`, `qXfer:features:read`, `QStartNoAckMode`
+//! and the thread queries GDB sends on connect. Anything else gets the empty
+//! reply that tells GDB a packet is not supported.
+
+use super::{Access, Breakpoint, Bus, Register, StopReason, Watchpoint};
+use crate::nes::Nes;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+/// Registers in GDB's numbering
+const REGISTERS: [Register; 6] = [
+    Register::A,
+    Register::X,
+    Register::Y,
+    Register::P,
+    Register::Sp,
+    Register::Pc,
+];
+
+/// Target description naming the registers for GDB
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.neser.mos6502">
+    <reg name="a" bitsize="8" type="uint8" regnum="0"/>
+    <reg name="x" bitsize="8" type="uint8"/>
+    <reg name="y" bitsize="8" type="uint8"/>
+    <reg name="p" bitsize="8" type="uint8"/>
+    <reg name="sp" bitsize="8" type="uint8"/>
+    <reg name="pc" bitsize="16" type="code_ptr"/>
+  </feature>
+</target>
+"#;
+
+/// Largest packet we accept, advertised to the client
+const PACKET_SIZE: usize = 0x1000;
+
+/// Instructions to run between checks for an interrupt from the client
+const INTERRUPT_POLL_INTERVAL: u32 = 1000;
+
+/// Byte the client sends to interrupt a running target (Ctrl-C)
+const INTERRUPT: u8 = 0x03;
+
+/// Signal numbers used in stop replies
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+
+/// Wait for a GDB connection on `listener` and serve it
+///
+/// Returns when the client detaches, kills the target or disconnects. The
+/// target starts stopped at the current instruction.
+pub fn run_gdb_server(nes: &mut Nes, listener: &TcpListener) -> io::Result<()> {
+    let (stream, _) = listener.accept()?;
+    stream.set_nodelay(true)?;
+    let mut session = Session {
+        connection: Connection::new(stream),
+        points: HashMap::new(),
+    };
+    session.run(nes)
+}
+
+/// What to do after handling a packet
+enum Response {
+    Reply(String),
+    /// Reply `OK` and close the connection
+    Detach,
+    /// Close the connection without replying
+    Kill,
+}
+
+/// Packet framing over a TCP stream
+struct Connection {
+    stream: TcpStream,
+    /// Bytes received but not processed yet
+    pending: VecDeque<u8>,
+    /// Whether `+`/`-` acknowledgements are turned off
+    no_ack: bool,
+    /// Whether the client closed the connection
+    closed: bool,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            pending: VecDeque::new(),
+            no_ack: false,
+            closed: false,
+        }
+    }
+
+    /// Read the next byte, or `None` once the client has disconnected
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        if self.pending.is_empty() && !self.closed {
+            self.fill()?;
+        }
+        Ok(self.pending.pop_front())
+    }
+
+    /// Read whatever the client has sent into `pending`
+    fn fill(&mut self) -> io::Result<()> {
+        let mut buffer = [0; 1024];
+        let count = self.stream.read(&mut buffer)?;
+        if count == 0 {
+            self.closed = true;
+        }
+        self.pending.extend(&buffer[..count]);
+        Ok(())
+    }
+
+    /// Read the next packet, acknowledging it
+    ///
+    /// Acknowledgements and interrupts sent while the target is stopped are
+    /// skipped. Packets with a bad checksum are rejected with `-`, which makes
+    /// the client resend them. Returns `None` once the client has disconnected.
+    fn read_packet(&mut self) -> io::Result<Option<String>> {
+        loop {
+            match self.read_byte()? {
+                None => return Ok(None),
+                Some(b'$') => {}
+                Some(_) => continue,
+            }
+            let mut data = Vec::new();
+            loop {
+                match self.read_byte()? {
+                    None => return Ok(None),
+                    Some(b'#') => break,
+                    Some(byte) => data.push(byte),
+                }
+            }
+            let mut checksum = [0; 2];
+            for digit in &mut checksum {
+                *digit = match self.read_byte()? {
+                    Some(byte) => byte,
+                    None => return Ok(None),
+                };
+            }
+            let valid = std::str::from_utf8(&checksum)
+                .ok()
+                .and_then(|text| u8::from_str_radix(text, 16).ok())
+                == Some(checksum_of(&data));
+            if !self.no_ack {
+                self.stream.write_all(if valid { b"+" } else { b"-" })?;
+            }
+            if valid {
+                return Ok(Some(String::from_utf8_lossy(&data).into_owned()));
+            }
+        }
+    }
+
+    fn send_packet(&mut self, data: &str) -> io::Result<()> {
+        let packet = format!("${}#{:02x}", data, checksum_of(data.as_bytes()));
+        self.stream.write_all(packet.as_bytes())
+    }
+
+    /// Check, without blocking, whether the client sent an interrupt
+    ///
+    /// A disconnect or I/O error also counts as an interrupt, so execution
+    /// stops and the session can end.
+    fn interrupt_requested(&mut self) -> bool {
+        if !self.pending.contains(&INTERRUPT) && !self.closed {
+            let filled = self
+                .stream
+                .set_nonblocking(true)
+                .and_then(|()| self.fill())
+                .and_then(|()| self.stream.set_nonblocking(false));
+            match filled {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    return self.stream.set_nonblocking(false).is_err();
+                }
+                Err(_) => return true,
+            }
+        }
+        match self.pending.iter().position(|&byte| byte == INTERRUPT) {
+            Some(index) => {
+                self.pending.drain(..=index);
+                true
+            }
+            None => self.closed,
+        }
+    }
+}
+
+fn checksum_of(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, &byte| sum.wrapping_add(byte))
+}
+
+/// A GDB breakpoint or watchpoint: type (0-4), address and kind/length
+type PointKey = (u8, u16, u16);
+
+struct Session {
+    connection: Connection,
+    /// Debugger ids of the breakpoints and watchpoints GDB has set
+    points: HashMap<PointKey, usize>,
+}
+
+impl Session {
+    fn run(&mut self, nes: &mut Nes) -> io::Result<()> {
+        while let Some(packet) = self.connection.read_packet()? {
+            let response = self.handle(nes, &packet);
+            // The client may have disconnected while the target was running
+            if self.connection.closed {
+                return Ok(());
+            }
+            match response {
+                Response::Reply(reply) => self.connection.send_packet(&reply)?,
+                Response::Detach => return self.connection.send_packet("OK"),
+                Response::Kill => return Ok(()),
+            }
+            if packet == "QStartNoAckMode" {
+                self.connection.no_ack = true;
+            }
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, nes: &mut Nes, packet: &str) -> Response {
+        let (command, args) = if packet.is_char_boundary(1) {
+            packet.split_at(1)
+        } else {
+            ("", packet)
+        };
+        let reply = match (command, args) {
+            ("?", _) => format!("S{:02x}", SIGTRAP),
+            ("g", _) => REGISTERS
+                .iter()
+                .map(|register| register_hex(nes, *register))
+                .collect(),
+            ("G", values) => write_registers(nes, values),
+            ("p", number) => parse_register(number)
+                .map(|register| register_hex(nes, register))
+                .unwrap_or_else(|| "E01".to_string()),
+            ("P", assignment) => write_register(nes, assignment),
+            ("m", args) => read_memory(nes, args),
+            ("M", args) => write_memory(nes, args),
+            ("c", addr) => {
+                if let Some(reply) = jump(nes, addr) {
+                    return Response::Reply(reply);
+                }
+                let connection = &mut self.connection;
+                let mut count: u32 = 0;
+                let reason = nes.continue_until_interrupted(|| {
+                    count += 1;
+                    count.is_multiple_of(INTERRUPT_POLL_INTERVAL)
+                        && connection.interrupt_requested()
+                });
+                self.stop_reply(nes, reason)
+            }
+            ("s", addr) => {
+                if let Some(reply) = jump(nes, addr) {
+                    return Response::Reply(reply);
+                }
+                let reason = nes.step_instruction();
+                self.stop_reply(nes, reason)
+            }
+            ("Z", args) => self.insert_point(nes, args),
+            ("z", args) => self.remove_point(nes, args),
+            ("D", _) => return Response::Detach,
+            ("k", _) => return Response::Kill,
+            ("H", _) | ("T", _) => "OK".to_string(),
+            _ => query(packet),
+        };
+        Response::Reply(reply)
+    }
+
+    /// Stop reply for why execution stopped
+    fn stop_reply(&self, nes: &Nes, reason: StopReason) -> String {
+        match reason {
+            StopReason::Watchpoint(hit) if hit.access != Access::Execute => {
+                let watchpoint = nes
+                    .debugger()
+                    .watchpoints()
+                    .iter()
+                    .find(|(id, _)| *id == hit.id)
+                    .map(|(_, watchpoint)| *watchpoint);
+                let kind = match watchpoint {
+                    Some(watchpoint) if watchpoint.read && watchpoint.write => "awatch",
+                    _ if hit.access == Access::Read => "rwatch",
+                    _ => "watch",
+                };
+                format!("T{:02x}{}:{:04x};", SIGTRAP, kind, hit.addr)
+            }
+            StopReason::Interrupted => format!("S{:02x}", SIGINT),
+            StopReason::Halted => format!("S{:02x}", SIGILL),
+            _ => format!("S{:02x}", SIGTRAP),
+        }
+    }
+
+    /// Handle `Z<type>,<addr>,<kind>`
+    fn insert_point(&mut self, nes: &mut Nes, args: &str) -> String {
+        let Some(key) = parse_point(args) else {
+            return "E01".to_string();
+        };
+        if self.points.contains_key(&key) {
+            return "OK".to_string();
+        }
+        let (kind, addr, len) = key;
+        let id = match kind {
+            0 | 1 => nes.add_breakpoint(Breakpoint::at(addr)),
+            2..=4 => {
+                let accesses: &[Access] = match kind {
+                    2 => &[Access::Write],
+                    3 => &[Access::Read],
+                    _ => &[Access::Read, Access::Write],
+                };
+                let end = addr.checked_add(len.max(1) - 1);
+                match end.and_then(|end| Watchpoint::new(Bus::Cpu, addr, end, accesses).ok()) {
+                    Some(watchpoint) => nes.add_watchpoint(watchpoint),
+                    None => return "E01".to_string(),
+                }
+            }
+            _ => return String::new(),
+        };
+        self.points.insert(key, id);
+        "OK".to_string()
+    }
+
+    /// Handle `z<type>,<addr>,<kind>`
+    fn remove_point(&mut self, nes: &mut Nes, args: &str) -> String {
+        match parse_point(args).and_then(|key| self.points.remove(&key)) {
+            Some(id) => {
+                nes.remove_breakpoint(id);
+                "OK".to_string()
+            }
+            None => "E01".to_string(),
+        }
+    }
+}
+
+/// Reply to the `q`/`Q`/`v` packets GDB needs answered
+fn query(packet: &str) -> String {
+    if packet.starts_with("qSupported") {
+        return format!(
+            "PacketSize={:x};qXfer:features:read+;QStartNoAckMode+",
+            PACKET_SIZE
+        );
+    }
+    if let Some(args) = packet.strip_prefix("qXfer:features:read:target.xml:") {
+        let Some((offset, length)) = args.split_once(',').and_then(|(offset, length)| {
+            let offset = usize::from_str_radix(offset, 16).ok()?;
+            let length = usize::from_str_radix(length, 16).ok()?;
+            Some((offset, length))
+        }) else {
+            return "E01".to_string();
+        };
+        let start = offset.min(TARGET_XML.len());
+        let end = start.saturating_add(length).min(TARGET_XML.len());
+        let more = if end < TARGET_XML.len() { 'm' } else { 'l' };
+        return format!("{}{}", more, &TARGET_XML[start..end]);
+    }
+    match packet {
+        "QStartNoAckMode" => "OK",
+        "qAttached" => "1",
+        "qC" => "QC1",
+        "qfThreadInfo" => "m1",
+        "qsThreadInfo" => "l",
+        _ => "",
+    }
+    .to_string()
+}
+
+/// Parse a register number in hex
+fn parse_register(number: &str) -> Option<Register> {
+    let number = usize::from_str_radix(number, 16).ok()?;
+    REGISTERS.get(number).copied()
+}
+
+/// A register's value as little-endian hex bytes
+fn register_hex(nes: &mut Nes, register: Register) -> String {
+    let value = register.get(nes.cpu.get_state());
+    if register == Register::Pc {
+        hex(&value.to_le_bytes())
+    } else {
+        format!("{:02x}", value)
+    }
+}
+
+/// Handle `G`: all registers in the order of `g`
+fn write_registers(nes: &mut Nes, values: &str) -> String {
+    let Some(bytes) = parse_hex_bytes(values).filter(|bytes| bytes.len() == 7) else {
+        return "E01".to_string();
+    };
+    let state = nes.cpu.get_state();
+    state.a = bytes[0];
+    state.x = bytes[1];
+    state.y = bytes[2];
+    state.p = bytes[3];
+    state.sp = bytes[4];
+    state.pc = u16::from_le_bytes([bytes[5], bytes[6]]);
+    "OK".to_string()
+}
+
+/// Handle `P<n>=<value>`
+fn write_register(nes: &mut Nes, assignment: &str) -> String {
+    let parsed = assignment.split_once('=').and_then(|(number, value)| {
+        let register = parse_register(number)?;
+        let bytes = parse_hex_bytes(value)?;
+        let value = match (register, bytes.as_slice()) {
+            (Register::Pc, [low, high]) => u16::from_le_bytes([*low, *high]),
+            (Register::Pc, _) => return None,
+            (_, [byte]) => *byte as u16,
+            _ => return None,
+        };
+        Some((register, value))
+    });
+    match parsed {
+        Some((register, value)) if register.set(nes.cpu.get_state(), value).is_ok() => {
+            "OK".to_string()
+        }
+        _ => "E01".to_string(),
+    }
+}
+
+/// Parse `<addr>,<length>`, rejecting ranges that leave the address space
+fn parse_range(args: &str) -> Option<(u16, usize)> {
+    let (addr, length) = args.split_once(',')?;
+    let addr = u16::from_str_radix(addr, 16).ok()?;
+    let length = usize::from_str_radix(length, 16).ok()?;
+    (addr as usize + length <= 0x10000).then_some((addr, length))
+}
+
+/// Handle `m<addr>,<length>`
+///
+/// Memory is read without side effects (see `Nes::peek`), so registers show
+/// the open bus value.
+fn read_memory(nes: &Nes, args: &str) -> String {
+    match parse_range(args) {
+        Some((addr, length)) if length * 2 <= PACKET_SIZE => {
+            let bytes: Vec<u8> = (0..length)
+                .map(|offset| nes.peek(addr.wrapping_add(offset as u16)))
+                .collect();
+            hex(&bytes)
+        }
+        _ => "E01".to_string(),
+    }
+}
+
+/// Handle `M<addr>,<length>:<bytes>`
+fn write_memory(nes: &mut Nes, args: &str) -> String {
+    let parsed = args.split_once(':').and_then(|(range, data)| {
+        let (addr, length) = parse_range(range)?;
+        let bytes = parse_hex_bytes(data)?;
+        (bytes.len() == length).then_some((addr, bytes))
+    });
+    let Some((addr, bytes)) = parsed else {
+        return "E01".to_string();
+    };
+    for (offset, byte) in bytes.iter().enumerate() {
+        nes.poke(addr.wrapping_add(offset as u16), *byte);
+    }
+    "OK".to_string()
+}
+
+/// Set PC for `c<addr>` and `s<addr>`
+///
+/// Returns an error reply for an invalid address.
+fn jump(nes: &mut Nes, addr: &str) -> Option<String> {
+    if addr.is_empty() {
+        return None;
+    }
+    match u16::from_str_radix(addr, 16) {
+        Ok(addr) => {
+            nes.cpu.get_state().pc = addr;
+            None
+        }
+        Err(_) => Some("E01".to_string()),
+    }
+}
+
+/// Parse `<type>,<addr>,<kind>` of `Z` and `z` packets
+fn parse_point(args: &str) -> Option<PointKey> {
+    let mut fields = args.split(';').next()?.split(',');
+    let kind = fields.next()?.parse().ok()?;
+    let addr = u16::from_str_radix(fields.next()?, 16).ok()?;
+    let len = u16::from_str_radix(fields.next()?, 16).ok()?;
+    Some((kind, addr, len))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn parse_hex_bytes(text: &str) -> Option<Vec<u8>> {
+    if !text.is_ascii() || !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nes::TvSystem;
+    use std::io::BufReader;
+    use std::thread;
+    use std::time::Duration;
+
+    /// Minimal GDB client that sends a packet and waits for the reply
+    struct Client {
+        reader: BufReader<TcpStream>,
+        stream: TcpStream,
+    }
+
+    impl Client {
+        fn connect(addr: std::net::SocketAddr) -> Self {
+            let stream = TcpStream::connect(addr).unwrap();
+            Self {
+                reader: BufReader::new(stream.try_clone().unwrap()),
+                stream,
+            }
+        }
+
+        fn send(&mut self, packet: &str) {
+            let framed = format!("${}#{:02x}", packet, checksum_of(packet.as_bytes()));
+            self.stream.write_all(framed.as_bytes()).unwrap();
+        }
+
+        fn request(&mut self, packet: &str) -> String {
+            self.send(packet);
+            self.reply()
+        }
+
+        /// Read the next reply packet, skipping acknowledgements
+        fn reply(&mut self) -> String {
+            let mut byte = [0];
+            loop {
+                self.reader.read_exact(&mut byte).unwrap();
+                if byte[0] == b'$' {
+                    break;
+                }
+            }
+            let mut data = Vec::new();
+            loop {
+                self.reader.read_exact(&mut byte).unwrap();
+                if byte[0] == b'#' {
+                    break;
+                }
+                data.push(byte[0]);
+            }
+            let mut checksum = [0; 2];
+            self.reader.read_exact(&mut checksum).unwrap();
+            let checksum = u8::from_str_radix(std::str::from_utf8(&checksum).unwrap(), 16);
+            assert_eq!(checksum, Ok(checksum_of(&data)));
+            self.stream.write_all(b"+").unwrap();
+            String::from_utf8(data).unwrap()
+        }
+    }
+
+    /// Run a scripted client against a server for `nes`
+    fn with_client(nes: &mut Nes, script: impl FnOnce(&mut Client) + Send + 'static) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = thread::spawn(move || script(&mut Client::connect(addr)));
+        run_gdb_server(nes, &listener).unwrap();
+        client.join().unwrap();
+    }
+
+    /// Load a program into RAM at $0200 and point PC at it, with IRQs masked
+    fn load_program(nes: &mut Nes, program: &[u8]) {
+        for (offset, byte) in program.iter().enumerate() {
+            nes.poke(0x0200 + offset as u16, *byte);
+        }
+        let state = nes.cpu.get_state();
+        state.pc = 0x0200;
+        state.sp = 0xFD;
+        state.p = 0x24;
+    }
+
+    #[test]
+    fn test_registers_memory_and_breakpoints() {
+        let mut nes = Nes::new(TvSystem::Ntsc);
+        // LDA #$42; STA $10; LDX $10; JMP $0206
+        load_program(
+            &mut nes,
+            &[0xA9, 0x42, 0x85, 0x10, 0xA6, 0x10, 0x4C, 0x06, 0x02],
+        );
+
+        with_client(&mut nes, |client| {
+            assert!(
+                client
+                    .request("qSupported:swbreak+")
+                    .contains("qXfer:features:read+")
+            );
+            assert!(
+                client
+                    .request("qXfer:features:read:target.xml:0,1000")
+                    .starts_with("l<?xml")
+            );
+            assert_eq!(client.request("?"), "S05");
+            assert_eq!(client.request("g"), "00000024fd0002");
+            assert_eq!(client.request("m0200,2"), "a942");
+            assert_eq!(client.request("M0300,2:beef"), "OK");
+            assert_eq!(client.request("m0300,2"), "beef");
+
+            assert_eq!(client.request("s"), "S05");
+            assert_eq!(client.request("p0"), "42");
+            assert_eq!(client.request("p5"), "0202");
+
+            assert_eq!(client.request("Z0,0204,1"), "OK");
+            assert_eq!(client.request("c"), "S05");
+            assert_eq!(client.request("p5"), "0402");
+            assert_eq!(client.request("z0,0204,1"), "OK");
+            assert_eq!(client.request("z0,0204,1"), "E01");
+
+            assert_eq!(client.request("P1=07"), "OK");
+            assert_eq!(client.request("G01020324fd0002"), "OK");
+            assert_eq!(client.request("g"), "01020324fd0002");
+            assert_eq!(client.request("vMustReplyEmpty"), "");
+            assert_eq!(client.request("D"), "OK");
+        });
+
+        assert_eq!(nes.cpu.get_state().pc, 0x0200);
+        assert!(nes.debugger().breakpoints().is_empty());
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        let mut nes = Nes::new(TvSystem::Ntsc);
+        // LDA #$42; STA $10; LDX $10; JMP $0206
+        load_program(
+            &mut nes,
+            &[0xA9, 0x42, 0x85, 0x10, 0xA6, 0x10, 0x4C, 0x06, 0x02],
+        );
+
+        with_client(&mut nes, |client| {
+            assert_eq!(client.request("Z2,0010,1"), "OK");
+            assert_eq!(client.request("c"), "T05watch:0010;");
+            assert_eq!(client.request("z2,0010,1"), "OK");
+            assert_eq!(client.request("Z3,0010,1"), "OK");
+            assert_eq!(client.request("c"), "T05rwatch:0010;");
+            assert_eq!(client.request("p5"), "0602");
+            assert_eq!(client.request("Z4,0000,10000"), "E01");
+            client.send("k");
+        });
+    }
+
+    #[test]
+    fn test_interrupt_and_bad_checksum() {
+        let mut nes = Nes::new(TvSystem::Ntsc);
+        // JMP $0200
+        load_program(&mut nes, &[0x4C, 0x00, 0x02]);
+
+        with_client(&mut nes, |client| {
+            client.stream.write_all(b"$g#00").unwrap();
+            let mut nack = [0];
+            client.reader.read_exact(&mut nack).unwrap();
+            assert_eq!(&nack, b"-");
+
+            client.send("c");
+            thread::sleep(Duration::from_millis(50));
+            client.stream.write_all(&[INTERRUPT]).unwrap();
+            assert_eq!(client.reply(), "S02");
+            assert_eq!(client.request("p5"), "0002");
+        });
+    }
+}
@@ -0,0 +1,225 @@
+//! Interactive debugger
+//!
+//! The `Debugger` holds breakpoints and watchpoints. Run control lives on `Nes`
+//! (`step_instruction`, `step_over`, `step_out`, `run_to_scanline`, `run_to_nmi`
+//! and `continue_execution`), which checks breakpoints and execute watchpoints at
+//! every instruction boundary. Read/write watchpoints are checked by the
+//! `MemController` as accesses happen. `run_terminal` drives all of it from
+//! text commands, and `run_gdb_server` from a GDB remote protocol client.
+
+pub mod breakpoint;
+pub mod gdb;
+pub mod terminal;
+pub mod watchpoint;
+
+pub use breakpoint::{Breakpoint, Condition, Register};
+pub use gdb::run_gdb_server;
+pub use terminal::run_terminal;
+pub use watchpoint::{Access, Bus, WatchHit, Watchpoint};
+
+use crate::cpu2::CpuState;
+use std::fmt;
+
+/// Why execution stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// The requested step completed
+    Step,
+    /// The breakpoint with this id triggered
+    Breakpoint(usize),
+    /// A watchpoint triggered
+    Watchpoint(WatchHit),
+    /// The PPU reached the requested scanline
+    Scanline(u16),
+    /// The CPU entered the NMI handler
+    Nmi,
+    /// The CPU executed a KIL opcode
+    Halted,
+    /// The user interrupted execution
+    Interrupted,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StopReason::Step => write!(f, "Stepped"),
+            StopReason::Breakpoint(id) => write!(f, "Breakpoint {}", id),
+            StopReason::Watchpoint(hit) => write!(f, "Watchpoint {}: {}", hit.id, hit),
+            StopReason::Scanline(scanline) => write!(f, "Reached scanline {}", scanline),
+            StopReason::Nmi => write!(f, "Entered NMI handler"),
+            StopReason::Halted => write!(f, "CPU halted"),
+            StopReason::Interrupted => write!(f, "Interrupted"),
+        }
+    }
+}
+
+/// Breakpoints and watchpoints, numbered from 1 in the order they were added
+#[derive(Debug, Default)]
+pub struct Debugger {
+    breakpoints: Vec<(usize, Breakpoint)>,
+    watchpoints: Vec<(usize, Watchpoint)>,
+    last_id: usize,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a breakpoint and return its id
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> usize {
+        self.last_id += 1;
+        self.breakpoints.push((self.last_id, breakpoint));
+        self.last_id
+    }
+
+    /// Add a watchpoint and return its id
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) -> usize {
+        self.last_id += 1;
+        self.watchpoints.push((self.last_id, watchpoint));
+        self.last_id
+    }
+
+    /// Remove the breakpoint or watchpoint with the given id
+    ///
+    /// Returns false if there is no such id.
+    pub fn remove(&mut self, id: usize) -> bool {
+        let count = self.breakpoints.len() + self.watchpoints.len();
+        self.breakpoints.retain(|(bp_id, _)| *bp_id != id);
+        self.watchpoints.retain(|(wp_id, _)| *wp_id != id);
+        count != self.breakpoints.len() + self.watchpoints.len()
+    }
+
+    /// Breakpoints with their ids
+    pub fn breakpoints(&self) -> &[(usize, Breakpoint)] {
+        &self.breakpoints
+    }
+
+    /// Watchpoints with their ids
+    pub fn watchpoints(&self) -> &[(usize, Watchpoint)] {
+        &self.watchpoints
+    }
+
+    /// Check breakpoints and execute watchpoints before running the instruction at PC
+    ///
+    /// # Arguments
+    /// * `state` - CPU state at the instruction boundary
+    /// * `opcode` - Opcode at PC, reported by execute watchpoints
+    pub fn check(&self, state: &CpuState, opcode: u8) -> Option<StopReason> {
+        if let Some((id, _)) = self.breakpoints.iter().find(|(_, bp)| bp.is_hit(state)) {
+            return Some(StopReason::Breakpoint(*id));
+        }
+        self.watchpoints
+            .iter()
+            .find(|(_, wp)| wp.matches(Bus::Cpu, state.pc, Access::Execute))
+            .map(|(id, _)| {
+                StopReason::Watchpoint(WatchHit {
+                    id: *id,
+                    bus: Bus::Cpu,
+                    addr: state.pc,
+                    value: opcode,
+                    access: Access::Execute,
+                })
+            })
+    }
+}
+
+/// Parse a number as `$hex`, `0xhex` or decimal
+pub(crate) fn parse_value(text: &str) -> Result<u16, String> {
+    let parsed = if let Some(hex) = text.strip_prefix('$') {
+        u16::from_str_radix(hex, 16)
+    } else if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        u16::from_str_radix(hex, 16)
+    } else {
+        text.parse()
+    };
+    parsed.map_err(|_| format!("Invalid number \"{}\" (use $hex, 0xhex or decimal)", text))
+}
+
+/// Parse an address as a number, a symbol name, or a symbol plus an offset (`table+2`)
+///
+/// # Arguments
+/// * `text` - The address to parse
+/// * `resolve` - Looks up a symbol's CPU address
+pub(crate) fn parse_address(
+    text: &str,
+    resolve: &dyn Fn(&str) -> Option<u16>,
+) -> Result<u16, String> {
+    if text.starts_with(|c: char| c == '$' || c.is_ascii_digit()) {
+        return parse_value(text);
+    }
+    let (name, offset) = match text.split_once('+') {
+        Some((name, offset)) => (name, parse_value(offset)?),
+        None => (text, 0),
+    };
+    resolve(name)
+        .map(|addr| addr.wrapping_add(offset))
+        .ok_or_else(|| format!("Unknown symbol \"{}\"", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("$C000"), Ok(0xC000));
+        assert_eq!(parse_value("0x10"), Ok(0x10));
+        assert_eq!(parse_value("16"), Ok(16));
+        assert!(parse_value("$10000").is_err());
+        assert!(parse_value("C000").is_err());
+    }
+
+    #[test]
+    fn test_parse_address() {
+        let resolve = |name: &str| (name == "reset").then_some(0xC000);
+        assert_eq!(parse_address("$8000", &resolve), Ok(0x8000));
+        assert_eq!(parse_address("reset", &resolve), Ok(0xC000));
+        assert_eq!(parse_address("reset+$10", &resolve), Ok(0xC010));
+        assert!(parse_address("missing", &resolve).is_err());
+    }
+
+    #[test]
+    fn test_ids_are_shared_and_removable() {
+        let mut debugger = Debugger::new();
+        let bp = debugger.add_breakpoint(Breakpoint::at(0x8000));
+        let wp =
+            debugger.add_watchpoint(Watchpoint::new(Bus::Cpu, 0, 0, &[Access::Write]).unwrap());
+        assert_eq!((bp, wp), (1, 2));
+
+        assert!(debugger.remove(bp));
+        assert!(!debugger.remove(bp));
+        assert!(debugger.breakpoints().is_empty());
+        assert_eq!(debugger.watchpoints().len(), 1);
+    }
+
+    #[test]
+    fn test_check_breakpoints_and_execute_watchpoints() {
+        let mut debugger = Debugger::new();
+        debugger.add_breakpoint(Breakpoint::at(0x8000));
+        debugger
+            .add_watchpoint(Watchpoint::new(Bus::Cpu, 0x9000, 0x90FF, &[Access::Execute]).unwrap());
+
+        let mut state = CpuState {
+            pc: 0x8000,
+            ..CpuState::default()
+        };
+        assert_eq!(
+            debugger.check(&state, 0xEA),
+            Some(StopReason::Breakpoint(1))
+        );
+
+        state.pc = 0x9010;
+        match debugger.check(&state, 0xEA) {
+            Some(StopReason::Watchpoint(hit)) => {
+                assert_eq!(hit.id, 2);
+                assert_eq!(hit.access, Access::Execute);
+                assert_eq!(hit.value, 0xEA);
+            }
+            other => panic!("Expected execute watchpoint, got {:?}", other),
+        }
+
+        state.pc = 0x8001;
+        assert_eq!(debugger.check(&state, 0xEA), None);
+    }
+}
@@ -0,0 +1,560 @@
+use super::{
+    Access, Breakpoint, Bus, Condition, Register, StopReason, Watchpoint, parse_address,
+    parse_value,
+};
+use crate::disasm::{self, Labels};
+use crate::nes::Nes;
+use std::io::{self, BufRead, Write};
+
+/// A command of the terminal debugger
+#[derive(Debug, Clone, PartialEq)]
+pub enum DebugCommand {
+    /// Execute this many instructions
+    Step(u32),
+    /// Step over subroutine calls
+    Next,
+    /// Run until the current subroutine returns
+    Finish,
+    /// Run until something stops execution
+    Continue,
+    /// Run until the PPU enters a scanline
+    RunToScanline(u16),
+    /// Run until the CPU enters the NMI handler
+    RunToNmi,
+    Break(Breakpoint),
+    Watch(Watchpoint),
+    /// Remove a breakpoint or watchpoint by id
+    Delete(usize),
+    /// List breakpoints and watchpoints
+    List,
+    Registers,
+    SetRegister(Register, u16),
+    /// Disassemble CPU memory, from PC when no address is given
+    Disassemble {
+        addr: Option<u16>,
+        count: usize,
+    },
+    /// Dump CPU memory
+    Examine {
+        addr: u16,
+        len: u16,
+    },
+    /// Write bytes to CPU memory
+    Poke {
+        addr: u16,
+        bytes: Vec<u8>,
+    },
+    Help,
+    Quit,
+}
+
+/// Help text for the terminal debugger
+const HELP: &str = "Commands:
+  s, step [N]                 Execute N instructions (default 1)
+  n, next                     Step over JSR
+  finish                      Run until the current subroutine or handler returns
+  c, continue                 Run until a breakpoint or watchpoint triggers
+  scanline <N>                Run until the PPU enters scanline N
+  nmi                         Run until the CPU enters the NMI handler
+  b, break <ADDR> [if <COND>] Break at an address, optionally when COND holds
+  b, break if <COND>          Break anywhere when COND holds, e.g. \"x >= $10\"
+  watch [r|w|rw|x] <RANGE>    Watch CPU addresses (default w), e.g. $0300-$03FF
+  pwatch [r|w|rw] <RANGE>     Watch PPU addresses accessed through $2007
+  d, delete <ID>              Remove a breakpoint or watchpoint
+  l, list                     List breakpoints and watchpoints
+  r, regs                     Show registers
+  set <REG> <VALUE>           Set A, X, Y, SP, P or PC
+  dis [ADDR] [COUNT]          Disassemble COUNT instructions (default PC, 10)
+  x <ADDR> [LEN]              Dump CPU memory (default 16 bytes)
+  poke <ADDR> <BYTE>...       Write bytes to CPU memory
+  h, help                     Show this help
+  q, quit                     Leave the debugger
+Numbers are $hex, 0xhex or decimal. Addresses and register values can also be
+symbol names, optionally plus an offset (table+2). An empty line repeats the last command.";
+
+/// Parse one line of debugger input
+///
+/// Returns `Ok(None)` for an empty line.
+///
+/// # Arguments
+/// * `line` - The input line
+/// * `resolve` - Looks up the CPU address of a symbol name
+pub fn parse_command(
+    line: &str,
+    resolve: &dyn Fn(&str) -> Option<u16>,
+) -> Result<Option<DebugCommand>, String> {
+    let address = |text: &str| parse_address(text, resolve);
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let Some((&name, args)) = words.split_first() else {
+        return Ok(None);
+    };
+
+    let command = match name.to_lowercase().as_str() {
+        "s" | "step" => match args {
+            [] => DebugCommand::Step(1),
+            [count] => DebugCommand::Step(
+                count
+                    .parse()
+                    .map_err(|_| format!("Invalid step count \"{}\"", count))?,
+            ),
+            _ => return Err("Usage: step [N]".to_string()),
+        },
+        "n" | "next" => DebugCommand::Next,
+        "finish" => DebugCommand::Finish,
+        "c" | "continue" => DebugCommand::Continue,
+        "scanline" => match args {
+            [scanline] => DebugCommand::RunToScanline(parse_value(scanline)?),
+            _ => return Err("Usage: scanline <N>".to_string()),
+        },
+        "nmi" => DebugCommand::RunToNmi,
+        "b" | "break" => DebugCommand::Break(parse_breakpoint(args, resolve)?),
+        "watch" => DebugCommand::Watch(parse_watchpoint(Bus::Cpu, args, resolve)?),
+        "pwatch" => DebugCommand::Watch(parse_watchpoint(Bus::Ppu, args, resolve)?),
+        "d" | "delete" => match args {
+            [id] => DebugCommand::Delete(
+                id.parse()
+                    .map_err(|_| format!("Invalid breakpoint id \"{}\"", id))?,
+            ),
+            _ => return Err("Usage: delete <ID>".to_string()),
+        },
+        "l" | "list" => DebugCommand::List,
+        "r" | "regs" => DebugCommand::Registers,
+        "set" => {
+            // Accept both "set a $10" and "set a=$10"
+            let assignment = args.join(" ").replace('=', " ");
+            match assignment.split_whitespace().collect::<Vec<_>>()[..] {
+                [register, value] => DebugCommand::SetRegister(
+                    Register::from_name(register)
+                        .ok_or_else(|| format!("Unknown register \"{}\"", register))?,
+                    address(value)?,
+                ),
+                _ => return Err("Usage: set <REG> <VALUE>".to_string()),
+            }
+        }
+        "dis" | "disasm" => {
+            let addr = args.first().map(|addr| address(addr)).transpose()?;
+            let count = match args.get(1) {
+                Some(count) => count
+                    .parse()
+                    .map_err(|_| format!("Invalid instruction count \"{}\"", count))?,
+                None => 10,
+            };
+            if args.len() > 2 {
+                return Err("Usage: dis [ADDR] [COUNT]".to_string());
+            }
+            DebugCommand::Disassemble { addr, count }
+        }
+        "x" => match args {
+            [addr] => DebugCommand::Examine {
+                addr: address(addr)?,
+                len: 16,
+            },
+            [addr, len] => DebugCommand::Examine {
+                addr: address(addr)?,
+                len: parse_value(len)?,
+            },
+            _ => return Err("Usage: x <ADDR> [LEN]".to_string()),
+        },
+        "poke" => match args {
+            [addr, bytes @ ..] if !bytes.is_empty() => DebugCommand::Poke {
+                addr: address(addr)?,
+                bytes: bytes
+                    .iter()
+                    .map(|byte| parse_byte(byte))
+                    .collect::<Result<_, _>>()?,
+            },
+            _ => return Err("Usage: poke <ADDR> <BYTE>...".to_string()),
+        },
+        "h" | "help" | "?" => DebugCommand::Help,
+        "q" | "quit" | "exit" => DebugCommand::Quit,
+        _ => return Err(format!("Unknown command \"{}\" (try help)", name)),
+    };
+    Ok(Some(command))
+}
+
+/// Parse `<ADDR> [if <COND>]` or `if <COND>`
+fn parse_breakpoint(
+    args: &[&str],
+    resolve: &dyn Fn(&str) -> Option<u16>,
+) -> Result<Breakpoint, String> {
+    let usage = || "Usage: break <ADDR> [if <COND>] or break if <COND>".to_string();
+    match args {
+        [addr] => Ok(Breakpoint::at(parse_address(addr, resolve)?)),
+        [keyword, condition @ ..]
+            if keyword.eq_ignore_ascii_case("if") && !condition.is_empty() =>
+        {
+            Ok(Breakpoint::when(Condition::parse(
+                &condition.join(" "),
+                resolve,
+            )?))
+        }
+        [addr, keyword, condition @ ..]
+            if keyword.eq_ignore_ascii_case("if") && !condition.is_empty() =>
+        {
+            Ok(Breakpoint::at(parse_address(addr, resolve)?)
+                .with_condition(Condition::parse(&condition.join(" "), resolve)?))
+        }
+        _ => Err(usage()),
+    }
+}
+
+/// Parse `[r|w|rw|x] <ADDR>[-<END>]`
+fn parse_watchpoint(
+    bus: Bus,
+    args: &[&str],
+    resolve: &dyn Fn(&str) -> Option<u16>,
+) -> Result<Watchpoint, String> {
+    let (mode, range) = match args {
+        [range] => ("w", *range),
+        [mode, range] => (*mode, *range),
+        _ => return Err("Usage: watch [r|w|rw|x] <ADDR>[-<END>]".to_string()),
+    };
+
+    let accesses = mode
+        .chars()
+        .map(|kind| match kind.to_ascii_lowercase() {
+            'r' => Ok(Access::Read),
+            'w' => Ok(Access::Write),
+            'x' => Ok(Access::Execute),
+            _ => Err(format!(
+                "Unknown watch mode \"{}\" (use r, w, rw or x)",
+                mode
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (start, end) = match range.split_once('-') {
+        Some((start, end)) => (parse_address(start, resolve)?, parse_address(end, resolve)?),
+        None => {
+            let addr = parse_address(range, resolve)?;
+            (addr, addr)
+        }
+    };
+    Watchpoint::new(bus, start, end, &accesses)
+}
+
+fn parse_byte(text: &str) -> Result<u8, String> {
+    let value = parse_value(text)?;
+    u8::try_from(value).map_err(|_| format!("${:X} does not fit in a byte", value))
+}
+
+/// Execute a command and return the text to show
+///
+/// Returns `None` for `Quit`.
+pub fn execute(nes: &mut Nes, command: &DebugCommand) -> Option<Result<String, String>> {
+    let result = match command {
+        DebugCommand::Step(count) => {
+            let mut reason = StopReason::Step;
+            for _ in 0..*count {
+                reason = nes.step_instruction();
+                if reason != StopReason::Step {
+                    break;
+                }
+            }
+            Ok(stopped(reason, nes))
+        }
+        DebugCommand::Next => Ok(stopped(nes.step_over(), nes)),
+        DebugCommand::Finish => Ok(stopped(nes.step_out(), nes)),
+        DebugCommand::Continue => Ok(stopped(nes.continue_execution(), nes)),
+        DebugCommand::RunToScanline(scanline) => {
+            let scanlines = nes.tv_system().scanlines_per_frame();
+            if *scanline < scanlines {
+                Ok(stopped(nes.run_to_scanline(*scanline), nes))
+            } else {
+                Err(format!("Scanline must be below {}", scanlines))
+            }
+        }
+        DebugCommand::RunToNmi => Ok(stopped(nes.run_to_nmi(), nes)),
+        DebugCommand::Break(breakpoint) => {
+            let id = nes.add_breakpoint(*breakpoint);
+            Ok(format!("Breakpoint {}: {}", id, breakpoint))
+        }
+        DebugCommand::Watch(watchpoint) => {
+            let id = nes.add_watchpoint(*watchpoint);
+            Ok(format!("Watchpoint {}: {}", id, watchpoint))
+        }
+        DebugCommand::Delete(id) => {
+            if nes.remove_breakpoint(*id) {
+                Ok(format!("Deleted {}", id))
+            } else {
+                Err(format!("No breakpoint or watchpoint {}", id))
+            }
+        }
+        DebugCommand::List => Ok(list(nes)),
+        DebugCommand::Registers => Ok(registers(nes)),
+        DebugCommand::SetRegister(register, value) => register
+            .set(nes.cpu.get_state(), *value)
+            .map(|()| registers(nes)),
+        DebugCommand::Disassemble { addr, count } => {
+            let addr = addr.unwrap_or(nes.cpu.get_state().pc);
+            let instructions = disasm::disassemble_count(addr, *count, |addr| nes.peek(addr));
+            Ok(disasm::listing(&instructions, &nes.labels()))
+        }
+        DebugCommand::Examine { addr, len } => Ok(dump(nes, *addr, *len)),
+        DebugCommand::Poke { addr, bytes } => {
+            for (offset, byte) in bytes.iter().enumerate() {
+                nes.poke(addr.wrapping_add(offset as u16), *byte);
+            }
+            Ok(dump(nes, *addr, bytes.len() as u16))
+        }
+        DebugCommand::Help => Ok(HELP.to_string()),
+        DebugCommand::Quit => return None,
+    };
+    Some(result)
+}
+
+/// Describe why execution stopped and show the next instruction
+fn stopped(reason: StopReason, nes: &mut Nes) -> String {
+    let pc = nes.cpu.get_state().pc;
+    let label = nes.labels().label(pc).map(|label| format!("{}:\n", label));
+    format!(
+        "{}\n{}{}",
+        reason,
+        label.unwrap_or_default(),
+        nes.trace(false)
+    )
+}
+
+fn list(nes: &Nes) -> String {
+    let debugger = nes.debugger();
+    let mut lines: Vec<(usize, String)> = debugger
+        .breakpoints()
+        .iter()
+        .map(|(id, bp)| (*id, format!("{:>3}  break  {}", id, bp)))
+        .chain(
+            debugger
+                .watchpoints()
+                .iter()
+                .map(|(id, wp)| (*id, format!("{:>3}  watch  {}", id, wp))),
+        )
+        .collect();
+    if lines.is_empty() {
+        return "No breakpoints or watchpoints".to_string();
+    }
+    lines.sort_by_key(|(id, _)| *id);
+    lines
+        .into_iter()
+        .map(|(_, line)| line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn registers(nes: &mut Nes) -> String {
+    let state = *nes.cpu.get_state();
+    let flags: String = "NV-BDIZC"
+        .chars()
+        .enumerate()
+        .map(|(bit, flag)| {
+            if state.p & (0x80 >> bit) != 0 {
+                flag
+            } else {
+                '.'
+            }
+        })
+        .collect();
+    let ppu = nes.ppu();
+    format!(
+        "PC:${:04X} A:${:02X} X:${:02X} Y:${:02X} SP:${:02X} P:${:02X} [{}] PPU:{},{} CYC:{}",
+        state.pc,
+        state.a,
+        state.x,
+        state.y,
+        state.sp,
+        state.p,
+        flags,
+        ppu.scanline(),
+        ppu.pixel(),
+        nes.cpu.total_cycles()
+    )
+}
+
+/// Format memory as rows of 16 bytes
+fn dump(nes: &Nes, addr: u16, len: u16) -> String {
+    let bytes: Vec<u8> = (0..len).map(|i| nes.peek(addr.wrapping_add(i))).collect();
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02X}", byte)).collect();
+            format!(
+                "${:04X}: {}",
+                addr.wrapping_add(row as u16 * 16),
+                hex.join(" ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Run the debugger command loop
+///
+/// Reads commands from `input` until `quit` or end of input, writing results
+/// to `output`. Execution starts stopped at the current instruction.
+pub fn run_terminal(nes: &mut Nes, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    writeln!(output, "{}", nes.trace(false))?;
+    write!(output, "(neser) ")?;
+    output.flush()?;
+
+    let mut last_command = None;
+    for line in input.lines() {
+        let command = match parse_command(&line?, &|name| nes.symbol_address(name)) {
+            Ok(Some(command)) => Some(command),
+            Ok(None) => last_command.clone(),
+            Err(message) => {
+                writeln!(output, "{}", message)?;
+                None
+            }
+        };
+
+        if let Some(command) = command {
+            match execute(nes, &command) {
+                None => return Ok(()),
+                Some(Ok(text)) => writeln!(output, "{}", text)?,
+                Some(Err(message)) => writeln!(output, "Error: {}", message)?,
+            }
+            last_command = Some(command);
+        }
+        write!(output, "(neser) ")?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nes::TvSystem;
+
+    fn parse(line: &str) -> DebugCommand {
+        parse_command(line, &|_| None).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse_command("   ", &|_| None).unwrap(), None);
+        assert_eq!(parse("s"), DebugCommand::Step(1));
+        assert_eq!(parse("step 10"), DebugCommand::Step(10));
+        assert_eq!(parse("scanline 241"), DebugCommand::RunToScanline(241));
+        assert_eq!(
+            parse("set pc=$C000"),
+            DebugCommand::SetRegister(Register::Pc, 0xC000)
+        );
+        assert_eq!(
+            parse("x $0300 32"),
+            DebugCommand::Examine {
+                addr: 0x0300,
+                len: 32
+            }
+        );
+        assert_eq!(
+            parse("poke $10 1 $FF"),
+            DebugCommand::Poke {
+                addr: 0x10,
+                bytes: vec![1, 0xFF]
+            }
+        );
+        assert_eq!(
+            parse("dis $C000 4"),
+            DebugCommand::Disassemble {
+                addr: Some(0xC000),
+                count: 4
+            }
+        );
+        assert!(parse_command("poke $10 $100", &|_| None).is_err());
+        assert!(parse_command("frobnicate", &|_| None).is_err());
+    }
+
+    #[test]
+    fn test_parse_breakpoints() {
+        assert_eq!(
+            parse("b $8000"),
+            DebugCommand::Break(Breakpoint::at(0x8000))
+        );
+        assert_eq!(
+            parse("break $8000 if a == 3"),
+            DebugCommand::Break(
+                Breakpoint::at(0x8000).with_condition(Condition::parse("a==3", &|_| None).unwrap())
+            )
+        );
+        assert_eq!(
+            parse("break if x>=$10"),
+            DebugCommand::Break(Breakpoint::when(
+                Condition::parse("x >= $10", &|_| None).unwrap()
+            ))
+        );
+        assert!(parse_command("break", &|_| None).is_err());
+    }
+
+    #[test]
+    fn test_parse_symbols() {
+        let resolve = |name: &str| match name {
+            "reset" => Some(0xC000),
+            "buffer" => Some(0x0300),
+            _ => None,
+        };
+        let parse = |line| parse_command(line, &resolve).unwrap().unwrap();
+        assert_eq!(
+            parse("break reset+2"),
+            DebugCommand::Break(Breakpoint::at(0xC002))
+        );
+        assert_eq!(
+            parse("break if pc == reset"),
+            DebugCommand::Break(Breakpoint::when(
+                Condition::parse("pc == $C000", &|_| None).unwrap()
+            ))
+        );
+        assert_eq!(
+            parse("watch buffer-buffer+$FF"),
+            DebugCommand::Watch(
+                Watchpoint::new(Bus::Cpu, 0x0300, 0x03FF, &[Access::Write]).unwrap()
+            )
+        );
+        assert_eq!(
+            parse("x buffer"),
+            DebugCommand::Examine {
+                addr: 0x0300,
+                len: 16
+            }
+        );
+        assert!(parse_command("break missing", &resolve).is_err());
+    }
+
+    #[test]
+    fn test_parse_watchpoints() {
+        assert_eq!(
+            parse("watch $0300-$03FF"),
+            DebugCommand::Watch(
+                Watchpoint::new(Bus::Cpu, 0x0300, 0x03FF, &[Access::Write]).unwrap()
+            )
+        );
+        assert_eq!(
+            parse("pwatch rw $2000"),
+            DebugCommand::Watch(
+                Watchpoint::new(Bus::Ppu, 0x2000, 0x2000, &[Access::Read, Access::Write]).unwrap()
+            )
+        );
+        assert!(parse_command("pwatch x $2000", &|_| None).is_err());
+        assert!(parse_command("watch q $10", &|_| None).is_err());
+    }
+
+    #[test]
+    fn test_terminal_session() {
+        let mut nes = Nes::new(TvSystem::Ntsc);
+        // LDA #$42; STA $10; NOP
+        for (offset, byte) in [0xA9, 0x42, 0x85, 0x10, 0xEA].iter().enumerate() {
+            nes.poke(0x0200 + offset as u16, *byte);
+        }
+        nes.cpu.get_state().pc = 0x0200;
+
+        let input = "dis $0200 2\nwatch $10\nc\nx $10 1\nset a 7\nq\n";
+        let mut output = Vec::new();
+        run_terminal(&mut nes, input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("0200  A9 42     LDA #$42\n0202  85 10     STA $10"));
+        assert!(output.contains("Watchpoint 1: cpu w $0010"));
+        assert!(output.contains("Watchpoint 1: cpu write $0010 = $42"));
+        assert!(output.contains("$0010: 42"));
+        assert!(output.contains("A:$07"));
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt;
+
+/// Address space a watchpoint observes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bus {
+    /// CPU address space ($0000-$FFFF)
+    Cpu,
+    /// PPU address space ($0000-$3FFF), as accessed through PPUDATA ($2007)
+    Ppu,
+}
+
+/// Kind of memory access
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    /// Instruction fetch, checked at instruction boundaries (CPU only)
+    Execute,
+}
+
+/// Stops execution when an address range is read, written or executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub bus: Bus,
+    /// First watched address
+    pub start: u16,
+    /// Last watched address (inclusive)
+    pub end: u16,
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+}
+
+impl Watchpoint {
+    /// Watch a range for the given kinds of access
+    ///
+    /// Returns an error for an empty range, PPU addresses above $3FFF, or
+    /// execute watchpoints on the PPU bus.
+    pub fn new(bus: Bus, start: u16, end: u16, accesses: &[Access]) -> Result<Self, String> {
+        if end < start {
+            return Err(format!(
+                "Watchpoint range ${:04X}-${:04X} is empty",
+                start, end
+            ));
+        }
+        if bus == Bus::Ppu && end > 0x3FFF {
+            return Err(format!("PPU address ${:04X} is above $3FFF", end));
+        }
+        if accesses.is_empty() {
+            return Err("Watchpoint needs at least one kind of access".to_string());
+        }
+        let execute = accesses.contains(&Access::Execute);
+        if bus == Bus::Ppu && execute {
+            return Err("PPU watchpoints cannot watch execution".to_string());
+        }
+        Ok(Self {
+            bus,
+            start,
+            end,
+            read: accesses.contains(&Access::Read),
+            write: accesses.contains(&Access::Write),
+            execute,
+        })
+    }
+
+    /// Check whether an access triggers this watchpoint
+    pub fn matches(&self, bus: Bus, addr: u16, access: Access) -> bool {
+        let watched = match access {
+            Access::Read => self.read,
+            Access::Write => self.write,
+            Access::Execute => self.execute,
+        };
+        watched && self.bus == bus && (self.start..=self.end).contains(&addr)
+    }
+}
+
+impl fmt::Display for Watchpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bus = match self.bus {
+            Bus::Cpu => "cpu",
+            Bus::Ppu => "ppu",
+        };
+        let mut kinds = String::new();
+        for (enabled, kind) in [(self.read, 'r'), (self.write, 'w'), (self.execute, 'x')] {
+            if enabled {
+                kinds.push(kind);
+            }
+        }
+        if self.start == self.end {
+            write!(f, "{} {} ${:04X}", bus, kinds, self.start)
+        } else {
+            write!(f, "{} {} ${:04X}-${:04X}", bus, kinds, self.start, self.end)
+        }
+    }
+}
+
+/// An access that triggered a watchpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchHit {
+    /// Id of the watchpoint that triggered
+    pub id: usize,
+    pub bus: Bus,
+    pub addr: u16,
+    /// Value read or written (the opcode for execute hits)
+    pub value: u8,
+    pub access: Access,
+}
+
+impl fmt::Display for WatchHit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bus = match self.bus {
+            Bus::Cpu => "cpu",
+            Bus::Ppu => "ppu",
+        };
+        let access = match self.access {
+            Access::Read => "read",
+            Access::Write => "write",
+            Access::Execute => "execute",
+        };
+        write!(
+            f,
+            "{} {} ${:04X} = ${:02X}",
+            bus, access, self.addr, self.value
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchpoint_matches_range_and_access() {
+        let watch = Watchpoint::new(Bus::Cpu, 0x0300, 0x03FF, &[Access::Write]).unwrap();
+        assert!(watch.matches(Bus::Cpu, 0x0300, Access::Write));
+        assert!(watch.matches(Bus::Cpu, 0x03FF, Access::Write));
+        assert!(!watch.matches(Bus::Cpu, 0x0400, Access::Write));
+        assert!(!watch.matches(Bus::Cpu, 0x0300, Access::Read));
+        assert!(!watch.matches(Bus::Ppu, 0x0300, Access::Write));
+    }
+
+    #[test]
+    fn test_invalid_watchpoints() {
+        assert!(Watchpoint::new(Bus::Cpu, 0x10, 0x0F, &[Access::Read]).is_err());
+        assert!(Watchpoint::new(Bus::Ppu, 0x3F00, 0x4000, &[Access::Read]).is_err());
+        assert!(Watchpoint::new(Bus::Ppu, 0x2000, 0x2000, &[Access::Execute]).is_err());
+        assert!(Watchpoint::new(Bus::Cpu, 0x10, 0x10, &[]).is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let watch =
+            Watchpoint::new(Bus::Ppu, 0x2000, 0x23FF, &[Access::Read, Access::Write]).unwrap();
+        assert_eq!(watch.to_string(), "ppu rw $2000-$23FF");
+    }
+}
@@ -0,0 +1,309 @@
+//! 6502 disassembler
+//!
+//! Decodes instructions from any byte source: CPU memory through
+//! `Nes::peek`, the current trace position, or a PRG-ROM bank that is not
+//! mapped in. Mnemonics come from the `cpu` opcode table, where unofficial
+//! opcodes carry a `*` prefix (`*NOP`, `*LAX`, ...).
+
+use crate::cpu;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Source of label names for operand addresses
+pub trait Labels {
+    /// Label for a CPU address, if there is one
+    fn label(&self, addr: u16) -> Option<&str>;
+}
+
+/// Disassemble without labels
+pub struct NoLabels;
+
+impl Labels for NoLabels {
+    fn label(&self, _addr: u16) -> Option<&str> {
+        None
+    }
+}
+
+impl Labels for HashMap<u16, String> {
+    fn label(&self, addr: u16) -> Option<&str> {
+        self.get(&addr).map(String::as_str)
+    }
+}
+
+/// A decoded instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    /// Address of the opcode
+    pub addr: u16,
+    pub opcode: u8,
+    /// Operand bytes; unused bytes are 0
+    pub operand: [u8; 2],
+    /// Mnemonic, with a `*` prefix for unofficial opcodes
+    pub mnemonic: &'static str,
+    /// Addressing mode, as in the `cpu` opcode table (`IMM`, `ZPX`, `INDY`, ...)
+    pub mode: &'static str,
+    /// Length in bytes, from the `cpu` opcode table
+    size: u8,
+}
+
+impl Instruction {
+    /// Length in bytes (1-3)
+    pub fn size(&self) -> u16 {
+        self.size as u16
+    }
+
+    /// Address of the following instruction
+    pub fn next_addr(&self) -> u16 {
+        self.addr.wrapping_add(self.size())
+    }
+
+    /// The 16-bit operand of 3-byte instructions
+    fn word(&self) -> u16 {
+        u16::from_le_bytes(self.operand)
+    }
+
+    /// Address the operand refers to, before indexing
+    ///
+    /// This is the branch target for relative branches and the pointer address
+    /// for indirect modes. Returns `None` for implied, accumulator and immediate
+    /// operands.
+    pub fn operand_addr(&self) -> Option<u16> {
+        match self.mode {
+            "ZP" | "ZPX" | "ZPY" | "INDX" | "INDY" => Some(self.operand[0] as u16),
+            "ABS" | "ABSX" | "ABSY" | "IND" => Some(self.word()),
+            "REL" => Some(self.next_addr().wrapping_add(self.operand[0] as i8 as u16)),
+            _ => None,
+        }
+    }
+
+    /// Instruction bytes as hex, padded to the width of a 3-byte instruction
+    pub fn hex(&self) -> String {
+        match self.size() {
+            1 => format!("{:02X}      ", self.opcode),
+            2 => format!("{:02X} {:02X}   ", self.opcode, self.operand[0]),
+            _ => format!(
+                "{:02X} {:02X} {:02X}",
+                self.opcode, self.operand[0], self.operand[1]
+            ),
+        }
+    }
+
+    /// Format as assembly, using labels for operand addresses where available
+    pub fn format(&self, labels: &dyn Labels) -> String {
+        let target = || {
+            let addr = self.operand_addr().unwrap_or(0);
+            match labels.label(addr) {
+                Some(label) => label.to_string(),
+                None if self.size() == 2 && self.mode != "REL" => format!("${:02X}", addr),
+                None => format!("${:04X}", addr),
+            }
+        };
+        let operand = match self.mode {
+            "IMP" => return self.mnemonic.to_string(),
+            "ACC" => "A".to_string(),
+            "IMM" => format!("#${:02X}", self.operand[0]),
+            "ZP" | "ABS" | "REL" => target(),
+            "ZPX" | "ABSX" => format!("{},X", target()),
+            "ZPY" | "ABSY" => format!("{},Y", target()),
+            "INDX" => format!("({},X)", target()),
+            "INDY" => format!("({}),Y", target()),
+            "IND" => format!("({})", target()),
+            _ => unreachable!("Unknown addressing mode {}", self.mode),
+        };
+        format!("{} {}", self.mnemonic, operand)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format(&NoLabels))
+    }
+}
+
+/// Decode the instruction at `addr`
+///
+/// Only the bytes that belong to the instruction are read.
+pub fn decode(addr: u16, read: impl Fn(u16) -> u8) -> Instruction {
+    let opcode = read(addr);
+    let info = cpu::lookup(opcode)
+        .unwrap_or_else(|| panic!("Opcode table has no entry for 0x{:02X}", opcode));
+    let mut instruction = Instruction {
+        addr,
+        opcode,
+        operand: [0; 2],
+        mnemonic: info.mnemonic,
+        mode: info.mode,
+        size: info.bytes(),
+    };
+    for i in 1..instruction.size() {
+        instruction.operand[i as usize - 1] = read(addr.wrapping_add(i));
+    }
+    instruction
+}
+
+/// Disassemble the instructions that start in `start..=end`
+pub fn disassemble(start: u16, end: u16, read: impl Fn(u16) -> u8) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut addr = start as u32;
+    while addr <= end as u32 {
+        let instruction = decode(addr as u16, &read);
+        addr += instruction.size() as u32;
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+/// Disassemble `count` instructions starting at `start`
+pub fn disassemble_count(start: u16, count: usize, read: impl Fn(u16) -> u8) -> Vec<Instruction> {
+    let mut instructions = Vec::with_capacity(count);
+    let mut addr = start;
+    for _ in 0..count {
+        let instruction = decode(addr, &read);
+        addr = instruction.next_addr();
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+/// Read a PRG-ROM bank as if it were mapped at `base`
+///
+/// Addresses outside the bank read as 0.
+///
+/// # Arguments
+/// * `prg_rom` - The whole PRG-ROM
+/// * `bank` - Bank number
+/// * `bank_size` - Bank size in bytes
+/// * `base` - CPU address the bank is mapped at
+pub fn prg_bank_reader(
+    prg_rom: &[u8],
+    bank: usize,
+    bank_size: usize,
+    base: u16,
+) -> impl Fn(u16) -> u8 + '_ {
+    move |addr| {
+        let offset = addr.wrapping_sub(base) as usize;
+        if offset < bank_size {
+            prg_rom.get(bank * bank_size + offset).copied().unwrap_or(0)
+        } else {
+            0
+        }
+    }
+}
+
+/// Format instructions as a listing, one per line, with label lines before labelled addresses
+///
+/// Lines look like `C000  A9 10     LDA #$10`.
+pub fn listing(instructions: &[Instruction], labels: &dyn Labels) -> String {
+    let mut lines = Vec::new();
+    for instruction in instructions {
+        if let Some(label) = labels.label(instruction.addr) {
+            lines.push(format!("{}:", label));
+        }
+        lines.push(format!(
+            "{:04X}  {}  {}",
+            instruction.addr,
+            instruction.hex(),
+            instruction.format(labels)
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory(bytes: &[u8]) -> impl Fn(u16) -> u8 + '_ {
+        move |addr| bytes.get(addr as usize).copied().unwrap_or(0)
+    }
+
+    #[test]
+    fn test_every_opcode_decodes() {
+        for opcode in 0..=255u8 {
+            let bytes = [opcode, 0x34, 0x12];
+            let instruction = decode(0, memory(&bytes));
+            assert_eq!(instruction.opcode, opcode);
+            assert!(!instruction.to_string().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_addressing_mode_formats() {
+        let cases: [(&[u8], &str); 12] = [
+            (&[0xEA], "NOP"),
+            (&[0x0A], "ASL A"),
+            (&[0xA9, 0x10], "LDA #$10"),
+            (&[0xA5, 0x10], "LDA $10"),
+            (&[0xB5, 0x10], "LDA $10,X"),
+            (&[0xB6, 0x10], "LDX $10,Y"),
+            (&[0xAD, 0x00, 0x03], "LDA $0300"),
+            (&[0xBD, 0x00, 0x03], "LDA $0300,X"),
+            (&[0xB9, 0x00, 0x03], "LDA $0300,Y"),
+            (&[0xA1, 0x10], "LDA ($10,X)"),
+            (&[0xB1, 0x10], "LDA ($10),Y"),
+            (&[0x6C, 0xFC, 0xFF], "JMP ($FFFC)"),
+        ];
+        for (bytes, expected) in cases {
+            assert_eq!(decode(0, memory(bytes)).to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_unofficial_opcodes() {
+        assert_eq!(decode(0, memory(&[0xA7, 0x10])).to_string(), "*LAX $10");
+        assert_eq!(decode(0, memory(&[0x04, 0x10])).to_string(), "*NOP $10");
+    }
+
+    #[test]
+    fn test_branch_target() {
+        // BNE -2 at $C000 branches to itself
+        let read = |addr: u16| match addr {
+            0xC000 => 0xD0,
+            0xC001 => 0xFE,
+            _ => 0,
+        };
+        let instruction = decode(0xC000, read);
+        assert_eq!(instruction.operand_addr(), Some(0xC000));
+        assert_eq!(instruction.to_string(), "BNE $C000");
+    }
+
+    #[test]
+    fn test_labels() {
+        let mut labels = HashMap::new();
+        labels.insert(0x0010, "ptr".to_string());
+        labels.insert(0x8000, "reset".to_string());
+
+        let jsr = decode(0, memory(&[0x20, 0x00, 0x80]));
+        assert_eq!(jsr.format(&labels), "JSR reset");
+        let indirect = decode(0, memory(&[0xB1, 0x10]));
+        assert_eq!(indirect.format(&labels), "LDA (ptr),Y");
+        let immediate = decode(0, memory(&[0xA9, 0x10]));
+        assert_eq!(immediate.format(&labels), "LDA #$10");
+    }
+
+    #[test]
+    fn test_disassemble_range_and_listing() {
+        // LDA #$01; STA $0200; RTS
+        let bytes = [0xA9, 0x01, 0x8D, 0x00, 0x02, 0x60];
+        let instructions = disassemble(0, 5, memory(&bytes));
+        assert_eq!(instructions.len(), 3);
+        assert_eq!(instructions[2].addr, 5);
+
+        let mut labels = HashMap::new();
+        labels.insert(0x0000, "start".to_string());
+        assert_eq!(
+            listing(&instructions[..2], &labels),
+            "start:\n0000  A9 01     LDA #$01\n0002  8D 00 02  STA $0200"
+        );
+        assert_eq!(disassemble_count(0, 2, memory(&bytes))[1].addr, 2);
+    }
+
+    #[test]
+    fn test_prg_bank_reader() {
+        let mut prg_rom = vec![0u8; 0x8000];
+        prg_rom[0x4000] = 0x60; // First byte of bank 1
+        let read = prg_bank_reader(&prg_rom, 1, 0x4000, 0x8000);
+        assert_eq!(decode(0x8000, &read).to_string(), "RTS");
+        assert_eq!(read(0xC000), 0);
+    }
+}
@@ -1,22 +1,57 @@
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
 use sdl2::pixels::PixelFormatEnum;
+use sdl2::rect::Rect;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
 
 use crate::audio::NesAudio;
+use crate::config::{HotkeyConfig, InputConfig, Overscan, RewindConfig};
 use crate::input::Button;
+use crate::movie::MovieSession;
 use crate::nes::TvSystem;
+use crate::netplay::NetplaySession;
+use crate::rewind::RewindBuffer;
+use crate::runahead::RunAhead;
+use crate::trace::TraceLogger;
+use crate::worker::{Channels, Command, Worker};
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+/// Keys bound to emulator functions
+struct Hotkeys {
+    quit: Keycode,
+    pause: Keycode,
+    reset: Keycode,
+    rewind: Keycode,
+    frame_advance: Keycode,
+    read_only: Keycode,
+    show_info: Keycode,
+    cheats: Keycode,
+}
 
 /// EventLoop manages the SDL2 event loop for the application.
 /// It handles user input and window events, exiting when Escape is pressed or the window is closed.
+/// Emulation itself runs on a separate thread (see `worker`); the event loop only presents it.
 pub struct EventLoop {
     _sdl_context: sdl2::Sdl,
     canvas: Option<Canvas<Window>>,
     event_pump: sdl2::EventPump,
+    video_scale: f32,
     timing_scale: f32,
-    paused: bool,
     audio: Option<NesAudio>,
+    /// Controller (1 or 2) and button for each bound key
+    key_bindings: HashMap<Keycode, (u8, Button)>,
+    hotkeys: Hotkeys,
+    /// Pixels cropped from each edge of the picture
+    overscan: Overscan,
+    rewind: RewindConfig,
+    run_ahead: Option<RunAhead>,
+    /// Start paused, with the input editable and the frame counter shown
+    tas: bool,
+    netplay: Option<NetplaySession>,
 }
 
 impl EventLoop {
@@ -27,6 +62,8 @@ impl EventLoop {
     const CLEAR_COLOR_R: u8 = 0;
     const CLEAR_COLOR_G: u8 = 0;
     const CLEAR_COLOR_B: u8 = 0;
+    /// How long to wait for the next frame before polling events again
+    const PRESENT_POLL: Duration = Duration::from_millis(1);
 
     /// Creates a new EventLoop instance.
     ///
@@ -94,12 +131,162 @@ impl EventLoop {
             _sdl_context: sdl_context,
             canvas,
             event_pump,
+            video_scale: clamped_video_scale,
             timing_scale: clamped_timing_scale,
-            paused: false,
             audio,
+            key_bindings: Self::default_key_bindings(),
+            hotkeys: Hotkeys {
+                quit: Keycode::Escape,
+                pause: Keycode::Space,
+                reset: Keycode::F1,
+                rewind: Keycode::Backspace,
+                frame_advance: Keycode::F,
+                read_only: Keycode::Q,
+                show_info: Keycode::F3,
+                cheats: Keycode::F4,
+            },
+            overscan: Overscan::default(),
+            rewind: RewindConfig::default(),
+            run_ahead: None,
+            tas: false,
+            netplay: None,
         })
     }
 
+    /// Crop the edges of the picture and resize the window to fit the rest
+    pub fn set_overscan(&mut self, overscan: Overscan) -> Result<(), String> {
+        self.overscan = overscan;
+        if let Some(ref mut canvas) = self.canvas {
+            let (width, height) = Self::visible_size(&overscan);
+            canvas
+                .window_mut()
+                .set_size(
+                    (width as f32 * self.video_scale) as u32,
+                    (height as f32 * self.video_scale) as u32,
+                )
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Set how much play can be rewound with the rewind key
+    pub fn set_rewind(&mut self, rewind: &RewindConfig) {
+        self.rewind = rewind.clone();
+    }
+
+    /// Show each frame some frames early to hide input latency
+    pub fn set_run_ahead(&mut self, run_ahead: RunAhead) {
+        self.run_ahead = Some(run_ahead);
+    }
+
+    /// Start paused for making a tool-assisted movie
+    ///
+    /// The frame counter, lag counter and input are shown, and while paused
+    /// the controller keys toggle buttons for the next frame instead of being
+    /// held.
+    pub fn set_tas(&mut self, tas: bool) {
+        self.tas = tas;
+    }
+
+    /// Play with another emulator over the network
+    ///
+    /// Rewinding is turned off, since the other side would not go back too.
+    pub fn set_netplay(&mut self, netplay: NetplaySession) {
+        self.netplay = Some(netplay);
+    }
+
+    /// Size of the picture left after cropping
+    fn visible_size(overscan: &Overscan) -> (u32, u32) {
+        (
+            256u32.saturating_sub(overscan.left + overscan.right).max(1),
+            240u32.saturating_sub(overscan.top + overscan.bottom).max(1),
+        )
+    }
+
+    /// Map a window position to a screen pixel, taking scaling and cropping into account
+    ///
+    /// Returns `None` for positions outside the window.
+    fn window_to_screen(
+        x: i32,
+        y: i32,
+        window_size: (u32, u32),
+        overscan: &Overscan,
+    ) -> Option<(u32, u32)> {
+        let (window_width, window_height) = window_size;
+        if x < 0 || y < 0 || x as u32 >= window_width || y as u32 >= window_height {
+            return None;
+        }
+        let (width, height) = Self::visible_size(overscan);
+        Some((
+            overscan.left + x as u32 * width / window_width,
+            overscan.top + y as u32 * height / window_height,
+        ))
+    }
+
+    /// Default keyboard layout for controller 1
+    ///
+    /// - Arrow Keys: D-Pad (Up, Down, Left, Right)
+    /// - Z: B button
+    /// - X: A button
+    /// - A: Select button
+    /// - S: Start button
+    fn default_key_bindings() -> HashMap<Keycode, (u8, Button)> {
+        HashMap::from([
+            (Keycode::Up, (1, Button::Up)),
+            (Keycode::Down, (1, Button::Down)),
+            (Keycode::Left, (1, Button::Left)),
+            (Keycode::Right, (1, Button::Right)),
+            (Keycode::Z, (1, Button::B)),
+            (Keycode::X, (1, Button::A)),
+            (Keycode::A, (1, Button::Select)),
+            (Keycode::S, (1, Button::Start)),
+        ])
+    }
+
+    /// Apply key bindings and hotkeys from the configuration
+    ///
+    /// Key names are resolved with SDL's key name table (e.g. "Up", "Z", "Right Shift").
+    /// Unknown key names are reported as warnings and leave the previous binding in place.
+    pub fn set_input_config(&mut self, input: &InputConfig, hotkeys: &HotkeyConfig) {
+        for (player, bindings) in [(1u8, &input.player1), (2u8, &input.player2)] {
+            for (button, name) in bindings.buttons() {
+                let Some(keycode) = Self::resolve_key(name) else {
+                    continue;
+                };
+                self.key_bindings
+                    .retain(|_, binding| *binding != (player, button));
+                self.key_bindings.insert(keycode, (player, button));
+            }
+        }
+
+        for (target, name) in [
+            (&mut self.hotkeys.quit, &hotkeys.quit),
+            (&mut self.hotkeys.pause, &hotkeys.pause),
+            (&mut self.hotkeys.reset, &hotkeys.reset),
+            (&mut self.hotkeys.rewind, &hotkeys.rewind),
+            (&mut self.hotkeys.frame_advance, &hotkeys.frame_advance),
+            (&mut self.hotkeys.read_only, &hotkeys.read_only),
+            (&mut self.hotkeys.show_info, &hotkeys.show_info),
+            (&mut self.hotkeys.cheats, &hotkeys.cheats),
+        ] {
+            if let Some(keycode) = Self::resolve_key(name) {
+                *target = keycode;
+            }
+        }
+    }
+
+    /// Look up a key by name, warning if SDL does not know it
+    fn resolve_key(name: &str) -> Option<Keycode> {
+        let keycode = Keycode::from_name(name);
+        if keycode.is_none() {
+            eprintln!(
+                "Warning: Unknown key name \"{}\" in config. Keeping the previous binding.",
+                name
+            );
+        }
+        keycode
+    }
+
     /// Clamps the video scaling factor to the valid range [1.0, 5.0].
     /// Prints a warning to stderr if clamping occurs.
     fn clamp_scale(scale: f32) -> f32 {
@@ -195,35 +382,29 @@ impl EventLoop {
     //     false
     // }
 
-    /// Renders the current frame from the PPU screen buffer to the screen.
+    /// Renders a frame from the emulation thread to the screen.
     fn render_frame(
         canvas: &mut Canvas<Window>,
         texture: &mut sdl2::render::Texture,
-        nes: &crate::nes::Nes,
+        frame: &[u8],
+        overscan: &Overscan,
     ) -> Result<(), String> {
-        // Update texture from PPU screen buffer (256x240 pixels)
+        // Update texture from the RGB frame (256x240 pixels)
         const TEXTURE_WIDTH: u32 = 256;
         const TEXTURE_HEIGHT: u32 = 240;
+        const ROW_BYTES: usize = TEXTURE_WIDTH as usize * 3;
 
         texture
             .with_lock(None, |buffer: &mut [u8], pitch: usize| {
-                // Get the PPU screen buffer and copy its RGB data to the texture
-                let mut screen_buffer = nes.get_screen_buffer();
-
                 // Check if we can do a direct copy (pitch == width * 3 bytes per pixel)
-                if pitch == (TEXTURE_WIDTH as usize * 3) {
+                if pitch == ROW_BYTES {
                     // Fast path: direct buffer copy
-                    screen_buffer.copy_buffer(buffer);
+                    buffer[..frame.len()].copy_from_slice(frame);
                 } else {
                     // Slow path: copy row by row to handle non-standard pitch
-                    for y in 0..TEXTURE_HEIGHT {
-                        for x in 0..TEXTURE_WIDTH {
-                            let (r, g, b) = screen_buffer.get_pixel(x, y);
-                            let offset = (y as usize * pitch) + (x as usize * 3);
-                            buffer[offset] = r;
-                            buffer[offset + 1] = g;
-                            buffer[offset + 2] = b;
-                        }
+                    for y in 0..TEXTURE_HEIGHT as usize {
+                        buffer[y * pitch..y * pitch + ROW_BYTES]
+                            .copy_from_slice(&frame[y * ROW_BYTES..(y + 1) * ROW_BYTES]);
                     }
                 }
             })
@@ -235,8 +416,15 @@ impl EventLoop {
             Self::CLEAR_COLOR_B,
         ));
         canvas.clear();
+        let (visible_width, visible_height) = Self::visible_size(overscan);
+        let source = Rect::new(
+            overscan.left as i32,
+            overscan.top as i32,
+            visible_width,
+            visible_height,
+        );
         canvas
-            .copy(texture, None, None)
+            .copy(texture, Some(source), None)
             .map_err(|e| e.to_string())?;
         canvas.present();
 
@@ -245,215 +433,200 @@ impl EventLoop {
 
     /// Runs the event loop, processing events until the user presses Escape or closes the window.
     ///
-    /// Continuously runs CPU opcodes on the provided NES instance according to the CPU clock
-    /// frequency of the TV system.
+    /// The NES runs on its own thread at the CPU clock frequency of the TV system (scaled by
+    /// the timing scale, and unthrottled when headless). This thread only forwards input to
+    /// it and shows the frames and plays the audio it produces.
     ///
     /// # Arguments
     ///
     /// * `nes` - A mutable reference to the NES instance to run.
-    /// * `trace` - If `true`, prints a trace line for each CPU instruction executed.
+    /// * `trace` - Trace logger to show each CPU instruction before it executes.
+    /// * `movie` - Movie to record the input into, or to play back.
     ///
     /// # Errors
     ///
-    /// Currently returns Ok(()) in all cases, but the Result type is kept for future error handling.
-    pub fn run(&mut self, nes: &mut crate::nes::Nes, trace: bool) -> Result<(), String> {
+    /// Returns an error if rendering fails, the trace log cannot be written or
+    /// the netplay connection fails.
+    pub fn run(
+        &mut self,
+        nes: &mut crate::nes::Nes,
+        trace: Option<&mut TraceLogger>,
+        movie: Option<&mut MovieSession>,
+    ) -> Result<(), String> {
+        let channels = Channels::new(self.audio.as_ref().map(NesAudio::sample_queue));
+        // Frame limiting - maintain ~60 FPS (or scaled by timing_scale)
+        let frame_time = self
+            .canvas
+            .is_some()
+            .then(|| Duration::from_secs_f64((1.0 / 60.0) / self.timing_scale as f64));
+
         // Start audio playback if audio is enabled
         if let Some(ref audio) = self.audio {
             audio.resume();
         }
 
-        if let Some(ref mut canvas) = self.canvas {
-            // We have a window - run with rendering
-            let texture_creator = canvas.texture_creator();
-
-            // Create a 256x240 texture matching the PPU screen buffer dimensions
-            const TEXTURE_WIDTH: u32 = 256;
-            const TEXTURE_HEIGHT: u32 = 240;
-
-            let mut texture = texture_creator
-                .create_texture_streaming(PixelFormatEnum::RGB24, TEXTURE_WIDTH, TEXTURE_HEIGHT)
-                .map_err(|e| e.to_string())?;
-
-            let timer = self._sdl_context.timer()?;
-            let mut last_frame_time = timer.performance_counter();
-            let performance_frequency = timer.performance_frequency() as f64;
-
-            loop {
-                // 1. Poll ALL events (non-blocking)
-                for event in self.event_pump.poll_iter() {
-                    match event {
-                        Event::Quit { .. }
-                        | Event::KeyDown {
-                            keycode: Some(Keycode::Escape),
-                            ..
-                        } => return Ok(()),
-                        Event::KeyDown {
-                            keycode: Some(Keycode::Space),
-                            ..
-                        } => {
-                            self.paused = !self.paused;
-                        }
-                        Event::KeyDown {
-                            keycode: Some(Keycode::F1),
-                            ..
-                        } => {
-                            println!("Resetting NES...");
-                            nes.reset();
-                        }
-                        Event::KeyDown {
-                            keycode: Some(keycode),
-                            ..
-                        } => {
-                            Self::handle_key_down(nes, keycode);
-                        }
-                        Event::KeyUp {
-                            keycode: Some(keycode),
-                            ..
-                        } => {
-                            Self::handle_key_up(nes, keycode);
-                        }
-                        _ => {}
-                    }
-                }
+        let rewind = (self.rewind.enabled && self.netplay.is_none()).then(|| {
+            RewindBuffer::new(
+                self.rewind.memory_mb as usize * 1024 * 1024,
+                self.rewind.interval,
+            )
+        });
+        let run_ahead = self.run_ahead.take();
+        let tas = self.tas;
+        let netplay = self.netplay.take();
+
+        thread::scope(|scope| {
+            let worker = scope.spawn(|| {
+                Worker::new(nes, trace, &channels, frame_time)
+                    .with_movie(movie)
+                    .with_rewind(rewind)
+                    .with_run_ahead(run_ahead)
+                    .with_tas(tas)
+                    .with_netplay(netplay)
+                    .run()
+            });
+            let presented = self.present(&channels, || worker.is_finished());
+            channels.send(Command::Quit);
+            let emulated = worker
+                .join()
+                .unwrap_or_else(|_| Err("Emulation thread panicked".to_string()));
+            presented.and(emulated)
+        })
+    }
 
-                // Skip emulation and rendering if paused
-                if self.paused {
-                    std::thread::sleep(std::time::Duration::from_millis(16));
-                    continue;
-                }
+    /// Forward input to the emulation thread and show its frames until quit is
+    /// requested or the thread stops
+    fn present(
+        &mut self,
+        channels: &Channels,
+        worker_finished: impl Fn() -> bool,
+    ) -> Result<(), String> {
+        // Create a 256x240 texture matching the PPU screen buffer dimensions
+        let texture_creator = self.canvas.as_ref().map(|canvas| canvas.texture_creator());
+        let mut texture = match &texture_creator {
+            Some(texture_creator) => Some(
+                texture_creator
+                    .create_texture_streaming(PixelFormatEnum::RGB24, 256, 240)
+                    .map_err(|e| e.to_string())?,
+            ),
+            None => None,
+        };
 
-                // 2. Emulate until PPU completes a full frame (reaches VBlank)
-                // The PPU runs at 3x CPU clock (NTSC) or 3.2x (PAL), so run_cpu_tick()
-                // automatically runs the correct number of PPU cycles per CPU instruction.
-                // A full frame is 262 scanlines × 341 pixels = 89,342 PPU cycles for NTSC
-                while !nes.is_ready_to_render() && !nes.cpu.is_halted() {
-                    if trace {
-                        println!("{}", nes.trace(false));
-                    }
-                    nes.run_cpu_tick();
-
-                    // Poll audio samples from APU and queue them
-                    if let Some(ref mut audio) = self.audio {
-                        while nes.sample_ready() {
-                            if let Some(sample) = nes.get_sample() {
-                                audio.queue_sample(sample);
-                            }
-                        }
-                    }
+        while !worker_finished() {
+            // 1. Poll ALL events (non-blocking)
+            let events: Vec<Event> = self.event_pump.poll_iter().collect();
+            for event in events {
+                if !self.handle_event(event, channels) {
+                    return Ok(());
                 }
-                nes.clear_ready_to_render();
-                // println!(
-                //     "Frame emulated. Scanline: {}, Pixel: {}",
-                //     nes.ppu.borrow().scanline(),
-                //     nes.ppu.borrow().pixel()
-                // );
-
-                // 3. Render the frame
-                Self::render_frame(canvas, &mut texture, nes)?;
-                // println!("Frame rendered.");
-
-                // 4. Frame limiting - maintain ~60 FPS (or scaled by timing_scale)
-                let current_time = timer.performance_counter();
-                let elapsed_ticks = (current_time - last_frame_time) as f64;
-                let elapsed_seconds = elapsed_ticks / performance_frequency;
-                // Adjust target frame time by timing scale (1.0 = normal speed, 2.0 = 2x speed, etc.)
-                let target_frame_time = (1.0 / 60.0) / self.timing_scale as f64;
-
-                // Calculate FPS before sleeping
-                // let fps = 1.0 / elapsed_seconds;
-                // println!("FPS: {:.2}", fps);
-
-                // Update last_frame_time before sleeping to avoid timing drift
-                last_frame_time = current_time;
-
-                if elapsed_seconds < target_frame_time {
-                    let sleep_time = target_frame_time - elapsed_seconds;
-                    std::thread::sleep(std::time::Duration::from_secs_f64(sleep_time));
-                }
-                // println!("Frame limited.");
             }
-        } else {
-            // Headless mode - just run without rendering
-            loop {
-                for event in self.event_pump.poll_iter() {
-                    match event {
-                        Event::Quit { .. }
-                        | Event::KeyDown {
-                            keycode: Some(Keycode::Escape),
-                            ..
-                        } => return Ok(()),
-                        Event::KeyDown {
-                            keycode: Some(Keycode::F1),
-                            ..
-                        } => {
-                            println!("Resetting NES...");
-                            nes.reset();
-                        }
-                        Event::KeyDown {
-                            keycode: Some(keycode),
-                            ..
-                        } => {
-                            Self::handle_key_down(nes, keycode);
-                        }
-                        Event::KeyUp {
-                            keycode: Some(keycode),
-                            ..
-                        } => {
-                            Self::handle_key_up(nes, keycode);
-                        }
-                        _ => {}
-                    }
-                }
-
-                nes.run_cpu_tick();
 
-                // Poll audio samples from APU and queue them
-                if let Some(ref mut audio) = self.audio {
-                    while nes.sample_ready() {
-                        if let Some(sample) = nes.get_sample() {
-                            audio.queue_sample(sample);
-                        }
+            // 2. Show the newest frame, if the emulation thread finished one
+            match channels.latest_frame() {
+                Some(frame) => {
+                    if let (Some(canvas), Some(texture)) = (&mut self.canvas, &mut texture) {
+                        Self::render_frame(canvas, texture, &frame, &self.overscan)?;
                     }
+                    channels.recycle(frame);
                 }
+                None => thread::sleep(Self::PRESENT_POLL),
             }
         }
+        Ok(())
     }
 
-    /// Handle keyboard key press events
+    /// Turn a window event into commands for the emulation thread
     ///
-    /// Maps keyboard keys to NES controller buttons:
-    /// - Arrow Keys: D-Pad (Up, Down, Left, Right)
-    /// - Z: B button
-    /// - X: A button
-    /// - A: Select button
-    /// - S: Start button
-    fn handle_key_down(nes: &mut crate::nes::Nes, keycode: Keycode) {
-        match keycode {
-            Keycode::Up => nes.set_button(1, Button::Up, true),
-            Keycode::Down => nes.set_button(1, Button::Down, true),
-            Keycode::Left => nes.set_button(1, Button::Left, true),
-            Keycode::Right => nes.set_button(1, Button::Right, true),
-            Keycode::Z => nes.set_button(1, Button::B, true),
-            Keycode::X => nes.set_button(1, Button::A, true),
-            Keycode::A => nes.set_button(1, Button::Select, true),
-            Keycode::S => nes.set_button(1, Button::Start, true),
+    /// Returns `false` if the user asked to quit.
+    fn handle_event(&mut self, event: Event, channels: &Channels) -> bool {
+        match event {
+            Event::Quit { .. } => return false,
+            Event::KeyDown {
+                keycode: Some(keycode),
+                ..
+            } if keycode == self.hotkeys.quit => return false,
+            Event::KeyDown {
+                keycode: Some(keycode),
+                ..
+            } if keycode == self.hotkeys.pause => channels.send(Command::TogglePause),
+            // The mouse drives the Zapper
+            Event::MouseMotion { x, y, .. } => {
+                if let Some(canvas) = &self.canvas {
+                    let aim = Self::window_to_screen(x, y, canvas.window().size(), &self.overscan);
+                    channels.send(Command::ZapperAim(aim));
+                }
+            }
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                ..
+            } => channels.send(Command::ZapperTrigger(true)),
+            Event::MouseButtonUp {
+                mouse_btn: MouseButton::Left,
+                ..
+            } => channels.send(Command::ZapperTrigger(false)),
+            Event::KeyDown {
+                keycode: Some(keycode),
+                ..
+            } if keycode == self.hotkeys.reset => channels.send(Command::Reset),
+            Event::KeyDown {
+                keycode: Some(keycode),
+                repeat: false,
+                ..
+            } if keycode == self.hotkeys.rewind => channels.send(Command::Rewind(true)),
+            Event::KeyUp {
+                keycode: Some(keycode),
+                ..
+            } if keycode == self.hotkeys.rewind => channels.send(Command::Rewind(false)),
+            // Holding the key keeps advancing
+            Event::KeyDown {
+                keycode: Some(keycode),
+                ..
+            } if keycode == self.hotkeys.frame_advance => channels.send(Command::FrameAdvance),
+            Event::KeyDown {
+                keycode: Some(keycode),
+                repeat: false,
+                ..
+            } if keycode == self.hotkeys.read_only => channels.send(Command::ToggleReadOnly),
+            Event::KeyDown {
+                keycode: Some(keycode),
+                repeat: false,
+                ..
+            } if keycode == self.hotkeys.show_info => channels.send(Command::ToggleInfo),
+            Event::KeyDown {
+                keycode: Some(keycode),
+                repeat: false,
+                ..
+            } if keycode == self.hotkeys.cheats => channels.send(Command::ToggleCheats),
+            // Repeats would toggle buttons that are edited while paused
+            Event::KeyDown {
+                keycode: Some(keycode),
+                repeat: false,
+                ..
+            } => Self::handle_key(channels, &self.key_bindings, keycode, true),
+            Event::KeyUp {
+                keycode: Some(keycode),
+                ..
+            } => Self::handle_key(channels, &self.key_bindings, keycode, false),
             _ => {}
         }
+        true
     }
 
-    /// Handle keyboard key release events
-    fn handle_key_up(nes: &mut crate::nes::Nes, keycode: Keycode) {
-        use crate::input::Button;
-        match keycode {
-            Keycode::Up => nes.set_button(1, Button::Up, false),
-            Keycode::Down => nes.set_button(1, Button::Down, false),
-            Keycode::Left => nes.set_button(1, Button::Left, false),
-            Keycode::Right => nes.set_button(1, Button::Right, false),
-            Keycode::Z => nes.set_button(1, Button::B, false),
-            Keycode::X => nes.set_button(1, Button::A, false),
-            Keycode::A => nes.set_button(1, Button::Select, false),
-            Keycode::S => nes.set_button(1, Button::Start, false),
-            _ => {}
+    /// Handle keyboard key press and release events
+    ///
+    /// Keys bound to a controller button update that button, other keys are ignored.
+    fn handle_key(
+        channels: &Channels,
+        key_bindings: &HashMap<Keycode, (u8, Button)>,
+        keycode: Keycode,
+        pressed: bool,
+    ) {
+        if let Some(&(controller, button)) = key_bindings.get(&keycode) {
+            channels.send(Command::Button {
+                controller,
+                button,
+                pressed,
+            });
         }
     }
 }
@@ -518,6 +691,55 @@ mod tests {
         let _ = &mut nes;
     }
 
+    #[test]
+    fn test_default_key_bindings() {
+        let bindings = EventLoop::default_key_bindings();
+        assert_eq!(bindings.get(&Keycode::Z), Some(&(1, Button::B)));
+        assert_eq!(bindings.get(&Keycode::X), Some(&(1, Button::A)));
+        assert_eq!(bindings.get(&Keycode::S), Some(&(1, Button::Start)));
+        assert_eq!(bindings.len(), 8);
+    }
+
+    #[test]
+    fn test_window_to_screen() {
+        let overscan = Overscan::default();
+        assert_eq!(
+            EventLoop::window_to_screen(0, 0, (512, 480), &overscan),
+            Some((0, 0))
+        );
+        assert_eq!(
+            EventLoop::window_to_screen(511, 479, (512, 480), &overscan),
+            Some((255, 239))
+        );
+        assert_eq!(
+            EventLoop::window_to_screen(512, 0, (512, 480), &overscan),
+            None
+        );
+        assert_eq!(
+            EventLoop::window_to_screen(-1, 0, (512, 480), &overscan),
+            None
+        );
+    }
+
+    #[test]
+    fn test_window_to_screen_with_overscan() {
+        let overscan = Overscan {
+            top: 8,
+            bottom: 8,
+            left: 0,
+            right: 0,
+        };
+        // 256x224 visible picture shown at 2x
+        assert_eq!(
+            EventLoop::window_to_screen(0, 0, (512, 448), &overscan),
+            Some((0, 8))
+        );
+        assert_eq!(
+            EventLoop::window_to_screen(511, 447, (512, 448), &overscan),
+            Some((255, 231))
+        );
+    }
+
     #[test]
     fn test_render_frame_should_use_256x240_texture() {
         // Verify that render_frame uses correct PPU screen buffer dimensions
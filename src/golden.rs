@@ -0,0 +1,384 @@
+//! Golden image tests
+//!
+//! Runs a ROM for a number of frames, optionally pressing buttons from an
+//! input script, and compares the screen to a stored PNG in `GOLDEN_DIR`. This
+//! catches rendering regressions down to single pixels, such as changes in
+//! sprite 0 hit timing or the leftmost 8 pixels.
+//!
+//! When an image differs, the actual screen and a diff image are written to
+//! `ARTIFACT_DIR`. Running with `NESER_UPDATE_GOLDEN=1` set rewrites the
+//! stored images instead, after a rendering change has been checked by eye.
+
+use crate::cartridge::{Cartridge, load_rom};
+use crate::headless::{InputScript, Runner, SCREEN_SIZE, write_png};
+use crate::nes::Nes;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Where the golden images are stored
+pub const GOLDEN_DIR: &str = "roms/golden";
+
+/// Where actual and diff images are written on a mismatch
+pub const ARTIFACT_DIR: &str = "target/golden";
+
+/// Set to a non-empty value to rewrite golden images instead of checking them
+pub const UPDATE_ENV: &str = "NESER_UPDATE_GOLDEN";
+
+/// A ROM, the input to give it and the frame to compare
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldenCase<'a> {
+    /// File name of the golden image, without `.png`
+    pub name: &'a str,
+    pub rom_path: &'a str,
+    /// Frames to run before comparing the screen
+    pub frames: u64,
+    /// Input script in the format of `InputScript::parse`
+    pub script: &'a str,
+}
+
+/// How the screen compared to the golden image
+#[derive(Debug, Clone, PartialEq)]
+pub enum Comparison {
+    Match,
+    /// The golden image was written (update mode)
+    Updated,
+    /// The screen differs, see the actual and diff images
+    Mismatch {
+        pixels: usize,
+        actual: PathBuf,
+        diff: PathBuf,
+    },
+}
+
+impl GoldenCase<'_> {
+    /// Path of the stored golden image
+    pub fn golden_path(&self) -> PathBuf {
+        Path::new(GOLDEN_DIR).join(format!("{}.png", self.name))
+    }
+
+    /// Run the ROM and return the RGB pixels of the last frame
+    ///
+    /// The ROM runs in the region of its header, with default settings.
+    pub fn render(&self) -> Result<Vec<u8>, String> {
+        let rom_data = load_rom(Path::new(self.rom_path), None)
+            .map_err(|e| format!("Failed to load ROM {}: {}", self.rom_path, e))?;
+        let cartridge = Cartridge::new(&rom_data)
+            .map_err(|e| format!("Failed to parse ROM {}: {}", self.rom_path, e))?;
+        let script = InputScript::parse(self.script)
+            .map_err(|message| format!("Input script of {}: {}", self.name, message))?;
+
+        let mut nes = Nes::new(cartridge.region().tv_system());
+        nes.insert_cartridge(cartridge);
+        nes.reset();
+        Runner::new(&mut nes, script, vec![]).run(self.frames);
+        Ok(nes.ppu().screen_buffer().pixels().to_vec())
+    }
+
+    /// Render the case and compare it to its golden image
+    ///
+    /// With `update`, a missing or different golden image is written instead.
+    pub fn check(&self, update: bool) -> Result<Comparison, String> {
+        let actual = self.render()?;
+        let golden_path = self.golden_path();
+        let write_error =
+            |path: &Path, e: io::Error| format!("Cannot write \"{}\": {}", path.display(), e);
+
+        let golden = match read_png(&golden_path) {
+            Ok(golden) => Some(golden),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("Cannot read \"{}\": {}", golden_path.display(), e)),
+        };
+        if golden.as_ref() == Some(&actual) {
+            return Ok(Comparison::Match);
+        }
+        if update {
+            fs::create_dir_all(GOLDEN_DIR).map_err(|e| write_error(Path::new(GOLDEN_DIR), e))?;
+            write_png(&golden_path, &actual).map_err(|e| write_error(&golden_path, e))?;
+            return Ok(Comparison::Updated);
+        }
+        let Some(golden) = golden else {
+            return Err(format!(
+                "No golden image \"{}\", run with {}=1 to create it",
+                golden_path.display(),
+                UPDATE_ENV
+            ));
+        };
+
+        let artifacts = Path::new(ARTIFACT_DIR);
+        fs::create_dir_all(artifacts).map_err(|e| write_error(artifacts, e))?;
+        let actual_path = artifacts.join(format!("{}.actual.png", self.name));
+        let diff_path = artifacts.join(format!("{}.diff.png", self.name));
+        write_png(&actual_path, &actual).map_err(|e| write_error(&actual_path, e))?;
+        let (diff, pixels) = diff_image(&golden, &actual);
+        write_png(&diff_path, &diff).map_err(|e| write_error(&diff_path, e))?;
+        Ok(Comparison::Mismatch {
+            pixels,
+            actual: actual_path,
+            diff: diff_path,
+        })
+    }
+}
+
+/// Read the RGB pixels of a 256x240 PNG saved by `write_png`
+pub fn read_png(path: &Path) -> io::Result<Vec<u8>> {
+    let decoder = png::Decoder::new(File::open(path)?);
+    let mut reader = decoder.read_info().map_err(io::Error::other)?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).map_err(io::Error::other)?;
+    if (info.width, info.height) != SCREEN_SIZE
+        || info.color_type != png::ColorType::Rgb
+        || info.bit_depth != png::BitDepth::Eight
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a 256x240 8-bit RGB image",
+        ));
+    }
+    pixels.truncate(info.buffer_size());
+    Ok(pixels)
+}
+
+/// Whether golden images should be rewritten, from `UPDATE_ENV`
+pub fn update_requested() -> bool {
+    std::env::var_os(UPDATE_ENV).is_some_and(|value| !value.is_empty())
+}
+
+/// Mark the pixels that differ between two RGB images
+///
+/// Differing pixels are red, the others a dimmed grey of the expected image
+/// so the picture stays recognisable. Also returns the number of differing
+/// pixels.
+pub fn diff_image(expected: &[u8], actual: &[u8]) -> (Vec<u8>, usize) {
+    let mut differing = 0;
+    let diff = expected
+        .chunks(3)
+        .zip(actual.chunks(3))
+        .flat_map(|(expected, actual)| {
+            if expected == actual {
+                let grey = (expected.iter().map(|&c| c as u32).sum::<u32>() / 9) as u8;
+                [grey, grey, grey]
+            } else {
+                differing += 1;
+                [255, 0, 0]
+            }
+        })
+        .collect();
+    (diff, differing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check a case, or rewrite its golden image in update mode
+    fn assert_golden(case: &GoldenCase) {
+        match case.check(update_requested()) {
+            Ok(Comparison::Match) => {}
+            Ok(Comparison::Updated) => println!("Updated {}", case.golden_path().display()),
+            Ok(Comparison::Mismatch {
+                pixels,
+                actual,
+                diff,
+            }) => panic!(
+                "{} differs from {} in {} pixels, see {} and {}",
+                case.name,
+                case.golden_path().display(),
+                pixels,
+                actual.display(),
+                diff.display()
+            ),
+            Err(message) => panic!("{}", message),
+        }
+    }
+
+    /// Macro to generate a golden image test, named after the image
+    macro_rules! golden_test {
+        ($test_name:ident, $name:expr, $rom_path:expr, $frames:expr) => {
+            golden_test!($test_name, $name, $rom_path, $frames, "");
+        };
+        ($test_name:ident, $name:expr, $rom_path:expr, $frames:expr, $script:expr) => {
+            #[test]
+            fn $test_name() {
+                assert_golden(&GoldenCase {
+                    name: $name,
+                    rom_path: $rom_path,
+                    frames: $frames,
+                    script: $script,
+                });
+            }
+        };
+    }
+
+    golden_test!(
+        test_golden_palette,
+        "palette",
+        "roms/rainwarrior/palette.nes",
+        60
+    );
+    golden_test!(
+        test_golden_palette_emphasis,
+        "palette_emphasis",
+        "roms/rainwarrior/palette.nes",
+        60,
+        "20 up\n25"
+    );
+    golden_test!(
+        test_golden_palette_pal,
+        "palette_pal",
+        "roms/rainwarrior/palette_pal.nes",
+        60
+    );
+    golden_test!(
+        test_golden_color_test,
+        "color_test",
+        "roms/rainwarrior/color_test.nes",
+        60,
+        "20 up\n25"
+    );
+    golden_test!(
+        test_golden_ntsc_torture,
+        "ntsc_torture",
+        "roms/rainwarrior/ntsc_torture.nes",
+        60
+    );
+    golden_test!(
+        test_golden_ntsc_torture_select,
+        "ntsc_torture_select",
+        "roms/rainwarrior/ntsc_torture.nes",
+        60,
+        "20 select\n25"
+    );
+    // The timed line starts at x=81 in this frame and at 80, with the reference
+    // lines, in the next, as the demo's readme describes for hardware
+    golden_test!(
+        test_golden_nmi_sync_ntsc,
+        "nmi_sync_ntsc",
+        "roms/nmi_sync/demo_ntsc.nes",
+        120
+    );
+    // The timed line starts at x=83, between the reference lines at 82 and 84.
+    // The next frame is wrong (see the PAL edge test below)
+    golden_test!(
+        test_golden_nmi_sync_pal,
+        "nmi_sync_pal",
+        "roms/nmi_sync/demo_pal.nes",
+        120
+    );
+    // The ROM changes the emphasis bits mid-frame, so each of the four colors
+    // on screen shows up as a band for each of the 8 emphasis settings
+    golden_test!(
+        test_golden_full_nes_palette,
+        "full_nes_palette",
+        "roms/full_nes_palette.nes",
+        60
+    );
+
+    /// Column of the first pixel in row `y` that differs from the row's left end
+    fn left_edge(pixels: &[u8], y: usize) -> Option<usize> {
+        let row = &pixels[y * 256 * 3..(y + 1) * 256 * 3];
+        (0..256).find(|&x| row[x * 3..x * 3 + 3] != row[..3])
+    }
+
+    /// Left edges of the upper reference line, the NMI-timed line and the lower
+    /// reference line of an nmi_sync demo, in two successive frames
+    fn nmi_sync_edges(rom_path: &'static str) -> Vec<[Option<usize>; 3]> {
+        [120, 121]
+            .into_iter()
+            .map(|frames| {
+                let case = GoldenCase {
+                    name: "nmi_sync",
+                    rom_path,
+                    frames,
+                    script: "",
+                };
+                let pixels = case.render().expect("demo renders");
+                [119, 121, 123].map(|y| left_edge(&pixels, y))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_nmi_sync_ntsc_edge_flashes_next_to_references() {
+        // As on hardware, the timed line starts with the reference lines every
+        // other frame and one pixel right of them in between
+        assert_eq!(
+            nmi_sync_edges("roms/nmi_sync/demo_ntsc.nes"),
+            [
+                [Some(80), Some(81), Some(80)],
+                [Some(80), Some(80), Some(80)]
+            ]
+        );
+    }
+
+    // TODO: On PAL the timed line starts at 83 and 81 in successive frames, so
+    // every other frame it is left of the upper reference. Moving the write
+    // offset or the power-up CPU/PPU alignment doesn't fix it: over all 16
+    // alignments the line starts anywhere from 81 to 84, one pixel more than
+    // the 82 to 84 that hardware allows.
+    #[test]
+    #[ignore = "PAL timed writes land left of the reference lines every other frame"]
+    fn test_nmi_sync_pal_edge_flashes_between_references() {
+        // As on hardware, the timed line starts between the upper and lower
+        // reference lines in every frame, and its left pixels flash
+        let edges = nmi_sync_edges("roms/nmi_sync/demo_pal.nes");
+        for [upper, line, lower] in &edges {
+            assert!(
+                upper <= line && line <= lower,
+                "timed line outside the reference lines: {:?}",
+                edges
+            );
+        }
+        assert_ne!(edges[0][1], edges[1][1], "left edge doesn't flash");
+    }
+
+    #[test]
+    fn test_diff_image_marks_differing_pixels() {
+        let expected = [30, 60, 90, 0, 0, 0];
+        let actual = [30, 60, 90, 0, 0, 1];
+        assert_eq!(
+            diff_image(&expected, &actual),
+            (vec![20, 20, 20, 255, 0, 0], 1)
+        );
+    }
+
+    #[test]
+    fn test_mismatch_writes_actual_and_diff_images() {
+        let case = GoldenCase {
+            name: "mismatch_test",
+            rom_path: "roms/rainwarrior/palette.nes",
+            frames: 1,
+            script: "",
+        };
+        let golden_dir = Path::new(GOLDEN_DIR);
+        let golden = case.golden_path();
+        fs::create_dir_all(golden_dir).unwrap();
+        write_png(&golden, &vec![0xFF; 256 * 240 * 3]).unwrap();
+        let comparison = case.check(false);
+        fs::remove_file(&golden).unwrap();
+
+        let Ok(Comparison::Mismatch {
+            pixels,
+            actual,
+            diff,
+        }) = comparison
+        else {
+            panic!("Expected a mismatch, got {:?}", comparison);
+        };
+        assert!(pixels > 0);
+        assert_eq!(read_png(&actual).unwrap(), case.render().unwrap());
+        assert_eq!(read_png(&diff).unwrap().len(), 256 * 240 * 3);
+    }
+
+    #[test]
+    fn test_missing_golden_is_an_error() {
+        let case = GoldenCase {
+            name: "does_not_exist",
+            rom_path: "roms/rainwarrior/palette.nes",
+            frames: 1,
+            script: "",
+        };
+        let message = case.check(false).unwrap_err();
+        assert!(message.contains(UPDATE_ENV), "{}", message);
+    }
+}
@@ -0,0 +1,491 @@
+//! Headless batch runner
+//!
+//! Runs a ROM without a window, audio device or SDL at all, for a number of
+//! frames or until a stop condition is met, pressing buttons from an optional
+//! input script. Afterwards the screen can be saved as a PNG and the audio as
+//! a WAV file, and the outcome becomes the process exit code, so test ROMs can
+//! run in CI.
+//!
+//! Test ROMs are checked through their $6000 status byte or the text on
+//! screen, as described in `test_rom`.
+
+use crate::input::Button;
+use crate::movie::{Desync, MovieSession};
+use crate::nes::Nes;
+use crate::test_rom::{TestResult, TestStatus, screen_text};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Frames run when no limit is given (one minute of NTSC time)
+pub const DEFAULT_FRAMES: u64 = 3600;
+
+/// When to stop a headless run
+#[derive(Debug, Clone, PartialEq)]
+pub enum StopCondition {
+    /// The CPU is about to execute the instruction at this address
+    Pc(u16),
+    /// The test ROM reported a result at $6000
+    Status,
+    /// The screen shows this text (case-insensitive)
+    Text(String),
+}
+
+impl StopCondition {
+    /// Parse `pc:<ADDR>`, `status` or `text:<TEXT>`
+    pub fn parse(value: &str) -> Result<Self, String> {
+        if value.eq_ignore_ascii_case("status") {
+            return Ok(StopCondition::Status);
+        }
+        match value.split_once(':') {
+            Some((kind, addr)) if kind.eq_ignore_ascii_case("pc") => {
+                crate::debugger::parse_value(addr).map(StopCondition::Pc)
+            }
+            Some((kind, text)) if kind.eq_ignore_ascii_case("text") && !text.is_empty() => {
+                Ok(StopCondition::Text(text.to_string()))
+            }
+            _ => Err(format!(
+                "Invalid stop condition \"{}\" (use pc:<ADDR>, status or text:<TEXT>)",
+                value
+            )),
+        }
+    }
+}
+
+/// How a headless run ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// A stop condition was met, or all frames ran when there are none
+    Done(String),
+    /// The test ROM reported a failure code at $6000
+    Failed(u8),
+    /// The CPU executed a KIL opcode
+    Halted(u16),
+    /// No stop condition was met within the frame limit
+    TimedOut,
+    /// The played movie went out of sync with its recording
+    Desynced(Desync),
+}
+
+impl Outcome {
+    /// Process exit code: 0 when done, 3 for failures and desyncs and 4 for
+    /// timeouts
+    ///
+    /// 1 and 2 are left for errors and invalid command lines.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Outcome::Done(_) => 0,
+            Outcome::Failed(_) | Outcome::Halted(_) | Outcome::Desynced(_) => 3,
+            Outcome::TimedOut => 4,
+        }
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Done(reason) => write!(f, "{}", reason),
+            Outcome::Failed(code) => write!(f, "Test failed with status code ${:02X}", code),
+            Outcome::Halted(pc) => write!(f, "CPU halted at ${:04X}", pc),
+            Outcome::TimedOut => write!(f, "Timed out"),
+            Outcome::Desynced(desync) => write!(f, "{}", desync),
+        }
+    }
+}
+
+/// Buttons to hold from given frames on
+///
+/// Each line holds a frame number and the controller 1 buttons held from that
+/// frame on, for example `60 start` or `120 right a`. Buttons after a `|` are
+/// for controller 2. A frame number alone releases everything. Empty lines and
+/// lines starting with `#` are ignored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputScript {
+    /// Frame and buttons held on each controller, in frame order
+    changes: Vec<(u64, [Vec<Button>; 2])>,
+}
+
+impl InputScript {
+    const BUTTONS: [(&'static str, Button); 8] = [
+        ("a", Button::A),
+        ("b", Button::B),
+        ("select", Button::Select),
+        ("start", Button::Start),
+        ("up", Button::Up),
+        ("down", Button::Down),
+        ("left", Button::Left),
+        ("right", Button::Right),
+    ];
+
+    /// Read a script from a file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read input script \"{}\": {}", path.display(), e))?;
+        Self::parse(&text)
+            .map_err(|message| format!("Input script \"{}\": {}", path.display(), message))
+    }
+
+    /// Parse a script, reporting the first invalid line
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut changes: Vec<(u64, [Vec<Button>; 2])> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| format!("line {}: {}", number + 1, message);
+
+            let (frame, buttons) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let frame: u64 = frame
+                .parse()
+                .map_err(|_| error(format!("expected a frame number, got \"{}\"", frame)))?;
+            if let Some(&(previous, _)) = changes.last()
+                && frame <= previous
+            {
+                return Err(error(format!(
+                    "frame {} does not come after frame {}",
+                    frame, previous
+                )));
+            }
+
+            let (player1, player2) = buttons.split_once('|').unwrap_or((buttons, ""));
+            let held = [
+                Self::parse_buttons(player1).map_err(error)?,
+                Self::parse_buttons(player2).map_err(error)?,
+            ];
+            changes.push((frame, held));
+        }
+        Ok(Self { changes })
+    }
+
+    fn parse_buttons(names: &str) -> Result<Vec<Button>, String> {
+        names
+            .split_whitespace()
+            .map(|name| {
+                Self::BUTTONS
+                    .iter()
+                    .find(|(button_name, _)| button_name.eq_ignore_ascii_case(name))
+                    .map(|&(_, button)| button)
+                    .ok_or_else(|| format!("unknown button \"{}\"", name))
+            })
+            .collect()
+    }
+
+    /// Set the buttons for `frame`, if the script changes them there
+    pub fn apply(&self, frame: u64, nes: &mut Nes) {
+        let Ok(index) = self.changes.binary_search_by_key(&frame, |&(at, _)| at) else {
+            return;
+        };
+        for (controller, held) in [1u8, 2].into_iter().zip(&self.changes[index].1) {
+            for (_, button) in Self::BUTTONS {
+                nes.set_button(controller, button, held.contains(&button));
+            }
+        }
+    }
+}
+
+/// Runs a `Nes` frame by frame until a stop condition is met
+pub struct Runner<'a> {
+    nes: &'a mut Nes,
+    script: InputScript,
+    conditions: Vec<StopCondition>,
+    status: TestStatus,
+    /// Audio samples, if they are being recorded
+    samples: Option<Vec<f32>>,
+    /// Movie being recorded or played
+    movie: Option<&'a mut MovieSession>,
+    frames: u64,
+}
+
+impl<'a> Runner<'a> {
+    pub fn new(nes: &'a mut Nes, script: InputScript, conditions: Vec<StopCondition>) -> Self {
+        Self {
+            nes,
+            script,
+            conditions,
+            status: TestStatus::new(),
+            samples: None,
+            movie: None,
+            frames: 0,
+        }
+    }
+
+    /// Keep the audio samples, to save with `save_wav`
+    pub fn record_audio(&mut self) {
+        self.samples = Some(Vec::new());
+    }
+
+    /// Record the input into a movie, or play one back
+    ///
+    /// A played movie sets the buttons after the input script.
+    pub fn set_movie(&mut self, movie: &'a mut MovieSession) {
+        self.movie = Some(movie);
+    }
+
+    /// Run up to `max_frames` frames
+    ///
+    /// Stop conditions are checked after every instruction (`Pc`) or at the
+    /// end of every frame (`Status`, `Text`).
+    pub fn run(&mut self, max_frames: u64) -> Outcome {
+        while self.frames < max_frames {
+            self.script.apply(self.frames, self.nes);
+            if let Some(movie) = &mut self.movie {
+                movie.start_frame(self.nes);
+            }
+            if let Some(outcome) = self.run_frame() {
+                return outcome;
+            }
+            self.frames += 1;
+            if let Some(movie) = &mut self.movie
+                && let Err(desync) = movie.end_frame(self.nes)
+            {
+                return Outcome::Desynced(desync);
+            }
+            if let Some(outcome) = self.check_frame() {
+                return outcome;
+            }
+        }
+        if self.conditions.is_empty() {
+            Outcome::Done(format!("Ran {} frames", self.frames))
+        } else {
+            Outcome::TimedOut
+        }
+    }
+
+    /// Frames completed so far
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Recorded audio samples (empty unless `record_audio` was called)
+    pub fn samples(&self) -> &[f32] {
+        self.samples.as_deref().unwrap_or_default()
+    }
+
+    /// Emulate until the PPU finishes a frame, checking PC conditions
+    fn run_frame(&mut self) -> Option<Outcome> {
+        while !self.nes.is_ready_to_render() {
+            self.nes.run_cpu_tick();
+            self.status.poll(self.nes);
+            while self.nes.sample_ready() {
+                if let Some(sample) = self.nes.get_sample()
+                    && let Some(samples) = &mut self.samples
+                {
+                    samples.push(sample);
+                }
+            }
+
+            let pc = self.nes.cpu.get_state().pc;
+            if self.nes.cpu.is_halted() {
+                return Some(Outcome::Halted(pc));
+            }
+            if self.conditions.contains(&StopCondition::Pc(pc)) {
+                return Some(Outcome::Done(format!("Reached ${:04X}", pc)));
+            }
+        }
+        self.nes.clear_ready_to_render();
+        None
+    }
+
+    /// Check the conditions that are only looked at once per frame
+    fn check_frame(&mut self) -> Option<Outcome> {
+        if self.conditions.contains(&StopCondition::Status) {
+            match self.status.check(self.nes) {
+                Some(TestResult::Pass) => return Some(Outcome::Done("Test passed".to_string())),
+                Some(TestResult::Fail(code)) => return Some(Outcome::Failed(code)),
+                None => {}
+            }
+        }
+        let texts: Vec<&String> = self
+            .conditions
+            .iter()
+            .filter_map(|condition| match condition {
+                StopCondition::Text(text) => Some(text),
+                _ => None,
+            })
+            .collect();
+        if !texts.is_empty() {
+            let screen = screen_text(self.nes).to_lowercase();
+            if let Some(text) = texts
+                .into_iter()
+                .find(|text| screen.contains(&text.to_lowercase()))
+            {
+                return Some(Outcome::Done(format!("Found \"{}\" on screen", text)));
+            }
+        }
+        None
+    }
+}
+
+/// CRC-32 of the screen's RGB bytes, for comparing frames between runs
+pub fn frame_hash(nes: &Nes) -> u32 {
+    crc32fast::hash(nes.ppu().screen_buffer().pixels())
+}
+
+/// Width and height of the screen in pixels
+pub const SCREEN_SIZE: (u32, u32) = (256, 240);
+
+/// Save the screen as an RGB PNG
+pub fn save_png(path: &Path, nes: &Nes) -> io::Result<()> {
+    write_png(path, nes.ppu().screen_buffer().pixels())
+}
+
+/// Save 256x240 RGB pixels as a PNG
+pub fn write_png(path: &Path, pixels: &[u8]) -> io::Result<()> {
+    let out = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(out, SCREEN_SIZE.0, SCREEN_SIZE.1);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(pixels).map_err(io::Error::other)
+}
+
+/// Save APU samples as a mono 16-bit PCM WAV file
+pub fn save_wav(path: &Path, samples: &[f32], sample_rate: u32) -> io::Result<()> {
+    write_wav(
+        &mut BufWriter::new(File::create(path)?),
+        samples,
+        sample_rate,
+    )
+}
+
+fn write_wav(out: &mut impl Write, samples: &[f32], sample_rate: u32) -> io::Result<()> {
+    // The APU mixer outputs 0.0-1.177, scaled to full range as in the audio output
+    const NES_APU_MAX: f32 = 1.177;
+    const BYTES_PER_SAMPLE: u32 = 2;
+
+    let data_size = samples.len() as u32 * BYTES_PER_SAMPLE;
+    out.write_all(b"RIFF")?;
+    out.write_all(&(36 + data_size).to_le_bytes())?;
+    out.write_all(b"WAVEfmt ")?;
+    out.write_all(&16u32.to_le_bytes())?; // fmt chunk size
+    out.write_all(&1u16.to_le_bytes())?; // PCM
+    out.write_all(&1u16.to_le_bytes())?; // mono
+    out.write_all(&sample_rate.to_le_bytes())?;
+    out.write_all(&(sample_rate * BYTES_PER_SAMPLE).to_le_bytes())?; // byte rate
+    out.write_all(&(BYTES_PER_SAMPLE as u16).to_le_bytes())?; // block align
+    out.write_all(&16u16.to_le_bytes())?; // bits per sample
+    out.write_all(b"data")?;
+    out.write_all(&data_size.to_le_bytes())?;
+    for &sample in samples {
+        let value = (sample / NES_APU_MAX).clamp(-1.0, 1.0) * i16::MAX as f32;
+        out.write_all(&(value as i16).to_le_bytes())?;
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stop_conditions() {
+        assert_eq!(
+            StopCondition::parse("pc:$C000"),
+            Ok(StopCondition::Pc(0xC000))
+        );
+        assert_eq!(
+            StopCondition::parse("PC:0x8000"),
+            Ok(StopCondition::Pc(0x8000))
+        );
+        assert_eq!(StopCondition::parse("status"), Ok(StopCondition::Status));
+        assert_eq!(
+            StopCondition::parse("text:Passed"),
+            Ok(StopCondition::Text("Passed".to_string()))
+        );
+        assert!(StopCondition::parse("text:").is_err());
+        assert!(StopCondition::parse("frame:10").is_err());
+        assert!(StopCondition::parse("pc:nowhere").is_err());
+    }
+
+    #[test]
+    fn test_parse_input_script() {
+        let script = InputScript::parse("# title screen\n60 start\n\n62\n120 right A | b\n")
+            .expect("valid script");
+        assert_eq!(
+            script.changes,
+            vec![
+                (60, [vec![Button::Start], vec![]]),
+                (62, [vec![], vec![]]),
+                (120, [vec![Button::Right, Button::A], vec![Button::B]]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_input_script_errors_name_the_line() {
+        assert_eq!(
+            InputScript::parse("10 a\nten b"),
+            Err("line 2: expected a frame number, got \"ten\"".to_string())
+        );
+        assert_eq!(
+            InputScript::parse("10 turbo"),
+            Err("line 1: unknown button \"turbo\"".to_string())
+        );
+        assert!(InputScript::parse("10 a\n10 b").is_err());
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(Outcome::Done(String::new()).exit_code(), 0);
+        assert_eq!(Outcome::Failed(2).exit_code(), 3);
+        assert_eq!(Outcome::Halted(0x8000).exit_code(), 3);
+        assert_eq!(Outcome::TimedOut.exit_code(), 4);
+    }
+
+    #[test]
+    fn test_runs_frames_without_conditions() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let mut runner = Runner::new(&mut nes, InputScript::default(), vec![]);
+        assert_eq!(runner.run(3), Outcome::Done("Ran 3 frames".to_string()));
+        assert_eq!(runner.frames(), 3);
+    }
+
+    #[test]
+    fn test_stops_at_pc() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        nes.run_cpu_tick();
+        let pc = nes.cpu.get_state().pc;
+
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let mut runner = Runner::new(
+            &mut nes,
+            InputScript::default(),
+            vec![StopCondition::Pc(pc)],
+        );
+        assert_eq!(runner.run(1), Outcome::Done(format!("Reached ${:04X}", pc)));
+        assert_eq!(runner.frames(), 0);
+    }
+
+    #[test]
+    fn test_text_condition_times_out() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let conditions = vec![StopCondition::Text("Passed".to_string())];
+        let mut runner = Runner::new(&mut nes, InputScript::default(), conditions);
+        assert_eq!(runner.run(2), Outcome::TimedOut);
+    }
+
+    #[test]
+    fn test_status_condition_passes_blargg_rom() {
+        let mut nes = Nes::power_on("roms/blargg/cpu_reset/registers.nes");
+        let mut runner = Runner::new(
+            &mut nes,
+            InputScript::default(),
+            vec![StopCondition::Status],
+        );
+        assert_eq!(runner.run(1200), Outcome::Done("Test passed".to_string()));
+        assert!(screen_text(&nes).contains("Passed"));
+    }
+
+    #[test]
+    fn test_write_wav_header() {
+        let mut out = Vec::new();
+        write_wav(&mut out, &[0.0, 1.177], 44100).unwrap();
+        assert_eq!(&out[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(out[4..8].try_into().unwrap()), 36 + 4);
+        assert_eq!(&out[8..16], b"WAVEfmt ");
+        assert_eq!(u32::from_le_bytes(out[24..28].try_into().unwrap()), 44100);
+        assert_eq!(&out[36..40], b"data");
+        assert_eq!(&out[44..], &[0, 0, 0xFF, 0x7F]);
+    }
+}
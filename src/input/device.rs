@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// Device plugged into a controller port
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputDevice {
+    /// Standard NES controller
+    #[default]
+    Controller,
+    /// NES Zapper light gun
+    Zapper,
+}
@@ -1,3 +1,5 @@
+use crate::savestate::{SaveState, StateReader, StateWriter};
+
 /// NES Controller Button
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Button {
@@ -72,6 +74,30 @@ impl Joypad {
             self.button_states &= !(1 << bit);
         }
     }
+
+    /// The held buttons, one bit per `Button`
+    pub fn buttons(&self) -> u8 {
+        self.button_states
+    }
+
+    /// Set all buttons at once, one bit per `Button`
+    pub fn set_buttons(&mut self, buttons: u8) {
+        self.button_states = buttons;
+    }
+}
+
+/// The held buttons are input from the player, not part of the state
+impl SaveState for Joypad {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bool(self.strobe);
+        state.write_u8(self.button_index);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        self.strobe = state.read_bool()?;
+        self.button_index = state.read_u8()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -1,3 +1,7 @@
+mod device;
 mod joypad;
+mod zapper;
 
+pub use device::InputDevice;
 pub use joypad::{Button, Joypad};
+pub use zapper::Zapper;
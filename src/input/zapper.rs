@@ -0,0 +1,129 @@
+use crate::screen_buffer::ScreenBuffer;
+
+/// NES Zapper light gun
+///
+/// The Zapper reports two signals on bits 3 and 4 of its port register:
+/// - Bit 3: light sensed (0 = light detected, 1 = no light)
+/// - Bit 4: trigger (1 = pulled)
+///
+/// The photodiode sees light when the aimed-at pixel is bright and the electron
+/// beam drew it recently. Its output stays active for a number of scanlines
+/// after the beam passes, which games rely on when they poll the port.
+#[derive(Default)]
+pub struct Zapper {
+    /// Screen pixel the gun points at, or `None` when aimed off-screen
+    aim: Option<(u32, u32)>,
+    trigger: bool,
+}
+
+impl Zapper {
+    /// Number of scanlines the photodiode stays active after the beam passes
+    const LIGHT_SCANLINES: u16 = 20;
+
+    /// Minimum brightness (0-255) that counts as light
+    const LIGHT_THRESHOLD: u32 = 0x80;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Point the gun at a screen pixel, or `None` to aim away from the screen
+    pub fn set_aim(&mut self, aim: Option<(u32, u32)>) {
+        self.aim = aim.filter(|&(x, y)| x < 256 && y < 240);
+    }
+
+    /// Pull or release the trigger
+    pub fn set_trigger(&mut self, pulled: bool) {
+        self.trigger = pulled;
+    }
+
+    /// Read the port register ($4017)
+    ///
+    /// # Arguments
+    /// * `scanline` - Scanline the PPU is currently drawing
+    /// * `pixel` - Pixel (cycle) within the scanline
+    /// * `screen` - Frame being drawn, used to check the brightness of the aimed-at pixel
+    pub fn read(&self, scanline: u16, pixel: u16, screen: &ScreenBuffer) -> u8 {
+        let light = if self.senses_light(scanline, pixel, screen) {
+            0x00
+        } else {
+            0x08
+        };
+        let trigger = if self.trigger { 0x10 } else { 0x00 };
+        light | trigger
+    }
+
+    /// Check whether the photodiode currently sees light
+    fn senses_light(&self, scanline: u16, pixel: u16, screen: &ScreenBuffer) -> bool {
+        let Some((x, y)) = self.aim else {
+            return false;
+        };
+
+        // The beam must already have drawn the target pixel in this frame
+        let drawn = scanline as u32 > y || (scanline as u32 == y && pixel as u32 > x + 1);
+        let recent = (scanline as u32) < y + Self::LIGHT_SCANLINES as u32;
+        if !drawn || !recent {
+            return false;
+        }
+
+        let (r, g, b) = screen.get_pixel(x, y);
+        // Perceived brightness (ITU-R BT.601 weights)
+        let brightness = (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000;
+        brightness >= Self::LIGHT_THRESHOLD
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen_with_white_pixel(x: u32, y: u32) -> ScreenBuffer {
+        let mut screen = ScreenBuffer::new();
+        screen.set_pixel(x, y, 0xFF, 0xFF, 0xFF);
+        screen
+    }
+
+    #[test]
+    fn test_idle_zapper_reports_no_light() {
+        let zapper = Zapper::new();
+        assert_eq!(zapper.read(100, 100, &ScreenBuffer::new()), 0x08);
+    }
+
+    #[test]
+    fn test_trigger() {
+        let mut zapper = Zapper::new();
+        zapper.set_trigger(true);
+        assert_eq!(zapper.read(0, 0, &ScreenBuffer::new()) & 0x10, 0x10);
+        zapper.set_trigger(false);
+        assert_eq!(zapper.read(0, 0, &ScreenBuffer::new()) & 0x10, 0x00);
+    }
+
+    #[test]
+    fn test_light_sensed_after_beam_passes_bright_pixel() {
+        let screen = screen_with_white_pixel(100, 50);
+        let mut zapper = Zapper::new();
+        zapper.set_aim(Some((100, 50)));
+
+        // Beam has not reached the pixel yet
+        assert_eq!(zapper.read(40, 0, &screen) & 0x08, 0x08);
+        // Beam just drew it
+        assert_eq!(zapper.read(52, 0, &screen) & 0x08, 0x00);
+        // Photodiode output has decayed
+        assert_eq!(zapper.read(90, 0, &screen) & 0x08, 0x08);
+    }
+
+    #[test]
+    fn test_dark_pixel_is_not_light() {
+        let screen = screen_with_white_pixel(100, 50);
+        let mut zapper = Zapper::new();
+        zapper.set_aim(Some((20, 50)));
+        assert_eq!(zapper.read(52, 0, &screen) & 0x08, 0x08);
+    }
+
+    #[test]
+    fn test_aim_off_screen() {
+        let mut zapper = Zapper::new();
+        zapper.set_aim(Some((300, 10)));
+        assert_eq!(zapper.aim, None);
+    }
+}
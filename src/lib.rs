@@ -5,12 +5,31 @@ pub mod apu;
 pub mod audio;
 pub mod blargg_tests;
 pub mod cartridge;
+pub mod cdl;
+pub mod cheats;
+pub mod cli;
+pub mod config;
 pub mod cpu;
 pub mod cpu2; // Second attempt at cycle-accurate CPU
+pub mod cpu_diff;
+pub mod debugger;
+pub mod disasm;
 pub mod eventloop;
+pub mod golden;
+pub mod headless;
 pub mod input;
 pub mod mem_controller;
+pub mod movie;
 pub mod nes;
+pub mod netplay;
 pub mod newcpu; // New cycle-accurate CPU implementation
+pub mod osd;
 pub mod ppu; // Modular PPU structure
+pub mod rewind;
+pub mod runahead;
+pub mod savestate;
 pub mod screen_buffer;
+pub mod symbols;
+pub mod test_rom;
+pub mod trace;
+pub mod worker;
@@ -1,111 +1,623 @@
 mod apu;
 mod audio;
 mod cartridge;
+mod cdl;
+mod cheats;
+mod cli;
+mod config;
 mod cpu;
 mod cpu2;
+mod debugger;
+mod disasm;
 mod eventloop;
+mod headless;
 mod input;
 mod mem_controller;
+mod movie;
 mod nes;
+mod netplay;
 mod newcpu;
+mod osd;
 mod ppu;
+mod rewind;
+mod runahead;
+mod savestate;
 mod screen_buffer;
+mod symbols;
+mod test_rom;
+mod trace;
+mod worker;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Parse command-line arguments
-    let args: Vec<String> = std::env::args().collect();
-
-    // Show help if requested
-    if args.contains(&"--help".to_string()) || args.contains(&"-h".to_string()) {
-        println!("NES Emulator");
-        println!("\nUsage: neser [OPTIONS]");
-        println!("\nOptions:");
-        println!("  -pal                  Use PAL TV system (default: NTSC)");
-        println!("  --no-audio            Disable audio output");
-        println!("\nAPU Channel Control (for debugging):");
-        println!("  --disable-pulse1      Mute pulse 1 channel");
-        println!("  --disable-pulse2      Mute pulse 2 channel");
-        println!("  --disable-triangle    Mute triangle channel");
-        println!("  --disable-noise       Mute noise channel");
-        println!("  --disable-dmc         Mute DMC channel");
-        println!("\nExample:");
-        println!("  neser --disable-pulse2 --disable-triangle    # Only pulse1, noise, and DMC");
-        return Ok(());
-    }
-
-    let tv_system = if args.contains(&"-pal".to_string()) {
-        nes::TvSystem::Pal
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let options = match cli::parse_args(&args) {
+        Ok(cli::Command::Help) => {
+            println!("{}", cli::usage());
+            return;
+        }
+        Ok(cli::Command::Run(options)) => *options,
+        Ok(cli::Command::TestRoms(options)) => match test_roms(options) {
+            Ok(code) => std::process::exit(code),
+            Err(message) => {
+                eprintln!("neser: {}", message);
+                std::process::exit(1);
+            }
+        },
+        Ok(cli::Command::Nes20Db(path)) => {
+            match std::fs::read_to_string(&path) {
+                Ok(xml) => {
+                    for entry in cartridge::import_nes20db(&xml) {
+                        println!("{}", entry);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("neser: Failed to read {}: {}", path, e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Ok(cli::Command::Disasm(options)) => {
+            if let Err(message) = disassemble(options) {
+                eprintln!("neser: {}", message);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Err(message) => {
+            eprintln!("neser: {}", message);
+            std::process::exit(2);
+        }
+    };
+
+    match run(options) {
+        Ok(0) => {}
+        Ok(code) => std::process::exit(code),
+        Err(message) => {
+            eprintln!("neser: {}", message);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Load the ROM and run the emulator with the given options
+///
+/// Returns the exit code, which is only non-zero for failed headless runs.
+fn run(options: cli::CliOptions) -> Result<i32, String> {
+    let config_path = config_path(&options);
+    let config = match &config_path {
+        Some(path) => config::Config::load(path),
+        None => config::Config::default(),
+    };
+
+    let cart = load_cartridge(&options.rom_path, options.archive_member.as_deref())?;
+
+    if let Some(title) = cart.title() {
+        println!("Loaded {}", title);
+    }
+
+    // Game overrides apply on top of the config file, and command-line options on top of both
+    let mut config = match &config_path {
+        Some(path) => config.for_game(path, cart.crc32()),
+        None => config,
+    };
+    apply_cli_options(&mut config, &options);
+
+    if options.save_game_settings {
+        let path = config_path
+            .as_deref()
+            .ok_or("Cannot save game settings: no config directory")?;
+        let saved = config
+            .save_game_overrides(path, cart.crc32())
+            .map_err(|e| format!("Cannot save game settings: {}", e))?;
+        println!("Saved game settings to {}", saved.display());
+    }
+
+    let movie = match &options.play_movie {
+        Some(path) => Some(movie::Movie::load(std::path::Path::new(path))?),
+        None => None,
+    };
+
+    // A movie plays in its own region; without an explicit region, use the one
+    // from the (corrected) header
+    let tv_system = match &movie {
+        Some(movie) => movie.tv_system,
+        None => config
+            .tv_system()
+            .unwrap_or_else(|| cart.region().tv_system()),
+    };
+
+    let rom_sizes = (cart.header().prg_rom_size, cart.header().chr_rom_size);
+    let crc32 = cart.crc32();
+    let mut nes_instance = nes::Nes::new(tv_system);
+    nes_instance.insert_cartridge(cart);
+    nes_instance.apply_config(&config);
+    nes_instance.reset();
+
+    // Apply sample rate and channel enable/disable settings
+    {
+        let apu = nes_instance.bus.apu_mut();
+        apu.set_sample_rate(config.audio.sample_rate as f32);
+        apu.set_pulse1_enabled(!options.disable_pulse1);
+        apu.set_pulse2_enabled(!options.disable_pulse2);
+        apu.set_triangle_enabled(!options.disable_triangle);
+        apu.set_noise_enabled(!options.disable_noise);
+        apu.set_dmc_enabled(!options.disable_dmc);
+    }
+
+    for path in &options.symbol_files {
+        let count = nes_instance
+            .load_symbols(std::path::Path::new(path))
+            .map_err(|e| format!("Cannot load symbols \"{}\": {}", path, e))?;
+        println!("Loaded {} symbols from {}", count, path);
+    }
+
+    if let Some(path) = &options.cdl_path {
+        nes_instance.start_cdl(load_cdl(std::path::Path::new(path), rom_sizes)?);
+    }
+
+    if let Some(cheats) = load_cheats(&options, config_path.as_deref(), crc32)? {
+        *nes_instance.cheats_mut() = cheats;
+    }
+
+    let mut session = match movie {
+        Some(movie) => Some(movie::MovieSession::play(movie, &mut nes_instance)?),
+        None if options.record_movie.is_some() => {
+            let rom_name = std::path::Path::new(&options.rom_path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned());
+            Some(movie::MovieSession::record(&nes_instance, rom_name))
+        }
+        None => None,
+    };
+
+    let result = if options.debug {
+        let stdin = std::io::stdin();
+        debugger::run_terminal(&mut nes_instance, stdin.lock(), std::io::stdout())
+            .map(|()| 0)
+            .map_err(|e| format!("Debugger I/O error: {}", e))
+    } else if let Some(port) = options.gdb_port {
+        run_gdb(&mut nes_instance, port).map(|()| 0)
+    } else if options.headless {
+        run_headless(&mut nes_instance, &options, &config, session.as_mut())
     } else {
-        nes::TvSystem::Ntsc
+        run_window(
+            &mut nes_instance,
+            &options,
+            &config,
+            tv_system,
+            session.as_mut(),
+        )
+        .map(|()| 0)
     };
-    let no_audio = args.contains(&"--no-audio".to_string());
 
-    // Channel enable/disable flags (default: all enabled)
-    let pulse1_enabled = !args.contains(&"--disable-pulse1".to_string());
-    let pulse2_enabled = !args.contains(&"--disable-pulse2".to_string());
-    let triangle_enabled = !args.contains(&"--disable-triangle".to_string());
-    let noise_enabled = !args.contains(&"--disable-noise".to_string());
-    let dmc_enabled = !args.contains(&"--disable-dmc".to_string());
+    if let (Some(path), Some(session)) = (&options.record_movie, &session) {
+        session.movie().save(std::path::Path::new(path))?;
+        println!(
+            "Saved movie of {} frames to {}",
+            session.movie().frames.len(),
+            path
+        );
+    }
+
+    if let (Some(path), Some(cdl)) = (&options.cdl_path, nes_instance.cdl()) {
+        cdl.save(std::path::Path::new(path))
+            .map_err(|e| format!("Cannot write code/data log \"{}\": {}", path, e))?;
+        println!("{}", cdl.coverage());
+    }
+    result
+}
+
+/// Wait for a GDB connection on a local port and serve it
+fn run_gdb(nes_instance: &mut nes::Nes, port: u16) -> Result<(), String> {
+    let listener = std::net::TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| format!("Cannot listen on port {}: {}", port, e))?;
+    println!("Waiting for GDB on 127.0.0.1:{}", port);
+    debugger::run_gdb_server(nes_instance, &listener)
+        .map_err(|e| format!("GDB connection error: {}", e))
+}
+
+/// Run without SDL until the frame limit or a stop condition, and save the results
+///
+/// Returns the exit code for the outcome.
+fn run_headless(
+    nes_instance: &mut nes::Nes,
+    options: &cli::CliOptions,
+    config: &config::Config,
+    session: Option<&mut movie::MovieSession>,
+) -> Result<i32, String> {
+    let script = match &options.input_script {
+        Some(path) => headless::InputScript::load(std::path::Path::new(path))?,
+        None => headless::InputScript::default(),
+    };
+    let mut runner = headless::Runner::new(nes_instance, script, options.until.clone());
+    if options.wav_path.is_some() {
+        runner.record_audio();
+    }
+    // A played movie runs to its end unless told otherwise
+    let mut default_frames = headless::DEFAULT_FRAMES;
+    if let Some(session) = session {
+        if session.mode() == movie::MovieMode::Playing {
+            default_frames = session.movie().frames.len() as u64;
+        }
+        runner.set_movie(session);
+    }
+    let outcome = runner.run(options.frames.unwrap_or(default_frames));
+    let frames = runner.frames();
+
+    if let Some(path) = &options.wav_path {
+        headless::save_wav(
+            std::path::Path::new(path),
+            runner.samples(),
+            config.audio.sample_rate,
+        )
+        .map_err(|e| format!("Cannot write audio \"{}\": {}", path, e))?;
+    }
+    if let Some(path) = &options.screenshot_path {
+        headless::save_png(std::path::Path::new(path), nes_instance)
+            .map_err(|e| format!("Cannot write screenshot \"{}\": {}", path, e))?;
+    }
+
+    println!("{} after {} frames", outcome, frames);
+    println!("Frame hash: {:08x}", headless::frame_hash(nes_instance));
+    if let headless::Outcome::Failed(_) = outcome {
+        println!("Console output:\n{}", test_rom::screen_text(nes_instance));
+    }
+    Ok(outcome.exit_code())
+}
+
+/// Load a code/data log to continue, or start a new one if the file does not exist
+fn load_cdl(
+    path: &std::path::Path,
+    (prg_size, chr_size): (usize, usize),
+) -> Result<cdl::CodeDataLog, String> {
+    if !path.exists() {
+        return Ok(cdl::CodeDataLog::new(prg_size, chr_size));
+    }
+    cdl::CodeDataLog::load(path, prg_size, chr_size)
+        .map_err(|e| format!("Cannot load code/data log \"{}\": {}", path.display(), e))
+}
+
+/// Open the window and run the emulator until it is closed
+fn run_window(
+    nes_instance: &mut nes::Nes,
+    options: &cli::CliOptions,
+    config: &config::Config,
+    tv_system: nes::TvSystem,
+    session: Option<&mut movie::MovieSession>,
+) -> Result<(), String> {
+    // Connect before opening the window, which waits for the other player
+    let netplay = connect_netplay(options, nes_instance)?;
 
     // Initialize SDL2
     let sdl_context = sdl2::init()?;
 
-    // Create audio output (44.1 kHz) unless disabled
-    let audio = if no_audio {
+    // Create audio output unless disabled
+    let audio = if options.no_audio {
         None
     } else {
-        Some(audio::NesAudio::new(&sdl_context, 44100)?)
+        let audio = audio::NesAudio::new(
+            &sdl_context,
+            config.audio.sample_rate as i32,
+            config.audio.latency_ms,
+        )?;
+        audio.set_volume(config.audio.volume as f32 / 100.0);
+        Some(audio)
     };
 
-    let mut event_loop = eventloop::EventLoop::new(false, tv_system, 4.0, 1.0, audio)?;
-    let mut nes_instance = nes::Nes::new(tv_system);
+    let mut event_loop = eventloop::EventLoop::new(
+        false,
+        tv_system,
+        config.video.scale,
+        config.emulation.speed,
+        audio,
+    )?;
+    event_loop.set_input_config(&config.input, &config.hotkeys);
+    event_loop.set_overscan(config.video.overscan)?;
+    event_loop.set_rewind(&config.rewind);
+    event_loop.set_tas(options.tas);
+    if config.run_ahead.frames > 0 {
+        event_loop.set_run_ahead(run_ahead(
+            options,
+            config,
+            tv_system,
+            nes_instance.cheats(),
+        )?);
+    }
+    if let Some(netplay) = netplay {
+        event_loop.set_netplay(netplay);
+    }
 
-    // Palette display requiring only scanline-based palette changes,
-    // intended to demonstrate the full palette even on less advanced emulators
-    // Seems to work ok!
-    // let rom_data = std::fs::read("roms/rainwarrior/palette.nes")?;
-
-    // Simple display of any chosen color full-screen
-    // Seems to work ok!
-    // let rom_data = std::fs::read("roms/rainwarrior/color_test.nes")?;
-
-    // NTSC Torture Test displays visual patterns to demonstrate NTSC signal artifacts
-    // Shows a but for x < 8 pixels on each scanline where the leftmost 8 pixels are incorrect
-    // but otherwise seems to work well
-    // let rom_data = std::fs::read("roms/rainwarrior/ntsc_torture.nes")?;
-
-    // Verifies NMI timing by creating a specific pattern on the screen (NTSC & PAL versions)
-    // Not working ok right now. NMI sync seems off.
-    // let rom_data = std::fs::read("roms/nmi_sync/demo_ntsc.nes")?;
-
-    // let rom_data = std::fs::read("roms/cpu_interrupts.nes")?;
-
-    // Load game cartridge
-    // let rom_data = std::fs::read("roms/games/pac-man.nes")?;
-    // let rom_data = std::fs::read("roms/games/Balloon_fight.nes")?;
-    // let rom_data = std::fs::read("roms/games/donkey kong.nes")?;
-    let rom_data = std::fs::read("roms/games/zelda.nes")?;
-
-    // Unknown status
-    // let rom_data = std::fs::read("roms/full_nes_palette.nes")?;
-    // let rom_data = std::fs::read("roms/nmi_sync/demo_ntsc.nes")?;
-    // let rom_data = std::fs::read("roms/blargg/4015_cleared.nes")?;
-    // let rom_data = std::fs::read("roms/blargg/cpu_interrupts_v2/rom_singles/5-branch_delays_irq.nes")?;
-    let cart = cartridge::Cartridge::new(&rom_data)?;
-    nes_instance.insert_cartridge(cart);
-    nes_instance.reset();
+    let mut trace = match &options.trace_path {
+        Some(path) => Some(
+            trace::TraceLogger::create(std::path::Path::new(path), options.trace)
+                .map_err(|e| format!("Cannot create trace log \"{}\": {}", path, e))?,
+        ),
+        None => None,
+    };
+    event_loop.run(nes_instance, trace.as_mut(), session)
+}
 
-    // Apply channel enable/disable settings
-    {
-        let mut apu = nes_instance.apu.borrow_mut();
-        apu.set_pulse1_enabled(pulse1_enabled);
-        apu.set_pulse2_enabled(pulse2_enabled);
-        apu.set_triangle_enabled(triangle_enabled);
-        apu.set_noise_enabled(noise_enabled);
-        apu.set_dmc_enabled(dmc_enabled);
+/// Set up run-ahead, loading the ROM again for a second instance if configured
+fn run_ahead(
+    options: &cli::CliOptions,
+    config: &config::Config,
+    tv_system: nes::TvSystem,
+    cheats: &cheats::CheatList,
+) -> Result<runahead::RunAhead, String> {
+    let frames = config.run_ahead.frames;
+    if !config.run_ahead.second_instance {
+        return Ok(runahead::RunAhead::new(frames));
+    }
+    let mut second = nes::Nes::new(tv_system);
+    second.insert_cartridge(load_cartridge(
+        &options.rom_path,
+        options.archive_member.as_deref(),
+    )?);
+    second.apply_config(config);
+    *second.cheats_mut() = cheats.clone();
+    Ok(runahead::RunAhead::with_second_instance(frames, second))
+}
+
+/// Load the cheat list given with `--cheats`, or else the game's own if it
+/// has one
+fn load_cheats(
+    options: &cli::CliOptions,
+    config_path: Option<&std::path::Path>,
+    crc32: u32,
+) -> Result<Option<cheats::CheatList>, String> {
+    let path = match (&options.cheats_path, config_path) {
+        (Some(path), _) => std::path::PathBuf::from(path),
+        (None, Some(config_path)) => {
+            let path = cheats::CheatList::path(config_path, crc32);
+            if !path.exists() {
+                return Ok(None);
+            }
+            path
+        }
+        (None, None) => return Ok(None),
+    };
+    let cheats = cheats::CheatList::load(&path)?;
+    println!(
+        "Loaded {} cheats from {}",
+        cheats.cheats().len(),
+        path.display()
+    );
+    Ok(Some(cheats))
+}
+
+/// Host or join a netplay game if asked to
+///
+/// Both sides must load the same ROM with the same settings.
+fn connect_netplay(
+    options: &cli::CliOptions,
+    nes_instance: &nes::Nes,
+) -> Result<Option<netplay::NetplaySession>, String> {
+    let delay = options
+        .netplay_delay
+        .map_or(netplay::NetplaySession::DEFAULT_DELAY, |delay| {
+            delay as usize
+        });
+    let session = if let Some(port) = options.netplay_host {
+        let socket = std::net::UdpSocket::bind(("0.0.0.0", port))
+            .map_err(|e| format!("Cannot listen on port {}: {}", port, e))?;
+        println!("Waiting for player 2 on UDP port {}", port);
+        netplay::NetplaySession::host(socket, nes_instance, delay)?
+    } else if let Some(address) = &options.netplay_join {
+        let peer = std::net::ToSocketAddrs::to_socket_addrs(address.as_str())
+            .ok()
+            .and_then(|mut addresses| addresses.next())
+            .ok_or_else(|| format!("Cannot resolve \"{}\"", address))?;
+        let local = if peer.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = std::net::UdpSocket::bind(local)
+            .map_err(|e| format!("Cannot open a UDP socket: {}", e))?;
+        println!("Joining {}", peer);
+        netplay::NetplaySession::join(socket, peer, nes_instance, delay)?
+    } else {
+        return Ok(None);
+    };
+    println!("Connected, playing as player {}", session.player());
+    Ok(Some(session))
+}
+
+/// Read and parse a ROM file
+fn load_cartridge(
+    path: &str,
+    archive_member: Option<&str>,
+) -> Result<cartridge::Cartridge, String> {
+    let rom_data = cartridge::load_rom(std::path::Path::new(path), archive_member)
+        .map_err(|e| format!("Cannot read ROM \"{}\": {}", path, e))?;
+    cartridge::Cartridge::new(&rom_data).map_err(|e| format!("Cannot load ROM \"{}\": {}", path, e))
+}
+
+/// Print a disassembly of a ROM (`neser disasm`)
+fn disassemble(options: cli::DisasmOptions) -> Result<(), String> {
+    let cart = load_cartridge(&options.rom_path, options.archive_member.as_deref())?;
+    let start = options.start.unwrap_or(0x8000);
+    let mut symbols = symbols::SymbolTable::new();
+    for path in &options.symbol_files {
+        symbols
+            .load(std::path::Path::new(path))
+            .map_err(|e| format!("Cannot load symbols \"{}\": {}", path, e))?;
+    }
+
+    let listing = match options.bank {
+        Some(bank) => {
+            let prg_rom = cart.mapper().prg_rom();
+            let banks = prg_rom.len() / cli::DISASM_BANK_SIZE;
+            if bank >= banks {
+                return Err(format!(
+                    "Bank {} does not exist, the ROM has {} banks of 16 KiB",
+                    bank, banks
+                ));
+            }
+            let bank_end = start.saturating_add((cli::DISASM_BANK_SIZE - 1) as u16);
+            let end = options.end.unwrap_or(bank_end).min(bank_end);
+            let read = disasm::prg_bank_reader(prg_rom, bank, cli::DISASM_BANK_SIZE, start);
+            let labels = symbols::MappedLabels {
+                symbols: &symbols,
+                prg_offset: |addr: u16| {
+                    let offset = addr.wrapping_sub(start) as usize;
+                    (addr >= 0x8000 && offset < cli::DISASM_BANK_SIZE)
+                        .then_some(bank * cli::DISASM_BANK_SIZE + offset)
+                },
+            };
+            disasm::listing(&disasm::disassemble(start, end, read), &labels)
+        }
+        None => {
+            let mut nes = nes::Nes::new(nes::TvSystem::Ntsc);
+            nes.insert_cartridge(cart);
+            let end = options.end.unwrap_or(0xFFFF);
+            let labels = symbols::MappedLabels {
+                symbols: &symbols,
+                prg_offset: |addr| nes.bus.prg_rom_offset(addr),
+            };
+            disasm::listing(
+                &disasm::disassemble(start, end, |addr| nes.peek(addr)),
+                &labels,
+            )
+        }
+    };
+
+    println!("{}", listing);
+    Ok(())
+}
+
+/// Run the test ROMs listed in manifests and print a pass/fail matrix
+/// (`neser test-roms`)
+///
+/// Returns exit code 3 if any ROM did not give the expected result.
+fn test_roms(options: cli::TestRomsOptions) -> Result<i32, String> {
+    let dir = std::path::Path::new(&options.dir);
+    let mut entries = test_rom::load_manifests(dir)?;
+    if let Some(filter) = &options.filter {
+        entries.retain(|entry| entry.rom.to_string_lossy().contains(filter.as_str()));
+        if entries.is_empty() {
+            return Err(format!("No test ROMs match \"{}\"", filter));
+        }
+    }
+
+    let names: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            entry
+                .rom
+                .strip_prefix(dir)
+                .unwrap_or(&entry.rom)
+                .display()
+                .to_string()
+        })
+        .collect();
+    let width = names.iter().map(String::len).max().unwrap_or(0);
+    let reports = test_rom::run_all(&entries);
+    println!(
+        "{:width$}  {:8}  {:14}  {:>6}",
+        "ROM", "Expected", "Result", "Frames"
+    );
+    let mut unexpected = 0;
+    for ((entry, name), report) in entries.iter().zip(&names).zip(reports) {
+        let expected = match entry.expected {
+            test_rom::Expected::Pass => "pass",
+            test_rom::Expected::Fail => "fail",
+        };
+        let report = match report {
+            Ok(report) => report,
+            Err(message) => {
+                unexpected += 1;
+                println!("{:width$}  {:8}  {}", name, expected, message);
+                continue;
+            }
+        };
+        let verdict = match (entry.expected, entry.expected.matches(report.result)) {
+            (_, true) => "",
+            (test_rom::Expected::Pass, false) => "  REGRESSION",
+            (test_rom::Expected::Fail, false) => "  FIXED",
+        };
+        println!(
+            "{:width$}  {:8}  {:14}  {:>6}{}",
+            name,
+            expected,
+            report.result.to_string(),
+            report.frames,
+            verdict
+        );
+        if !verdict.is_empty() {
+            unexpected += 1;
+            for line in report.text.lines() {
+                println!("    {}", line);
+            }
+        }
     }
 
-    event_loop
-        .run(&mut nes_instance, false)
-        .map_err(|e| e.into())
+    println!(
+        "\n{} ROMs, {} as expected, {} not",
+        entries.len(),
+        entries.len() - unexpected,
+        unexpected
+    );
+    Ok(if unexpected == 0 { 0 } else { 3 })
+}
+
+/// The config file given with `--config`, or the one in the user's config directory
+///
+/// The user's config file is created with the defaults if it doesn't exist yet.
+fn config_path(options: &cli::CliOptions) -> Option<std::path::PathBuf> {
+    match &options.config_path {
+        Some(path) => {
+            let path = std::path::PathBuf::from(path);
+            if !path.exists() {
+                eprintln!(
+                    "Warning: Config file {} does not exist. Using defaults.",
+                    path.display()
+                );
+            }
+            Some(path)
+        }
+        None => {
+            let path = config::Config::default_path()?;
+            if !path.exists() {
+                // Write the defaults on first run so there is a file to edit
+                match config::Config::default().save(&path) {
+                    Ok(()) => println!("Wrote default config to {}", path.display()),
+                    Err(e) => eprintln!(
+                        "Warning: Cannot write default config {}: {}",
+                        path.display(),
+                        e
+                    ),
+                }
+            }
+            Some(path)
+        }
+    }
+}
+
+/// Override config settings with the options given on the command line
+fn apply_cli_options(config: &mut config::Config, options: &cli::CliOptions) {
+    if let Some(scale) = options.scale {
+        config.video.scale = scale;
+    }
+    if let Some(speed) = options.speed {
+        config.emulation.speed = speed;
+    }
+    if let Some(tv_system) = options.tv_system {
+        config.emulation.region = match tv_system {
+            nes::TvSystem::Ntsc => "ntsc",
+            nes::TvSystem::Pal => "pal",
+        }
+        .to_string();
+    }
+    if let Some(sample_rate) = options.sample_rate {
+        config.audio.sample_rate = sample_rate;
+    }
+    if let Some(volume) = options.volume {
+        config.audio.volume = volume;
+    }
+    if let Some(frames) = options.run_ahead {
+        config.run_ahead.frames = frames;
+    }
 }
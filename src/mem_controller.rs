@@ -1,62 +1,227 @@
 use crate::apu;
 use crate::cartridge::Cartridge;
-use crate::input::Joypad;
-use crate::ppu;
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::cdl::{self, CodeDataLog};
+use crate::cheats::CheatList;
+use crate::debugger::{Access, Bus, WatchHit, Watchpoint};
+use crate::disasm;
+use crate::input::{InputDevice, Joypad, Zapper};
+use crate::nes::TvSystem;
+use crate::ppu::{self, ChrBus};
+use crate::savestate::{SaveState, StateReader, StateWriter};
 
 /// NES Memory (64KB address space)
+///
+/// The CPU bus, and the single owner of everything on it: the PPU, the APU,
+/// the cartridge and the controllers. The CPU borrows it mutably for every
+/// cycle, so no component is shared.
 pub struct MemController {
     cpu_ram: Vec<u8>,
-    cartridge: Option<Rc<RefCell<Cartridge>>>,
-    ppu: Rc<RefCell<ppu::Ppu>>,
-    apu: Rc<RefCell<apu::Apu>>,
+    cartridge: Option<Cartridge>,
+    ppu: ppu::Ppu,
+    apu: apu::Apu,
     oam_dma_page: Option<u8>, // Stores the page for pending OAM DMA
-    joypad1: RefCell<Joypad>,
-    joypad2: RefCell<Joypad>,
-    open_bus: RefCell<u8>, // Last value on the data bus for open bus behavior
+    oam_dma_byte: u8,         // Byte read by the last OAM DMA read cycle
+    joypad1: Joypad,
+    joypad2: Joypad,
+    /// $4016 was read since `take_input_read`, for counting lag frames
+    input_read: bool,
+    zapper: Zapper,
+    port2_device: InputDevice,
+    open_bus: u8, // Last value on the data bus for open bus behavior
+    watchpoints: Vec<(usize, Watchpoint)>,
+    watch_hits: Vec<WatchHit>, // Accesses that triggered a watchpoint
+    cdl: Option<CodeDataLog>,
+    cdl_instruction: Option<disasm::Instruction>, // Instruction being executed, for the CDL
+    flat: bool,                                   // All 64KB is plain RAM, for CPU tests
+    bus_log: Option<Vec<BusCycle>>,
+    clock: MasterClock,
+    /// Cheats patching PRG-ROM reads and poking RAM each frame
+    cheats: CheatList,
+}
+
+/// Position of the CPU, PPU and APU on the shared master clock
+///
+/// Both chips divide the same master clock: NTSC by 12 for the CPU and 4 for
+/// the PPU (3 dots per CPU cycle), PAL by 16 and 5 (3.2 dots). Every CPU bus
+/// access first runs the PPU up to the point of the access within the current
+/// CPU cycle, so register reads and writes see and change the PPU on the exact
+/// dot they happen. The rest of the cycle runs in `finish_cpu_cycles`.
+///
+/// The APU runs on the CPU clock and is clocked at the start of each cycle.
+/// It is caught up the same way, so an access sees it clocked for its cycle.
+struct MasterClock {
+    ppu_divider: u64,
+    cpu_divider: u64,
+    /// Offsets of reads and writes into a CPU cycle, in master clocks
+    read_offset: u64,
+    write_offset: u64,
+    /// Start of the current CPU cycle
+    cpu: u64,
+    /// Start of the next PPU dot
+    ppu: u64,
+    /// Start of the next CPU cycle the APU has not been clocked for
+    apu: u64,
+}
+
+impl MasterClock {
+    /// Master clocks the CPU starts after the PPU
+    ///
+    /// Consoles power up in one of several CPU/PPU alignments. This one starts
+    /// the PPU two dots ahead of the first CPU cycle and is the alignment the
+    /// blargg NMI and sprite 0 hit timing tests pass with.
+    const CPU_PHASE: u64 = 11;
+
+    fn new(tv_system: TvSystem) -> Self {
+        // Accesses happen around the middle of the cycle, where M2 rises:
+        // reads a master clock before it and writes a master clock after
+        let (read_offset, write_offset) = match tv_system {
+            TvSystem::Ntsc => (5, 7),
+            TvSystem::Pal => (7, 9),
+        };
+        Self {
+            ppu_divider: tv_system.master_clocks_per_ppu_dot(),
+            cpu_divider: tv_system.master_clocks_per_cpu_cycle(),
+            read_offset,
+            write_offset,
+            cpu: Self::CPU_PHASE,
+            ppu: 0,
+            apu: Self::CPU_PHASE,
+        }
+    }
+
+    /// Start both clocks again from their power-on alignment
+    fn restart(&mut self) {
+        self.cpu = Self::CPU_PHASE;
+        self.ppu = 0;
+        // The reset sequence reads without advancing the clock, and the APU
+        // only starts after it (see `restart_apu_clock`)
+        self.apu = u64::MAX;
+    }
+
+    /// Count the PPU dots that end by master clock `time` and move past them
+    fn take_dots_until(&mut self, time: u64) -> u64 {
+        if self.ppu + self.ppu_divider > time {
+            return 0;
+        }
+        let dots = (time - self.ppu) / self.ppu_divider;
+        self.ppu += dots * self.ppu_divider;
+        dots
+    }
+
+    /// Count the CPU cycles starting by master clock `time` that the APU has
+    /// not been clocked for, and move past them
+    fn take_apu_cycles_until(&mut self, time: u64) -> u64 {
+        if self.apu > time {
+            return 0;
+        }
+        let cycles = (time - self.apu) / self.cpu_divider + 1;
+        self.apu += cycles * self.cpu_divider;
+        cycles
+    }
+}
+
+/// One CPU bus access, as recorded by the bus log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusCycle {
+    pub addr: u16,
+    pub value: u8,
+    pub access: Access,
 }
 
 impl MemController {
-    /// Create a new memory instance with 64KB of RAM initialized to 0
-    pub fn new(ppu: Rc<RefCell<ppu::Ppu>>, apu: Rc<RefCell<apu::Apu>>) -> Self {
+    /// Create a new memory instance with 64KB of RAM initialized to 0, and a
+    /// PPU and APU for the TV system
+    pub fn new(tv_system: TvSystem) -> Self {
         Self {
             cpu_ram: vec![0; 0x10000],
             cartridge: None,
-            ppu,
-            apu,
+            ppu: ppu::Ppu::new(tv_system),
+            apu: apu::Apu::new(),
             oam_dma_page: None,
-            joypad1: RefCell::new(Joypad::new()),
-            joypad2: RefCell::new(Joypad::new()),
-            open_bus: RefCell::new(0xFF), // Initialize to 0xFF (common power-on state)
+            oam_dma_byte: 0,
+            joypad1: Joypad::new(),
+            joypad2: Joypad::new(),
+            input_read: false,
+            zapper: Zapper::new(),
+            port2_device: InputDevice::Controller,
+            open_bus: 0xFF, // Initialize to 0xFF (common power-on state)
+            watchpoints: Vec::new(),
+            watch_hits: Vec::new(),
+            cdl: None,
+            cdl_instruction: None,
+            flat: false,
+            bus_log: None,
+            clock: MasterClock::new(tv_system),
+            cheats: CheatList::new(),
         }
     }
 
-    /// Map a cartridge into memory
+    /// Create a memory with all 64KB mapped as RAM and no registers
     ///
-    /// This method:
-    /// 1. Wraps the cartridge in Rc<RefCell<>> for shared ownership
-    /// 2. Shares the cartridge with the PPU for CHR ROM/RAM access
-    /// 3. Configures initial PPU mirroring mode from mapper
-    /// 4. Makes cartridge accessible to CPU for PRG ROM/RAM operations
+    /// For CPU tests that expect a flat address space, such as the
+    /// SingleStepTests suites.
+    #[cfg(test)]
+    pub fn new_flat() -> Self {
+        Self {
+            flat: true,
+            ..Self::new(TvSystem::Ntsc)
+        }
+    }
+
+    /// Map a cartridge into memory
     ///
-    /// The shared reference pattern allows both CPU (via MemController) and PPU
-    /// to access the cartridge mapper independently while maintaining proper
-    /// bank switching state.
+    /// The memory controller owns the cartridge. PRG accesses go straight to its
+    /// mapper, and the PPU borrows it for CHR accesses (see `ChrBus`). The PPU
+    /// mirroring starts out as the mapper's.
     pub fn map_cartridge(&mut self, cartridge: Cartridge) {
-        // Wrap cartridge in Rc<RefCell<>> for shared access between CPU and PPU
-        let cartridge_rc = Rc::new(RefCell::new(cartridge));
+        self.ppu.set_mirroring(cartridge.mapper().get_mirroring());
+        self.cartridge = Some(cartridge);
+    }
+
+    /// The inserted cartridge, if any
+    pub fn cartridge(&self) -> Option<&Cartridge> {
+        self.cartridge.as_ref()
+    }
+
+    /// The PPU
+    pub fn ppu(&self) -> &ppu::Ppu {
+        &self.ppu
+    }
+
+    /// The PPU, mutably
+    pub fn ppu_mut(&mut self) -> &mut ppu::Ppu {
+        &mut self.ppu
+    }
+
+    /// The APU
+    pub fn apu(&self) -> &apu::Apu {
+        &self.apu
+    }
 
-        // Share cartridge reference with PPU for dynamic CHR access
-        let mut ppu = self.ppu.borrow_mut();
-        ppu.set_cartridge(cartridge_rc.clone());
-        ppu.set_mirroring(cartridge_rc.borrow().mapper().get_mirroring());
+    /// The APU, mutably
+    pub fn apu_mut(&mut self) -> &mut apu::Apu {
+        &mut self.apu
+    }
 
-        self.cartridge = Some(cartridge_rc);
+    /// The PPU, and the cartridge side of its bus to lend it for CHR accesses
+    fn chr_bus(&mut self) -> (&mut ppu::Ppu, ChrBus<'_>) {
+        let chr = ChrBus {
+            cartridge: self.cartridge.as_mut(),
+            cdl: self.cdl.as_mut(),
+        };
+        (&mut self.ppu, chr)
     }
 
     /// Read a byte from memory
-    pub fn read(&self, addr: u16) -> u8 {
+    pub fn read(&mut self, addr: u16) -> u8 {
+        if self.flat {
+            let value = self.cpu_ram[addr as usize];
+            self.log_bus_cycle(addr, value, Access::Read);
+            return value;
+        }
+        self.catch_up_apu(self.clock.cpu);
+        self.catch_up_ppu(self.clock.read_offset);
+        let ppu_data_addr = self.watched_ppu_data_addr(addr);
         let value = match addr {
             // RAM ($0000-$1FFF) with mirroring
             0x0000..=0x1FFF => self.cpu_ram[(addr & 0x07FF) as usize],
@@ -65,45 +230,60 @@ impl MemController {
             0x2000..=0x3FFF => match addr & 0x2007 {
                 // Write-only registers return the PPU I/O bus latch
                 // The PPU I/O bus is separate from the CPU data bus!
-                0x2000 => self.ppu.borrow().registers.io_bus(),
-                0x2001 => self.ppu.borrow().registers.io_bus(),
+                0x2000 => self.ppu.registers.io_bus(),
+                0x2001 => self.ppu.registers.io_bus(),
                 0x2002 => {
                     // PPUSTATUS: get_status() already handles updating I/O bus correctly
-                    self.ppu.borrow_mut().get_status()
+                    self.ppu.get_status()
+                }
+                0x2003 => self.ppu.registers.io_bus(),
+                0x2004 => self.ppu.read_oam_data(),
+                0x2005 => self.ppu.registers.io_bus(),
+                0x2006 => self.ppu.registers.io_bus(),
+                0x2007 => {
+                    let (ppu, mut chr) = self.chr_bus();
+                    ppu.read_data(&mut chr)
                 }
-                0x2003 => self.ppu.borrow().registers.io_bus(),
-                0x2004 => self.ppu.borrow_mut().read_oam_data(),
-                0x2005 => self.ppu.borrow().registers.io_bus(),
-                0x2006 => self.ppu.borrow().registers.io_bus(),
-                0x2007 => self.ppu.borrow_mut().read_data(),
                 _ => panic!("Should never happen!"),
             },
 
             // APU registers ($4000-$4017)
             // Most APU registers are write-only and return open bus when read
-            0x4000..=0x4013 => *self.open_bus.borrow(), // APU write-only registers
-            0x4014 => *self.open_bus.borrow(),          // OAM DMA (write-only)
-            0x4015 => self.apu.borrow_mut().read_status(*self.open_bus.borrow()),
+            0x4000..=0x4013 => self.open_bus, // APU write-only registers
+            0x4014 => self.open_bus,          // OAM DMA (write-only)
+            0x4015 => self.apu.read_status(self.open_bus),
             0x4016 => {
                 // Joypad 1: bit 0 = button state, bits 1-7 = open bus
-                let button_state = self.joypad1.borrow_mut().read();
-                let open_bus = *self.open_bus.borrow();
-                (open_bus & 0xFE) | button_state
-            }
-            0x4017 => {
-                // Joypad 2: bit 0 = button state, bits 1-7 = open bus
-                let button_state = self.joypad2.borrow_mut().read();
-                let open_bus = *self.open_bus.borrow();
+                self.input_read = true;
+                let button_state = self.joypad1.read();
+                let open_bus = self.open_bus;
                 (open_bus & 0xFE) | button_state
             }
+            0x4017 => match self.port2_device {
+                InputDevice::Controller => {
+                    // Joypad 2: bit 0 = button state, bits 1-7 = open bus
+                    let button_state = self.joypad2.read();
+                    let open_bus = self.open_bus;
+                    (open_bus & 0xFE) | button_state
+                }
+                InputDevice::Zapper => {
+                    // Zapper: bit 3 = light sense, bit 4 = trigger, bits 5-7 = open bus
+                    let ppu = &self.ppu;
+                    let state = self
+                        .zapper
+                        .read(ppu.scanline(), ppu.pixel(), ppu.screen_buffer());
+                    let open_bus = self.open_bus;
+                    (open_bus & 0xE0) | state
+                }
+            },
 
             // Unallocated I/O space ($4018-$40FF) returns open bus
-            0x4018..=0x40FF => *self.open_bus.borrow(),
+            0x4018..=0x40FF => self.open_bus,
 
             // PRG-RAM ($6000-$7FFF)
             0x6000..=0x7FFF => {
-                if let Some(ref cartridge) = self.cartridge {
-                    cartridge.borrow().mapper().read_prg(addr)
+                if let Some(cartridge) = &self.cartridge {
+                    cartridge.mapper().read_prg(addr)
                 } else {
                     eprintln!(
                         "Warning: Read from PRG-RAM {:04X} without cartridge, returning 0",
@@ -115,8 +295,9 @@ impl MemController {
 
             // PRG ROM ($8000-$FFFF)
             0x8000..=0xFFFF => {
-                if let Some(ref cartridge) = self.cartridge {
-                    cartridge.borrow().mapper().read_prg(addr)
+                if let Some(cartridge) = &self.cartridge {
+                    let value = cartridge.mapper().read_prg(addr);
+                    self.cheats.patch_rom_read(addr, value)
                 } else {
                     panic!("No cartridge mapped, cannot read from {:04X}", addr);
                 }
@@ -132,25 +313,213 @@ impl MemController {
             }
         };
 
+        if addr >= 0x8000 && self.cdl.is_some() {
+            self.log_data_read(addr);
+        }
+
         // Update open bus with the value read
-        *self.open_bus.borrow_mut() = value;
+        self.open_bus = value;
         // self.print_open_bus();
+        self.log_bus_cycle(addr, value, Access::Read);
+
+        if !self.watchpoints.is_empty() {
+            self.record_access(Bus::Cpu, addr, value, Access::Read);
+            if let Some(ppu_addr) = ppu_data_addr {
+                self.record_access(Bus::Ppu, ppu_addr, value, Access::Read);
+            }
+        }
         value
     }
 
+    /// Read the opcode of the next instruction
+    ///
+    /// Same as `read`, but lets the code/data logger mark the instruction bytes
+    /// as code and tell the instruction's operand reads apart from data reads.
+    pub fn read_opcode(&mut self, addr: u16) -> u8 {
+        if self.cdl.is_some() {
+            self.log_instruction(addr);
+        }
+        self.read(addr)
+    }
+
+    /// Read a byte without side effects, for debuggers
+    ///
+    /// RAM and cartridge space read normally. Registers, whose reads would change
+    /// PPU, APU or controller state, return the current open bus value instead.
+    pub fn peek(&self, addr: u16) -> u8 {
+        if self.flat {
+            return self.cpu_ram[addr as usize];
+        }
+        match addr {
+            0x0000..=0x1FFF => self.cpu_ram[(addr & 0x07FF) as usize],
+            0x6000..=0xFFFF => self
+                .cartridge
+                .as_ref()
+                .map_or(0, |cartridge| cartridge.mapper().read_prg(addr)),
+            _ => self.open_bus,
+        }
+    }
+
+    /// Get the PRG-ROM offset a CPU address is currently mapped to
+    ///
+    /// Returns `None` outside PRG-ROM or without a cartridge.
+    pub fn prg_rom_offset(&self, addr: u16) -> Option<usize> {
+        self.cartridge
+            .as_ref()
+            .and_then(|cartridge| cartridge.mapper().prg_rom_offset(addr))
+    }
+
+    /// Start recording PRG-ROM and CHR-ROM accesses in a code/data log
+    pub fn set_cdl(&mut self, cdl: CodeDataLog) {
+        self.cdl = Some(cdl);
+        self.cdl_instruction = None;
+    }
+
+    /// The code/data log, if one was started
+    pub fn cdl(&self) -> Option<&CodeDataLog> {
+        self.cdl.as_ref()
+    }
+
+    /// Record a DMC sample fetch in the code/data log
+    pub fn log_dmc_fetch(&mut self, addr: u16) {
+        if let Some(offset) = self.prg_rom_offset(addr)
+            && let Some(cdl) = &mut self.cdl
+        {
+            cdl.mark_prg(offset, addr, cdl::PCM);
+        }
+    }
+
+    /// Mark the bytes of the instruction at `addr` as code
+    ///
+    /// The opcode is also marked as an indirect jump target when the previous
+    /// instruction was `JMP ($nnnn)`.
+    fn log_instruction(&mut self, addr: u16) {
+        let instruction = disasm::decode(addr, |addr| self.peek(addr));
+        let previous = self.cdl_instruction.replace(instruction);
+        let jumped_indirect = previous.is_some_and(|previous| previous.mode == "IND");
+
+        for i in 0..instruction.size() {
+            let addr = addr.wrapping_add(i);
+            if let Some(offset) = self.prg_rom_offset(addr)
+                && let Some(cdl) = &mut self.cdl
+            {
+                let flags = if i == 0 && jumped_indirect {
+                    cdl::CODE | cdl::INDIRECT_CODE
+                } else {
+                    cdl::CODE
+                };
+                cdl.mark_prg(offset, addr, flags);
+            }
+        }
+    }
+
+    /// Mark a PRG-ROM read as data, unless the current instruction is fetching
+    /// its own bytes
+    ///
+    /// Instructions without operands still read the byte after the opcode, so
+    /// that byte is never counted as data either.
+    fn log_data_read(&mut self, addr: u16) {
+        let instruction = self.cdl_instruction;
+        if let Some(instruction) = instruction
+            && addr.wrapping_sub(instruction.addr) < instruction.size().max(2)
+        {
+            return;
+        }
+        let Some(offset) = self.prg_rom_offset(addr) else {
+            return;
+        };
+        let indirect =
+            instruction.is_some_and(|instruction| matches!(instruction.mode, "INDX" | "INDY"));
+        let flags = if indirect {
+            cdl::DATA | cdl::INDIRECT_DATA
+        } else {
+            cdl::DATA
+        };
+        if let Some(cdl) = &mut self.cdl {
+            cdl.mark_prg(offset, addr, flags);
+        }
+    }
+
+    /// Replace the watchpoints checked on every read and write
+    pub fn set_watchpoints(&mut self, watchpoints: Vec<(usize, Watchpoint)>) {
+        self.watchpoints = watchpoints;
+        self.watch_hits.clear();
+    }
+
+    /// Take the watchpoint hits recorded since the last call
+    pub fn take_watch_hits(&mut self) -> Vec<WatchHit> {
+        std::mem::take(&mut self.watch_hits)
+    }
+
+    /// PPU address accessed through PPUDATA, if a CPU access to `addr` is one
+    /// and any watchpoint could see it
+    fn watched_ppu_data_addr(&self, addr: u16) -> Option<u16> {
+        if self.watchpoints.is_empty() || !(0x2000..=0x3FFF).contains(&addr) || addr & 7 != 7 {
+            return None;
+        }
+        Some(self.ppu.v_register() & 0x3FFF)
+    }
+
+    /// Record a hit for every watchpoint matching an access
+    fn record_access(&mut self, bus: Bus, addr: u16, value: u8, access: Access) {
+        for (id, watchpoint) in &self.watchpoints {
+            if watchpoint.matches(bus, addr, access) {
+                self.watch_hits.push(WatchHit {
+                    id: *id,
+                    bus,
+                    addr,
+                    value,
+                    access,
+                });
+            }
+        }
+    }
+
+    /// Start recording every CPU bus read and write, in order
     #[cfg(test)]
-    pub fn read_for_testing(&self, addr: u16) -> u8 {
-        let old_open_bus = *self.open_bus.borrow_mut();
-        let value = self.read(addr);
-        *self.open_bus.borrow_mut() = old_open_bus;
-        value
+    pub fn start_bus_log(&mut self) {
+        self.bus_log = Some(Vec::new());
+    }
+
+    /// Take the bus cycles recorded since the last call
+    #[cfg(test)]
+    pub fn take_bus_log(&mut self) -> Vec<BusCycle> {
+        self.bus_log
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    fn log_bus_cycle(&mut self, addr: u16, value: u8, access: Access) {
+        if let Some(log) = &mut self.bus_log {
+            log.push(BusCycle {
+                addr,
+                value,
+                access,
+            });
+        }
     }
 
     /// Write a byte to memory
     /// Returns true if an OAM DMA was triggered (at $4014)
     pub fn write(&mut self, addr: u16, value: u8, is_dummy_write: bool) -> bool {
+        self.log_bus_cycle(addr, value, Access::Write);
+        if self.flat {
+            self.cpu_ram[addr as usize] = value;
+            return false;
+        }
+        self.catch_up_apu(self.clock.cpu);
+        self.catch_up_ppu(self.clock.write_offset);
+
         // Update open bus with the value being written
-        *self.open_bus.borrow_mut() = value;
+        self.open_bus = value;
+
+        if !self.watchpoints.is_empty() {
+            self.record_access(Bus::Cpu, addr, value, Access::Write);
+            if let Some(ppu_addr) = self.watched_ppu_data_addr(addr) {
+                self.record_access(Bus::Ppu, ppu_addr, value, Access::Write);
+            }
+        }
 
         // println!("Write to {:04X}: {:02X}", addr, value);
         match addr {
@@ -161,65 +530,55 @@ impl MemController {
 
             // PPU registers ($2000-$3FFF) with mirroring every 8 bytes
             0x2000..=0x3FFF => match addr & 0x2007 {
-                0x2000 => self.ppu.borrow_mut().write_control(value),
-                0x2001 => self.ppu.borrow_mut().write_mask(value),
+                0x2000 => self.ppu.write_control(value),
+                0x2001 => self.ppu.write_mask(value),
                 0x2002 => {
                     // PPUSTATUS is read-only, but writes still update the I/O bus!
-                    self.ppu.borrow_mut().registers.set_io_bus(value);
+                    self.ppu.registers.set_io_bus(value);
+                }
+                0x2003 => self.ppu.write_oam_address(value),
+                0x2004 => self.ppu.write_oam_data(value),
+                0x2005 => self.ppu.write_scroll(value, is_dummy_write),
+                0x2006 => self.ppu.write_address(value, is_dummy_write),
+                0x2007 => {
+                    let (ppu, mut chr) = self.chr_bus();
+                    ppu.write_data(value, &mut chr);
                 }
-                0x2003 => self.ppu.borrow_mut().write_oam_address(value),
-                0x2004 => self.ppu.borrow_mut().write_oam_data(value),
-                0x2005 => self.ppu.borrow_mut().write_scroll(value, is_dummy_write),
-                0x2006 => self.ppu.borrow_mut().write_address(value, is_dummy_write),
-                0x2007 => self.ppu.borrow_mut().write_data(value),
                 _ => panic!("Should never happen!"),
             },
 
             // APU and I/O registers ($4000-$4017)
             0x4000..=0x4017 => match addr {
                 // Pulse 1 registers
-                0x4000 => self.apu.borrow_mut().pulse1_mut().write_control(value),
-                0x4001 => self.apu.borrow_mut().pulse1_mut().write_sweep(value),
-                0x4002 => self.apu.borrow_mut().pulse1_mut().write_timer_low(value),
-                0x4003 => self
-                    .apu
-                    .borrow_mut()
-                    .pulse1_mut()
-                    .write_length_counter_timer_high(value),
+                0x4000 => self.apu.pulse1_mut().write_control(value),
+                0x4001 => self.apu.pulse1_mut().write_sweep(value),
+                0x4002 => self.apu.pulse1_mut().write_timer_low(value),
+                0x4003 => self.apu.pulse1_mut().write_length_counter_timer_high(value),
 
                 // Pulse 2 registers
-                0x4004 => self.apu.borrow_mut().pulse2_mut().write_control(value),
-                0x4005 => self.apu.borrow_mut().pulse2_mut().write_sweep(value),
-                0x4006 => self.apu.borrow_mut().pulse2_mut().write_timer_low(value),
-                0x4007 => self
-                    .apu
-                    .borrow_mut()
-                    .pulse2_mut()
-                    .write_length_counter_timer_high(value),
+                0x4004 => self.apu.pulse2_mut().write_control(value),
+                0x4005 => self.apu.pulse2_mut().write_sweep(value),
+                0x4006 => self.apu.pulse2_mut().write_timer_low(value),
+                0x4007 => self.apu.pulse2_mut().write_length_counter_timer_high(value),
 
                 // Triangle registers
-                0x4008 => self
-                    .apu
-                    .borrow_mut()
-                    .triangle_mut()
-                    .write_linear_counter(value),
-                0x400A => self.apu.borrow_mut().triangle_mut().write_timer_low(value),
+                0x4008 => self.apu.triangle_mut().write_linear_counter(value),
+                0x400A => self.apu.triangle_mut().write_timer_low(value),
                 0x400B => self
                     .apu
-                    .borrow_mut()
                     .triangle_mut()
                     .write_length_counter_timer_high(value),
 
                 // Noise registers
-                0x400C => self.apu.borrow_mut().noise_mut().write_envelope(value),
-                0x400E => self.apu.borrow_mut().noise_mut().write_period(value),
-                0x400F => self.apu.borrow_mut().noise_mut().write_length(value),
+                0x400C => self.apu.noise_mut().write_envelope(value),
+                0x400E => self.apu.noise_mut().write_period(value),
+                0x400F => self.apu.noise_mut().write_length(value),
 
                 // DMC registers
-                0x4010 => self.apu.borrow_mut().dmc_mut().write_flags_and_rate(value),
-                0x4011 => self.apu.borrow_mut().dmc_mut().write_direct_load(value),
-                0x4012 => self.apu.borrow_mut().dmc_mut().write_sample_address(value),
-                0x4013 => self.apu.borrow_mut().dmc_mut().write_sample_length(value),
+                0x4010 => self.apu.dmc_mut().write_flags_and_rate(value),
+                0x4011 => self.apu.dmc_mut().write_direct_load(value),
+                0x4012 => self.apu.dmc_mut().write_sample_address(value),
+                0x4013 => self.apu.dmc_mut().write_sample_length(value),
 
                 0x4014 => {
                     // OAMDMA - Store the page for later DMA execution
@@ -228,13 +587,13 @@ impl MemController {
                 }
 
                 // APU Control registers
-                0x4015 => self.apu.borrow_mut().write_enable(value),
+                0x4015 => self.apu.write_enable(value),
                 0x4016 => {
                     // Controller strobe - write to both controllers
-                    self.joypad1.borrow_mut().write_strobe(value);
-                    self.joypad2.borrow_mut().write_strobe(value);
+                    self.joypad1.write_strobe(value);
+                    self.joypad2.write_strobe(value);
                 }
-                0x4017 => self.apu.borrow_mut().write_frame_counter(value),
+                0x4017 => self.apu.write_frame_counter(value),
 
                 // Unused APU registers
                 0x4009 | 0x400D => {
@@ -265,8 +624,8 @@ impl MemController {
                         addr, value, value as char
                     );
                 }
-                if let Some(ref cartridge) = self.cartridge {
-                    cartridge.borrow_mut().mapper_mut().write_prg(addr, value);
+                if let Some(cartridge) = &mut self.cartridge {
+                    cartridge.mapper_mut().write_prg(addr, value);
                 } else {
                     eprintln!(
                         "Warning: Write to PRG-RAM {:04X} without cartridge, ignored",
@@ -278,8 +637,8 @@ impl MemController {
             // PRG ROM area ($8000-$FFFF)
             // Writes here are typically mapper register writes, not ROM writes
             0x8000..=0xFFFF => {
-                if let Some(ref cartridge) = self.cartridge {
-                    cartridge.borrow_mut().mapper_mut().write_prg(addr, value);
+                if let Some(cartridge) = &mut self.cartridge {
+                    cartridge.mapper_mut().write_prg(addr, value);
                 } else {
                     eprintln!(
                         "Warning: Write to PRG ROM area {:04X} without cartridge, ignored",
@@ -300,7 +659,7 @@ impl MemController {
     }
 
     /// Read a 16-bit word from memory (little-endian)
-    pub fn read_u16(&self, addr: u16) -> u16 {
+    pub fn read_u16(&mut self, addr: u16) -> u16 {
         let lo = self.read(addr) as u16;
         let hi = self.read(addr.wrapping_add(1)) as u16;
         (hi << 8) | lo
@@ -320,28 +679,182 @@ impl MemController {
         self.oam_dma_page.take()
     }
 
-    /// Execute an OAM DMA transfer from the specified page to OAM
-    /// Returns the number of bytes transferred (always 256)
-    pub fn execute_oam_dma(&mut self, page: u8) {
-        let source_page = (page as u16) << 8;
-        for i in 0..256u16 {
-            let byte = self.read(source_page + i);
-            self.ppu.borrow_mut().write_oam_data(byte);
+    /// Run one transfer cycle of an OAM DMA from `page`
+    ///
+    /// The 512 transfer cycles alternate between reading the next byte of the
+    /// page and writing it to OAMDATA, each at its own point on the master
+    /// clock. `cycle` counts them from 0.
+    pub fn oam_dma_cycle(&mut self, page: u8, cycle: u16) {
+        let addr = ((page as u16) << 8) | (cycle / 2);
+        if cycle.is_multiple_of(2) {
+            self.oam_dma_byte = self.read(addr);
+        } else {
+            self.write(0x2004, self.oam_dma_byte, false);
+        }
+    }
+
+    /// Run the PPU to `offset` master clocks into the current CPU cycle
+    fn catch_up_ppu(&mut self, offset: u64) {
+        let dots = self.clock.take_dots_until(self.clock.cpu + offset);
+        if dots > 0 {
+            let (ppu, mut chr) = self.chr_bus();
+            ppu.run_ppu_cycles(dots, &mut chr);
         }
     }
 
+    /// Clock the APU for every CPU cycle starting by master clock `time`
+    fn catch_up_apu(&mut self, time: u64) {
+        for _ in 0..self.clock.take_apu_cycles_until(time) {
+            self.apu.clock();
+            if let Some(addr) = self.apu.dmc_mut().take_sample_fetch() {
+                self.log_dmc_fetch(addr);
+            }
+        }
+    }
+
+    /// End the current CPU cycle and the next `cycles - 1`, running the PPU up
+    /// to the start of the cycle after them and clocking the APU for them
+    ///
+    /// Called after each CPU cycle's bus access (if any), and for cycles
+    /// without one, such as those of interrupt entry.
+    pub fn finish_cpu_cycles(&mut self, cycles: u64) {
+        self.clock.cpu += cycles * self.clock.cpu_divider;
+        self.catch_up_apu(self.clock.cpu - 1);
+        self.catch_up_ppu(0);
+    }
+
+    /// Restart the CPU and PPU clocks in their power-on alignment, after the
+    /// PPU has been reset, and run the PPU up to the first CPU cycle
+    pub fn restart_clock(&mut self) {
+        self.clock.restart();
+        self.catch_up_ppu(0);
+    }
+
+    /// Start the APU at the current CPU cycle, once the reset sequence is done
+    pub fn restart_apu_clock(&mut self) {
+        self.clock.apu = self.clock.cpu;
+    }
+
     /// Set button state for a controller
     pub fn set_button(&mut self, controller: u8, button: crate::input::Button, pressed: bool) {
         match controller {
-            1 => self.joypad1.borrow_mut().set_button(button, pressed),
-            2 => self.joypad2.borrow_mut().set_button(button, pressed),
+            1 => self.joypad1.set_button(button, pressed),
+            2 => self.joypad2.set_button(button, pressed),
+            _ => {}
+        }
+    }
+
+    /// Held buttons of a controller, one bit per `Button`
+    pub fn buttons(&self, controller: u8) -> u8 {
+        match controller {
+            1 => self.joypad1.buttons(),
+            2 => self.joypad2.buttons(),
+            _ => 0,
+        }
+    }
+
+    /// Set all buttons of a controller, one bit per `Button`
+    pub fn set_buttons(&mut self, controller: u8, buttons: u8) {
+        match controller {
+            1 => self.joypad1.set_buttons(buttons),
+            2 => self.joypad2.set_buttons(buttons),
             _ => {}
         }
     }
 
+    /// The cheats applied to the game
+    pub fn cheats(&self) -> &CheatList {
+        &self.cheats
+    }
+
+    /// The cheats applied to the game, to add or switch them
+    pub fn cheats_mut(&mut self) -> &mut CheatList {
+        &mut self.cheats
+    }
+
+    /// Write the values of RAM cheats, once per frame
+    ///
+    /// The writes bypass the bus, so they take no time and trigger no
+    /// watchpoints.
+    pub fn apply_ram_cheats(&mut self) {
+        for &(addr, value) in self.cheats.ram_writes() {
+            match addr {
+                0x0000..=0x1FFF => self.cpu_ram[(addr & 0x07FF) as usize] = value,
+                _ => {
+                    if let Some(cartridge) = &mut self.cartridge {
+                        cartridge.mapper_mut().write_prg(addr, value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether the game read $4016 since the last call
+    pub fn take_input_read(&mut self) -> bool {
+        std::mem::take(&mut self.input_read)
+    }
+
+    /// Select the device plugged into controller port 2
+    pub fn set_port2_device(&mut self, device: InputDevice) {
+        self.port2_device = device;
+    }
+
+    /// Aim the Zapper at a screen pixel, or `None` to aim off-screen
+    pub fn set_zapper_aim(&mut self, aim: Option<(u32, u32)>) {
+        self.zapper.set_aim(aim);
+    }
+
+    /// Pull or release the Zapper trigger
+    pub fn set_zapper_trigger(&mut self, pulled: bool) {
+        self.zapper.set_trigger(pulled);
+    }
+
     /// Print the current open bus value to stdout (for debugging)
     pub fn print_open_bus(&self) {
-        println!("Open bus: 0x{:02X}", *self.open_bus.borrow());
+        println!("Open bus: 0x{:02X}", self.open_bus);
+    }
+}
+
+/// Only the 2 KiB of internal RAM is saved, not the flat test memory. The
+/// Zapper, port 2 device and debugging aids are not part of the state.
+impl SaveState for MemController {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.cpu_ram[..0x0800]);
+        state.write_bool(self.cartridge.is_some());
+        if let Some(cartridge) = &self.cartridge {
+            cartridge.save_state(state);
+        }
+        self.ppu.save_state(state);
+        self.apu.save_state(state);
+        state.write_option_u8(self.oam_dma_page);
+        self.joypad1.save_state(state);
+        self.joypad2.save_state(state);
+        state.write_bool(self.input_read);
+        state.write_u8(self.open_bus);
+        state.write_u64(self.clock.cpu);
+        state.write_u64(self.clock.ppu);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.read_bytes_into(&mut self.cpu_ram[..0x0800])?;
+        if state.read_bool()? != self.cartridge.is_some() {
+            return Err("The state does not match the inserted cartridge".to_string());
+        }
+        if let Some(cartridge) = &mut self.cartridge {
+            cartridge.load_state(state)?;
+        }
+        self.ppu.load_state(state)?;
+        self.apu.load_state(state)?;
+        self.oam_dma_page = state.read_option_u8()?;
+        self.joypad1.load_state(state)?;
+        self.joypad2.load_state(state)?;
+        self.input_read = state.read_bool()?;
+        self.open_bus = state.read_u8()?;
+        self.clock.cpu = state.read_u64()?;
+        self.clock.ppu = state.read_u64()?;
+        // States are saved between instructions, where the APU is caught up
+        self.clock.apu = self.clock.cpu;
+        Ok(())
     }
 }
 
@@ -350,14 +863,12 @@ mod tests {
     use super::*;
 
     fn create_test_memory() -> MemController {
-        let ppu = Rc::new(RefCell::new(ppu::Ppu::new(crate::nes::TvSystem::Ntsc)));
-        let apu = Rc::new(RefCell::new(apu::Apu::new()));
-        MemController::new(ppu, apu)
+        MemController::new(TvSystem::Ntsc)
     }
 
     #[test]
     fn test_new_memory_is_initialized() {
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
         assert_eq!(memory.read(0x0000), 0);
         assert_eq!(memory.read(0x1234), 0);
         assert_eq!(memory.read(0x3FFF), 0);
@@ -725,7 +1236,7 @@ mod tests {
     #[test]
     fn test_read_apu_status_register() {
         // Test reading from $4015 returns APU status
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Reading $4015 should return the APU status register
         let status = memory.read(0x4015);
@@ -739,11 +1250,11 @@ mod tests {
     #[test]
     fn test_read_apu_status_after_enable() {
         // Test that reading $4015 returns the APU's status
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Directly configure pulse 1 through the APU to test reading
         {
-            let mut apu = memory.apu.borrow_mut();
+            let apu = &mut memory.apu;
             apu.write_enable(0b0000_0001); // Enable pulse 1
             // Set length counter to non-zero by writing to register 3
             apu.pulse1_mut()
@@ -758,7 +1269,7 @@ mod tests {
     #[test]
     fn test_apu_status_register_mirrored() {
         // Test that $4015 is not mirrored (only accessible at exact address)
-        let memory = create_test_memory();
+        let mut memory = create_test_memory();
 
         // Reading exactly $4015 should work
         let status = memory.read(0x4015);
@@ -790,7 +1301,7 @@ mod tests {
         memory.write(0x4003, 0b11111000, false);
 
         // Verify writes reached the APU by checking pulse1 length counter
-        let apu = memory.apu.borrow();
+        let apu = &memory.apu;
         assert!(apu.pulse1().get_length_counter() > 0);
     }
 
@@ -809,7 +1320,7 @@ mod tests {
         memory.write(0x4007, 0b11110000, false);
 
         // Verify writes reached the APU
-        let apu = memory.apu.borrow();
+        let apu = &memory.apu;
         assert!(apu.pulse2().get_length_counter() > 0);
     }
 
@@ -828,7 +1339,7 @@ mod tests {
         memory.write(0x400B, 0b11110000, false);
 
         // Verify writes reached the APU
-        let apu = memory.apu.borrow();
+        let apu = &memory.apu;
         assert!(apu.triangle().get_length_counter() > 0);
     }
 
@@ -847,7 +1358,7 @@ mod tests {
         memory.write(0x400F, 0b11110000, false);
 
         // Verify writes reached the APU
-        let apu = memory.apu.borrow();
+        let apu = &memory.apu;
         assert!(apu.noise().get_length_counter() > 0);
     }
 
@@ -897,7 +1408,37 @@ mod tests {
         memory.write(0x4017, 0b10000000, false);
 
         // Verify write reached the APU
-        let apu = memory.apu.borrow();
+        let apu = &memory.apu;
         assert_eq!(apu.frame_counter().get_mode(), true);
     }
+
+    #[test]
+    fn test_reading_4016_counts_as_input() {
+        let mut memory = MemController::new(TvSystem::Ntsc);
+        assert!(!memory.take_input_read());
+        memory.read(0x4017);
+        memory.peek(0x4016);
+        assert!(!memory.take_input_read());
+        memory.read(0x4016);
+        assert!(memory.take_input_read());
+        assert!(!memory.take_input_read());
+    }
+
+    #[test]
+    fn test_zapper_on_port_2() {
+        let mut memory = create_test_memory();
+        memory.set_port2_device(InputDevice::Zapper);
+
+        // No light, trigger released: only the "no light" bit is set
+        assert_eq!(memory.read(0x4017) & 0x1F, 0x08);
+
+        memory.set_zapper_trigger(true);
+        assert_eq!(memory.read(0x4017) & 0x1F, 0x18);
+
+        // Port 1 still reads the controller
+        memory.set_button(1, crate::input::Button::A, true);
+        memory.write(0x4016, 1, false);
+        memory.write(0x4016, 0, false);
+        assert_eq!(memory.read(0x4016) & 0x01, 1);
+    }
 }
@@ -0,0 +1,981 @@
+//! Input movies
+//!
+//! A movie holds the controller input of every frame since power-on or since
+//! an embedded save state, so a run can be replayed exactly, e.g. to
+//! reproduce a bug or as a regression test.
+//! It names the ROM it was recorded with by CRC-32, and every
+//! `MovieSession::HASH_INTERVAL` frames it stores a hash of CPU RAM, so
+//! playback can tell when it no longer does what the recording did.
+//!
+//! Movies are text files. The native format starts with a small header,
+//! followed by one line per frame in the style of FCEUX movies:
+//!
+//! ```text
+//! neser-movie 1
+//! rom-name game.nes
+//! rom-crc32 3337EC46
+//! rom-md5 8E3630186E35D477231BF8FD50E54CDD
+//! region ntsc
+//! start power-on
+//! |0|........|........|
+//! |0|....T...|........|
+//! |1|R......A|........|5A1C03F2
+//! ```
+//!
+//! Each frame line holds the commands (1 presses reset before the frame) and
+//! the buttons of both controllers in the order RLDUTSBA (T is Start, S is
+//! Select), with `.` for released buttons. The RAM hash after the frame
+//! follows the last `|` where there is one.
+//!
+//! A movie that starts from a save state has `start savestate` followed by
+//! the state in hex instead of `start power-on`.
+//!
+//! FCEUX `.fm2` movies with standard controllers can be imported and exported.
+//! FCEUX identifies the ROM by MD5 and each movie by a GUID, which exported
+//! movies carry; a new GUID is made on every export. The MD5 of an imported
+//! movie is kept but not checked against the loaded ROM.
+//! A save state in an `.fm2` movie can only be one of ours, as FCEUX states
+//! cannot be loaded.
+
+use crate::nes::{Nes, TvSystem};
+use crate::savestate;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// First line of a native movie file
+const NATIVE_MAGIC: &str = "neser-movie 1";
+
+/// Button letters of a frame line, for bits 7 down to 0
+const BUTTON_LETTERS: &[u8; 8] = b"RLDUTSBA";
+
+/// Command bit to press reset before a frame
+const COMMAND_RESET: u8 = 1;
+/// Command bit to power-cycle before a frame (FCEUX)
+const COMMAND_POWER: u8 = 2;
+
+/// Input of one frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MovieFrame {
+    /// Held buttons of controllers 1 and 2, one bit per `Button`
+    pub buttons: [u8; 2],
+    /// Press reset before the frame
+    pub reset: bool,
+    /// Hash of CPU RAM after the frame, see `ram_hash`
+    pub ram_hash: Option<u32>,
+}
+
+/// Recorded input, from power-on or a save state
+#[derive(Debug, Clone, PartialEq)]
+pub struct Movie {
+    /// File name or title of the ROM, for people reading the movie
+    pub rom_name: Option<String>,
+    /// CRC-32 of the ROM (unknown for imported FCEUX movies)
+    pub rom_crc32: Option<u32>,
+    /// MD5 of the ROM, which FCEUX movies name it by
+    pub rom_md5: Option<[u8; 16]>,
+    pub tv_system: TvSystem,
+    /// State the first frame starts from, or `None` for power-on
+    pub start_state: Option<Vec<u8>>,
+    pub frames: Vec<MovieFrame>,
+}
+
+impl Movie {
+    /// Read a movie, as FCEUX `.fm2` if the file name says so
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read movie \"{}\": {}", path.display(), e))?;
+        let parsed = if is_fm2(path) {
+            Self::parse_fm2(&text)
+        } else {
+            Self::parse(&text)
+        };
+        parsed.map_err(|message| format!("Movie \"{}\": {}", path.display(), message))
+    }
+
+    /// Write a movie, as FCEUX `.fm2` if the file name says so
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = if is_fm2(path) {
+            self.to_fm2()
+        } else {
+            self.to_text()
+        };
+        fs::write(path, text)
+            .map_err(|e| format!("Cannot write movie \"{}\": {}", path.display(), e))
+    }
+
+    /// Parse a native movie, reporting the first invalid line
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate();
+        match lines.next() {
+            Some((_, line)) if line.trim() == NATIVE_MAGIC => {}
+            _ => return Err(format!("not a movie, expected \"{}\"", NATIVE_MAGIC)),
+        }
+
+        let mut movie = Movie {
+            rom_name: None,
+            rom_crc32: None,
+            rom_md5: None,
+            tv_system: TvSystem::Ntsc,
+            start_state: None,
+            frames: Vec::new(),
+        };
+        for (number, line) in lines {
+            let error = |message: String| format!("line {}: {}", number + 1, message);
+            let line = line.trim();
+            if line.starts_with('|') {
+                movie.frames.push(parse_frame(line).map_err(error)?);
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if !movie.frames.is_empty() {
+                return Err(error("header line after the input".to_string()));
+            }
+            let (key, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let value = value.trim();
+            match key {
+                "rom-name" => movie.rom_name = Some(value.to_string()),
+                "rom-crc32" => {
+                    movie.rom_crc32 =
+                        Some(u32::from_str_radix(value, 16).map_err(|_| {
+                            error(format!("expected a hex CRC-32, got \"{}\"", value))
+                        })?);
+                }
+                "rom-md5" => {
+                    let md5 = parse_hex(value)
+                        .and_then(|bytes| <[u8; 16]>::try_from(bytes).ok())
+                        .ok_or_else(|| error(format!("expected a hex MD5, got \"{}\"", value)))?;
+                    movie.rom_md5 = Some(md5);
+                }
+                "region" => {
+                    movie.tv_system = match crate::cli::parse_region(value) {
+                        Ok(Some(tv_system)) => tv_system,
+                        _ => return Err(error(format!("expected ntsc or pal, got \"{}\"", value))),
+                    };
+                }
+                "start" => {
+                    movie.start_state = match value.split_once(char::is_whitespace) {
+                        None if value == "power-on" => None,
+                        Some(("savestate", state)) => {
+                            Some(parse_state(state.trim()).map_err(error)?)
+                        }
+                        _ => {
+                            return Err(error(format!(
+                                "expected power-on or savestate, got \"{}\"",
+                                value
+                            )));
+                        }
+                    };
+                }
+                _ => return Err(error(format!("unknown header \"{}\"", key))),
+            }
+        }
+        Ok(movie)
+    }
+
+    /// The movie in the native format
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\n", NATIVE_MAGIC);
+        if let Some(name) = &self.rom_name {
+            text.push_str(&format!("rom-name {}\n", name));
+        }
+        if let Some(crc32) = self.rom_crc32 {
+            text.push_str(&format!("rom-crc32 {:08X}\n", crc32));
+        }
+        if let Some(md5) = &self.rom_md5 {
+            text.push_str(&format!("rom-md5 {}\n", format_hex(md5)));
+        }
+        text.push_str(&format!("region {}\n", region_name(self.tv_system)));
+        match &self.start_state {
+            Some(state) => text.push_str(&format!("start savestate {}\n", format_hex(state))),
+            None => text.push_str("start power-on\n"),
+        }
+        for frame in &self.frames {
+            text.push_str(&format_frame(frame));
+            if let Some(hash) = frame.ram_hash {
+                text.push_str(&format!("{:08X}", hash));
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Parse an FCEUX `.fm2` movie with standard controllers
+    pub fn parse_fm2(text: &str) -> Result<Self, String> {
+        let mut movie = Movie {
+            rom_name: None,
+            rom_crc32: None,
+            rom_md5: None,
+            tv_system: TvSystem::Ntsc,
+            start_state: None,
+            frames: Vec::new(),
+        };
+        let mut ports = [1u8, 1];
+        for (number, line) in text.lines().enumerate() {
+            let error = |message: String| format!("line {}: {}", number + 1, message);
+            let line = line.trim_end_matches('\r');
+            if line.starts_with('|') {
+                let (mut frame, command) = parse_fm2_frame(line, ports).map_err(error)?;
+                if command & COMMAND_POWER != 0 {
+                    if !movie.frames.is_empty() {
+                        return Err(error("power cycling is not supported".to_string()));
+                    }
+                    // Movies start at power-on anyway
+                    frame.reset = false;
+                }
+                movie.frames.push(frame);
+                continue;
+            }
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let flag = || value.trim() == "1";
+            match key {
+                "version" if value.trim() != "3" => {
+                    return Err(error(format!("unsupported version {}", value)));
+                }
+                "binary" if flag() => {
+                    return Err(error("binary input is not supported".to_string()));
+                }
+                "fourscore" if flag() => {
+                    return Err(error("Four Score input is not supported".to_string()));
+                }
+                "savestate" => {
+                    // FCEUX writes binary fields as base64: or 0x followed by hex
+                    let state = value
+                        .trim()
+                        .strip_prefix("0x")
+                        .and_then(|hex| parse_state(hex).ok())
+                        .ok_or_else(|| {
+                            error("only save states written by neser can be loaded".to_string())
+                        })?;
+                    movie.start_state = Some(state);
+                }
+                "palFlag" => {
+                    movie.tv_system = if flag() {
+                        TvSystem::Pal
+                    } else {
+                        TvSystem::Ntsc
+                    };
+                }
+                "romFilename" => movie.rom_name = Some(value.to_string()),
+                "romChecksum" => {
+                    let md5 = value
+                        .trim()
+                        .strip_prefix("base64:")
+                        .and_then(|encoded| BASE64.decode(encoded).ok())
+                        .and_then(|bytes| <[u8; 16]>::try_from(bytes).ok())
+                        .ok_or_else(|| {
+                            error(format!("expected a base64 MD5, got \"{}\"", value))
+                        })?;
+                    movie.rom_md5 = Some(md5);
+                }
+                "port0" | "port1" => {
+                    let port = &mut ports[usize::from(key == "port1")];
+                    *port = match value.trim() {
+                        "0" => 0,
+                        "1" => 1,
+                        _ => {
+                            return Err(error(format!(
+                                "only gamepads are supported, {} is {}",
+                                key, value
+                            )));
+                        }
+                    };
+                }
+                _ => {}
+            }
+        }
+        Ok(movie)
+    }
+
+    /// The movie as an FCEUX `.fm2` movie
+    pub fn to_fm2(&self) -> String {
+        let mut text =
+            String::from("version 3\nemuVersion 22020\nrerecordCount 0\ncomment author neser\n");
+        text.push_str(&format!(
+            "palFlag {}\n",
+            u8::from(self.tv_system == TvSystem::Pal)
+        ));
+        if let Some(name) = &self.rom_name {
+            text.push_str(&format!("romFilename {}\n", name));
+        }
+        if let Some(md5) = &self.rom_md5 {
+            text.push_str(&format!("romChecksum base64:{}\n", BASE64.encode(md5)));
+        }
+        text.push_str(&format!("guid {}\n", random_guid()));
+        if let Some(state) = &self.start_state {
+            text.push_str(&format!("savestate 0x{}\n", format_hex(state)));
+        }
+        text.push_str("fourscore 0\nmicrophone 0\nport0 1\nport1 1\nport2 0\nFDS 0\nNewPPU 0\n");
+        for frame in &self.frames {
+            text.push_str(&format_frame(frame));
+            text.push_str("|\n");
+        }
+        text
+    }
+}
+
+/// A random GUID in the form FCEUX writes, like
+/// `6B3F4E1A-93C2-4D0E-A1F5-0C2B7E9D4A38`
+fn random_guid() -> String {
+    let guid: u128 = rand::random();
+    format!(
+        "{:08X}-{:04X}-{:04X}-{:04X}-{:012X}",
+        guid >> 96,
+        (guid >> 80) & 0xFFFF,
+        (guid >> 64) & 0xFFFF,
+        (guid >> 48) & 0xFFFF,
+        guid & 0xFFFF_FFFF_FFFF
+    )
+}
+
+/// Whether a movie file is in the FCEUX format, going by its extension
+fn is_fm2(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("fm2"))
+}
+
+fn region_name(tv_system: TvSystem) -> &'static str {
+    match tv_system {
+        TvSystem::Ntsc => "ntsc",
+        TvSystem::Pal => "pal",
+    }
+}
+
+/// `|commands|controller 1|controller 2|`
+fn format_frame(frame: &MovieFrame) -> String {
+    let command = if frame.reset { COMMAND_RESET } else { 0 };
+    format!(
+        "|{}|{}|{}|",
+        command,
+        format_buttons(frame.buttons[0]),
+        format_buttons(frame.buttons[1])
+    )
+}
+
+/// Held buttons as RLDUTSBA, with `.` for released buttons
+pub fn format_buttons(buttons: u8) -> String {
+    BUTTON_LETTERS
+        .iter()
+        .enumerate()
+        .map(|(index, &letter)| {
+            if buttons & (0x80 >> index) != 0 {
+                letter as char
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
+
+/// Parse RLDUTSBA, where `.` or a space is a released button
+fn parse_buttons(field: &str) -> Result<u8, String> {
+    if field.len() != BUTTON_LETTERS.len() {
+        return Err(format!(
+            "expected 8 buttons like RLDUTSBA, got \"{}\"",
+            field
+        ));
+    }
+    Ok(field
+        .bytes()
+        .enumerate()
+        .filter(|&(_, letter)| letter != b'.' && letter != b' ')
+        .fold(0, |buttons, (index, _)| buttons | (0x80 >> index)))
+}
+
+/// Bytes as uppercase hex digits
+fn format_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+/// Bytes from hex digits, as written by `format_hex`
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Parse a save state written by `format_hex`
+fn parse_state(hex: &str) -> Result<Vec<u8>, String> {
+    let state = parse_hex(hex).ok_or_else(|| "expected a save state in hex".to_string())?;
+    if !state.starts_with(savestate::MAGIC) {
+        return Err("not a save state of this emulator".to_string());
+    }
+    Ok(state)
+}
+
+fn parse_command(field: &str) -> Result<u8, String> {
+    field
+        .trim()
+        .parse()
+        .map_err(|_| format!("expected a command number, got \"{}\"", field))
+}
+
+/// Parse a native frame line, `|commands|controller 1|controller 2|[hash]`
+fn parse_frame(line: &str) -> Result<MovieFrame, String> {
+    let fields: Vec<&str> = line.split('|').collect();
+    let [_, command, player1, player2, hash] = fields[..] else {
+        return Err(format!(
+            "expected |commands|controller 1|controller 2|, got \"{}\"",
+            line
+        ));
+    };
+    let command = parse_command(command)?;
+    if command & !COMMAND_RESET != 0 {
+        return Err(format!("unknown command {}", command));
+    }
+    let hash = hash.trim();
+    Ok(MovieFrame {
+        buttons: [parse_buttons(player1)?, parse_buttons(player2)?],
+        reset: command & COMMAND_RESET != 0,
+        ram_hash: if hash.is_empty() {
+            None
+        } else {
+            Some(
+                u32::from_str_radix(hash, 16)
+                    .map_err(|_| format!("expected a hex RAM hash, got \"{}\"", hash))?,
+            )
+        },
+    })
+}
+
+/// Parse an FCEUX frame line, `|commands|port 0|port 1|port 2|`, into the
+/// frame and its commands
+///
+/// Ports without a gamepad (`0` in `ports`) have an empty field.
+fn parse_fm2_frame(line: &str, ports: [u8; 2]) -> Result<(MovieFrame, u8), String> {
+    let fields: Vec<&str> = line.split('|').collect();
+    if fields.len() < 5 {
+        return Err(format!(
+            "expected |commands|port 0|port 1|port 2|, got \"{}\"",
+            line
+        ));
+    }
+    let command = parse_command(fields[1])?;
+    let mut buttons = [0; 2];
+    for (index, held) in buttons.iter_mut().enumerate() {
+        if ports[index] == 1 {
+            *held = parse_buttons(fields[2 + index])?;
+        }
+    }
+    let frame = MovieFrame {
+        buttons,
+        reset: command & COMMAND_RESET != 0,
+        ram_hash: None,
+    };
+    Ok((frame, command))
+}
+
+/// CRC-32 of CPU RAM ($0000-$07FF)
+///
+/// Two runs that hash the same after a frame are very likely in the same
+/// state, which is cheaper to check than the whole machine.
+pub fn ram_hash(nes: &Nes) -> u32 {
+    let ram: Vec<u8> = (0..0x800).map(|addr| nes.peek(addr)).collect();
+    crc32fast::hash(&ram)
+}
+
+/// Playback got a different RAM hash than the recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Desync {
+    /// Frame after which the hashes differed, counting from 1
+    pub frame: usize,
+    pub expected: u32,
+    pub actual: u32,
+}
+
+impl fmt::Display for Desync {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Movie desynced at frame {}: RAM hash {:08X}, recorded {:08X}",
+            self.frame, self.actual, self.expected
+        )
+    }
+}
+
+/// Whether a session records input or plays it back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovieMode {
+    Recording,
+    Playing,
+}
+
+/// Records input into a movie, or plays one back, frame by frame
+///
+/// The emulator calls `start_frame` before emulating each frame and
+/// `end_frame` after it. Recording and playback start at power-on, or at the
+/// movie's save state.
+///
+/// After loading an earlier state, e.g. when rewinding, the emulator calls
+/// `seek`. A read-only session then plays the movie from there, and a
+/// read-write one cuts the movie off there and records new input after it.
+pub struct MovieSession {
+    movie: Movie,
+    mode: MovieMode,
+    read_only: bool,
+    /// Frames completed so far
+    frame: usize,
+    /// Reset was pressed since the last recorded frame
+    reset_pending: bool,
+    /// A desync was reported, so later ones are not
+    desynced: bool,
+}
+
+impl MovieSession {
+    /// Frames between RAM hashes in recorded movies
+    pub const HASH_INTERVAL: usize = 60;
+
+    /// Start recording the input of a freshly powered-on `nes`
+    pub fn record(nes: &Nes, rom_name: Option<String>) -> Self {
+        Self {
+            movie: Movie {
+                rom_name,
+                rom_crc32: nes.cartridge().map(|cartridge| cartridge.crc32()),
+                rom_md5: nes.cartridge().map(|cartridge| cartridge.md5()),
+                tv_system: nes.tv_system(),
+                start_state: None,
+                frames: Vec::new(),
+            },
+            mode: MovieMode::Recording,
+            read_only: false,
+            frame: 0,
+            reset_pending: false,
+            desynced: false,
+        }
+    }
+
+    /// Start playing `movie` on a freshly powered-on `nes`, loading the
+    /// movie's save state if it starts from one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the movie was recorded with a different ROM or on
+    /// a different TV system, or its save state cannot be loaded.
+    pub fn play(movie: Movie, nes: &mut Nes) -> Result<Self, String> {
+        let rom_crc32 = nes.cartridge().map(|cartridge| cartridge.crc32());
+        if let (Some(expected), Some(actual)) = (movie.rom_crc32, rom_crc32)
+            && expected != actual
+        {
+            return Err(format!(
+                "The movie was recorded with ROM CRC-32 {:08X}, not {:08X}",
+                expected, actual
+            ));
+        }
+        if movie.tv_system != nes.tv_system() {
+            return Err(format!(
+                "The movie was recorded on {}, not {}",
+                region_name(movie.tv_system).to_uppercase(),
+                region_name(nes.tv_system()).to_uppercase()
+            ));
+        }
+        if let Some(state) = &movie.start_state {
+            nes.load_state(state)
+                .map_err(|e| format!("Cannot load the movie's save state: {}", e))?;
+        }
+        Ok(Self {
+            movie,
+            mode: MovieMode::Playing,
+            read_only: true,
+            frame: 0,
+            reset_pending: false,
+            desynced: false,
+        })
+    }
+
+    pub fn mode(&self) -> MovieMode {
+        self.mode
+    }
+
+    /// Frames completed so far
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Whether going back plays the movie instead of recording over it
+    ///
+    /// Recording starts read-write and playback read-only.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Choose whether going back plays or records over the movie
+    ///
+    /// Making a recording read-only stops it, keeping the input recorded so
+    /// far for playback.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+        if read_only {
+            self.mode = MovieMode::Playing;
+        }
+    }
+
+    /// Continue from `frame` after a state from that frame was loaded
+    ///
+    /// A read-only session plays the movie from `frame` on. A read-write one
+    /// drops the input after `frame` and records from there.
+    pub fn seek(&mut self, frame: usize) {
+        self.frame = frame;
+        self.reset_pending = false;
+        self.desynced = false;
+        if !self.read_only {
+            self.movie.frames.truncate(frame);
+            self.mode = MovieMode::Recording;
+        }
+    }
+
+    /// The movie recorded so far, or being played
+    pub fn movie(&self) -> &Movie {
+        &self.movie
+    }
+
+    /// Whether playback has gone past the last frame of the movie
+    pub fn is_finished(&self) -> bool {
+        self.mode == MovieMode::Playing && self.frame >= self.movie.frames.len()
+    }
+
+    /// Note that reset was pressed, to press it again on playback
+    pub fn note_reset(&mut self) {
+        self.reset_pending = true;
+    }
+
+    /// Record the held buttons, or set them (and press reset) from the movie
+    ///
+    /// Once playback is finished, all buttons are released and the input is
+    /// left to the player.
+    pub fn start_frame(&mut self, nes: &mut Nes) {
+        match self.mode {
+            MovieMode::Recording => {
+                self.movie.frames.push(MovieFrame {
+                    buttons: [nes.buttons(1), nes.buttons(2)],
+                    reset: std::mem::take(&mut self.reset_pending),
+                    ram_hash: None,
+                });
+            }
+            MovieMode::Playing => {
+                let Some(frame) = self.movie.frames.get(self.frame) else {
+                    if self.frame == self.movie.frames.len() {
+                        nes.set_buttons(1, 0);
+                        nes.set_buttons(2, 0);
+                    }
+                    return;
+                };
+                if frame.reset {
+                    nes.reset();
+                }
+                nes.set_buttons(1, frame.buttons[0]);
+                nes.set_buttons(2, frame.buttons[1]);
+            }
+        }
+    }
+
+    /// Finish a frame, hashing RAM every `HASH_INTERVAL` frames
+    ///
+    /// # Errors
+    ///
+    /// During playback, returns the first frame at which RAM does not hash to
+    /// what was recorded.
+    pub fn end_frame(&mut self, nes: &Nes) -> Result<(), Desync> {
+        let index = self.frame;
+        self.frame += 1;
+        match self.mode {
+            MovieMode::Recording => {
+                if self.frame.is_multiple_of(Self::HASH_INTERVAL)
+                    && let Some(frame) = self.movie.frames.get_mut(index)
+                {
+                    frame.ram_hash = Some(ram_hash(nes));
+                }
+            }
+            MovieMode::Playing => {
+                if let Some(expected) = self.movie.frames.get(index).and_then(|f| f.ram_hash)
+                    && !self.desynced
+                {
+                    let actual = ram_hash(nes);
+                    if actual != expected {
+                        self.desynced = true;
+                        return Err(Desync {
+                            frame: self.frame,
+                            expected,
+                            actual,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::{InputScript, Runner};
+
+    /// MD5 of Super Mario Bros., as `base64:jjYwGG411HcjG/j9UOVM3Q==` in FCEUX movies
+    const SMB_MD5: [u8; 16] = [
+        0x8E, 0x36, 0x30, 0x18, 0x6E, 0x35, 0xD4, 0x77, 0x23, 0x1B, 0xF8, 0xFD, 0x50, 0xE5, 0x4C,
+        0xDD,
+    ];
+
+    fn sample_movie() -> Movie {
+        Movie {
+            rom_name: Some("game.nes".to_string()),
+            rom_crc32: Some(0x3337EC46),
+            rom_md5: Some(SMB_MD5),
+            tv_system: TvSystem::Ntsc,
+            start_state: None,
+            frames: vec![
+                MovieFrame::default(),
+                MovieFrame {
+                    buttons: [0x08, 0x00],
+                    reset: false,
+                    ram_hash: None,
+                },
+                MovieFrame {
+                    buttons: [0x81, 0x40],
+                    reset: true,
+                    ram_hash: Some(0x5A1C03F2),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_native_format_round_trip() {
+        let movie = sample_movie();
+        let text = movie.to_text();
+        assert_eq!(
+            text,
+            "neser-movie 1\nrom-name game.nes\nrom-crc32 3337EC46\n\
+             rom-md5 8E3630186E35D477231BF8FD50E54CDD\nregion ntsc\n\
+             start power-on\n|0|........|........|\n|0|....T...|........|\n\
+             |1|R......A|.L......|5A1C03F2\n"
+        );
+        assert_eq!(Movie::parse(&text), Ok(movie));
+    }
+
+    #[test]
+    fn test_start_state_round_trip() {
+        let mut movie = sample_movie();
+        movie.start_state = Some(b"NESS\x03\x00\xFF".to_vec());
+        let text = movie.to_text();
+        assert!(text.contains("\nstart savestate 4E4553530300FF\n"));
+        assert_eq!(Movie::parse(&text), Ok(movie.clone()));
+
+        let fm2 = movie.to_fm2();
+        assert!(fm2.contains("\nsavestate 0x4E4553530300FF\n"));
+        movie.rom_crc32 = None;
+        movie.frames[2].ram_hash = None;
+        assert_eq!(Movie::parse_fm2(&fm2), Ok(movie));
+
+        // Only states of this emulator can be loaded
+        assert!(Movie::parse("neser-movie 1\nstart savestate 0102").is_err());
+        assert!(Movie::parse("neser-movie 1\nstart savestate 4E45535").is_err());
+        assert!(Movie::parse_fm2("version 3\nsavestate base64:TkVTUw==\n").is_err());
+    }
+
+    #[test]
+    fn test_native_format_errors() {
+        assert!(Movie::parse("|0|........|........|").is_err());
+        assert_eq!(
+            Movie::parse("neser-movie 1\n|0|...|........|"),
+            Err("line 2: expected 8 buttons like RLDUTSBA, got \"...\"".to_string())
+        );
+        assert!(Movie::parse("neser-movie 1\nstart savestate").is_err());
+        assert!(Movie::parse("neser-movie 1\nspeed 2").is_err());
+        assert!(Movie::parse("neser-movie 1\nrom-md5 8E3630").is_err());
+        assert!(Movie::parse("neser-movie 1\n|4|........|........|").is_err());
+    }
+
+    #[test]
+    fn test_fm2_round_trip() {
+        let mut movie = sample_movie();
+        let fm2 = movie.to_fm2();
+        assert!(fm2.starts_with("version 3\n"));
+        assert!(fm2.contains(
+            "\nromFilename game.nes\nromChecksum base64:jjYwGG411HcjG/j9UOVM3Q==\nguid "
+        ));
+        let guid = fm2
+            .lines()
+            .find_map(|line| line.strip_prefix("guid "))
+            .unwrap();
+        assert_eq!(guid.len(), 36);
+        assert!(guid.split('-').map(str::len).eq([8, 4, 4, 4, 12]));
+        assert!(
+            guid.chars()
+                .all(|c| c == '-' || c.is_ascii_digit() || c.is_ascii_uppercase())
+        );
+        assert!(fm2.ends_with("|1|R......A|.L......||\n"));
+
+        // FCEUX movies carry neither the CRC-32 nor RAM hashes
+        movie.rom_crc32 = None;
+        movie.frames[2].ram_hash = None;
+        assert_eq!(Movie::parse_fm2(&fm2), Ok(movie));
+    }
+
+    #[test]
+    fn test_parse_fm2_from_fceux() {
+        let fm2 = "version 3\nemuVersion 22020\nrerecordCount 5\npalFlag 1\n\
+                   romFilename smb\nromChecksum base64:jjYwGG411HcjG/j9UOVM3Q==\n\
+                   guid 6B3F4E1A-0000-0000-0000-000000000000\nfourscore 0\n\
+                   port0 1\nport1 0\nport2 0\n\
+                   |2|........|||\n|0|....T...|||\n|0| . . . A|||\n";
+        let movie = Movie::parse_fm2(fm2).expect("valid movie");
+        assert_eq!(movie.tv_system, TvSystem::Pal);
+        assert_eq!(movie.rom_name.as_deref(), Some("smb"));
+        assert_eq!(movie.rom_md5, Some(SMB_MD5));
+        assert_eq!(
+            movie
+                .frames
+                .iter()
+                .map(|frame| (frame.buttons, frame.reset))
+                .collect::<Vec<_>>(),
+            vec![([0, 0], false), ([0x08, 0], false), ([0x01, 0], false)]
+        );
+
+        assert!(Movie::parse_fm2("version 3\nbinary 1\n").is_err());
+        assert!(Movie::parse_fm2("version 3\nport1 2\n").is_err());
+        assert!(Movie::parse_fm2("version 3\nromChecksum base64:AAAA\n").is_err());
+        assert!(Movie::parse_fm2("version 2\n").is_err());
+        assert!(Movie::parse_fm2("version 3\n|0|........|||\n|2|........|||\n").is_err());
+    }
+
+    /// Run a movie session for `frames` frames, pressing buttons from `script`
+    fn run(nes: &mut Nes, session: &mut MovieSession, script: &str, frames: u64) -> Option<Desync> {
+        let script = InputScript::parse(script).unwrap();
+        let mut runner = Runner::new(nes, script, vec![]);
+        runner.set_movie(session);
+        match runner.run(frames) {
+            crate::headless::Outcome::Desynced(desync) => Some(desync),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_playback_reproduces_recording() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let mut recording = MovieSession::record(&nes, None);
+        assert_eq!(
+            run(&mut nes, &mut recording, "10 start\n12\n20 down\n", 130),
+            None
+        );
+        let movie = recording.movie().clone();
+        assert_eq!(movie.frames.len(), 130);
+        assert_eq!(
+            movie.rom_md5,
+            nes.cartridge().map(|cartridge| cartridge.md5())
+        );
+        assert_eq!(movie.frames[10].buttons, [0x08, 0]);
+        assert!(movie.frames[59].ram_hash.is_some());
+        assert!(movie.frames[60].ram_hash.is_none());
+        let recorded_ram = ram_hash(&nes);
+
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let mut playback = MovieSession::play(movie.clone(), &mut nes).unwrap();
+        assert_eq!(run(&mut nes, &mut playback, "", 130), None);
+        assert!(playback.is_finished());
+        assert_eq!(ram_hash(&nes), recorded_ram);
+
+        // Different input than recorded goes out of sync
+        let mut tampered = movie;
+        for frame in &mut tampered.frames[20..] {
+            frame.buttons = [0; 2];
+        }
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let mut playback = MovieSession::play(tampered, &mut nes).unwrap();
+        let desync = run(&mut nes, &mut playback, "", 130).expect("desync");
+        assert_eq!(desync.frame, 60);
+    }
+
+    #[test]
+    fn test_playback_from_save_state() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let mut before = MovieSession::record(&nes, None);
+        run(&mut nes, &mut before, "10 start\n12\n", 30);
+        let start_state = nes.save_state();
+        let mut recording = MovieSession::record(&nes, None);
+        assert_eq!(run(&mut nes, &mut recording, "5 down\n", 70), None);
+        let recorded_ram = ram_hash(&nes);
+        let recorded_state = nes.save_state();
+        let mut movie = recording.movie().clone();
+        movie.start_state = Some(start_state);
+
+        // Through the file format and back, on a console just powered on
+        let parsed = Movie::parse(&movie.to_text()).unwrap();
+        assert_eq!(parsed, movie);
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let mut playback = MovieSession::play(parsed, &mut nes).unwrap();
+        assert_eq!(nes.frame_count(), 30);
+        assert_eq!(run(&mut nes, &mut playback, "", 70), None);
+        assert!(playback.is_finished());
+        assert_eq!(ram_hash(&nes), recorded_ram);
+        assert!(nes.save_state() == recorded_state);
+
+        // A state that does not fit the console is an error
+        movie.start_state.as_mut().unwrap()[4] = 0;
+        assert!(MovieSession::play(movie, &mut Nes::power_on("roms/nestest.nes")).is_err());
+    }
+
+    #[test]
+    fn test_play_checks_rom_and_region() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let mut movie = MovieSession::record(&nes, None).movie().clone();
+        assert!(MovieSession::play(movie.clone(), &mut nes).is_ok());
+
+        movie.tv_system = TvSystem::Pal;
+        assert!(MovieSession::play(movie.clone(), &mut nes).is_err());
+        movie.tv_system = TvSystem::Ntsc;
+        movie.rom_crc32 = movie.rom_crc32.map(|crc| !crc);
+        assert!(MovieSession::play(movie, &mut nes).is_err());
+    }
+
+    #[test]
+    fn test_read_only_seek_plays_and_read_write_seek_records() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let mut recording = MovieSession::record(&nes, None);
+        assert!(!recording.is_read_only());
+        run(&mut nes, &mut recording, "10 start\n12\n20 down\n", 40);
+        let movie = recording.movie().clone();
+
+        // Read-only: going back keeps the movie and plays it
+        let mut session = MovieSession::play(movie.clone(), &mut nes).unwrap();
+        assert!(session.is_read_only());
+        session.seek(30);
+        assert_eq!(session.mode(), MovieMode::Playing);
+        session.start_frame(&mut nes);
+        assert_eq!(nes.buttons(1), movie.frames[30].buttons[0]);
+        assert_eq!(session.movie(), &movie);
+
+        // Read-write: going back records over the rest
+        session.set_read_only(false);
+        session.seek(25);
+        assert_eq!(session.mode(), MovieMode::Recording);
+        assert_eq!(session.movie().frames[..], movie.frames[..25]);
+        nes.set_buttons(1, 0x02);
+        session.start_frame(&mut nes);
+        assert_eq!(session.movie().frames[25].buttons, [0x02, 0]);
+
+        // Making a recording read-only plays back what it has
+        session.set_read_only(true);
+        assert_eq!(session.mode(), MovieMode::Playing);
+    }
+
+    #[test]
+    fn test_recording_notes_resets() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let mut session = MovieSession::record(&nes, None);
+        session.start_frame(&mut nes);
+        session.note_reset();
+        session.start_frame(&mut nes);
+        session.start_frame(&mut nes);
+        let resets: Vec<bool> = session.movie().frames.iter().map(|f| f.reset).collect();
+        assert_eq!(resets, vec![false, true, false]);
+    }
+}
@@ -1,11 +1,20 @@
 use crate::apu;
 use crate::cartridge::Cartridge;
-use crate::cpu;
+use crate::cdl::CodeDataLog;
+use crate::cheats::CheatList;
 use crate::cpu2;
+use crate::debugger::{Breakpoint, Debugger, StopReason, Watchpoint};
+use crate::disasm;
 use crate::mem_controller;
 use crate::ppu;
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::savestate::{self, SaveState, StateReader, StateWriter};
+use crate::symbols::{MappedLabels, SymbolTable};
+use std::io;
+use std::path::Path;
+
+/// Cycles of the IRQ sequence before its vector is chosen, during which an
+/// NMI hijacks it
+const IRQ_HIJACK_CYCLES: u8 = 4;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TvSystem {
@@ -18,6 +27,7 @@ impl TvSystem {
     ///
     /// NTSC: 3.0 PPU cycles per CPU cycle (exact)
     /// PAL: 3.2 PPU cycles per CPU cycle (requires fractional tracking)
+    #[cfg(test)]
     pub fn ppu_cycles_per_cpu_cycle(&self) -> f64 {
         match self {
             TvSystem::Ntsc => 3.0,
@@ -25,6 +35,22 @@ impl TvSystem {
         }
     }
 
+    /// Returns the master clock cycles per CPU cycle
+    pub fn master_clocks_per_cpu_cycle(&self) -> u64 {
+        match self {
+            TvSystem::Ntsc => 12,
+            TvSystem::Pal => 16,
+        }
+    }
+
+    /// Returns the master clock cycles per PPU cycle (dot)
+    pub fn master_clocks_per_ppu_dot(&self) -> u64 {
+        match self {
+            TvSystem::Ntsc => 4,
+            TvSystem::Pal => 5,
+        }
+    }
+
     /// Returns the number of scanlines per frame for this TV system
     ///
     /// NTSC: 262 scanlines per frame
@@ -52,51 +78,86 @@ impl TvSystem {
     }
 }
 
+/// The console: the CPU and the bus it drives
+///
+/// The bus owns the PPU, APU, cartridge and controllers, and is lent to the CPU
+/// for every cycle.
 pub struct Nes {
-    pub ppu: Rc<RefCell<ppu::Ppu>>,
-    pub apu: Rc<RefCell<apu::Apu>>,
-    pub memory: Rc<RefCell<mem_controller::MemController>>,
+    pub bus: mem_controller::MemController,
     pub cpu: cpu2::Cpu2,
     tv_system: TvSystem,
-    fractional_ppu_cycles: f64,
     ready_to_render: bool,
+    debugger: Debugger,
+    symbols: SymbolTable,
+    nmi_taken: bool, // Whether the last run_cpu_tick entered the NMI handler
+    /// Frames completed since power-on
+    frame_count: u64,
+    /// Frames in which the game did not read the controllers
+    lag_frames: u64,
+    /// The last completed frame was a lag frame
+    lag_frame: bool,
 }
 
 impl Nes {
     pub fn new(tv_system: TvSystem) -> Self {
-        let ppu = Rc::new(RefCell::new(ppu::Ppu::new(tv_system)));
-        let apu = Rc::new(RefCell::new(apu::Apu::new()));
-        let memory = Rc::new(RefCell::new(mem_controller::MemController::new(
-            ppu.clone(),
-            apu.clone(),
-        )));
-        let cpu = cpu2::Cpu2::new(memory.clone());
-
-        // Initialize PPU 1 cycle ahead for proper sprite 0 hit timing
-        // This creates a one-cycle offset where PPU state changes become
-        // visible to the CPU one cycle later, matching hardware behavior
-        ppu.borrow_mut().run_ppu_cycles(1);
+        let mut bus = mem_controller::MemController::new(tv_system);
+        bus.restart_clock();
+        bus.restart_apu_clock();
 
         Self {
-            ppu,
-            apu,
-            memory,
-            cpu,
+            bus,
+            cpu: cpu2::Cpu2::new(),
             tv_system,
-            fractional_ppu_cycles: 0.0,
             ready_to_render: false,
+            debugger: Debugger::new(),
+            symbols: SymbolTable::new(),
+            nmi_taken: false,
+            frame_count: 0,
+            lag_frames: 0,
+            lag_frame: false,
         }
     }
 
     /// Get the TV system this NES instance is configured for
-    #[cfg(test)]
     pub fn tv_system(&self) -> TvSystem {
         self.tv_system
     }
 
+    /// The PPU
+    pub fn ppu(&self) -> &ppu::Ppu {
+        self.bus.ppu()
+    }
+
+    /// The APU
+    pub fn apu(&self) -> &apu::Apu {
+        self.bus.apu()
+    }
+
     /// Insert a cartridge and map it into memory
     pub fn insert_cartridge(&mut self, cartridge: Cartridge) {
-        self.memory.borrow_mut().map_cartridge(cartridge);
+        self.bus.map_cartridge(cartridge);
+    }
+
+    /// The inserted cartridge, if any
+    pub fn cartridge(&self) -> Option<&Cartridge> {
+        self.bus.cartridge()
+    }
+
+    /// Load a ROM file into a console of the ROM's region and reset it
+    /// (for testing)
+    #[cfg(test)]
+    pub fn power_on(path: &str) -> Self {
+        let rom_data = std::fs::read(path).expect("Failed to load ROM");
+        let cartridge = Cartridge::new(&rom_data).expect("Failed to parse ROM");
+        let mut nes = Nes::new(cartridge.region().tv_system());
+        nes.insert_cartridge(cartridge);
+        nes.reset();
+        nes
+    }
+
+    /// Set the palette used to convert NES colors to RGB
+    pub fn set_palette(&mut self, palette: ppu::Palette) {
+        self.bus.ppu_mut().set_palette(palette);
     }
 
     /// Reset the NES system (CPU and PPU)
@@ -104,14 +165,80 @@ impl Nes {
         // Get CPU cycle count before reset for coordinated APU timing
         let cpu_cycle = self.cpu.get_total_cycles();
 
-        self.cpu.reset();
-        self.ppu.borrow_mut().reset();
-        self.apu.borrow_mut().reset(cpu_cycle);
-        self.fractional_ppu_cycles = 0.0;
+        self.bus.ppu_mut().reset();
+        self.bus.restart_clock();
+        self.bus.apu_mut().reset(cpu_cycle);
         self.ready_to_render = false;
 
-        // Re-establish 1-cycle PPU offset after reset
-        self.ppu.borrow_mut().run_ppu_cycles(1);
+        // The reset vector reads run the PPU up to the CPU, so reset it first
+        self.cpu.reset(&mut self.bus);
+        self.bus.restart_apu_clock();
+    }
+
+    /// Save the state of the whole console
+    ///
+    /// The state is taken between instructions, where `run_cpu_tick` leaves
+    /// the CPU. It can only be loaded into a `Nes` with the same TV system and
+    /// ROM.
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut state = StateWriter::new();
+        for byte in savestate::MAGIC {
+            state.write_u8(*byte);
+        }
+        state.write_u8(savestate::VERSION);
+        state.write_bool(self.tv_system == TvSystem::Pal);
+        state.write_u32(self.cartridge().map_or(0, Cartridge::crc32));
+        self.cpu.save_state(&mut state);
+        self.bus.save_state(&mut state);
+        state.write_bool(self.ready_to_render);
+        state.write_bool(self.nmi_taken);
+        state.write_u64(self.frame_count);
+        state.write_u64(self.lag_frames);
+        state.write_bool(self.lag_frame);
+        state.into_bytes()
+    }
+
+    /// Continue from a state saved by `save_state`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state is from a different version, TV system or
+    /// ROM, or is damaged. A damaged state may have been partly loaded.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let mut state = StateReader::new(data);
+        let mut magic = [0; 4];
+        for byte in &mut magic {
+            *byte = state.read_u8()?;
+        }
+        if &magic != savestate::MAGIC {
+            return Err("Not a save state".to_string());
+        }
+        let version = state.read_u8()?;
+        if version != savestate::VERSION {
+            return Err(format!(
+                "Save state version {} is not supported (expected {})",
+                version,
+                savestate::VERSION
+            ));
+        }
+        if state.read_bool()? != (self.tv_system == TvSystem::Pal) {
+            return Err("The state was saved on a different TV system".to_string());
+        }
+        let crc32 = state.read_u32()?;
+        if crc32 != self.cartridge().map_or(0, Cartridge::crc32) {
+            return Err(format!(
+                "The state was saved with a different ROM (CRC-32 {:08X})",
+                crc32
+            ));
+        }
+        self.cpu.load_state(&mut state)?;
+        self.bus.load_state(&mut state)?;
+        self.ready_to_render = state.read_bool()?;
+        self.nmi_taken = state.read_bool()?;
+        self.frame_count = state.read_u64()?;
+        self.lag_frames = state.read_u64()?;
+        self.lag_frame = state.read_bool()?;
+        state.finish()
     }
 
     /// Run one CPU "tick", executing one opcode and the corresponding PPU cycles
@@ -122,43 +249,47 @@ impl Nes {
     /// - NTSC: 3 PPU cycles per CPU cycle
     /// - PAL: 3.2 PPU cycles per CPU cycle
     ///
-    /// For PAL, fractional cycles are accumulated to maintain timing accuracy.
-    ///
-    /// # Known Limitations
+    /// The PPU is not ticked here: `MemController` runs it up to the exact master
+    /// clock of every CPU bus access, and to the end of each CPU cycle through
+    /// `finish_cpu_cycles`. PPU register writes (PPUCTRL, PPUMASK, PPUSCROLL, etc.)
+    /// therefore land on the dot they happen on, and register reads see the PPU
+    /// as it is at that dot. The APU is caught up the same way, so register
+    /// accesses see it clocked for the cycle they happen in.
     ///
-    /// This implementation ticks the PPU after each complete CPU opcode, not cycle-by-cycle.
-    /// This means PPU register writes (PPUCTRL, PPUMASK, PPUSCROLL, etc.) take effect for
-    /// all PPU cycles in the opcode, rather than at the exact cycle when the write occurs.
-    /// This can cause timing issues with ROMs that update PPU registers mid-scanline for
-    /// visual effects. Most games work fine, but some test ROMs (like palette.nes) may
-    /// show minor rendering artifacts due to this limitation.
+    /// NMI is sampled at the end of every cycle. An edge in the last cycle of an
+    /// instruction is too late for it and is taken after the next instruction,
+    /// as on hardware. A taken branch that stays on its page also delays an
+    /// interrupt arriving in its second cycle past the next instruction.
     pub fn run_cpu_tick(&mut self) -> u8 {
+        self.nmi_taken = false;
+
         // Check if an OAM DMA is pending before executing the opcode
-        let oam_dma_page = self.memory.borrow_mut().take_oam_dma_page();
+        let oam_dma_page = self.bus.take_oam_dma_page();
         if let Some(page) = oam_dma_page {
             // OAM DMA takes 513 cycles on even CPU cycle, or 514 cycles on odd CPU cycle
             // Check current CPU cycle parity
             let is_odd_cycle = self.cpu.get_total_cycles() % 2 == 1;
             let dma_cycles = if is_odd_cycle { 514u16 } else { 513u16 };
 
-            // Execute the DMA transfer
-            self.memory.borrow_mut().execute_oam_dma(page);
-
-            // Tick the PPU for the DMA cycles
-            self.tick_ppu_u16(dma_cycles);
-
-            // Clock the APU for the DMA cycles
-            self.tick_apu_u16(dma_cycles);
+            // Execute the DMA transfer one cycle at a time: a halt cycle, an
+            // alignment cycle on odd cycles, then 256 reads and writes
+            let mut nmi_edge = false;
+            for cycle in 0..dma_cycles {
+                if let Some(transfer_cycle) = (cycle + 512).checked_sub(dma_cycles) {
+                    self.bus.oam_dma_cycle(page, transfer_cycle);
+                }
+                self.bus.finish_cpu_cycles(1);
+                nmi_edge |= self.bus.ppu_mut().poll_nmi();
+            }
 
             // Add DMA cycles to CPU's total cycle counter
             self.cpu.add_cycles(dma_cycles as u64);
 
             // Check for NMI after DMA
-            if self.ppu.borrow_mut().poll_nmi() {
-                let nmi_cycles = self.cpu.trigger_nmi();
-                // Tick PPU and APU for the NMI handling cycles
-                self.tick_ppu(nmi_cycles);
-                self.tick_apu(nmi_cycles);
+            if nmi_edge {
+                self.nmi_taken = true;
+                let nmi_cycles = self.cpu.trigger_nmi(&mut self.bus);
+                self.bus.finish_cpu_cycles(nmi_cycles as u64);
             }
 
             // Return DMA cycles (capped at u8::MAX)
@@ -167,108 +298,86 @@ impl Nes {
 
         // Execute CPU instruction cycle-by-cycle
         let mut cpu_cycles = 0;
-        loop {
-            // Tick PPU and APU BEFORE executing CPU cycle
-            // This ensures NMI edges are detected before the CPU modifies its state
-            // NOTE: This order is critical for correct NMI timing in cycle-accurate emulation.
-            // However, there remains a ~75 CPU cycle (225 PPU cycle) synchronization offset
-            // that affects tests requiring sub-scanline timing precision (e.g., cpu_interrupts test 2).
-            // This is a known limitation even on real NES hardware, as documented in the test itself:
-            // "Occasionally fails on NES due to PPU-CPU synchronization."
-            // print!("*");
-            self.tick_ppu(1);
-            self.tick_apu(1);
-
-            // Check for NMI edge after PPU tick, before CPU execution
-            // This allows the CPU to see NMI edges at instruction boundaries
-            if self.ppu.borrow_mut().poll_nmi() {
-                self.cpu.set_nmi_pending(true);
-            }
-
-            // Execute one CPU cycle
-            // print!(".");
-            let instruction_complete = self.cpu.tick_cycle();
+        let mut irq_asserted = false;
+        let mut prev_irq_asserted = false;
+        let mut late_nmi_edge = false;
+        let nmi_edge = loop {
+            // Execute one CPU cycle; its bus access catches the PPU and APU up
+            let instruction_complete = self.cpu.tick_cycle(&mut self.bus);
+            self.bus.finish_cpu_cycles(1);
             cpu_cycles += 1;
 
-            // Break after instruction completes
+            // Check for an NMI edge during this cycle
+            let nmi_edge = self.bus.ppu_mut().poll_nmi();
             if instruction_complete {
-                break;
+                break nmi_edge;
+            }
+            if nmi_edge {
+                self.cpu.set_nmi_pending(true);
             }
+            late_nmi_edge = nmi_edge;
+            // The IRQ line is polled at the end of the second to last cycle
+            prev_irq_asserted = irq_asserted;
+            irq_asserted = self.apu().poll_irq();
+        };
+
+        // A taken branch that stays on its page polls interrupts before its
+        // last two cycles, so one arriving in its second cycle waits until
+        // after the next instruction
+        let short_taken_branch = cpu_cycles == 3 && self.cpu.executed_branch();
+        let nmi_deferred = short_taken_branch && late_nmi_edge;
+        if short_taken_branch && !prev_irq_asserted {
+            irq_asserted = false;
         }
-        // println!("");
 
         // Only trigger interrupts after instruction completes
         // Check if NMI needs to be triggered
         // (BRK may have consumed it via vector hijacking)
-        if self.cpu.is_nmi_pending() {
+        if self.cpu.is_nmi_pending() && self.cpu.should_poll_interrupts() && !nmi_deferred {
             self.cpu.set_nmi_pending(false);
-            let nmi_cycles = self.cpu.trigger_nmi();
-            // Tick PPU and APU for the NMI handling cycles
-            self.tick_ppu(nmi_cycles);
-            self.tick_apu(nmi_cycles);
+            self.nmi_taken = true;
+            let nmi_cycles = self.cpu.trigger_nmi(&mut self.bus);
+            self.bus.finish_cpu_cycles(nmi_cycles as u64);
             cpu_cycles += nmi_cycles;
         }
+        if nmi_edge {
+            self.cpu.set_nmi_pending(true);
+        }
 
         // Check for IRQ after executing instruction
         // IRQ is maskable and checked after NMI
         // First, update the IRQ pending state based on hardware sources (APU)
-        let irq_asserted = self.apu.borrow().poll_irq();
         self.cpu.set_irq_pending(irq_asserted);
 
         // Then check if CPU should service the IRQ (not masked and not in delay period)
         if self.cpu.should_poll_irq() {
-            let irq_cycles = self.cpu.trigger_irq();
-            if irq_cycles > 0 {
-                // Only tick if IRQ was actually taken (not masked)
-                self.tick_ppu(irq_cycles);
-                self.tick_apu(irq_cycles);
-                cpu_cycles += irq_cycles;
+            // The vector is chosen when P is pushed in the fifth cycle, so an NMI
+            // during the first four cycles hijacks the IRQ
+            for _ in 0..IRQ_HIJACK_CYCLES {
+                self.bus.finish_cpu_cycles(1);
+                if self.bus.ppu_mut().poll_nmi() {
+                    self.cpu.set_nmi_pending(true);
+                }
             }
+            let irq_cycles = self.cpu.trigger_irq(&mut self.bus);
+            self.bus
+                .finish_cpu_cycles((irq_cycles - IRQ_HIJACK_CYCLES) as u64);
+            cpu_cycles += irq_cycles;
         }
 
-        if self.ppu.borrow_mut().poll_frame_complete() {
+        if self.bus.ppu_mut().poll_frame_complete() {
+            self.bus.apply_ram_cheats();
             self.ready_to_render = true;
+            self.frame_count += 1;
+            self.lag_frame = !self.bus.take_input_read();
+            if self.lag_frame {
+                self.lag_frames += 1;
+            }
         }
 
         cpu_cycles
     }
 
-    /// Run the PPU for the appropriate number of cycles based on CPU cycles
-    ///
-    /// For PAL, fractional cycles are accumulated to maintain timing accuracy.
-    fn tick_ppu(&mut self, cpu_cycles: u8) {
-        let ppu_cycles = cpu_cycles as f64 * self.tv_system.ppu_cycles_per_cpu_cycle();
-        self.fractional_ppu_cycles += ppu_cycles;
-
-        let ppu_cycles_to_run = self.fractional_ppu_cycles as u64;
-        self.fractional_ppu_cycles -= ppu_cycles_to_run as f64;
-
-        self.ppu.borrow_mut().run_ppu_cycles(ppu_cycles_to_run);
-    }
-
-    fn tick_ppu_u16(&mut self, cpu_cycles: u16) {
-        let ppu_cycles = cpu_cycles as f64 * self.tv_system.ppu_cycles_per_cpu_cycle();
-        self.fractional_ppu_cycles += ppu_cycles;
-
-        let ppu_cycles_to_run = self.fractional_ppu_cycles as u64;
-        self.fractional_ppu_cycles -= ppu_cycles_to_run as f64;
-
-        self.ppu.borrow_mut().run_ppu_cycles(ppu_cycles_to_run);
-    }
-
-    /// Clock the APU for the specified number of CPU cycles
-    fn tick_apu(&mut self, cpu_cycles: u8) {
-        for _ in 0..cpu_cycles {
-            self.apu.borrow_mut().clock();
-        }
-    }
-
-    fn tick_apu_u16(&mut self, cpu_cycles: u16) {
-        for _ in 0..cpu_cycles {
-            self.apu.borrow_mut().clock();
-        }
-    }
-
     /// NES system palette - 64 RGB color values (0x00-0x3F)
     /// TODO Implement all known palettes and have the user be able to select system palette variant
     #[rustfmt::skip]
@@ -291,8 +400,8 @@ impl Nes {
     /// Get a reference to the PPU's screen buffer
     ///
     /// Returns a mutable reference to the 256x240 RGB buffer containing the current frame.
-    pub fn get_screen_buffer(&self) -> std::cell::RefMut<'_, crate::screen_buffer::ScreenBuffer> {
-        std::cell::RefMut::map(self.ppu.borrow_mut(), |ppu| ppu.screen_buffer_mut())
+    pub fn get_screen_buffer(&mut self) -> &mut crate::screen_buffer::ScreenBuffer {
+        self.bus.ppu_mut().screen_buffer_mut()
     }
 
     /// Check if a frame is ready to be rendered
@@ -303,6 +412,26 @@ impl Nes {
         self.ready_to_render
     }
 
+    /// Frames completed since power-on
+    ///
+    /// Resets do not restart the count, so it numbers the frames of a movie.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Frames in which the game did not read controller port 1 ($4016)
+    ///
+    /// Input given during a lag frame has no effect, which matters when
+    /// making tool-assisted movies.
+    pub fn lag_frames(&self) -> u64 {
+        self.lag_frames
+    }
+
+    /// Whether the last completed frame was a lag frame
+    pub fn is_lag_frame(&self) -> bool {
+        self.lag_frame
+    }
+
     /// Clear the ready-to-render flag after rendering a frame
     pub fn clear_ready_to_render(&mut self) {
         self.ready_to_render = false;
@@ -313,7 +442,7 @@ impl Nes {
     /// Returns true when the APU has generated a new audio sample.
     /// After checking this flag, call `get_sample()` to retrieve the sample.
     pub fn sample_ready(&self) -> bool {
-        self.apu.borrow().sample_ready()
+        self.apu().sample_ready()
     }
 
     /// Get the next audio sample if one is ready
@@ -322,7 +451,7 @@ impl Nes {
     /// The sample is in the range 0.0 to 1.0.
     /// After calling this, `sample_ready()` will return false until the next sample is generated.
     pub fn get_sample(&mut self) -> Option<f32> {
-        self.apu.borrow_mut().get_sample()
+        self.bus.apu_mut().get_sample()
     }
 
     /// Set button state for a controller
@@ -332,192 +461,294 @@ impl Nes {
     /// * `button` - Which button to set
     /// * `pressed` - true if pressed, false if released
     pub fn set_button(&mut self, controller: u8, button: crate::input::Button, pressed: bool) {
-        self.memory
-            .borrow_mut()
-            .set_button(controller, button, pressed);
+        self.bus.set_button(controller, button, pressed);
     }
 
-    /// Generate a trace line for the current CPU state
+    /// Held buttons of a controller (1 or 2), one bit per `Button`
+    pub fn buttons(&self, controller: u8) -> u8 {
+        self.bus.buttons(controller)
+    }
+
+    /// Set all buttons of a controller (1 or 2), one bit per `Button`
+    pub fn set_buttons(&mut self, controller: u8, buttons: u8) {
+        self.bus.set_buttons(controller, buttons);
+    }
+
+    /// Select the device plugged into controller port 2
+    pub fn set_port2_device(&mut self, device: crate::input::InputDevice) {
+        self.bus.set_port2_device(device);
+    }
+
+    /// Aim the Zapper at a screen pixel, or `None` to aim off-screen
+    pub fn set_zapper_aim(&mut self, aim: Option<(u32, u32)>) {
+        self.bus.set_zapper_aim(aim);
+    }
+
+    /// Pull or release the Zapper trigger
+    pub fn set_zapper_trigger(&mut self, pulled: bool) {
+        self.bus.set_zapper_trigger(pulled);
+    }
+
+    /// Apply the emulation settings of a configuration
     ///
-    /// Returns a string in the nestest.log format showing the current instruction,
-    /// registers, and PPU state. Useful for debugging and comparing against reference logs.
+    /// Sets the palette, the sprite limit and the device in controller port 2.
+    /// Call this after `insert_cartridge` with the configuration that has the
+    /// game's overrides applied (see `Config::for_game`).
+    pub fn apply_config(&mut self, config: &crate::config::Config) {
+        self.set_palette(config.palette());
+        self.set_sprite_limit(config.emulation.sprite_limit);
+        self.set_port2_device(config.input.port2);
+    }
+
+    /// Enable or disable the 8-sprites-per-scanline limit
+    pub fn set_sprite_limit(&mut self, enabled: bool) {
+        self.bus.ppu_mut().set_sprite_limit(enabled);
+    }
+
+    /// Breakpoints and watchpoints
+    pub fn debugger(&self) -> &Debugger {
+        &self.debugger
+    }
+
+    /// Add a breakpoint and return its id
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> usize {
+        self.debugger.add_breakpoint(breakpoint)
+    }
+
+    /// Add a watchpoint and return its id
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) -> usize {
+        let id = self.debugger.add_watchpoint(watchpoint);
+        self.sync_watchpoints();
+        id
+    }
+
+    /// Remove the breakpoint or watchpoint with the given id
     ///
-    /// Format: `PC  OPCODE  INSTRUCTION                 A:XX X:XX Y:XX P:XX SP:XX PPU:SSS,PPP CYC:C`
-    pub fn trace(&mut self, nestest: bool) -> String {
-        let pc = self.cpu.get_state().pc;
-        let memory = self.memory.borrow();
-        // Read the opcode and determine instruction size
-        let opcode_byte = memory.read(pc);
-        let instruction = cpu::lookup(opcode_byte)
-            .unwrap_or_else(|| panic!("Invalid opcode: 0x{:02X}", opcode_byte));
-
-        // Read operand bytes
-        let byte1 = if instruction.bytes() > 1 {
-            memory.read(pc.wrapping_add(1))
-        } else {
-            0
-        };
-        let byte2 = if instruction.bytes() > 2 {
-            memory.read(pc.wrapping_add(2))
-        } else {
-            0
-        };
+    /// Returns false if there is no such id.
+    pub fn remove_breakpoint(&mut self, id: usize) -> bool {
+        let removed = self.debugger.remove(id);
+        self.sync_watchpoints();
+        removed
+    }
 
-        // Build the hex dump string based on instruction bytes
-        let hex_dump = match instruction.bytes() {
-            1 => format!("{:02X}      ", opcode_byte),
-            2 => format!("{:02X} {:02X}   ", opcode_byte, byte1),
-            3 => format!("{:02X} {:02X} {:02X}", opcode_byte, byte1, byte2),
-            _ => panic!("Invalid instruction byte count"),
+    /// Hand the read/write watchpoints to the memory controller, which checks them
+    fn sync_watchpoints(&mut self) {
+        let watchpoints = self
+            .debugger
+            .watchpoints()
+            .iter()
+            .filter(|(_, wp)| wp.read || wp.write)
+            .copied()
+            .collect();
+        self.bus.set_watchpoints(watchpoints);
+    }
+
+    /// Load a symbol file for traces and the debugger (see `SymbolTable::load`)
+    ///
+    /// Returns how many symbols the file added.
+    pub fn load_symbols(&mut self, path: &Path) -> io::Result<usize> {
+        self.symbols.load(path)
+    }
+
+    /// Get the CPU address of a symbol
+    ///
+    /// PRG-ROM symbols resolve to where their bank is currently mapped, falling
+    /// back to the address they were defined at when the bank is not mapped in.
+    pub fn symbol_address(&self, name: &str) -> Option<u16> {
+        let symbol = self.symbols.find(name)?;
+        let Some(offset) = symbol.prg_offset else {
+            return Some(symbol.addr);
         };
+        // Mapper banks are at least 8 KiB, so only these addresses can hold the
+        // offset. The defining address comes first, so mirrors are only a fallback.
+        let memory = &self.bus;
+        let mapped = std::iter::once(symbol.addr)
+            .chain(
+                (0x8000..=0xFFFFu16)
+                    .step_by(0x2000)
+                    .map(|bank_start| bank_start | (offset & 0x1FFF) as u16),
+            )
+            .find(|&addr| memory.prg_rom_offset(addr) == Some(offset));
+        Some(mapped.unwrap_or(symbol.addr))
+    }
 
-        // Build the assembly instruction string
-        let asm = match instruction.mode {
-            "IMP" => format!("{}", instruction.mnemonic),
-            "ACC" => format!("{} A", instruction.mnemonic),
-            "IMM" => format!("{} #${:02X}", instruction.mnemonic, byte1),
-            "ZP" => {
-                let addr = byte1 as u16;
-                if nestest {
-                    let mut value = memory.read(addr);
-                    if addr >= 0x4000 && addr < 0x4100 {
-                        value = 0xFF;
-                    }
-                    format!("{} ${:02X} = {:02X}", instruction.mnemonic, byte1, value)
-                } else {
-                    format!("{} ${:02X}", instruction.mnemonic, byte1)
-                }
-            }
-            "ZPX" => {
-                let addr = byte1.wrapping_add(self.cpu.get_state().x) as u16;
-                if nestest {
-                    let mut value = memory.read(addr);
-                    if addr >= 0x4000 && addr < 0x4100 {
-                        value = 0xFF;
-                    }
-                    format!(
-                        "{} ${:02X},X @ {:02X} = {:02X}",
-                        instruction.mnemonic, byte1, addr as u8, value
-                    )
-                } else {
-                    format!("{} ${:02X},X", instruction.mnemonic, byte1)
-                }
-            }
-            "ZPY" => {
-                let addr = byte1.wrapping_add(self.cpu.get_state().y) as u16;
-                if nestest {
-                    let mut value = memory.read(addr);
-                    if addr >= 0x4000 && addr < 0x4100 {
-                        value = 0xFF;
-                    }
-                    format!(
-                        "{} ${:02X},Y @ {:02X} = {:02X}",
-                        instruction.mnemonic, byte1, addr as u8, value
-                    )
-                } else {
-                    format!("{} ${:02X},Y", instruction.mnemonic, byte1)
-                }
-            }
-            "ABS" => {
-                let addr = u16::from_le_bytes([byte1, byte2]);
-                // JMP and JSR don't show memory value for ABS addressing
-                if instruction.mnemonic == "JMP" || instruction.mnemonic == "JSR" {
-                    format!("{} ${:04X}", instruction.mnemonic, addr)
-                } else if nestest {
-                    let mut value = memory.read(addr);
-                    if addr >= 0x4000 && addr < 0x4100 {
-                        value = 0xFF;
-                    }
-                    format!("{} ${:04X} = {:02X}", instruction.mnemonic, addr, value)
-                } else {
-                    format!("{} ${:04X}", instruction.mnemonic, addr)
-                }
-            }
-            "ABSX" => {
-                let addr = u16::from_le_bytes([byte1, byte2]);
-                if nestest {
-                    let effective_addr = addr.wrapping_add(self.cpu.get_state().x as u16);
-                    let value = memory.read(effective_addr);
-                    format!(
-                        "{} ${:04X},X @ {:04X} = {:02X}",
-                        instruction.mnemonic, addr, effective_addr, value
-                    )
-                } else {
-                    format!("{} ${:04X},X", instruction.mnemonic, addr)
-                }
-            }
-            "ABSY" => {
-                let addr = u16::from_le_bytes([byte1, byte2]);
-                if nestest {
-                    let effective_addr = addr.wrapping_add(self.cpu.get_state().y as u16);
-                    let value = memory.read(effective_addr);
-                    format!(
-                        "{} ${:04X},Y @ {:04X} = {:02X}",
-                        instruction.mnemonic, addr, effective_addr, value
-                    )
-                } else {
-                    format!("{} ${:04X},Y", instruction.mnemonic, addr)
-                }
-            }
-            "INDX" => {
-                if nestest {
-                    let zp_addr = byte1.wrapping_add(self.cpu.get_state().x);
-                    let addr_lo = memory.read(zp_addr as u16);
-                    let addr_hi = memory.read(zp_addr.wrapping_add(1) as u16);
-                    let addr = u16::from_le_bytes([addr_lo, addr_hi]);
-                    let value = memory.read(addr);
-                    format!(
-                        "{} (${:02X},X) @ {:02X} = {:04X} = {:02X}",
-                        instruction.mnemonic, byte1, zp_addr, addr, value
-                    )
-                } else {
-                    format!("{} (${:02X},X)", instruction.mnemonic, byte1)
-                }
+    /// Symbol names for CPU addresses under the current bank mapping
+    pub fn labels(&self) -> impl disasm::Labels + '_ {
+        MappedLabels {
+            symbols: &self.symbols,
+            prg_offset: |addr| self.bus.prg_rom_offset(addr),
+        }
+    }
+
+    /// Start recording how PRG-ROM and CHR-ROM bytes are used
+    ///
+    /// `cdl` can be a new log or one loaded to continue an earlier session.
+    pub fn start_cdl(&mut self, cdl: CodeDataLog) {
+        self.bus.set_cdl(cdl);
+    }
+
+    /// The code/data log, if one was started
+    pub fn cdl(&self) -> Option<&CodeDataLog> {
+        self.bus.cdl()
+    }
+
+    /// The cheats applied to the game
+    pub fn cheats(&self) -> &CheatList {
+        self.bus.cheats()
+    }
+
+    /// The cheats applied to the game, to add, remove or switch them at any
+    /// time
+    pub fn cheats_mut(&mut self) -> &mut CheatList {
+        self.bus.cheats_mut()
+    }
+
+    /// Read CPU memory without side effects (see `MemController::peek`)
+    pub fn peek(&self, addr: u16) -> u8 {
+        self.bus.peek(addr)
+    }
+
+    /// Write CPU memory as the CPU would
+    ///
+    /// Writes to registers and cartridge space have their usual side effects,
+    /// such as mapper bank switches.
+    pub fn poke(&mut self, addr: u16, value: u8) {
+        self.bus.write(addr, value, false);
+        // The debugger's own writes never trigger watchpoints
+        self.bus.take_watch_hits();
+    }
+
+    /// Execute one instruction (and any interrupt entered after it)
+    pub fn step_instruction(&mut self) -> StopReason {
+        self.run_until(|_, _| Some(StopReason::Step))
+    }
+
+    /// Execute one instruction, running a called subroutine to its return
+    pub fn step_over(&mut self) -> StopReason {
+        const JSR: u8 = 0x20;
+        let state = *self.cpu.get_state();
+        if self.peek(state.pc) != JSR {
+            return self.step_instruction();
+        }
+        let return_pc = state.pc.wrapping_add(3);
+        self.run_until(|nes, _| {
+            let now = *nes.cpu.get_state();
+            (now.pc == return_pc && now.sp >= state.sp).then_some(StopReason::Step)
+        })
+    }
+
+    /// Run until the current subroutine or interrupt handler returns
+    pub fn step_out(&mut self) -> StopReason {
+        const RTI: u8 = 0x40;
+        const RTS: u8 = 0x60;
+        let sp = self.cpu.get_state().sp;
+        self.run_until(|nes, step| {
+            let returned = matches!(step.opcode, RTS | RTI) && nes.cpu.get_state().sp > sp;
+            returned.then_some(StopReason::Step)
+        })
+    }
+
+    /// Run until the PPU enters the given scanline
+    ///
+    /// # Panics
+    /// Panics if the scanline does not exist for the TV system.
+    pub fn run_to_scanline(&mut self, scanline: u16) -> StopReason {
+        assert!(
+            scanline < self.tv_system.scanlines_per_frame(),
+            "Scanline {} does not exist",
+            scanline
+        );
+        self.run_until(|nes, step| {
+            let now = nes.ppu().scanline();
+            (now == scanline && step.scanline != scanline).then_some(StopReason::Scanline(now))
+        })
+    }
+
+    /// Run until the CPU enters the NMI handler
+    pub fn run_to_nmi(&mut self) -> StopReason {
+        self.run_until(|nes, _| nes.nmi_taken.then_some(StopReason::Nmi))
+    }
+
+    /// Run until a breakpoint or watchpoint triggers or the CPU halts
+    pub fn continue_execution(&mut self) -> StopReason {
+        self.run_until(|_, _| None)
+    }
+
+    /// Run like `continue_execution`, also stopping once `interrupted` returns true
+    ///
+    /// `interrupted` is called after every instruction, so it should be cheap.
+    pub fn continue_until_interrupted(
+        &mut self,
+        mut interrupted: impl FnMut() -> bool,
+    ) -> StopReason {
+        self.run_until(|_, _| interrupted().then_some(StopReason::Interrupted))
+    }
+
+    /// Execute instructions until `done` returns a stop reason, or the debugger stops
+    ///
+    /// Breakpoints are checked before each instruction except the first, so
+    /// execution can resume from a breakpoint. Watchpoints are checked after each
+    /// instruction.
+    fn run_until(
+        &mut self,
+        mut done: impl FnMut(&mut Nes, &DebugStep) -> Option<StopReason>,
+    ) -> StopReason {
+        // Discard accesses made outside of execution, e.g. by trace()
+        self.bus.take_watch_hits();
+
+        let mut first = true;
+        loop {
+            let state = *self.cpu.get_state();
+            let opcode = self.peek(state.pc);
+            if !first && let Some(reason) = self.debugger.check(&state, opcode) {
+                return reason;
             }
-            "INDY" => {
-                if nestest {
-                    let addr_lo = memory.read(byte1 as u16);
-                    let addr_hi = memory.read(byte1.wrapping_add(1) as u16);
-                    let base_addr = u16::from_le_bytes([addr_lo, addr_hi]);
-                    let effective_addr = base_addr.wrapping_add(self.cpu.get_state().y as u16);
-                    let value = memory.read(effective_addr);
-                    format!(
-                        "{} (${:02X}),Y = {:04X} @ {:04X} = {:02X}",
-                        instruction.mnemonic, byte1, base_addr, effective_addr, value
-                    )
-                } else {
-                    format!("{} (${:02X}),Y", instruction.mnemonic, byte1)
-                }
+            first = false;
+
+            let step = DebugStep {
+                opcode,
+                scanline: self.ppu().scanline(),
+            };
+            self.run_cpu_tick();
+
+            if let Some(hit) = self.bus.take_watch_hits().into_iter().next() {
+                return StopReason::Watchpoint(hit);
             }
-            "IND" => {
-                if nestest {
-                    let ptr_addr = u16::from_le_bytes([byte1, byte2]);
-                    let addr_lo = memory.read(ptr_addr);
-                    // 6502 bug: if ptr_addr is at page boundary (e.g., $02FF),
-                    // high byte wraps within same page instead of crossing to next page
-                    let hi_addr = if ptr_addr & 0xFF == 0xFF {
-                        ptr_addr & 0xFF00 // Wrap to start of same page
-                    } else {
-                        ptr_addr.wrapping_add(1)
-                    };
-                    let addr_hi = memory.read(hi_addr);
-                    let target_addr = u16::from_le_bytes([addr_lo, addr_hi]);
-                    format!(
-                        "{} (${:04X}) = {:04X}",
-                        instruction.mnemonic, ptr_addr, target_addr
-                    )
-                } else {
-                    let ptr_addr = u16::from_le_bytes([byte1, byte2]);
-                    format!("{} (${:04X})", instruction.mnemonic, ptr_addr)
-                }
+            if self.cpu.is_halted() {
+                return StopReason::Halted;
             }
-            "REL" => {
-                let offset = byte1 as i8;
-                let target = pc.wrapping_add(2).wrapping_add(offset as u16);
-                format!("{} ${:04X}", instruction.mnemonic, target)
+            if let Some(reason) = done(self, &step) {
+                return reason;
             }
-            _ => panic!("Unknown addressing mode"),
+        }
+    }
+
+    /// Generate a trace line for the current CPU state
+    ///
+    /// Returns a string in the nestest.log format showing the current instruction,
+    /// registers, and PPU state. Useful for debugging and comparing against reference logs.
+    /// Outside nestest mode, operands use symbol names where they are known.
+    ///
+    /// Format: `PC  OPCODE  INSTRUCTION                 A:XX X:XX Y:XX P:XX SP:XX PPU:SSS,PPP CYC:C`
+    pub fn trace(&mut self, nestest: bool) -> String {
+        let registers = *self.cpu.get_state();
+        let pc = registers.pc;
+        let memory = &self.bus;
+        let instruction = disasm::decode(pc, |addr| memory.peek(addr));
+        let hex_dump = instruction.hex();
+        let mut asm = if nestest {
+            instruction.to_string()
+        } else {
+            instruction.format(&self.labels())
         };
+        if nestest {
+            asm.push_str(&Self::nestest_operand_values(
+                &instruction,
+                memory,
+                registers.x,
+                registers.y,
+            ));
+        }
 
         // Adjust spacing for 4-character mnemonics (starts one character earlier)
         let (pad_before, width) = if instruction.mnemonic.len() == 4 {
@@ -527,8 +758,8 @@ impl Nes {
         };
 
         let total_cycles = self.cpu.get_total_cycles();
+        let (scanline, pixel) = (self.ppu().scanline(), self.ppu().pixel());
         let state = self.cpu.get_state();
-        let ppu = self.ppu.borrow();
         format!(
             "{:04X}  {}{}{:<width$} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PPU:{:3},{:3} CYC:{}",
             pc,
@@ -540,17 +771,102 @@ impl Nes {
             state.y,
             state.p,
             state.sp,
-            ppu.scanline(),
-            ppu.pixel(),
+            scanline,
+            pixel,
             total_cycles,
             width = width
         )
     }
 
-    /// Get base nametable address from PPUCTRL (for testing)
-    #[cfg(test)]
+    /// Effective addresses and values that nestest.log shows after the operand
+    ///
+    /// # Arguments
+    /// * `instruction` - The instruction at PC
+    /// * `memory` - Memory to peek operand values from
+    /// * `x` - X register, for indexed modes
+    /// * `y` - Y register, for indexed modes
+    fn nestest_operand_values(
+        instruction: &disasm::Instruction,
+        memory: &mem_controller::MemController,
+        x: u8,
+        y: u8,
+    ) -> String {
+        let byte1 = instruction.operand[0];
+        let word = u16::from_le_bytes(instruction.operand);
+        // nestest.log shows APU and I/O registers as $FF
+        let read_masked = |addr: u16| {
+            let value = memory.peek(addr);
+            if (0x4000..0x4100).contains(&addr) {
+                0xFF
+            } else {
+                value
+            }
+        };
+
+        match instruction.mode {
+            "ZP" => format!(" = {:02X}", read_masked(byte1 as u16)),
+            "ZPX" | "ZPY" => {
+                let index = if instruction.mode == "ZPX" { x } else { y };
+                let addr = byte1.wrapping_add(index) as u16;
+                format!(" @ {:02X} = {:02X}", addr, read_masked(addr))
+            }
+            // JMP and JSR don't show memory value for ABS addressing
+            "ABS" if instruction.mnemonic == "JMP" || instruction.mnemonic == "JSR" => {
+                String::new()
+            }
+            "ABS" => format!(" = {:02X}", read_masked(word)),
+            "ABSX" | "ABSY" => {
+                let index = if instruction.mode == "ABSX" { x } else { y };
+                let effective_addr = word.wrapping_add(index as u16);
+                format!(
+                    " @ {:04X} = {:02X}",
+                    effective_addr,
+                    memory.peek(effective_addr)
+                )
+            }
+            "INDX" => {
+                let zp_addr = byte1.wrapping_add(x);
+                let addr_lo = memory.peek(zp_addr as u16);
+                let addr_hi = memory.peek(zp_addr.wrapping_add(1) as u16);
+                let addr = u16::from_le_bytes([addr_lo, addr_hi]);
+                format!(
+                    " @ {:02X} = {:04X} = {:02X}",
+                    zp_addr,
+                    addr,
+                    memory.peek(addr)
+                )
+            }
+            "INDY" => {
+                let addr_lo = memory.peek(byte1 as u16);
+                let addr_hi = memory.peek(byte1.wrapping_add(1) as u16);
+                let base_addr = u16::from_le_bytes([addr_lo, addr_hi]);
+                let effective_addr = base_addr.wrapping_add(y as u16);
+                format!(
+                    " = {:04X} @ {:04X} = {:02X}",
+                    base_addr,
+                    effective_addr,
+                    memory.peek(effective_addr)
+                )
+            }
+            "IND" => {
+                let addr_lo = memory.peek(word);
+                // 6502 bug: if the pointer is at a page boundary (e.g., $02FF),
+                // the high byte wraps within the same page instead of crossing to the next
+                let hi_addr = if word & 0xFF == 0xFF {
+                    word & 0xFF00
+                } else {
+                    word.wrapping_add(1)
+                };
+                let addr_hi = memory.peek(hi_addr);
+                format!(" = {:04X}", u16::from_le_bytes([addr_lo, addr_hi]))
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// Get base nametable address from PPUCTRL
     pub fn base_nametable_addr(&self) -> u16 {
-        self.ppu.borrow().base_nametable_addr()
+        self.ppu().base_nametable_addr()
     }
 
     /// Read nametable text for automated test verification
@@ -564,9 +880,8 @@ impl Nes {
     ///
     /// # Returns
     /// String containing the decoded text
-    #[cfg(test)]
     pub fn read_nametable_text(&self, nametable_addr: u16, length: usize) -> String {
-        let ppu = self.ppu.borrow();
+        let ppu = self.ppu();
         let mut text = String::new();
 
         for i in 0..length {
@@ -575,7 +890,7 @@ impl Nes {
 
             // Decode tile index to character
             // Blargg's branch timing tests store ASCII values directly as tiles
-            let ch = if tile_index >= 0x20 && tile_index <= 0x7E {
+            let ch = if (0x20..=0x7E).contains(&tile_index) {
                 tile_index as char
             } else if tile_index == 0x00 {
                 ' ' // Treat 0x00 as space
@@ -590,9 +905,18 @@ impl Nes {
     }
 }
 
+/// State captured before an instruction executed by `Nes::run_until`
+struct DebugStep {
+    /// Opcode of the instruction
+    opcode: u8,
+    /// PPU scanline before the instruction
+    scanline: u16,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ppu::ChrBus;
     use std::fs;
 
     #[test]
@@ -608,12 +932,16 @@ mod tests {
         // Create NES and insert cartridge
         let mut nes = Nes::new(TvSystem::Ntsc);
         nes.insert_cartridge(cartridge);
-        nes.cpu.reset();
+        // Set up the registers as the reset sequence leaves them, without its
+        // bus accesses, which would run the PPU into the first CPU cycle
+        let state = nes.cpu.get_state();
+        state.sp = 0xFD;
+        state.p = 0x24;
         // nestest automated test starts execution at $C000 (not reset vector $C004)
-        nes.cpu.get_state().pc = 0xC000;
+        state.pc = 0xC000;
         // CPU reset takes 7 cycles, manually sync PPU and CPU cycle counters
-        // PPU already has 1 cycle from initialization, so add 20 more (21 - 1 = 20)
-        nes.ppu.borrow_mut().run_ppu_cycles(20); // 7 * 3 = 21 PPU cycles for NTSC
+        // PPU already starts 2 cycles ahead, so add 19 more (21 - 2 = 19)
+        nes.bus.ppu_mut().run_ppu_cycles(19, &mut ChrBus::default()); // 7 * 3 = 21 PPU cycles for NTSC
         nes.cpu.set_total_cycles(7); // Account for reset cycles
 
         for line in golden_log.lines() {
@@ -646,6 +974,13 @@ mod tests {
         assert_eq!(pal_nes.tv_system(), TvSystem::Pal);
     }
 
+    #[test]
+    fn test_nes_is_send() {
+        // The emulator runs on a worker thread, away from the window
+        fn assert_send<T: Send>() {}
+        assert_send::<Nes>();
+    }
+
     #[test]
     fn test_ntsc_ppu_cycles_per_cpu_cycle() {
         let ntsc = TvSystem::Ntsc;
@@ -678,27 +1013,27 @@ mod tests {
     fn test_ntsc_ppu_runs_3x_cpu_cycles() {
         let mut nes = Nes::new(TvSystem::Ntsc);
         // Write NOP to RAM and set PC directly (skip reset to avoid ROM requirement)
-        nes.memory.borrow_mut().write(0x0000, 0xEA, false); // NOP in RAM
+        nes.bus.write(0x0000, 0xEA, false); // NOP in RAM
         nes.cpu.get_state().pc = 0x0000; // Set PC to RAM address
 
         // NOP takes 2 CPU cycles, so PPU should run 6 cycles (3x ratio for NTSC)
-        // Plus 1 cycle for initial PPU offset (sprite 0 hit timing correction)
+        // Plus the 2 cycles the PPU starts ahead of the first CPU cycle
         nes.run_cpu_tick();
-        assert_eq!(nes.ppu.borrow().total_cycles(), 7);
+        assert_eq!(nes.ppu().total_cycles(), 8);
     }
 
     #[test]
     fn test_pal_ppu_runs_3_2x_cpu_cycles() {
         let mut nes = Nes::new(TvSystem::Pal);
         // Write NOP to RAM and set PC directly
-        nes.memory.borrow_mut().write(0x0000, 0xEA, false); // NOP in RAM
+        nes.bus.write(0x0000, 0xEA, false); // NOP in RAM
         nes.cpu.get_state().pc = 0x0000;
 
         // NOP takes 2 CPU cycles, PAL ratio is 3.2, so 2 * 3.2 = 6.4
-        // Plus 1 cycle for initial PPU offset
-        // Should accumulate fractional part
+        // Plus the 2.2 cycles the PPU starts ahead of the first CPU cycle
+        // Should keep the fractional part for the next instruction
         nes.run_cpu_tick();
-        assert_eq!(nes.ppu.borrow().total_cycles(), 7);
+        assert_eq!(nes.ppu().total_cycles(), 8);
     }
 
     #[test]
@@ -706,16 +1041,16 @@ mod tests {
         let mut nes = Nes::new(TvSystem::Pal);
         // Write NOP instructions to RAM
         for i in 0..10 {
-            nes.memory.borrow_mut().write(i, 0xEA, false); // NOP
+            nes.bus.write(i, 0xEA, false); // NOP
         }
         nes.cpu.get_state().pc = 0x0000;
 
         // Run 5 NOPs: 5 instructions * 2 cycles = 10 CPU cycles
-        // 10 * 3.2 = 32 PPU cycles, plus 1 cycle initial offset
+        // 10 * 3.2 = 32 PPU cycles, plus 2.2 cycles initial offset
         for _ in 0..5 {
             nes.run_cpu_tick();
         }
-        assert_eq!(nes.ppu.borrow().total_cycles(), 33);
+        assert_eq!(nes.ppu().total_cycles(), 34);
     }
 
     #[test]
@@ -723,29 +1058,29 @@ mod tests {
         let mut nes = Nes::new(TvSystem::Ntsc);
         // Write NOP instructions to RAM
         for i in 0..3 {
-            nes.memory.borrow_mut().write(i, 0xEA, false); // NOP (2 cycles each)
+            nes.bus.write(i, 0xEA, false); // NOP (2 cycles each)
         }
         nes.cpu.get_state().pc = 0x0000;
 
-        // 3 NOPs = 6 CPU cycles, 18 PPU cycles (6 * 3), plus 1 cycle initial offset
+        // 3 NOPs = 6 CPU cycles, 18 PPU cycles (6 * 3), plus 2 cycles initial offset
         nes.run_cpu_tick();
         nes.run_cpu_tick();
         nes.run_cpu_tick();
-        assert_eq!(nes.ppu.borrow().total_cycles(), 19);
+        assert_eq!(nes.ppu().total_cycles(), 20);
     }
 
     #[test]
     fn test_ppu_cycles_reset_on_nes_reset() {
         let mut nes = Nes::new(TvSystem::Ntsc);
-        nes.memory.borrow_mut().write(0x0000, 0xEA, false); // NOP
+        nes.bus.write(0x0000, 0xEA, false); // NOP
         nes.cpu.get_state().pc = 0x0000;
 
         nes.run_cpu_tick();
-        assert_eq!(nes.ppu.borrow().total_cycles(), 7); // 6 + 1 offset
+        assert_eq!(nes.ppu().total_cycles(), 8); // 6 + 2 offset
 
         // Reset just the PPU to test the counter is cleared
-        nes.ppu.borrow_mut().reset();
-        assert_eq!(nes.ppu.borrow().total_cycles(), 0);
+        nes.bus.ppu_mut().reset();
+        assert_eq!(nes.ppu().total_cycles(), 0);
     }
 
     #[test]
@@ -765,7 +1100,7 @@ mod tests {
 
     #[test]
     fn test_nes_provides_access_to_ppu_screen_buffer() {
-        let nes = Nes::new(TvSystem::Ntsc);
+        let mut nes = Nes::new(TvSystem::Ntsc);
 
         // Should be able to access the PPU's screen buffer
         let screen_buffer = nes.get_screen_buffer();
@@ -810,7 +1145,7 @@ mod tests {
         let rom_data = create_minimal_rom();
         let cartridge = Cartridge::new(&rom_data).expect("Failed to create cartridge");
         nes.insert_cartridge(cartridge);
-        nes.cpu.reset();
+        nes.cpu.reset(&mut nes.bus);
 
         // Set CPU to an even cycle (8)
         nes.cpu.set_total_cycles(8);
@@ -819,7 +1154,7 @@ mod tests {
         assert_eq!(cycles_before % 2, 0, "Should start on even cycle");
 
         // Trigger OAM DMA by writing to $4014
-        nes.memory.borrow_mut().write(0x4014, 0x02, false);
+        nes.bus.write(0x4014, 0x02, false);
 
         // Run one CPU tick which should process the DMA
         nes.run_cpu_tick();
@@ -839,7 +1174,7 @@ mod tests {
         let rom_data = create_minimal_rom();
         let cartridge = Cartridge::new(&rom_data).expect("Failed to create cartridge");
         nes.insert_cartridge(cartridge);
-        nes.cpu.reset();
+        nes.cpu.reset(&mut nes.bus);
 
         // Set CPU to an odd cycle (7)
         nes.cpu.set_total_cycles(7);
@@ -848,7 +1183,7 @@ mod tests {
         assert_eq!(cycles_before % 2, 1, "Should start on odd cycle");
 
         // Trigger OAM DMA by writing to $4014
-        nes.memory.borrow_mut().write(0x4014, 0x02, false);
+        nes.bus.write(0x4014, 0x02, false);
 
         // Run one CPU tick which should process the DMA
         nes.run_cpu_tick();
@@ -868,25 +1203,23 @@ mod tests {
         let rom_data = create_minimal_rom();
         let cartridge = Cartridge::new(&rom_data).expect("Failed to create cartridge");
         nes.insert_cartridge(cartridge);
-        nes.cpu.reset();
+        nes.cpu.reset(&mut nes.bus);
 
         // Set up test data in RAM at page $02 ($0200-$02FF)
         for i in 0..256u16 {
-            nes.memory
-                .borrow_mut()
-                .write(0x0200 + i, (i & 0xFF) as u8, false);
+            nes.bus.write(0x0200 + i, (i & 0xFF) as u8, false);
         }
 
         // Trigger OAM DMA from page $02
-        nes.memory.borrow_mut().write(0x4014, 0x02, false);
+        nes.bus.write(0x4014, 0x02, false);
         nes.run_cpu_tick();
 
         // Verify all 256 bytes were copied to OAM by reading through $2004
         for i in 0..256 {
             // Set OAM address via $2003
-            nes.memory.borrow_mut().write(0x2003, i as u8, false);
+            nes.bus.write(0x2003, i as u8, false);
             // Read OAM data via $2004
-            let oam_byte = nes.memory.borrow().read(0x2004);
+            let oam_byte = nes.bus.read(0x2004);
             let expected = if (i & 0x03) == 2 {
                 // Attribute byte: mask bits 2-4
                 ((i & 0xFF) as u8) & 0xE3
@@ -907,24 +1240,24 @@ mod tests {
         let rom_data = create_minimal_rom();
         let cartridge = Cartridge::new(&rom_data).expect("Failed to create cartridge");
         nes.insert_cartridge(cartridge);
-        nes.cpu.reset();
+        nes.cpu.reset(&mut nes.bus);
 
         // Set up distinct data in different pages
         // Page $03: $0300-$03FF
         for i in 0..256u16 {
-            nes.memory.borrow_mut().write(0x0300 + i, 0xAA, false); // Marker value
+            nes.bus.write(0x0300 + i, 0xAA, false); // Marker value
         }
 
         // Trigger OAM DMA from page $03
-        nes.memory.borrow_mut().write(0x4014, 0x03, false);
+        nes.bus.write(0x4014, 0x03, false);
         nes.run_cpu_tick();
 
         // Verify bytes came from page $03 by reading through $2004
         for i in 0..256 {
             // Set OAM address via $2003
-            nes.memory.borrow_mut().write(0x2003, i as u8, false);
+            nes.bus.write(0x2003, i as u8, false);
             // Read OAM data via $2004
-            let oam_byte = nes.memory.borrow().read(0x2004);
+            let oam_byte = nes.bus.read(0x2004);
             let expected = if (i & 0x03) == 2 {
                 // Attribute byte: 0xAA with masking = 0xAA & 0xE3 = 0xA2
                 0xA2
@@ -945,21 +1278,22 @@ mod tests {
         let rom_data = create_minimal_rom();
         let cartridge = Cartridge::new(&rom_data).expect("Failed to create cartridge");
         nes.insert_cartridge(cartridge);
-        nes.cpu.reset();
+
+        // Get initial PPU state, before the reset vector reads run the PPU
+        // into the first CPU cycle
+        let initial_ppu_cycles = nes.ppu().total_cycles();
+        nes.cpu.reset(&mut nes.bus);
 
         // Set CPU to an even cycle (8)
         nes.cpu.set_total_cycles(8);
 
-        // Get initial PPU state
-        let initial_ppu_cycles = nes.ppu.borrow().total_cycles();
-
         // Trigger OAM DMA
-        nes.memory.borrow_mut().write(0x4014, 0x02, false);
+        nes.bus.write(0x4014, 0x02, false);
         nes.run_cpu_tick();
 
         // PPU should have advanced by 513 CPU cycles * 3 PPU cycles per CPU cycle
         let expected_ppu_cycles = initial_ppu_cycles + (513 * 3);
-        let actual_ppu_cycles = nes.ppu.borrow().total_cycles();
+        let actual_ppu_cycles = nes.ppu().total_cycles();
 
         assert_eq!(
             actual_ppu_cycles, expected_ppu_cycles,
@@ -1021,13 +1355,13 @@ mod tests {
         nes.reset();
 
         // Get initial frame counter cycle count
-        let initial_cycle = nes.apu.borrow().frame_counter().get_cycle_counter();
+        let initial_cycle = nes.apu().frame_counter().get_cycle_counter();
 
         // Execute one CPU instruction (NOP = 2 cycles)
         let cpu_cycles = nes.run_cpu_tick();
 
         // APU should have been clocked once per CPU cycle
-        let final_cycle = nes.apu.borrow().frame_counter().get_cycle_counter();
+        let final_cycle = nes.apu().frame_counter().get_cycle_counter();
         let apu_cycles_elapsed = final_cycle - initial_cycle;
 
         assert_eq!(
@@ -1047,16 +1381,16 @@ mod tests {
         nes.reset();
 
         // Get initial APU cycle count
-        let initial_cycle = nes.apu.borrow().frame_counter().get_cycle_counter();
+        let initial_cycle = nes.apu().frame_counter().get_cycle_counter();
 
         // Trigger an OAM DMA by writing to $4014
-        nes.memory.borrow_mut().write(0x4014, 0x02, false);
+        nes.bus.write(0x4014, 0x02, false);
 
         // Run a CPU tick which should execute the DMA
         let dma_cycles = nes.run_cpu_tick();
 
         // APU should have been clocked for all DMA cycles
-        let final_cycle = nes.apu.borrow().frame_counter().get_cycle_counter();
+        let final_cycle = nes.apu().frame_counter().get_cycle_counter();
         let apu_cycles_elapsed = final_cycle - initial_cycle;
 
         // OAM DMA takes 513 or 514 cycles, but returns min(cycles, 255) as u8
@@ -1167,6 +1501,282 @@ mod tests {
         assert!(sample.is_none());
     }
 
+    /// Helper function to load a program into RAM at $0200 and point PC at it
+    ///
+    /// IRQs are masked, as there is no cartridge to provide the IRQ vector.
+    fn load_ram_program(nes: &mut Nes, program: &[u8]) {
+        for (offset, byte) in program.iter().enumerate() {
+            nes.poke(0x0200 + offset as u16, *byte);
+        }
+        let state = nes.cpu.get_state();
+        state.pc = 0x0200;
+        state.sp = 0xFD;
+        state.p = cpu2::types::FLAG_INTERRUPT | cpu2::types::FLAG_UNUSED;
+    }
+
+    /// JSR $0210; JMP $0203; ...; $0210: LDX #$01; RTS
+    fn subroutine_program() -> Vec<u8> {
+        let mut program = vec![0x20, 0x10, 0x02, 0x4C, 0x03, 0x02];
+        program.resize(0x10, 0xEA);
+        program.extend_from_slice(&[0xA2, 0x01, 0x60]);
+        program
+    }
+
+    #[test]
+    fn test_step_over_and_step_out() {
+        let mut nes = Nes::new(TvSystem::Ntsc);
+        load_ram_program(&mut nes, &subroutine_program());
+
+        assert_eq!(nes.step_over(), StopReason::Step);
+        assert_eq!(nes.cpu.get_state().pc, 0x0203);
+        assert_eq!(nes.cpu.get_state().x, 0x01);
+
+        nes.cpu.get_state().pc = 0x0200;
+        assert_eq!(nes.step_instruction(), StopReason::Step);
+        assert_eq!(nes.cpu.get_state().pc, 0x0210);
+        assert_eq!(nes.step_out(), StopReason::Step);
+        assert_eq!(nes.cpu.get_state().pc, 0x0203);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut nes = Nes::new(TvSystem::Ntsc);
+        load_ram_program(&mut nes, &subroutine_program());
+
+        let id = nes.add_breakpoint(Breakpoint::at(0x0212));
+        assert_eq!(nes.continue_execution(), StopReason::Breakpoint(id));
+        assert_eq!(nes.cpu.get_state().pc, 0x0212);
+
+        // Continuing from a breakpoint executes the instruction it stopped at
+        assert!(nes.remove_breakpoint(id));
+        let condition = crate::debugger::Condition::parse("pc == $0203", &|_| None).unwrap();
+        let id = nes.add_breakpoint(Breakpoint::when(condition));
+        assert_eq!(nes.continue_execution(), StopReason::Breakpoint(id));
+        assert_eq!(nes.step_instruction(), StopReason::Step);
+        assert_eq!(nes.continue_execution(), StopReason::Breakpoint(id));
+    }
+
+    #[test]
+    fn test_cpu_and_ppu_watchpoints() {
+        use crate::debugger::{Access, Bus};
+
+        let mut nes = Nes::new(TvSystem::Ntsc);
+        // STA $10; LDA #$20; STA $2006; LDA #$00; STA $2006; LDA #$55; STA $2007
+        load_ram_program(
+            &mut nes,
+            &[
+                0x85, 0x10, 0xA9, 0x20, 0x8D, 0x06, 0x20, 0xA9, 0x00, 0x8D, 0x06, 0x20, 0xA9, 0x55,
+                0x8D, 0x07, 0x20,
+            ],
+        );
+        let cpu_watch = nes
+            .add_watchpoint(Watchpoint::new(Bus::Cpu, 0x0010, 0x0010, &[Access::Write]).unwrap());
+        let ppu_watch = nes
+            .add_watchpoint(Watchpoint::new(Bus::Ppu, 0x2000, 0x23FF, &[Access::Write]).unwrap());
+
+        match nes.continue_execution() {
+            StopReason::Watchpoint(hit) => {
+                assert_eq!(hit.id, cpu_watch);
+                assert_eq!(hit.addr, 0x0010);
+            }
+            other => panic!("Expected CPU watchpoint, got {:?}", other),
+        }
+        match nes.continue_execution() {
+            StopReason::Watchpoint(hit) => {
+                assert_eq!(hit.id, ppu_watch);
+                assert_eq!(hit.bus, Bus::Ppu);
+                assert_eq!(hit.addr, 0x2000);
+                assert_eq!(hit.value, 0x55);
+            }
+            other => panic!("Expected PPU watchpoint, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_bank_aware_symbols() {
+        let mut nes = Nes::new(TvSystem::Ntsc);
+        // UxROM with four 16 KiB banks and CHR-RAM
+        let mut rom_data = vec![0x4E, 0x45, 0x53, 0x1A, 4, 0, 0x20, 0];
+        rom_data.resize(16 + 4 * 0x4000, 0);
+        nes.insert_cartridge(Cartridge::new(&rom_data).unwrap());
+
+        let path = std::env::temp_dir().join("neser_test_bank_aware_symbols.mlb");
+        fs::write(&path, "P:4010:bank1_entry\nR:0010:player_x\n").unwrap();
+        assert_eq!(nes.load_symbols(&path).unwrap(), 2);
+        std::fs::remove_file(&path).ok();
+
+        // JSR $8010; STA $10
+        load_ram_program(&mut nes, &[0x20, 0x10, 0x80, 0x85, 0x10]);
+        assert!(nes.trace(false).contains("JSR $8010"));
+        // Bank 1 is not mapped, so the symbol falls back to the address it was given
+        assert_eq!(nes.symbol_address("bank1_entry"), Some(0xC010));
+
+        nes.poke(0x8000, 1);
+        assert!(nes.trace(false).contains("JSR bank1_entry"));
+        assert_eq!(nes.symbol_address("bank1_entry"), Some(0x8010));
+        assert_eq!(nes.symbol_address("player_x"), Some(0x0010));
+        nes.cpu.get_state().pc = 0x0203;
+        assert!(nes.trace(false).contains("STA player_x"));
+    }
+
+    #[test]
+    fn test_run_to_scanline_and_nmi() {
+        let mut nes = Nes::new(TvSystem::Ntsc);
+        let cartridge = Cartridge::new(&create_minimal_nrom_rom()).unwrap();
+        nes.insert_cartridge(cartridge);
+        // LDA #$80; STA $2000; JMP $0205
+        load_ram_program(&mut nes, &[0xA9, 0x80, 0x8D, 0x00, 0x20, 0x4C, 0x05, 0x02]);
+
+        assert_eq!(nes.run_to_scanline(100), StopReason::Scanline(100));
+        assert_eq!(nes.ppu().scanline(), 100);
+
+        assert_eq!(nes.run_to_nmi(), StopReason::Nmi);
+        // The NOP-filled PRG ROM has its NMI vector at $EAEA
+        assert_eq!(nes.cpu.get_state().pc, 0xEAEA);
+        assert_eq!(nes.ppu().scanline(), 241);
+    }
+
+    #[test]
+    fn test_code_data_log() {
+        use crate::cdl;
+
+        let mut nes = Nes::new(TvSystem::Ntsc);
+        let mut rom_data = create_minimal_nrom_rom();
+        // $8000: LDA $9000; LDA ($10),Y; JMP ($0020); ...; $9000: data
+        let program = [0xAD, 0x00, 0x90, 0xB1, 0x10, 0x6C, 0x20, 0x00];
+        rom_data[16..16 + program.len()].copy_from_slice(&program);
+        nes.insert_cartridge(Cartridge::new(&rom_data).unwrap());
+        nes.start_cdl(CodeDataLog::new(0x8000, 0x2000));
+
+        // ($10) points to $9100 and ($20) to $8100
+        for (addr, value) in [(0x10, 0x00), (0x11, 0x91), (0x20, 0x00), (0x21, 0x81)] {
+            nes.poke(addr, value);
+        }
+        nes.cpu.get_state().pc = 0x8000;
+        for _ in 0..5 {
+            nes.run_cpu_tick();
+        }
+
+        let log = nes.cdl().unwrap();
+        let prg = log.prg();
+        assert_eq!(prg[0x0000..0x0008], [cdl::CODE; 8]);
+        assert_eq!(prg[0x1000], cdl::DATA);
+        assert_eq!(prg[0x1100], cdl::DATA | cdl::INDIRECT_DATA);
+        assert_eq!(prg[0x0100], cdl::CODE | cdl::INDIRECT_CODE);
+        // NOP's dummy read of the byte after it is not counted as data
+        assert_eq!(prg[0x0101], cdl::CODE);
+        assert_eq!(log.coverage().code, 10);
+    }
+
+    /// Run frames with changing input, returning the last screen and the audio
+    fn run_frames(nes: &mut Nes, frames: u32) -> (Vec<u8>, Vec<f32>) {
+        let mut samples = Vec::new();
+        for frame in 0..frames {
+            nes.set_buttons(1, (frame as u8).wrapping_mul(37));
+            while !nes.is_ready_to_render() {
+                nes.run_cpu_tick();
+                while nes.sample_ready() {
+                    samples.extend(nes.get_sample());
+                }
+            }
+            nes.clear_ready_to_render();
+        }
+        (nes.ppu().screen_buffer().pixels().to_vec(), samples)
+    }
+
+    #[test]
+    fn test_loaded_state_continues_identically() {
+        for path in [
+            "roms/nestest.nes",
+            "roms/blargg/instr_misc/instr_misc.nes",
+            "roms/blargg/cpu_dummy_reads/cpu_dummy_reads.nes",
+            "roms/rainwarrior/ntsc_torture.nes",
+            "roms/nmi_sync/demo_pal.nes",
+        ] {
+            let mut nes = Nes::power_on(path);
+            run_frames(&mut nes, 45);
+            let state = nes.save_state();
+            let first = run_frames(&mut nes, 30);
+            let first_state = nes.save_state();
+
+            nes.load_state(&state).unwrap();
+            assert_eq!(nes.save_state(), state, "{}", path);
+            let second = run_frames(&mut nes, 30);
+            assert!(first == second, "{} plays differently after loading", path);
+            assert_eq!(nes.save_state(), first_state, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_load_state_rejects_other_consoles() {
+        let state = Nes::power_on("roms/nestest.nes").save_state();
+
+        let mut other_rom = Nes::power_on("roms/cpu.nes");
+        let message = other_rom.load_state(&state).unwrap_err();
+        assert!(message.contains("different ROM"), "{}", message);
+
+        let mut pal = Nes::power_on("roms/nestest.nes");
+        pal.tv_system = TvSystem::Pal;
+        assert!(pal.load_state(&state).is_err());
+
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        assert!(nes.load_state(&state[..state.len() - 1]).is_err());
+        assert!(nes.load_state(b"not a state").is_err());
+        let mut longer = state.clone();
+        longer.push(0);
+        assert!(nes.load_state(&longer).is_err());
+    }
+
+    #[test]
+    fn test_lag_frames_are_counted() {
+        // nestest reads the controllers every frame once it has started
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        run_frames(&mut nes, 30);
+        assert_eq!(nes.frame_count(), 30);
+        assert_eq!(nes.lag_frames(), 4);
+        assert!(!nes.is_lag_frame());
+
+        // Test ROMs that report through memory never read them
+        let mut nes = Nes::power_on("roms/blargg/instr_misc/instr_misc.nes");
+        run_frames(&mut nes, 30);
+        assert_eq!(nes.lag_frames(), 30);
+        assert!(nes.is_lag_frame());
+
+        // Reset keeps counting
+        nes.reset();
+        run_frames(&mut nes, 5);
+        assert_eq!((nes.frame_count(), nes.lag_frames()), (35, 35));
+    }
+
+    #[test]
+    fn test_cheats_patch_rom_and_poke_ram_every_frame() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let original = nes.peek(0xC000);
+        let next = nes.peek(0xC001);
+        let cheats = nes.cheats_mut();
+        cheats
+            .add("Patch", &format!("C000?{:02X}:EA", original))
+            .unwrap();
+        cheats
+            .add("Miss", &format!("C001?{:02X}:EA", next ^ 1))
+            .unwrap();
+        cheats.add("Poke", "0300:42").unwrap();
+        assert_eq!(nes.bus.read(0xC000), 0xEA);
+        assert_eq!(nes.bus.read(0xC001), next);
+
+        run_frames(&mut nes, 1);
+        assert_eq!(nes.peek(0x0300), 0x42);
+        nes.poke(0x0300, 0x00);
+        run_frames(&mut nes, 1);
+        assert_eq!(nes.peek(0x0300), 0x42);
+
+        nes.cheats_mut().set_active(false);
+        assert_eq!(nes.bus.read(0xC000), original);
+        nes.poke(0x0300, 0x00);
+        run_frames(&mut nes, 1);
+        assert_eq!(nes.peek(0x0300), 0x00);
+    }
+
     /// Helper function to create a minimal NROM ROM for testing
     fn create_minimal_nrom_rom() -> Vec<u8> {
         let mut rom = Vec::new();
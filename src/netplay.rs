@@ -0,0 +1,756 @@
+//! Rollback netplay
+//!
+//! Two emulators, each with its own player, play one game over UDP. Every
+//! frame each side sends the input of its player to the other. The input of
+//! a frame is applied `delay` frames after it is sampled, which gives it time
+//! to arrive. When the input of the other player for a frame has not arrived
+//! yet, the emulator guesses it is the same as the last input that did and
+//! goes on. If the guess turns out wrong, it loads the state saved before that
+//! frame and runs the frames since again with the right input, without sound,
+//! before showing the next one. A side that gets `MAX_ROLLBACK` frames ahead
+//! of the input it has waits for the other.
+//!
+//! Both sides must then emulate exactly the same thing. The core is
+//! deterministic: RAM powers on cleared and nothing in it is random (only the
+//! CPU fuzzer in `cpu_diff` uses `rand`, with a given seed). To catch the two
+//! drifting apart anyway, e.g. because of a bug in a save state, the sides
+//! exchange a hash of CPU RAM every `HASH_INTERVAL` frames once both inputs of
+//! those frames are known, and report the first mismatch.
+//!
+//! Packets are small and self-contained. Input packets repeat all input the
+//! other side has not acknowledged yet, so a lost packet is made up for by the
+//! next one. A session starts with each side sending a hello that names its
+//! player, TV system, ROM and input delay, so two different games are never
+//! started and both sides apply input on the same frames.
+
+use crate::input::Button;
+use crate::movie::ram_hash;
+use crate::nes::{Nes, TvSystem};
+use crate::runahead::run_hidden_frame;
+use crate::savestate::{StateReader, StateWriter};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// Version of the packets, changed whenever their layout changes
+const PROTOCOL_VERSION: u8 = 2;
+
+const PACKET_HELLO: u8 = 0;
+const PACKET_INPUT: u8 = 1;
+
+/// Largest packet that is read
+const MAX_PACKET: usize = 512;
+
+/// Time between hellos while joining
+const HELLO_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Time without a packet after which the other side is given up on
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The two RAM hashes of a frame differed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Desync {
+    /// Frame after which the hashes differed, counting from 1
+    pub frame: usize,
+    pub local: u32,
+    pub remote: u32,
+}
+
+impl fmt::Display for Desync {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Netplay desynced at frame {}: RAM hash {:08X}, other side has {:08X}",
+            self.frame, self.local, self.remote
+        )
+    }
+}
+
+/// What a side tells the other when connecting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Hello {
+    player: u8,
+    pal: bool,
+    rom_crc32: u32,
+    /// Frames of input delay
+    delay: usize,
+}
+
+impl Hello {
+    fn new(player: u8, nes: &Nes, delay: usize) -> Self {
+        Self {
+            player,
+            pal: nes.tv_system() == TvSystem::Pal,
+            rom_crc32: nes.cartridge().map_or(0, |cartridge| cartridge.crc32()),
+            delay,
+        }
+    }
+
+    /// Check that `other` plays the same game as the other player
+    fn check(&self, other: &Hello) -> Result<(), String> {
+        if other.rom_crc32 != self.rom_crc32 {
+            return Err(format!(
+                "The other side plays ROM CRC-32 {:08X}, not {:08X}",
+                other.rom_crc32, self.rom_crc32
+            ));
+        }
+        if other.pal != self.pal {
+            return Err("The other side emulates a different TV system".to_string());
+        }
+        if other.player == self.player {
+            return Err(format!("Both sides are player {}", self.player));
+        }
+        if other.delay != self.delay {
+            return Err(format!(
+                "The other side uses {} frames of input delay, not {}",
+                other.delay, self.delay
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Input of a side, from its first frame not acknowledged by the other
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InputPacket {
+    /// Frames of the receiver's input the sender has
+    ack: u32,
+    /// Frame of the first input
+    start: u32,
+    buttons: Vec<u8>,
+    /// The sender's latest RAM hash after a frame both inputs are known for
+    ram_hash: Option<(u32, u32)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Packet {
+    Hello(Hello),
+    Input(InputPacket),
+}
+
+impl Packet {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut w = StateWriter::new();
+        match self {
+            Packet::Hello(hello) => {
+                w.write_u8(PACKET_HELLO);
+                w.write_u8(PROTOCOL_VERSION);
+                w.write_u8(hello.player);
+                w.write_bool(hello.pal);
+                w.write_u32(hello.rom_crc32);
+                w.write_u32(hello.delay as u32);
+            }
+            Packet::Input(input) => {
+                w.write_u8(PACKET_INPUT);
+                w.write_u32(input.ack);
+                w.write_u32(input.start);
+                w.write_bytes(&input.buttons);
+                w.write_bool(input.ram_hash.is_some());
+                let (frame, hash) = input.ram_hash.unwrap_or_default();
+                w.write_u32(frame);
+                w.write_u32(hash);
+            }
+        }
+        w.into_bytes()
+    }
+
+    fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        let mut r = StateReader::new(data);
+        let packet = match r.read_u8()? {
+            PACKET_HELLO => {
+                let version = r.read_u8()?;
+                if version != PROTOCOL_VERSION {
+                    return Err(format!(
+                        "The other side speaks netplay version {}, not {}",
+                        version, PROTOCOL_VERSION
+                    ));
+                }
+                Packet::Hello(Hello {
+                    player: r.read_u8()?,
+                    pal: r.read_bool()?,
+                    rom_crc32: r.read_u32()?,
+                    delay: r.read_u32()? as usize,
+                })
+            }
+            PACKET_INPUT => {
+                let ack = r.read_u32()?;
+                let start = r.read_u32()?;
+                let buttons = r.read_bytes()?.to_vec();
+                let has_hash = r.read_bool()?;
+                let hash = (r.read_u32()?, r.read_u32()?);
+                Packet::Input(InputPacket {
+                    ack,
+                    start,
+                    buttons,
+                    ram_hash: has_hash.then_some(hash),
+                })
+            }
+            kind => return Err(format!("Unknown packet type {}", kind)),
+        };
+        r.finish()?;
+        Ok(packet)
+    }
+}
+
+/// One side of a netplay game
+///
+/// Like a `MovieSession`, the emulator calls `start_frame` before emulating
+/// each frame and `end_frame` after it. `start_frame` sets the buttons of
+/// both controllers, after rolling back and running again the frames that
+/// were emulated with a wrong guess.
+pub struct NetplaySession {
+    socket: UdpSocket,
+    peer: SocketAddr,
+    hello: Hello,
+    /// Frames of input between sampling and applying it
+    delay: usize,
+    /// Frames completed so far
+    frame: usize,
+    /// Buttons the local player holds now
+    local_buttons: u8,
+    /// Input of the local player, by frame, up to `frame + delay`
+    local_inputs: Vec<u8>,
+    /// Input of the other player received so far, by frame
+    remote_inputs: Vec<u8>,
+    /// Input used for the other player, by frame, guessed or not
+    used_inputs: Vec<u8>,
+    /// Frames of local input the other side has
+    acked: usize,
+    /// States before each frame from `states_start` to `frame`
+    states: VecDeque<Vec<u8>>,
+    states_start: usize,
+    /// RAM hashes after frames, by frames completed
+    hashes: BTreeMap<usize, u32>,
+    remote_hashes: BTreeMap<usize, u32>,
+    /// A desync was reported, so later ones are not
+    desynced: bool,
+    /// When input of the other player last arrived
+    last_received: Instant,
+    /// Frames emulated again after a wrong guess
+    rolled_back_frames: usize,
+}
+
+impl NetplaySession {
+    /// Frames of input delay used unless told otherwise
+    pub const DEFAULT_DELAY: usize = 2;
+
+    /// Most frames that can be emulated ahead of the other player's input
+    pub const MAX_ROLLBACK: usize = 8;
+
+    /// Frames between RAM hash checks
+    pub const HASH_INTERVAL: usize = 30;
+
+    /// Wait on `socket` for a player to join, and play as player 1
+    ///
+    /// `nes` must be freshly powered on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket fails or the player who joins plays
+    /// something else.
+    pub fn host(socket: UdpSocket, nes: &Nes, delay: usize) -> Result<Self, String> {
+        let hello = Hello::new(1, nes, delay);
+        socket
+            .set_read_timeout(None)
+            .map_err(|e| format!("Cannot wait for a player: {}", e))?;
+        let mut buffer = [0; MAX_PACKET];
+        loop {
+            let (length, from) = socket
+                .recv_from(&mut buffer)
+                .map_err(|e| format!("Cannot wait for a player: {}", e))?;
+            // Anything but a hello is not meant for us
+            if let Ok(Packet::Hello(other)) = Packet::from_bytes(&buffer[..length]) {
+                // Answer even a mismatch, so the other side reports it too
+                send(&socket, from, &Packet::Hello(hello))?;
+                hello.check(&other)?;
+                return Self::new(socket, from, hello);
+            }
+        }
+    }
+
+    /// Join the player hosting at `peer` from `socket`, and play as player 2
+    ///
+    /// `nes` must be freshly powered on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket fails, nobody answers or the host plays
+    /// something else.
+    pub fn join(
+        socket: UdpSocket,
+        peer: SocketAddr,
+        nes: &Nes,
+        delay: usize,
+    ) -> Result<Self, String> {
+        let hello = Hello::new(2, nes, delay);
+        socket
+            .set_read_timeout(Some(HELLO_INTERVAL))
+            .map_err(|e| format!("Cannot join {}: {}", peer, e))?;
+        let started = Instant::now();
+        let mut buffer = [0; MAX_PACKET];
+        while started.elapsed() < TIMEOUT {
+            send(&socket, peer, &Packet::Hello(hello))?;
+            let deadline = Instant::now() + HELLO_INTERVAL;
+            while Instant::now() < deadline {
+                let length = match socket.recv_from(&mut buffer) {
+                    Ok((length, from)) if from == peer => length,
+                    Ok(_) => continue,
+                    Err(e) if is_transient(&e) => continue,
+                    Err(e) => return Err(format!("Cannot join {}: {}", peer, e)),
+                };
+                match Packet::from_bytes(&buffer[..length]) {
+                    Ok(Packet::Hello(other)) => {
+                        hello.check(&other)?;
+                        return Self::new(socket, peer, hello);
+                    }
+                    // The host got our hello but its answer was lost
+                    Ok(Packet::Input(_)) => return Self::new(socket, peer, hello),
+                    Err(message) => return Err(message),
+                }
+            }
+        }
+        Err(format!("Nobody answered at {}", peer))
+    }
+
+    fn new(socket: UdpSocket, peer: SocketAddr, hello: Hello) -> Result<Self, String> {
+        let delay = hello.delay;
+        socket
+            .set_nonblocking(true)
+            .map_err(|e| format!("Cannot set up netplay: {}", e))?;
+        Ok(Self {
+            socket,
+            peer,
+            hello,
+            delay,
+            frame: 0,
+            local_buttons: 0,
+            // Nobody presses anything before the first input arrives
+            local_inputs: vec![0; delay],
+            remote_inputs: vec![0; delay],
+            used_inputs: Vec::new(),
+            acked: delay,
+            states: VecDeque::new(),
+            states_start: 0,
+            hashes: BTreeMap::new(),
+            remote_hashes: BTreeMap::new(),
+            desynced: false,
+            last_received: Instant::now(),
+            rolled_back_frames: 0,
+        })
+    }
+
+    /// Controller of the local player
+    pub fn player(&self) -> u8 {
+        self.hello.player
+    }
+
+    /// Frames the input of both players is known for
+    pub fn confirmed_frames(&self) -> usize {
+        self.remote_inputs.len().min(self.frame)
+    }
+
+    /// Press or release a button of the local player
+    pub fn set_button(&mut self, button: Button, pressed: bool) {
+        let bit = 1 << button as u8;
+        if pressed {
+            self.local_buttons |= bit;
+        } else {
+            self.local_buttons &= !bit;
+        }
+    }
+
+    /// Exchange input with the other side, and run again the frames emulated
+    /// with a wrong guess of it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket fails or a saved state cannot be loaded.
+    pub fn poll(&mut self, nes: &mut Nes) -> Result<(), String> {
+        let wrong_guess = self.receive()?;
+        if let Some(frame) = wrong_guess {
+            self.roll_back(nes, frame)?;
+        }
+        // States before the first frame with a guess are never needed again
+        while self.states_start < self.confirmed_frames() {
+            self.states.pop_front();
+            self.states_start += 1;
+        }
+        self.send_input()
+    }
+
+    /// Sample the local input and set the buttons of both controllers for the
+    /// next frame
+    ///
+    /// Returns `false` without setting anything when the next frame has to
+    /// wait for the other player's input. Call again on the next frame then.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket fails, a saved state cannot be loaded
+    /// or the other side has not been heard from for a while.
+    pub fn start_frame(&mut self, nes: &mut Nes) -> Result<bool, String> {
+        while self.local_inputs.len() <= self.frame + self.delay {
+            self.local_inputs.push(self.local_buttons);
+        }
+        self.poll(nes)?;
+        if self.frame >= self.remote_inputs.len() + Self::MAX_ROLLBACK {
+            if self.last_received.elapsed() > TIMEOUT {
+                return Err("Lost the connection to the other side".to_string());
+            }
+            return Ok(false);
+        }
+        self.states.push_back(nes.save_state());
+        self.set_frame_buttons(nes);
+        Ok(true)
+    }
+
+    /// Finish a frame, hashing RAM every `HASH_INTERVAL` frames
+    ///
+    /// # Errors
+    ///
+    /// Returns the first frame at which RAM hashed differently on the two
+    /// sides.
+    pub fn end_frame(&mut self, nes: &Nes) -> Result<(), Desync> {
+        self.frame += 1;
+        self.note_hash(nes);
+        self.check_hashes()
+    }
+
+    /// Set the buttons of both controllers for frame `self.frame`
+    fn set_frame_buttons(&mut self, nes: &mut Nes) {
+        let frame = self.frame;
+        let remote = match self.remote_inputs.get(frame) {
+            Some(&buttons) => buttons,
+            // Guess the other player still holds what they held last
+            None => self.remote_inputs.last().copied().unwrap_or(0),
+        };
+        self.used_inputs.truncate(frame);
+        self.used_inputs.push(remote);
+        let local = self.local_inputs[frame];
+        nes.set_buttons(self.hello.player, local);
+        nes.set_buttons(3 - self.hello.player, remote);
+    }
+
+    fn note_hash(&mut self, nes: &Nes) {
+        if self.frame.is_multiple_of(Self::HASH_INTERVAL) {
+            self.hashes.insert(self.frame, ram_hash(nes));
+        }
+    }
+
+    /// Compare the hashes both sides have for frames they know all input of
+    fn check_hashes(&mut self) -> Result<(), Desync> {
+        let confirmed = self.confirmed_frames();
+        while let Some((&frame, &remote)) = self.remote_hashes.first_key_value() {
+            if frame > confirmed {
+                break;
+            }
+            self.remote_hashes.remove(&frame);
+            if let Some(local) = self.hashes.get(&frame).copied()
+                && local != remote
+                && !self.desynced
+            {
+                self.desynced = true;
+                return Err(Desync {
+                    frame,
+                    local,
+                    remote,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Load the state before `frame` and emulate the frames since again
+    fn roll_back(&mut self, nes: &mut Nes, frame: usize) -> Result<(), String> {
+        let index = frame - self.states_start;
+        nes.load_state(&self.states[index])
+            .map_err(|message| format!("Cannot roll back: {}", message))?;
+        self.states.truncate(index);
+        let end = self.frame;
+        self.frame = frame;
+        while self.frame < end {
+            self.states.push_back(nes.save_state());
+            self.set_frame_buttons(nes);
+            run_hidden_frame(nes);
+            self.frame += 1;
+            self.note_hash(nes);
+        }
+        self.rolled_back_frames += end - frame;
+        Ok(())
+    }
+
+    /// Read all packets that arrived, and return the first frame emulated
+    /// with a wrong guess, if any
+    fn receive(&mut self) -> Result<Option<usize>, String> {
+        let mut wrong_guess = None;
+        let mut buffer = [0; MAX_PACKET];
+        loop {
+            let length = match self.socket.recv_from(&mut buffer) {
+                Ok((length, from)) if from == self.peer => length,
+                Ok(_) => continue,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(wrong_guess),
+                Err(e) if is_transient(&e) => continue,
+                Err(e) => return Err(format!("Netplay connection failed: {}", e)),
+            };
+            let input = match Packet::from_bytes(&buffer[..length]) {
+                Ok(Packet::Input(input)) => input,
+                Ok(Packet::Hello(_)) => {
+                    // Our answer to the hello got lost
+                    if self.hello.player == 1 {
+                        send(&self.socket, self.peer, &Packet::Hello(self.hello))?;
+                    }
+                    continue;
+                }
+                Err(message) => {
+                    eprintln!("Warning: Ignoring a netplay packet: {}", message);
+                    continue;
+                }
+            };
+            self.acked = self.acked.max(input.ack as usize);
+            if let Some((frame, hash)) = input.ram_hash {
+                self.remote_hashes.insert(frame as usize, hash);
+            }
+            for (offset, &buttons) in input.buttons.iter().enumerate() {
+                let frame = input.start as usize + offset;
+                // Only the next missing frame can be added
+                if frame != self.remote_inputs.len() {
+                    continue;
+                }
+                self.remote_inputs.push(buttons);
+                // Only new input shows the other side is still playing
+                self.last_received = Instant::now();
+                if frame < self.frame && self.used_inputs[frame] != buttons {
+                    wrong_guess = Some(wrong_guess.map_or(frame, |first: usize| first.min(frame)));
+                }
+            }
+        }
+    }
+
+    fn send_input(&mut self) -> Result<(), String> {
+        let confirmed = self.confirmed_frames();
+        let ram_hash = self
+            .hashes
+            .range(..=confirmed)
+            .next_back()
+            .map(|(&frame, &hash)| (frame as u32, hash));
+        // Older hashes will never be asked about
+        if let Some((frame, _)) = ram_hash {
+            self.hashes = self.hashes.split_off(&(frame as usize));
+        }
+        let start = self.acked.min(self.local_inputs.len());
+        let packet = Packet::Input(InputPacket {
+            ack: self.remote_inputs.len() as u32,
+            start: start as u32,
+            buttons: self.local_inputs[start..].to_vec(),
+            ram_hash,
+        });
+        send(&self.socket, self.peer, &packet)
+    }
+}
+
+fn send(socket: &UdpSocket, peer: SocketAddr, packet: &Packet) -> Result<(), String> {
+    match socket.send_to(&packet.to_bytes(), peer) {
+        Ok(_) => Ok(()),
+        // Nobody listening yet, or a full buffer: the next packet repeats it
+        Err(e) if is_transient(&e) || e.kind() == ErrorKind::WouldBlock => Ok(()),
+        Err(e) => Err(format!("Netplay connection failed: {}", e)),
+    }
+}
+
+/// Errors left behind by a packet the other side did not take, which say
+/// nothing about the next one
+fn is_transient(error: &std::io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::ConnectionRefused | ErrorKind::ConnectionReset | ErrorKind::TimedOut
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    const NESTEST: &str = "roms/nestest.nes";
+
+    /// Buttons player `player` holds on `frame`, changing often to make wrong
+    /// guesses likely
+    fn buttons(player: u8, frame: usize) -> u8 {
+        ((frame / 3) as u8).wrapping_mul(37 + player * 16)
+    }
+
+    fn run_frame(nes: &mut Nes) {
+        while !nes.is_ready_to_render() {
+            nes.run_cpu_tick();
+        }
+        nes.clear_ready_to_render();
+    }
+
+    /// Connect a host with `host_rom` and a player who joins with `join_rom`,
+    /// with the input delays in `delays`
+    fn connect(
+        host_rom: &str,
+        join_rom: &str,
+        delays: [usize; 2],
+    ) -> (
+        Result<NetplaySession, String>,
+        Result<NetplaySession, String>,
+    ) {
+        let host_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = host_socket.local_addr().unwrap();
+        let host_nes = Nes::power_on(host_rom);
+        let host = thread::spawn(move || NetplaySession::host(host_socket, &host_nes, delays[0]));
+        let join_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let joined =
+            NetplaySession::join(join_socket, address, &Nes::power_on(join_rom), delays[1]);
+        (host.join().unwrap(), joined)
+    }
+
+    /// Play `frames` frames on both sides in turn, then wait until both
+    /// know all input, and return the first desync
+    fn play(
+        sides: &mut [(NetplaySession, Nes); 2],
+        frames: usize,
+        on_frame: impl Fn(u8, usize, &mut Nes),
+    ) -> Option<Desync> {
+        let mut desync = None;
+        while sides.iter().any(|(session, _)| session.frame < frames) {
+            for (session, nes) in sides.iter_mut() {
+                if session.frame >= frames {
+                    session.poll(nes).unwrap();
+                    continue;
+                }
+                session.local_buttons = buttons(session.player(), session.frame);
+                if session.start_frame(nes).unwrap() {
+                    run_frame(nes);
+                    on_frame(session.player(), session.frame, nes);
+                    desync = desync.or(session.end_frame(nes).err());
+                }
+            }
+        }
+        let started = Instant::now();
+        while sides
+            .iter()
+            .any(|(session, _)| session.confirmed_frames() < frames)
+        {
+            assert!(started.elapsed() < TIMEOUT, "Input never arrived");
+            for (session, nes) in sides.iter_mut() {
+                session.poll(nes).unwrap();
+            }
+        }
+        desync
+    }
+
+    #[test]
+    fn test_packets_round_trip() {
+        let packets = [
+            Packet::Hello(Hello {
+                player: 2,
+                pal: true,
+                rom_crc32: 0x3337EC46,
+                delay: 3,
+            }),
+            Packet::Input(InputPacket {
+                ack: 12,
+                start: 10,
+                buttons: vec![0x81, 0x00, 0x08],
+                ram_hash: Some((30, 0xDEADBEEF)),
+            }),
+            Packet::Input(InputPacket {
+                ack: 0,
+                start: 0,
+                buttons: Vec::new(),
+                ram_hash: None,
+            }),
+        ];
+        for packet in packets {
+            assert_eq!(Packet::from_bytes(&packet.to_bytes()), Ok(packet));
+        }
+        assert!(Packet::from_bytes(&[PACKET_INPUT, 1]).is_err());
+        assert!(
+            Packet::from_bytes(&[PACKET_HELLO, PROTOCOL_VERSION + 1, 1, 0, 0, 0, 0, 0]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_two_instances_on_localhost_play_the_same_game() {
+        // Without delay, input always arrives after it is needed
+        let delay = 0;
+        let frames = 40;
+        let (host, joined) = connect(NESTEST, NESTEST, [delay; 2]);
+        let mut sides = [
+            (host.unwrap(), Nes::power_on(NESTEST)),
+            (joined.unwrap(), Nes::power_on(NESTEST)),
+        ];
+        assert_eq!(play(&mut sides, frames, |_, _, _| {}), None);
+
+        // Both end where one console given both inputs directly ends
+        let mut reference = Nes::power_on(NESTEST);
+        for frame in 0..frames {
+            for player in 1..=2 {
+                let pressed = frame.checked_sub(delay).map_or(0, |f| buttons(player, f));
+                reference.set_buttons(player, pressed);
+            }
+            run_frame(&mut reference);
+        }
+        for (session, nes) in &sides {
+            assert!(
+                nes.save_state() == reference.save_state(),
+                "player {}",
+                session.player()
+            );
+        }
+        // Input changes every few frames, so some guesses were wrong
+        assert!(
+            sides
+                .iter()
+                .any(|(session, _)| session.rolled_back_frames > 0)
+        );
+    }
+
+    #[test]
+    fn test_desync_is_detected() {
+        let (host, joined) = connect(NESTEST, NESTEST, [NetplaySession::DEFAULT_DELAY; 2]);
+        let mut sides = [
+            (host.unwrap(), Nes::power_on(NESTEST)),
+            (joined.unwrap(), Nes::power_on(NESTEST)),
+        ];
+        // Change RAM on one side behind the emulation's back
+        let desync = play(
+            &mut sides,
+            NetplaySession::HASH_INTERVAL * 2,
+            |player, frame, nes| {
+                if player == 2 && frame >= 5 {
+                    nes.poke(0x0700, 0x55);
+                }
+            },
+        );
+        assert_eq!(
+            desync.map(|desync| desync.frame),
+            Some(NetplaySession::HASH_INTERVAL)
+        );
+    }
+
+    #[test]
+    fn test_joining_with_another_rom_fails() {
+        let (host, joined) = connect(NESTEST, "roms/cpu.nes", [0; 2]);
+        assert!(host.err().unwrap().contains("ROM CRC-32"));
+        assert!(joined.err().unwrap().contains("ROM CRC-32"));
+    }
+
+    #[test]
+    fn test_joining_with_another_delay_fails() {
+        // Input would be applied on different frames on the two sides
+        let (host, joined) = connect(NESTEST, NESTEST, [2, 3]);
+        assert_eq!(
+            host.err(),
+            Some("The other side uses 3 frames of input delay, not 2".to_string())
+        );
+        assert_eq!(
+            joined.err(),
+            Some("The other side uses 2 frames of input delay, not 3".to_string())
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! On-screen display
+//!
+//! Draws the frame counter, lag counter and held buttons over a finished
+//! frame, for making tool-assisted movies. Text uses a built-in 3x5 pixel
+//! font with a dark shadow, so it stays readable on any background. Only the
+//! frame handed to the frontend is drawn on, never the PPU's screen.
+
+use crate::movie::{self, MovieMode, MovieSession};
+use crate::nes::Nes;
+
+const WIDTH: u32 = 256;
+const HEIGHT: u32 = 240;
+
+/// Pixels from one character to the next
+const ADVANCE: u32 = 4;
+/// Pixels from one line to the next
+const LINE_HEIGHT: u32 = 7;
+
+/// Corner of the first line, inside the overscan most TVs cut off
+const ORIGIN: (u32, u32) = (8, 8);
+
+pub type Color = (u8, u8, u8);
+
+pub const WHITE: Color = (0xFF, 0xFF, 0xFF);
+pub const RED: Color = (0xFF, 0x40, 0x40);
+const SHADOW: Color = (0x00, 0x00, 0x00);
+
+/// Lines shown for `nes`, with their colors
+///
+/// The first line is the frame number (with the length and mode of the movie,
+/// if one is running), then the lag counter, in red right after a lag frame,
+/// then the buttons each controller holds for the next frame.
+pub fn info_lines(nes: &Nes, movie: Option<&MovieSession>) -> Vec<(String, Color)> {
+    let frame = match movie {
+        None => nes.frame_count().to_string(),
+        Some(session) => {
+            let access = if session.is_read_only() { "RO" } else { "RW" };
+            match session.mode() {
+                MovieMode::Playing => format!(
+                    "{}/{} PLAY {}",
+                    session.frame(),
+                    session.movie().frames.len(),
+                    access
+                ),
+                MovieMode::Recording => format!("{} REC {}", session.frame(), access),
+            }
+        }
+    };
+    let lag_color = if nes.is_lag_frame() { RED } else { WHITE };
+    let mut lines = vec![
+        (frame, WHITE),
+        (format!("LAG {}", nes.lag_frames()), lag_color),
+    ];
+    for controller in 1..=2 {
+        let buttons = movie::format_buttons(nes.buttons(controller));
+        lines.push((format!("P{} {}", controller, buttons), WHITE));
+    }
+    lines
+}
+
+/// Draw `info_lines` in the top left corner of an RGB24 frame
+pub fn draw_info(frame: &mut [u8], nes: &Nes, movie: Option<&MovieSession>) {
+    let (x, mut y) = ORIGIN;
+    for (line, color) in info_lines(nes, movie) {
+        draw_text(frame, x, y, &line, color);
+        y += LINE_HEIGHT;
+    }
+}
+
+/// Draw a line of text with its top left corner at `(x, y)` in an RGB24
+/// frame of 256x240 pixels
+///
+/// Lower case letters are drawn as upper case and characters without a glyph
+/// as spaces. Text past the edges is cut off.
+pub fn draw_text(frame: &mut [u8], x: u32, y: u32, text: &str, color: Color) {
+    for (offset, color) in [(1, SHADOW), (0, color)] {
+        for (index, character) in text.chars().enumerate() {
+            let left = x + index as u32 * ADVANCE + offset;
+            for (row, bits) in glyph(character).iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (0b100 >> column) != 0 {
+                        set_pixel(frame, left + column, y + row as u32 + offset, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn set_pixel(frame: &mut [u8], x: u32, y: u32, (r, g, b): Color) {
+    if x < WIDTH && y < HEIGHT {
+        let offset = ((y * WIDTH + x) * 3) as usize;
+        frame[offset..offset + 3].copy_from_slice(&[r, g, b]);
+    }
+}
+
+/// Rows of a character, top first, with the leftmost pixel in bit 2
+fn glyph(character: char) -> [u8; 5] {
+    match character.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        _ => [0; 5],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(frame: &[u8], x: u32, y: u32) -> Color {
+        let offset = ((y * WIDTH + x) * 3) as usize;
+        (frame[offset], frame[offset + 1], frame[offset + 2])
+    }
+
+    #[test]
+    fn test_text_is_drawn_with_a_shadow() {
+        let grey = (0x80, 0x80, 0x80);
+        let mut frame = [0x80; (WIDTH * HEIGHT * 3) as usize];
+        draw_text(&mut frame, 10, 20, "T1", WHITE);
+
+        // The bar of the T, with the shadow below its stem
+        for x in 10..13 {
+            assert_eq!(pixel(&frame, x, 20), WHITE);
+        }
+        assert_eq!(pixel(&frame, 11, 24), WHITE);
+        assert_eq!(pixel(&frame, 12, 25), SHADOW);
+        assert_eq!(pixel(&frame, 10, 24), grey);
+        // The foot of the 1, one character along
+        assert_eq!(pixel(&frame, 14, 24), WHITE);
+        assert_eq!(pixel(&frame, 13, 24), grey);
+    }
+
+    #[test]
+    fn test_text_past_the_edge_is_cut_off() {
+        let mut frame = vec![0; (WIDTH * HEIGHT * 3) as usize];
+        draw_text(&mut frame, 250, 237, "WWWW", WHITE);
+        assert_eq!(pixel(&frame, 252, 239), WHITE);
+    }
+
+    #[test]
+    fn test_info_lines() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        nes.set_buttons(1, 0x81);
+        let mut session = MovieSession::record(&nes, None);
+        session.start_frame(&mut nes);
+        while !nes.is_ready_to_render() {
+            nes.run_cpu_tick();
+        }
+        assert!(session.end_frame(&nes).is_ok());
+
+        let text = |lines: Vec<(String, Color)>| -> Vec<String> {
+            lines.into_iter().map(|(line, _)| line).collect()
+        };
+        assert_eq!(
+            text(info_lines(&nes, None)),
+            ["1", "LAG 1", "P1 R......A", "P2 ........"]
+        );
+        assert_eq!(info_lines(&nes, None)[1].1, RED);
+        assert_eq!(text(info_lines(&nes, Some(&session)))[0], "1 REC RW");
+
+        let session = MovieSession::play(session.movie().clone(), &mut nes).unwrap();
+        assert_eq!(text(info_lines(&nes, Some(&session)))[0], "0/1 PLAY RO");
+    }
+}
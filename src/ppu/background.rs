@@ -1,3 +1,5 @@
+use crate::savestate::{SaveState, StateReader, StateWriter};
+
 /// Manages background rendering including shift registers, tile fetching, and pixel composition
 pub struct Background {
     /// Background pattern shift register - low bit plane (16 bits)
@@ -69,9 +71,9 @@ impl Background {
     }
 
     /// Fetch pattern table low byte from CHR ROM
-    pub fn fetch_pattern_lo<F>(&mut self, pattern_table_base: u16, v: u16, read_chr: F)
+    pub fn fetch_pattern_lo<F>(&mut self, pattern_table_base: u16, v: u16, mut read_chr: F)
     where
-        F: Fn(u16) -> u8,
+        F: FnMut(u16) -> u8,
     {
         let tile_index = self.nametable_latch as u16;
         let fine_y = (v >> 12) & 0x07;
@@ -80,9 +82,9 @@ impl Background {
     }
 
     /// Fetch pattern table high byte from CHR ROM
-    pub fn fetch_pattern_hi<F>(&mut self, pattern_table_base: u16, v: u16, read_chr: F)
+    pub fn fetch_pattern_hi<F>(&mut self, pattern_table_base: u16, v: u16, mut read_chr: F)
     where
-        F: Fn(u16) -> u8,
+        F: FnMut(u16) -> u8,
     {
         let tile_index = self.nametable_latch as u16;
         let fine_y = (v >> 12) & 0x07;
@@ -205,6 +207,47 @@ impl Background {
     }
 }
 
+impl SaveState for Background {
+    fn save_state(&self, state: &mut StateWriter) {
+        for shift in [
+            self.bg_pattern_shift_lo,
+            self.bg_pattern_shift_hi,
+            self.bg_attribute_shift_lo,
+            self.bg_attribute_shift_hi,
+        ] {
+            state.write_u16(shift);
+        }
+        for latch in [
+            self.nametable_latch,
+            self.attribute_latch,
+            self.pattern_lo_latch,
+            self.pattern_hi_latch,
+        ] {
+            state.write_u8(latch);
+        }
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        for shift in [
+            &mut self.bg_pattern_shift_lo,
+            &mut self.bg_pattern_shift_hi,
+            &mut self.bg_attribute_shift_lo,
+            &mut self.bg_attribute_shift_hi,
+        ] {
+            *shift = state.read_u16()?;
+        }
+        for latch in [
+            &mut self.nametable_latch,
+            &mut self.attribute_latch,
+            &mut self.pattern_lo_latch,
+            &mut self.pattern_hi_latch,
+        ] {
+            *latch = state.read_u8()?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,6 +1,16 @@
 use crate::cartridge::{Cartridge, MirroringMode};
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::cdl::{self, CodeDataLog};
+use crate::savestate::{SaveState, StateReader, StateWriter};
+
+/// The cartridge side of the PPU bus
+///
+/// The PPU does not own the cartridge. The CPU bus lends it, together with the
+/// code/data log if one is recording, to every PPU call that can access CHR.
+#[derive(Default)]
+pub struct ChrBus<'a> {
+    pub cartridge: Option<&'a mut Cartridge>,
+    pub cdl: Option<&'a mut CodeDataLog>,
+}
 
 /// Manages PPU memory including VRAM, palette RAM, and CHR ROM
 pub struct Memory {
@@ -44,15 +54,39 @@ impl Memory {
     /// - Proper hardware-accurate memory access
     ///
     /// Returns 0 if no cartridge is loaded.
-    pub fn read_chr(&self, addr: u16, cartridge: &Option<Rc<RefCell<Cartridge>>>) -> u8 {
+    pub fn read_chr(&self, addr: u16, chr: &ChrBus) -> u8 {
         let masked_addr = addr & 0x1FFF;
-        if let Some(cart) = cartridge {
-            cart.borrow().mapper().read_chr(masked_addr)
+        if let Some(cart) = &chr.cartridge {
+            cart.mapper().read_chr(masked_addr)
         } else {
             0 // No cartridge loaded, return 0
         }
     }
 
+    /// Read a pattern table byte for rendering
+    ///
+    /// Same as `read_chr`, but marks the byte as drawn in the code/data log.
+    pub fn fetch_pattern(&self, addr: u16, chr: &mut ChrBus) -> u8 {
+        Self::log_chr(addr, chr, cdl::DRAWN);
+        self.read_chr(addr, chr)
+    }
+
+    /// Read a CHR byte through PPUDATA ($2007)
+    ///
+    /// Same as `read_chr`, but marks the byte as read in the code/data log.
+    pub fn read_chr_data(&self, addr: u16, chr: &mut ChrBus) -> u8 {
+        Self::log_chr(addr, chr, cdl::READ);
+        self.read_chr(addr, chr)
+    }
+
+    fn log_chr(addr: u16, chr: &mut ChrBus, flags: u8) {
+        if let (Some(cdl), Some(cart)) = (&mut chr.cdl, &chr.cartridge)
+            && let Some(offset) = cart.mapper().chr_rom_offset(addr & 0x1FFF)
+        {
+            cdl.mark_chr(offset, flags);
+        }
+    }
+
     /// Write to CHR memory at the specified address through the mapper
     ///
     /// This method routes the write through the cartridge mapper.
@@ -62,10 +96,10 @@ impl Memory {
     ///
     /// The address is masked to 13 bits (0x0000-0x1FFF, 8KB range).
     /// No-op if no cartridge is loaded.
-    pub fn write_chr(&mut self, addr: u16, value: u8, cartridge: &Option<Rc<RefCell<Cartridge>>>) {
+    pub fn write_chr(&mut self, addr: u16, value: u8, chr: &mut ChrBus) {
         let masked_addr = addr & 0x1FFF;
-        if let Some(cart) = cartridge {
-            cart.borrow_mut().mapper_mut().write_chr(masked_addr, value);
+        if let Some(cart) = &mut chr.cartridge {
+            cart.mapper_mut().write_chr(masked_addr, value);
         }
     }
 
@@ -149,6 +183,20 @@ impl Memory {
     }
 }
 
+impl SaveState for Memory {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.ppu_ram);
+        state.write_bytes(&self.palette);
+        self.mirroring_mode.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.read_bytes_into(&mut self.ppu_ram)?;
+        state.read_bytes_into(&mut self.palette)?;
+        self.mirroring_mode.load_state(state)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,7 +204,7 @@ mod tests {
     #[test]
     fn test_memory_new() {
         let mem = Memory::new();
-        assert_eq!(mem.read_chr(0, &None), 0);
+        assert_eq!(mem.read_chr(0, &ChrBus::default()), 0);
         assert_eq!(mem.read_palette(0x3F00), 0);
     }
 
@@ -387,17 +435,18 @@ mod tests {
     #[test]
     fn test_chr_write_no_cartridge() {
         let mut mem = Memory::new();
+        let mut chr = ChrBus::default();
         // With no cartridge, reads return 0
-        assert_eq!(mem.read_chr(0x0000, &None), 0x00);
+        assert_eq!(mem.read_chr(0x0000, &chr), 0x00);
 
         // Write to CHR memory (no-op without cartridge)
-        mem.write_chr(0x0000, 0xAA, &None);
-        mem.write_chr(0x1000, 0xBB, &None);
-        mem.write_chr(0x1FFF, 0xCC, &None);
+        mem.write_chr(0x0000, 0xAA, &mut chr);
+        mem.write_chr(0x1000, 0xBB, &mut chr);
+        mem.write_chr(0x1FFF, 0xCC, &mut chr);
 
         // Reads still return 0 (no cartridge to store data)
-        assert_eq!(mem.read_chr(0x0000, &None), 0x00);
-        assert_eq!(mem.read_chr(0x1000, &None), 0x00);
-        assert_eq!(mem.read_chr(0x1FFF, &None), 0x00);
+        assert_eq!(mem.read_chr(0x0000, &chr), 0x00);
+        assert_eq!(mem.read_chr(0x1000, &chr), 0x00);
+        assert_eq!(mem.read_chr(0x1FFF, &chr), 0x00);
     }
 }
@@ -1,5 +1,6 @@
 mod background;
 mod memory;
+mod palette;
 mod ppu;
 mod registers;
 mod rendering;
@@ -8,7 +9,8 @@ mod status;
 mod timing;
 
 pub use background::Background;
-pub use memory::Memory;
+pub use memory::{ChrBus, Memory};
+pub use palette::{Palette, default_palette, load_palette_file};
 pub use ppu::Ppu;
 pub use registers::Registers;
 pub use rendering::Rendering;
@@ -0,0 +1,75 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Number of colors in the NES master palette
+pub const PALETTE_SIZE: usize = 0x40;
+
+/// RGB values for the 64 NES color indices
+pub type Palette = [(u8, u8, u8); PALETTE_SIZE];
+
+/// The built-in system palette
+pub fn default_palette() -> Palette {
+    std::array::from_fn(|index| crate::nes::Nes::lookup_system_palette(index as u8))
+}
+
+/// Load a palette from a `.pal` file
+///
+/// `.pal` files hold consecutive RGB triplets. Files with 64 entries (192 bytes)
+/// and files that append the 7 emphasis variants (1536 bytes) are accepted;
+/// only the first 64 colors are used.
+pub fn load_palette_file(path: &Path) -> io::Result<Palette> {
+    parse_palette(&fs::read(path)?)
+}
+
+/// Parse `.pal` file contents
+pub fn parse_palette(data: &[u8]) -> io::Result<Palette> {
+    if data.len() < PALETTE_SIZE * 3 || !data.len().is_multiple_of(PALETTE_SIZE * 3) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Palette file must be a multiple of {} bytes, got {}",
+                PALETTE_SIZE * 3,
+                data.len()
+            ),
+        ));
+    }
+
+    Ok(std::array::from_fn(|index| {
+        let offset = index * 3;
+        (data[offset], data[offset + 1], data[offset + 2])
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_palette_matches_system_palette() {
+        let palette = default_palette();
+        assert_eq!(palette[0x16], crate::nes::Nes::lookup_system_palette(0x16));
+        assert_eq!(palette[0x3F], crate::nes::Nes::lookup_system_palette(0x3F));
+    }
+
+    #[test]
+    fn test_parse_palette() {
+        let data: Vec<u8> = (0..192).map(|i| i as u8).collect();
+        let palette = parse_palette(&data).unwrap();
+        assert_eq!(palette[0], (0, 1, 2));
+        assert_eq!(palette[63], (189, 190, 191));
+    }
+
+    #[test]
+    fn test_parse_palette_with_emphasis_variants() {
+        let mut data = vec![0x11; 192];
+        data.extend(vec![0x22; 192 * 7]);
+        assert_eq!(parse_palette(&data).unwrap()[63], (0x11, 0x11, 0x11));
+    }
+
+    #[test]
+    fn test_parse_palette_rejects_bad_size() {
+        assert!(parse_palette(&[0; 100]).is_err());
+        assert!(parse_palette(&[0; 200]).is_err());
+    }
+}
@@ -1,14 +1,20 @@
-use crate::cartridge::{Cartridge, MirroringMode};
+use crate::cartridge::MirroringMode;
 use crate::nes::TvSystem;
-use crate::ppu::{Background, Memory, Registers, Rendering, Sprites, Status, Timing};
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::ppu::{
+    Background, ChrBus, Memory, Palette, Registers, Rendering, Sprites, Status, Timing,
+    default_palette,
+};
+use crate::savestate::{SaveState, StateReader, StateWriter};
 
 #[cfg(test)]
 use std::fs::OpenOptions;
 #[cfg(test)]
 use std::io::Write;
 
+/// Dots between a pixel being drawn and it reaching the video output, where
+/// grayscale and color emphasis are applied
+const MASK_OUTPUT_DELAY: u16 = 2;
+
 /// Refactored PPU using modular components
 pub struct Ppu {
     /// Timing and cycle management
@@ -28,13 +34,11 @@ pub struct Ppu {
     rendering: Rendering,
     /// Previous A12 state for change detection (bit 12 of PPU address)
     prev_a12: bool,
-    /// Cartridge reference for dynamic CHR ROM/RAM access through mapper
-    ///
-    /// The PPU holds a shared reference to the cartridge to enable:
-    /// - Real-time CHR bank switching during rendering
-    /// - Proper mapper integration for pattern table access
-    /// - Hardware-accurate CHR-ROM and CHR-RAM behavior
-    cartridge: Option<Rc<RefCell<Cartridge>>>,
+    /// RGB values used to turn palette RAM entries into screen colors
+    palette: Palette,
+    /// Palette RAM colors drawn on the current scanline, before grayscale and
+    /// color emphasis
+    line_colors: [u8; 256],
 }
 
 impl Ppu {
@@ -49,10 +53,26 @@ impl Ppu {
             sprites: Sprites::new(),
             rendering: Rendering::new(),
             prev_a12: false,
-            cartridge: None,
+            palette: default_palette(),
+            line_colors: [0; 256],
         }
     }
 
+    /// Get the TV system this PPU was created for
+    pub fn tv_system(&self) -> TvSystem {
+        self.timing.tv_system()
+    }
+
+    /// Replace the palette used to convert NES colors to RGB
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    /// Enable or disable the 8-sprites-per-scanline limit
+    pub fn set_sprite_limit(&mut self, enabled: bool) {
+        self.sprites.set_sprite_limit(enabled);
+    }
+
     /// Reset the PPU to its initial state
     pub fn reset(&mut self) {
         self.timing.reset();
@@ -65,38 +85,34 @@ impl Ppu {
     }
 
     /// Run the PPU for a specified number of cycles
-    pub fn run_ppu_cycles(&mut self, cycles: u64) {
+    ///
+    /// `chr` gives access to the cartridge's CHR memory for rendering fetches.
+    pub fn run_ppu_cycles(&mut self, cycles: u64, chr: &mut ChrBus) {
         for _ in 0..cycles {
-            self.tick();
+            self.tick(chr);
         }
     }
 
     /// Process a single PPU cycle
-    fn tick(&mut self) {
+    fn tick(&mut self, chr: &mut ChrBus) {
         // Advance timing
         let _skipped = self.timing.tick(self.registers.is_rendering_enabled());
 
         // Tick the registers for decay timing
         self.registers.tick();
 
-        // Clear VBlank start cycle flag from previous cycle
-        self.status.clear_vblank_start_cycle();
-
         // Enter VBlank at scanline 241, pixel 1
         if self.timing.scanline() == 241 && self.timing.pixel() == 1 {
             self.status
                 .enter_vblank(self.registers.should_generate_nmi());
         }
 
-        // Exit VBlank at the end of scanline 260 (one scanline before pre-render)
-        // The hardware clears VBL slightly before the pre-render scanline starts
-        // This happens approximately 2270 CPU cycles after VBlank starts
+        // Exit VBlank at dot 1 of the pre-render scanline
         let prerender_scanline = match self.timing.tv_system() {
             TvSystem::Ntsc => 261,
             TvSystem::Pal => 311,
         };
-        let vblank_end_scanline = prerender_scanline - 1;
-        if self.timing.scanline() == vblank_end_scanline && self.timing.pixel() == 340 {
+        if self.timing.scanline() == prerender_scanline && self.timing.pixel() == 1 {
             self.status.exit_vblank();
         }
 
@@ -104,7 +120,6 @@ impl Ppu {
         let pixel = self.timing.pixel();
 
         // Clear sprite 0 hit and sprite overflow at dot 0 of pre-render scanline
-        // This is 1 cycle after VBL flag is cleared (scanline 260, pixel 340)
         // For sprite_hit timing test: clear_time = 6819 cycles after VBL = scanline 261, pixel 0
         if scanline == prerender_scanline && pixel == 0 {
             self.status.clear_sprite_flags();
@@ -154,20 +169,18 @@ impl Ppu {
                         // Fetch pattern table low byte
                         let v = self.registers.v();
                         let bg_pattern_table = self.registers.bg_pattern_table_addr();
-                        let cartridge = &self.cartridge;
                         self.background
                             .fetch_pattern_lo(bg_pattern_table, v, |addr| {
-                                self.memory.read_chr(addr, cartridge)
+                                self.memory.fetch_pattern(addr, chr)
                             });
                     }
                     3 => {
                         // Fetch pattern table high byte
                         let v = self.registers.v();
                         let bg_pattern_table = self.registers.bg_pattern_table_addr();
-                        let cartridge = &self.cartridge;
                         self.background
                             .fetch_pattern_hi(bg_pattern_table, v, |addr| {
-                                self.memory.read_chr(addr, cartridge)
+                                self.memory.fetch_pattern(addr, chr)
                             });
                     }
                     _ => {}
@@ -267,19 +280,18 @@ impl Ppu {
 
                 if pixel == 256 {
                     // Finalize evaluation
-                    self.sprites.finalize_evaluation();
+                    self.sprites.finalize_evaluation(scanline, sprite_height);
                 }
             } else if pixel >= 257 && pixel <= 320 {
                 // Fetch sprite patterns for next scanline
                 let sprite_height = self.registers.sprite_height();
                 let sprite_pattern_table = self.registers.sprite_pattern_table_addr();
-                let cartridge = &self.cartridge;
                 self.sprites.fetch_sprite_pattern(
                     pixel,
                     scanline,
                     sprite_height,
                     sprite_pattern_table,
-                    |addr| self.memory.read_chr(addr, cartridge),
+                    |addr| self.memory.fetch_pattern(addr, chr),
                 );
             } else if pixel == 321 {
                 // Swap sprite buffers for rendering
@@ -339,93 +351,15 @@ impl Ppu {
                         bg_pixel // No sprite
                     };
 
-                // Apply grayscale if enabled (mask to monochrome palette)
-                let final_palette_index = if self.registers.is_grayscale() {
-                    palette_index & 0x30
-                } else {
-                    palette_index
-                };
-
                 // Look up color in palette (convert index to address)
-                let palette_addr = 0x3F00 + (final_palette_index as u16);
+                let palette_addr = 0x3F00 + (palette_index as u16);
                 let color_value = self.memory.read_palette(palette_addr);
-                let (r, g, b) = crate::nes::Nes::lookup_system_palette(color_value);
-
-                // Debug: Log palette lookups
-                // if scanline == 0 && screen_x >= 7 && screen_x < 12 {
-                //     static mut PAL_LOG_COUNT: u32 = 0;
-                //     unsafe {
-                //         if PAL_LOG_COUNT < 5 {
-                //             println!(
-                //                 "Palette lookup: x={}, palette_idx={}, addr=${:04X}, color_value=${:02X}, rgb=({},{},{})",
-                //                 screen_x, final_palette_index, palette_addr, color_value, r, g, b
-                //             );
-                //             PAL_LOG_COUNT += 1;
-                //         }
-                //     }
-                // }
-
-                // Apply color emphasis/tint
-                let (final_r, final_g, final_b) = if self.registers.color_emphasis() != 0 {
-                    let emphasis = self.registers.color_emphasis();
-                    let emphasize_red = (emphasis & 0x01) != 0;
-                    let emphasize_green = (emphasis & 0x02) != 0;
-                    let emphasize_blue = (emphasis & 0x04) != 0;
-
-                    const ATTENUATION: f32 = 0.75;
-                    const BOOST: f32 = 1.1;
-
-                    let mut fr = r as f32;
-                    let mut fg = g as f32;
-                    let mut fb = b as f32;
-
-                    if emphasize_red {
-                        fr = (fr * BOOST).min(255.0);
-                        if !emphasize_green {
-                            fg *= ATTENUATION;
-                        }
-                        if !emphasize_blue {
-                            fb *= ATTENUATION;
-                        }
-                    }
-                    if emphasize_green {
-                        fg = (fg * BOOST).min(255.0);
-                        if !emphasize_red {
-                            fr *= ATTENUATION;
-                        }
-                        if !emphasize_blue {
-                            fb *= ATTENUATION;
-                        }
-                    }
-                    if emphasize_blue {
-                        fb = (fb * BOOST).min(255.0);
-                        if !emphasize_red {
-                            fr *= ATTENUATION;
-                        }
-                        if !emphasize_green {
-                            fg *= ATTENUATION;
-                        }
-                    }
-
-                    (fr as u8, fg as u8, fb as u8)
-                } else {
-                    (r, g, b)
-                };
-
-                // Write pixel to screen buffer
-                self.rendering
-                    .screen_buffer_mut()
-                    .set_pixel(screen_x, screen_y, final_r, final_g, final_b);
+                self.output_pixel(screen_x, screen_y, color_value);
             } else {
                 // When rendering is disabled, output the backdrop color
                 let backdrop_addr = 0x3F00;
                 let color_value = self.memory.read_palette(backdrop_addr);
-                let (r, g, b) = crate::nes::Nes::lookup_system_palette(color_value);
-
-                // Write backdrop color to screen buffer
-                self.rendering
-                    .screen_buffer_mut()
-                    .set_pixel(screen_x, screen_y, r, g, b);
+                self.output_pixel(screen_x, screen_y, color_value);
             }
 
             // Per NES Dev wiki: "On every dot in these background fetch regions, a 4-bit pixel
@@ -491,10 +425,105 @@ impl Ppu {
     pub fn write_mask(&mut self, value: u8) {
         self.registers.write_mask(value);
         self.registers.set_io_bus(value); // Update I/O bus
+        self.reoutput_pipeline();
+    }
+
+    /// Send a color read from palette RAM to the screen
+    ///
+    /// Grayscale and color emphasis apply at the video output, to the color
+    /// rather than the palette index, so they also apply to the backdrop.
+    fn output_pixel(&mut self, screen_x: u32, screen_y: u32, color_value: u8) {
+        self.line_colors[screen_x as usize] = color_value;
+        let color_value = if self.registers.is_grayscale() {
+            color_value & 0x30
+        } else {
+            color_value
+        };
+        let (r, g, b) = self.palette[(color_value & 0x3F) as usize];
+
+        // Apply color emphasis/tint
+        let (final_r, final_g, final_b) = if self.registers.color_emphasis() != 0 {
+            let emphasis = self.registers.color_emphasis();
+            let emphasize_red = (emphasis & 0x01) != 0;
+            let emphasize_green = (emphasis & 0x02) != 0;
+            let emphasize_blue = (emphasis & 0x04) != 0;
+
+            const ATTENUATION: f32 = 0.75;
+            const BOOST: f32 = 1.1;
+
+            let mut fr = r as f32;
+            let mut fg = g as f32;
+            let mut fb = b as f32;
+
+            if emphasize_red {
+                fr = (fr * BOOST).min(255.0);
+                if !emphasize_green {
+                    fg *= ATTENUATION;
+                }
+                if !emphasize_blue {
+                    fb *= ATTENUATION;
+                }
+            }
+            if emphasize_green {
+                fg = (fg * BOOST).min(255.0);
+                if !emphasize_red {
+                    fr *= ATTENUATION;
+                }
+                if !emphasize_blue {
+                    fb *= ATTENUATION;
+                }
+            }
+            if emphasize_blue {
+                fb = (fb * BOOST).min(255.0);
+                if !emphasize_red {
+                    fr *= ATTENUATION;
+                }
+                if !emphasize_green {
+                    fg *= ATTENUATION;
+                }
+            }
+
+            (fr as u8, fg as u8, fb as u8)
+        } else {
+            (r, g, b)
+        };
+
+        self.rendering
+            .screen_buffer_mut()
+            .set_pixel(screen_x, screen_y, final_r, final_g, final_b);
+    }
+
+    /// Output again the pixels still in the video pipeline, after a PPUMASK
+    /// write changed grayscale or color emphasis
+    ///
+    /// A pixel reaches the output `MASK_OUTPUT_DELAY` dots after it is drawn,
+    /// so a write also changes the last pixels drawn before it.
+    fn reoutput_pipeline(&mut self) {
+        let (scanline, pixel) = (self.timing.scanline(), self.timing.pixel());
+        if scanline >= 240 {
+            return;
+        }
+        // Dots 1-256 draw screen x 0-255
+        let first_dot = pixel.saturating_sub(MASK_OUTPUT_DELAY - 1).max(1);
+        for dot in first_dot..=pixel.min(256) {
+            let screen_x = (dot - 1) as u32;
+            let color_value = self.line_colors[screen_x as usize];
+            self.output_pixel(screen_x, scanline as u32, color_value);
+        }
     }
 
     /// Read status register ($2002)
     pub fn get_status(&mut self) -> u8 {
+        // Reads racing the start of VBlank: one dot before it, the flag reads
+        // clear and is not set this frame; on its first two dots, the flag
+        // reads set and the NMI is lost
+        if self.timing.scanline() == 241 {
+            match self.timing.pixel() {
+                0 => self.status.suppress_vblank(),
+                1 | 2 => self.status.cancel_nmi(),
+                _ => {}
+            }
+        }
         let status = self.status.read_status();
         self.registers.clear_w(); // Reading status clears write toggle
         // Update I/O bus: status bits go to bits 5-7, bits 0-4 remain from previous value
@@ -518,14 +547,14 @@ impl Ppu {
     }
 
     /// Read from data register ($2007)
-    pub fn read_data(&mut self) -> u8 {
+    pub fn read_data(&mut self, chr: &mut ChrBus) -> u8 {
         let addr = self.registers.v();
         let result = match addr {
             0x0000..=0x1FFF => {
                 // CHR ROM: buffered read
                 let buffered = self.registers.data_buffer();
                 self.registers
-                    .set_data_buffer(self.memory.read_chr(addr, &self.cartridge));
+                    .set_data_buffer(self.memory.read_chr_data(addr, chr));
                 buffered
             }
             0x2000..=0x3EFF => {
@@ -569,13 +598,13 @@ impl Ppu {
     }
 
     /// Write to data register ($2007)
-    pub fn write_data(&mut self, value: u8) {
+    pub fn write_data(&mut self, value: u8, chr: &mut ChrBus) {
         self.registers.set_io_bus(value); // Update I/O bus
         let addr = self.registers.v();
         match addr {
             0x0000..=0x1FFF => {
                 // CHR memory - routes through mapper for ROM/RAM handling
-                self.memory.write_chr(addr, value, &self.cartridge);
+                self.memory.write_chr(addr, value, chr);
             }
             0x2000..=0x3EFF => {
                 self.memory.write_nametable(addr, value);
@@ -594,15 +623,6 @@ impl Ppu {
         }
     }
 
-    /// Set the cartridge reference for dynamic CHR ROM/RAM access
-    ///
-    /// This establishes the connection between the PPU and the cartridge mapper,
-    /// enabling hardware-accurate CHR access with bank switching support.
-    /// Must be called after inserting a cartridge into the system.
-    pub fn set_cartridge(&mut self, cartridge: Rc<RefCell<Cartridge>>) {
-        self.cartridge = Some(cartridge);
-    }
-
     /// Set mirroring mode
     pub fn set_mirroring(&mut self, mirroring: MirroringMode) {
         self.memory.set_mirroring(mirroring);
@@ -628,6 +648,11 @@ impl Ppu {
         self.timing.pixel()
     }
 
+    /// Get the number of frames started since power-on or reset
+    pub fn frame_count(&self) -> u64 {
+        self.timing.frame_count()
+    }
+
     /// Write to OAM address register ($2003)
     pub fn write_oam_address(&mut self, value: u8) {
         self.registers.oam_address = value;
@@ -712,20 +737,17 @@ impl Ppu {
         self.timing.total_cycles()
     }
 
-    /// Get v register (for testing)
-    #[cfg(test)]
+    /// Get v register (current VRAM address)
     pub fn v_register(&self) -> u16 {
         self.registers.v()
     }
 
     /// Read nametable for debugging/testing (doesn't affect PPU state)
-    #[cfg(test)]
     pub fn read_nametable_for_debug(&self, addr: u16) -> u8 {
         self.memory.read_nametable(addr)
     }
 
-    /// Get base nametable address from PPUCTRL (for testing)
-    #[cfg(test)]
+    /// Get base nametable address from PPUCTRL
     pub fn base_nametable_addr(&self) -> u16 {
         self.registers.base_nametable_addr()
     }
@@ -766,9 +788,34 @@ impl Ppu {
     }
 }
 
+/// The screen and the palette are outputs and settings, not part of the state
+impl SaveState for Ppu {
+    fn save_state(&self, state: &mut StateWriter) {
+        self.timing.save_state(state);
+        self.status.save_state(state);
+        self.registers.save_state(state);
+        self.memory.save_state(state);
+        self.background.save_state(state);
+        self.sprites.save_state(state);
+        state.write_bool(self.prev_a12);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        self.timing.load_state(state)?;
+        self.status.load_state(state)?;
+        self.registers.load_state(state)?;
+        self.memory.load_state(state)?;
+        self.background.load_state(state)?;
+        self.sprites.load_state(state)?;
+        self.prev_a12 = state.read_bool()?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cartridge::Cartridge;
 
     #[test]
     fn test_ppu_modular_new() {
@@ -780,7 +827,7 @@ mod tests {
     #[test]
     fn test_ppu_modular_reset() {
         let mut ppu = Ppu::new(TvSystem::Ntsc);
-        ppu.run_ppu_cycles(100);
+        ppu.run_ppu_cycles(100, &mut ChrBus::default());
         ppu.reset();
         assert_eq!(ppu.scanline(), 0);
         assert_eq!(ppu.pixel(), 0);
@@ -798,30 +845,23 @@ mod tests {
         let mut ppu = Ppu::new(TvSystem::Ntsc);
         ppu.write_address(0x3F, false);
         ppu.write_address(0x00, false);
-        ppu.write_data(0x42);
+        ppu.write_data(0x42, &mut ChrBus::default());
 
         ppu.write_address(0x3F, false);
         ppu.write_address(0x00, false);
         // Palette RAM only stores 6 bits (0x42 & 0x3F = 0x02)
         // Reading returns palette bits 5-0 combined with open bus bits 7-6
         // After writing 0x00 to address, io_bus = 0x00, so result is 0x02
-        assert_eq!(ppu.read_data(), 0x02);
+        assert_eq!(ppu.read_data(&mut ChrBus::default()), 0x02);
     }
 
     #[test]
     fn test_ppu_modular_vblank() {
         let mut ppu = Ppu::new(TvSystem::Ntsc);
         // Advance to VBlank (scanline 241, pixel 1)
-        ppu.run_ppu_cycles(241 * 341 + 1);
+        ppu.run_ppu_cycles(241 * 341 + 1, &mut ChrBus::default());
 
-        let status = ppu.get_status();
         // VBlank flag should be set (bit 7)
-        assert_eq!(status & 0x80, 0x80);
-
-        // Advance one more cycle to get past vblank_start_cycle
-        ppu.run_ppu_cycles(1);
-
-        // Reading status should clear VBlank flag (now that we're past vblank_start_cycle)
         let status_first_read = ppu.get_status();
         assert_eq!(status_first_read & 0x80, 0x80);
 
@@ -830,20 +870,63 @@ mod tests {
         assert_eq!(status_second_read & 0x80, 0);
     }
 
+    /// Run an NMI-enabled PPU to a dot just before or after VBlank starts, read
+    /// $2002 there, and return what it read and whether the NMI survived
+    fn read_status_near_vblank(pixel: u64) -> (u8, bool) {
+        let mut ppu = Ppu::new(TvSystem::Ntsc);
+        ppu.write_control(0x80);
+        ppu.run_ppu_cycles(241 * 341 + pixel, &mut ChrBus::default());
+        let status = ppu.get_status();
+        ppu.run_ppu_cycles(10, &mut ChrBus::default());
+        (status & 0x80, ppu.poll_nmi())
+    }
+
+    #[test]
+    fn test_status_read_one_dot_before_vblank_suppresses_it() {
+        let mut ppu = Ppu::new(TvSystem::Ntsc);
+        ppu.write_control(0x80);
+        ppu.run_ppu_cycles(241 * 341, &mut ChrBus::default());
+        assert_eq!(ppu.get_status() & 0x80, 0);
+
+        // The flag is not set this frame, and no NMI happens
+        ppu.run_ppu_cycles(10, &mut ChrBus::default());
+        assert!(!ppu.is_in_vblank());
+        assert!(!ppu.poll_nmi());
+    }
+
+    #[test]
+    fn test_status_read_as_vblank_starts_cancels_nmi() {
+        // On the dot VBlank starts and the next, the flag reads set and the
+        // NMI is lost
+        assert_eq!(read_status_near_vblank(1), (0x80, false));
+        assert_eq!(read_status_near_vblank(2), (0x80, false));
+        // Later reads leave the NMI alone
+        assert_eq!(read_status_near_vblank(3), (0x80, true));
+    }
+
+    #[test]
+    fn test_vblank_ends_on_dot_1_of_prerender_scanline() {
+        let mut ppu = Ppu::new(TvSystem::Ntsc);
+        ppu.run_ppu_cycles(261 * 341, &mut ChrBus::default());
+        assert!(ppu.is_in_vblank());
+        ppu.run_ppu_cycles(1, &mut ChrBus::default());
+        assert!(!ppu.is_in_vblank());
+    }
+
     // PPU Data tests
     #[test]
     fn test_read_data_from_palette() {
         let mut ppu = Ppu::new(TvSystem::Ntsc);
         ppu.write_address(0x3F, false);
         ppu.write_address(0x00, false);
-        ppu.write_data(0x42);
+        ppu.write_data(0x42, &mut ChrBus::default());
 
         ppu.write_address(0x3F, false);
         ppu.write_address(0x00, false);
         // Palette RAM only stores 6 bits (0x42 & 0x3F = 0x02)
         // Reading returns palette bits 5-0 combined with open bus bits 7-6
         // After writing 0x00 to address, io_bus = 0x00, so result is 0x02
-        assert_eq!(ppu.read_data(), 0x02);
+        assert_eq!(ppu.read_data(&mut ChrBus::default()), 0x02);
     }
 
     #[test]
@@ -851,13 +934,13 @@ mod tests {
         let mut ppu = Ppu::new(TvSystem::Ntsc);
         ppu.write_address(0x3F, false);
         ppu.write_address(0x00, false);
-        ppu.write_data(0x10);
-        ppu.write_data(0x20);
+        ppu.write_data(0x10, &mut ChrBus::default());
+        ppu.write_data(0x20, &mut ChrBus::default());
 
         ppu.write_address(0x3F, false);
         ppu.write_address(0x00, false);
-        assert_eq!(ppu.read_data(), 0x10);
-        assert_eq!(ppu.read_data(), 0x20);
+        assert_eq!(ppu.read_data(&mut ChrBus::default()), 0x10);
+        assert_eq!(ppu.read_data(&mut ChrBus::default()), 0x20);
     }
 
     #[test]
@@ -865,12 +948,12 @@ mod tests {
         let mut ppu = Ppu::new(TvSystem::Ntsc);
         ppu.write_address(0x20, false);
         ppu.write_address(0x00, false);
-        ppu.write_data(0x42);
+        ppu.write_data(0x42, &mut ChrBus::default());
 
         ppu.write_address(0x20, false);
         ppu.write_address(0x00, false);
-        let _ = ppu.read_data(); // Dummy read for buffer
-        assert_eq!(ppu.read_data(), 0x42);
+        let _ = ppu.read_data(&mut ChrBus::default()); // Dummy read for buffer
+        assert_eq!(ppu.read_data(&mut ChrBus::default()), 0x42);
     }
 
     // OAM tests
@@ -949,7 +1032,7 @@ mod tests {
         assert_eq!(ppu.oam_address(), 0x42);
 
         // Run to scanline 0, pixel 257 (start of sprite loading interval)
-        ppu.run_ppu_cycles(257);
+        ppu.run_ppu_cycles(257, &mut ChrBus::default());
 
         // OAMADDR should be cleared to 0 during pixels 257-320
         assert_eq!(
@@ -960,7 +1043,7 @@ mod tests {
 
         // Set it again to verify it keeps getting cleared during the interval
         ppu.write_oam_address(0x99);
-        ppu.run_ppu_cycles(1); // Still in the 257-320 interval
+        ppu.run_ppu_cycles(1, &mut ChrBus::default()); // Still in the 257-320 interval
         assert_eq!(
             ppu.oam_address(),
             0x00,
@@ -968,11 +1051,11 @@ mod tests {
         );
 
         // Run past pixel 320
-        ppu.run_ppu_cycles(64); // Now at pixel 257+1+64 = 322
+        ppu.run_ppu_cycles(64, &mut ChrBus::default()); // Now at pixel 257+1+64 = 322
 
         // Now OAMADDR should stay whatever we set it to
         ppu.write_oam_address(0x55);
-        ppu.run_ppu_cycles(1);
+        ppu.run_ppu_cycles(1, &mut ChrBus::default());
         assert_eq!(
             ppu.oam_address(),
             0x55,
@@ -989,10 +1072,10 @@ mod tests {
         ppu.write_mask(0x18);
 
         // Run to pre-render scanline (261), pixel 257
-        ppu.run_ppu_cycles(261 * 341 + 257);
+        ppu.run_ppu_cycles(261 * 341 + 257, &mut ChrBus::default());
 
         ppu.write_oam_address(0x42);
-        ppu.run_ppu_cycles(1); // Pixel 258, should clear OAMADDR
+        ppu.run_ppu_cycles(1, &mut ChrBus::default()); // Pixel 258, should clear OAMADDR
         assert_eq!(
             ppu.oam_address(),
             0x00,
@@ -1011,7 +1094,7 @@ mod tests {
         ppu.write_oam_address(0x42);
 
         // Run through the sprite loading interval
-        ppu.run_ppu_cycles(320);
+        ppu.run_ppu_cycles(320, &mut ChrBus::default());
 
         // OAMADDR should still be 0x42
         assert_eq!(
@@ -1031,7 +1114,7 @@ mod tests {
         ppu.write_mask(0x18);
 
         // Setup: Write distinct values to different parts of OAM during vblank
-        ppu.run_ppu_cycles(241 * 341 + 10); // In vblank
+        ppu.run_ppu_cycles(241 * 341 + 10, &mut ChrBus::default()); // In vblank
 
         // Write pattern to OAM[0..7]
         ppu.write_oam_address(0x00);
@@ -1050,7 +1133,7 @@ mod tests {
 
         // Run to pre-render scanline sprite tile loading (scanline 261, pixel 257)
         // At this point, OAM corruption should occur
-        ppu.run_ppu_cycles((261 - 241) * 341 + 257 - 10);
+        ppu.run_ppu_cycles((261 - 241) * 341 + 257 - 10, &mut ChrBus::default());
 
         // Check that OAM[0..7] has been corrupted with data from OAM[0x10..0x17]
         // OAMADDR was 0x10, so 0x10 & 0xF8 = 0x10, meaning OAM[0x10..0x17] -> OAM[0..7]
@@ -1081,7 +1164,7 @@ mod tests {
         ppu.write_mask(0x18);
 
         // Setup OAM during vblank
-        ppu.run_ppu_cycles(241 * 341 + 10);
+        ppu.run_ppu_cycles(241 * 341 + 10, &mut ChrBus::default());
 
         ppu.write_oam_address(0x00);
         for i in 0..8 {
@@ -1092,7 +1175,7 @@ mod tests {
         ppu.write_oam_address(0x05);
 
         // Run to pre-render sprite tile loading
-        ppu.run_ppu_cycles((261 - 241) * 341 + 257 - 10);
+        ppu.run_ppu_cycles((261 - 241) * 341 + 257 - 10, &mut ChrBus::default());
 
         // OAM[0..7] should be unchanged
         ppu.write_oam_address(0x00);
@@ -1121,12 +1204,12 @@ mod tests {
         ppu.write_mask(0x18);
 
         // Write initial value to OAM during vblank (should work)
-        ppu.run_ppu_cycles(241 * 341 + 10); // In vblank
+        ppu.run_ppu_cycles(241 * 341 + 10, &mut ChrBus::default()); // In vblank
         ppu.write_oam_address(0x05);
         ppu.write_oam_data(0x42);
 
         // Run to visible scanline, avoiding the OAMADDR clearing period (257-320)
-        ppu.run_ppu_cycles((262 - 241) * 341 + 100); // Scanline 0, pixel 100
+        ppu.run_ppu_cycles((262 - 241) * 341 + 100, &mut ChrBus::default()); // Scanline 0, pixel 100
 
         // Try to write during rendering (should be ignored)
         ppu.write_oam_address(0x05);
@@ -1151,7 +1234,7 @@ mod tests {
         ppu.write_mask(0x18);
 
         // Run to visible scanline
-        ppu.run_ppu_cycles(100); // Scanline 0, pixel 100
+        ppu.run_ppu_cycles(100, &mut ChrBus::default()); // Scanline 0, pixel 100
 
         // Set OAMADDR to 0x10 and write (write ignored, but address incremented by 4)
         ppu.write_oam_address(0x10);
@@ -1184,7 +1267,7 @@ mod tests {
         ppu.write_mask(0x18);
 
         // Run to vblank
-        ppu.run_ppu_cycles(241 * 341 + 10);
+        ppu.run_ppu_cycles(241 * 341 + 10, &mut ChrBus::default());
 
         // Write during vblank (should work)
         ppu.write_oam_address(0x00);
@@ -1242,7 +1325,7 @@ mod tests {
     #[test]
     fn test_scanline_increments() {
         let mut ppu = Ppu::new(TvSystem::Ntsc);
-        ppu.run_ppu_cycles(341); // One full scanline
+        ppu.run_ppu_cycles(341, &mut ChrBus::default()); // One full scanline
         assert_eq!(ppu.scanline(), 1);
         assert_eq!(ppu.pixel(), 0);
     }
@@ -1250,7 +1333,7 @@ mod tests {
     #[test]
     fn test_frame_wraps_at_262_scanlines() {
         let mut ppu = Ppu::new(TvSystem::Ntsc);
-        ppu.run_ppu_cycles(262 * 341); // One full frame
+        ppu.run_ppu_cycles(262 * 341, &mut ChrBus::default()); // One full frame
         assert_eq!(ppu.scanline(), 0);
         assert_eq!(ppu.pixel(), 0);
     }
@@ -1259,7 +1342,7 @@ mod tests {
     #[test]
     fn test_status_read_clears_vblank() {
         let mut ppu = Ppu::new(TvSystem::Ntsc);
-        ppu.run_ppu_cycles(241 * 341 + 2); // Past vblank start
+        ppu.run_ppu_cycles(241 * 341 + 2, &mut ChrBus::default()); // Past vblank start
 
         let status1 = ppu.get_status();
         assert_eq!(status1 & 0x80, 0x80); // VBlank set
@@ -1290,13 +1373,13 @@ mod tests {
         // Write to nametable 0
         ppu.write_address(0x20, false);
         ppu.write_address(0x00, false);
-        ppu.write_data(0x42);
+        ppu.write_data(0x42, &mut ChrBus::default());
 
         // Read from nametable 2 (should mirror to 0)
         ppu.write_address(0x28, false);
         ppu.write_address(0x00, false);
-        let _ = ppu.read_data(); // Dummy read
-        assert_eq!(ppu.read_data(), 0x42);
+        let _ = ppu.read_data(&mut ChrBus::default()); // Dummy read
+        assert_eq!(ppu.read_data(&mut ChrBus::default()), 0x42);
     }
 
     #[test]
@@ -1307,13 +1390,13 @@ mod tests {
         // Write to nametable 0
         ppu.write_address(0x20, false);
         ppu.write_address(0x00, false);
-        ppu.write_data(0x55);
+        ppu.write_data(0x55, &mut ChrBus::default());
 
         // Read from nametable 1 (should mirror to 0 in horizontal)
         ppu.write_address(0x24, false);
         ppu.write_address(0x00, false);
-        let _ = ppu.read_data(); // Dummy read
-        let val = ppu.read_data();
+        let _ = ppu.read_data(&mut ChrBus::default()); // Dummy read
+        let val = ppu.read_data(&mut ChrBus::default());
         assert_eq!(val, 0x55); // Should be mirrored
     }
 
@@ -1322,7 +1405,7 @@ mod tests {
     fn test_nmi_polling() {
         let mut ppu = Ppu::new(TvSystem::Ntsc);
         ppu.write_control(0x80); // Enable NMI
-        ppu.run_ppu_cycles(241 * 341 + 1); // Enter VBlank
+        ppu.run_ppu_cycles(241 * 341 + 1, &mut ChrBus::default()); // Enter VBlank
 
         assert!(ppu.poll_nmi()); // Should return true once
         assert!(!ppu.poll_nmi()); // Should be cleared after polling
@@ -1331,7 +1414,7 @@ mod tests {
     #[test]
     fn test_frame_complete_polling() {
         let mut ppu = Ppu::new(TvSystem::Ntsc);
-        ppu.run_ppu_cycles(241 * 341 + 1); // Enter VBlank
+        ppu.run_ppu_cycles(241 * 341 + 1, &mut ChrBus::default()); // Enter VBlank
 
         assert!(ppu.poll_frame_complete()); // Should return true once
         assert!(!ppu.poll_frame_complete()); // Should be cleared after polling
@@ -1344,7 +1427,7 @@ mod tests {
         ppu.write_mask(0x18); // Enable background and sprite rendering
 
         // Run through a full scanline which includes pixel 0
-        ppu.run_ppu_cycles(341);
+        ppu.run_ppu_cycles(341, &mut ChrBus::default());
 
         // Should not panic - pixel 0 is handled correctly
         assert_eq!(ppu.scanline(), 1);
@@ -1359,7 +1442,7 @@ mod tests {
 
         // Run through multiple scanlines to test pixel 0 transitions
         for _ in 0..5 {
-            ppu.run_ppu_cycles(341);
+            ppu.run_ppu_cycles(341, &mut ChrBus::default());
         }
 
         // Should complete without panicking
@@ -1373,16 +1456,16 @@ mod tests {
         // Write to palette using full address
         ppu.write_address(0x3F, false);
         ppu.write_address(0x00, false);
-        ppu.write_data(0x30); // Write to backdrop color
+        ppu.write_data(0x30, &mut ChrBus::default()); // Write to backdrop color
 
         // Write to another palette entry
         ppu.write_address(0x3F, false);
         ppu.write_address(0x01, false);
-        ppu.write_data(0x16);
+        ppu.write_data(0x16, &mut ChrBus::default());
 
         // Enable rendering and run one scanline
         ppu.write_mask(0x18);
-        ppu.run_ppu_cycles(341);
+        ppu.run_ppu_cycles(341, &mut ChrBus::default());
 
         // Should complete without panic - palette lookups work correctly
         assert_eq!(ppu.scanline(), 1);
@@ -1399,20 +1482,20 @@ mod tests {
         ppu.write_scroll(0, false);
 
         // Run to pixel 8 of scanline 0 (first shift register load)
-        ppu.run_ppu_cycles(8);
+        ppu.run_ppu_cycles(8, &mut ChrBus::default());
         assert_eq!(ppu.scanline(), 0);
         assert_eq!(ppu.pixel(), 8);
 
         // Run to pixel 16 (second shift register load)
-        ppu.run_ppu_cycles(8);
+        ppu.run_ppu_cycles(8, &mut ChrBus::default());
         assert_eq!(ppu.pixel(), 16);
 
         // Run to pixel 24 (third shift register load)
-        ppu.run_ppu_cycles(8);
+        ppu.run_ppu_cycles(8, &mut ChrBus::default());
         assert_eq!(ppu.pixel(), 24);
 
         // Verify we can continue through the scanline without issues
-        ppu.run_ppu_cycles(256 - 24);
+        ppu.run_ppu_cycles(256 - 24, &mut ChrBus::default());
         assert_eq!(ppu.pixel(), 256);
     }
 
@@ -1430,11 +1513,11 @@ mod tests {
         let _v_before_256 = ppu.v_register();
 
         // Run to pixel 256 (increment_fine_y happens here)
-        ppu.run_ppu_cycles(256);
+        ppu.run_ppu_cycles(256, &mut ChrBus::default());
         assert_eq!(ppu.pixel(), 256);
 
         // Run to pixel 257 (copy_horizontal_bits happens here)
-        ppu.run_ppu_cycles(1);
+        ppu.run_ppu_cycles(1, &mut ChrBus::default());
         assert_eq!(ppu.pixel(), 257);
 
         // V register should have been updated
@@ -1444,7 +1527,7 @@ mod tests {
         // unless at a boundary condition)
 
         // Just verify we can continue without panic
-        ppu.run_ppu_cycles(341 - 257);
+        ppu.run_ppu_cycles(341 - 257, &mut ChrBus::default());
         assert_eq!(ppu.scanline(), 1);
     }
 
@@ -1455,23 +1538,23 @@ mod tests {
         ppu.write_mask(0x18);
 
         // Run to pre-render scanline (261)
-        ppu.run_ppu_cycles(261 * 341);
+        ppu.run_ppu_cycles(261 * 341, &mut ChrBus::default());
         assert_eq!(ppu.scanline(), 261);
 
         // Run to pixel 321 (start of pre-fetch)
-        ppu.run_ppu_cycles(321);
+        ppu.run_ppu_cycles(321, &mut ChrBus::default());
         assert_eq!(ppu.pixel(), 321);
 
         // Run to pixel 328 (first pre-fetch load)
-        ppu.run_ppu_cycles(7);
+        ppu.run_ppu_cycles(7, &mut ChrBus::default());
         assert_eq!(ppu.pixel(), 328);
 
         // Run to pixel 336 (second pre-fetch load)
-        ppu.run_ppu_cycles(8);
+        ppu.run_ppu_cycles(8, &mut ChrBus::default());
         assert_eq!(ppu.pixel(), 336);
 
         // Complete the scanline
-        ppu.run_ppu_cycles(341 - 336);
+        ppu.run_ppu_cycles(341 - 336, &mut ChrBus::default());
         assert_eq!(ppu.scanline(), 0); // Should wrap to scanline 0
     }
 
@@ -1483,16 +1566,16 @@ mod tests {
 
         // Run through visible pixels (1-256)
         for pixel in 1..=256 {
-            ppu.run_ppu_cycles(1);
+            ppu.run_ppu_cycles(1, &mut ChrBus::default());
             assert_eq!(ppu.pixel(), pixel);
         }
 
         // Continue through pre-fetch region (321-336)
-        ppu.run_ppu_cycles(321 - 256);
+        ppu.run_ppu_cycles(321 - 256, &mut ChrBus::default());
         assert_eq!(ppu.pixel(), 321);
 
         for pixel in 322..=336 {
-            ppu.run_ppu_cycles(1);
+            ppu.run_ppu_cycles(1, &mut ChrBus::default());
             assert_eq!(ppu.pixel(), pixel);
         }
     }
@@ -1504,15 +1587,15 @@ mod tests {
         ppu.write_mask(0x18);
 
         // Run to pixel 337
-        ppu.run_ppu_cycles(337);
+        ppu.run_ppu_cycles(337, &mut ChrBus::default());
         assert_eq!(ppu.pixel(), 337);
 
         // Run to pixel 339
-        ppu.run_ppu_cycles(2);
+        ppu.run_ppu_cycles(2, &mut ChrBus::default());
         assert_eq!(ppu.pixel(), 339);
 
         // Complete the scanline without panic
-        ppu.run_ppu_cycles(341 - 339);
+        ppu.run_ppu_cycles(341 - 339, &mut ChrBus::default());
         assert_eq!(ppu.scanline(), 1);
     }
 
@@ -1530,7 +1613,7 @@ mod tests {
 
         // Run to pixel 9 (first coarse X increment)
         // Per NES Dev wiki, shifters are reloaded at ticks 9, 17, 25, ..., 257
-        ppu.run_ppu_cycles(9);
+        ppu.run_ppu_cycles(9, &mut ChrBus::default());
         let v_after_9 = ppu.v_register();
 
         // Coarse X should have incremented (bits 0-4 of v register)
@@ -1539,7 +1622,7 @@ mod tests {
         assert_eq!(coarse_x_after_9, (coarse_x_initial + 1) & 0x001F);
 
         // Run to pixel 17 (second coarse X increment)
-        ppu.run_ppu_cycles(8);
+        ppu.run_ppu_cycles(8, &mut ChrBus::default());
         let v_after_17 = ppu.v_register();
         let coarse_x_after_17 = v_after_17 & 0x001F;
         assert_eq!(coarse_x_after_17, (coarse_x_initial + 2) & 0x001F);
@@ -1623,46 +1706,49 @@ mod tests {
         ines_data.extend_from_slice(&chr_rom);
 
         // Create cartridge from iNES data
-        let cartridge = Cartridge::new(&ines_data).expect("Failed to create cartridge");
-        ppu.set_cartridge(Rc::new(RefCell::new(cartridge)));
+        let mut cartridge = Cartridge::new(&ines_data).expect("Failed to create cartridge");
+        let mut chr = ChrBus {
+            cartridge: Some(&mut cartridge),
+            cdl: None,
+        };
 
         // Set up palette - use distinct colors for each palette entry
         // Palette 0 will be: backdrop (black), red, green, blue
         ppu.write_address(0x3F, false);
         ppu.write_address(0x00, false);
-        ppu.write_data(0x0F); // Universal backdrop (black)
+        ppu.write_data(0x0F, &mut chr); // Universal backdrop (black)
         ppu.write_address(0x3F, false);
         ppu.write_address(0x01, false);
-        ppu.write_data(0x16); // Palette 0, color 1 (red)
+        ppu.write_data(0x16, &mut chr); // Palette 0, color 1 (red)
         ppu.write_address(0x3F, false);
         ppu.write_address(0x02, false);
-        ppu.write_data(0x2A); // Palette 0, color 2 (green)
+        ppu.write_data(0x2A, &mut chr); // Palette 0, color 2 (green)
         ppu.write_address(0x3F, false);
         ppu.write_address(0x03, false);
-        ppu.write_data(0x12); // Palette 0, color 3 (blue)
+        ppu.write_data(0x12, &mut chr); // Palette 0, color 3 (blue)
 
         // Set up nametable - create a known pattern
         // Place tile 1 (solid, pattern 3) at position (0,0) - top-left corner
         ppu.write_address(0x20, false);
         ppu.write_address(0x00, false);
-        ppu.write_data(1); // Tile 1 at (0,0)
+        ppu.write_data(1, &mut chr); // Tile 1 at (0,0)
 
         // Place tile 2 (pattern 1) at position (1,0) - second tile in first row
-        ppu.write_data(2); // Tile 2 at (1,0)
+        ppu.write_data(2, &mut chr); // Tile 2 at (1,0)
 
         // Place tile 3 (pattern 2) at position (2,0) - third tile in first row
-        ppu.write_data(3); // Tile 3 at (2,0)
+        ppu.write_data(3, &mut chr); // Tile 3 at (2,0)
 
         // Fill rest of first row with tile 0 (empty/transparent)
         for _ in 3..32 {
-            ppu.write_data(0); // Empty tiles
+            ppu.write_data(0, &mut chr); // Empty tiles
         }
 
         // Set up attribute table - palette 0 for all tiles
         ppu.write_address(0x23, false);
         ppu.write_address(0xC0, false);
         for _ in 0..64 {
-            ppu.write_data(0x00); // Palette 0 for all
+            ppu.write_data(0x00, &mut chr); // Palette 0 for all
         }
 
         // Set scroll position to 0,0
@@ -1689,7 +1775,7 @@ mod tests {
         // First frame: renders with empty shift registers (will show offset)
         // Second frame: pre-render scanline 261 of first frame loads shift registers,
         //               so second frame renders correctly with tiles at positions 0-7, 8-15, etc.
-        ppu.run_ppu_cycles(2 * 262 * 341);
+        ppu.run_ppu_cycles(2 * 262 * 341, &mut chr);
 
         // println!("After rendering:");
         // println!("Scanline: {}, Pixel: {}", ppu.scanline(), ppu.pixel());
@@ -1699,7 +1785,7 @@ mod tests {
         // println!("Palette check after rendering:");
         ppu.write_address(0x3F, false);
         ppu.write_address(0x03, false);
-        let _pal3 = ppu.read_data();
+        let _pal3 = ppu.read_data(&mut chr);
         // println!("Palette $3F03 (should be 0x12): {:02X}", pal3);
 
         // Now check the screen buffer for expected colors
@@ -1812,23 +1898,26 @@ mod tests {
         ines_data.extend_from_slice(&chr_rom);
 
         // Create cartridge from iNES data
-        let cartridge = Cartridge::new(&ines_data).expect("Failed to create cartridge");
-        ppu.set_cartridge(Rc::new(RefCell::new(cartridge)));
+        let mut cartridge = Cartridge::new(&ines_data).expect("Failed to create cartridge");
+        let mut chr = ChrBus {
+            cartridge: Some(&mut cartridge),
+            cdl: None,
+        };
 
         // Set up sprite palette - use distinct colors
         // Palette 0 will be: backdrop (black), yellow, cyan, magenta
         ppu.write_address(0x3F, false);
         ppu.write_address(0x00, false);
-        ppu.write_data(0x0F); // Universal backdrop (black)
+        ppu.write_data(0x0F, &mut chr); // Universal backdrop (black)
         ppu.write_address(0x3F, false);
         ppu.write_address(0x11, false);
-        ppu.write_data(0x28); // Sprite palette 0, color 1 (yellow)
+        ppu.write_data(0x28, &mut chr); // Sprite palette 0, color 1 (yellow)
         ppu.write_address(0x3F, false);
         ppu.write_address(0x12, false);
-        ppu.write_data(0x2C); // Sprite palette 0, color 2 (cyan)
+        ppu.write_data(0x2C, &mut chr); // Sprite palette 0, color 2 (cyan)
         ppu.write_address(0x3F, false);
         ppu.write_address(0x13, false);
-        ppu.write_data(0x14); // Sprite palette 0, color 3 (magenta)
+        ppu.write_data(0x14, &mut chr); // Sprite palette 0, color 3 (magenta)
 
         // Set up sprites in OAM
         // Sprite 0: tile 1 (pattern 3 = magenta) at position (16, 16)
@@ -1867,7 +1956,7 @@ mod tests {
         ppu.write_mask(0b0001_0100); // Enable sprite rendering, no clipping
 
         // Run PPU to render two complete frames
-        ppu.run_ppu_cycles(2 * 262 * 341);
+        ppu.run_ppu_cycles(2 * 262 * 341, &mut chr);
 
         let screen_buffer = ppu.screen_buffer();
 
@@ -1,3 +1,5 @@
+use crate::savestate::{SaveState, StateReader, StateWriter};
+
 /// PPU Control Register ($2000) bit constants
 const GENERATE_NMI: u8 = 0b1000_0000;
 const SPRITE_SIZE: u8 = 0b0010_0000;
@@ -378,6 +380,49 @@ impl Registers {
     }
 }
 
+impl SaveState for Registers {
+    fn save_state(&self, state: &mut StateWriter) {
+        for value in [
+            self.control_register,
+            self.mask_register,
+            self.oam_address,
+            self.data_buffer,
+            self.io_bus,
+            self.x,
+        ] {
+            state.write_u8(value);
+        }
+        for time in self.io_bus_refresh_time {
+            state.write_u64(time);
+        }
+        state.write_u64(self.cycle_count);
+        state.write_u16(self.v);
+        state.write_u16(self.t);
+        state.write_bool(self.w);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        for value in [
+            &mut self.control_register,
+            &mut self.mask_register,
+            &mut self.oam_address,
+            &mut self.data_buffer,
+            &mut self.io_bus,
+            &mut self.x,
+        ] {
+            *value = state.read_u8()?;
+        }
+        for time in &mut self.io_bus_refresh_time {
+            *time = state.read_u64()?;
+        }
+        self.cycle_count = state.read_u64()?;
+        self.v = state.read_u16()?;
+        self.t = state.read_u16()?;
+        self.w = state.read_bool()?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,3 +1,5 @@
+use crate::savestate::{SaveState, StateReader, StateWriter};
+
 /// Manages sprite evaluation, OAM, and sprite rendering
 pub struct Sprites {
     /// OAM (Object Attribute Memory) - 256 bytes for sprite data
@@ -24,24 +26,34 @@ pub struct Sprites {
     sprite_eval_cycle: u8,
     /// Whether current sprite being evaluated is in range
     sprite_eval_in_range: bool,
-    /// Sprite pattern shift registers - low bit plane (8 sprites) - CURRENT scanline
-    sprite_pattern_shift_lo: [u8; 8],
-    /// Sprite pattern shift registers - high bit plane (8 sprites) - CURRENT scanline
-    sprite_pattern_shift_hi: [u8; 8],
+    /// Sprite pattern shift registers - low bit plane (up to 64 sprites) - CURRENT scanline
+    sprite_pattern_shift_lo: [u8; MAX_SPRITES],
+    /// Sprite pattern shift registers - high bit plane (up to 64 sprites) - CURRENT scanline
+    sprite_pattern_shift_hi: [u8; MAX_SPRITES],
     /// Sprite X position counters - CURRENT scanline
-    sprite_x_positions: [u8; 8],
+    sprite_x_positions: [u8; MAX_SPRITES],
     /// Sprite attributes (palette, priority, flip bits) - CURRENT scanline
-    sprite_attributes: [u8; 8],
-    /// Sprite pattern shift registers - low bit plane (8 sprites) - NEXT scanline
-    next_sprite_pattern_shift_lo: [u8; 8],
-    /// Sprite pattern shift registers - high bit plane (8 sprites) - NEXT scanline
-    next_sprite_pattern_shift_hi: [u8; 8],
+    sprite_attributes: [u8; MAX_SPRITES],
+    /// Sprite pattern shift registers - low bit plane (up to 64 sprites) - NEXT scanline
+    next_sprite_pattern_shift_lo: [u8; MAX_SPRITES],
+    /// Sprite pattern shift registers - high bit plane (up to 64 sprites) - NEXT scanline
+    next_sprite_pattern_shift_hi: [u8; MAX_SPRITES],
     /// Sprite X position counters - NEXT scanline
-    next_sprite_x_positions: [u8; 8],
+    next_sprite_x_positions: [u8; MAX_SPRITES],
     /// Sprite attributes - NEXT scanline
-    next_sprite_attributes: [u8; 8],
+    next_sprite_attributes: [u8; MAX_SPRITES],
+    /// Whether only 8 sprites per scanline are drawn, as on hardware
+    sprite_limit: bool,
+    /// In-range sprites beyond the first 8 (OAM bytes), drawn when the limit is off
+    extra_sprites: Vec<[u8; 4]>,
 }
 
+/// Number of sprites the hardware draws per scanline
+const HARDWARE_SPRITE_LIMIT: usize = 8;
+
+/// Number of sprites in OAM, the most that can share a scanline
+const MAX_SPRITES: usize = 64;
+
 /// OAM attribute byte mask - bits 2-4 are unimplemented and always read as 0
 /// Mask: 11100011 (0xE3) - preserves bits 7-5 (priority/palette) and 1-0 (flip bits)
 const OAM_ATTRIBUTE_MASK: u8 = 0xE3;
@@ -62,17 +74,28 @@ impl Sprites {
             sprite_eval_m: 0,
             sprite_eval_cycle: 0,
             sprite_eval_in_range: false,
-            sprite_pattern_shift_lo: [0; 8],
-            sprite_pattern_shift_hi: [0; 8],
-            sprite_x_positions: [0; 8],
-            sprite_attributes: [0; 8],
-            next_sprite_pattern_shift_lo: [0; 8],
-            next_sprite_pattern_shift_hi: [0; 8],
-            next_sprite_x_positions: [0; 8],
-            next_sprite_attributes: [0; 8],
+            sprite_pattern_shift_lo: [0; MAX_SPRITES],
+            sprite_pattern_shift_hi: [0; MAX_SPRITES],
+            sprite_x_positions: [0; MAX_SPRITES],
+            sprite_attributes: [0; MAX_SPRITES],
+            next_sprite_pattern_shift_lo: [0; MAX_SPRITES],
+            next_sprite_pattern_shift_hi: [0; MAX_SPRITES],
+            next_sprite_x_positions: [0; MAX_SPRITES],
+            next_sprite_attributes: [0; MAX_SPRITES],
+            sprite_limit: true,
+            extra_sprites: Vec::with_capacity(MAX_SPRITES - HARDWARE_SPRITE_LIMIT),
         }
     }
 
+    /// Enable or disable the 8-sprites-per-scanline limit
+    ///
+    /// With the limit disabled, sprites beyond the eighth are drawn as well, which
+    /// removes flicker in many games. Sprite evaluation, the overflow flag and
+    /// sprite 0 hit still behave as on hardware.
+    pub fn set_sprite_limit(&mut self, enabled: bool) {
+        self.sprite_limit = enabled;
+    }
+
     /// Reset sprite state
     pub fn reset(&mut self) {
         self.oam_data = [0xFF; 256];
@@ -269,9 +292,9 @@ impl Sprites {
         scanline: u16,
         sprite_height: u8,
         sprite_pattern_table_base: u16,
-        read_chr: F,
+        mut read_chr: F,
     ) where
-        F: Fn(u16) -> u8,
+        F: FnMut(u16) -> u8,
     {
         let cycle_offset = pixel - 257;
         let sprite_index = (cycle_offset / 8) as usize;
@@ -279,61 +302,101 @@ impl Sprites {
 
         if fetch_step == 7 && sprite_index < self.sprites_found as usize {
             let sec_oam_offset = sprite_index * 4;
-            let sprite_y = self.secondary_oam[sec_oam_offset];
-            let tile_index = self.secondary_oam[sec_oam_offset + 1];
-            let attributes = self.secondary_oam[sec_oam_offset + 2];
-
-            let next_scanline = if scanline == 261 { 0 } else { scanline + 1 };
-            // Adjust Y position: add 1 to sprite_y to move sprites 2 pixels down
-            let sprite_row = next_scanline.wrapping_sub((sprite_y.wrapping_add(1)) as u16) as u8;
-
-            // Calculate pattern address
-            let pattern_table_base = if sprite_height == 8 {
-                // Use pattern table base from PPUCTRL (provided by caller)
-                sprite_pattern_table_base
-            } else {
-                // 8x16 sprites: use bit 0 of tile index
-                ((tile_index & 0x01) as u16) << 12
-            };
-
-            let tile_offset = if sprite_height == 8 {
-                (tile_index as u16) << 4
-            } else {
-                ((tile_index & 0xFE) as u16) << 4
-            };
+            let mut sprite = [0u8; 4];
+            sprite.copy_from_slice(&self.secondary_oam[sec_oam_offset..sec_oam_offset + 4]);
+            self.load_sprite_pattern(
+                sprite_index,
+                sprite,
+                scanline,
+                sprite_height,
+                sprite_pattern_table_base,
+                &mut read_chr,
+            );
 
-            let effective_row = if (attributes & 0x80) != 0 {
-                if sprite_height == 8 {
-                    7 - sprite_row
-                } else {
-                    15 - sprite_row
+            // Sprites beyond the hardware limit have no fetch slots of their own,
+            // so load them all together with the last one
+            if sprite_index == HARDWARE_SPRITE_LIMIT - 1 {
+                for extra_index in 0..self.extra_sprites.len() {
+                    let sprite = self.extra_sprites[extra_index];
+                    self.load_sprite_pattern(
+                        HARDWARE_SPRITE_LIMIT + extra_index,
+                        sprite,
+                        scanline,
+                        sprite_height,
+                        sprite_pattern_table_base,
+                        &mut read_chr,
+                    );
                 }
-            } else {
-                sprite_row
-            };
+            }
+        }
+    }
 
-            let tile_row = if sprite_height == 16 && effective_row >= 8 {
-                effective_row - 8 + 16
-            } else {
-                effective_row
-            };
+    /// Load pattern data for one sprite into the NEXT scanline buffers
+    ///
+    /// `sprite` holds the sprite's four OAM bytes (Y, tile, attributes, X).
+    fn load_sprite_pattern<F>(
+        &mut self,
+        slot: usize,
+        sprite: [u8; 4],
+        scanline: u16,
+        sprite_height: u8,
+        sprite_pattern_table_base: u16,
+        read_chr: &mut F,
+    ) where
+        F: FnMut(u16) -> u8,
+    {
+        let [sprite_y, tile_index, attributes, sprite_x] = sprite;
 
-            let addr = pattern_table_base | tile_offset | (tile_row as u16);
+        let next_scanline = if scanline == 261 { 0 } else { scanline + 1 };
+        // Adjust Y position: add 1 to sprite_y to move sprites 2 pixels down
+        let sprite_row = next_scanline.wrapping_sub((sprite_y.wrapping_add(1)) as u16) as u8;
 
-            let pattern_lo = read_chr(addr);
-            let pattern_hi = read_chr(addr + 8);
+        // Calculate pattern address
+        let pattern_table_base = if sprite_height == 8 {
+            // Use pattern table base from PPUCTRL (provided by caller)
+            sprite_pattern_table_base
+        } else {
+            // 8x16 sprites: use bit 0 of tile index
+            ((tile_index & 0x01) as u16) << 12
+        };
+
+        let tile_offset = if sprite_height == 8 {
+            (tile_index as u16) << 4
+        } else {
+            ((tile_index & 0xFE) as u16) << 4
+        };
 
-            let (final_lo, final_hi) = if (attributes & 0x40) != 0 {
-                (pattern_lo.reverse_bits(), pattern_hi.reverse_bits())
+        let effective_row = if (attributes & 0x80) != 0 {
+            if sprite_height == 8 {
+                7 - sprite_row
             } else {
-                (pattern_lo, pattern_hi)
-            };
+                15 - sprite_row
+            }
+        } else {
+            sprite_row
+        };
 
-            self.next_sprite_pattern_shift_lo[sprite_index] = final_lo;
-            self.next_sprite_pattern_shift_hi[sprite_index] = final_hi;
-            self.next_sprite_attributes[sprite_index] = attributes;
-            self.next_sprite_x_positions[sprite_index] = self.secondary_oam[sec_oam_offset + 3];
-        }
+        let tile_row = if sprite_height == 16 && effective_row >= 8 {
+            effective_row - 8 + 16
+        } else {
+            effective_row
+        };
+
+        let addr = pattern_table_base | tile_offset | (tile_row as u16);
+
+        let pattern_lo = read_chr(addr);
+        let pattern_hi = read_chr(addr + 8);
+
+        let (final_lo, final_hi) = if (attributes & 0x40) != 0 {
+            (pattern_lo.reverse_bits(), pattern_hi.reverse_bits())
+        } else {
+            (pattern_lo, pattern_hi)
+        };
+
+        self.next_sprite_pattern_shift_lo[slot] = final_lo;
+        self.next_sprite_pattern_shift_hi[slot] = final_hi;
+        self.next_sprite_attributes[slot] = attributes;
+        self.next_sprite_x_positions[slot] = sprite_x;
     }
 
     /// Swap sprite buffers for next scanline
@@ -371,8 +434,24 @@ impl Sprites {
     }
 
     /// Finalize sprite count for next scanline
-    pub fn finalize_evaluation(&mut self) {
-        self.next_sprite_count = self.sprites_found;
+    ///
+    /// When the sprite limit is disabled and evaluation filled all 8 slots, the
+    /// remaining in-range sprites are collected so they can be drawn too.
+    pub fn finalize_evaluation(&mut self, scanline: u16, sprite_height: u8) {
+        self.extra_sprites.clear();
+        if !self.sprite_limit && self.sprites_found as usize == HARDWARE_SPRITE_LIMIT {
+            let next_scanline = scanline + 1;
+            let in_range = self.oam_data.chunks_exact(4).filter(|sprite| {
+                let sprite_y = sprite[0];
+                let diff = next_scanline.wrapping_sub(sprite_y.wrapping_add(1) as u16);
+                sprite_y < 0xF0 && diff < sprite_height as u16
+            });
+            for sprite in in_range.skip(HARDWARE_SPRITE_LIMIT) {
+                self.extra_sprites
+                    .push([sprite[0], sprite[1], sprite[2], sprite[3]]);
+            }
+        }
+        self.next_sprite_count = self.sprites_found + self.extra_sprites.len() as u8;
     }
 
     /// Mark buffers as ready
@@ -469,6 +548,78 @@ impl Sprites {
     }
 }
 
+/// The sprite limit is a setting, not part of the state
+impl SaveState for Sprites {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.oam_data);
+        state.write_bytes(&self.secondary_oam);
+        for value in [
+            self.sprites_found,
+            self.sprite_count,
+            self.next_sprite_count,
+            self.sprite_eval_n,
+            self.sprite_eval_m,
+            self.sprite_eval_cycle,
+        ] {
+            state.write_u8(value);
+        }
+        state.write_bool(self.sprite_buffers_ready);
+        state.write_bool(self.sprite_eval_in_range);
+        state.write_option_u8(self.sprite_0_index.map(|index| index as u8));
+        state.write_option_u8(self.next_sprite_0_index.map(|index| index as u8));
+        for buffer in [
+            &self.sprite_pattern_shift_lo,
+            &self.sprite_pattern_shift_hi,
+            &self.sprite_x_positions,
+            &self.sprite_attributes,
+            &self.next_sprite_pattern_shift_lo,
+            &self.next_sprite_pattern_shift_hi,
+            &self.next_sprite_x_positions,
+            &self.next_sprite_attributes,
+        ] {
+            state.write_bytes(buffer);
+        }
+        state.write_bytes(self.extra_sprites.as_flattened());
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.read_bytes_into(&mut self.oam_data)?;
+        state.read_bytes_into(&mut self.secondary_oam)?;
+        for value in [
+            &mut self.sprites_found,
+            &mut self.sprite_count,
+            &mut self.next_sprite_count,
+            &mut self.sprite_eval_n,
+            &mut self.sprite_eval_m,
+            &mut self.sprite_eval_cycle,
+        ] {
+            *value = state.read_u8()?;
+        }
+        self.sprite_buffers_ready = state.read_bool()?;
+        self.sprite_eval_in_range = state.read_bool()?;
+        self.sprite_0_index = state.read_option_u8()?.map(usize::from);
+        self.next_sprite_0_index = state.read_option_u8()?.map(usize::from);
+        for buffer in [
+            &mut self.sprite_pattern_shift_lo,
+            &mut self.sprite_pattern_shift_hi,
+            &mut self.sprite_x_positions,
+            &mut self.sprite_attributes,
+            &mut self.next_sprite_pattern_shift_lo,
+            &mut self.next_sprite_pattern_shift_hi,
+            &mut self.next_sprite_x_positions,
+            &mut self.next_sprite_attributes,
+        ] {
+            state.read_bytes_into(buffer)?;
+        }
+        self.extra_sprites = state
+            .read_bytes()?
+            .chunks_exact(4)
+            .map(|sprite| sprite.try_into().expect("chunk has 4 bytes"))
+            .collect();
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -604,6 +755,55 @@ mod tests {
         assert_eq!(sprites.next_sprite_x_positions[0], 100);
     }
 
+    /// Place `count` 8x8 sprites on scanline 20 with X positions 0, 10, 20, ...
+    fn sprites_on_one_line(count: usize) -> Sprites {
+        let mut sprites = Sprites::new();
+        for i in 0..count {
+            sprites.oam_data[i * 4] = 19; // Y (visible from scanline 20)
+            sprites.oam_data[i * 4 + 1] = 0; // Tile
+            sprites.oam_data[i * 4 + 2] = 0; // Attributes
+            sprites.oam_data[i * 4 + 3] = (i * 10) as u8; // X
+        }
+        sprites
+    }
+
+    /// Run evaluation, pattern fetch and buffer swap for scanline 20
+    fn prepare_scanline_20(sprites: &mut Sprites) {
+        sprites.reset_evaluation();
+        for pixel in 65..=256 {
+            sprites.evaluate_sprites(pixel, 19, 8);
+        }
+        sprites.finalize_evaluation(19, 8);
+        for pixel in 257..=320 {
+            sprites.fetch_sprite_pattern(pixel, 19, 8, 0x0000, |_| 0xFF);
+        }
+        sprites.mark_buffers_ready();
+        sprites.swap_buffers();
+    }
+
+    #[test]
+    fn test_sprite_limit_hides_ninth_sprite() {
+        let mut sprites = sprites_on_one_line(10);
+        prepare_scanline_20(&mut sprites);
+
+        assert_eq!(sprites.sprite_count(), 8);
+        assert!(sprites.get_pixel(70, true).is_some()); // Sprite 7
+        assert!(sprites.get_pixel(80, true).is_none()); // Sprite 8
+    }
+
+    #[test]
+    fn test_disabled_sprite_limit_draws_all_sprites() {
+        let mut sprites = sprites_on_one_line(10);
+        sprites.set_sprite_limit(false);
+        prepare_scanline_20(&mut sprites);
+
+        assert_eq!(sprites.sprite_count(), 10);
+        assert!(sprites.get_pixel(80, true).is_some()); // Sprite 8
+        assert!(sprites.get_pixel(90, true).is_some()); // Sprite 9
+        // Sprite 0 is still tracked in the hardware slots
+        assert_eq!(sprites.sprite_0_x_position(), Some(0));
+    }
+
     #[test]
     fn test_sprite_clipping_left_8_pixels() {
         let mut sprites = Sprites::new();
@@ -1,3 +1,5 @@
+use crate::savestate::{SaveState, StateReader, StateWriter};
+
 /// Manages PPU status flags including VBlank, sprite 0 hit, and NMI
 pub struct Status {
     /// VBlank flag (bit 7 of status register)
@@ -12,8 +14,8 @@ pub struct Status {
     nmi_enabled: bool,
     /// Frame complete flag - set when VBlank starts, regardless of NMI generation
     frame_complete: bool,
-    /// Flag to track if we're on the exact cycle when VBlank starts (for race condition)
-    vblank_start_cycle: bool,
+    /// VBlank will not start this frame, as $2002 was read just before it
+    vblank_suppressed: bool,
 }
 
 impl Status {
@@ -26,7 +28,7 @@ impl Status {
             sprite_overflow: false,
             nmi_enabled: false,
             frame_complete: false,
-            vblank_start_cycle: false,
+            vblank_suppressed: false,
         }
     }
 
@@ -38,15 +40,17 @@ impl Status {
         self.sprite_overflow = false;
         self.nmi_enabled = false;
         self.frame_complete = false;
-        self.vblank_start_cycle = false;
+        self.vblank_suppressed = false;
     }
 
     /// Enter VBlank period
     pub fn enter_vblank(&mut self, nmi_on_vblank: bool) {
         // println!("PPU Status: Entering VBlank");
-        self.vblank_flag = true;
         self.frame_complete = true;
-        self.vblank_start_cycle = true;
+        if std::mem::take(&mut self.vblank_suppressed) {
+            return;
+        }
+        self.vblank_flag = true;
         if nmi_on_vblank {
             self.nmi_enabled = true;
         }
@@ -72,14 +76,19 @@ impl Status {
         self.nmi_enabled = true;
     }
 
-    /// Clear the VBlank start cycle flag
-    pub fn clear_vblank_start_cycle(&mut self) {
-        self.vblank_start_cycle = false;
+    /// Keep the VBlank flag from being set when VBlank starts next
+    ///
+    /// For a status read one dot before VBlank starts.
+    pub fn suppress_vblank(&mut self) {
+        self.vblank_suppressed = true;
     }
 
-    /// Check if we're on the VBlank start cycle
-    pub fn is_vblank_start_cycle(&self) -> bool {
-        self.vblank_start_cycle
+    /// Drop a pending NMI
+    ///
+    /// For a status read on the first dots of VBlank, which clears the flag
+    /// before the CPU sees the NMI.
+    pub fn cancel_nmi(&mut self) {
+        self.nmi_enabled = false;
     }
 
     /// Read the status register (clears VBlank flag and write toggle)
@@ -97,10 +106,8 @@ impl Status {
             status |= 0b0010_0000; // Bit 5: Sprite overflow
         }
 
-        // Reading status clears VBlank flag (but not during vblank_start_cycle for race condition)
-        if !self.vblank_start_cycle {
-            self.vblank_flag = false;
-        }
+        // Reading status clears VBlank flag
+        self.vblank_flag = false;
 
         status
     }
@@ -155,6 +162,37 @@ impl Status {
     }
 }
 
+impl SaveState for Status {
+    fn save_state(&self, state: &mut StateWriter) {
+        for flag in [
+            self.vblank_flag,
+            self.sprite_0_hit,
+            self.pending_sprite_0_hit,
+            self.sprite_overflow,
+            self.nmi_enabled,
+            self.frame_complete,
+            self.vblank_suppressed,
+        ] {
+            state.write_bool(flag);
+        }
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        for flag in [
+            &mut self.vblank_flag,
+            &mut self.sprite_0_hit,
+            &mut self.pending_sprite_0_hit,
+            &mut self.sprite_overflow,
+            &mut self.nmi_enabled,
+            &mut self.frame_complete,
+            &mut self.vblank_suppressed,
+        ] {
+            *flag = state.read_bool()?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -179,7 +217,6 @@ mod tests {
         let mut status = Status::new();
         status.enter_vblank(true);
         assert!(status.is_in_vblank());
-        assert!(status.is_vblank_start_cycle());
     }
 
     #[test]
@@ -194,7 +231,6 @@ mod tests {
     fn test_read_status_clears_vblank() {
         let mut status = Status::new();
         status.enter_vblank(false);
-        status.clear_vblank_start_cycle();
 
         let status_byte = status.read_status();
         assert_eq!(status_byte & 0b1000_0000, 0b1000_0000);
@@ -202,13 +238,26 @@ mod tests {
     }
 
     #[test]
-    fn test_read_status_during_vblank_start() {
+    fn test_suppressed_vblank_does_not_set_flag_or_nmi() {
         let mut status = Status::new();
-        status.enter_vblank(false);
+        status.suppress_vblank();
+        status.enter_vblank(true);
+        assert!(!status.is_in_vblank());
+        assert!(!status.poll_nmi());
+        assert!(status.poll_frame_complete());
 
-        // Reading during vblank_start_cycle should not clear flag
-        let status_byte = status.read_status();
-        assert_eq!(status_byte & 0b1000_0000, 0b1000_0000);
+        // Only the next VBlank is suppressed
+        status.enter_vblank(true);
+        assert!(status.is_in_vblank());
+        assert!(status.poll_nmi());
+    }
+
+    #[test]
+    fn test_cancel_nmi() {
+        let mut status = Status::new();
+        status.enter_vblank(true);
+        status.cancel_nmi();
+        assert!(!status.poll_nmi());
         assert!(status.is_in_vblank());
     }
 
@@ -1,4 +1,5 @@
 use crate::nes::TvSystem;
+use crate::savestate::{SaveState, StateReader, StateWriter};
 
 /// Number of PPU cycles (pixels) per scanline
 const PIXELS_PER_SCANLINE: u16 = 341;
@@ -135,6 +136,24 @@ impl Timing {
     }
 }
 
+/// The TV system is a setting of the console, not part of the state
+impl SaveState for Timing {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u64(self.total_cycles);
+        state.write_u16(self.scanline);
+        state.write_u16(self.pixel);
+        state.write_u64(self.frame_count);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        self.total_cycles = state.read_u64()?;
+        self.scanline = state.read_u16()?;
+        self.pixel = state.read_u16()?;
+        self.frame_count = state.read_u64()?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
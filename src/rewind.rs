@@ -0,0 +1,288 @@
+//! Rewind
+//!
+//! Keeps save states of the recent past in a ring buffer, so play can be
+//! stepped backwards. A state is captured every `interval` frames. Every
+//! `KEYFRAME_INTERVAL`th state is a keyframe and is stored whole; the states
+//! in between are stored as their difference (XOR) to the keyframe before
+//! them, which is mostly zeros for nearby frames. Both are deflated.
+//!
+//! When the buffer grows past its memory budget, the oldest keyframe is
+//! dropped together with the states stored against it.
+
+use crate::nes::Nes;
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+
+/// A stored state
+enum Entry {
+    /// A whole state, deflated
+    Keyframe(Vec<u8>),
+    /// XOR of the state and the last keyframe before it, deflated
+    Delta(Vec<u8>),
+}
+
+impl Entry {
+    fn size(&self) -> usize {
+        match self {
+            Entry::Keyframe(data) | Entry::Delta(data) => data.len(),
+        }
+    }
+}
+
+/// Recent save states, newest last
+pub struct RewindBuffer {
+    entries: VecDeque<Entry>,
+    /// The newest keyframe in `entries`, inflated, for making and reading deltas
+    keyframe: Vec<u8>,
+    /// Deltas stored since the newest keyframe
+    deltas: usize,
+    /// Compressed bytes held in `entries`
+    used: usize,
+    budget: usize,
+    interval: u32,
+    /// Frames since the last capture
+    frames: u32,
+}
+
+impl RewindBuffer {
+    /// States between keyframes, plus one
+    pub const KEYFRAME_INTERVAL: usize = 60;
+
+    /// Keep states of every `interval`th frame in up to `budget` bytes
+    pub fn new(budget: usize, interval: u32) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            keyframe: Vec::new(),
+            deltas: 0,
+            used: 0,
+            budget,
+            interval: interval.max(1),
+            frames: 0,
+        }
+    }
+
+    /// Number of states that can be stepped back to
+    #[cfg(test)]
+    pub fn state_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Compressed size of the stored states in bytes
+    #[cfg(test)]
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Call once per frame, before emulating it, to capture every
+    /// `interval`th state
+    pub fn capture(&mut self, nes: &Nes) {
+        if self.frames == 0 {
+            self.push(&nes.save_state());
+        }
+        self.frames = (self.frames + 1) % self.interval;
+    }
+
+    /// Go back to the newest stored state, removing it from the buffer
+    ///
+    /// Returns `false` if there is no state left to go back to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state cannot be loaded.
+    pub fn rewind(&mut self, nes: &mut Nes) -> Result<bool, String> {
+        let Some(state) = self.pop() else {
+            return Ok(false);
+        };
+        nes.load_state(&state)
+            .map_err(|message| format!("Cannot rewind: {}", message))?;
+        // Capture again on the next frame played forward
+        self.frames = 0;
+        Ok(true)
+    }
+
+    /// Store a state, dropping the oldest ones if over budget
+    pub fn push(&mut self, state: &[u8]) {
+        let entry = if self.entries.is_empty()
+            || self.deltas + 1 >= Self::KEYFRAME_INTERVAL
+            || state.len() != self.keyframe.len()
+        {
+            self.keyframe = state.to_vec();
+            self.deltas = 0;
+            Entry::Keyframe(deflate(state))
+        } else {
+            let delta: Vec<u8> = state
+                .iter()
+                .zip(&self.keyframe)
+                .map(|(byte, key)| byte ^ key)
+                .collect();
+            self.deltas += 1;
+            Entry::Delta(deflate(&delta))
+        };
+        self.used += entry.size();
+        self.entries.push_back(entry);
+        self.evict();
+    }
+
+    /// Take the newest state out of the buffer
+    pub fn pop(&mut self) -> Option<Vec<u8>> {
+        let entry = self.entries.pop_back()?;
+        self.used -= entry.size();
+        match entry {
+            Entry::Keyframe(data) => {
+                let state = inflate(&data);
+                self.find_keyframe();
+                Some(state)
+            }
+            Entry::Delta(data) => {
+                self.deltas -= 1;
+                let mut state = inflate(&data);
+                for (byte, key) in state.iter_mut().zip(&self.keyframe) {
+                    *byte ^= key;
+                }
+                Some(state)
+            }
+        }
+    }
+
+    /// Drop the oldest keyframes and their deltas until within budget
+    ///
+    /// The newest keyframe is always kept, so the buffer can exceed a very
+    /// small budget.
+    fn evict(&mut self) {
+        while self.used > self.budget && self.entries.len() > self.deltas + 1 {
+            let Some(entry) = self.entries.pop_front() else {
+                break;
+            };
+            self.used -= entry.size();
+            while let Some(Entry::Delta(_)) = self.entries.front() {
+                let delta = self.entries.pop_front().expect("front exists");
+                self.used -= delta.size();
+            }
+        }
+    }
+
+    /// Inflate the newest remaining keyframe after the last one was popped
+    fn find_keyframe(&mut self) {
+        self.keyframe.clear();
+        self.deltas = 0;
+        for entry in self.entries.iter().rev() {
+            match entry {
+                Entry::Keyframe(data) => {
+                    self.keyframe = inflate(data);
+                    return;
+                }
+                Entry::Delta(_) => self.deltas += 1,
+            }
+        }
+    }
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+    encoder
+        .write_all(data)
+        .expect("writing to a Vec cannot fail");
+    encoder.finish().expect("writing to a Vec cannot fail")
+}
+
+fn inflate(data: &[u8]) -> Vec<u8> {
+    let mut state = Vec::new();
+    DeflateDecoder::new(data)
+        .read_to_end(&mut state)
+        .expect("the buffer only holds data it deflated");
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A state-like block where only a few bytes change between frames
+    fn fake_state(frame: usize) -> Vec<u8> {
+        let mut state: Vec<u8> = (0..4096).map(|i| (i * 7 % 251) as u8).collect();
+        state[frame % 4096] = frame as u8;
+        state[100..104].copy_from_slice(&(frame as u32).to_le_bytes());
+        state
+    }
+
+    #[test]
+    fn test_states_come_back_newest_first() {
+        let mut buffer = RewindBuffer::new(usize::MAX, 1);
+        for frame in 0..150 {
+            buffer.push(&fake_state(frame));
+        }
+        assert_eq!(buffer.state_count(), 150);
+        for frame in (0..150).rev() {
+            assert_eq!(buffer.pop(), Some(fake_state(frame)), "frame {}", frame);
+        }
+        assert_eq!(buffer.pop(), None);
+        assert_eq!(buffer.used(), 0);
+    }
+
+    #[test]
+    fn test_deltas_are_smaller_than_keyframes() {
+        let mut buffer = RewindBuffer::new(usize::MAX, 1);
+        buffer.push(&fake_state(0));
+        let keyframe = buffer.used();
+        buffer.push(&fake_state(1));
+        assert!(buffer.used() - keyframe < keyframe / 2);
+    }
+
+    #[test]
+    fn test_oldest_states_are_dropped_over_budget() {
+        let mut buffer = RewindBuffer::new(usize::MAX, 1);
+        for frame in 0..RewindBuffer::KEYFRAME_INTERVAL {
+            buffer.push(&fake_state(frame));
+        }
+        let group = buffer.used();
+
+        // Room for two keyframe groups and a bit
+        let mut buffer = RewindBuffer::new(group * 5 / 2, 1);
+        for frame in 0..1000 {
+            buffer.push(&fake_state(frame));
+            assert!(buffer.used() <= group * 5 / 2);
+        }
+        assert!(buffer.state_count() > RewindBuffer::KEYFRAME_INTERVAL);
+        let oldest = 1000 - buffer.state_count();
+        for frame in (oldest..1000).rev() {
+            assert_eq!(buffer.pop(), Some(fake_state(frame)), "frame {}", frame);
+        }
+    }
+
+    #[test]
+    fn test_newest_keyframe_is_kept_over_a_small_budget() {
+        let mut buffer = RewindBuffer::new(1, 1);
+        for frame in 0..5 {
+            buffer.push(&fake_state(frame));
+        }
+        assert_eq!(buffer.state_count(), 5);
+        assert_eq!(buffer.pop(), Some(fake_state(4)));
+    }
+
+    #[test]
+    fn test_rewind_returns_to_captured_frames() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+
+        let mut buffer = RewindBuffer::new(usize::MAX, 2);
+        let mut states = Vec::new();
+        for frame in 0..40u8 {
+            states.push(nes.save_state());
+            buffer.capture(&nes);
+            nes.set_buttons(1, frame.wrapping_mul(37));
+            while !nes.is_ready_to_render() {
+                nes.run_cpu_tick();
+            }
+            nes.clear_ready_to_render();
+        }
+        assert_eq!(buffer.state_count(), 20);
+
+        for frame in (0..40).step_by(2).rev() {
+            assert_eq!(buffer.rewind(&mut nes), Ok(true));
+            assert!(nes.save_state() == states[frame], "frame {}", frame);
+        }
+        assert_eq!(buffer.rewind(&mut nes), Ok(false));
+    }
+}
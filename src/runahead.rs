@@ -0,0 +1,162 @@
+//! Run-ahead
+//!
+//! Hides some of the latency between pressing a button and seeing the result.
+//! Most games react to input a frame or more after reading it, so after each
+//! real frame the console is saved, run `frames` frames further with the
+//! current input, and restored. The screen of that future frame is shown
+//! instead of the real one. The audio of the hidden frames is thrown away.
+//!
+//! With a second instance the frames ahead run on a copy of the console
+//! instead. The console that makes the sound is then never restored, so its
+//! audio continues undisturbed even by what a save state leaves out.
+
+use crate::nes::Nes;
+
+/// Runs frames ahead of a `Nes` to show them early
+pub struct RunAhead {
+    frames: u32,
+    /// Console to run ahead on, in place of restoring the real one
+    second_instance: Option<Nes>,
+}
+
+impl RunAhead {
+    /// Most frames that can be run ahead
+    pub const MAX_FRAMES: u32 = 6;
+
+    /// Run `frames` frames ahead on the real console
+    pub fn new(frames: u32) -> Self {
+        Self {
+            frames,
+            second_instance: None,
+        }
+    }
+
+    /// Run `frames` frames ahead on `second_instance`, which must hold the
+    /// same ROM and settings as the real console
+    pub fn with_second_instance(frames: u32, second_instance: Nes) -> Self {
+        Self {
+            frames,
+            second_instance: Some(second_instance),
+        }
+    }
+
+    /// The console frames are run ahead on, if it is not the real one
+    pub fn second_instance_mut(&mut self) -> Option<&mut Nes> {
+        self.second_instance.as_mut()
+    }
+
+    /// Call after each real frame, to leave the frame `frames` frames ahead on
+    /// the screen of `nes` without changing its state
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state of `nes` cannot be restored.
+    pub fn run(&mut self, nes: &mut Nes) -> Result<(), String> {
+        if self.frames == 0 || nes.cpu.is_halted() {
+            return Ok(());
+        }
+        let state = nes.save_state();
+        let run_ahead = |ahead: &mut Nes| {
+            for _ in 0..self.frames {
+                run_hidden_frame(ahead);
+            }
+        };
+
+        match &mut self.second_instance {
+            Some(second) => {
+                second
+                    .load_state(&state)
+                    .map_err(|message| format!("Cannot run ahead: {}", message))?;
+                // Controller buttons are input, not state
+                for controller in 1..=2 {
+                    second.set_buttons(controller, nes.buttons(controller));
+                }
+                run_ahead(second);
+                nes.get_screen_buffer()
+                    .copy_from(second.get_screen_buffer());
+            }
+            None => {
+                run_ahead(nes);
+                // The screen is not part of the state, so it keeps the frame
+                nes.load_state(&state)
+                    .map_err(|message| format!("Cannot run ahead: {}", message))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Emulate a frame, discarding its audio
+pub fn run_hidden_frame(nes: &mut Nes) {
+    while !nes.is_ready_to_render() && !nes.cpu.is_halted() {
+        nes.run_cpu_tick();
+        while nes.sample_ready() {
+            nes.get_sample();
+        }
+    }
+    nes.clear_ready_to_render();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NESTEST: &str = "roms/nestest.nes";
+
+    /// The Start button, which nestest waits for to run its tests
+    const START: u8 = 0x08;
+
+    /// Play `frames` frames holding Start, running ahead after each if given,
+    /// and return the shown screens, the audio and the final state
+    fn play(
+        nes: &mut Nes,
+        mut run_ahead: Option<RunAhead>,
+        frames: usize,
+    ) -> (Vec<Vec<u8>>, Vec<f32>, Vec<u8>) {
+        let mut screens = Vec::new();
+        let mut samples = Vec::new();
+        nes.set_buttons(1, START);
+        for _ in 0..frames {
+            while !nes.is_ready_to_render() {
+                nes.run_cpu_tick();
+                while nes.sample_ready() {
+                    samples.extend(nes.get_sample());
+                }
+            }
+            nes.clear_ready_to_render();
+            if let Some(run_ahead) = &mut run_ahead {
+                run_ahead.run(nes).unwrap();
+            }
+            screens.push(nes.get_screen_buffer().pixels().to_vec());
+        }
+        (screens, samples, nes.save_state())
+    }
+
+    #[test]
+    fn test_zero_frames_changes_nothing() {
+        let plain = play(&mut Nes::power_on(NESTEST), None, 10);
+        assert!(play(&mut Nes::power_on(NESTEST), Some(RunAhead::new(0)), 10) == plain);
+        let second = RunAhead::with_second_instance(0, Nes::power_on(NESTEST));
+        assert!(play(&mut Nes::power_on(NESTEST), Some(second), 10) == plain);
+    }
+
+    #[test]
+    fn test_future_frames_are_shown_without_changing_play() {
+        let (screens, _, _) = play(&mut Nes::power_on(NESTEST), None, 40);
+        let (_, samples, state) = play(&mut Nes::power_on(NESTEST), None, 38);
+        assert!(screens[0] != screens[39]);
+
+        for run_ahead in [
+            RunAhead::new(2),
+            RunAhead::with_second_instance(2, Nes::power_on(NESTEST)),
+        ] {
+            let (ahead, ahead_samples, ahead_state) =
+                play(&mut Nes::power_on(NESTEST), Some(run_ahead), 38);
+            assert!(ahead_samples == samples);
+            assert!(ahead_state == state);
+            for (frame, screen) in ahead.iter().enumerate() {
+                assert!(*screen == screens[frame + 2], "frame {}", frame);
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Save states
+//!
+//! A save state holds everything the emulated console needs to continue
+//! exactly where it was: CPU registers, RAM, PPU, APU, controllers and the
+//! cartridge's RAM and mapper registers. Each component implements `SaveState`
+//! by writing its fields in a fixed order and reading them back in the same
+//! order.
+//!
+//! Settings (palette, sprite limit, muted channels, sample rate), debugging
+//! aids and outputs such as the screen and queued audio samples are not part
+//! of a state. The screen is drawn again by the next frame.
+
+/// Written at the start of every state
+pub const MAGIC: &[u8; 4] = b"NESS";
+
+/// Layout version, raised whenever a component changes what it saves
+pub const VERSION: u8 = 3;
+
+/// A component whose state can be saved and restored
+pub trait SaveState {
+    /// Append the state to `state`
+    fn save_state(&self, state: &mut StateWriter);
+
+    /// Restore the state written by `save_state`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data ends early or holds an impossible value.
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String>;
+}
+
+/// Builds a state in little-endian byte order
+#[derive(Debug, Default)]
+pub struct StateWriter {
+    data: Vec<u8>,
+}
+
+impl StateWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The state written so far
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.write_u32(value.to_bits());
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    pub fn write_option_u8(&mut self, value: Option<u8>) {
+        self.write_bool(value.is_some());
+        self.write_u8(value.unwrap_or(0));
+    }
+
+    pub fn write_option_u16(&mut self, value: Option<u16>) {
+        self.write_bool(value.is_some());
+        self.write_u16(value.unwrap_or(0));
+    }
+
+    /// Write a block of bytes, preceded by its length
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_u32(bytes.len() as u32);
+        self.data.extend_from_slice(bytes);
+    }
+}
+
+/// Reads a state written by `StateWriter`
+#[derive(Debug)]
+pub struct StateReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> StateReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// Check that the whole state was read
+    pub fn finish(&self) -> Result<(), String> {
+        match self.data.len() - self.position {
+            0 => Ok(()),
+            left => Err(format!("{} unexpected bytes at the end of the state", left)),
+        }
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + N)
+            .ok_or("The state ends early")?;
+        self.position += N;
+        Ok(bytes.try_into().expect("slice has N bytes"))
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, String> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take()?))
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_bits(self.read_u32()?))
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, String> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(format!("Invalid flag {} in the state", value)),
+        }
+    }
+
+    pub fn read_option_u8(&mut self) -> Result<Option<u8>, String> {
+        let present = self.read_bool()?;
+        let value = self.read_u8()?;
+        Ok(present.then_some(value))
+    }
+
+    pub fn read_option_u16(&mut self) -> Result<Option<u16>, String> {
+        let present = self.read_bool()?;
+        let value = self.read_u16()?;
+        Ok(present.then_some(value))
+    }
+
+    /// Read a block written by `write_bytes`
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], String> {
+        let len = self.read_u32()? as usize;
+        let bytes = self
+            .data
+            .get(self.position..self.position + len)
+            .ok_or("The state ends early")?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    /// Read a block written by `write_bytes` into memory of the same size
+    pub fn read_bytes_into(&mut self, target: &mut [u8]) -> Result<(), String> {
+        let bytes = self.read_bytes()?;
+        if bytes.len() != target.len() {
+            return Err(format!(
+                "State holds {} bytes of memory, expected {}",
+                bytes.len(),
+                target.len()
+            ));
+        }
+        target.copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_round_trip() {
+        let mut writer = StateWriter::new();
+        writer.write_u8(0x12);
+        writer.write_u16(0x3456);
+        writer.write_u32(0x789A_BCDE);
+        writer.write_u64(u64::MAX - 1);
+        writer.write_f32(-1.5);
+        writer.write_bool(true);
+        writer.write_option_u8(None);
+        writer.write_option_u16(Some(0x8000));
+        writer.write_bytes(&[1, 2, 3]);
+        let data = writer.into_bytes();
+
+        let mut reader = StateReader::new(&data);
+        assert_eq!(reader.read_u8(), Ok(0x12));
+        assert_eq!(reader.read_u16(), Ok(0x3456));
+        assert_eq!(reader.read_u32(), Ok(0x789A_BCDE));
+        assert_eq!(reader.read_u64(), Ok(u64::MAX - 1));
+        assert_eq!(reader.read_f32(), Ok(-1.5));
+        assert_eq!(reader.read_bool(), Ok(true));
+        assert_eq!(reader.read_option_u8(), Ok(None));
+        assert_eq!(reader.read_option_u16(), Ok(Some(0x8000)));
+        let mut block = [0; 3];
+        assert_eq!(reader.read_bytes_into(&mut block), Ok(()));
+        assert_eq!(block, [1, 2, 3]);
+        assert_eq!(reader.finish(), Ok(()));
+    }
+
+    #[test]
+    fn test_short_and_invalid_data_are_errors() {
+        assert!(StateReader::new(&[1]).read_u16().is_err());
+        assert!(StateReader::new(&[2]).read_bool().is_err());
+        assert!(StateReader::new(&[9, 0, 0, 0, 1]).read_bytes().is_err());
+
+        let mut reader = StateReader::new(&[2, 0, 0, 0, 1, 2]);
+        assert!(reader.read_bytes_into(&mut [0; 3]).is_err());
+        assert!(StateReader::new(&[1]).finish().is_err());
+    }
+}
@@ -105,6 +105,16 @@ impl ScreenBuffer {
         result
     }
 
+    /// Returns the RGB bytes of all pixels, row by row from the top left.
+    pub fn pixels(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Copies all pixels from another screen buffer.
+    pub fn copy_from(&mut self, other: &ScreenBuffer) {
+        self.buffer.copy_from_slice(&other.buffer);
+    }
+
     /// Copies the entire buffer to the specified destination buffer.
     ///
     /// # Arguments
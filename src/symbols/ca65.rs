@@ -0,0 +1,196 @@
+//! ca65/ld65 debug info (`.dbg`)
+//!
+//! The file holds one record per line, a record type and a tab followed by
+//! comma-separated `key=value` pairs:
+//!
+//! ```text
+//! seg     id=0,name="CODE",start=0x008000,size=0x0123,addrsize=absolute,type=ro,oname="game.nes",ooffs=16
+//! sym     id=0,name="reset",addrsize=absolute,scope=0,def=1,val=0x8000,seg=0,type=lab
+//! ```
+//!
+//! Only `seg` and `sym` records are used. A label's PRG-ROM offset is found
+//! from its segment's offset in the output file, less the iNES header.
+
+use super::Symbol;
+use std::collections::HashMap;
+use std::io;
+
+/// iNES header size, used when the debug info has no `HEADER` segment
+const INES_HEADER_SIZE: usize = 16;
+
+/// A segment's load address and position in the output file
+struct Segment {
+    name: String,
+    start: usize,
+    size: usize,
+    /// Offset in the output file, for segments stored in it
+    output_offset: Option<usize>,
+}
+
+/// Split a record's `key=value` pairs, keeping commas inside quoted values
+fn fields(text: &str) -> HashMap<&str, &str> {
+    let mut fields = HashMap::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let Some((key, value)) = rest.split_once('=') else {
+            break;
+        };
+        let end = if let Some(quoted) = value.strip_prefix('"') {
+            quoted.find('"').map_or(value.len(), |end| end + 2)
+        } else {
+            value.find(',').unwrap_or(value.len())
+        };
+        fields.insert(key.trim(), value[..end].trim_matches('"'));
+        rest = value[end..].strip_prefix(',').unwrap_or(&value[end..]);
+    }
+    fields
+}
+
+/// Parse a decimal or `0x` hex number
+fn number(text: &str) -> Option<usize> {
+    match text.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Parse debug info
+///
+/// Labels (`type=lab`) become symbols. Equates (`type=equ`) are kept only in
+/// the $2000-$401F range, where they name PPU, APU and I/O registers; elsewhere
+/// they are usually constants rather than addresses. Imports are skipped since
+/// the exporting module defines the same symbol.
+pub fn parse_dbg(text: &str) -> io::Result<Vec<Symbol>> {
+    let mut segments = HashMap::new();
+    let mut records = Vec::new();
+    for (line_number, line) in text.lines().enumerate() {
+        let Some((kind, rest)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+        match kind {
+            "seg" => {
+                let fields = fields(rest);
+                let field = |key: &str| {
+                    fields.get(key).copied().and_then(number).ok_or_else(|| {
+                        invalid(format!(
+                            "Line {}: segment without a valid {}",
+                            line_number + 1,
+                            key
+                        ))
+                    })
+                };
+                let segment = Segment {
+                    name: fields.get("name").copied().unwrap_or_default().to_string(),
+                    start: field("start")?,
+                    size: field("size")?,
+                    output_offset: fields.get("ooffs").copied().and_then(number),
+                };
+                segments.insert(field("id")?, segment);
+            }
+            "sym" => records.push((line_number + 1, rest)),
+            _ => {}
+        }
+    }
+
+    let header_size = segments
+        .values()
+        .find(|segment| segment.name == "HEADER" && segment.output_offset == Some(0))
+        .map_or(INES_HEADER_SIZE, |segment| segment.size);
+
+    let mut symbols = Vec::new();
+    for (line_number, rest) in records {
+        let fields = fields(rest);
+        let Some(val) = fields.get("val").copied().and_then(number) else {
+            continue;
+        };
+        let keep = match fields.get("type").copied() {
+            Some("lab") => true,
+            Some("equ") => (0x2000..=0x401F).contains(&val),
+            _ => false,
+        };
+        if !keep {
+            continue;
+        }
+        let name = fields
+            .get("name")
+            .ok_or_else(|| invalid(format!("Line {}: symbol without a name", line_number)))?;
+        let addr = u16::try_from(val).map_err(|_| {
+            invalid(format!(
+                "Line {}: symbol {} has value {:#X}, outside the CPU address space",
+                line_number, name, val
+            ))
+        })?;
+
+        let segment = match fields.get("seg").copied().and_then(number) {
+            Some(id) => Some(segments.get(&id).ok_or_else(|| {
+                invalid(format!(
+                    "Line {}: symbol {} refers to unknown segment {}",
+                    line_number, name, id
+                ))
+            })?),
+            None => None,
+        };
+        let prg_offset = segment.filter(|_| addr >= 0x8000).and_then(|segment| {
+            let output_offset = segment.output_offset?;
+            (output_offset + val).checked_sub(header_size + segment.start)
+        });
+
+        symbols.push(Symbol {
+            name: name.to_string(),
+            addr,
+            prg_offset,
+        });
+    }
+    Ok(symbols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEBUG_INFO: &str = r#"version	major=2,minor=0
+file	id=0,name="main, with comma.s",size=100,mtime=0x5F000000,mod=0
+seg	id=0,name="HEADER",start=0x000000,size=0x0010,addrsize=absolute,type=ro,oname="game.nes",ooffs=0
+seg	id=1,name="BANK1",start=0x008000,size=0x4000,addrsize=absolute,type=ro,oname="game.nes",ooffs=16400
+seg	id=2,name="BSS",start=0x000300,size=0x0100,addrsize=absolute,type=rw
+sym	id=0,name="bank1_entry",addrsize=absolute,scope=0,def=1,val=0x8010,seg=1,type=lab
+sym	id=1,name="buffer",addrsize=absolute,scope=0,def=2,val=0x300,seg=2,type=lab
+sym	id=2,name="PPUCTRL",addrsize=absolute,scope=0,def=3,val=0x2000,type=equ
+sym	id=3,name="SPEED",addrsize=zeropage,scope=0,def=4,val=0x3,type=equ
+sym	id=4,name="helper",addrsize=absolute,scope=0,def=5,ref=6,type=imp
+"#;
+
+    #[test]
+    fn test_parse_debug_info() {
+        let symbols = parse_dbg(DEBUG_INFO).unwrap();
+        let summary: Vec<_> = symbols
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.addr, symbol.prg_offset))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("bank1_entry", 0x8010, Some(0x4010)),
+                ("buffer", 0x0300, None),
+                ("PPUCTRL", 0x2000, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_segment_is_an_error() {
+        let text = "sym\tid=0,name=\"x\",val=0x8000,seg=7,type=lab\n";
+        assert!(parse_dbg(text).is_err());
+    }
+
+    #[test]
+    fn test_fields_keep_quoted_commas() {
+        let fields = fields("id=0,name=\"a, b\",size=3");
+        assert_eq!(fields["name"], "a, b");
+        assert_eq!(fields["size"], "3");
+    }
+}
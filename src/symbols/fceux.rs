@@ -0,0 +1,85 @@
+//! FCEUX name lists (`.nl`)
+//!
+//! Each line is `$ADDR#NAME#COMMENT`, optionally with an array size as
+//! `$ADDR/SIZE#NAME#...`. Lines that do not start with `$` continue a
+//! multi-line comment. FCEUX keeps one file per 16 KiB PRG-ROM bank
+//! (`game.nes.0.nl`, `game.nes.1.nl`, ...) and one for RAM (`game.nes.ram.nl`).
+
+use super::{Symbol, parse_hex};
+use std::path::Path;
+
+/// Size of the banks FCEUX numbers its name lists by
+const BANK_SIZE: usize = 0x4000;
+
+/// Get the PRG-ROM bank a name list covers from its file name
+///
+/// Returns `None` for RAM name lists and file names without a bank number.
+pub fn bank_from_path(path: &Path) -> Option<usize> {
+    let stem = path.file_stem()?.to_str()?;
+    let (_, bank) = stem.rsplit_once('.')?;
+    usize::from_str_radix(bank, 16).ok()
+}
+
+/// Parse a name list
+///
+/// # Arguments
+/// * `text` - Contents of the `.nl` file
+/// * `bank` - PRG-ROM bank the file covers, or `None` for RAM
+pub fn parse_nl(text: &str, bank: Option<usize>) -> Vec<Symbol> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.strip_prefix('$')?;
+            let mut fields = line.splitn(3, '#');
+            let addr = fields.next()?;
+            let addr = addr.split_once('/').map_or(addr, |(addr, _)| addr);
+            let addr = u16::try_from(parse_hex(addr)?).ok()?;
+            let name = fields.next()?.trim();
+            if name.is_empty() {
+                return None;
+            }
+            let prg_offset = bank
+                .filter(|_| addr >= 0x8000)
+                .map(|bank| bank * BANK_SIZE + (addr as usize & (BANK_SIZE - 1)));
+            Some(Symbol {
+                name: name.to_string(),
+                addr,
+                prg_offset,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bank_name_list() {
+        let text = "$C000#reset#Entry point\n\
+                    \\continued comment\n\
+                    $C010/10#table#\n\
+                    $C020##Comment only\n";
+        let symbols = parse_nl(text, Some(3));
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].name, "reset");
+        assert_eq!(symbols[0].addr, 0xC000);
+        assert_eq!(symbols[0].prg_offset, Some(0xC000));
+        assert_eq!(symbols[1].name, "table");
+        assert_eq!(symbols[1].prg_offset, Some(0xC010));
+    }
+
+    #[test]
+    fn test_parse_ram_name_list() {
+        let symbols = parse_nl("$0010#player_x#\n", None);
+        assert_eq!(symbols[0].addr, 0x0010);
+        assert_eq!(symbols[0].prg_offset, None);
+    }
+
+    #[test]
+    fn test_bank_from_path() {
+        assert_eq!(bank_from_path(Path::new("game.nes.2.nl")), Some(2));
+        assert_eq!(bank_from_path(Path::new("game.nes.1F.nl")), Some(0x1F));
+        assert_eq!(bank_from_path(Path::new("game.nes.ram.nl")), None);
+        assert_eq!(bank_from_path(Path::new("game.nl")), None);
+    }
+}
@@ -0,0 +1,68 @@
+//! Mesen label files (`.mlb`)
+//!
+//! Each line is `TYPE:ADDR[-END]:LABEL[:COMMENT]` with hex addresses relative
+//! to the memory type. Both the single-letter types of Mesen 1 (`P`, `R`, ...)
+//! and the names used by Mesen 2 (`NesPrgRom`, `NesInternalRam`, ...) are read.
+
+use super::{Symbol, parse_hex};
+
+/// Parse a label file
+///
+/// PRG-ROM labels are given the CPU address of the offset in a 32 KiB window at
+/// $8000; the offset is what identifies them. Labels for memory types other than
+/// PRG-ROM, RAM, work/save RAM and registers (CHR, palette, ...) are skipped.
+pub fn parse_mlb(text: &str) -> Vec<Symbol> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.trim().splitn(4, ':');
+            let kind = fields.next()?;
+            let addr = fields.next()?;
+            let name = fields.next()?.trim();
+            if name.is_empty() {
+                return None;
+            }
+            let start = addr.split_once('-').map_or(addr, |(start, _)| start);
+            let offset = parse_hex(start)?;
+            let (addr, prg_offset) = match kind {
+                "P" | "NesPrgRom" => (0x8000 | (offset & 0x7FFF), Some(offset)),
+                "R" | "NesInternalRam" | "G" | "NesMemory" => (offset, None),
+                "S" | "W" | "NesSaveRam" | "NesWorkRam" => (0x6000 + offset, None),
+                _ => return None,
+            };
+            Some(Symbol {
+                name: name.to_string(),
+                addr: u16::try_from(addr).ok()?,
+                prg_offset,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_label_file() {
+        let text = "P:4000:bank1_entry:Comment\n\
+                    R:0010-0011:player_pos\n\
+                    G:2000:PPUCTRL\n\
+                    NesWorkRam:0100:save_data\n\
+                    C:0000:chr_tiles\n\
+                    P:0010::Comment only\n";
+        let symbols = parse_mlb(text);
+        let summary: Vec<_> = symbols
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.addr, symbol.prg_offset))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("bank1_entry", 0xC000, Some(0x4000)),
+                ("player_pos", 0x0010, None),
+                ("PPUCTRL", 0x2000, None),
+                ("save_data", 0x6100, None),
+            ]
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! Symbol files for debugging
+//!
+//! Labels are imported from ca65 debug info (`.dbg`), FCEUX name lists (`.nl`)
+//! and Mesen label files (`.mlb`) into a `SymbolTable`. Symbols in PRG-ROM keep
+//! their PRG-ROM offset as well as their CPU address, so the same CPU address
+//! can carry different names in different banks.
+
+mod ca65;
+mod fceux;
+mod mesen;
+
+use crate::disasm::Labels;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A named address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    /// CPU address the symbol was defined at
+    pub addr: u16,
+    /// PRG-ROM offset, for symbols in PRG-ROM
+    pub prg_offset: Option<usize>,
+}
+
+/// Symbols indexed by name, CPU address and PRG-ROM offset
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+    by_name: HashMap<String, usize>,
+    /// Symbols outside PRG-ROM
+    by_addr: HashMap<u16, usize>,
+    /// All symbols, for lookups where the bank mapping is unknown
+    by_any_addr: HashMap<u16, usize>,
+    by_prg_offset: HashMap<usize, usize>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a symbol
+    ///
+    /// When several symbols share a name, address or PRG-ROM offset, the first
+    /// one added is used for lookups.
+    pub fn insert(&mut self, symbol: Symbol) {
+        let index = self.symbols.len();
+        self.by_name.entry(symbol.name.clone()).or_insert(index);
+        self.by_any_addr.entry(symbol.addr).or_insert(index);
+        match symbol.prg_offset {
+            Some(offset) => {
+                self.by_prg_offset.entry(offset).or_insert(index);
+            }
+            None => {
+                self.by_addr.entry(symbol.addr).or_insert(index);
+            }
+        }
+        self.symbols.push(symbol);
+    }
+
+    /// Find a symbol by name
+    pub fn find(&self, name: &str) -> Option<&Symbol> {
+        self.by_name.get(name).map(|&index| &self.symbols[index])
+    }
+
+    /// Name of the symbol at a CPU address
+    ///
+    /// # Arguments
+    /// * `addr` - CPU address
+    /// * `prg_offset` - PRG-ROM offset `addr` is currently mapped to, if any.
+    ///   When `None` for a ROM address (no mapping known), PRG-ROM symbols
+    ///   defined at `addr` match regardless of bank.
+    pub fn name_at(&self, addr: u16, prg_offset: Option<usize>) -> Option<&str> {
+        let index = match prg_offset {
+            Some(offset) => self
+                .by_prg_offset
+                .get(&offset)
+                .or_else(|| self.by_addr.get(&addr)),
+            None => self
+                .by_addr
+                .get(&addr)
+                .or_else(|| self.by_any_addr.get(&addr)),
+        };
+        index.map(|&index| self.symbols[index].name.as_str())
+    }
+
+    /// Load a symbol file and add its symbols, returning how many were added
+    ///
+    /// The format is chosen by extension: `.dbg` (ca65), `.nl` (FCEUX) or
+    /// `.mlb` (Mesen). FCEUX name lists cover one bank each; the bank is taken
+    /// from the file name (`game.nes.2.nl` for bank 2, `game.nes.ram.nl` for RAM).
+    pub fn load(&mut self, path: &Path) -> io::Result<usize> {
+        let text = fs::read_to_string(path)?;
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+        let symbols = match extension.as_deref() {
+            Some("dbg") => ca65::parse_dbg(&text)?,
+            Some("nl") => fceux::parse_nl(&text, fceux::bank_from_path(path)),
+            Some("mlb") => mesen::parse_mlb(&text),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Unknown symbol file type (expected .dbg, .nl or .mlb)",
+                ));
+            }
+        };
+
+        let count = symbols.len();
+        for symbol in symbols {
+            self.insert(symbol);
+        }
+        Ok(count)
+    }
+}
+
+/// Labels for the current bank mapping
+///
+/// `prg_offset` maps a CPU address to the PRG-ROM offset it is currently
+/// mapped to (see `Mapper::prg_rom_offset`).
+pub struct MappedLabels<'a, F: Fn(u16) -> Option<usize>> {
+    pub symbols: &'a SymbolTable,
+    pub prg_offset: F,
+}
+
+impl<F: Fn(u16) -> Option<usize>> Labels for MappedLabels<'_, F> {
+    fn label(&self, addr: u16) -> Option<&str> {
+        self.symbols.name_at(addr, (self.prg_offset)(addr))
+    }
+}
+
+/// Parse a hex number with an optional `$` or `0x` prefix
+fn parse_hex(text: &str) -> Option<usize> {
+    let text = text.trim();
+    let digits = text
+        .strip_prefix('$')
+        .or_else(|| text.strip_prefix("0x"))
+        .or_else(|| text.strip_prefix("0X"))
+        .unwrap_or(text);
+    usize::from_str_radix(digits, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(name: &str, addr: u16, prg_offset: Option<usize>) -> Symbol {
+        Symbol {
+            name: name.to_string(),
+            addr,
+            prg_offset,
+        }
+    }
+
+    #[test]
+    fn test_bank_aware_lookup() {
+        let mut symbols = SymbolTable::new();
+        symbols.insert(symbol("player_x", 0x0010, None));
+        symbols.insert(symbol("bank0_entry", 0x8000, Some(0x0000)));
+        symbols.insert(symbol("bank1_entry", 0x8000, Some(0x4000)));
+
+        assert_eq!(symbols.name_at(0x0010, None), Some("player_x"));
+        assert_eq!(symbols.name_at(0x8000, Some(0x0000)), Some("bank0_entry"));
+        assert_eq!(symbols.name_at(0x8000, Some(0x4000)), Some("bank1_entry"));
+        assert_eq!(symbols.name_at(0x8000, Some(0x8000)), None);
+        // Without a known mapping, the first symbol at the address is used
+        assert_eq!(symbols.name_at(0x8000, None), Some("bank0_entry"));
+
+        assert_eq!(
+            symbols.find("bank1_entry").unwrap().prg_offset,
+            Some(0x4000)
+        );
+        assert!(symbols.find("missing").is_none());
+    }
+
+    #[test]
+    fn test_mapped_labels() {
+        let mut symbols = SymbolTable::new();
+        symbols.insert(symbol("bank1_entry", 0x8000, Some(0x4000)));
+        let labels = MappedLabels {
+            symbols: &symbols,
+            prg_offset: |addr| Some(0x4000 + (addr as usize - 0x8000)),
+        };
+        assert_eq!(labels.label(0x8000), Some("bank1_entry"));
+        assert_eq!(labels.label(0x8001), None);
+    }
+
+    #[test]
+    fn test_load_rejects_unknown_extension() {
+        let path = std::env::temp_dir().join("neser_symbols_test.txt");
+        fs::write(&path, "").unwrap();
+        assert!(SymbolTable::new().load(&path).is_err());
+        fs::remove_file(path).ok();
+    }
+}
@@ -0,0 +1,533 @@
+//! Test ROM framework
+//!
+//! Runs test ROMs and reads back their verdict. Most of blargg's test ROMs
+//! report through PRG-RAM:
+//! - $6000: status, $80 while running, $81 when the ROM asks for the reset
+//!   button to be pressed, then $00 for a pass or $01-$7F for a failure code
+//! - $6001-$6003: the signature DE B0 61, written once the status is valid
+//! - $6004: the text the ROM printed, terminated by a zero byte
+//!
+//! Older ROMs only print their result on screen, which is read back from the
+//! nametable as text.
+//!
+//! A directory of test ROMs can hold a manifest file listing its ROMs and the
+//! result expected from each. `load_manifests` collects them from a directory
+//! tree and `run_all` runs them, so a whole suite can be checked at once.
+
+use crate::cartridge::{Cartridge, load_rom};
+use crate::nes::{Nes, TvSystem};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Where test ROMs report their status
+pub const STATUS_ADDR: u16 = 0x6000;
+
+/// Where test ROMs write `SIGNATURE` once the status is valid
+pub const SIGNATURE_ADDR: u16 = 0x6001;
+
+/// Marks the status and text in PRG-RAM as written by a test ROM
+pub const SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+
+/// Where test ROMs write their zero-terminated text output
+pub const TEXT_ADDR: u16 = 0x6004;
+
+/// Name of the file listing the test ROMs in a directory
+pub const MANIFEST_NAME: &str = "manifest.txt";
+
+/// Result a test ROM reported at $6000
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestResult {
+    Pass,
+    Fail(u8),
+}
+
+/// Follows the $6000 status protocol
+///
+/// The status byte is only trusted once the signature is in place and the ROM
+/// has written $80, since PRG-RAM holds garbage before the test starts.
+pub struct TestStatus {
+    running: bool,
+    /// CPU cycle at which the ROM asked for a reset
+    reset_requested: Option<u64>,
+}
+
+impl TestStatus {
+    /// The ROMs want reset pressed at least 100 ms after asking for it
+    const RESET_DELAY_CYCLES: u64 = 1_789_773 / 10;
+
+    pub fn new() -> Self {
+        Self {
+            running: false,
+            reset_requested: None,
+        }
+    }
+
+    /// Read the status byte, noting whether the test has started
+    ///
+    /// Called after every instruction, so a short $80 is not missed.
+    pub fn poll(&mut self, nes: &Nes) -> u8 {
+        let status = nes.peek(STATUS_ADDR);
+        if !has_signature(nes) {
+            self.reset_requested = None;
+            return status;
+        }
+        if status == 0x80 {
+            self.running = true;
+        }
+        if status == 0x81 {
+            self.reset_requested
+                .get_or_insert(nes.cpu.get_total_cycles());
+        } else {
+            self.reset_requested = None;
+        }
+        status
+    }
+
+    /// Check the status at the end of a frame
+    ///
+    /// Presses reset when the ROM has asked for it long enough ago.
+    /// Returns `None` while the test has not finished.
+    pub fn check(&mut self, nes: &mut Nes) -> Option<TestResult> {
+        if !self.running {
+            return None;
+        }
+        match self.poll(nes) {
+            0x00 => Some(TestResult::Pass),
+            status @ 0x01..=0x7F => Some(TestResult::Fail(status)),
+            _ => {
+                if let Some(requested) = self.reset_requested
+                    && nes.cpu.get_total_cycles().saturating_sub(requested)
+                        >= Self::RESET_DELAY_CYCLES
+                {
+                    nes.reset();
+                    self.reset_requested = None;
+                }
+                None
+            }
+        }
+    }
+}
+
+impl Default for TestStatus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether PRG-RAM holds the test ROM signature
+pub fn has_signature(nes: &Nes) -> bool {
+    (0..SIGNATURE.len() as u16)
+        .map(|offset| nes.peek(SIGNATURE_ADDR + offset))
+        .eq(SIGNATURE)
+}
+
+/// Text the test ROM wrote at $6004, or `None` without the signature
+pub fn status_text(nes: &Nes) -> Option<String> {
+    if !has_signature(nes) {
+        return None;
+    }
+    let bytes: Vec<u8> = (TEXT_ADDR..=0x7FFF)
+        .map(|addr| nes.peek(addr))
+        .take_while(|&byte| byte != 0)
+        .collect();
+    Some(String::from_utf8_lossy(&bytes).trim_end().to_string())
+}
+
+/// Text on the screen, one line per non-empty row of tiles
+///
+/// Reads the nametable selected in PPUCTRL and treats tiles as ASCII, which is
+/// how test ROMs print their results.
+pub fn screen_text(nes: &Nes) -> String {
+    let text = nes.read_nametable_text(nes.base_nametable_addr(), 32 * 32);
+    text.as_bytes()
+        .chunks(32)
+        .map(|chunk| String::from_utf8_lossy(chunk).trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Result printed on screen by ROMs that do not use $6000
+///
+/// These print "PASSED" or "FAILED", or a result code like "$01" (pass) or
+/// "$03" (failure code 3).
+pub fn console_result(text: &str) -> Option<TestResult> {
+    let upper = text.to_uppercase();
+    if upper.contains("PASSED") || text == "$01" {
+        return Some(TestResult::Pass);
+    }
+    if text.len() == 3 && text.starts_with("$0") {
+        return Some(TestResult::Fail(
+            u8::from_str_radix(&text[1..], 16).unwrap_or(1),
+        ));
+    }
+    if upper.contains("FAILED") || upper.contains("ERROR") {
+        return Some(TestResult::Fail(1));
+    }
+    None
+}
+
+/// How a test ROM reports its result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+    /// Through the status byte at $6000
+    Status,
+    /// Only as text on the screen
+    Console,
+}
+
+/// Result of running a test ROM
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestRomResult {
+    Pass,
+    /// The ROM reported this failure code
+    Fail(u8),
+    /// The ROM did not report a result within the frame limit
+    Timeout,
+}
+
+impl fmt::Display for TestRomResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestRomResult::Pass => write!(f, "passed"),
+            TestRomResult::Fail(code) => write!(f, "failed (${:02X})", code),
+            TestRomResult::Timeout => write!(f, "timed out"),
+        }
+    }
+}
+
+/// What a test ROM reported
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestRomReport {
+    pub result: TestRomResult,
+    /// Text from $6004, or from the screen for console tests
+    pub text: String,
+    /// Frames run until the result was known
+    pub frames: u64,
+}
+
+/// Runs a test ROM until it reports a result
+pub struct TestRomRunner {
+    rom_path: PathBuf,
+    max_frames: u64,
+    verification: Verification,
+}
+
+impl TestRomRunner {
+    pub fn new(rom_path: impl Into<PathBuf>, max_frames: u64, verification: Verification) -> Self {
+        Self {
+            rom_path: rom_path.into(),
+            max_frames,
+            verification,
+        }
+    }
+
+    /// Run the ROM on an NTSC NES for up to `max_frames` frames
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ROM cannot be loaded.
+    pub fn run(&self) -> Result<TestRomReport, String> {
+        let rom_data = load_rom(&self.rom_path, None)
+            .map_err(|e| format!("Failed to load ROM {}: {}", self.rom_path.display(), e))?;
+        let cartridge = Cartridge::new(&rom_data)
+            .map_err(|e| format!("Failed to parse ROM {}: {}", self.rom_path.display(), e))?;
+        let mut nes = Nes::new(TvSystem::Ntsc);
+        nes.insert_cartridge(cartridge);
+        nes.reset();
+
+        let mut status = TestStatus::new();
+        for frame in 1..=self.max_frames {
+            while !nes.is_ready_to_render() && !nes.cpu.is_halted() {
+                nes.run_cpu_tick();
+                while nes.sample_ready() {
+                    nes.get_sample();
+                }
+                status.poll(&nes);
+            }
+            nes.clear_ready_to_render();
+
+            let result = match self.verification {
+                Verification::Status => status.check(&mut nes),
+                Verification::Console => console_result(&screen_text(&nes)),
+            };
+            if let Some(result) = result {
+                return Ok(TestRomReport {
+                    result: match result {
+                        TestResult::Pass => TestRomResult::Pass,
+                        TestResult::Fail(code) => TestRomResult::Fail(code),
+                    },
+                    text: self.text(&nes),
+                    frames: frame,
+                });
+            }
+            if nes.cpu.is_halted() {
+                break;
+            }
+        }
+        Ok(TestRomReport {
+            result: TestRomResult::Timeout,
+            text: self.text(&nes),
+            frames: self.max_frames,
+        })
+    }
+
+    fn text(&self, nes: &Nes) -> String {
+        match self.verification {
+            Verification::Status => status_text(nes).unwrap_or_else(|| screen_text(nes)),
+            Verification::Console => screen_text(nes),
+        }
+    }
+}
+
+/// Result a manifest expects from a test ROM
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expected {
+    Pass,
+    /// A known failure: the ROM fails or times out
+    Fail,
+}
+
+impl Expected {
+    /// Whether `result` is what was expected
+    pub fn matches(self, result: TestRomResult) -> bool {
+        (self == Expected::Pass) == (result == TestRomResult::Pass)
+    }
+}
+
+/// A test ROM listed in a manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestEntry {
+    /// Path to the ROM, relative to the manifest's directory when parsed
+    pub rom: PathBuf,
+    pub verification: Verification,
+    pub max_frames: u64,
+    pub expected: Expected,
+}
+
+impl ManifestEntry {
+    pub fn runner(&self) -> TestRomRunner {
+        TestRomRunner::new(&self.rom, self.max_frames, self.verification)
+    }
+}
+
+/// Parse a manifest, reporting the first invalid line
+///
+/// Each line holds a ROM file name, how it reports its result (`status` or
+/// `console`), the frames to run at most and the expected result (`pass` or
+/// `fail`), separated by whitespace. Text after a `#` is a comment.
+pub fn parse_manifest(text: &str) -> Result<Vec<ManifestEntry>, String> {
+    let mut entries = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message: String| format!("line {}: {}", number + 1, message);
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [rom, verification, max_frames, expected] = fields[..] else {
+            return Err(error(format!(
+                "expected <rom> <status|console> <frames> <pass|fail>, got \"{}\"",
+                line
+            )));
+        };
+        let verification = match verification.to_lowercase().as_str() {
+            "status" => Verification::Status,
+            "console" => Verification::Console,
+            _ => {
+                return Err(error(format!(
+                    "unknown verification \"{}\" (expected status or console)",
+                    verification
+                )));
+            }
+        };
+        let max_frames = max_frames
+            .parse()
+            .map_err(|_| error(format!("expected a frame count, got \"{}\"", max_frames)))?;
+        let expected = match expected.to_lowercase().as_str() {
+            "pass" => Expected::Pass,
+            "fail" => Expected::Fail,
+            _ => {
+                return Err(error(format!(
+                    "unknown result \"{}\" (expected pass or fail)",
+                    expected
+                )));
+            }
+        };
+        entries.push(ManifestEntry {
+            rom: PathBuf::from(rom),
+            verification,
+            max_frames,
+            expected,
+        });
+    }
+    Ok(entries)
+}
+
+/// Read the manifests in `root` and all directories below it
+///
+/// ROM paths are joined to their manifest's directory. Entries are sorted by
+/// directory, and listed in manifest order within one.
+pub fn load_manifests(root: &Path) -> Result<Vec<ManifestEntry>, String> {
+    let mut entries = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let manifest = dir.join(MANIFEST_NAME);
+        if manifest.is_file() {
+            let text = fs::read_to_string(&manifest)
+                .map_err(|e| format!("Cannot read \"{}\": {}", manifest.display(), e))?;
+            let parsed = parse_manifest(&text)
+                .map_err(|message| format!("{}: {}", manifest.display(), message))?;
+            entries.extend(parsed.into_iter().map(|entry| ManifestEntry {
+                rom: dir.join(&entry.rom),
+                ..entry
+            }));
+        }
+
+        let mut subdirs: Vec<PathBuf> = fs::read_dir(&dir)
+            .map_err(|e| format!("Cannot read directory \"{}\": {}", dir.display(), e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_dir())
+            .collect();
+        // Popped from the back, so reverse to visit in name order
+        subdirs.sort_by(|a, b| b.cmp(a));
+        dirs.extend(subdirs);
+    }
+    if entries.is_empty() {
+        return Err(format!(
+            "No {} found in \"{}\"",
+            MANIFEST_NAME,
+            root.display()
+        ));
+    }
+    Ok(entries)
+}
+
+/// Run every entry, spreading the ROMs over all CPU cores
+///
+/// Results are in the order of `entries`.
+pub fn run_all(entries: &[ManifestEntry]) -> Vec<Result<TestRomReport, String>> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![None; entries.len()]);
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(entries.len());
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(entry) = entries.get(index) else {
+                        break;
+                    };
+                    let report = entry.runner().run();
+                    results.lock().unwrap()[index] = Some(report);
+                }
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|report| report.expect("every entry is run"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_console_result() {
+        assert_eq!(
+            console_result("Branch Basics\nPASSED"),
+            Some(TestResult::Pass)
+        );
+        assert_eq!(console_result("$01"), Some(TestResult::Pass));
+        assert_eq!(console_result("$03"), Some(TestResult::Fail(3)));
+        assert_eq!(console_result("Failed #2"), Some(TestResult::Fail(1)));
+        assert_eq!(console_result("Running..."), None);
+    }
+
+    #[test]
+    fn test_parse_manifest() {
+        let entries = parse_manifest(
+            "# rom  verification  frames  expected\n\
+             \n\
+             registers.nes  status  600  pass\n\
+             old.nes        CONSOLE 60   fail  # known failure\n",
+        )
+        .expect("valid manifest");
+        assert_eq!(
+            entries,
+            vec![
+                ManifestEntry {
+                    rom: PathBuf::from("registers.nes"),
+                    verification: Verification::Status,
+                    max_frames: 600,
+                    expected: Expected::Pass,
+                },
+                ManifestEntry {
+                    rom: PathBuf::from("old.nes"),
+                    verification: Verification::Console,
+                    max_frames: 60,
+                    expected: Expected::Fail,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_manifest_errors_name_the_line() {
+        assert_eq!(
+            parse_manifest("a.nes status 60 pass\nb.nes status sixty pass"),
+            Err("line 2: expected a frame count, got \"sixty\"".to_string())
+        );
+        assert!(parse_manifest("a.nes status 60").is_err());
+        assert!(parse_manifest("a.nes screen 60 pass").is_err());
+        assert!(parse_manifest("a.nes status 60 maybe").is_err());
+    }
+
+    #[test]
+    fn test_expected_matches() {
+        assert!(Expected::Pass.matches(TestRomResult::Pass));
+        assert!(!Expected::Pass.matches(TestRomResult::Timeout));
+        assert!(Expected::Fail.matches(TestRomResult::Fail(2)));
+        assert!(Expected::Fail.matches(TestRomResult::Timeout));
+        assert!(!Expected::Fail.matches(TestRomResult::Pass));
+    }
+
+    #[test]
+    fn test_blargg_manifests_list_existing_roms() {
+        let entries = load_manifests(Path::new("roms/blargg")).expect("manifests load");
+        for entry in &entries {
+            assert!(entry.rom.is_file(), "{} is missing", entry.rom.display());
+        }
+    }
+
+    #[test]
+    fn test_status_text_is_read_from_prg_ram() {
+        let report = TestRomRunner::new(
+            "roms/blargg/cpu_reset/registers.nes",
+            1200,
+            Verification::Status,
+        )
+        .run()
+        .expect("ROM loads");
+        assert_eq!(report.result, TestRomResult::Pass);
+        assert!(report.text.contains("Passed"), "text was {:?}", report.text);
+    }
+
+    #[test]
+    fn test_missing_rom_is_an_error() {
+        let runner = TestRomRunner::new("roms/missing.nes", 1, Verification::Status);
+        assert!(runner.run().is_err());
+    }
+}
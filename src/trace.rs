@@ -0,0 +1,459 @@
+//! Instruction trace logging
+//!
+//! A `TraceLogger` writes one line per executed instruction to a file, in the
+//! layout of nestest.log, Mesen or FCEUX. Logging can start and stop at an
+//! address or frame, and in ring-buffer mode only the last N lines are kept and
+//! written when logging ends, such as when the CPU halts or the emulator panics.
+
+use crate::disasm;
+use crate::nes::Nes;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Size of the PRG-ROM banks shown in the bank field
+const BANK_SIZE: usize = 0x4000;
+
+/// Line layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TraceFormat {
+    /// nestest.log, as produced by `Nes::trace`; the field options do not apply
+    #[default]
+    Nestest,
+    /// Mesen's trace logger: disassembly first, then registers
+    Mesen,
+    /// FCEUX's trace logger: registers first, then the instruction
+    Fceux,
+}
+
+impl TraceFormat {
+    /// Look up a format by name (`nestest`, `mesen` or `fceux`), ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "nestest" => Some(TraceFormat::Nestest),
+            "mesen" => Some(TraceFormat::Mesen),
+            "fceux" => Some(TraceFormat::Fceux),
+            _ => None,
+        }
+    }
+}
+
+/// How the P register is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlagsStyle {
+    /// `P:24`
+    #[default]
+    Hex,
+    /// `P:nvUbdIzc`, upper case for set flags
+    Letters,
+}
+
+/// Optional fields of Mesen and FCEUX lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceFields {
+    /// CPU cycle count
+    pub cycle: bool,
+    /// PPU scanline and dot
+    pub scanline: bool,
+    /// PPU frame number
+    pub frame: bool,
+    /// 16 KiB PRG-ROM bank of the instruction
+    pub bank: bool,
+    pub flags: FlagsStyle,
+}
+
+impl Default for TraceFields {
+    fn default() -> Self {
+        Self {
+            cycle: true,
+            scanline: true,
+            frame: false,
+            bank: false,
+            flags: FlagsStyle::Hex,
+        }
+    }
+}
+
+impl TraceFields {
+    /// Parse a comma-separated field list such as `cycle,frame,bank`
+    ///
+    /// Only the listed fields are enabled; the flags style is kept.
+    pub fn parse(&self, list: &str) -> Result<Self, String> {
+        let mut fields = Self {
+            cycle: false,
+            scanline: false,
+            frame: false,
+            bank: false,
+            flags: self.flags,
+        };
+        for name in list
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            match name.to_lowercase().as_str() {
+                "cycle" => fields.cycle = true,
+                "scanline" => fields.scanline = true,
+                "frame" => fields.frame = true,
+                "bank" => fields.bank = true,
+                _ => {
+                    return Err(format!(
+                        "Unknown trace field \"{}\" (use cycle, scanline, frame or bank)",
+                        name
+                    ));
+                }
+            }
+        }
+        Ok(fields)
+    }
+}
+
+/// Condition that starts or stops logging
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    /// The CPU is about to execute the instruction at this address
+    Address(u16),
+    /// The PPU has reached this frame
+    Frame(u64),
+}
+
+impl Trigger {
+    /// Parse `<ADDR>` or `frame:<N>`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text.strip_prefix("frame:") {
+            Some(frame) => frame
+                .parse()
+                .map(Trigger::Frame)
+                .map_err(|_| format!("Invalid frame number \"{}\"", frame)),
+            None => crate::debugger::parse_value(text).map(Trigger::Address),
+        }
+    }
+
+    fn is_hit(&self, pc: u16, frame: u64) -> bool {
+        match *self {
+            Trigger::Address(addr) => pc == addr,
+            Trigger::Frame(start) => frame >= start,
+        }
+    }
+}
+
+/// Trace logger settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TraceOptions {
+    pub format: TraceFormat,
+    pub fields: TraceFields,
+    /// Start logging here; logging starts immediately when `None`
+    pub start: Option<Trigger>,
+    /// Stop logging after this instruction
+    pub stop: Option<Trigger>,
+    /// Keep only the last N lines and write them when logging ends
+    pub ring: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Waiting,
+    Logging,
+    Stopped,
+}
+
+/// Writes a line for every instruction it is shown
+pub struct TraceLogger {
+    out: Box<dyn Write + Send>,
+    options: TraceOptions,
+    state: State,
+    /// Buffered lines in ring-buffer mode
+    ring: VecDeque<String>,
+}
+
+impl TraceLogger {
+    /// Log to a file, replacing it if it exists
+    pub fn create(path: &Path, options: TraceOptions) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(Box::new(BufWriter::new(file)), options))
+    }
+
+    /// Log to any writer
+    pub fn new(out: Box<dyn Write + Send>, options: TraceOptions) -> Self {
+        Self {
+            out,
+            options,
+            state: if options.start.is_some() {
+                State::Waiting
+            } else {
+                State::Logging
+            },
+            ring: VecDeque::new(),
+        }
+    }
+
+    /// Log the instruction the CPU is about to execute
+    ///
+    /// Call this before every `Nes::run_cpu_tick`.
+    pub fn log(&mut self, nes: &mut Nes) -> io::Result<()> {
+        let pc = nes.cpu.get_state().pc;
+        let frame = nes.ppu().frame_count();
+        match self.state {
+            State::Stopped => return Ok(()),
+            State::Waiting => {
+                if !self
+                    .options
+                    .start
+                    .is_some_and(|start| start.is_hit(pc, frame))
+                {
+                    return Ok(());
+                }
+                self.state = State::Logging;
+            }
+            State::Logging => {}
+        }
+
+        let line = format_line(nes, &self.options);
+        match self.options.ring {
+            Some(capacity) => {
+                if self.ring.len() == capacity {
+                    self.ring.pop_front();
+                }
+                if capacity > 0 {
+                    self.ring.push_back(line);
+                }
+            }
+            None => writeln!(self.out, "{}", line)?,
+        }
+
+        if self.options.stop.is_some_and(|stop| stop.is_hit(pc, frame)) {
+            self.state = State::Stopped;
+            self.finish()?;
+        }
+        Ok(())
+    }
+
+    /// Write buffered lines and flush
+    ///
+    /// In ring-buffer mode this writes the lines kept so far; call it when the
+    /// CPU halts. It also runs when the logger is dropped, including while a
+    /// panic unwinds.
+    pub fn finish(&mut self) -> io::Result<()> {
+        for line in self.ring.drain(..) {
+            writeln!(self.out, "{}", line)?;
+        }
+        self.out.flush()
+    }
+}
+
+impl Drop for TraceLogger {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            eprintln!("Warning: Failed to write trace log: {}", e);
+        }
+    }
+}
+
+/// Format the instruction at PC as a trace line
+fn format_line(nes: &mut Nes, options: &TraceOptions) -> String {
+    if options.format == TraceFormat::Nestest {
+        return nes.trace(true);
+    }
+
+    let fields = &options.fields;
+    let state = *nes.cpu.get_state();
+    let instruction = disasm::decode(state.pc, |addr| nes.peek(addr));
+    let asm = instruction.format(&nes.labels());
+    let flags = match fields.flags {
+        FlagsStyle::Hex => format!("{:02X}", state.p),
+        FlagsStyle::Letters => flag_letters(state.p),
+    };
+    let registers = format!(
+        "A:{:02X} X:{:02X} Y:{:02X} S:{:02X} P:{}",
+        state.a, state.x, state.y, state.sp, flags
+    );
+    let bank = nes
+        .bus
+        .prg_rom_offset(state.pc)
+        .map(|offset| offset / BANK_SIZE);
+    let cycles = nes.cpu.get_total_cycles();
+    let (scanline, dot, frame) = {
+        let ppu = nes.ppu();
+        (ppu.scanline(), ppu.pixel(), ppu.frame_count())
+    };
+
+    let mut line = String::new();
+    match options.format {
+        TraceFormat::Mesen => {
+            line.push_str(&format!("{:04X}  {:<28} {}", state.pc, asm, registers));
+            if fields.scanline {
+                line.push_str(&format!(" SL:{:<3} CYC:{:<3}", scanline, dot));
+            }
+            if fields.cycle {
+                line.push_str(&format!(" CPU Cycle:{}", cycles));
+            }
+            if fields.frame {
+                line.push_str(&format!(" FC:{}", frame));
+            }
+            if fields.bank {
+                match bank {
+                    Some(bank) => line.push_str(&format!(" BANK:{:02X}", bank)),
+                    None => line.push_str(" BANK:--"),
+                }
+            }
+        }
+        TraceFormat::Fceux => {
+            if fields.frame {
+                line.push_str(&format!("f{:<6} ", frame));
+            }
+            if fields.cycle {
+                line.push_str(&format!("c{:<10} ", cycles));
+            }
+            line.push_str(&registers);
+            if fields.scanline {
+                line.push_str(&format!(" SL:{:<3} DOT:{:<3}", scanline, dot));
+            }
+            line.push_str("  $");
+            if let Some(bank) = bank.filter(|_| fields.bank) {
+                line.push_str(&format!("{:02X}:", bank));
+            }
+            line.push_str(&format!("{:04X}:{}  {}", state.pc, instruction.hex(), asm));
+        }
+        TraceFormat::Nestest => unreachable!(),
+    }
+    line.trim_end().to_string()
+}
+
+/// Flags as letters, upper case when set (`nvUbdIzc`)
+fn flag_letters(p: u8) -> String {
+    "NVUBDIZC"
+        .chars()
+        .enumerate()
+        .map(|(bit, flag)| {
+            if p & (0x80 >> bit) != 0 {
+                flag
+            } else {
+                flag.to_ascii_lowercase()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nes::TvSystem;
+    use std::fs;
+
+    /// LDA #$42; STA $10; NOP; NOP; KIL at $0200
+    fn halting_program() -> Nes {
+        let mut nes = Nes::new(TvSystem::Ntsc);
+        for (offset, byte) in [0xA9, 0x42, 0x85, 0x10, 0xEA, 0xEA, 0x02]
+            .iter()
+            .enumerate()
+        {
+            nes.poke(0x0200 + offset as u16, *byte);
+        }
+        let state = nes.cpu.get_state();
+        state.pc = 0x0200;
+        state.sp = 0xFD;
+        state.p = 0x24;
+        nes
+    }
+
+    /// Run until the CPU halts, logging to a temporary file, and return the lines
+    fn run_logged(name: &str, options: TraceOptions) -> Vec<String> {
+        let path = std::env::temp_dir().join(format!("neser_trace_{}.log", name));
+        let mut nes = halting_program();
+        {
+            let mut logger = TraceLogger::create(&path, options).unwrap();
+            while !nes.cpu.is_halted() {
+                logger.log(&mut nes).unwrap();
+                nes.run_cpu_tick();
+            }
+            logger.finish().unwrap();
+        }
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).ok();
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_formats() {
+        let nestest = run_logged("nestest", TraceOptions::default());
+        assert_eq!(nestest.len(), 5);
+        assert!(nestest[0].starts_with("0200  A9 42     LDA #$42"));
+
+        let fields = TraceFields {
+            frame: true,
+            flags: FlagsStyle::Letters,
+            ..TraceFields::default()
+        };
+        let mesen = run_logged(
+            "mesen",
+            TraceOptions {
+                format: TraceFormat::Mesen,
+                fields,
+                ..TraceOptions::default()
+            },
+        );
+        assert!(mesen[1].starts_with("0202  STA $10"));
+        assert!(mesen[1].contains("A:42 X:00 Y:00 S:FD P:nvUbdIzc SL:"));
+        assert!(mesen[1].contains(" CPU Cycle:"));
+        assert!(mesen[1].ends_with(" FC:0"));
+
+        let fceux = run_logged(
+            "fceux",
+            TraceOptions {
+                format: TraceFormat::Fceux,
+                fields: TraceFields {
+                    cycle: false,
+                    scanline: false,
+                    ..TraceFields::default()
+                },
+                ..TraceOptions::default()
+            },
+        );
+        assert_eq!(
+            fceux[0],
+            "A:00 X:00 Y:00 S:FD P:24  $0200:A9 42     LDA #$42"
+        );
+    }
+
+    #[test]
+    fn test_start_and_stop_triggers() {
+        let lines = run_logged(
+            "triggers",
+            TraceOptions {
+                start: Some(Trigger::Address(0x0202)),
+                stop: Some(Trigger::Address(0x0204)),
+                ..TraceOptions::default()
+            },
+        );
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("0202"));
+        assert!(lines[1].starts_with("0204"));
+    }
+
+    #[test]
+    fn test_ring_buffer_keeps_last_lines() {
+        let lines = run_logged(
+            "ring",
+            TraceOptions {
+                ring: Some(2),
+                ..TraceOptions::default()
+            },
+        );
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("0205"));
+        assert!(lines[1].starts_with("0206  02"));
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(Trigger::parse("$C000"), Ok(Trigger::Address(0xC000)));
+        assert_eq!(Trigger::parse("frame:60"), Ok(Trigger::Frame(60)));
+        assert!(Trigger::parse("frame:x").is_err());
+        assert_eq!(TraceFormat::from_name("FCEUX"), Some(TraceFormat::Fceux));
+
+        let fields = TraceFields::default().parse("frame,bank").unwrap();
+        assert!(fields.frame && fields.bank && !fields.cycle && !fields.scanline);
+        assert!(TraceFields::default().parse("frame,speed").is_err());
+    }
+}
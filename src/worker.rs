@@ -0,0 +1,813 @@
+//! Emulation thread
+//!
+//! The worker owns the running `Nes` while the window is open and paces it to
+//! the frame rate, so emulation keeps its timing whatever happens on the SDL
+//! thread (e.g. while the window is being dragged). It only talks to the
+//! frontend through lock-free queues: input commands come in, finished frames
+//! and audio samples go out.
+//!
+//! For making tool-assisted movies, the worker can advance paused emulation a
+//! frame at a time, let the input for the next frame be edited while paused
+//! and draw the frame and lag counters over the frames it hands out.
+//!
+//! In netplay, the keys of controller 1 play whichever player this side is,
+//! and a frame is skipped whenever the other side's input is too far behind.
+
+use crate::input::Button;
+use crate::movie::MovieSession;
+use crate::nes::Nes;
+use crate::netplay::NetplaySession;
+use crate::osd;
+use crate::rewind::RewindBuffer;
+use crate::runahead::RunAhead;
+use crate::trace::TraceLogger;
+use crossbeam_queue::ArrayQueue;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Bytes in one RGB24 frame of 256x240 pixels
+pub const FRAME_SIZE: usize = 256 * 240 * 3;
+
+/// Input from the frontend to the emulator
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Command {
+    /// Press or release a controller button
+    Button {
+        controller: u8,
+        button: Button,
+        pressed: bool,
+    },
+    /// Aim the Zapper at a screen pixel, or `None` for off-screen
+    ZapperAim(Option<(u32, u32)>),
+    /// Pull or release the Zapper trigger
+    ZapperTrigger(bool),
+    Reset,
+    TogglePause,
+    /// Pause after emulating one more frame
+    FrameAdvance,
+    /// Start or stop stepping backwards
+    Rewind(bool),
+    /// Switch the movie between read-only and read-write
+    ToggleReadOnly,
+    /// Show or hide the frame counter, lag counter and input
+    ToggleInfo,
+    /// Switch all cheats on or off
+    ToggleCheats,
+    /// Stop the worker
+    Quit,
+}
+
+impl Command {
+    /// Whether the command ends something an earlier one started, so that
+    /// losing it would leave a button held or the worker running
+    fn must_arrive(&self) -> bool {
+        matches!(
+            self,
+            Command::Button { pressed: false, .. }
+                | Command::ZapperTrigger(false)
+                | Command::Rewind(false)
+                | Command::Quit
+        )
+    }
+}
+
+/// Queues between the worker and the frontend
+pub struct Channels {
+    commands: ArrayQueue<Command>,
+    /// Finished frames, oldest first
+    frames: ArrayQueue<Vec<u8>>,
+    /// Frames the frontend is done with, for the worker to fill again
+    free_frames: ArrayQueue<Vec<u8>>,
+    /// Audio samples for the audio callback, if there is audio output
+    samples: Option<Arc<ArrayQueue<f32>>>,
+    /// The worker has stopped and takes no more commands
+    closed: AtomicBool,
+}
+
+impl Channels {
+    /// Commands that can wait in the queue before new ones are held back
+    const COMMAND_CAPACITY: usize = 256;
+    /// Frames that can wait for the frontend before the oldest are dropped
+    const FRAME_CAPACITY: usize = 2;
+
+    /// Create the queues, sending audio samples to `samples` if given
+    pub fn new(samples: Option<Arc<ArrayQueue<f32>>>) -> Self {
+        Self {
+            commands: ArrayQueue::new(Self::COMMAND_CAPACITY),
+            frames: ArrayQueue::new(Self::FRAME_CAPACITY),
+            free_frames: ArrayQueue::new(Self::FRAME_CAPACITY + 1),
+            samples,
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// Send a command to the worker
+    ///
+    /// If the worker has fallen far behind, a new press or toggle is dropped.
+    /// Releases and stops wait for room instead, unless the worker has stopped.
+    pub fn send(&self, command: Command) {
+        let mut command = command;
+        while let Err(rejected) = self.commands.push(command) {
+            if !rejected.must_arrive() || self.closed.load(Ordering::Acquire) {
+                return;
+            }
+            thread::yield_now();
+            command = rejected;
+        }
+    }
+
+    /// Take the newest finished frame, recycling any older ones
+    pub fn latest_frame(&self) -> Option<Vec<u8>> {
+        let mut latest = None;
+        while let Some(frame) = self.frames.pop() {
+            if let Some(older) = latest.replace(frame) {
+                self.recycle(older);
+            }
+        }
+        latest
+    }
+
+    /// Give a shown frame back to the worker
+    pub fn recycle(&self, frame: Vec<u8>) {
+        let _ = self.free_frames.push(frame);
+    }
+}
+
+/// Runs a `Nes` until told to quit
+pub struct Worker<'a> {
+    nes: &'a mut Nes,
+    trace: Option<&'a mut TraceLogger>,
+    channels: &'a Channels,
+    /// Time between frames, or `None` to run as fast as possible
+    frame_time: Option<Duration>,
+    paused: bool,
+    /// Emulate one frame even though paused
+    advance: bool,
+    /// While paused, button presses toggle the buttons for the next frame
+    edit_input: bool,
+    /// Draw the frame counter, lag counter and input over frames
+    show_info: bool,
+    /// Movie being recorded or played
+    movie: Option<&'a mut MovieSession>,
+    /// Recent states to step back through
+    rewind: Option<RewindBuffer>,
+    rewinding: bool,
+    /// Shows frames early to hide input latency
+    run_ahead: Option<RunAhead>,
+    /// Game shared with another emulator over the network
+    netplay: Option<NetplaySession>,
+}
+
+impl<'a> Worker<'a> {
+    /// How long to wait for commands while paused or halted
+    const IDLE_SLEEP: Duration = Duration::from_millis(16);
+
+    /// Create a worker for `nes`, showing each instruction to `trace` if given
+    pub fn new(
+        nes: &'a mut Nes,
+        trace: Option<&'a mut TraceLogger>,
+        channels: &'a Channels,
+        frame_time: Option<Duration>,
+    ) -> Self {
+        Self {
+            nes,
+            trace,
+            channels,
+            frame_time,
+            paused: false,
+            advance: false,
+            edit_input: false,
+            show_info: false,
+            movie: None,
+            rewind: None,
+            rewinding: false,
+            run_ahead: None,
+            netplay: None,
+        }
+    }
+
+    /// Start paused, showing the frame counter, lag counter and input, with
+    /// button presses toggling the buttons while paused
+    pub fn with_tas(mut self, tas: bool) -> Self {
+        self.paused |= tas;
+        self.edit_input = tas;
+        self.show_info |= tas;
+        self
+    }
+
+    /// Record the input into a movie, or play one back
+    pub fn with_movie(mut self, movie: Option<&'a mut MovieSession>) -> Self {
+        self.movie = movie;
+        self
+    }
+
+    /// Keep recent states so that play can be rewound
+    pub fn with_rewind(mut self, rewind: Option<RewindBuffer>) -> Self {
+        self.rewind = rewind;
+        self
+    }
+
+    /// Show each frame some frames early
+    pub fn with_run_ahead(mut self, run_ahead: Option<RunAhead>) -> Self {
+        self.run_ahead = run_ahead;
+        self
+    }
+
+    /// Play with another emulator over the network
+    pub fn with_netplay(mut self, netplay: Option<NetplaySession>) -> Self {
+        self.netplay = netplay;
+        self
+    }
+
+    /// Emulate frame by frame until a `Quit` command arrives
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the trace log cannot be written or the netplay
+    /// connection fails.
+    pub fn run(mut self) -> Result<(), String> {
+        let mut next_frame = Instant::now();
+        if self.paused {
+            self.publish_frame();
+        }
+        loop {
+            let mut changed = false;
+            while let Some(command) = self.channels.commands.pop() {
+                if command == Command::Quit {
+                    return Ok(());
+                }
+                self.apply(command);
+                changed = true;
+            }
+            // Show edited input and switched modes while paused
+            if changed && self.paused && !self.advance {
+                self.publish_frame();
+            }
+
+            // A halted CPU can still be rewound to before it crashed
+            if (self.paused && !self.advance) || (self.nes.cpu.is_halted() && !self.rewinding) {
+                thread::sleep(Self::IDLE_SLEEP);
+                next_frame = Instant::now();
+                continue;
+            }
+
+            if self.rewinding {
+                if self.rewind_frame()? {
+                    self.publish_frame();
+                }
+            } else {
+                self.advance = false;
+                self.play_frame()?;
+            }
+
+            if let Some(frame_time) = self.frame_time {
+                next_frame += frame_time;
+                let now = Instant::now();
+                if next_frame > now {
+                    thread::sleep(next_frame - now);
+                } else {
+                    // Running late: start over instead of rushing to catch up
+                    next_frame = now;
+                }
+            }
+        }
+    }
+
+    fn apply(&mut self, command: Command) {
+        match command {
+            Command::Button {
+                controller,
+                button,
+                pressed,
+            } if self.netplay.is_some() => {
+                if let Some(netplay) = &mut self.netplay
+                    && controller == 1
+                {
+                    netplay.set_button(button, pressed);
+                }
+            }
+            Command::Button {
+                controller,
+                button,
+                pressed,
+            } if self.paused && self.edit_input => {
+                if pressed {
+                    let held = self.nes.buttons(controller) & (1 << button as u8) != 0;
+                    self.nes.set_button(controller, button, !held);
+                }
+            }
+            Command::Button {
+                controller,
+                button,
+                pressed,
+            } => self.nes.set_button(controller, button, pressed),
+            Command::ZapperAim(aim) => {
+                self.nes.set_zapper_aim(aim);
+                if let Some(second) = self.second_instance() {
+                    second.set_zapper_aim(aim);
+                }
+            }
+            Command::ZapperTrigger(pulled) => {
+                self.nes.set_zapper_trigger(pulled);
+                if let Some(second) = self.second_instance() {
+                    second.set_zapper_trigger(pulled);
+                }
+            }
+            // The other side would not reset with us
+            Command::Reset if self.netplay.is_some() => {
+                eprintln!("Warning: Reset is not available in netplay");
+            }
+            Command::Reset => {
+                println!("Resetting NES...");
+                self.nes.reset();
+                if let Some(movie) = &mut self.movie {
+                    movie.note_reset();
+                }
+            }
+            Command::TogglePause => self.paused = !self.paused,
+            Command::FrameAdvance => {
+                self.paused = true;
+                self.advance = true;
+            }
+            Command::Rewind(held) => self.rewinding = held && self.rewind.is_some(),
+            Command::ToggleReadOnly => {
+                if let Some(movie) = &mut self.movie {
+                    movie.set_read_only(!movie.is_read_only());
+                    let access = if movie.is_read_only() {
+                        "read-only"
+                    } else {
+                        "read-write"
+                    };
+                    println!("Movie is {}", access);
+                }
+            }
+            Command::ToggleInfo => self.show_info = !self.show_info,
+            Command::ToggleCheats => {
+                let active = !self.nes.cheats().is_active();
+                self.nes.cheats_mut().set_active(active);
+                if let Some(second) = self.second_instance() {
+                    second.cheats_mut().set_active(active);
+                }
+                println!("Cheats {}", if active { "on" } else { "off" });
+            }
+            Command::Quit => {}
+        }
+    }
+
+    /// The console run-ahead runs on, if it has its own
+    ///
+    /// The Zapper is not part of the state run-ahead copies, so it is aimed
+    /// on both consoles.
+    fn second_instance(&mut self) -> Option<&mut Nes> {
+        self.run_ahead
+            .as_mut()
+            .and_then(RunAhead::second_instance_mut)
+    }
+
+    /// Emulate the next frame and show it, or the one run ahead to
+    fn play_frame(&mut self) -> Result<(), String> {
+        if let Some(netplay) = &mut self.netplay
+            && !netplay.start_frame(self.nes)?
+        {
+            // Wait for the other side's input
+            return Ok(());
+        }
+        if let Some(rewind) = &mut self.rewind {
+            rewind.capture(self.nes);
+        }
+        self.run_frame()?;
+        if let Some(netplay) = &mut self.netplay
+            && let Err(desync) = netplay.end_frame(self.nes)
+        {
+            eprintln!("Warning: {}", desync);
+        }
+        if let Some(run_ahead) = &mut self.run_ahead {
+            run_ahead.run(self.nes)?;
+        }
+        self.publish_frame();
+        Ok(())
+    }
+
+    /// Emulate until the PPU finishes a frame or the CPU halts
+    fn run_frame(&mut self) -> Result<(), String> {
+        let nes = &mut *self.nes;
+        if let Some(movie) = &mut self.movie {
+            movie.start_frame(nes);
+        }
+        while !nes.is_ready_to_render() && !nes.cpu.is_halted() {
+            if let Some(logger) = &mut self.trace {
+                logger.log(nes).map_err(trace_error)?;
+            }
+            nes.run_cpu_tick();
+
+            // Hand audio samples to the audio callback
+            if let Some(samples) = &self.channels.samples {
+                while nes.sample_ready() {
+                    if let Some(sample) = nes.get_sample() {
+                        // Drop samples if the callback falls behind
+                        let _ = samples.push(sample);
+                    }
+                }
+            }
+        }
+        nes.clear_ready_to_render();
+        if let Some(movie) = &mut self.movie {
+            let was_finished = movie.is_finished();
+            if let Err(desync) = movie.end_frame(nes) {
+                eprintln!("Warning: {}", desync);
+            }
+            if !was_finished && movie.is_finished() {
+                println!("Movie finished after {} frames", movie.frame());
+            }
+        }
+        if nes.cpu.is_halted()
+            && let Some(logger) = &mut self.trace
+        {
+            logger.finish().map_err(trace_error)?;
+        }
+        Ok(())
+    }
+
+    /// Step back to the last captured state and play its frame again, with
+    /// the audio reversed
+    ///
+    /// A movie goes back too: a read-only one plays the frame from the movie,
+    /// a read-write one records it over again.
+    ///
+    /// Returns `false` once there is nothing left to rewind.
+    fn rewind_frame(&mut self) -> Result<bool, String> {
+        let nes = &mut *self.nes;
+        let Some(rewind) = &mut self.rewind else {
+            return Ok(false);
+        };
+        if !rewind.rewind(nes)? {
+            return Ok(false);
+        }
+        if let Some(movie) = &mut self.movie {
+            movie.seek(nes.frame_count() as usize);
+            movie.start_frame(nes);
+        }
+
+        let mut samples = Vec::new();
+        while !nes.is_ready_to_render() && !nes.cpu.is_halted() {
+            nes.run_cpu_tick();
+            while nes.sample_ready() {
+                samples.extend(nes.get_sample());
+            }
+        }
+        nes.clear_ready_to_render();
+        if let Some(movie) = &mut self.movie
+            && let Err(desync) = movie.end_frame(nes)
+        {
+            eprintln!("Warning: {}", desync);
+        }
+        if let Some(queue) = &self.channels.samples {
+            for sample in samples.into_iter().rev() {
+                let _ = queue.push(sample);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Copy the screen into a frame for the frontend, dropping the oldest
+    /// waiting frame if it has not kept up
+    ///
+    /// The frame counter, lag counter and input are drawn on the copy.
+    fn publish_frame(&mut self) {
+        let mut frame = self
+            .channels
+            .free_frames
+            .pop()
+            .unwrap_or_else(|| vec![0; FRAME_SIZE]);
+        self.nes.get_screen_buffer().copy_buffer(&mut frame);
+        if self.show_info {
+            osd::draw_info(&mut frame, self.nes, self.movie.as_deref());
+        }
+        if let Some(dropped) = self.channels.frames.force_push(frame) {
+            self.channels.recycle(dropped);
+        }
+    }
+}
+
+impl Drop for Worker<'_> {
+    fn drop(&mut self) {
+        // Nothing waits for room in the command queue any more
+        self.channels.closed.store(true, Ordering::Release);
+    }
+}
+
+fn trace_error(e: std::io::Error) -> String {
+    format!("Failed to write trace log: {}", e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quit_stops_worker() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let channels = Channels::new(None);
+        channels.send(Command::Quit);
+        assert_eq!(Worker::new(&mut nes, None, &channels, None).run(), Ok(()));
+        assert_eq!(channels.latest_frame(), None);
+    }
+
+    #[test]
+    fn test_worker_publishes_frames_from_its_thread() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let channels = Channels::new(None);
+        let frame = thread::scope(|scope| {
+            let worker = scope.spawn(|| Worker::new(&mut nes, None, &channels, None).run());
+            let frame = loop {
+                if let Some(frame) = channels.latest_frame() {
+                    break frame;
+                }
+                thread::yield_now();
+            };
+            channels.send(Command::Quit);
+            assert_eq!(worker.join().unwrap(), Ok(()));
+            frame
+        });
+        assert_eq!(frame.len(), FRAME_SIZE);
+        assert!(nes.cpu.get_total_cycles() > 0);
+    }
+
+    #[test]
+    fn test_full_command_queue_keeps_releases() {
+        let channels = Channels::new(None);
+        let press = Command::Button {
+            controller: 1,
+            button: Button::A,
+            pressed: true,
+        };
+        let release = Command::Button {
+            controller: 1,
+            button: Button::A,
+            pressed: false,
+        };
+        channels.send(release);
+        for _ in 1..Channels::COMMAND_CAPACITY {
+            channels.send(press);
+        }
+        // A toggle sent to a full queue is dropped rather than the release
+        channels.send(Command::TogglePause);
+        assert_eq!(channels.commands.len(), Channels::COMMAND_CAPACITY);
+        assert_eq!(channels.commands.pop(), Some(release));
+
+        // A stop sent to a full queue waits until the worker makes room
+        channels.send(press);
+        thread::scope(|scope| {
+            scope.spawn(|| channels.send(Command::Rewind(false)));
+            let started = Instant::now();
+            let mut received = Vec::new();
+            while received.last() != Some(&Command::Rewind(false)) {
+                assert!(started.elapsed() < Duration::from_secs(10), "stop dropped");
+                match channels.commands.pop() {
+                    Some(command) => received.push(command),
+                    None => thread::yield_now(),
+                }
+            }
+            assert_eq!(received.len(), Channels::COMMAND_CAPACITY + 1);
+        });
+    }
+
+    #[test]
+    fn test_release_is_not_held_for_a_stopped_worker() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let channels = Channels::new(None);
+        for _ in 0..Channels::COMMAND_CAPACITY {
+            channels.send(Command::ToggleInfo);
+        }
+        drop(Worker::new(&mut nes, None, &channels, None));
+        channels.send(Command::Quit);
+        assert_eq!(channels.commands.len(), Channels::COMMAND_CAPACITY);
+    }
+
+    #[test]
+    fn test_latest_frame_recycles_older_frames() {
+        let channels = Channels::new(None);
+        channels.frames.push(vec![1]).unwrap();
+        channels.frames.push(vec![2]).unwrap();
+        assert_eq!(channels.latest_frame(), Some(vec![2]));
+        assert_eq!(channels.free_frames.pop(), Some(vec![1]));
+        assert_eq!(channels.latest_frame(), None);
+    }
+
+    #[test]
+    fn test_rewind_plays_frames_backwards() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let samples = Arc::new(ArrayQueue::new(100_000));
+        let channels = Channels::new(Some(samples.clone()));
+        let mut worker = Worker::new(&mut nes, None, &channels, None)
+            .with_rewind(Some(RewindBuffer::new(usize::MAX, 1)));
+        let mut frames = Vec::new();
+        for _ in 0..3 {
+            worker.rewind.as_mut().unwrap().capture(worker.nes);
+            worker.run_frame().unwrap();
+            frames.push(worker.nes.get_screen_buffer().pixels().to_vec());
+        }
+        let forward: Vec<f32> = std::iter::from_fn(|| samples.pop()).collect();
+
+        worker.apply(Command::Rewind(true));
+        assert!(worker.rewinding);
+        let mut backward = Vec::new();
+        for frame in frames.iter().rev() {
+            assert_eq!(worker.rewind_frame(), Ok(true));
+            assert!(worker.nes.get_screen_buffer().pixels() == &frame[..]);
+            backward.extend(std::iter::from_fn(|| samples.pop()));
+        }
+        assert_eq!(worker.rewind_frame(), Ok(false));
+        assert_eq!(backward.len(), forward.len());
+        assert!(backward.iter().rev().eq(forward.iter()));
+    }
+
+    #[test]
+    fn test_rewind_needs_a_buffer() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let channels = Channels::new(None);
+        let mut worker = Worker::new(&mut nes, None, &channels, None);
+        worker.apply(Command::Rewind(true));
+        assert!(!worker.rewinding);
+    }
+
+    #[test]
+    fn test_run_ahead_of_zero_frames_changes_nothing() {
+        let play = |run_ahead: Option<RunAhead>| {
+            let mut nes = Nes::power_on("roms/nestest.nes");
+            let samples = Arc::new(ArrayQueue::new(100_000));
+            let channels = Channels::new(Some(samples.clone()));
+            let mut worker = Worker::new(&mut nes, None, &channels, None).with_run_ahead(run_ahead);
+            let mut frames = Vec::new();
+            for _ in 0..5 {
+                worker.play_frame().unwrap();
+                frames.push(channels.latest_frame().unwrap());
+            }
+            let samples: Vec<f32> = std::iter::from_fn(|| samples.pop()).collect();
+            (frames, samples)
+        };
+        let plain = play(None);
+        assert!(play(Some(RunAhead::new(0))) == plain);
+        assert!(
+            play(Some(RunAhead::with_second_instance(
+                0,
+                Nes::power_on("roms/nestest.nes")
+            ))) == plain
+        );
+    }
+
+    /// Wait for the worker on another thread to hand out a frame
+    fn next_frame(channels: &Channels) -> Vec<u8> {
+        loop {
+            if let Some(frame) = channels.latest_frame() {
+                return frame;
+            }
+            thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn test_frame_advance_runs_one_frame_at_a_time() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let channels = Channels::new(None);
+        thread::scope(|scope| {
+            let worker = scope.spawn(|| {
+                Worker::new(&mut nes, None, &channels, None)
+                    .with_tas(true)
+                    .run()
+            });
+            // The paused power-on screen, then one frame per advance
+            next_frame(&channels);
+            for _ in 0..2 {
+                channels.send(Command::FrameAdvance);
+                next_frame(&channels);
+            }
+            channels.send(Command::Quit);
+            assert_eq!(worker.join().unwrap(), Ok(()));
+        });
+        assert_eq!(nes.frame_count(), 2);
+    }
+
+    #[test]
+    fn test_buttons_are_toggled_while_paused_for_tas() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let channels = Channels::new(None);
+        let mut worker = Worker::new(&mut nes, None, &channels, None).with_tas(true);
+        let press = |pressed| Command::Button {
+            controller: 1,
+            button: Button::Right,
+            pressed,
+        };
+        worker.apply(press(true));
+        worker.apply(press(false));
+        assert_eq!(worker.nes.buttons(1), 0x80);
+        worker.apply(press(true));
+        assert_eq!(worker.nes.buttons(1), 0);
+
+        // Running, the keys are held as usual
+        worker.apply(Command::TogglePause);
+        worker.apply(press(true));
+        assert_eq!(worker.nes.buttons(1), 0x80);
+        worker.apply(press(false));
+        assert_eq!(worker.nes.buttons(1), 0);
+    }
+
+    #[test]
+    fn test_info_is_drawn_on_handed_out_frames() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let channels = Channels::new(None);
+        let mut worker = Worker::new(&mut nes, None, &channels, None);
+        worker.play_frame().unwrap();
+        let plain = channels.latest_frame().unwrap();
+        worker.apply(Command::ToggleInfo);
+        worker.publish_frame();
+        assert!(channels.latest_frame().unwrap() != plain);
+    }
+
+    #[test]
+    fn test_rewinding_a_movie_plays_or_records_over_it() {
+        for read_only in [false, true] {
+            let mut nes = Nes::power_on("roms/nestest.nes");
+            let mut session = MovieSession::record(&nes, None);
+            let channels = Channels::new(None);
+            let mut worker = Worker::new(&mut nes, None, &channels, None)
+                .with_movie(Some(&mut session))
+                .with_rewind(Some(RewindBuffer::new(usize::MAX, 1)));
+            for _ in 0..5 {
+                worker.play_frame().unwrap();
+            }
+            if read_only {
+                worker.apply(Command::ToggleReadOnly);
+            }
+            worker.apply(Command::Rewind(true));
+            for _ in 0..2 {
+                assert_eq!(worker.rewind_frame(), Ok(true));
+            }
+            assert_eq!(worker.nes.frame_count(), 4);
+
+            let movie = worker.movie.as_deref().unwrap();
+            assert_eq!(movie.frame(), 4);
+            // Read-write cut the movie off after the frame played again
+            let expected = if read_only { 5 } else { 4 };
+            assert_eq!(movie.movie().frames.len(), expected);
+        }
+    }
+
+    #[test]
+    fn test_netplay_keys_play_the_local_player() {
+        let host_socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = host_socket.local_addr().unwrap();
+        let host = thread::spawn(move || {
+            NetplaySession::host(host_socket, &Nes::power_on("roms/nestest.nes"), 0)
+        });
+        let join_socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let joined = NetplaySession::join(join_socket, address, &nes, 0).unwrap();
+        let _host = host.join().unwrap().unwrap();
+
+        let channels = Channels::new(None);
+        let mut worker = Worker::new(&mut nes, None, &channels, None).with_netplay(Some(joined));
+        worker.apply(Command::Button {
+            controller: 1,
+            button: Button::A,
+            pressed: true,
+        });
+        worker.play_frame().unwrap();
+        // Player 2 holds A, and player 1 is guessed to hold nothing
+        assert_eq!(worker.nes.buttons(2), 0x01);
+        assert_eq!(worker.nes.buttons(1), 0x00);
+
+        let state = worker.nes.save_state();
+        worker.apply(Command::Reset);
+        assert!(worker.nes.save_state() == state);
+    }
+
+    #[test]
+    fn test_toggle_cheats() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        nes.cheats_mut().add("Lives", "SXIOPO").unwrap();
+        let mut second = Nes::power_on("roms/nestest.nes");
+        *second.cheats_mut() = nes.cheats().clone();
+        let channels = Channels::new(None);
+        let mut worker = Worker::new(&mut nes, None, &channels, None)
+            .with_run_ahead(Some(RunAhead::with_second_instance(1, second)));
+        worker.apply(Command::ToggleCheats);
+        assert!(!worker.nes.cheats().is_active());
+        assert!(!worker.second_instance().unwrap().cheats().is_active());
+        worker.apply(Command::ToggleCheats);
+        assert!(worker.nes.cheats().is_active());
+    }
+
+    #[test]
+    fn test_toggle_pause() {
+        let mut nes = Nes::power_on("roms/nestest.nes");
+        let channels = Channels::new(None);
+        let mut worker = Worker::new(&mut nes, None, &channels, None);
+        worker.apply(Command::TogglePause);
+        assert!(worker.paused);
+        worker.apply(Command::TogglePause);
+        assert!(!worker.paused);
+    }
+}